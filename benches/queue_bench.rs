@@ -0,0 +1,60 @@
+//! Throughput de la cola: agregar canciones clona el `TrackSource` (y sus textos)
+//! en cada paso, así que este benchmark mide el costo de esos clones.
+//!
+//! `cargo bench --bench queue_bench`
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use open_music::audio::queue::MusicQueue;
+use open_music::sources::{SourceType, TrackSource};
+use serenity::model::id::UserId;
+use std::time::Duration;
+
+const TRACKS: usize = 500;
+
+fn tracks() -> Vec<TrackSource> {
+    (0..TRACKS)
+        .map(|i| {
+            TrackSource::new(
+                format!("Artista {} - Canción con un título bastante largo (Official Video) #{}", i % 37, i),
+                format!("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PL{:08}", i),
+                SourceType::YouTube,
+                UserId::new(1 + (i % 5) as u64),
+            )
+            .with_artist(format!("Artista {}", i % 37))
+            .with_thumbnail(format!("https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg?{}", i))
+            .with_duration(Duration::from_secs(180 + i as u64))
+        })
+        .collect()
+}
+
+fn queue_add(c: &mut Criterion) {
+    let tracks = tracks();
+    let mut group = c.benchmark_group("queue");
+    group.throughput(Throughput::Elements(TRACKS as u64));
+
+    // Como `/playlist`: cada canción se clona al encolarla
+    group.bench_function("add_track", |b| {
+        b.iter_batched(
+            || MusicQueue::new(TRACKS),
+            |mut queue| {
+                for track in &tracks {
+                    queue.add_track(track.clone()).unwrap();
+                }
+                black_box(queue)
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Como `/queue`: se copia la cola entera para mostrarla
+    let mut queue = MusicQueue::new(TRACKS);
+    for track in &tracks {
+        queue.add_track(track.clone()).unwrap();
+    }
+    group.bench_function("get_tracks", |b| b.iter(|| black_box(queue.get_tracks())));
+
+    group.finish();
+}
+
+criterion_group!(benches, queue_add);
+criterion_main!(benches);
@@ -1,7 +1,11 @@
+use anyhow::Result;
 use dashmap::DashMap;
 use serenity::model::id::GuildId;
 use tracing::info;
 
+use super::loudness::ONE_PASS_FILTER;
+use crate::storage::MAX_EQ_GAIN_DB;
+
 /// Presets de ecualizador disponibles
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EqualizerPreset {
@@ -13,35 +17,186 @@ pub enum EqualizerPreset {
     Classical,
     Electronic,
     Vocal,
+    /// Ganancias por banda definidas por la guild (ver [`CUSTOM_EQ_FREQUENCIES`])
+    Custom,
 }
 
-/// Sistema de ecualizador con presets, **por servidor (guild)**.
+/// Frecuencias centrales (Hz) de las 10 bandas del ecualizador personalizado
+pub const CUSTOM_EQ_FREQUENCIES: [u32; 10] = [32, 64, 125, 250, 500, 1000, 2000, 4000, 8000, 16000];
+
+/// Posición de la banda de `band_hz` en [`CUSTOM_EQ_FREQUENCIES`]
+pub fn eq_band_index(band_hz: u32) -> Option<usize> {
+    CUSTOM_EQ_FREQUENCIES.iter().position(|freq| *freq == band_hz)
+}
+
+/// Velocidad mínima de `/speed`
+pub const MIN_TEMPO: f32 = 0.5;
+
+/// Velocidad máxima de `/speed`
+pub const MAX_TEMPO: f32 = 2.0;
+
+/// Rango que admite un único filtro `atempo` de ffmpeg
+const ATEMPO_STAGE_RANGE: (f32, f32) = (0.5, 2.0);
+
+/// Filtros `atempo` para cambiar la velocidad sin alterar el tono.
+///
+/// Cada `atempo` solo acepta factores entre 0.5 y 2.0, así que los factores
+/// fuera de ese rango se reparten en varias etapas encadenadas (4.0 = 2.0 × 2.0).
+pub fn atempo_filters(tempo: f32) -> Vec<String> {
+    let (min, max) = ATEMPO_STAGE_RANGE;
+    let mut filters = Vec::new();
+    let mut remaining = tempo;
+    while remaining > max {
+        filters.push(format!("atempo={}", max));
+        remaining /= max;
+    }
+    while remaining < min {
+        filters.push(format!("atempo={}", min));
+        remaining /= min;
+    }
+    if (remaining - 1.0).abs() > f32::EPSILON {
+        filters.push(format!("atempo={}", remaining));
+    }
+    filters
+}
+
+/// Volumen a partir del cual `/volume` avisa de la saturación y actúa el
+/// limitador (`/settings limiter`)
+pub const LIMITER_VOLUME_THRESHOLD: f32 = 1.5;
+
+/// Limitador suave (`alimiter`) para un volumen amplificado, o `None` si el
+/// volumen no supera [`LIMITER_VOLUME_THRESHOLD`].
+///
+/// Songbird aplica el volumen después de ffmpeg, así que el techo del limitador
+/// es `1 / volume`: tras la ganancia los picos quedan justo en 0 dBFS.
+pub fn limiter_filter(volume: f32) -> Option<String> {
+    (volume > LIMITER_VOLUME_THRESHOLD)
+        .then(|| format!("alimiter=limit={:.3}:attack=5:release=50:level=false", 1.0 / volume))
+}
+
+impl EqualizerPreset {
+    /// Nombre del preset tal como se usa en `/equalizer` y en la configuración persistida
+    pub fn name(&self) -> &'static str {
+        match self {
+            EqualizerPreset::Flat => "flat",
+            EqualizerPreset::Bass => "bass",
+            EqualizerPreset::Pop => "pop",
+            EqualizerPreset::Rock => "rock",
+            EqualizerPreset::Jazz => "jazz",
+            EqualizerPreset::Classical => "classical",
+            EqualizerPreset::Electronic => "electronic",
+            EqualizerPreset::Vocal => "vocal",
+            EqualizerPreset::Custom => "custom",
+        }
+    }
+
+    /// Parsea un nombre de preset (sin distinguir mayúsculas)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "flat" => Some(EqualizerPreset::Flat),
+            "bass" => Some(EqualizerPreset::Bass),
+            "pop" => Some(EqualizerPreset::Pop),
+            "rock" => Some(EqualizerPreset::Rock),
+            "jazz" => Some(EqualizerPreset::Jazz),
+            "classical" => Some(EqualizerPreset::Classical),
+            "electronic" => Some(EqualizerPreset::Electronic),
+            "vocal" => Some(EqualizerPreset::Vocal),
+            "custom" => Some(EqualizerPreset::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// Estado completo de los efectos de audio de una guild.
+///
+/// Reúne en un solo lugar todo lo que modifica la cadena ffmpeg, para poder
+/// mostrarlo (`/effects show`) y restablecerlo de una vez (`/effects clear`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectsState {
+    /// Preset de ecualizador activo
+    pub preset: EqualizerPreset,
+    /// Ganancias (dB) del preset `Custom`, si la guild las definió
+    pub custom_bands: Option<[f32; 10]>,
+    /// Normalización de sonoridad (`loudnorm`, EBU R128)
+    pub normalize: bool,
+    /// Velocidad de reproducción (1.0 = normal)
+    pub tempo: f32,
+}
+
+impl Default for EffectsState {
+    fn default() -> Self {
+        Self {
+            preset: EqualizerPreset::Flat,
+            custom_bands: None,
+            normalize: true,
+            tempo: 1.0,
+        }
+    }
+}
+
+impl EffectsState {
+    /// `true` si no hay ningún efecto que altere el sonido respecto al default
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Filtros activos además del ecualizador, para mostrarlos al usuario
+    pub fn active_filters(&self) -> Vec<String> {
+        let mut filters = Vec::new();
+        if self.normalize {
+            filters.push("Normalización (loudnorm)".to_string());
+        }
+        if (self.tempo - 1.0).abs() > f32::EPSILON {
+            filters.push(format!("Tempo {:.2}x", self.tempo));
+        }
+        filters
+    }
+}
+
+/// Sistema de efectos de audio (ecualizador, normalización, tempo), **por servidor (guild)**.
 ///
 /// Antes había un único preset global compartido por todas las guilds: cambiarlo
-/// en un servidor afectaba a todos. Ahora cada guild tiene el suyo.
+/// en un servidor afectaba a todos. Ahora cada guild tiene su [`EffectsState`].
 pub struct AudioEffects {
-    presets: DashMap<GuildId, EqualizerPreset>,
+    states: DashMap<GuildId, EffectsState>,
 }
 
 impl AudioEffects {
     pub fn new() -> Self {
         info!("🎛️ Sistema de ecualizador inicializado");
         Self {
-            presets: DashMap::new(),
+            states: DashMap::new(),
         }
     }
 
-    /// Construye la cadena de filtros ffmpeg (`-af`) para el preset de la guild.
+    /// Estado de efectos de la guild (valores por defecto si nunca se tocó)
+    pub fn state(&self, guild_id: GuildId) -> EffectsState {
+        self.states.get(&guild_id).map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Construye la cadena de filtros ffmpeg (`-af`) para el estado de la guild.
     ///
-    /// Siempre incluye `loudnorm` (EBU R128) para igualar el volumen percibido entre
-    /// temas, y añade bandas `equalizer` según el preset. Para `Flat` solo normaliza.
+    /// Por defecto incluye `loudnorm` (EBU R128) para igualar el volumen percibido
+    /// entre temas, y añade bandas `equalizer` según el preset y `atempo` si la
+    /// velocidad no es la normal. Sin ningún efecto se usa `anull`.
     pub fn build_filter(&self, guild_id: GuildId) -> String {
         // loudnorm de una sola pasada: consistente y apto para streaming.
-        let loudnorm = "loudnorm=I=-16:TP=-1.5:LRA=11";
+        self.build_filter_normalized(guild_id, ONE_PASS_FILTER)
+    }
 
-        let preset = self.get_current_preset(guild_id);
-        let eq = match preset {
+    /// Como [`build_filter`](Self::build_filter), pero normalizando con
+    /// `normalizer` (p. ej. el `loudnorm` con valores medidos de la pista).
+    pub fn build_filter_normalized(&self, guild_id: GuildId, normalizer: &str) -> String {
+        let state = self.state(guild_id);
+        let mut filters: Vec<String> = Vec::new();
+
+        if state.normalize {
+            filters.push(normalizer.to_string());
+        }
+
+        let eq = match state.preset {
             EqualizerPreset::Flat => "",
+            EqualizerPreset::Custom => "",
             // f=frecuencia(Hz), t=o (ancho en octavas), w=ancho, g=ganancia(dB)
             EqualizerPreset::Bass =>
                 "equalizer=f=60:t=o:w=2:g=6,equalizer=f=120:t=o:w=2:g=3",
@@ -58,25 +213,68 @@ impl AudioEffects {
             EqualizerPreset::Vocal =>
                 "equalizer=f=200:t=o:w=2:g=-2,equalizer=f=3000:t=o:w=2:g=4",
         };
+        if !eq.is_empty() {
+            filters.push(eq.to_string());
+        }
+        if state.preset == EqualizerPreset::Custom {
+            filters.extend(Self::custom_bands_filter(state.custom_bands.unwrap_or_default()));
+        }
+
+        if state.tempo > 0.0 {
+            filters.extend(atempo_filters(state.tempo));
+        }
 
-        let filter = if eq.is_empty() {
-            loudnorm.to_string()
+        let filter = if filters.is_empty() {
+            "anull".to_string()
         } else {
-            format!("{},{}", loudnorm, eq)
+            filters.join(",")
         };
-        info!("🎛️ Filtro ffmpeg ({:?}) guild {}: {}", preset, guild_id, filter);
+        info!("🎛️ Filtro ffmpeg ({:?}) guild {}: {}", state.preset, guild_id, filter);
         filter
     }
 
+    /// Bandas del preset `Custom`: un `equalizer` de una octava por cada ganancia no nula
+    fn custom_bands_filter(bands: [f32; 10]) -> impl Iterator<Item = String> {
+        CUSTOM_EQ_FREQUENCIES
+            .into_iter()
+            .zip(bands)
+            .filter(|(_, gain)| *gain != 0.0)
+            .map(|(freq, gain)| format!("equalizer=f={}:t=o:w=1:g={}", freq, gain.clamp(-12.0, 12.0)))
+    }
+
+    /// Aplica ganancias personalizadas (dB, 10 bandas) y activa el preset `Custom`
+    pub fn apply_custom_bands(&self, guild_id: GuildId, bands: [f32; 10]) {
+        self.states.entry(guild_id).or_default().custom_bands = Some(bands);
+        self.apply_equalizer_preset(guild_id, EqualizerPreset::Custom);
+    }
+
+    /// Cambia la ganancia (dB) de una sola banda del preset `Custom` y lo activa.
+    ///
+    /// Las demás bandas conservan su ganancia (0 dB si la guild no tenía bandas propias).
+    pub fn set_single_band(&self, guild_id: GuildId, band_hz: u32, gain_db: f32) -> Result<()> {
+        let Some(index) = eq_band_index(band_hz) else {
+            anyhow::bail!("La banda de {} Hz no existe; las bandas son {:?}", band_hz, CUSTOM_EQ_FREQUENCIES);
+        };
+        if !gain_db.is_finite() || gain_db.abs() > MAX_EQ_GAIN_DB {
+            anyhow::bail!("La ganancia debe estar entre -{0} y +{0} dB", MAX_EQ_GAIN_DB);
+        }
+
+        let mut bands = self.state(guild_id).custom_bands.unwrap_or_default();
+        bands[index] = gain_db;
+        info!("🎚️ Banda de {} Hz a {:+.1} dB (guild {})", band_hz, gain_db, guild_id);
+        self.apply_custom_bands(guild_id, bands);
+        Ok(())
+    }
+
     /// Aplica preset de ecualizador a una guild
     pub fn apply_equalizer_preset(&self, guild_id: GuildId, preset: EqualizerPreset) {
-        self.presets.insert(guild_id, preset);
+        self.states.entry(guild_id).or_default().preset = preset;
         info!("🎛️ Preset de ecualizador aplicado: {:?} (guild {})", preset, guild_id);
     }
 
     /// Obtiene el preset actual de la guild (Flat por defecto)
     pub fn get_current_preset(&self, guild_id: GuildId) -> EqualizerPreset {
-        self.presets.get(&guild_id).map(|p| *p).unwrap_or(EqualizerPreset::Flat)
+        self.states.get(&guild_id).map(|s| s.preset).unwrap_or(EqualizerPreset::Flat)
     }
 
     /// Obtiene detalles del ecualizador de la guild
@@ -91,13 +289,29 @@ impl AudioEffects {
             EqualizerPreset::Classical => "Ecualizador: Clásica".to_string(),
             EqualizerPreset::Electronic => "Ecualizador: Electrónica".to_string(),
             EqualizerPreset::Vocal => "Ecualizador: Vocal".to_string(),
+            EqualizerPreset::Custom => "Ecualizador: Personalizado".to_string(),
         }
     }
 
+    /// Cambia la velocidad de reproducción de la guild (sin alterar el tono).
+    /// Devuelve la velocidad anterior.
+    pub fn set_tempo(&self, guild_id: GuildId, tempo: f32) -> f32 {
+        let tempo = tempo.clamp(MIN_TEMPO, MAX_TEMPO);
+        let previous = std::mem::replace(&mut self.states.entry(guild_id).or_default().tempo, tempo);
+        info!("⏱️ Tempo {:.2}x (guild {})", tempo, guild_id);
+        previous
+    }
+
     /// Resetea el ecualizador de la guild a plano
     #[allow(dead_code)]
     pub fn reset_equalizer(&self, guild_id: GuildId) {
         self.apply_equalizer_preset(guild_id, EqualizerPreset::Flat);
         info!("🔄 Ecualizador reseteado a plano (guild {})", guild_id);
     }
-}
\ No newline at end of file
+
+    /// Restablece todos los efectos de la guild a sus valores por defecto
+    pub fn clear(&self, guild_id: GuildId) {
+        self.states.remove(&guild_id);
+        info!("🧹 Efectos de audio restablecidos (guild {})", guild_id);
+    }
+}
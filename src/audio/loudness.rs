@@ -0,0 +1,97 @@
+//! Normalización de sonoridad en dos pasadas (`loudnorm`, EBU R128).
+//!
+//! Los videos de YouTube llegan con sonoridades muy distintas (de -20 a -6 LUFS).
+//! El `loudnorm` de una sola pasada las iguala sobre la marcha, pero bombea el
+//! volumen al principio de cada tema. Con `LOUDNESS_NORMALIZATION`, la siguiente
+//! pista de la cola se mide antes de sonar (primera pasada, ffmpeg con
+//! `print_format=json`) y al reproducirla se le pasan los valores medidos a
+//! `loudnorm` en modo lineal (segunda pasada). Mientras no hay medición se usa
+//! una ganancia fija calculada sobre una sonoridad estimada.
+
+use serde::Deserialize;
+
+/// Sonoridad integrada objetivo (LUFS)
+pub const TARGET_LUFS: f32 = -16.0;
+
+/// Rango de sonoridad objetivo (LU)
+pub const TARGET_LRA: f32 = 11.0;
+
+/// Pico real máximo (dBTP)
+pub const TARGET_TRUE_PEAK: f32 = -1.5;
+
+/// Sonoridad que se supone a una pista aún sin medir: YouTube normaliza sus
+/// videos a -14 LUFS, así que es el valor más probable.
+pub const ESTIMATED_SOURCE_LUFS: f32 = -14.0;
+
+/// `loudnorm` de una sola pasada (el comportamiento sin `LOUDNESS_NORMALIZATION`)
+pub const ONE_PASS_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11";
+
+/// Filtro de la primera pasada: solo mide y deja el resumen JSON en el stderr
+pub const MEASURE_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11:print_format=json";
+
+/// Sonoridad medida de una pista (resumen de la primera pasada de `loudnorm`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessMeasurement {
+    /// Sonoridad integrada (LUFS)
+    pub input_i: f32,
+    /// Rango de sonoridad (LU)
+    pub input_lra: f32,
+    /// Pico real (dBTP)
+    pub input_tp: f32,
+    /// Umbral de la medición (LUFS)
+    pub input_thresh: f32,
+    /// Ganancia que `loudnorm` recomienda aplicar al final (dB)
+    pub target_offset: f32,
+}
+
+/// Resumen JSON que imprime `loudnorm` (todos los valores vienen como texto)
+#[derive(Deserialize)]
+struct LoudnormSummary {
+    input_i: String,
+    input_lra: String,
+    input_tp: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+impl LoudnessMeasurement {
+    /// `loudnorm` de la segunda pasada con los valores medidos
+    pub fn filter(&self) -> String {
+        format!(
+            "loudnorm=I={}:LRA={}:TP={}:measured_I={:.2}:measured_LRA={:.2}:measured_TP={:.2}:measured_thresh={:.2}:offset={:.2}:linear=true",
+            TARGET_LUFS,
+            TARGET_LRA,
+            TARGET_TRUE_PEAK,
+            self.input_i,
+            self.input_lra,
+            self.input_tp,
+            self.input_thresh,
+            self.target_offset
+        )
+    }
+}
+
+/// Extrae la medición del stderr de ffmpeg con [`MEASURE_FILTER`].
+///
+/// `None` si no aparece el resumen o algún valor no es finito (una pista muda
+/// mide `-inf`).
+pub fn parse_loudnorm_output(stderr: &str) -> Option<LoudnessMeasurement> {
+    let start = stderr.rfind('{')?;
+    let end = start + stderr[start..].find('}')?;
+    let summary: LoudnormSummary = serde_json::from_str(&stderr[start..=end]).ok()?;
+
+    let value = |text: &str| text.trim().parse::<f32>().ok().filter(|v| v.is_finite());
+    Some(LoudnessMeasurement {
+        input_i: value(&summary.input_i)?,
+        input_lra: value(&summary.input_lra)?,
+        input_tp: value(&summary.input_tp)?,
+        input_thresh: value(&summary.input_thresh)?,
+        target_offset: value(&summary.target_offset)?,
+    })
+}
+
+/// Ganancia fija para una pista sin medir: lleva [`ESTIMATED_SOURCE_LUFS`] a
+/// [`TARGET_LUFS`] sin compresión dinámica.
+pub fn estimated_filter() -> String {
+    format!("volume={}dB", TARGET_LUFS - ESTIMATED_SOURCE_LUFS)
+}
@@ -27,6 +27,12 @@
 //! - Audio filters and processing pipeline
 //! - Opus encoding optimization for Discord
 //!
+//! ### [`loudness`] - Loudness Normalization
+//! - Optional two-pass `loudnorm` with per-track measured values
+//!
+//! ### [`silence`] - Silence Detection
+//! - Skips tracks that stay silent (corrupted streams) via ffmpeg `silencedetect`
+//!
 //! ## Performance Characteristics
 //!
 //! - **Latency**: <100ms end-to-end audio latency
@@ -44,7 +50,7 @@
 //! ## Example Usage
 //!
 //! ```text
-//! let player = AudioPlayer::new(default_volume);
+//! let player = AudioPlayer::new(default_volume, smooth_volume);
 //! // Encolar y reproducir (requiere un handler de voz de songbird):
 //! player.play(guild_id, track_source, handler).await?;
 //! player.pause(guild_id).await?;
@@ -53,6 +59,8 @@
 //! ```
 
 pub mod effects;
+pub mod loudness;
 pub mod player;
 pub mod queue;
 pub mod robust_queue;
+pub mod silence;
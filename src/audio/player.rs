@@ -1,28 +1,207 @@
 use anyhow::Result;
 use dashmap::DashMap;
+use futures::future::BoxFuture;
 use parking_lot::RwLock;
-use serenity::model::id::{GuildId, UserId};
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
 use songbird::{
+    input::Input,
     tracks::{PlayMode, TrackHandle},
     Call, Event, EventContext, EventHandler as SongbirdEventHandler, TrackEvent,
 };
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Mutex;
-use tracing::{info, warn};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 use crate::{
     audio::{
-        effects::{AudioEffects, EqualizerPreset},
-        queue::{LoopMode, MusicQueue, QueueInfo, QueueItem},
+        effects::{limiter_filter, AudioEffects, EffectsState, EqualizerPreset},
+        loudness::{estimated_filter, LoudnessMeasurement},
+        queue::{LoopMode, MusicQueue, QueueInfo, QueueItem, RemovedTracks, RestoreOutcome, SortField, SortKey, SortOrder},
+        silence::{SilenceMonitor, SILENCE_MIN_DURATION},
     },
-    sources::TrackSource,
+    cache::lru_cache::LRUCache,
+    errors::{record_error, ErrorCategory},
+    sources::{direct, TrackSource},
+    ws::WsEvent,
 };
 
 /// Tiempo de gracia tras vaciarse la cola antes de desconectar del canal de voz.
 const AUTO_LEAVE_GRACE: Duration = Duration::from_secs(60);
 
+/// Cada cuánto se guarda la posición de la pista actual (para retomarla si se
+/// cae la conexión de voz).
+const POSITION_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Cada cuánto se consulta la metadata ICY de una radio para conocer la
+/// canción que suena.
+const STREAM_TITLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Cada cuánto se revisa si la pista llegó al final de su sección A-B.
+const AB_LOOP_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Duración mínima de una sección A-B: más corta, el salto atrás no llega a oírse.
+pub const MIN_AB_LOOP_LEN: Duration = Duration::from_secs(1);
+
+/// Cada cuánto se revisa si la pista actual sigue en silencio.
+const SILENCE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Por debajo de esta posición no vale la pena hacer seek al reconectar.
+const RESUME_MIN_POSITION: Duration = Duration::from_secs(2);
+
+/// Margen antes del final: al reconectar nunca se salta más allá de aquí.
+const RESUME_TAIL_MARGIN: Duration = Duration::from_secs(5);
+
+/// Duración de la rampa de volumen cuando `smooth_volume` está activo.
+const VOLUME_RAMP_DURATION: Duration = Duration::from_millis(300);
+
+/// Pasos en los que se divide la rampa de volumen.
+const VOLUME_RAMP_STEPS: usize = 10;
+
+/// Antelación con la que se precarga la siguiente pista antes de que termine la actual.
+const PREFETCH_LEAD: Duration = Duration::from_secs(20);
+
+/// Edad máxima de un input precargado: las URLs de stream de YouTube caducan y
+/// la conexión de yt-dlp queda parada mientras nadie lee.
+pub const PREFETCH_MAX_AGE: Duration = Duration::from_secs(90);
+
+/// Reinicios seguidos permitidos con `/restart-playback` sobre la misma pista.
+pub const MAX_PLAYBACK_RESTARTS: u32 = 3;
+
+/// Sección de la pista actual que se repite con `/ab-loop`. Son posiciones de la
+/// canción: con `/speed` se convierten al tiempo del stream al comparar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbLoop {
+    pub start: Duration,
+    pub end: Duration,
+}
+
+/// Sección A-B que no se puede repetir en la pista actual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AbLoopError {
+    #[error("La sección debe durar al menos 1 segundo (el final va después del inicio)")]
+    TooShort,
+    #[error("La sección termina después del final de la canción")]
+    PastTrackEnd,
+    #[error("Solo se puede repetir una sección de canciones con duración conocida")]
+    UnknownDuration,
+}
+
+impl AbLoop {
+    /// Valida la sección contra la duración de la pista (`None` = radio o directo).
+    pub fn new(start: Duration, end: Duration, track_duration: Option<Duration>) -> Result<Self, AbLoopError> {
+        let track_duration = track_duration.ok_or(AbLoopError::UnknownDuration)?;
+        if end < start + MIN_AB_LOOP_LEN {
+            return Err(AbLoopError::TooShort);
+        }
+        if end > track_duration {
+            return Err(AbLoopError::PastTrackEnd);
+        }
+        Ok(Self { start, end })
+    }
+
+    /// `true` si `position` (de la canción) está dentro de la sección
+    pub fn contains(&self, position: Duration) -> bool {
+        position >= self.start && position < self.end
+    }
+}
+
+/// La pista actual ya agotó sus reinicios con `/restart-playback`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("La canción ya se reinició {0} veces seguidas")]
+pub struct TooManyRestartsError(pub u32);
+
+/// Avisos de inicio (o fin) de pista pendientes antes de que un suscriptor
+/// lento pierda los más antiguos.
+const TRACK_START_CHANNEL_CAPACITY: usize = 64;
+
+/// Mediciones de sonoridad que se recuerdan (por URL) para no repetir la
+/// primera pasada de `loudnorm` al volver a sonar una pista.
+const LOUDNESS_CACHE_CAPACITY: usize = 1000;
+
+/// Eventos para el stream WebSocket pendientes antes de que un cliente lento
+/// pierda los más antiguos.
+const WS_EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Cambios de la cola que se pueden deshacer con `/undo`, por guild.
+pub const UNDO_DEPTH: usize = 5;
+
+/// Canciones que quitó un cambio destructivo (`/clear`, `/remove`...).
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    /// Comando que hizo el cambio, para avisar qué se deshizo
+    pub operation: &'static str,
+    pub removed: RemovedTracks,
+}
+
+/// Una pista de la cola empezó a sonar (ver [`AudioPlayer::subscribe_track_starts`]).
+#[derive(Debug, Clone)]
+pub struct TrackStarted {
+    pub guild_id: GuildId,
+    pub source: TrackSource,
+}
+
+/// Una pista terminó o se saltó (ver [`AudioPlayer::subscribe_track_finishes`]).
+#[derive(Debug, Clone)]
+pub struct TrackFinished {
+    pub guild_id: GuildId,
+    pub source: TrackSource,
+    /// Tiempo que sonó realmente (sin pausas)
+    pub played: Duration,
+}
+
+/// Valores intermedios de una rampa lineal de `from` a `to` en `steps` pasos.
+///
+/// El último valor es siempre `to`; con `steps == 0` o sin cambio se salta directo.
+pub fn volume_ramp(from: f32, to: f32, steps: usize) -> Vec<f32> {
+    if steps == 0 || (to - from).abs() < f32::EPSILON {
+        return vec![to];
+    }
+    (1..steps)
+        .map(|i| from + (to - from) * i as f32 / steps as f32)
+        .chain(std::iter::once(to))
+        .collect()
+}
+
+/// Posición desde la que retomar una pista tras reconectar.
+///
+/// `None` si la pista apenas había empezado (se reinicia desde cero). Si la
+/// posición guardada cae en los últimos segundos, se recorta para que la pista
+/// termine con normalidad en vez de fallar el seek.
+pub fn resume_point(saved: Duration, total: Option<Duration>) -> Option<Duration> {
+    if saved < RESUME_MIN_POSITION {
+        return None;
+    }
+    match total {
+        Some(total) if total > RESUME_TAIL_MARGIN => Some(saved.min(total - RESUME_TAIL_MARGIN)),
+        Some(_) => None,
+        None => Some(saved),
+    }
+}
+
+/// Cuánto esperar desde que arranca una pista para precargar la siguiente:
+/// [`PREFETCH_LEAD`] antes del final, a la velocidad `tempo`. `None` si la
+/// pista no tiene duración conocida (radios, directos).
+pub fn prefetch_delay(duration: Option<Duration>, tempo: f32) -> Option<Duration> {
+    let playback = duration?.div_f32(tempo.max(f32::EPSILON));
+    Some(playback.saturating_sub(PREFETCH_LEAD))
+}
+
+/// Input de la siguiente pista ya abierto (yt-dlp | ffmpeg) para que el cambio
+/// de canción no espere a yt-dlp.
+struct PrefetchedInput {
+    url: Arc<str>,
+    /// Filtro ffmpeg con el que se abrió: si los efectos cambian, no sirve
+    filter: String,
+    /// `Input` no es `Sync`; el mutex permite guardarlo en el estado compartido
+    input: parking_lot::Mutex<Input>,
+    silence: Option<Arc<SilenceMonitor>>,
+    fetched_at: Instant,
+}
+
 /// Estado compartido del reproductor.
 ///
 /// Vive detrás de un único `Arc`, de modo que tanto [`AudioPlayer`] como los
@@ -36,11 +215,34 @@ struct PlayerInner {
     effects: Arc<AudioEffects>,
     /// Handle de la pista que suena actualmente, por guild.
     current_tracks: DashMap<GuildId, TrackHandle>,
+    /// Fuente de la pista actual, para poder re-crear su input al reconectar.
+    current_sources: DashMap<GuildId, TrackSource>,
+    /// Última posición conocida de la pista actual (muestreada periódicamente).
+    positions: DashMap<GuildId, Duration>,
+    /// Reinicios seguidos de la pista actual con `/restart-playback`.
+    restarts: DashMap<GuildId, u32>,
+    /// Canción que anuncia la radio actual (metadata ICY), si la pista es una radio.
+    stream_titles: DashMap<GuildId, String>,
+    /// Silencio tolerado antes de saltar la pista, por guild (ausente = sin detección).
+    silence_limits: DashMap<GuildId, Duration>,
+    /// Guilds con el limitador activo para volúmenes amplificados (`/settings limiter`).
+    limiters: DashMap<GuildId, ()>,
+    /// Proxy de yt-dlp global (`YTDLP_PROXY`).
+    ytdlp_proxy: RwLock<Option<String>>,
+    /// Proxy de yt-dlp propio de cada guild (`/settings proxy`); gana sobre el global.
+    proxy_overrides: DashMap<GuildId, String>,
+    /// Último mensaje de "reproduciendo ahora" publicado por guild (canal, mensaje).
+    now_playing_messages: DashMap<GuildId, (ChannelId, MessageId)>,
     /// Volumen efectivo por guild (0.0–2.0). Se aplica a cada pista nueva para
     /// que el ajuste persista entre canciones, no solo en la que suena.
     volumes: DashMap<GuildId, f32>,
     /// Volumen por defecto (de la config) cuando una guild no tiene ajuste propio.
     default_volume: f32,
+    /// Cambiar el volumen con una rampa corta en vez de un salto (`SMOOTH_VOLUME`).
+    smooth_volume: bool,
+    /// Contador de cambios de volumen por guild: una rampa en curso se cancela
+    /// en cuanto llega otro cambio (gana el último).
+    volume_ramps: DashMap<GuildId, Arc<AtomicU64>>,
     /// Contador de "generación" por guild. Cada vez que arranca una pista nueva
     /// se incrementa; el event handler de fin sólo avanza si su generación sigue
     /// vigente. Así distinguimos un fin natural de un stop/skip/leave manual.
@@ -48,6 +250,26 @@ struct PlayerInner {
     /// Lock por guild para serializar las transiciones de pista (evita carreras
     /// entre el avance automático y un `/play` simultáneo).
     advance_locks: DashMap<GuildId, Arc<Mutex<()>>>,
+    /// Avisa cuando la cola arranca una pista nueva (p. ej. para los DMs de `/notify`).
+    track_starts: broadcast::Sender<TrackStarted>,
+    /// Avisa cuando una pista termina o se salta, con el tiempo que sonó.
+    track_finishes: broadcast::Sender<TrackFinished>,
+    /// Eventos del reproductor para los clientes del stream WebSocket (`WS_PORT`).
+    events: broadcast::Sender<WsEvent>,
+    /// Input precargado de la siguiente pista, por guild.
+    prefetched: DashMap<GuildId, PrefetchedInput>,
+    /// Tarea que precarga la siguiente pista, para cancelarla en `stop`/`clear`.
+    prefetch_tasks: DashMap<GuildId, JoinHandle<()>>,
+    /// Sección A-B de la pista actual (`/ab-loop`); se borra al cambiar de pista.
+    ab_loops: DashMap<GuildId, AbLoop>,
+    /// Normalización en dos pasadas con la sonoridad medida (`LOUDNESS_NORMALIZATION`).
+    loudness_normalization: AtomicBool,
+    /// Sonoridad medida por URL de pista.
+    loudness: LRUCache<String, LoudnessMeasurement>,
+    /// URLs que se están midiendo, para no lanzar dos mediciones de la misma pista.
+    loudness_pending: DashMap<Arc<str>, ()>,
+    /// Estados previos de la cola para `/undo` (el último al final, como mucho `UNDO_DEPTH`).
+    undo_stacks: DashMap<GuildId, VecDeque<UndoEntry>>,
 }
 
 impl PlayerInner {
@@ -73,6 +295,13 @@ impl PlayerInner {
             .clone()
     }
 
+    fn volume_ramp_counter(&self, guild_id: GuildId) -> Arc<AtomicU64> {
+        self.volume_ramps
+            .entry(guild_id)
+            .or_insert_with(|| Arc::new(AtomicU64::new(0)))
+            .clone()
+    }
+
     fn advance_lock(&self, guild_id: GuildId) -> Arc<Mutex<()>> {
         self.advance_locks
             .entry(guild_id)
@@ -80,6 +309,82 @@ impl PlayerInner {
             .clone()
     }
 
+    /// Publica un evento para el stream WebSocket (sin clientes no pasa nada).
+    fn emit(&self, event: WsEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Publica la cantidad de canciones pendientes de la guild.
+    fn emit_queue_updated(&self, guild_id: GuildId) {
+        let queue_length = self.queue(guild_id).read().len();
+        self.emit(WsEvent::QueueUpdated { guild_id, queue_length });
+    }
+
+    /// Filtro ffmpeg para `source`: con la normalización en dos pasadas usa su
+    /// sonoridad medida o, si aún no se midió, una ganancia estimada. Las radios
+    /// siempre usan el `loudnorm` de una pasada.
+    fn track_filter(&self, guild_id: GuildId, source: &TrackSource) -> String {
+        let filter = if !self.loudness_normalization.load(Ordering::Relaxed) || source.is_radio() {
+            self.effects.build_filter(guild_id)
+        } else {
+            let normalizer = match self.loudness.get(&source.url().to_string()) {
+                Some(measurement) => measurement.filter(),
+                None => estimated_filter(),
+            };
+            self.effects.build_filter_normalized(guild_id, &normalizer)
+        };
+        match self.limiter_filter(guild_id) {
+            Some(limiter) => format!("{},{}", filter, limiter),
+            None => filter,
+        }
+    }
+
+    /// Proxy de yt-dlp de la guild: el propio si lo tiene, si no el global
+    fn ytdlp_proxy(&self, guild_id: GuildId) -> Option<String> {
+        match self.proxy_overrides.get(&guild_id) {
+            Some(proxy) => Some(proxy.clone()),
+            None => self.ytdlp_proxy.read().clone(),
+        }
+    }
+
+    /// `alimiter` para el volumen actual, si la guild tiene el limitador activo
+    /// y el volumen supera [`LIMITER_VOLUME_THRESHOLD`](crate::audio::effects::LIMITER_VOLUME_THRESHOLD).
+    fn limiter_filter(&self, guild_id: GuildId) -> Option<String> {
+        if !self.limiters.contains_key(&guild_id) {
+            return None;
+        }
+        limiter_filter(self.effective_volume(guild_id))
+    }
+
+    /// Olvida la pista actual de la guild (handle, fuente y posición).
+    fn forget_track(&self, guild_id: GuildId) -> Option<TrackHandle> {
+        self.current_sources.remove(&guild_id);
+        self.positions.remove(&guild_id);
+        self.restarts.remove(&guild_id);
+        self.stream_titles.remove(&guild_id);
+        self.ab_loops.remove(&guild_id);
+        self.current_tracks.remove(&guild_id).map(|(_, track)| track)
+    }
+
+    /// Cancela la precarga en curso y descarta el input precargado (al soltarlo
+    /// songbird mata yt-dlp y ffmpeg).
+    fn cancel_prefetch(&self, guild_id: GuildId) {
+        if let Some((_, task)) = self.prefetch_tasks.remove(&guild_id) {
+            task.abort();
+        }
+        self.prefetched.remove(&guild_id);
+    }
+
+    /// Input precargado de `url`, si se abrió con el mismo filtro y sigue vigente.
+    fn take_prefetched(&self, guild_id: GuildId, url: &str, filter: &str, silence: bool) -> Option<PrefetchedInput> {
+        let (_, prefetched) = self.prefetched.remove(&guild_id)?;
+        let usable = &*prefetched.url == url
+            && prefetched.filter == filter
+            && prefetched.silence.is_some() == silence
+            && prefetched.fetched_at.elapsed() < PREFETCH_MAX_AGE;
+        usable.then_some(prefetched)
+    }
+
     /// `true` si hay una pista activa (reproduciéndose o en pausa) en la guild.
     async fn is_occupied(&self, guild_id: GuildId) -> bool {
         if let Some(track) = self.current_tracks.get(&guild_id) {
@@ -91,25 +396,97 @@ impl PlayerInner {
     }
 }
 
+/// Foto del estado del reproductor de una guild, para diagnóstico (`/debug`).
+#[derive(Debug, Clone)]
+pub struct PlayerDebugState {
+    /// Hay una pista sonando o en pausa
+    pub is_playing: bool,
+    /// Estado crudo de la pista en songbird (`Play`, `Pause`, `End`, `Errored`...)
+    pub play_mode: Option<String>,
+    /// Pista actual según la cola
+    pub current: Option<TrackSource>,
+    /// Posición de la pista actual, en tiempo de la canción
+    pub position: Option<Duration>,
+    /// Canciones pendientes en la cola
+    pub queue_len: usize,
+    pub loop_mode: LoopMode,
+    pub shuffle: bool,
+    /// Volumen efectivo (0.0–2.0)
+    pub volume: f32,
+    pub effects: EffectsState,
+    /// Generación de la pista actual (cambia en cada inicio/stop/skip)
+    pub generation: u64,
+}
+
 pub struct AudioPlayer {
     inner: Arc<PlayerInner>,
 }
 
 impl AudioPlayer {
-    pub fn new(default_volume: f32) -> Self {
+    pub fn new(default_volume: f32, smooth_volume: bool) -> Self {
         Self {
             inner: Arc::new(PlayerInner {
                 queues: DashMap::new(),
                 effects: Arc::new(AudioEffects::new()),
                 current_tracks: DashMap::new(),
+                current_sources: DashMap::new(),
+                positions: DashMap::new(),
+                restarts: DashMap::new(),
+                stream_titles: DashMap::new(),
+                silence_limits: DashMap::new(),
+                limiters: DashMap::new(),
+                ytdlp_proxy: RwLock::new(None),
+                proxy_overrides: DashMap::new(),
+                now_playing_messages: DashMap::new(),
                 volumes: DashMap::new(),
                 default_volume: default_volume.clamp(0.0, 2.0),
+                smooth_volume,
+                volume_ramps: DashMap::new(),
                 generations: DashMap::new(),
                 advance_locks: DashMap::new(),
+                track_starts: broadcast::channel(TRACK_START_CHANNEL_CAPACITY).0,
+                track_finishes: broadcast::channel(TRACK_START_CHANNEL_CAPACITY).0,
+                events: broadcast::channel(WS_EVENT_CHANNEL_CAPACITY).0,
+                prefetched: DashMap::new(),
+                prefetch_tasks: DashMap::new(),
+                ab_loops: DashMap::new(),
+                loudness_normalization: AtomicBool::new(false),
+                loudness: LRUCache::new(LOUDNESS_CACHE_CAPACITY),
+                loudness_pending: DashMap::new(),
+                undo_stacks: DashMap::new(),
             }),
         }
     }
 
+    /// Recibe un [`TrackStarted`] cada vez que la cola arranca la siguiente pista.
+    ///
+    /// No incluye los reinicios, `/seek` ni los saltos directos (`/jump`, `/previous`).
+    pub fn subscribe_track_starts(&self) -> broadcast::Receiver<TrackStarted> {
+        self.inner.track_starts.subscribe()
+    }
+
+    /// Recibe un [`TrackFinished`] cada vez que una pista termina sola o se salta
+    /// (`/skip`, botón ⏭️); no incluye `/stop` ni las desconexiones.
+    pub fn subscribe_track_finishes(&self) -> broadcast::Receiver<TrackFinished> {
+        self.inner.track_finishes.subscribe()
+    }
+
+    /// Recibe los eventos del reproductor que se reenvían por el stream WebSocket.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<WsEvent> {
+        self.inner.events.subscribe()
+    }
+
+    /// Activa o desactiva la normalización de sonoridad en dos pasadas
+    /// (`LOUDNESS_NORMALIZATION`). Se aplica desde la próxima pista.
+    pub fn set_loudness_normalization(&self, enabled: bool) {
+        self.inner.loudness_normalization.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Publica un evento ajeno al reproductor (p. ej. entrar o salir de voz).
+    pub fn emit(&self, event: WsEvent) {
+        self.inner.emit(event);
+    }
+
     /// Agrega una canción a la cola y comienza a reproducir si no hay nada sonando.
     pub async fn play(
         &self,
@@ -122,6 +499,7 @@ impl AudioPlayer {
             let mut q = queue.write();
             q.add_track(source)?;
         }
+        self.inner.emit_queue_updated(guild_id);
 
         // Sólo arranca si está libre. La decisión se toma dentro del lock para
         // que dos `/play` simultáneos no inicien dos pistas a la vez.
@@ -139,12 +517,15 @@ impl AudioPlayer {
     ) -> Result<()> {
         let lock = self.inner.advance_lock(guild_id);
         let _guard = lock.lock().await;
+        self.inner.restarts.remove(&guild_id);
+        self.inner.ab_loops.remove(&guild_id);
         Self::start_track(&self.inner, guild_id, source, &handler).await
     }
 
     /// Arranca la siguiente canción de la cola **solo si no hay nada sonando**.
     /// Lo usan el flujo de playlist y el arranque inicial.
     pub async fn play_next(&self, guild_id: GuildId, handler: Arc<Mutex<Call>>) -> Result<()> {
+        self.inner.emit_queue_updated(guild_id);
         Self::try_start_if_idle(&self.inner, guild_id, handler).await;
         Ok(())
     }
@@ -179,13 +560,16 @@ impl AudioPlayer {
     pub async fn stop(&self, guild_id: GuildId) -> Result<()> {
         self.inner.generation(guild_id).fetch_add(1, Ordering::AcqRel);
 
-        if let Some((_, track)) = self.inner.current_tracks.remove(&guild_id) {
+        if let Some(track) = self.inner.forget_track(guild_id) {
             if let Err(e) = track.stop() {
                 warn!("Error deteniendo track: {:?}", e);
             }
         }
 
-        self.clear_queue(guild_id).await?;
+        // Detener no se deshace: vacía la cola sin guardar su estado
+        self.inner.cancel_prefetch(guild_id);
+        self.inner.queue(guild_id).write().clear();
+        self.inner.emit_queue_updated(guild_id);
         info!("⏹️ Reproducción detenida en guild {}", guild_id);
         Ok(())
     }
@@ -207,6 +591,20 @@ impl AudioPlayer {
             q.skip(amount.saturating_sub(1));
         }
 
+        if let Some(source) = self.inner.current_sources.get(&guild_id).map(|source| source.clone()) {
+            self.inner.emit(WsEvent::TrackSkipped {
+                guild_id,
+                title: source.title().to_string(),
+                url: source.url().to_string(),
+                requested_by: source.requested_by(),
+            });
+            let track = self.inner.current_tracks.get(&guild_id).map(|t| t.clone());
+            let played = match track {
+                Some(track) => track.get_info().await.map(|info| info.play_time).unwrap_or_default(),
+                None => Duration::ZERO,
+            };
+            let _ = self.inner.track_finishes.send(TrackFinished { guild_id, source, played });
+        }
         Self::force_advance(&self.inner, guild_id, handler).await;
         info!("⏭️ Saltadas {} canciones en guild {}", amount, guild_id);
         Ok(())
@@ -222,10 +620,73 @@ impl AudioPlayer {
         false
     }
 
+    /// Activa (con el silencio tolerado) o desactiva la detección de silencio de
+    /// la guild. Se aplica desde la próxima pista que arranque.
+    pub fn set_silence_detection(&self, guild_id: GuildId, limit: Option<Duration>) {
+        match limit {
+            Some(limit) => self.inner.silence_limits.insert(guild_id, limit),
+            None => self.inner.silence_limits.remove(&guild_id).map(|(_, limit)| limit),
+        };
+    }
+
+    /// Activa o desactiva el limitador suave de la guild. Como el ecualizador,
+    /// se aplica desde el próximo input.
+    pub fn set_limiter(&self, guild_id: GuildId, enabled: bool) {
+        if enabled {
+            self.inner.limiters.insert(guild_id, ());
+        } else {
+            self.inner.limiters.remove(&guild_id);
+        }
+    }
+
+    /// Cambia el proxy global de yt-dlp (`YTDLP_PROXY`). Se aplica desde el próximo input.
+    pub fn set_ytdlp_proxy(&self, proxy: Option<String>) {
+        *self.inner.ytdlp_proxy.write() = proxy;
+    }
+
+    /// Sobrescribe (o con `None`, deja de sobrescribir) el proxy de yt-dlp de la guild.
+    pub fn set_proxy_override(&self, guild_id: GuildId, proxy: Option<String>) {
+        match proxy {
+            Some(proxy) => self.inner.proxy_overrides.insert(guild_id, proxy),
+            None => self.inner.proxy_overrides.remove(&guild_id).map(|(_, proxy)| proxy),
+        };
+    }
+
+    /// Proxy de yt-dlp con el que se abren las pistas de la guild.
+    pub fn ytdlp_proxy(&self, guild_id: GuildId) -> Option<String> {
+        self.inner.ytdlp_proxy(guild_id)
+    }
+
+    /// `true` si el limitador actúa con el volumen actual de la guild.
+    pub fn limiter_engaged(&self, guild_id: GuildId) -> bool {
+        self.inner.limiter_filter(guild_id).is_some()
+    }
+
+    /// Registra el último mensaje de "reproduciendo ahora" de la guild y
+    /// devuelve el anterior, si era otro mensaje.
+    pub fn replace_now_playing_message(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        message_id: MessageId,
+    ) -> Option<(ChannelId, MessageId)> {
+        self.inner
+            .now_playing_messages
+            .insert(guild_id, (channel_id, message_id))
+            .filter(|(_, previous)| *previous != message_id)
+    }
+
+    /// Pista actual. En radios, el título es la canción que anuncia la emisora.
     pub async fn get_current_track(&self, guild_id: GuildId) -> Option<TrackSource> {
-        let queue = self.inner.queue(guild_id);
-        let q = queue.read();
-        q.current_track()
+        let current = {
+            let queue = self.inner.queue(guild_id);
+            let q = queue.read();
+            q.current_track()
+        }?;
+        match self.inner.stream_titles.get(&guild_id) {
+            Some(title) if current.is_radio() => Some(current.with_title(title.clone())),
+            _ => Some(current),
+        }
     }
 
     pub async fn get_queue_info(&self, guild_id: GuildId) -> Result<QueueInfo> {
@@ -254,9 +715,15 @@ impl AudioPlayer {
     }
 
     pub async fn clear_queue(&self, guild_id: GuildId) -> Result<()> {
+        self.inner.cancel_prefetch(guild_id);
         let queue = self.inner.queue(guild_id);
         let mut q = queue.write();
+        let snapshot = q.snapshot();
         q.clear();
+        let removed = q.removed_since(snapshot);
+        drop(q);
+        self.push_undo(guild_id, "/clear", removed);
+        self.inner.emit_queue_updated(guild_id);
         info!("🗑️ Cola limpiada en guild {}", guild_id);
         Ok(())
     }
@@ -273,6 +740,45 @@ impl AudioPlayer {
         Ok(shuffled)
     }
 
+    /// Mezcla una vez las canciones pendientes, sin activar el modo aleatorio.
+    pub async fn shuffle_once(&self, guild_id: GuildId) -> Result<usize> {
+        let queue = self.inner.queue(guild_id);
+        let mut q = queue.write();
+        let shuffled = q.shuffle_once();
+        info!("🔀 Cola mezclada una vez en guild {} ({} canciones)", guild_id, shuffled);
+        Ok(shuffled)
+    }
+
+    /// Mezcla una vez las canciones pendientes dejando las fijadas en su lugar.
+    pub async fn shuffle_pending(&self, guild_id: GuildId) -> Result<usize> {
+        let queue = self.inner.queue(guild_id);
+        let mut q = queue.write();
+        Ok(q.shuffle_pending())
+    }
+
+    /// Ordena las canciones pendientes sin tocar la que suena.
+    pub async fn sort_queue(&self, guild_id: GuildId, key: SortKey, order: SortOrder) -> Result<usize> {
+        let queue = self.inner.queue(guild_id);
+        let mut q = queue.write();
+        Ok(q.sort_by(key, order))
+    }
+
+    /// Reordena las canciones pendientes según el criterio de `/queue-sort`.
+    pub async fn sort_queue_by_field(&self, guild_id: GuildId, field: SortField) -> Result<usize> {
+        let queue = self.inner.queue(guild_id);
+        let mut q = queue.write();
+        let sorted = q.sort_by_field(field);
+        info!("↕️ Cola ordenada en guild {} por {}", guild_id, field.display_name());
+        Ok(sorted)
+    }
+
+    /// Mezcla una vez las canciones pendientes con una semilla reproducible.
+    pub async fn shuffle_with_seed(&self, guild_id: GuildId, seed: u64) -> Result<usize> {
+        let queue = self.inner.queue(guild_id);
+        let mut q = queue.write();
+        Ok(q.shuffle_with_seed(seed))
+    }
+
     pub async fn set_loop_mode_specific(&self, guild_id: GuildId, mode: LoopMode) -> Result<()> {
         let queue = self.inner.queue(guild_id);
         let mut q = queue.write();
@@ -286,10 +792,27 @@ impl AudioPlayer {
         // Persistir el ajuste para que se aplique también a las próximas canciones.
         self.inner.volumes.insert(guild_id, volume);
 
-        if let Some(track) = self.inner.current_tracks.get(&guild_id) {
-            if let Err(e) = track.set_volume(volume) {
-                warn!("Error estableciendo volumen: {:?}", e);
-                anyhow::bail!("Error al establecer volumen: {:?}", e);
+        // Cualquier cambio nuevo cancela la rampa que esté en curso
+        let counter = self.inner.volume_ramp_counter(guild_id);
+        let ramp_id = counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let track = self.inner.current_tracks.get(&guild_id).map(|t| t.clone());
+        if let Some(track) = track {
+            let current = match track.get_info().await {
+                Ok(info) if self.inner.smooth_volume => Some(info.volume),
+                _ => None,
+            };
+
+            match current {
+                Some(from) => {
+                    tokio::spawn(ramp_volume(track, from, volume, counter, ramp_id));
+                }
+                None => {
+                    if let Err(e) = track.set_volume(volume) {
+                        warn!("Error estableciendo volumen: {:?}", e);
+                        anyhow::bail!("Error al establecer volumen: {:?}", e);
+                    }
+                }
             }
         }
         info!("🔊 Volumen ajustado a {:.1}% en guild {}", volume * 100.0, guild_id);
@@ -317,26 +840,322 @@ impl AudioPlayer {
         Ok(())
     }
 
+    /// Aplica ganancias personalizadas por banda (activa el preset `Custom`)
+    pub async fn apply_custom_equalizer(&self, guild_id: GuildId, bands: [f32; 10]) -> Result<()> {
+        self.inner.effects.apply_custom_bands(guild_id, bands);
+        Ok(())
+    }
+
+    /// Cambia la ganancia de una banda del ecualizador personalizado y devuelve
+    /// las 10 bandas resultantes. Se aplica desde el próximo input; ver
+    /// [`rebuild_current_input`](Self::rebuild_current_input).
+    pub async fn set_equalizer_band(&self, guild_id: GuildId, band_hz: u32, gain_db: f32) -> Result<[f32; 10]> {
+        self.inner.effects.set_single_band(guild_id, band_hz, gain_db)?;
+        Ok(self.inner.effects.state(guild_id).custom_bands.unwrap_or_default())
+    }
+
     #[allow(dead_code)]
     pub async fn reset_equalizer(&self, guild_id: GuildId) -> Result<()> {
         self.inner.effects.reset_equalizer(guild_id);
         Ok(())
     }
 
+    /// Cambia la velocidad (`atempo`) de la guild y devuelve la anterior. Se
+    /// aplica desde el próximo input; ver [`rebuild_current_input_scaled`](Self::rebuild_current_input_scaled).
+    pub fn set_tempo(&self, guild_id: GuildId, tempo: f32) -> f32 {
+        self.inner.effects.set_tempo(guild_id, tempo)
+    }
+
     pub fn get_equalizer_details(&self, guild_id: GuildId) -> String {
         self.inner.effects.get_equalizer_details(guild_id)
     }
 
+    /// Estado del reproductor de la guild para diagnóstico.
+    pub async fn debug_state(&self, guild_id: GuildId) -> PlayerDebugState {
+        let track_info = match self.inner.current_tracks.get(&guild_id).map(|t| t.clone()) {
+            Some(track) => track.get_info().await.ok(),
+            None => None,
+        };
+        let (queue_len, loop_mode, shuffle, current) = {
+            let queue = self.inner.queue(guild_id);
+            let q = queue.read();
+            let info = q.get_info();
+            (info.items.len(), info.loop_mode, info.shuffle, q.current_track())
+        };
+
+        let effects = self.inner.effects.state(guild_id);
+        PlayerDebugState {
+            is_playing: self.inner.is_occupied(guild_id).await,
+            play_mode: track_info.as_ref().map(|info| format!("{:?}", info.playing)),
+            current,
+            position: track_info.as_ref().map(|info| info.position.mul_f32(effects.tempo)),
+            queue_len,
+            loop_mode,
+            shuffle,
+            volume: track_info
+                .map(|info| info.volume)
+                .unwrap_or_else(|| self.inner.effective_volume(guild_id)),
+            effects,
+            generation: self.inner.generation(guild_id).load(Ordering::Acquire),
+        }
+    }
+
+    /// Estado completo de los efectos de audio de la guild.
+    pub fn get_effects_state(&self, guild_id: GuildId) -> EffectsState {
+        self.inner.effects.state(guild_id)
+    }
+
+    /// Restablece todos los efectos de la guild. Se aplican desde el próximo
+    /// input; usar [`rebuild_current_input`](Self::rebuild_current_input) para
+    /// oírlo ya en la pista actual.
+    pub fn clear_effects(&self, guild_id: GuildId) {
+        self.inner.effects.clear(guild_id);
+    }
+
     pub async fn clear_duplicates(&self, guild_id: GuildId) -> Result<usize> {
         let queue = self.inner.queue(guild_id);
         let mut q = queue.write();
-        Ok(q.clear_duplicates())
+        let snapshot = q.snapshot();
+        let removed = q.clear_duplicates();
+        let removed_tracks = q.removed_since(snapshot);
+        drop(q);
+        self.push_undo(guild_id, "/clear duplicates", removed_tracks);
+        self.inner.emit_queue_updated(guild_id);
+        Ok(removed)
     }
 
     pub async fn clear_user_tracks(&self, guild_id: GuildId, user_id: UserId) -> Result<usize> {
         let queue = self.inner.queue(guild_id);
         let mut q = queue.write();
-        Ok(q.clear_user_tracks(user_id))
+        let snapshot = q.snapshot();
+        let removed = q.clear_user_tracks(user_id);
+        let removed_tracks = q.removed_since(snapshot);
+        drop(q);
+        self.push_undo(guild_id, "/clear user", removed_tracks);
+        self.inner.emit_queue_updated(guild_id);
+        Ok(removed)
+    }
+
+    /// Quita la canción pendiente en `position` (1 = la siguiente).
+    pub async fn remove_queued(&self, guild_id: GuildId, position: usize) -> Result<()> {
+        let queue = self.inner.queue(guild_id);
+        let mut q = queue.write();
+        let queue_len = q.len();
+        if position == 0 || position > queue_len {
+            anyhow::bail!("Posición {} fuera de rango (1-{})", position, queue_len);
+        }
+        let snapshot = q.snapshot();
+        q.remove_track(position - 1)?;
+        let removed = q.removed_since(snapshot);
+        drop(q);
+        self.push_undo(guild_id, "/remove", removed);
+        self.inner.emit_queue_updated(guild_id);
+        Ok(())
+    }
+
+    /// Guarda las canciones que quitó un cambio destructivo; con más de
+    /// [`UNDO_DEPTH`] se olvida el más viejo. Un cambio que no quitó nada no se guarda.
+    fn push_undo(&self, guild_id: GuildId, operation: &'static str, removed: RemovedTracks) {
+        if removed.is_empty() {
+            return;
+        }
+        let mut stack = self.inner.undo_stacks.entry(guild_id).or_default();
+        if stack.len() >= UNDO_DEPTH {
+            stack.pop_front();
+        }
+        stack.push_back(UndoEntry { operation, removed });
+    }
+
+    /// Deshace el último cambio destructivo de la cola (`/undo`): vuelve a poner
+    /// las canciones que quitó en su posición. Lo agregado o reproducido después
+    /// se queda como está, y no toca la canción que suena ni su posición. Si la
+    /// cola se llenó mientras tanto, solo vuelven las que caben.
+    pub async fn undo(&self, guild_id: GuildId) -> Option<(UndoEntry, RestoreOutcome)> {
+        let entry = self.inner.undo_stacks.get_mut(&guild_id)?.pop_back()?;
+        self.inner.cancel_prefetch(guild_id);
+        let outcome = self.inner.queue(guild_id).write().restore_removed(entry.removed.clone());
+        self.inner.emit_queue_updated(guild_id);
+        info!("↩️ Deshecho {} en guild {}", entry.operation, guild_id);
+        Some((entry, outcome))
+    }
+
+    /// `true` si la canción sigue pendiente en la cola del guild.
+    pub fn is_queued(&self, guild_id: GuildId, url: &str) -> bool {
+        self.inner.queue(guild_id).read().contains_url(url)
+    }
+
+    /// Completa en su lugar la metadata de las canciones pendientes con esa URL.
+    pub fn enrich_queued(&self, guild_id: GuildId, enriched: &TrackSource) -> usize {
+        self.inner.queue(guild_id).write().enrich_track(enriched)
+    }
+
+    /// Canciones ya reproducidas en el guild, sin repetir URLs ni radios (más reciente primero).
+    pub fn history(&self, guild_id: GuildId) -> Vec<TrackSource> {
+        let queue = self.inner.queue(guild_id);
+        let q = queue.read();
+        let mut seen = std::collections::HashSet::new();
+        q.get_history()
+            .into_iter()
+            .rev()
+            .filter(|item| !item.source.is_radio() && seen.insert(item.url.clone()))
+            .map(|item| item.source.clone())
+            .collect()
+    }
+
+    /// Guilds que tienen una cola en memoria.
+    pub fn active_guilds(&self) -> Vec<GuildId> {
+        self.inner.queues.iter().map(|entry| *entry.key()).collect()
+    }
+
+    /// Posición de reproducción de la pista actual, si hay una activa, en tiempo
+    /// de la canción: songbird cuenta tiempo del stream, ya acelerado por `atempo`.
+    pub async fn get_playback_position(&self, guild_id: GuildId) -> Option<Duration> {
+        let track = self.inner.current_tracks.get(&guild_id).map(|t| t.clone())?;
+        let tempo = self.inner.effects.state(guild_id).tempo;
+        track.get_info().await.ok().map(|info| info.position.mul_f32(tempo))
+    }
+
+    /// Salta a una posición (en tiempo de la canción) de la pista actual y
+    /// devuelve la alcanzada, también en tiempo de la canción.
+    ///
+    /// Las entradas por streaming (yt-dlp | ffmpeg) solo admiten saltos hacia
+    /// adelante; songbird devuelve error si se pide retroceder.
+    pub async fn seek(&self, guild_id: GuildId, position: Duration) -> Result<Duration> {
+        let track = self
+            .inner
+            .current_tracks
+            .get(&guild_id)
+            .map(|t| t.clone())
+            .ok_or_else(|| anyhow::anyhow!("No hay nada reproduciéndose"))?;
+
+        let tempo = self.inner.effects.state(guild_id).tempo;
+        let reached = track
+            .seek_async(position.div_f32(tempo))
+            .await
+            .map_err(|e| anyhow::anyhow!("No se pudo saltar a {:?}: {}", position, e))?
+            .mul_f32(tempo);
+        info!("⏩ Seek a {:?} en guild {}", reached, guild_id);
+        Ok(reached)
+    }
+
+    /// Repite la sección `ab_loop` de la pista actual hasta que se quite o cambie
+    /// la canción. Si la pista está fuera de la sección, salta a su inicio.
+    pub fn set_ab_loop(&self, guild_id: GuildId, ab_loop: AbLoop) -> Result<()> {
+        if !self.inner.current_sources.contains_key(&guild_id) {
+            anyhow::bail!("No hay nada reproduciéndose");
+        }
+        self.inner.ab_loops.insert(guild_id, ab_loop);
+        info!("🔁 Sección A-B {:?} → {:?} en guild {}", ab_loop.start, ab_loop.end, guild_id);
+        Ok(())
+    }
+
+    /// Quita la sección A-B; devuelve la que estaba activa.
+    pub fn clear_ab_loop(&self, guild_id: GuildId) -> Option<AbLoop> {
+        self.inner.ab_loops.remove(&guild_id).map(|(_, ab_loop)| ab_loop)
+    }
+
+    /// Sección A-B activa en la pista actual.
+    pub fn ab_loop(&self, guild_id: GuildId) -> Option<AbLoop> {
+        self.inner.ab_loops.get(&guild_id).map(|ab_loop| *ab_loop)
+    }
+
+    /// Re-crea el input de la pista actual y la retoma desde la última
+    /// posición conocida.
+    ///
+    /// Se usa tras una reconexión del driver de voz (el stream de yt-dlp | ffmpeg
+    /// queda cortado y la pista se detiene o vuelve a empezar) y al cambiar los
+    /// efectos, que solo se aplican al abrir el stream. Devuelve `false` si no
+    /// había nada que retomar.
+    pub async fn rebuild_current_input(&self, guild_id: GuildId, handler: Arc<Mutex<Call>>) -> Result<bool> {
+        self.rebuild_current_input_scaled(guild_id, handler, 1.0).await
+    }
+
+    /// Como [`rebuild_current_input`](Self::rebuild_current_input), multiplicando
+    /// la posición a retomar por `position_scale`.
+    ///
+    /// Con `atempo` la posición de songbird cuenta tiempo del stream ya acelerado:
+    /// al cambiar de velocidad se pasa `tempo_anterior / tempo_nuevo` para caer en
+    /// el mismo punto de la canción.
+    pub async fn rebuild_current_input_scaled(
+        &self,
+        guild_id: GuildId,
+        handler: Arc<Mutex<Call>>,
+        position_scale: f64,
+    ) -> Result<bool> {
+        let lock = self.inner.advance_lock(guild_id);
+        let _guard = lock.lock().await;
+
+        let Some(source) = self.inner.current_sources.get(&guild_id).map(|s| s.clone()) else {
+            return Ok(false);
+        };
+
+        let previous = self.inner.current_tracks.get(&guild_id).map(|t| t.clone());
+        let mut saved = self.inner.positions.get(&guild_id).map(|p| *p).unwrap_or_default();
+        let mut paused = false;
+        if let Some(info) = match previous {
+            Some(track) => track.get_info().await.ok(),
+            None => None,
+        } {
+            paused = matches!(info.playing, PlayMode::Pause);
+            saved = saved.max(info.position);
+        }
+
+        let saved = saved.mul_f64(position_scale);
+        // Duración del stream a la velocidad actual
+        let tempo = self.inner.effects.state(guild_id).tempo;
+        let total = source.duration().map(|duration| duration.div_f32(tempo));
+
+        Self::start_track(&self.inner, guild_id, source.clone(), &handler).await?;
+        let track = self
+            .inner
+            .current_tracks
+            .get(&guild_id)
+            .map(|t| t.clone())
+            .ok_or_else(|| anyhow::anyhow!("La pista no se pudo re-crear"))?;
+
+        if let Some(position) = resume_point(saved, total) {
+            match track.seek_async(position).await {
+                Ok(reached) => {
+                    self.inner.positions.insert(guild_id, reached);
+                    info!("🔄 Reproducción retomada en {:?} (guild {})", reached, guild_id);
+                }
+                Err(e) => warn!("No se pudo retomar en {:?} (guild {}): {}", position, guild_id, e),
+            }
+        }
+        if paused {
+            let _ = track.pause();
+        }
+        Ok(true)
+    }
+
+    /// Cuenta un reinicio de la pista actual y devuelve cuántos lleva seguidos.
+    ///
+    /// Falla a partir del reinicio [`MAX_PLAYBACK_RESTARTS`] + 1, para que una pista
+    /// que se cuelga siempre no quede en bucle. El contador vuelve a cero al cambiar
+    /// de pista.
+    pub fn register_restart(&self, guild_id: GuildId) -> Result<u32, TooManyRestartsError> {
+        let mut restarts = self.inner.restarts.entry(guild_id).or_insert(0);
+        if *restarts >= MAX_PLAYBACK_RESTARTS {
+            return Err(TooManyRestartsError(*restarts));
+        }
+        *restarts += 1;
+        Ok(*restarts)
+    }
+
+    /// Recupera una pista atascada (`/restart-playback`): detiene el handle actual,
+    /// vuelve a abrir el input de la misma canción y la retoma desde la última
+    /// posición conocida.
+    ///
+    /// Devuelve el número de reinicios seguidos, o `None` si no había nada sonando.
+    pub async fn restart_playback(&self, guild_id: GuildId, handler: Arc<Mutex<Call>>) -> Result<Option<u32>> {
+        if !self.inner.current_sources.contains_key(&guild_id) {
+            return Ok(None);
+        }
+
+        let attempt = self.register_restart(guild_id)?;
+        info!("🩹 Reiniciando reproducción en guild {} (intento {}/{})", guild_id, attempt, MAX_PLAYBACK_RESTARTS);
+        let restarted = self.rebuild_current_input(guild_id, handler).await?;
+        Ok(restarted.then_some(attempt))
     }
 
     #[allow(dead_code)]
@@ -377,6 +1196,8 @@ impl AudioPlayer {
         guild_id: GuildId,
         handler: &Arc<Mutex<Call>>,
     ) {
+        // La sección A-B era de la pista que termina
+        inner.ab_loops.remove(&guild_id);
         for _ in 0..10 {
             let next = {
                 let queue = inner.queue(guild_id);
@@ -385,16 +1206,34 @@ impl AudioPlayer {
             };
 
             match next {
-                Some(source) => match Self::start_track(inner, guild_id, source, handler).await {
-                    Ok(()) => return,
+                Some(source) => match Self::start_track(inner, guild_id, source.clone(), handler).await {
+                    Ok(()) => {
+                        inner.restarts.remove(&guild_id);
+                        inner.emit(WsEvent::TrackStarted {
+                            guild_id,
+                            title: source.title().to_string(),
+                            url: source.url().to_string(),
+                            requested_by: source.requested_by(),
+                        });
+                        inner.emit_queue_updated(guild_id);
+                        // Sin suscriptores el envío falla y no pasa nada
+                        let _ = inner.track_starts.send(TrackStarted { guild_id, source });
+                        return;
+                    }
                     Err(e) => {
                         warn!("❌ Error reproduciendo track, saltando al siguiente: {:?}", e);
+                        record_error(ErrorCategory::Playback);
+                        inner.emit(WsEvent::Error {
+                            guild_id,
+                            category: "source".to_string(),
+                            message: e.to_string(),
+                        });
                         continue;
                     }
                 },
                 None => {
                     info!("📭 Cola vacía en guild {}", guild_id);
-                    inner.current_tracks.remove(&guild_id);
+                    inner.forget_track(guild_id);
                     Self::schedule_auto_leave(inner.clone(), guild_id, handler.clone());
                     return;
                 }
@@ -402,12 +1241,17 @@ impl AudioPlayer {
         }
 
         warn!("⚠️ Demasiados errores consecutivos en guild {}, deteniendo", guild_id);
-        inner.current_tracks.remove(&guild_id);
+        inner.forget_track(guild_id);
         Self::schedule_auto_leave(inner.clone(), guild_id, handler.clone());
     }
 
     /// Reproduce una fuente concreta deteniendo antes la pista anterior, y
     /// registra los handlers de fin/error con la generación vigente.
+    #[tracing::instrument(
+        name = "player.start_track",
+        skip_all,
+        fields(guild_id = %guild_id, track_url = %source.url(), source_type = source.source_type().as_str())
+    )]
     async fn start_track(
         inner: &Arc<PlayerInner>,
         guild_id: GuildId,
@@ -426,11 +1270,23 @@ impl AudioPlayer {
         }
 
         info!("▶️ Iniciando reproducción de: {}", source.title());
-        let filter = inner.effects.build_filter(guild_id);
-        let input = source
-            .get_input(&filter)
-            .await
-            .map_err(|e| anyhow::anyhow!("Error obteniendo input: {:?}", e))?;
+        let filter = inner.track_filter(guild_id, &source);
+        let silence_limit = inner.silence_limits.get(&guild_id).map(|limit| *limit);
+        let (input, monitor) = match inner.take_prefetched(guild_id, &source.url(), &filter, silence_limit.is_some()) {
+            Some(prefetched) => {
+                info!("⚡ Usando input precargado para: {}", source.title());
+                (prefetched.input.into_inner(), prefetched.silence)
+            }
+            None => {
+                let monitor = silence_limit.map(|_| Arc::new(SilenceMonitor::default()));
+                let input = source
+                    .get_input(&filter, monitor.clone(), inner.ytdlp_proxy(guild_id).as_deref())
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Error obteniendo input: {:?}", e))?;
+                (input, monitor)
+            }
+        };
+        let silence = silence_limit.zip(monitor);
 
         let track_handle = {
             let mut call = handler.lock().await;
@@ -459,12 +1315,265 @@ impl AudioPlayer {
         track_handle
             .add_event(Event::Track(TrackEvent::Error), error_handler)
             .ok();
+        track_handle
+            .add_event(
+                Event::Periodic(POSITION_SAMPLE_INTERVAL, None),
+                PositionTracker {
+                    guild_id,
+                    generation: new_gen,
+                    inner: inner.clone(),
+                },
+            )
+            .ok();
+        track_handle
+            .add_event(
+                Event::Periodic(AB_LOOP_CHECK_INTERVAL, None),
+                AbLoopWatcher {
+                    guild_id,
+                    generation: new_gen,
+                    inner: inner.clone(),
+                    handler: handler.clone(),
+                },
+            )
+            .ok();
 
         inner.current_tracks.insert(guild_id, track_handle);
+        inner.positions.insert(guild_id, Duration::ZERO);
+        inner.current_sources.insert(guild_id, source.clone());
+        inner.stream_titles.remove(&guild_id);
+        if source.is_radio() {
+            tokio::spawn(Self::watch_stream_title(inner.clone(), guild_id, new_gen, source.url()));
+        }
+        if let Some((limit, monitor)) = silence {
+            tokio::spawn(Self::watch_silence(inner.clone(), guild_id, new_gen, handler.clone(), monitor, limit));
+        }
+        Self::schedule_prefetch(inner, guild_id, new_gen, &source);
+        Self::schedule_loudness_measurement(inner, guild_id);
         info!("🎵 Reproduciendo: {} en guild {}", source.title(), guild_id);
         Ok(())
     }
 
+    /// Precarga en segundo plano el input de la siguiente pista de la cola, poco
+    /// antes de que termine `current`, para que el cambio sea inmediato.
+    ///
+    /// Si la cola cambia entretanto, `start_track` descarta la precarga (otra
+    /// URL) y obtiene el input como siempre.
+    fn schedule_prefetch(inner: &Arc<PlayerInner>, guild_id: GuildId, generation: u64, current: &TrackSource) {
+        inner.cancel_prefetch(guild_id);
+        let tempo = inner.effects.state(guild_id).tempo;
+        let Some(delay) = prefetch_delay(current.duration(), tempo) else {
+            return;
+        };
+
+        let task_inner = inner.clone();
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let inner = task_inner;
+            if inner.generation(guild_id).load(Ordering::Acquire) != generation {
+                return; // la pista ya cambió
+            }
+            let Some(next) = inner.queue(guild_id).read().peek_next() else {
+                return;
+            };
+            if next.is_radio() {
+                return; // un directo no se abre antes de tiempo
+            }
+
+            let filter = inner.track_filter(guild_id, &next);
+            let silence = inner
+                .silence_limits
+                .contains_key(&guild_id)
+                .then(|| Arc::new(SilenceMonitor::default()));
+            let proxy = inner.ytdlp_proxy(guild_id);
+            let input = match next.get_input(&filter, silence.clone(), proxy.as_deref()).await {
+                Ok(input) => input,
+                Err(e) => {
+                    warn!("Precarga de {} fallida, se cargará al llegar su turno: {:?}", next.title(), e);
+                    return;
+                }
+            };
+            if inner.generation(guild_id).load(Ordering::Acquire) != generation {
+                return;
+            }
+
+            let fetched_at = Instant::now();
+            inner.prefetched.insert(
+                guild_id,
+                PrefetchedInput { url: next.url(), filter, input: parking_lot::Mutex::new(input), silence, fetched_at },
+            );
+            debug!("⚡ Precargada {} (guild {})", next.title(), guild_id);
+
+            // No retener procesos parados más de lo que dura la URL del stream
+            tokio::time::sleep(PREFETCH_MAX_AGE).await;
+            inner.prefetched.remove_if(&guild_id, |_, prefetched| prefetched.fetched_at == fetched_at);
+        });
+        inner.prefetch_tasks.insert(guild_id, task);
+    }
+
+    /// Primera pasada de la normalización: mide en segundo plano la sonoridad
+    /// de la siguiente pista de la cola, para que al sonar ya tenga sus valores.
+    fn schedule_loudness_measurement(inner: &Arc<PlayerInner>, guild_id: GuildId) {
+        if !inner.loudness_normalization.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(next) = inner.queue(guild_id).read().peek_next() else {
+            return;
+        };
+        let url = next.url();
+        if next.is_radio() || inner.loudness.get(&url.to_string()).is_some() {
+            return;
+        }
+        if inner.loudness_pending.insert(url.clone(), ()).is_some() {
+            return; // ya se está midiendo
+        }
+
+        let inner = inner.clone();
+        tokio::spawn(async move {
+            let proxy = inner.ytdlp_proxy(guild_id);
+            match next.measure_loudness(proxy.as_deref()).await {
+                Ok(measurement) => {
+                    debug!(
+                        "🔉 Sonoridad de {}: {:.1} LUFS (guild {})",
+                        next.title(),
+                        measurement.input_i,
+                        guild_id
+                    );
+                    inner.loudness.insert_with_ttl(url.to_string(), measurement, None);
+                }
+                Err(e) => debug!("No se pudo medir la sonoridad de {}: {:?}", next.title(), e),
+            }
+            inner.loudness_pending.remove(&url);
+        });
+    }
+
+    /// Sigue la metadata ICY de una radio mientras su pista siga vigente, para
+    /// que "reproduciendo ahora" muestre la canción que suena en la emisora.
+    async fn watch_stream_title(inner: Arc<PlayerInner>, guild_id: GuildId, generation: u64, url: Arc<str>) {
+        loop {
+            tokio::time::sleep(STREAM_TITLE_POLL_INTERVAL).await;
+
+            let still_playing = inner.generation(guild_id).load(Ordering::Acquire) == generation
+                && inner.current_tracks.contains_key(&guild_id);
+            if !still_playing {
+                return; // la radio se detuvo o se saltó
+            }
+
+            let Some(title) = direct::current_stream_title(&url).await else {
+                continue;
+            };
+            // Se vuelve a comprobar: la consulta pudo tardar varios segundos
+            if inner.generation(guild_id).load(Ordering::Acquire) != generation {
+                return;
+            }
+            let changed = inner.stream_titles.get(&guild_id).is_none_or(|current| *current != title);
+            if changed {
+                info!("📻 Ahora en la radio (guild {}): {}", guild_id, title);
+                inner.stream_titles.insert(guild_id, title);
+            }
+        }
+    }
+
+    /// Detector de silencio de la pista: si suena en silencio más de `limit`
+    /// seguidos (streams corruptos de yt-dlp), la salta. Termina con la pista.
+    ///
+    /// Devuelve un `BoxFuture`: al saltar vuelve a `start_track`, que lo lanza de
+    /// nuevo, y un `async fn` recursivo no permitiría probar que es `Send`.
+    fn watch_silence(
+        inner: Arc<PlayerInner>,
+        guild_id: GuildId,
+        generation: u64,
+        handler: Arc<Mutex<Call>>,
+        monitor: Arc<SilenceMonitor>,
+        limit: Duration,
+    ) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            // Tiempo de silencio reproducido desde que ffmpeg lo reportó (en pausa no cuenta)
+            let mut silent_for = Duration::ZERO;
+            loop {
+                tokio::time::sleep(SILENCE_CHECK_INTERVAL).await;
+
+                if inner.generation(guild_id).load(Ordering::Acquire) != generation {
+                    return; // la pista terminó, se saltó o se re-creó
+                }
+                let playing = match inner.current_tracks.get(&guild_id).map(|t| t.clone()) {
+                    Some(track) => matches!(track.get_info().await.map(|info| info.playing), Ok(PlayMode::Play)),
+                    None => return,
+                };
+                if !playing || !monitor.is_silent() {
+                    silent_for = Duration::ZERO;
+                    continue;
+                }
+
+                silent_for += SILENCE_CHECK_INTERVAL;
+                // ffmpeg reporta el silencio cuando ya lleva `SILENCE_MIN_DURATION`
+                if SILENCE_MIN_DURATION + silent_for <= limit {
+                    continue;
+                }
+
+                let lock = inner.advance_lock(guild_id);
+                let _guard = lock.lock().await;
+                if inner.generation(guild_id).load(Ordering::Acquire) != generation {
+                    return;
+                }
+                warn!(
+                    "🔇 Pista en silencio durante más de {}s en guild {}, saltando",
+                    limit.as_secs(),
+                    guild_id
+                );
+                Self::advance_pop_and_start(&inner, guild_id, &handler).await;
+                return;
+            }
+        })
+    }
+
+    /// Devuelve la pista al inicio de su sección A-B (`start`, tiempo del stream).
+    ///
+    /// Las entradas por streaming no retroceden: si el salto falla, se re-abre
+    /// el input y se avanza hasta `start` en el nuevo, como al reconectar.
+    async fn rewind_ab_loop(
+        inner: Arc<PlayerInner>,
+        guild_id: GuildId,
+        generation: u64,
+        track: TrackHandle,
+        handler: Arc<Mutex<Call>>,
+        start: Duration,
+    ) {
+        if track.seek_async(start).await.is_ok() {
+            // El watcher se canceló al disparar; sigue vigilando la misma pista
+            let watcher = AbLoopWatcher { guild_id, generation, inner, handler };
+            track.add_event(Event::Periodic(AB_LOOP_CHECK_INTERVAL, None), watcher).ok();
+            return;
+        }
+
+        let lock = inner.advance_lock(guild_id);
+        let _guard = lock.lock().await;
+        if inner.generation(guild_id).load(Ordering::Acquire) != generation || !inner.ab_loops.contains_key(&guild_id) {
+            return; // la pista cambió o se quitó la sección mientras tanto
+        }
+        let Some(source) = inner.current_sources.get(&guild_id).map(|s| s.clone()) else {
+            return;
+        };
+        let paused = matches!(track.get_info().await.map(|info| info.playing), Ok(PlayMode::Pause));
+
+        if let Err(e) = Self::start_track(&inner, guild_id, source, &handler).await {
+            warn!("No se pudo repetir la sección A-B en guild {}: {:?}", guild_id, e);
+            return;
+        }
+        let Some(track) = inner.current_tracks.get(&guild_id).map(|t| t.clone()) else {
+            return;
+        };
+        match track.seek_async(start).await {
+            Ok(reached) => {
+                inner.positions.insert(guild_id, reached);
+                debug!("🔁 Sección A-B reiniciada en {:?} (guild {})", reached, guild_id);
+            }
+            Err(e) => warn!("No se pudo volver al inicio de la sección A-B (guild {}): {}", guild_id, e),
+        }
+        if paused {
+            let _ = track.pause();
+        }
+    }
+
     /// Programa la desconexión del canal de voz tras un periodo de gracia,
     /// siempre que siga sin haber pista ni cola.
     fn schedule_auto_leave(inner: Arc<PlayerInner>, guild_id: GuildId, handler: Arc<Mutex<Call>>) {
@@ -485,13 +1594,32 @@ impl AudioPlayer {
             let mut call = handler.lock().await;
             if let Err(e) = call.leave().await {
                 warn!("Error al auto-desconectar en guild {}: {:?}", guild_id, e);
+                record_error(ErrorCategory::Voice);
             } else {
+                inner.emit(WsEvent::BotLeftVoice { guild_id });
                 info!("👋 Auto-desconectado: cola finalizada (guild {})", guild_id);
             }
         });
     }
 }
 
+/// Lleva el volumen de la pista de `from` a `to` en pasos pequeños durante
+/// [`VOLUME_RAMP_DURATION`]. Se detiene si llega otro cambio de volumen.
+async fn ramp_volume(track: TrackHandle, from: f32, to: f32, counter: Arc<AtomicU64>, ramp_id: u64) {
+    let step_delay = VOLUME_RAMP_DURATION / VOLUME_RAMP_STEPS as u32;
+
+    for value in volume_ramp(from, to, VOLUME_RAMP_STEPS) {
+        if counter.load(Ordering::SeqCst) != ramp_id {
+            return; // un cambio más reciente tomó el control
+        }
+        if let Err(e) = track.set_volume(value) {
+            warn!("Rampa de volumen interrumpida: {:?}", e);
+            return;
+        }
+        tokio::time::sleep(step_delay).await;
+    }
+}
+
 /// Handler de fin de pista. Sólo avanza si su generación sigue vigente.
 struct TrackEndHandler {
     guild_id: GuildId,
@@ -502,7 +1630,7 @@ struct TrackEndHandler {
 
 #[async_trait::async_trait]
 impl SongbirdEventHandler for TrackEndHandler {
-    async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
         let current_gen = self.inner.generation(self.guild_id).load(Ordering::Acquire);
         if current_gen != self.generation {
             // La pista fue detenida/saltada manualmente: este fin es obsoleto.
@@ -510,6 +1638,20 @@ impl SongbirdEventHandler for TrackEndHandler {
         }
 
         info!("🎵 Track terminó en guild {}, avanzando al siguiente...", self.guild_id);
+        if let Some(source) = self.inner.current_sources.get(&self.guild_id).map(|source| source.clone()) {
+            self.inner.emit(WsEvent::TrackEnded {
+                guild_id: self.guild_id,
+                title: source.title().to_string(),
+                url: source.url().to_string(),
+                requested_by: source.requested_by(),
+            });
+            let played = match ctx {
+                EventContext::Track(tracks) => tracks.first().map(|(state, _)| state.play_time),
+                _ => None,
+            };
+            let played = played.or_else(|| source.duration()).unwrap_or_default();
+            let _ = self.inner.track_finishes.send(TrackFinished { guild_id: self.guild_id, source, played });
+        }
         AudioPlayer::force_advance(&self.inner, self.guild_id, self.handler.clone()).await;
         None
     }
@@ -527,13 +1669,92 @@ struct TrackErrorHandler {
 impl SongbirdEventHandler for TrackErrorHandler {
     async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
         tracing::error!("❌ Error en track para guild {}: {:?}", self.guild_id, ctx);
+        record_error(ErrorCategory::Playback);
 
         let current_gen = self.inner.generation(self.guild_id).load(Ordering::Acquire);
         if current_gen != self.generation {
             return None;
         }
 
+        let message = match ctx {
+            EventContext::Track(tracks) => match tracks.first().map(|(state, _)| &state.playing) {
+                Some(PlayMode::Errored(e)) => e.to_string(),
+                _ => "error de reproducción".to_string(),
+            },
+            _ => "error de reproducción".to_string(),
+        };
+        self.inner.emit(WsEvent::Error {
+            guild_id: self.guild_id,
+            category: "playback".to_string(),
+            message,
+        });
+
         AudioPlayer::force_advance(&self.inner, self.guild_id, self.handler.clone()).await;
         None
     }
 }
+
+/// Guarda periódicamente la posición de la pista para poder retomarla tras
+/// una reconexión. Misma guarda de generación que los demás handlers.
+struct PositionTracker {
+    guild_id: GuildId,
+    generation: u64,
+    inner: Arc<PlayerInner>,
+}
+
+/// Vigila la sección A-B de la pista (`/ab-loop`): al salir de ella, la
+/// devuelve a su inicio. Sin sección activa no hace nada.
+struct AbLoopWatcher {
+    guild_id: GuildId,
+    generation: u64,
+    inner: Arc<PlayerInner>,
+    handler: Arc<Mutex<Call>>,
+}
+
+#[async_trait::async_trait]
+impl SongbirdEventHandler for AbLoopWatcher {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.inner.generation(self.guild_id).load(Ordering::Acquire) != self.generation {
+            return Some(Event::Cancel);
+        }
+        let ab_loop = self.inner.ab_loops.get(&self.guild_id).map(|ab_loop| *ab_loop)?;
+        let EventContext::Track(tracks) = ctx else {
+            return None;
+        };
+        let (state, track) = tracks.first()?;
+
+        // La posición de songbird es tiempo del stream, ya acelerado por `atempo`
+        let tempo = self.inner.effects.state(self.guild_id).tempo;
+        if ab_loop.contains(state.position.mul_f32(tempo)) {
+            return None;
+        }
+
+        // Se cancela mientras salta; `rewind_ab_loop` lo vuelve a registrar
+        tokio::spawn(AudioPlayer::rewind_ab_loop(
+            self.inner.clone(),
+            self.guild_id,
+            self.generation,
+            (*track).clone(),
+            self.handler.clone(),
+            ab_loop.start.div_f32(tempo),
+        ));
+        Some(Event::Cancel)
+    }
+}
+
+#[async_trait::async_trait]
+impl SongbirdEventHandler for PositionTracker {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let current_gen = self.inner.generation(self.guild_id).load(Ordering::Acquire);
+        if current_gen != self.generation {
+            return None;
+        }
+
+        if let EventContext::Track(tracks) = ctx {
+            if let Some((state, _)) = tracks.first() {
+                self.inner.positions.insert(self.guild_id, state.position);
+            }
+        }
+        None
+    }
+}
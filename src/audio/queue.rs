@@ -1,24 +1,28 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 use serenity::model::id::UserId;
-use std::{collections::VecDeque, time::Duration};
-use tracing::{debug, info};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+use tracing::{debug, info, warn};
 
 use crate::sources::TrackSource;
 
 #[derive(Debug, Clone)]
 pub struct QueueItem {
     pub source: TrackSource,
-    pub title: String,
-    pub artist: Option<String>,
+    /// Comparten memoria con `source` (`Arc<str>`), clonar el item no copia textos
+    pub title: Arc<str>,
+    pub artist: Option<Arc<str>>,
     pub duration: Option<Duration>,
     #[allow(dead_code)]
-    pub thumbnail: Option<String>,
-    pub url: String,
+    pub thumbnail: Option<Arc<str>>,
+    pub url: Arc<str>,
     pub requested_by: UserId,
     #[allow(dead_code)]
     pub added_at: DateTime<Utc>,
+    /// Colocado en una posición concreta con `/insert`: el modo aleatorio la respeta
+    pub pinned: bool,
 }
 
 impl From<TrackSource> for QueueItem {
@@ -31,16 +35,136 @@ impl From<TrackSource> for QueueItem {
             url: source.url(),
             requested_by: source.requested_by(),
             added_at: Utc::now(),
+            pinned: false,
             source,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl QueueItem {
+    /// `true` si ambos son la misma entrada de la cola (o una copia suya), no
+    /// solo la misma canción pedida dos veces
+    fn is_same_entry(&self, other: &QueueItem) -> bool {
+        Arc::ptr_eq(&self.url, &other.url)
+            && self.added_at == other.added_at
+            && self.requested_by == other.requested_by
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub enum LoopMode {
+    #[default]
     Off,
     Track,
     Queue,
+    /// Repite la canción actual N veces más y luego sigue con la cola
+    TrackCount(u32),
+}
+
+/// Criterio de `/queue-sort` para reordenar las canciones pendientes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Title,
+    Artist,
+    DurationAsc,
+    DurationDesc,
+    Requester,
+    Shuffle,
+}
+
+impl SortField {
+    /// Interpreta el valor de la opción `by` del comando
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "title" => Some(Self::Title),
+            "artist" => Some(Self::Artist),
+            "duration-asc" => Some(Self::DurationAsc),
+            "duration-desc" => Some(Self::DurationDesc),
+            "requester" => Some(Self::Requester),
+            "shuffle" => Some(Self::Shuffle),
+            _ => None,
+        }
+    }
+
+    /// Nombre legible del criterio para los embeds
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Title => "Título (A-Z)",
+            Self::Artist => "Artista (A-Z)",
+            Self::DurationAsc => "Duración (más cortas primero)",
+            Self::DurationDesc => "Duración (más largas primero)",
+            Self::Requester => "Solicitante",
+            Self::Shuffle => "Aleatorio",
+        }
+    }
+}
+
+/// Criterio de orden de `/queue sort`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Duration,
+    Title,
+    Requester,
+}
+
+impl SortKey {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "duration" => Some(Self::Duration),
+            "title" => Some(Self::Title),
+            "requester" => Some(Self::Requester),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Duration => "duración",
+            Self::Title => "título",
+            Self::Requester => "solicitante",
+        }
+    }
+}
+
+/// Sentido del orden de `/queue sort`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Copia de las canciones pendientes antes de un cambio, para saber qué quitó
+/// (ver [`MusicQueue::removed_since`])
+#[derive(Debug, Clone)]
+pub struct QueueSnapshot {
+    items: VecDeque<QueueItem>,
+}
+
+/// Canciones que quitó un cambio destructivo, cada una con la que la precedía
+/// en la cola (`None` si iba primera), para deshacerlo con `/undo`
+#[derive(Debug, Clone, Default)]
+pub struct RemovedTracks {
+    tracks: Vec<(Option<QueueItem>, QueueItem)>,
+}
+
+impl RemovedTracks {
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+}
+
+/// Resultado de [`MusicQueue::restore_removed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreOutcome {
+    /// Canciones que volvieron a la cola
+    pub restored: usize,
+    /// Canciones que no volvieron porque la cola se llenó
+    pub dropped: usize,
 }
 
 #[derive(Debug)]
@@ -49,7 +173,12 @@ pub struct MusicQueue {
     current: Option<QueueItem>,
     history: Vec<QueueItem>,
     loop_mode: LoopMode,
+    /// Veces que la canción actual volvió a sonar por loop; se reinicia al
+    /// cambiar de canción o al desactivar la repetición
+    loop_count: u32,
     shuffle: bool,
+    /// Semilla de la última mezcla determinista (`/shuffle seed:<n>`)
+    shuffle_seed: Option<u64>,
     max_size: usize,
     max_history: usize,
 }
@@ -61,7 +190,9 @@ impl MusicQueue {
             current: None,
             history: Vec::new(),
             loop_mode: LoopMode::Off,
+            loop_count: 0,
             shuffle: false,
+            shuffle_seed: None,
             max_size,
             max_history: 50,
         }
@@ -80,6 +211,26 @@ impl MusicQueue {
         Ok(())
     }
 
+    /// Inserta un track en `index` (0 = siguiente en sonar; `len()` = al final).
+    ///
+    /// El track queda fijado en esa posición: con el modo aleatorio activo
+    /// suenan antes, en orden aleatorio, solo las canciones que tiene delante.
+    pub fn insert_at(&mut self, index: usize, source: TrackSource) -> Result<()> {
+        if self.items.len() >= self.max_size {
+            anyhow::bail!("La cola está llena (máximo {} canciones)", self.max_size);
+        }
+        if index > self.items.len() {
+            anyhow::bail!("Posición {} fuera de rango (1-{})", index + 1, self.items.len() + 1);
+        }
+
+        let mut item = QueueItem::from(source);
+        item.pinned = true;
+        info!("📍 Insertado en la posición {}: {}", index + 1, item.title);
+        self.items.insert(index, item);
+
+        Ok(())
+    }
+
     /// Agrega múltiples tracks (playlist)
     #[allow(dead_code)]
     pub fn add_playlist(&mut self, sources: Vec<TrackSource>) -> Result<usize> {
@@ -104,22 +255,45 @@ impl MusicQueue {
             // Si está en modo loop track, devolver el mismo
             if self.loop_mode == LoopMode::Track {
                 self.current = Some(current.clone());
+                self.loop_count += 1;
                 info!("🔂 Repitiendo track: {}", current.title);
                 return Some(current.source);
             }
+
+            // Repeticiones contadas: re-servir la actual hasta agotar el contador
+            if let LoopMode::TrackCount(remaining) = self.loop_mode {
+                if remaining > 0 {
+                    self.loop_mode = match remaining - 1 {
+                        0 => LoopMode::Off,
+                        left => LoopMode::TrackCount(left),
+                    };
+                    self.current = Some(current.clone());
+                    self.loop_count += 1;
+                    info!("🔂 Repitiendo track ({} restantes): {}", remaining - 1, current.title);
+                    return Some(current.source);
+                }
+                self.loop_mode = LoopMode::Off;
+            }
         }
 
         // Obtener siguiente de la cola - SIEMPRE en orden FIFO a menos que shuffle esté activo
         let next = if self.shuffle && !self.items.is_empty() {
-            // Modo shuffle: elegir aleatorio
+            // Modo shuffle: elegir aleatorio, pero solo entre las canciones
+            // anteriores a la primera fijada con `/insert`
+            let candidates = self
+                .items
+                .iter()
+                .position(|item| item.pinned)
+                .unwrap_or(self.items.len())
+                .max(1);
             let mut rng = rand::thread_rng();
-            let index = (0..self.items.len())
+            let index = (0..candidates)
                 .collect::<Vec<_>>()
                 .choose(&mut rng)
                 .copied()
                 .unwrap_or(0);
             let selected = self.items.remove(index);
-            info!("🔀 Seleccionado aleatoriamente: {}", selected.as_ref().map(|s| s.title.as_str()).unwrap_or("Unknown"));
+            info!("🔀 Seleccionado aleatoriamente: {}", selected.as_ref().map(|s| &*s.title).unwrap_or("Unknown"));
             selected
         } else {
             // Modo normal: ESTRICTO FIFO - primero en entrar, primero en salir
@@ -138,6 +312,7 @@ impl MusicQueue {
             }
 
             self.current = Some(next_item.clone());
+            self.loop_count = 0;
             Some(next_item.source)
         } else {
             info!("📭 Cola vacía, no hay siguiente track");
@@ -145,9 +320,28 @@ impl MusicQueue {
         }
     }
 
-    /// Salta canciones
+    /// Pista que devolverá `next_track`, si se puede saber de antemano. Con
+    /// shuffle la elección es aleatoria, así que no se adelanta.
+    pub fn peek_next(&self) -> Option<TrackSource> {
+        if let Some(current) = &self.current {
+            if matches!(self.loop_mode, LoopMode::Track | LoopMode::TrackCount(1..)) {
+                return Some(current.source.clone());
+            }
+        }
+        if self.shuffle {
+            return None;
+        }
+        self.items.front().map(|item| item.source.clone())
+    }
+
+    /// Salta canciones. Saltar cancela las repeticiones contadas pendientes.
     #[allow(dead_code)]
     pub fn skip(&mut self, amount: usize) -> usize {
+        if let LoopMode::TrackCount(remaining) = self.loop_mode {
+            info!("⏭️ Salto: se cancelan {} repeticiones pendientes", remaining);
+            self.loop_mode = LoopMode::Off;
+        }
+
         let skipped = amount.min(self.items.len());
 
         for _ in 0..skipped {
@@ -162,6 +356,7 @@ impl MusicQueue {
     /// Limpia la cola
     pub fn clear(&mut self) {
         self.items.clear();
+        self.shuffle_seed = None;
         info!("🗑️ Cola limpiada");
     }
 
@@ -193,14 +388,84 @@ impl MusicQueue {
         removed
     }
 
-    /// Mezcla la cola
-    #[allow(dead_code)]
-    pub fn shuffle_queue(&mut self) {
-        let mut items: Vec<_> = self.items.drain(..).collect();
+    /// Mezcla una sola vez las canciones pendientes (Fisher–Yates in-place).
+    ///
+    /// A diferencia del modo aleatorio, reordena la cola visible y no toca la
+    /// canción actual. Retorna el número de canciones mezcladas.
+    pub fn shuffle_once(&mut self) -> usize {
         let mut rng = rand::thread_rng();
-        items.shuffle(&mut rng);
-        self.items.extend(items);
-        info!("🔀 Cola mezclada");
+        self.items.make_contiguous().shuffle(&mut rng);
+        self.unpin_all();
+        self.shuffle_seed = None;
+        info!("🔀 Cola mezclada ({} canciones)", self.items.len());
+        self.items.len()
+    }
+
+    /// Mezcla una vez las canciones pendientes con una semilla fija.
+    ///
+    /// La misma semilla sobre la misma cola produce siempre el mismo orden, así
+    /// varios usuarios pueden compartir la semilla y escuchar igual.
+    pub fn shuffle_with_seed(&mut self, seed: u64) -> usize {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        rng.shuffle(self.items.make_contiguous());
+        self.unpin_all();
+        self.shuffle_seed = Some(seed);
+        info!("🔀 Cola mezclada con semilla {} ({} canciones)", seed, self.items.len());
+        self.items.len()
+    }
+
+    /// Reordena las canciones pendientes según `field` (la actual no se mueve).
+    ///
+    /// La ordenación es estable; las canciones sin duración o sin artista van al
+    /// final. `Shuffle` mezcla una vez sin activar el modo aleatorio. Retorna el
+    /// número de canciones reordenadas.
+    pub fn sort_by_field(&mut self, field: SortField) -> usize {
+        match field {
+            SortField::Title => return self.sort_by(SortKey::Title, SortOrder::Asc),
+            SortField::DurationAsc => return self.sort_by(SortKey::Duration, SortOrder::Asc),
+            SortField::DurationDesc => return self.sort_by(SortKey::Duration, SortOrder::Desc),
+            SortField::Requester => return self.sort_by(SortKey::Requester, SortOrder::Asc),
+            SortField::Shuffle => return self.shuffle_once(),
+            SortField::Artist => self.items.make_contiguous().sort_by_key(|item| {
+                (item.artist.is_none(), item.artist.as_deref().map(str::to_lowercase))
+            }),
+        }
+        self.unpin_all();
+        self.shuffle_seed = None;
+        info!("↕️ Cola ordenada por {:?} ({} canciones)", field, self.items.len());
+        self.items.len()
+    }
+
+    /// Mezcla una vez solo las canciones pendientes no fijadas.
+    ///
+    /// Las colocadas con `/insert` conservan su posición y la canción actual
+    /// sigue sonando. Retorna el número de canciones mezcladas.
+    pub fn shuffle_pending(&mut self) -> usize {
+        let slots: Vec<usize> = (0..self.items.len()).filter(|&i| !self.items[i].pinned).collect();
+        let mut pending: Vec<QueueItem> = slots.iter().map(|&i| self.items[i].clone()).collect();
+        pending.shuffle(&mut rand::thread_rng());
+        for (slot, item) in slots.iter().zip(pending) {
+            self.items[*slot] = item;
+        }
+        self.shuffle_seed = None;
+        info!(
+            "🔀 Canciones pendientes mezcladas ({} de {}, fijadas en su lugar)",
+            slots.len(),
+            self.items.len()
+        );
+        slots.len()
+    }
+
+    /// Una mezcla explícita reordena todo, incluidas las canciones fijadas
+    fn unpin_all(&mut self) {
+        for item in self.items.iter_mut() {
+            item.pinned = false;
+        }
+    }
+
+    /// Semilla de la última mezcla determinista, si la hubo
+    pub fn get_shuffle_seed(&self) -> Option<u64> {
+        self.shuffle_seed
     }
 
     /// Cambia el modo de shuffle
@@ -217,13 +482,66 @@ impl MusicQueue {
     /// Cambia el modo de loop
     pub fn set_loop_mode(&mut self, mode: LoopMode) {
         self.loop_mode = mode;
+        if mode == LoopMode::Off {
+            self.loop_count = 0;
+        }
         match mode {
             LoopMode::Off => info!("➡️ Repetición desactivada"),
             LoopMode::Track => info!("🔂 Repetir canción activado"),
             LoopMode::Queue => info!("🔁 Repetir cola activado"),
+            LoopMode::TrackCount(times) => info!("🔂 Repetir canción {} veces", times),
+        }
+    }
+
+    /// Copia las canciones pendientes (no la actual ni el historial)
+    pub fn snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            items: self.items.clone(),
         }
     }
 
+    /// Canciones de `before` que ya no están pendientes, cada una con la que la
+    /// precedía en `before`.
+    ///
+    /// Pensado para cambios que solo quitan canciones (`/clear`, `/remove`): las
+    /// que quedan siguen en el mismo orden.
+    pub fn removed_since(&self, before: QueueSnapshot) -> RemovedTracks {
+        let mut kept = self.items.iter().peekable();
+        let mut previous: Option<QueueItem> = None;
+        let mut tracks = Vec::new();
+        for item in before.items {
+            if kept.peek().is_some_and(|kept| kept.is_same_entry(&item)) {
+                kept.next();
+            } else {
+                tracks.push((previous.clone(), item.clone()));
+            }
+            previous = Some(item);
+        }
+        RemovedTracks { tracks }
+    }
+
+    /// Vuelve a poner cada canción quitada detrás de la que la precedía (al
+    /// principio si esa ya sonó). Lo que se agregó o sonó después no se toca, y
+    /// la actual sigue sonando. Solo vuelven las que caben en la cola; el resto
+    /// se descarta y se cuenta en [`RestoreOutcome::dropped`].
+    pub fn restore_removed(&mut self, removed: RemovedTracks) -> RestoreOutcome {
+        let available_space = self.max_size.saturating_sub(self.items.len());
+        let restored = removed.tracks.len().min(available_space);
+        let dropped = removed.tracks.len() - restored;
+        for (previous, item) in removed.tracks.into_iter().take(restored) {
+            let position = previous
+                .and_then(|previous| self.items.iter().position(|queued| queued.is_same_entry(&previous)))
+                .map_or(0, |index| index + 1);
+            self.items.insert(position, item);
+        }
+        if dropped > 0 {
+            warn!("↩️ {} canciones devueltas a la cola, {} no cupieron", restored, dropped);
+        } else {
+            info!("↩️ {} canciones devueltas a la cola", restored);
+        }
+        RestoreOutcome { restored, dropped }
+    }
+
     /// Obtiene información de la cola
     pub fn get_info(&self) -> QueueInfo {
         QueueInfo {
@@ -231,7 +549,9 @@ impl MusicQueue {
             items: self.items.iter().cloned().collect(),
             total_items: self.items.len(),
             loop_mode: self.loop_mode,
+            loop_count: self.loop_count,
             shuffle: self.shuffle,
+            shuffle_seed: self.get_shuffle_seed(),
             total_duration: self.calculate_total_duration(),
         }
     }
@@ -254,6 +574,33 @@ impl MusicQueue {
         self.items.len()
     }
 
+    /// Ordena las canciones pendientes (la actual sigue sonando).
+    ///
+    /// El orden es estable: las canciones empatadas conservan su orden relativo.
+    /// Las de duración desconocida (en vivo) quedan al final en ambos sentidos.
+    /// Retorna el número de canciones ordenadas.
+    pub fn sort_by(&mut self, key: SortKey, order: SortOrder) -> usize {
+        let directed = |ordering: std::cmp::Ordering| match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+
+        self.items.make_contiguous().sort_by(|a, b| match key {
+            SortKey::Duration => match (a.duration, b.duration) {
+                (Some(x), Some(y)) => directed(x.cmp(&y)),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            },
+            SortKey::Title => directed(a.title.to_lowercase().cmp(&b.title.to_lowercase())),
+            SortKey::Requester => directed(a.requested_by.cmp(&b.requested_by)),
+        });
+        self.unpin_all();
+        self.shuffle_seed = None;
+        info!("🔃 Cola ordenada por {} ({} canciones)", key.display_name(), self.items.len());
+        self.items.len()
+    }
+
     /// Mueve un track a una nueva posición
     #[allow(dead_code)]
     pub fn move_track(&mut self, from: usize, to: usize) -> Result<()> {
@@ -301,6 +648,7 @@ impl MusicQueue {
         let previous = self.history.pop()?;
         info!("⏮️ Volviendo a: {}", previous.title);
         self.current = Some(previous.clone());
+        self.loop_count = 0;
         Some(previous.source)
     }
 
@@ -328,11 +676,92 @@ impl MusicQueue {
         let target = self.items.pop_front()?;
         info!("🎯 Saltando a posición {}: {}", position, target.title);
         self.current = Some(target.clone());
+        self.loop_count = 0;
         Some(target.source)
     }
 
+    /// Avanza hasta la próxima canción pedida por `user_id` y la fija como actual.
+    ///
+    /// Las canciones intermedias pasan al historial (con loop de cola vuelven
+    /// al final). Si el usuario no tiene nada en cola no cambia nada y retorna
+    /// `None`; si no, el número de canciones saltadas y la nueva actual.
+    pub fn advance_to_user(&mut self, user_id: UserId) -> Option<(usize, TrackSource)> {
+        let skipped = self.items.iter().position(|item| item.requested_by == user_id)?;
+
+        if let LoopMode::TrackCount(remaining) = self.loop_mode {
+            info!("⏭️ Salto: se cancelan {} repeticiones pendientes", remaining);
+            self.loop_mode = LoopMode::Off;
+        }
+
+        if let Some(current) = self.current.take() {
+            self.add_to_history(current);
+        }
+
+        for _ in 0..skipped {
+            if let Some(item) = self.items.pop_front() {
+                if self.loop_mode == LoopMode::Queue {
+                    self.items.push_back(item.clone());
+                }
+                self.add_to_history(item);
+            }
+        }
+
+        let target = self.items.pop_front()?;
+        if self.loop_mode == LoopMode::Queue {
+            self.items.push_back(target.clone());
+        }
+        info!("👤 Saltando {} canciones hasta: {}", skipped, target.title);
+        self.current = Some(target.clone());
+        self.loop_count = 0;
+        Some((skipped, target.source))
+    }
+
+    /// Busca `query` (sin distinguir mayúsculas) en el título y el artista de la
+    /// canción actual (posición 0) y de las pendientes (posición 1 en adelante).
+    pub fn find_track(&self, query: &str) -> Vec<(usize, &TrackSource)> {
+        let query = query.to_lowercase();
+        let matches = |item: &QueueItem| {
+            item.title.to_lowercase().contains(&query)
+                || item.artist.as_ref().is_some_and(|artist| artist.to_lowercase().contains(&query))
+        };
+
+        self.current
+            .iter()
+            .map(|item| (0, item))
+            .chain(self.items.iter().enumerate().map(|(i, item)| (i + 1, item)))
+            .filter(|(_, item)| matches(item))
+            .map(|(position, item)| (position, &item.source))
+            .collect()
+    }
+
+    /// Quita de la cola pendiente la primera canción con esa URL
+    pub fn remove_by_url(&mut self, url: &str) -> Option<TrackSource> {
+        let index = self.items.iter().position(|item| &*item.url == url)?;
+        self.items.remove(index).map(|item| item.source)
+    }
+
+    /// `true` si alguna canción pendiente tiene esa URL
+    pub fn contains_url(&self, url: &str) -> bool {
+        self.items.iter().any(|item| &*item.url == url)
+    }
+
+    /// Completa la metadata de las canciones pendientes con la URL de `enriched`,
+    /// sin tocar su posición, quién la pidió ni si está fijada.
+    /// Retorna cuántas se actualizaron.
+    pub fn enrich_track(&mut self, enriched: &TrackSource) -> usize {
+        let url = enriched.url();
+        let mut updated = 0;
+        for item in self.items.iter_mut().filter(|item| item.url == url) {
+            item.source = item.source.clone().with_metadata_from(enriched);
+            item.artist = item.source.artist();
+            item.duration = item.source.duration();
+            item.thumbnail = item.source.thumbnail();
+            updated += 1;
+        }
+        updated
+    }
+
     /// Obtiene el historial de reproducción
-    #[allow(dead_code)]
     pub fn get_history(&self) -> Vec<&QueueItem> {
         self.history.iter().collect()
     }
@@ -393,7 +822,7 @@ impl MusicQueue {
     /// Verifica si loop está activado
     #[allow(dead_code)]
     pub fn is_loop(&self) -> bool {
-        matches!(self.loop_mode, LoopMode::Track | LoopMode::Queue)
+        matches!(self.loop_mode, LoopMode::Track | LoopMode::Queue | LoopMode::TrackCount(_))
     }
 
     /// Activa/desactiva loop (modo simple)
@@ -427,7 +856,10 @@ pub struct QueueInfo {
     pub items: Vec<QueueItem>,
     pub total_items: usize,
     pub loop_mode: LoopMode,
+    /// Veces que la canción actual se repitió por loop
+    pub loop_count: u32,
     pub shuffle: bool,
+    pub shuffle_seed: Option<u64>,
     pub total_duration: Duration,
 }
 
@@ -456,3 +888,36 @@ pub struct QueuePage {
     #[allow(dead_code)]
     pub total_items: usize,
 }
+
+/// Hace que la lista empiece en la posición `index` (base 1, acotada al largo de la lista).
+///
+/// Con `wrap` los tracks anteriores pasan al final; sin él se descartan.
+/// Retorna la posición efectiva usada.
+pub fn start_tracks_at(tracks: &mut Vec<TrackSource>, index: usize, wrap: bool) -> usize {
+    let start = index.clamp(1, tracks.len().max(1));
+    if wrap {
+        tracks.rotate_left(start - 1);
+    } else {
+        tracks.drain(..start - 1);
+    }
+    start
+}
+
+/// Elimina URLs repetidas conservando la primera aparición y el orden.
+/// Retorna cuántos tracks se descartaron.
+pub fn dedupe_tracks(tracks: &mut Vec<TrackSource>) -> usize {
+    let mut seen = std::collections::HashSet::new();
+    let original_len = tracks.len();
+    tracks.retain(|track| seen.insert(track.url()));
+    original_len - tracks.len()
+}
+
+/// Agrupa las canciones consecutivas del mismo álbum (`album_name`).
+///
+/// Cada grupo es un tramo contiguo de `items`; las canciones sin álbum quedan
+/// siempre en un grupo propio.
+pub fn group_by_album(items: &[QueueItem]) -> Vec<&[QueueItem]> {
+    items
+        .chunk_by(|a, b| a.source.album_name().is_some() && a.source.album_name() == b.source.album_name())
+        .collect()
+}
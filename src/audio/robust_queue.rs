@@ -77,7 +77,7 @@ impl RobustQueue {
         }
 
         // Verificar si el track ya falló demasiadas veces
-        if let Some(&retry_count) = queue.retry_count.get(&source.url()) {
+        if let Some(&retry_count) = queue.retry_count.get(&*source.url()) {
             let recovery = self.error_recovery.lock().await;
             if retry_count >= recovery.max_retries {
                 warn!("🚫 Track {} ha fallado {} veces, no se agregará", source.title(), retry_count);
@@ -104,7 +104,7 @@ impl RobustQueue {
 
             // Si está en modo loop track, devolver el mismo (si no ha fallado)
             if queue.loop_mode == LoopMode::Track {
-                let retry_count = queue.retry_count.get(&current.url).copied().unwrap_or(0);
+                let retry_count = queue.retry_count.get(&*current.url).copied().unwrap_or(0);
                 if retry_count < recovery.max_retries {
                     queue.current = Some(current.clone());
                     return Some(current.source);
@@ -116,7 +116,7 @@ impl RobustQueue {
 
         // Intentar obtener siguiente track válido
         while let Some(next_item) = self.get_next_item(&mut queue).await {
-            let retry_count = queue.retry_count.get(&next_item.url).copied().unwrap_or(0);
+            let retry_count = queue.retry_count.get(&*next_item.url).copied().unwrap_or(0);
             
             // Si el track ha fallado demasiadas veces, saltarlo si está en modo recovery
             if recovery.skip_failed_tracks && retry_count >= recovery.max_retries {
@@ -191,7 +191,7 @@ impl RobustQueue {
         
         for item in failed_to_retry {
             // Resetear contador de retries para dar una nueva oportunidad
-            queue.retry_count.insert(item.url.clone(), 0);
+            queue.retry_count.insert(item.url.to_string(), 0);
             queue.items.push_back(item);
             info!("🔄 Track recuperado para retry: {}", queue.items.back().unwrap().title);
         }
@@ -231,7 +231,7 @@ impl RobustQueue {
         // Mover track actual a fallidos si excede máximo de retries
         if *retry_count >= recovery.max_retries {
             if let Some(current) = queue.current.take() {
-                if current.url == track_url {
+                if &*current.url == track_url {
                     warn!("🚫 Moviendo track problemático a lista de fallidos: {}", current.title);
                     queue.failed_tracks.push(current);
                 }
@@ -314,7 +314,9 @@ impl RobustQueue {
             items: queue.items.iter().cloned().collect(),
             total_items: queue.items.len(),
             loop_mode: queue.loop_mode,
+            loop_count: 0,
             shuffle: queue.shuffle,
+            shuffle_seed: None,
             total_duration: self.calculate_total_duration(&queue).await,
         };
 
@@ -398,6 +400,7 @@ impl RobustQueue {
             LoopMode::Off => info!("➡️ Repetición desactivada (robusto)"),
             LoopMode::Track => info!("🔂 Repetir canción activado (robusto)"),
             LoopMode::Queue => info!("🔁 Repetir cola activado (robusto)"),
+            LoopMode::TrackCount(times) => info!("🔂 Repetir canción {} veces (robusto)", times),
         }
     }
 
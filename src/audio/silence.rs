@@ -0,0 +1,84 @@
+//! Detección de silencio en la salida de ffmpeg (`silencedetect`).
+//!
+//! Algunos fallos de yt-dlp producen streams casi mudos que "suenan" durante
+//! toda su duración. ffmpeg avisa por stderr cuándo empieza y termina cada
+//! tramo de silencio; [`SilenceMonitor`] guarda ese estado para que el
+//! reproductor salte la pista si el silencio se alarga demasiado.
+
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Filtro de ffmpeg: silencio por debajo de -50 dB durante al menos 5 segundos
+pub const SILENCE_FILTER: &str = "silencedetect=n=-50dB:d=5";
+
+/// Silencio que ya transcurrió cuando ffmpeg reporta `silence_start` (el `d=` del filtro)
+pub const SILENCE_MIN_DURATION: Duration = Duration::from_secs(5);
+
+/// Línea de `silencedetect` en el stderr de ffmpeg
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SilenceEvent {
+    Start,
+    End,
+}
+
+/// Reconoce las líneas `silence_start: ...` / `silence_end: ...` de ffmpeg
+pub fn parse_silencedetect_line(line: &str) -> Option<SilenceEvent> {
+    if !line.contains("silencedetect") {
+        return None;
+    }
+    if line.contains("silence_start:") {
+        Some(SilenceEvent::Start)
+    } else if line.contains("silence_end:") {
+        Some(SilenceEvent::End)
+    } else {
+        None
+    }
+}
+
+/// Agrega [`SILENCE_FILTER`] al final de una cadena de filtros de ffmpeg
+pub fn with_silence_filter(filter: &str) -> String {
+    if filter.is_empty() || filter == "anull" {
+        SILENCE_FILTER.to_string()
+    } else {
+        format!("{},{}", filter, SILENCE_FILTER)
+    }
+}
+
+/// Estado de silencio de la pista actual, alimentado por el stderr de ffmpeg
+#[derive(Debug, Default)]
+pub struct SilenceMonitor {
+    silent: AtomicBool,
+}
+
+impl SilenceMonitor {
+    /// `true` mientras ffmpeg reporta un tramo de silencio sin terminar
+    pub fn is_silent(&self) -> bool {
+        self.silent.load(Ordering::Acquire)
+    }
+
+    pub fn observe_line(&self, line: &str) {
+        match parse_silencedetect_line(line) {
+            Some(SilenceEvent::Start) => self.silent.store(true, Ordering::Release),
+            Some(SilenceEvent::End) => self.silent.store(false, Ordering::Release),
+            None => {}
+        }
+    }
+
+    /// Lee el stderr de ffmpeg en un hilo propio hasta que el proceso lo cierra.
+    ///
+    /// Además de actualizar el estado, vacía el pipe: si nadie lo leyera, ffmpeg
+    /// se bloquearía al llenarse.
+    pub fn watch_stderr(self: Arc<Self>, stderr: impl Read + Send + 'static) {
+        std::thread::spawn(move || {
+            // Sin `lines()`: una línea con UTF-8 inválido cortaría la lectura
+            for line in BufReader::new(stderr).split(b'\n') {
+                match line {
+                    Ok(line) => self.observe_line(&String::from_utf8_lossy(&line)),
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+/// Tamaño a partir del cual se rota el log de una guild (`guild_{id}.log.1`)
+pub const MAX_AUDIT_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Entradas por página en `/audit`
+pub const AUDIT_PAGE_SIZE: usize = 10;
+
+/// Detalles relevantes de un comando auditado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditDetails {
+    /// Búsqueda o URL pasada al comando
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Canción sonando tras el comando
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_before: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_after: Option<usize>,
+    /// Volumen tras el comando (porcentaje)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u32>,
+    /// `false` si el comando terminó con error
+    #[serde(default = "default_success")]
+    pub success: bool,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+impl Default for AuditDetails {
+    fn default() -> Self {
+        Self {
+            query: None,
+            track_url: None,
+            queue_before: None,
+            queue_after: None,
+            volume: None,
+            success: true,
+        }
+    }
+}
+
+/// Una línea del log de auditoría
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub user_id: u64,
+    pub username: String,
+    pub command: String,
+    #[serde(flatten)]
+    pub details: AuditDetails,
+}
+
+/// Log de auditoría de comandos, un archivo JSON Lines por guild
+#[derive(Debug)]
+pub struct AuditLog {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Serializa escrituras y rotaciones
+    write_lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Ruta del log activo de una guild
+    pub fn path(&self, guild_id: u64) -> PathBuf {
+        self.dir.join(format!("guild_{}.log", guild_id))
+    }
+
+    /// Añade una entrada al log de la guild, rotándolo si supera el tamaño máximo
+    pub async fn log_audit_event(&self, guild_id: u64, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let path = self.path(guild_id);
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            if metadata.len() >= self.max_bytes {
+                tokio::fs::rename(&path, rotated_path(&path)).await?;
+                info!("📜 Log de auditoría de guild {} rotado", guild_id);
+            }
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Últimas `limit` entradas del log activo, de la más reciente a la más antigua
+    pub async fn read_recent(&self, guild_id: u64, limit: usize) -> Result<Vec<AuditEntry>> {
+        let content = match tokio::fs::read_to_string(self.path(guild_id)).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect())
+    }
+
+    /// Borra los logs (activos y rotados) sin modificar desde hace más de `retention`
+    pub async fn purge_older_than(&self, retention: Duration) -> Result<usize> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = SystemTime::now();
+        let mut removed = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let modified = entry.metadata().await?.modified()?;
+            let expired = now.duration_since(modified).is_ok_and(|age| age > retention);
+            if expired {
+                tokio::fs::remove_file(entry.path()).await?;
+                debug!("🗑️ Log de auditoría eliminado: {:?}", entry.path());
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// `guild_{id}.log` → `guild_{id}.log.1`
+pub fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+/// Página `page` (1 = más reciente) de `entries` y el total de páginas.
+/// Una página fuera de rango se ajusta a la última.
+pub fn paginate(entries: &[AuditEntry], page: usize, per_page: usize) -> (&[AuditEntry], usize) {
+    let per_page = per_page.max(1);
+    let total_pages = entries.len().div_ceil(per_page).max(1);
+    let page = page.clamp(1, total_pages);
+    let start = (page - 1) * per_page;
+    let end = (start + per_page).min(entries.len());
+    (&entries[start..end], total_pages)
+}
@@ -0,0 +1,106 @@
+use serenity::model::{
+    application::{CommandDataOption, CommandDataOptionValue, CommandInteraction},
+    id::{AttachmentId, ChannelId, UserId},
+};
+
+/// Error al leer las opciones de un comando slash
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CommandParseError {
+    /// Falta una opción marcada como requerida
+    #[error("Falta la opción requerida `{0}`")]
+    Missing(String),
+    /// La opción existe pero su valor no es del tipo esperado
+    #[error("La opción `{0}` no tiene el tipo esperado")]
+    WrongType(String),
+}
+
+/// Acceso tipado a las opciones de un comando (o de un subcomando).
+///
+/// Reemplaza el patrón `options.iter().find(|o| o.name == ..).and_then(|o| o.value.as_str())`.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandOptions<'a> {
+    options: &'a [CommandDataOption],
+}
+
+impl<'a> CommandOptions<'a> {
+    pub fn new(options: &'a [CommandDataOption]) -> Self {
+        Self { options }
+    }
+
+    /// Opciones de primer nivel del comando
+    pub fn from_command(command: &'a CommandInteraction) -> Self {
+        Self::new(&command.data.options)
+    }
+
+    fn value(&self, name: &str) -> Option<&'a CommandDataOptionValue> {
+        self.options.iter().find(|opt| opt.name == name).map(|opt| &opt.value)
+    }
+
+    /// `true` si no se pasó ninguna opción
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&'a str> {
+        self.value(name).and_then(|value| value.as_str())
+    }
+
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.value(name).and_then(|value| value.as_i64())
+    }
+
+    /// Valor decimal; acepta también opciones enteras
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        self.value(name)
+            .and_then(|value| value.as_f64().or_else(|| value.as_i64().map(|v| v as f64)))
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.value(name).and_then(|value| value.as_bool())
+    }
+
+    pub fn get_user_id(&self, name: &str) -> Option<UserId> {
+        self.value(name).and_then(|value| value.as_user_id())
+    }
+
+    pub fn get_channel_id(&self, name: &str) -> Option<ChannelId> {
+        self.value(name).and_then(|value| value.as_channel_id())
+    }
+
+    /// Archivo adjunto; sus datos están en `command.data.resolved.attachments`
+    pub fn get_attachment_id(&self, name: &str) -> Option<AttachmentId> {
+        self.value(name).and_then(|value| value.as_attachment_id())
+    }
+
+    /// Texto de una opción requerida
+    pub fn require_str(&self, name: &str) -> Result<&'a str, CommandParseError> {
+        let value = self.value(name).ok_or_else(|| CommandParseError::Missing(name.to_string()))?;
+        value.as_str().ok_or_else(|| CommandParseError::WrongType(name.to_string()))
+    }
+
+    /// Entero de una opción requerida
+    pub fn require_i64(&self, name: &str) -> Result<i64, CommandParseError> {
+        let value = self.value(name).ok_or_else(|| CommandParseError::Missing(name.to_string()))?;
+        value.as_i64().ok_or_else(|| CommandParseError::WrongType(name.to_string()))
+    }
+
+    /// Usuario de una opción requerida
+    pub fn require_user_id(&self, name: &str) -> Result<UserId, CommandParseError> {
+        let value = self.value(name).ok_or_else(|| CommandParseError::Missing(name.to_string()))?;
+        value.as_user_id().ok_or_else(|| CommandParseError::WrongType(name.to_string()))
+    }
+
+    /// Subcomando invocado (`/settings search ...`) y sus propias opciones.
+    ///
+    /// Para un grupo (`/settings block keyword ...`) devuelve el grupo; su
+    /// subcomando se obtiene llamando otra vez a `subcommand()`.
+    pub fn subcommand(&self) -> Option<(&'a str, CommandOptions<'a>)> {
+        let option = self.options.first()?;
+        match &option.value {
+            CommandDataOptionValue::SubCommand(options) | CommandDataOptionValue::SubCommandGroup(options) => {
+                Some((option.name.as_str(), CommandOptions::new(options)))
+            }
+            _ => None,
+        }
+    }
+}
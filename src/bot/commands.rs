@@ -1,83 +1,163 @@
 use anyhow::Result;
 use serenity::{
     builder::{CreateCommand, CreateCommandOption},
-    model::{application::CommandOptionType, id::GuildId},
+    model::{
+        application::{Command, CommandOptionType, CommandType},
+        channel::ChannelType,
+        id::GuildId,
+        permissions::Permissions,
+    },
     prelude::Context,
 };
+use tokio::sync::Mutex;
 
+use crate::audio::effects::{MAX_TEMPO, MIN_TEMPO};
+use crate::config::MAX_SEARCH_RESULT_COUNT;
+use crate::sources::SourceType;
+use crate::storage::{JsonStorage, MAX_EQ_GAIN_DB};
 
-/// Registra comandos globales
-pub async fn register_global_commands(ctx: &Context) -> Result<()> {
-    let commands = vec![
+
+/// Clave en el storage del hash de los comandos globales
+const GLOBAL_SCOPE: &str = "global";
+
+/// Todos los comandos del bot (mismo conjunto para registro global y por guild)
+fn all_commands() -> Vec<CreateCommand> {
+    vec![
         play_command(),
+        play_this_command(),
         search_command(),
         playlist_command(),
+        radio_command(),
         pause_command(),
         resume_command(),
         skip_command(),
         previous_command(),
         restart_command(),
+        restart_playback_command(),
         stop_command(),
         queue_command(),
+        queue_sort_command(),
+        queue_manage_command(),
         nowplaying_command(),
+        leaderboard_command(),
+        top_rated_command(),
+        stats_command(),
+        quiz_command(),
+        queue_save_command(),
+        queue_load_command(),
+        playlists_command(),
         shuffle_command(),
         loop_command(),
         clear_command(),
+        undo_command(),
         volume_command(),
         equalizer_command(),
+        eq_command(),
+        speed_command(),
+        effects_command(),
+        session_command(),
         join_command(),
+        move_channel_command(),
+        summon_command(),
         leave_command(),
         seek_command(),
+        ab_loop_command(),
+        chapters_command(),
         add_command(),
+        insert_command(),
         remove_command(),
         jump_command(),
+        skipto_user_command(),
+        find_command(),
+        notify_command(),
+        my_data_command(),
         help_command(),
         health_command(),
         metrics_command(),
-    ];
+        cache_command(),
+        clearcache_command(),
+        cachestats_command(),
+        prefix_command(),
+        settings_command(),
+        audit_command(),
+        export_config_command(),
+        import_config_command(),
+        config_reload_command(),
+        debug_command(),
+        test_proxy_command(),
+        cookies_command(),
+    ]
+}
 
+/// Hash estable (FNV-1a sobre el JSON que se envía a Discord) del conjunto de comandos.
+///
+/// No se usa `DefaultHasher` porque su salida puede cambiar entre versiones de
+/// Rust, y el hash se persiste entre reinicios.
+pub fn command_set_hash(commands: &[CreateCommand]) -> u64 {
+    let json = serde_json::to_vec(commands).unwrap_or_default();
+    json.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Nombres de los comandos, ordenados
+pub fn command_names(commands: &[CreateCommand]) -> Vec<String> {
+    let mut names: Vec<String> = commands
+        .iter()
+        .filter_map(|command| serde_json::to_value(command).ok())
+        .filter_map(|value| value.get("name").and_then(|n| n.as_str()).map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
 
-    for command in commands {
-        ctx.http.create_global_command(&command).await?;
+/// `true` si hay que volver a registrar: cambió el hash guardado o los comandos
+/// registrados en Discord no coinciden (p. ej. alguien los borró a mano).
+fn needs_registration(local: &[CreateCommand], stored_hash: Option<u64>, remote: &[Command]) -> bool {
+    let mut remote_names: Vec<String> = remote.iter().map(|c| c.name.clone()).collect();
+    remote_names.sort();
+
+    stored_hash != Some(command_set_hash(local)) || remote_names != command_names(local)
+}
+
+/// Registra comandos globales si cambiaron desde el último registro.
+///
+/// Retorna `Ok(false)` si el registro fue un no-op. Evita re-registrar en cada
+/// arranque: Discord tarda hasta ~1h en propagar los comandos globales.
+pub async fn register_global_commands(ctx: &Context, storage: &Mutex<JsonStorage>) -> Result<bool> {
+    let commands = all_commands();
+    let stored_hash = storage.lock().await.get_command_hash(GLOBAL_SCOPE);
+    let remote = ctx.http.get_global_commands().await?;
+
+    if !needs_registration(&commands, stored_hash, &remote) {
+        return Ok(false);
     }
 
-    Ok(())
+    let hash = command_set_hash(&commands);
+    Command::set_global_commands(&ctx.http, commands).await?;
+    storage.lock().await.set_command_hash(GLOBAL_SCOPE, hash).await?;
+
+    Ok(true)
 }
 
-/// Registra comandos para una guild específica (desarrollo)
-pub async fn register_guild_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-    let commands = vec![
-        play_command(),
-        search_command(),
-        playlist_command(),
-        pause_command(),
-        resume_command(),
-        skip_command(),
-        previous_command(),
-        restart_command(),
-        stop_command(),
-        queue_command(),
-        nowplaying_command(),
-        shuffle_command(),
-        loop_command(),
-        clear_command(),
-        volume_command(),
-        equalizer_command(),
-        join_command(),
-        leave_command(),
-        seek_command(),
-        add_command(),
-        remove_command(),
-        jump_command(),
-        help_command(),
-        health_command(),
-        metrics_command(),
-    ];
+/// Registra comandos para una guild específica (desarrollo) si cambiaron.
+///
+/// Retorna `Ok(false)` si el registro fue un no-op.
+pub async fn register_guild_commands(ctx: &Context, guild_id: GuildId, storage: &Mutex<JsonStorage>) -> Result<bool> {
+    let commands = all_commands();
+    let scope = format!("guild_{}", guild_id);
+    let stored_hash = storage.lock().await.get_command_hash(&scope);
+    let remote = guild_id.get_commands(&ctx.http).await?;
 
+    if !needs_registration(&commands, stored_hash, &remote) {
+        return Ok(false);
+    }
 
+    let hash = command_set_hash(&commands);
     guild_id.set_commands(&ctx.http, commands).await?;
+    storage.lock().await.set_command_hash(&scope, hash).await?;
 
-    Ok(())
+    Ok(true)
 }
 
 // Comandos de reproducción
@@ -92,10 +172,21 @@ fn play_command() -> CreateCommand {
                 "query",
                 "URL o término de búsqueda",
             )
-            .required(true),
+            .required(true)
+            .set_autocomplete(true),
         )
 }
 
+/// Nombre del comando de menú contextual sobre mensajes
+pub const PLAY_THIS_COMMAND: &str = "▶ Play This";
+
+/// Menú contextual (clic derecho → Apps) que reproduce el enlace de un mensaje
+fn play_this_command() -> CreateCommand {
+    CreateCommand::new(PLAY_THIS_COMMAND)
+        .kind(CommandType::Message)
+        .dm_permission(false)
+}
+
 fn search_command() -> CreateCommand {
     CreateCommand::new("search")
         .description("Busca canciones y muestra resultados")
@@ -111,6 +202,11 @@ fn search_command() -> CreateCommand {
                 .add_string_choice("SoundCloud", "soundcloud")
                 .add_string_choice("Tidal", "tidal"),
         )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "count", "Cantidad de resultados (1-10)")
+                .min_int_value(1)
+                .max_int_value(MAX_SEARCH_RESULT_COUNT as u64),
+        )
 }
 
 fn playlist_command() -> CreateCommand {
@@ -126,6 +222,16 @@ fn playlist_command() -> CreateCommand {
             "shuffle",
             "Mezclar la playlist al cargar",
         ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "dedupe",
+            "Eliminar canciones repetidas al cargar",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "wrap",
+            "Con &index=N en la URL, encolar al final las canciones anteriores",
+        ))
 }
 
 // Comandos de control
@@ -169,6 +275,12 @@ fn restart_command() -> CreateCommand {
         .dm_permission(false)
 }
 
+fn restart_playback_command() -> CreateCommand {
+    CreateCommand::new("restart-playback")
+        .description("Recupera la canción actual si se quedó en silencio, desde donde iba")
+        .dm_permission(false)
+}
+
 fn seek_command() -> CreateCommand {
     CreateCommand::new("seek")
         .description("Salta a una posición específica en la canción")
@@ -183,6 +295,32 @@ fn seek_command() -> CreateCommand {
         )
 }
 
+fn ab_loop_command() -> CreateCommand {
+    CreateCommand::new("ab-loop")
+        .description("Repite una sección de la canción actual")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Repite el tramo entre dos tiempos")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "start", "Inicio (ej: 1:00)").required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "end", "Fin (ej: 2:30)").required(true),
+                ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "clear",
+            "Deja de repetir la sección",
+        ))
+}
+
+fn chapters_command() -> CreateCommand {
+    CreateCommand::new("chapters")
+        .description("Muestra los capítulos de la canción actual y permite saltar a ellos")
+        .dm_permission(false)
+}
+
 fn add_command() -> CreateCommand {
     CreateCommand::new("add")
         .description("Agrega una canción a la cola sin reproducir")
@@ -197,6 +335,29 @@ fn add_command() -> CreateCommand {
         )
 }
 
+fn insert_command() -> CreateCommand {
+    CreateCommand::new("insert")
+        .description("Inserta una canción en una posición concreta de la cola")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "query",
+                "URL o término de búsqueda",
+            )
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "position",
+                "Posición en la cola (1 = siguiente; la última + 1 = al final)",
+            )
+            .required(true)
+            .min_int_value(1),
+        )
+}
+
 fn remove_command() -> CreateCommand {
     CreateCommand::new("remove")
         .description("Remueve una canción de la cola por posición")
@@ -227,6 +388,47 @@ fn jump_command() -> CreateCommand {
         )
 }
 
+fn skipto_user_command() -> CreateCommand {
+    CreateCommand::new("skipto-user")
+        .description("Salta a la próxima canción pedida por un usuario")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "Usuario cuya próxima canción quieres escuchar",
+            )
+            .required(true),
+        )
+}
+
+fn find_command() -> CreateCommand {
+    CreateCommand::new("find")
+        .description("Busca una canción en la cola por título o artista")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "query", "Parte del título o del artista")
+                .required(true)
+                .max_length(100),
+        )
+}
+
+fn queue_sort_command() -> CreateCommand {
+    CreateCommand::new("queue-sort")
+        .description("Reordena las canciones pendientes de la cola")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "by", "Criterio de ordenación")
+                .add_string_choice("Título", "title")
+                .add_string_choice("Artista", "artist")
+                .add_string_choice("Duración (más cortas primero)", "duration-asc")
+                .add_string_choice("Duración (más largas primero)", "duration-desc")
+                .add_string_choice("Solicitante", "requester")
+                .add_string_choice("Aleatorio", "shuffle")
+                .required(true),
+        )
+}
+
 fn stop_command() -> CreateCommand {
     CreateCommand::new("stop")
         .description("Detiene la reproducción y limpia la cola")
@@ -237,12 +439,52 @@ fn stop_command() -> CreateCommand {
 
 fn queue_command() -> CreateCommand {
     CreateCommand::new("queue")
-        .description("Muestra la cola de reproducción")
+        .description("Muestra u ordena la cola de reproducción")
         .dm_permission(false)
         .add_option(
-            CreateCommandOption::new(CommandOptionType::Integer, "page", "Número de página")
-                .min_int_value(1),
+            CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Muestra la cola de reproducción")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "page", "Número de página")
+                        .min_int_value(1),
+                ),
         )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "sort", "Ordena las canciones pendientes")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "key", "Criterio de orden")
+                        .add_string_choice("Duración", "duration")
+                        .add_string_choice("Título", "title")
+                        .add_string_choice("Solicitante", "requester")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "order", "Sentido (por defecto, ascendente)")
+                        .add_string_choice("Ascendente", "asc")
+                        .add_string_choice("Descendente", "desc"),
+                ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "shuffle-remaining",
+            "Mezcla una vez las canciones pendientes (las de /insert no se mueven)",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "loop-stats",
+            "Muestra cuántas veces se repitió la canción actual",
+        ))
+}
+
+fn queue_manage_command() -> CreateCommand {
+    CreateCommand::new("queue-manage")
+        .description("Reordena las primeras canciones de la cola con menús")
+        .dm_permission(false)
+}
+
+fn undo_command() -> CreateCommand {
+    CreateCommand::new("undo")
+        .description("Deshace el último /clear o /remove de la cola")
+        .dm_permission(false)
 }
 
 fn nowplaying_command() -> CreateCommand {
@@ -251,10 +493,168 @@ fn nowplaying_command() -> CreateCommand {
         .dm_permission(false)
 }
 
+fn leaderboard_command() -> CreateCommand {
+    CreateCommand::new("leaderboard")
+        .description("Muestra las canciones más escuchadas del servidor")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "scope", "Período del ranking (por defecto, desde siempre)")
+                .required(false)
+                .add_string_choice("Esta semana", "week")
+                .add_string_choice("Este mes", "month")
+                .add_string_choice("Desde siempre", "all-time")
+                .add_string_choice("Más votadas 👍", "votes"),
+        )
+}
+
+fn top_rated_command() -> CreateCommand {
+    CreateCommand::new("top-rated")
+        .description("Muestra las canciones mejor valoradas del servidor")
+        .dm_permission(false)
+}
+
+fn stats_command() -> CreateCommand {
+    CreateCommand::new("stats")
+        .description("Estadísticas de música")
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "guild",
+            "Lo que se escuchó en el servidor: totales, quién más pide y actividad por día",
+        ))
+}
+
+fn quiz_command() -> CreateCommand {
+    CreateCommand::new("quiz")
+        .description("Quiz musical: adivina canciones del historial del servidor")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "start", "Empieza un quiz de 10 rondas")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "duration_minutes",
+                        "Tiempo máximo del quiz en minutos",
+                    )
+                    .min_int_value(1)
+                    .max_int_value(60),
+                ),
+        )
+}
+
+fn queue_save_command() -> CreateCommand {
+    CreateCommand::new("queue-save")
+        .description("Guarda la cola actual como playlist personal")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "name", "Nombre de la playlist")
+                .required(true)
+                .min_length(1)
+                .max_length(50),
+        )
+}
+
+fn queue_load_command() -> CreateCommand {
+    CreateCommand::new("queue-load")
+        .description("Carga una playlist personal en la cola")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "playlist", "ID o nombre de la playlist")
+                .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "mode", "Agregar a la cola o reemplazarla")
+                .add_string_choice("Agregar", "append")
+                .add_string_choice("Reemplazar", "replace"),
+        )
+}
+
+fn playlists_command() -> CreateCommand {
+    let playlist_option = || {
+        CreateCommandOption::new(CommandOptionType::String, "playlist", "ID o nombre de la playlist").required(true)
+    };
+    let user_option = |description: &str| {
+        CreateCommandOption::new(CommandOptionType::User, "user", description).required(true)
+    };
+
+    CreateCommand::new("playlists")
+        .description("Tus playlists guardadas y las que compartes con otros")
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "Lista tus playlists y las que puedes editar",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "add-editor", "Permite a otro usuario editar tu playlist")
+                .add_sub_option(playlist_option())
+                .add_sub_option(user_option("Usuario que podrá agregar y quitar canciones")),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove-editor", "Quita a un editor de tu playlist")
+                .add_sub_option(playlist_option())
+                .add_sub_option(user_option("Editor a quitar")),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "add-track", "Agrega una canción a la playlist")
+                .add_sub_option(playlist_option())
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "query",
+                    "URL o búsqueda (por defecto, la canción actual)",
+                )),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove-track", "Quita una canción de la playlist")
+                .add_sub_option(playlist_option())
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "position", "Posición de la canción")
+                        .required(true)
+                        .min_int_value(1),
+                ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "activity", "Últimos cambios de la playlist")
+                .add_sub_option(playlist_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "export", "Descarga la playlist como archivo")
+                .add_sub_option(playlist_option())
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "format", "Formato del archivo")
+                        .add_string_choice("M3U8", "m3u8"),
+                ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "import", "Crea una playlist desde un archivo M3U8")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Attachment, "file", "Archivo .m3u8 o .m3u").required(true),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "name",
+                    "Nombre de la playlist (por defecto, el del archivo)",
+                )),
+        )
+}
+
 fn shuffle_command() -> CreateCommand {
     CreateCommand::new("shuffle")
-        .description("Activa/desactiva el modo aleatorio")
+        .description("Mezcla la cola o activa/desactiva el modo aleatorio")
         .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "mode", "Qué hacer")
+                .add_string_choice("Mezclar la cola ahora", "now")
+                .add_string_choice("Activar/desactivar modo aleatorio", "toggle"),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "seed",
+                "Semilla para una mezcla reproducible (misma semilla, mismo orden)",
+            )
+            .min_int_value(0),
+        )
 }
 
 fn loop_command() -> CreateCommand {
@@ -268,6 +668,15 @@ fn loop_command() -> CreateCommand {
                 .add_string_choice("Cola", "queue")
                 .required(true),
         )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "times",
+                "Con modo Canción: repetir solo N veces y luego seguir con la cola",
+            )
+            .min_int_value(1)
+            .max_int_value(100),
+        )
 }
 
 fn clear_command() -> CreateCommand {
@@ -314,6 +723,18 @@ fn join_command() -> CreateCommand {
         .dm_permission(false)
 }
 
+fn move_channel_command() -> CreateCommand {
+    CreateCommand::new("move-channel")
+        .description("Mueve el bot a tu canal de voz sin cortar la reproducción")
+        .dm_permission(false)
+}
+
+fn summon_command() -> CreateCommand {
+    CreateCommand::new("summon")
+        .description("Trae el bot a tu canal de voz (alias de /move-channel)")
+        .dm_permission(false)
+}
+
 fn leave_command() -> CreateCommand {
     CreateCommand::new("leave")
         .description("Desconecta el bot del canal de voz")
@@ -341,6 +762,114 @@ fn equalizer_command() -> CreateCommand {
         )
 }
 
+fn eq_command() -> CreateCommand {
+    CreateCommand::new("eq")
+        .description("Ajusta el ecualizador personalizado banda por banda")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "band", "Cambia la ganancia de una banda")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "frequency", "Frecuencia de la banda (Hz)")
+                        .set_autocomplete(true)
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Number, "gain_db", "Ganancia en dB")
+                        .min_number_value(-MAX_EQ_GAIN_DB as f64)
+                        .max_number_value(MAX_EQ_GAIN_DB as f64)
+                        .required(true),
+                ),
+        )
+}
+
+fn speed_command() -> CreateCommand {
+    CreateCommand::new("speed")
+        .description("Cambia la velocidad de reproducción sin alterar el tono")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Number, "factor", "Velocidad (1.0 = normal)")
+                .min_number_value(MIN_TEMPO as f64)
+                .max_number_value(MAX_TEMPO as f64)
+                .required(true),
+        )
+}
+
+fn effects_command() -> CreateCommand {
+    CreateCommand::new("effects")
+        .description("Efectos de audio de la guild")
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "show",
+            "Muestra ecualizador, normalización, tempo y filtros activos",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "clear",
+            "Restablece todos los efectos y reinicia el audio de la canción actual",
+        ))
+}
+
+fn session_command() -> CreateCommand {
+    CreateCommand::new("session")
+        .description("Sesión de música en un hilo")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "start",
+                "Crea un hilo al que se enviarán los mensajes del bot",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::Channel, "channel", "Canal donde crear el hilo (por defecto, este)")
+                    .channel_types(vec![ChannelType::Text]),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "end",
+            "Cierra el hilo de la sesión y vuelve al comportamiento normal",
+        ))
+}
+
+fn radio_command() -> CreateCommand {
+    CreateCommand::new("radio")
+        .description("Reproduce una radio por internet (Shoutcast/Icecast)")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "url", "URL del stream de la radio")
+                .required(true),
+        )
+}
+
+fn notify_command() -> CreateCommand {
+    CreateCommand::new("notify")
+        .description("Avisarte por DM cuando empiece a sonar una canción que pediste")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "state", "Activar o desactivar los avisos")
+                .add_string_choice("Activar", "on")
+                .add_string_choice("Desactivar", "off")
+                .required(true),
+        )
+}
+
+fn my_data_command() -> CreateCommand {
+    CreateCommand::new("my-data")
+        .description("Tus datos guardados en este servidor")
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "export",
+            "Descarga en JSON tus playlists, historial y preferencias",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "delete",
+            "Borra tus playlists, historial y preferencias",
+        ))
+}
+
 fn help_command() -> CreateCommand {
     CreateCommand::new("help")
         .description("Muestra información de ayuda")
@@ -363,3 +892,330 @@ fn metrics_command() -> CreateCommand {
         .dm_permission(false)
 }
 
+fn cache_command() -> CreateCommand {
+    CreateCommand::new("cache")
+        .description("Muestra el uso y la tasa de aciertos de los caches del bot")
+        .dm_permission(false)
+}
+
+fn prefix_command() -> CreateCommand {
+    CreateCommand::new("prefix")
+        .description("Configura el prefijo de comandos de texto (ej: !play)")
+        .dm_permission(false)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "value",
+                "Nuevo prefijo (1-5 caracteres) u 'off' para desactivar",
+            )
+            .max_length(5),
+        )
+}
+
+fn settings_command() -> CreateCommand {
+    CreateCommand::new("settings")
+        .description("Configuración del servidor")
+        .dm_permission(false)
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "search",
+                "Ranking de resultados de búsqueda (sin opciones muestra la configuración actual)",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "min_minutes",
+                    "Duración mínima preferida en minutos",
+                )
+                .min_int_value(0)
+                .max_int_value(600),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "max_minutes",
+                    "Duración máxima preferida en minutos (ej: 180 para podcasts)",
+                )
+                .min_int_value(1)
+                .max_int_value(600),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "penalize_versions",
+                "Penalizar live, cover, reaction, remix...",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "prefer_official",
+                "Preferir canales oficiales (VEVO, '- Topic')",
+            ))
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "reset",
+                "Restaurar los valores por defecto",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "url-detect",
+                "Reaccionar con ▶️ a enlaces de música en los mensajes para encolarlos",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "state", "Activar o desactivar")
+                    .required(true)
+                    .add_string_choice("on", "on")
+                    .add_string_choice("off", "off"),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "same-channel",
+                "Exigir estar en el canal de voz del bot para controlar la reproducción",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "state", "Activar o desactivar")
+                    .required(true)
+                    .add_string_choice("on", "on")
+                    .add_string_choice("off", "off"),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "limiter",
+                "Limitador suave para que el volumen por encima de 150% no sature",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "state", "Activar o desactivar")
+                    .required(true)
+                    .add_string_choice("on", "on")
+                    .add_string_choice("off", "off"),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "single-now-playing",
+                "Borra el \"reproduciendo ahora\" anterior al publicar uno nuevo",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "state", "Activar o desactivar")
+                    .required(true)
+                    .add_string_choice("on", "on")
+                    .add_string_choice("off", "off"),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "search-source",
+                "Fuente de las búsquedas por texto de /play, /search y /add",
+            )
+            .add_sub_option(
+                // Solo las fuentes que buscan por texto; las demás solo aceptan URLs
+                SourceType::ALL.iter().filter(|source| source.supports_text_search()).fold(
+                    CreateCommandOption::new(CommandOptionType::String, "source", "Fuente de búsqueda").required(true),
+                    |option, source| option.add_string_choice(source.as_str(), source.as_str()),
+                ),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "proxy",
+                "Proxy de yt-dlp de este servidor (solo dueño del bot)",
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "proxy",
+                "http://host:puerto o socks5://host:puerto; vacío para usar el global",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "bot-nickname",
+                "Apodo del bot mientras suena música ({track}, {guild})",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "pattern",
+                    "Patrón, ej: '🎵 {track}', u 'off' para usar el nombre por defecto",
+                )
+                .required(true)
+                .max_length(32),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "max-volume",
+                "Volumen máximo que se puede poner con /volume",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "percent", "Techo en % (10-200)")
+                    .required(true)
+                    .min_int_value(10)
+                    .max_int_value(200),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "dj_bypass",
+                "Permitir que el rol de DJ supere el techo",
+            )),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "color",
+                "Color de los embeds de música del servidor",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "hex",
+                    "Color en hexadecimal, ej: '#FF6B6B', o 'reset' para la paleta por defecto",
+                )
+                .required(true)
+                .max_length(7),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommandGroup,
+                "block",
+                "Bloquear palabras, canales o fuentes en /play",
+            )
+            .add_sub_option(block_list_option("keyword", "Palabra bloqueada en los títulos"))
+            .add_sub_option(block_list_option("channel", "Canal de YouTube bloqueado (nombre o ID)"))
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "sources", "Fuentes permitidas")
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "value",
+                            "Lista separada por comas (youtube, tidal, direct) o 'all'",
+                        )
+                        .required(true)
+                        .max_length(32),
+                    ),
+            )
+            .add_sub_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "Muestra el filtro actual",
+            )),
+        )
+}
+
+/// Subcomando `add|remove <value>` de `/settings block`
+fn block_list_option(name: &str, description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::SubCommand, name, description)
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "action", "Agregar o quitar")
+                .required(true)
+                .add_string_choice("add", "add")
+                .add_string_choice("remove", "remove"),
+        )
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "value", description)
+                .required(true)
+                .max_length(100),
+        )
+}
+
+fn audit_command() -> CreateCommand {
+    CreateCommand::new("audit")
+        .description("Historial de comandos de música usados en el servidor")
+        .dm_permission(false)
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "page", "Página del historial (1 = más reciente)")
+                .min_int_value(1),
+        )
+}
+
+fn export_config_command() -> CreateCommand {
+    CreateCommand::new("export-config")
+        .description("Exporta la configuración del servidor como archivo JSON")
+        .dm_permission(false)
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+}
+
+fn import_config_command() -> CreateCommand {
+    CreateCommand::new("import-config")
+        .description("Importa una configuración exportada con /export-config")
+        .dm_permission(false)
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Attachment, "file", "Archivo JSON de configuración")
+                .required(true),
+        )
+}
+
+// Comandos de administración (solo dueño del bot)
+
+fn clearcache_command() -> CreateCommand {
+    CreateCommand::new("clearcache")
+        .description("Limpia los caches del bot (solo dueño)")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "target", "Qué cache limpiar")
+                .add_string_choice("Todo", "all")
+                .add_string_choice("Metadata", "metadata")
+                .add_string_choice("Búsquedas", "search"),
+        )
+}
+
+fn cachestats_command() -> CreateCommand {
+    CreateCommand::new("cachestats")
+        .description("Muestra estadísticas de los caches del bot (solo dueño)")
+        .dm_permission(false)
+}
+
+fn debug_command() -> CreateCommand {
+    CreateCommand::new("debug")
+        .description("Muestra el estado interno del reproductor (solo dueño)")
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "guild",
+            "ID de otra guild a inspeccionar (por defecto, esta)",
+        ))
+}
+
+fn test_proxy_command() -> CreateCommand {
+    CreateCommand::new("test-proxy")
+        .description("Prueba el proxy de yt-dlp (solo dueño)")
+        .dm_permission(false)
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "proxy",
+            "URL del proxy a probar (por defecto, el de este servidor)",
+        ))
+}
+
+fn cookies_command() -> CreateCommand {
+    CreateCommand::new("cookies")
+        .description("Cookies de yt-dlp para videos con restricción de edad o de miembros (solo dueño)")
+        .dm_permission(false)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "set-file", "Usa otro archivo cookies.txt")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "path", "Ruta al cookies.txt en el servidor del bot")
+                        .required(true),
+                ),
+        )
+}
+
+fn config_reload_command() -> CreateCommand {
+    CreateCommand::new("config-reload")
+        .description("Recarga la configuración sin reiniciar el bot (solo dueño)")
+        .dm_permission(false)
+}
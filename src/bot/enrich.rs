@@ -0,0 +1,118 @@
+use serenity::model::id::GuildId;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+
+use crate::{
+    audio::player::AudioPlayer,
+    cache::{CachedTrackInfo, MusicCache},
+    sources::{ExtractionPool, SourceType, TrackSource, YtDlpOptimizedClient},
+};
+
+/// Cuánto dura en caché la metadata completa de una canción de YouTube
+const ENRICHED_METADATA_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Máximo de canciones que se completan por playlist (la cola admite 100)
+const MAX_ENRICHED_TRACKS: usize = 100;
+
+/// `true` si a la canción le falta metadata que la extracción plana no trae
+pub fn needs_enrichment(track: &TrackSource) -> bool {
+    track.duration().is_none() || track.thumbnail().is_none()
+}
+
+/// Completa en segundo plano la metadata de las canciones encoladas desde una
+/// playlist (la extracción plana no trae duración ni miniatura).
+pub struct QueueEnricher {
+    pub player: Arc<AudioPlayer>,
+    pub cache: Arc<MusicCache>,
+    pub extractions: Arc<ExtractionPool>,
+    pub client: YtDlpOptimizedClient,
+    /// Límite de cada extracción (`EXTRACTION_TIMEOUT`)
+    pub timeout: Duration,
+}
+
+impl QueueEnricher {
+    /// Pide una por una la metadata completa (duración, miniatura, capítulos) y
+    /// la actualiza en su lugar en la cola. Pasa por el pool de extracciones, así
+    /// que nunca ocupa más cupo que una reproducción normal, y se salta las
+    /// canciones que ya sonaron o se quitaron de la cola.
+    pub async fn run(self, guild_id: GuildId, tracks: Vec<TrackSource>, mut shutdown_rx: broadcast::Receiver<()>) {
+        let pending: Vec<_> = tracks
+            .into_iter()
+            .filter(needs_enrichment)
+            .take(MAX_ENRICHED_TRACKS)
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+
+        let total = pending.len();
+        let mut enriched = 0;
+        for track in pending {
+            let url = track.url();
+            if !self.player.is_queued(guild_id, &url) {
+                continue;
+            }
+
+            let key = format!("youtube:{}", url);
+            // Lo extraído con la sesión del navegador se guarda aparte
+            let authenticated_key = format!("youtube:authenticated:{}", url);
+            let full = match self.cache.get(&key).or_else(|| self.cache.get(&authenticated_key)) {
+                Some(cached) => cached_track(&track, cached),
+                None => {
+                    let extraction = tokio::select! {
+                        result = self.extractions.run(guild_id, self.timeout, self.client.get_track_authenticated(&url)) => result,
+                        _ = shutdown_rx.recv() => {
+                            debug!("🛑 Metadata de la cola interrumpida por el apagado");
+                            return;
+                        }
+                    };
+                    match extraction {
+                        Ok((full, authenticated)) => {
+                            let key = if authenticated { authenticated_key } else { key };
+                            self.cache.insert_with_ttl(key, cache_entry(&full, authenticated), Some(ENRICHED_METADATA_TTL));
+                            full
+                        }
+                        Err(e) => {
+                            debug!("Sin metadata completa para {}: {:?}", url, e);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            if self.player.enrich_queued(guild_id, &full) > 0 {
+                enriched += 1;
+            }
+        }
+
+        info!("🏷️ Metadata completada para {}/{} canciones de la cola en {}", enriched, total, guild_id);
+    }
+}
+
+/// Reconstruye la canción con la metadata guardada (los capítulos no se cachean)
+fn cached_track(track: &TrackSource, cached: CachedTrackInfo) -> TrackSource {
+    let mut full = TrackSource::new(cached.title, cached.url, track.source_type(), track.requested_by());
+    if let Some(artist) = cached.artist {
+        full = full.with_artist(artist);
+    }
+    if let Some(duration) = cached.duration {
+        full = full.with_duration(duration);
+    }
+    if let Some(thumbnail) = cached.thumbnail {
+        full = full.with_thumbnail(thumbnail);
+    }
+    full
+}
+
+fn cache_entry(track: &TrackSource, authenticated: bool) -> CachedTrackInfo {
+    CachedTrackInfo {
+        title: track.title().to_string(),
+        artist: track.artist().map(|artist| artist.to_string()),
+        duration: track.duration(),
+        thumbnail: track.thumbnail().map(|thumbnail| thumbnail.to_string()),
+        url: track.url().to_string(),
+        source: SourceType::YouTube.as_str().to_string(),
+        authenticated,
+    }
+}
@@ -119,15 +119,30 @@ impl VoiceEventHandler for ReceiveHandler {
     }
 }
 
-/// Handler para reconexiones automáticas
+/// Handler para reconexiones automáticas: retoma la pista actual desde la
+/// última posición conocida en vez de dejarla cortada o reiniciada.
 pub struct ReconnectHandler {
     pub guild_id: GuildId,
+    pub player: Arc<AudioPlayer>,
+    pub handler: Arc<tokio::sync::Mutex<songbird::Call>>,
 }
 
 #[async_trait]
 impl VoiceEventHandler for ReconnectHandler {
     async fn act(&self, _ctx: &EventContext<'_>) -> Option<VoiceEvent> {
-        info!("🔄 Reconectando al canal de voz en guild {}", self.guild_id);
+        info!("🔄 Reconectado al canal de voz en guild {}", self.guild_id);
+
+        // Se lanza aparte: re-crear el input (yt-dlp) tarda y no debe bloquear
+        // el hilo de eventos del driver.
+        let player = self.player.clone();
+        let handler = self.handler.clone();
+        let guild_id = self.guild_id;
+        tokio::spawn(async move {
+            if let Err(e) = player.rebuild_current_input(guild_id, handler).await {
+                warn!("Error retomando la reproducción en guild {}: {:?}", guild_id, e);
+            }
+        });
+
         None
     }
 }
@@ -135,7 +150,7 @@ impl VoiceEventHandler for ReconnectHandler {
 /// Envía un mensaje de \"Now Playing\" al canal
 #[allow(dead_code)]
 async fn send_now_playing(ctx: &Context, channel_id: ChannelId, track: &QueueItem) -> Result<()> {
-    let embed = crate::ui::embeds::create_now_playing_embed(track);
+    let embed = crate::ui::embeds::create_now_playing_embed(track, None);
     let buttons = crate::ui::buttons::create_player_buttons();
 
     channel_id
@@ -188,7 +203,11 @@ pub fn register_voice_events(
 
     // Handler para reconexiones
     handler.add_global_event(
-        VoiceEvent::Core(songbird::events::CoreEvent::DriverConnect),
-        ReconnectHandler { guild_id },
+        VoiceEvent::Core(songbird::events::CoreEvent::DriverReconnect),
+        ReconnectHandler {
+            guild_id,
+            player,
+            handler: handler_arc,
+        },
     );
 }
@@ -0,0 +1,121 @@
+//! Estadísticas de música del servidor (`/stats guild`).
+//!
+//! Una tarea de fondo suma cada canción que empieza a sonar en
+//! [`PlayStatsStore`], que también lleva el ranking de `/leaderboard` (al
+//! terminar o saltarse la canción suma el tiempo que sonó); el comando muestra el
+//! resumen, cacheado unos minutos por guild para no recalcularlo en cada consulta.
+
+use serenity::{
+    all::{CommandInteraction, Context},
+    builder::{CreateInteractionResponse, CreateInteractionResponseMessage},
+    model::id::GuildId,
+};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use super::OpenMusicBot;
+use crate::{
+    audio::player::AudioPlayer,
+    cache::lru_cache::LRUCache,
+    storage::play_stats::{GuildStatsSummary, PlayStatsStore},
+    ui::embeds,
+};
+
+/// Tiempo que se reutiliza el resumen de una guild
+pub const STATS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Resúmenes recientes de `/stats guild` por guild
+static STATS_CACHE: LazyLock<LRUCache<GuildId, GuildStatsSummary>> = LazyLock::new(|| LRUCache::new(1000));
+
+/// Tarea de fondo que registra cada canción que empieza a sonar y, en el
+/// ranking, el tiempo que sonó al terminar o saltarse
+pub async fn play_stats_task(
+    player: Arc<AudioPlayer>,
+    play_stats: Arc<PlayStatsStore>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut track_starts = player.subscribe_track_starts();
+    let mut track_finishes = player.subscribe_track_finishes();
+
+    loop {
+        let started = tokio::select! {
+            started = track_starts.recv() => started,
+            finished = track_finishes.recv() => {
+                match finished {
+                    Ok(finished) => {
+                        if let Err(e) = play_stats
+                            .record_listened(finished.guild_id.get(), &finished.source.url(), finished.played)
+                            .await
+                        {
+                            warn!("Error guardando ranking de guild {}: {:?}", finished.guild_id, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Se perdieron {} finales de canción para el ranking", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+                continue;
+            }
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Estadísticas de reproducción detenidas");
+                break;
+            }
+        };
+
+        match started {
+            Ok(started) => {
+                let track = &started.source;
+                if let Err(e) = play_stats
+                    .record_play(
+                        started.guild_id.get(),
+                        track.requested_by().get(),
+                        &track.url(),
+                        &track.title(),
+                        track.duration(),
+                    )
+                    .await
+                {
+                    warn!("Error guardando estadísticas de guild {}: {:?}", started.guild_id, e);
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!("Se perdieron {} reproducciones para las estadísticas", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// `/stats guild`: resumen de lo que se escuchó en el servidor
+pub async fn handle_stats_command(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> anyhow::Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let summary = match STATS_CACHE.get(&guild_id) {
+        Some(summary) => Some(summary),
+        None => {
+            let summary = bot.play_stats.summary(guild_id.get());
+            if let Some(summary) = &summary {
+                STATS_CACHE.insert_with_ttl(guild_id, summary.clone(), Some(STATS_CACHE_TTL));
+            }
+            summary
+        }
+    };
+
+    let message = match summary {
+        Some(summary) => {
+            let guild_name = ctx.cache.guild(guild_id).map(|g| g.name.clone()).unwrap_or_else(|| "el servidor".to_string());
+            CreateInteractionResponseMessage::new().embed(embeds::create_guild_stats_embed(&guild_name, &summary))
+        }
+        None => CreateInteractionResponseMessage::new()
+            .content("📊 Todavía no sonó ninguna canción en este servidor")
+            .ephemeral(true),
+    };
+
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await?;
+    Ok(())
+}
@@ -1,22 +1,48 @@
 use anyhow::Result;
+use chrono::Utc;
 use serenity::{
-    builder::{CreateInteractionResponse, CreateInteractionResponseMessage},
+    builder::{
+        CreateActionRow, CreateAttachment, CreateAutocompleteResponse, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+        CreateMessage, CreateThread, EditInteractionResponse, EditThread,
+    },
     model::{
-        application::{CommandInteraction, ComponentInteraction},
+        application::{ButtonStyle, CommandInteraction, CommandType, ComponentInteraction, ResolvedTarget},
+        channel::{AutoArchiveDuration, ChannelType},
         id::{ChannelId, GuildId, UserId},
     },
     prelude::Context,
 };
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::LazyLock;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use parking_lot::Mutex;
 use tokio::io::AsyncBufReadExt;
-use tracing::{info, warn};
+use tracing::{debug, info, info_span, warn, Instrument};
+use uuid::Uuid;
 
 use crate::{
-    bot::OpenMusicBot,
-    sources::{MusicSource, TrackSource, SourceType, YtDlpOptimizedClient},
+    audio::{
+        effects::{eq_band_index, CUSTOM_EQ_FREQUENCIES, LIMITER_VOLUME_THRESHOLD, MAX_TEMPO, MIN_TEMPO},
+        player::{AbLoop, TooManyRestartsError, MAX_PLAYBACK_RESTARTS},
+        queue::{SortKey, SortOrder},
+    },
+    audit::{AuditDetails, AuditEntry, AUDIT_PAGE_SIZE},
+    bot::{
+        command_options::CommandOptions,
+        permissions,
+        playlist_load::{PlaylistDecision, PlaylistLoad},
+        quiz,
+        OpenMusicBot,
+    },
+    cache::lru_cache::LRUCache,
+    errors::{record_error, ErrorCategory, ERROR_STATS, ERROR_WINDOW},
+    health::{ComponentStatus, DEPENDENCY_VERSIONS},
+    storage::{leaderboard::LeaderboardScope, m3u, PlaylistTrack, ServerConfig, UserPlaylist, MAX_EQ_GAIN_DB},
+    sources::{
+        content_filter, direct, ranking, BlockedTrackError, MusicSource, RestrictedVideoError, SearchResult, SearchPreferences, SourceType,
+        TrackFailureReason, TrackSource, YtDlpOptimizedClient,
+    },
     ui::{buttons, embeds},
 };
 
@@ -30,7 +56,7 @@ const RATE_LIMIT_WINDOW_SECS: u64 = 10;
 const RATE_LIMIT_MAX_COMMANDS: u32 = 5;
 
 /// Verifica si el usuario está rate limited
-fn check_rate_limit(guild_id: GuildId, user_id: UserId) -> bool {
+pub(crate) fn check_rate_limit(guild_id: GuildId, user_id: UserId) -> bool {
     let mut limiter = RATE_LIMITER.lock();
     let key = (guild_id, user_id);
     let now = Instant::now();
@@ -54,15 +80,210 @@ fn check_rate_limit(guild_id: GuildId, user_id: UserId) -> bool {
     }
 }
 
+// ===== AUTOCOMPLETE =====
+
+/// Última búsqueda de autocompletado lanzada por cada usuario
+static AUTOCOMPLETE_COOLDOWN: LazyLock<Mutex<HashMap<UserId, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Sugerencias `(título, url)` ya resueltas, por consulta normalizada
+pub(crate) static AUTOCOMPLETE_CACHE: LazyLock<LRUCache<String, Vec<(String, String)>>> =
+    LazyLock::new(|| LRUCache::new(500));
+
+const AUTOCOMPLETE_COOLDOWN_MS: u64 = 500;
+const AUTOCOMPLETE_CACHE_TTL: Duration = Duration::from_secs(600);
+const AUTOCOMPLETE_MIN_QUERY_LEN: usize = 3;
+/// Discord descarta la interacción si no se responde en 3 s
+const AUTOCOMPLETE_TIMEOUT: Duration = Duration::from_millis(2500);
+const AUTOCOMPLETE_MAX_CHOICES: usize = 5;
+/// Límite de Discord para el nombre y el valor de cada opción
+const AUTOCOMPLETE_MAX_CHOICE_LEN: usize = 100;
+
+/// `true` si el usuario lanzó otra búsqueda de autocompletado hace menos de 500 ms
+fn autocomplete_on_cooldown(user_id: UserId) -> bool {
+    let mut cooldowns = AUTOCOMPLETE_COOLDOWN.lock();
+    let now = Instant::now();
+    match cooldowns.get(&user_id) {
+        Some(last) if now.duration_since(*last) < Duration::from_millis(AUTOCOMPLETE_COOLDOWN_MS) => true,
+        _ => {
+            cooldowns.insert(user_id, now);
+            false
+        }
+    }
+}
+
+/// Convierte resultados de búsqueda en opciones de autocompletado válidas para Discord
+pub fn autocomplete_choices(tracks: &[TrackSource]) -> Vec<(String, String)> {
+    tracks
+        .iter()
+        .filter(|track| track.url().len() <= AUTOCOMPLETE_MAX_CHOICE_LEN)
+        .take(AUTOCOMPLETE_MAX_CHOICES)
+        .map(|track| {
+            let title = track.title();
+            let name = if title.chars().count() > AUTOCOMPLETE_MAX_CHOICE_LEN {
+                let truncated: String = title.chars().take(AUTOCOMPLETE_MAX_CHOICE_LEN - 1).collect();
+                format!("{}…", truncated)
+            } else {
+                title.to_string()
+            };
+            (name, track.url().to_string())
+        })
+        .collect()
+}
+
+/// Sugerencias de búsqueda para la opción `query` de `/play`.
+///
+/// Usa primero el caché de sugerencias; si hay que buscar, la búsqueda sigue en
+/// segundo plano aunque se pase del tiempo límite, para que la próxima tecla la
+/// encuentre cacheada.
+async fn play_suggestions(bot: &OpenMusicBot, user_id: UserId, partial: &str) -> Vec<(String, String)> {
+    let query = partial.trim().to_lowercase();
+    if query.chars().count() < AUTOCOMPLETE_MIN_QUERY_LEN || query.starts_with("http") {
+        return Vec::new();
+    }
+
+    let cached = AUTOCOMPLETE_CACHE.get(&query);
+    debug!(cache_hit = cached.is_some(), "💡 Autocompletado de /play");
+    if let Some(cached) = cached {
+        return cached;
+    }
+
+    if autocomplete_on_cooldown(user_id) {
+        return Vec::new();
+    }
+
+    let source_manager = crate::sources::SourceManager::with_config(&bot.settings());
+    let search = tokio::spawn(async move {
+        let results = source_manager.search_all(&query, AUTOCOMPLETE_MAX_CHOICES).await?;
+        let choices = autocomplete_choices(results.first().map(|r| r.tracks.as_slice()).unwrap_or_default());
+        AUTOCOMPLETE_CACHE.insert_with_ttl(query, choices.clone(), Some(AUTOCOMPLETE_CACHE_TTL));
+        anyhow::Ok(choices)
+    });
+
+    match tokio::time::timeout(AUTOCOMPLETE_TIMEOUT, search).await {
+        Ok(Ok(Ok(choices))) => choices,
+        Ok(Ok(Err(e))) => {
+            warn!("Error en búsqueda de autocompletado: {:?}", e);
+            Vec::new()
+        }
+        Ok(Err(e)) => {
+            warn!("Tarea de autocompletado abortada: {:?}", e);
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Responde a las interacciones de autocompletado (por ahora solo `/play query`)
+pub async fn handle_autocomplete(ctx: &Context, interaction: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let choices = match interaction.data.autocomplete() {
+        Some(focused) if interaction.data.name == "play" && focused.name == "query" => {
+            play_suggestions(bot, interaction.user.id, focused.value).await
+        }
+        Some(focused) if interaction.data.name == "eq" && focused.name == "frequency" => {
+            eq_frequency_suggestions(focused.value)
+        }
+        _ => Vec::new(),
+    };
+
+    let response = choices
+        .into_iter()
+        .fold(CreateAutocompleteResponse::new(), |response, (name, value)| {
+            response.add_string_choice(name, value)
+        });
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await?;
+
+    Ok(())
+}
+
 // ===== DJ ROLE VALIDATION =====
 
+/// Tiempo para confirmar un `/skip` antes de cancelarlo
+const SKIP_CONFIRM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Botones de `create_confirmation_buttons("skip")`
+const SKIP_CONFIRM_ID: &str = "confirm_skip";
+const SKIP_CANCEL_ID: &str = "cancel_action";
+
+/// Coincidencias que muestra `/find`
+const FIND_MAX_RESULTS: usize = 5;
+
+/// Tiempo durante el que los botones "Quitar" de `/find` siguen activos
+const FIND_REMOVE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Prefijo de los botones "Quitar" de `/find` (seguido del índice del resultado)
+const FIND_REMOVE_PREFIX: &str = "find_remove_";
+
 /// Comandos que requieren rol de DJ
 const DJ_REQUIRED_COMMANDS: &[&str] = &[
-    "stop", "clear", "skip", "remove", "jump", "volume", "equalizer"
+    "stop", "clear", "skip", "remove", "jump", "skipto-user", "volume", "equalizer", "eq", "speed", "queue-save", "queue-load", "session",
+    "queue-manage", "undo"
+];
+
+/// Comandos que agregan canciones a la cola y cuentan para el anti-spam de pedidos
+/// (una playlist completa cuenta como un solo pedido)
+const TRACK_REQUEST_COMMANDS: &[&str] = &[
+    "play", "add", "insert", "playlist", "radio", super::commands::PLAY_THIS_COMMAND
 ];
 
+/// Respuesta a un pedido de canciones mientras hay un quiz musical
+pub const QUIZ_ACTIVE_MESSAGE: &str = "🎧 Hay un quiz musical en curso: podrás pedir canciones cuando termine";
+
+/// Por qué la guild no acepta pedidos de canciones ahora mismo (sin contar el anti-spam)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackRequestBlock {
+    /// yt-dlp/ffmpeg no están disponibles
+    AudioBackendUnavailable,
+    /// Hay un quiz musical en curso
+    QuizActive,
+}
+
+/// Comprobación común a [`TRACK_REQUEST_COMMANDS`] y a los botones que encolan canciones
+pub fn track_request_block(audio_backend_available: bool, quizzes: &quiz::QuizSessions, guild_id: GuildId) -> Option<TrackRequestBlock> {
+    if !audio_backend_available {
+        Some(TrackRequestBlock::AudioBackendUnavailable)
+    } else if quizzes.is_active(guild_id) {
+        Some(TrackRequestBlock::QuizActive)
+    } else {
+        None
+    }
+}
+
+/// Comandos que controlan la reproducción: con `require_same_channel` exigen
+/// estar en el canal de voz del bot
+const PLAYBACK_CONTROL_COMMANDS: &[&str] = &[
+    "pause", "resume", "skip", "previous", "restart", "restart-playback", "seek", "ab-loop", "stop", "shuffle", "loop",
+    "clear", "volume", "equalizer", "eq", "speed", "effects", "remove", "jump", "skipto-user", "leave",
+    "queue-manage", "undo", "queue sort", "queue shuffle-remaining", "queue-sort", "move-channel", "summon"
+];
+
+/// Respuesta cuando `require_same_channel` rechaza un control de reproducción
+pub(crate) const SAME_CHANNEL_REQUIRED: &str = "🔇 Debes estar en mi canal de voz para controlar la reproducción";
+
+/// Subcomandos de `/queue` que cambian la cola. En las listas de permisos y en
+/// la auditoría cuentan como `queue <subcomando>`, no como `/queue` (solo lectura).
+const QUEUE_EDIT_SUBCOMMANDS: &[&str] = &["sort", "shuffle-remaining"];
+
+/// Comandos de solo lectura que no se registran en el log de auditoría
+const UNAUDITED_COMMANDS: &[&str] = &[
+    "queue", "nowplaying", "leaderboard", "top-rated", "stats", "chapters", "help", "health", "metrics", "notify", "find", "cache", "cachestats", "debug", "audit",
+    "export-config", "my-data", "test-proxy"
+];
+
+/// Nombre con el que se busca el comando en las listas de permisos y se audita
+fn permission_name(command: &CommandInteraction) -> String {
+    let name = command.data.name.as_str();
+    match CommandOptions::from_command(command).subcommand() {
+        Some((sub, _)) if name == "queue" && QUEUE_EDIT_SUBCOMMANDS.contains(&sub) => format!("{} {}", name, sub),
+        _ => name.to_string(),
+    }
+}
+
 /// Verifica si el usuario tiene permisos de DJ para el comando
-async fn has_dj_permission(
+pub(crate) async fn has_dj_permission(
     ctx: &Context,
     guild_id: GuildId,
     user_id: UserId,
@@ -74,6 +295,47 @@ async fn has_dj_permission(
         return true;
     }
     
+    is_dj(ctx, guild_id, user_id, bot).await
+}
+
+/// Verifica `require_same_channel` si el comando controla la reproducción
+pub(crate) async fn has_channel_permission(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    command_name: &str,
+    bot: &OpenMusicBot,
+) -> bool {
+    if !PLAYBACK_CONTROL_COMMANDS.contains(&command_name) {
+        return true;
+    }
+
+    is_in_bot_channel(ctx, bot, guild_id, user_id).await
+}
+
+/// Con `require_same_channel` activo, solo quien está en el canal de voz del
+/// bot controla la reproducción. DJs y administradores pueden hacerlo igual.
+pub(crate) async fn is_in_bot_channel(ctx: &Context, bot: &OpenMusicBot, guild_id: GuildId, user_id: UserId) -> bool {
+    if !bot.storage.lock().await.get_require_same_channel(guild_id.get()) {
+        return true;
+    }
+
+    let bot_channel = match bot.get_voice_handler(guild_id) {
+        Some(handler) => handler
+            .lock()
+            .await
+            .current_channel()
+            .map(|channel| ChannelId::new(channel.0.get())),
+        None => None,
+    };
+    let user_channel = get_user_voice_channel(ctx, guild_id, user_id).await.ok();
+
+    permissions::may_control_playback(bot_channel, user_channel)
+        || has_dj_role_or_admin(ctx, guild_id, user_id, bot).await
+}
+
+/// Verifica si el usuario es DJ (o admin). Sin rol de DJ configurado, todos lo son.
+pub(crate) async fn is_dj(ctx: &Context, guild_id: GuildId, user_id: UserId, bot: &OpenMusicBot) -> bool {
     // Obtener configuración del servidor
     let dj_role_id = {
         let storage = bot.storage.lock().await;
@@ -86,36 +348,118 @@ async fn has_dj_permission(
         None => return true,
     };
     
-    // Verificar si el usuario tiene el rol de DJ
-    if let Ok(member) = guild_id.member(&ctx.http, user_id).await {
-        if member.roles.contains(&dj_role) {
-            return true;
-        }
-        
-        // También verificar permisos de administrador
-        if let Some(guild) = ctx.cache.guild(guild_id) {
-            let permissions = guild.member_permissions(&member);
-            if permissions.administrator() {
-                return true;
-            }
-        }
+    // Verificar si el usuario tiene el rol de DJ (o es administrador)
+    match guild_id.member(&ctx.http, user_id).await {
+        Ok(member) => member.roles.contains(&dj_role) || is_admin(ctx, guild_id, &member),
+        Err(_) => false,
+    }
+}
+
+/// Verifica permisos de administrador con la guild en caché
+fn is_admin(ctx: &Context, guild_id: GuildId, member: &serenity::model::guild::Member) -> bool {
+    ctx.cache
+        .guild(guild_id)
+        .is_some_and(|guild| guild.member_permissions(member).administrator())
+}
+
+/// Como [`is_dj`], pero sin rol de DJ configurado solo cuentan los administradores
+async fn has_dj_role_or_admin(ctx: &Context, guild_id: GuildId, user_id: UserId, bot: &OpenMusicBot) -> bool {
+    let has_dj_role = bot.storage.lock().await.get_dj_role(guild_id.get()).is_some();
+    if has_dj_role {
+        return is_dj(ctx, guild_id, user_id, bot).await;
+    }
+
+    match guild_id.member(&ctx.http, user_id).await {
+        Ok(member) => is_admin(ctx, guild_id, &member),
+        Err(_) => false,
     }
-    
-    false
+}
+
+/// Anti-spam de pedidos de canciones: cuenta el pedido y, si el usuario superó
+/// su límite por minuto, devuelve el embed que explica la espera. DJs y
+/// administradores no tienen límite.
+pub(crate) async fn check_request_spam(
+    ctx: &Context,
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    user_id: UserId,
+    username: &str,
+    command_name: &str,
+) -> Option<CreateEmbed> {
+    let max_per_minute = bot.storage.lock().await.get_max_requests_per_minute(guild_id.get());
+    let throttled = bot.spam_detector.check(guild_id, user_id, max_per_minute).err()?;
+    if has_dj_role_or_admin(ctx, guild_id, user_id, bot).await {
+        return None;
+    }
+
+    if throttled.newly_blocked {
+        warn!(
+            "🚫 {} superó {} pedidos por minuto en guild {}, bloqueado {}s",
+            username,
+            max_per_minute,
+            guild_id,
+            throttled.wait.as_secs()
+        );
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            user_id: user_id.get(),
+            username: username.to_string(),
+            command: "anti-spam".to_string(),
+            details: AuditDetails {
+                query: Some(format!("/{}", command_name)),
+                success: false,
+                ..Default::default()
+            },
+        };
+        bot.log_audit_event(guild_id, entry).await;
+    }
+
+    Some(embeds::create_request_throttled_embed(max_per_minute, throttled.wait))
 }
 
 /// Maneja comandos slash
+///
+/// Todo lo que se loguea durante el comando queda dentro de un span con un
+/// `request_id` único, para correlacionar las líneas de una misma interacción.
 pub async fn handle_command(
     ctx: &Context,
     command: CommandInteraction,
     bot: &OpenMusicBot,
 ) -> Result<()> {
+    let span = info_span!(
+        "command",
+        request_id = %Uuid::new_v4(),
+        guild_id = tracing::field::Empty,
+        user_id = command.user.id.get(),
+        command = %command.data.name,
+        track_url = tracing::field::Empty,
+        duration_ms = tracing::field::Empty,
+    );
+    if let Some(guild_id) = command.guild_id {
+        span.record("guild_id", guild_id.get());
+    }
+
+    let command_name = command.data.name.clone();
+    let started = Instant::now();
+    let result = run_command(ctx, command, bot).instrument(span.clone()).await;
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    span.record("duration_ms", duration_ms);
+    crate::telemetry::record_command_duration(&command_name, duration_ms);
+    span.in_scope(|| debug!(duration_ms, ok = result.is_ok(), "⏱️ Comando completado"));
+
+    result
+}
+
+/// Verificaciones comunes (rate limit, DJ, auditoría) y despacho del comando
+async fn run_command(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command
         .guild_id
         .ok_or_else(|| anyhow::anyhow!("Comando usado fuera de un servidor"))?;
 
     let user_id = command.user.id;
     let command_name = command.data.name.as_str();
+    let permission_name = permission_name(&command);
 
     // ===== RATE LIMITING CHECK =====
     if check_rate_limit(guild_id, user_id) {
@@ -133,7 +477,7 @@ pub async fn handle_command(
     }
 
     // ===== DJ ROLE CHECK =====
-    if !has_dj_permission(ctx, guild_id, user_id, command_name, bot).await {
+    if !has_dj_permission(ctx, guild_id, user_id, &permission_name, bot).await {
         command
             .create_response(
                 &ctx.http,
@@ -147,12 +491,102 @@ pub async fn handle_command(
         return Ok(());
     }
 
+    // ===== MISMO CANAL DE VOZ =====
+    if !has_channel_permission(ctx, guild_id, user_id, &permission_name, bot).await {
+        command
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(SAME_CHANNEL_REQUIRED)
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    // ===== BACKEND DE AUDIO / QUIZ EN CURSO =====
+    let request_block = TRACK_REQUEST_COMMANDS
+        .contains(&command_name)
+        .then(|| track_request_block(bot.audio_backend_available(), &bot.quizzes, guild_id))
+        .flatten();
+    if let Some(block) = request_block {
+        let message = match block {
+            TrackRequestBlock::AudioBackendUnavailable => {
+                CreateInteractionResponseMessage::new().embed(embeds::create_audio_backend_unavailable_embed())
+            }
+            TrackRequestBlock::QuizActive => CreateInteractionResponseMessage::new().content(QUIZ_ACTIVE_MESSAGE),
+        };
+        command
+            .create_response(&ctx.http, CreateInteractionResponse::Message(message.ephemeral(true)))
+            .await?;
+        return Ok(());
+    }
+
+    // ===== ANTI-SPAM DE PEDIDOS =====
+    if TRACK_REQUEST_COMMANDS.contains(&command_name) {
+        if let Some(embed) = check_request_spam(ctx, bot, guild_id, user_id, &command.user.name, command_name).await {
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new().embed(embed).ephemeral(true),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
     info!(
         "📝 Comando /{} usado por {} en guild {}",
         command_name, command.user.name, guild_id
     );
 
-    match command_name {
+    if command.data.kind == CommandType::Message {
+        if command_name == super::commands::PLAY_THIS_COMMAND {
+            handle_play_this(ctx, command, bot).await?;
+        }
+        return Ok(());
+    }
+
+    if UNAUDITED_COMMANDS.contains(&permission_name.as_str()) {
+        return dispatch_command(ctx, command, bot).await;
+    }
+
+    // Auditoría: estado de la cola antes y después del comando
+    let options = CommandOptions::from_command(&command);
+    let query = options.get_str("query").or_else(|| options.get_str("url")).map(str::to_string);
+    let name = permission_name;
+    let username = command.user.name.clone();
+    let queue_before = bot.player.get_or_create_queue(guild_id).read().len();
+
+    let result = dispatch_command(ctx, command, bot).await;
+    let queue_after = bot.player.get_or_create_queue(guild_id).read().len();
+
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        user_id: user_id.get(),
+        username,
+        command: name,
+        details: AuditDetails {
+            query,
+            track_url: bot.player.get_now_playing(guild_id).map(|track| track.url().to_string()),
+            queue_before: Some(queue_before),
+            queue_after: Some(queue_after),
+            volume: bot.player.get_volume(guild_id).await.map(|v| (v * 100.0).round() as u32),
+            success: result.is_ok(),
+        },
+    };
+    bot.log_audit_event(guild_id, entry).await;
+
+    result
+}
+
+/// Ejecuta el handler de un comando slash
+async fn dispatch_command(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    match command.data.name.as_str() {
         "play" => handle_play(ctx, command, bot).await?,
         "pause" => handle_pause(ctx, command, bot).await?,
         "resume" => handle_resume(ctx, command, bot).await?,
@@ -160,24 +594,60 @@ pub async fn handle_command(
         "stop" => handle_stop(ctx, command, bot).await?,
         "leave" => handle_leave(ctx, command, bot).await?,
         "nowplaying" => handle_nowplaying(ctx, command, bot).await?,
+        "leaderboard" => handle_leaderboard(ctx, command, bot).await?,
+        "top-rated" => handle_top_rated(ctx, command, bot).await?,
+        "stats" => super::guild_stats::handle_stats_command(ctx, command, bot).await?,
+        "quiz" => handle_quiz(ctx, command, bot).await?,
+        "queue-save" => handle_queue_save(ctx, command, bot).await?,
+        "queue-load" => handle_queue_load(ctx, command, bot).await?,
+        "playlists" => handle_playlists(ctx, command, bot).await?,
+        "prefix" => handle_prefix(ctx, command, bot).await?,
+        "settings" => handle_settings(ctx, command, bot).await?,
         "volume" => handle_volume(ctx, command, bot).await?,
         "queue" => handle_queue(ctx, command, bot).await?,
         "search" => super::search::handle_search_command(ctx, command, bot).await?,
+        "queue-manage" => super::queue_manage::handle_queue_manage_command(ctx, command, bot).await?,
         "shuffle" => handle_shuffle(ctx, command, bot).await?,
+        "undo" => handle_undo(ctx, command, bot).await?,
         "loop" => handle_loop(ctx, command, bot).await?,
         "join" => handle_join(ctx, command, bot).await?,
+        "move-channel" | "summon" => handle_move_channel(ctx, command, bot).await?,
         "equalizer" => handle_equalizer(ctx, command, bot).await?,
+        "eq" => handle_eq(ctx, command, bot).await?,
+        "effects" => handle_effects(ctx, command, bot).await?,
+        "speed" => handle_speed(ctx, command, bot).await?,
+        "session" => handle_session(ctx, command, bot).await?,
         "clear" => handle_clear(ctx, command, bot).await?,
         "playlist" => handle_playlist(ctx, command, bot).await?,
+        "radio" => handle_radio(ctx, command, bot).await?,
         "previous" => handle_previous(ctx, command, bot).await?,
         "restart" => handle_restart(ctx, command, bot).await?,
+        "restart-playback" => handle_restart_playback(ctx, command, bot).await?,
         "seek" => handle_seek(ctx, command, bot).await?,
+        "ab-loop" => handle_ab_loop(ctx, command, bot).await?,
+        "chapters" => handle_chapters(ctx, command, bot).await?,
         "add" => handle_add(ctx, command, bot).await?,
+        "insert" => handle_insert(ctx, command, bot).await?,
         "remove" => handle_remove(ctx, command, bot).await?,
         "jump" => handle_jump(ctx, command, bot).await?,
+        "skipto-user" => handle_skipto_user(ctx, command, bot).await?,
+        "find" => handle_find(ctx, command, bot).await?,
+        "queue-sort" => handle_queue_sort(ctx, command, bot).await?,
+        "notify" => handle_notify(ctx, command, bot).await?,
+        "my-data" => handle_my_data(ctx, command, bot).await?,
         "help" => handle_help(ctx, command, bot).await?,
         "health" => handle_health(ctx, command, bot).await?,
         "metrics" => handle_metrics(ctx, command, bot).await?,
+        "cache" => handle_cache(ctx, command, bot).await?,
+        "clearcache" => handle_clearcache(ctx, command, bot).await?,
+        "cachestats" => handle_cachestats(ctx, command, bot).await?,
+        "config-reload" => handle_config_reload(ctx, command, bot).await?,
+        "audit" => handle_audit(ctx, command, bot).await?,
+        "export-config" => handle_export_config(ctx, command, bot).await?,
+        "import-config" => handle_import_config(ctx, command, bot).await?,
+        "debug" => handle_debug(ctx, command, bot).await?,
+        "test-proxy" => handle_test_proxy(ctx, command, bot).await?,
+        "cookies" => handle_cookies(ctx, command, bot).await?,
         _ => {
             command
                 .create_response(
@@ -196,10 +666,33 @@ pub async fn handle_command(
 }
 
 /// Maneja interacciones con componentes (botones, menús, etc.)
+///
+/// Igual que los comandos slash, todo queda dentro de un span con `request_id`,
+/// guild, usuario y el `custom_id` del componente como `command`.
 pub async fn handle_component(
     ctx: &Context,
     component: ComponentInteraction,
     bot: &OpenMusicBot,
+) -> Result<()> {
+    let span = info_span!(
+        "component",
+        request_id = %Uuid::new_v4(),
+        guild_id = tracing::field::Empty,
+        user_id = component.user.id.get(),
+        command = %component.data.custom_id,
+    );
+    if let Some(guild_id) = component.guild_id {
+        span.record("guild_id", guild_id.get());
+    }
+
+    dispatch_component(ctx, component, bot).instrument(span).await
+}
+
+/// Despacha un componente según su `custom_id`
+async fn dispatch_component(
+    ctx: &Context,
+    component: ComponentInteraction,
+    bot: &OpenMusicBot,
 ) -> Result<()> {
     let guild_id = component
         .guild_id
@@ -223,6 +716,22 @@ pub async fn handle_component(
                 }
             }
         }
+        "chapter_select" => handle_chapter_selection(ctx, &component, bot).await?,
+        // Los atienden los collectors de `handle_skip_confirm` y `handle_find`
+        SKIP_CONFIRM_ID | SKIP_CANCEL_ID => {}
+        id if id.starts_with(FIND_REMOVE_PREFIX) => {}
+        id if id.starts_with(super::rating::RATING_PREFIX) => {
+            super::rating::handle_rating_button(ctx, &component, bot, guild_id).await?;
+        }
+        id if id.starts_with(super::leaderboard::LEADERBOARD_PREFIX) => {
+            super::leaderboard::handle_leaderboard_page(ctx, &component, bot, guild_id).await?;
+        }
+        id if id.starts_with(super::queue_manage::QUEUE_MANAGE_PREFIX) => {
+            super::queue_manage::handle_queue_manage_select(ctx, &component, bot, guild_id).await?;
+        }
+        id if id.starts_with(super::quiz::QUIZ_PREFIX) => {
+            super::quiz::handle_quiz_button(ctx, &component, bot, guild_id).await?;
+        }
         // Delegar todos los botones musicales al handler especializado
         id if id.starts_with("music_") => {
             crate::ui::buttons::handle_music_component(ctx, &component, bot).await?;
@@ -248,74 +757,471 @@ pub async fn handle_component(
     Ok(())
 }
 
-// Handlers específicos para cada comando
+/// Informa al usuario de un fallo permanente de extracción (video privado,
+/// eliminado, restringido por edad, etc.) editando la respuesta diferida.
+/// Otros errores se propagan.
+async fn report_extraction_error(guard: &InteractionGuard<'_>, error: anyhow::Error) -> Result<()> {
+    let embed = if let Some(restriction) = error.downcast_ref::<RestrictedVideoError>() {
+        embeds::create_restricted_video_embed(*restriction, YtDlpOptimizedClient::find_cookies_path().is_some())
+    } else if let Some(permanent) = error.downcast_ref::<crate::sources::PermanentExtractionError>() {
+        embeds::create_error_embed("No se pudo reproducir", &permanent.to_string())
+    } else if let Some(timeout) = error.downcast_ref::<crate::sources::ExtractionTimeoutError>() {
+        embeds::create_error_embed("Tiempo agotado", &timeout.to_string())
+    } else if let Some(blocked) = error.downcast_ref::<BlockedTrackError>() {
+        embeds::create_blocked_track_embed(blocked)
+    } else {
+        return Err(error);
+    };
 
-async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
-    let guild_id = command.guild_id.unwrap();
-    let query = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "query")
-        .and_then(|opt| opt.value.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Query no proporcionado"))?;
+    guard.finish(CommandReply::embed(embed), vec![]).await
+}
 
-    // Defer la respuesta inmediatamente para evitar timeout
-    if let Err(e) = command
+// ===== RESPUESTAS COMPARTIDAS (SLASH Y TEXTO) =====
+
+/// Respuesta de un comando, independiente de si llegó como slash o como texto con prefijo
+#[derive(Clone)]
+pub(crate) enum CommandReply {
+    /// Mensaje visible para todos
+    Content(String),
+    /// Aviso o error: efímero en slash, mensaje normal en texto
+    Notice(String),
+    Embed(Box<CreateEmbed>),
+}
+
+impl CommandReply {
+    pub(crate) fn content(text: impl Into<String>) -> Self {
+        Self::Content(text.into())
+    }
+
+    pub(crate) fn notice(text: impl Into<String>) -> Self {
+        Self::Notice(text.into())
+    }
+
+    pub(crate) fn embed(embed: CreateEmbed) -> Self {
+        Self::Embed(Box::new(embed))
+    }
+
+    fn into_interaction_message(self) -> CreateInteractionResponseMessage {
+        let message = CreateInteractionResponseMessage::new();
+        match self {
+            Self::Content(text) => message.content(text),
+            Self::Notice(text) => message.content(text).ephemeral(true),
+            Self::Embed(embed) => message.embed(*embed),
+        }
+    }
+
+    fn into_edit_response(self) -> EditInteractionResponse {
+        let edit = EditInteractionResponse::new();
+        match self {
+            Self::Content(text) | Self::Notice(text) => edit.content(text),
+            Self::Embed(embed) => edit.embed(*embed),
+        }
+    }
+
+    pub(crate) fn into_message(self) -> CreateMessage {
+        let message = CreateMessage::new();
+        match self {
+            Self::Content(text) | Self::Notice(text) => message.content(text),
+            Self::Embed(embed) => message.embed(*embed),
+        }
+    }
+}
+
+/// Responde a una interacción slash con un `CommandReply`
+async fn respond(ctx: &Context, command: &CommandInteraction, reply: CommandReply) -> Result<()> {
+    command
         .create_response(
             &ctx.http,
-            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+            CreateInteractionResponse::Message(reply.into_interaction_message()),
         )
-        .await
-    {
-        warn!("Error al hacer defer de la respuesta: {}", e);
-        // Intentar responder con error
-        let _ = command
-            .create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content("❌ Error al procesar el comando")
-                        .ephemeral(true),
-                ),
-            )
-            .await;
-        return Err(e.into());
-    }
+        .await?;
+    Ok(())
+}
 
-    // Verificar que el usuario esté en un canal de voz
-    let voice_channel_id = match get_user_voice_channel(ctx, guild_id, command.user.id).await {
-        Ok(channel_id) => channel_id,
-        Err(e) => {
-            warn!("Usuario no está en un canal de voz: {}", e);
-            let _ = command
-                .edit_response(
-                    &ctx.http,
-                    serenity::builder::EditInteractionResponse::new()
-                        .content("❌ Debes estar en un canal de voz para usar este comando"),
-                )
-                .await;
-            return Err(e);
+// ===== RESPUESTAS DIFERIDAS =====
+
+/// Vida del token de una interacción: pasado este tiempo Discord ya no acepta
+/// ediciones de la respuesta diferida ("Unknown Webhook").
+pub const INTERACTION_TOKEN_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// `true` si el token de una interacción diferida en `deferred_at` ya caducó
+pub fn interaction_token_expired(deferred_at: Instant, now: Instant) -> bool {
+    now.duration_since(deferred_at) >= INTERACTION_TOKEN_TTL
+}
+
+/// `true` si un error HTTP de Discord indica que la interacción ya no existe:
+/// 404, "Unknown Webhook" (10015), "Unknown interaction" (10062) o
+/// "Invalid Webhook Token" (50027).
+pub fn is_expired_interaction_status(status: u16, discord_code: isize) -> bool {
+    status == 404 || matches!(discord_code, 10015 | 10062 | 50027)
+}
+
+fn is_expired_interaction_error(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(serenity::http::HttpError::UnsuccessfulRequest(response)) => {
+            is_expired_interaction_status(response.status_code.as_u16(), response.error.code)
         }
-    };
+        _ => false,
+    }
+}
 
-    // Conectar al canal de voz si no está conectado
-    if bot.get_voice_handler(guild_id).is_none() {
-        bot.join_voice_channel(ctx, guild_id, voice_channel_id)
-            .await?;
+/// Respuesta diferida de un comando slash que sobrevive a la caducidad del token.
+///
+/// Si editar la respuesta falla porque la interacción expiró (p. ej. una
+/// playlist enorme que tarda más de 15 minutos), el resultado final se envía
+/// como mensaje nuevo en el canal original mencionando al usuario.
+pub(crate) struct InteractionGuard<'a> {
+    ctx: &'a Context,
+    command: &'a CommandInteraction,
+    deferred_at: Instant,
+    expired: AtomicBool,
+}
+
+impl<'a> InteractionGuard<'a> {
+    /// Envuelve una interacción que se acaba de diferir
+    pub(crate) fn new(ctx: &'a Context, command: &'a CommandInteraction) -> Self {
+        Self {
+            ctx,
+            command,
+            deferred_at: Instant::now(),
+            expired: AtomicBool::new(false),
+        }
     }
 
-    // Buscar y agregar a la cola con sistema optimizado
-    let is_url = query.starts_with("http");
-    // Detectar playlist por el parámetro `list=` (cubre tanto
-    // youtube.com/playlist?list=... como watch?v=...&list=..., la forma más
-    // común de compartir una lista desde un video).
-    let has_list = query.contains("list=");
-    let is_playlist = is_url && has_list;
-    // Tope de 15 temas al cargar una lista vía /play (incluye mixes/radios
-    // infinitos list=RD/UL). Para cargar listas completas está el comando
-    // /playlist, que no aplica este tope.
-    let playlist_limit: Option<usize> = Some(15);
+    /// Intenta editar la respuesta; `false` si la interacción ya expiró
+    async fn try_edit(&self, edit: EditInteractionResponse) -> Result<bool> {
+        if self.expired.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+
+        let expired = if interaction_token_expired(self.deferred_at, Instant::now()) {
+            true
+        } else {
+            match self.command.edit_response(&self.ctx.http, edit).await {
+                Ok(_) => return Ok(true),
+                Err(e) if is_expired_interaction_error(&e) => true,
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        if expired && !self.expired.swap(true, Ordering::Relaxed) {
+            warn!(
+                "⏰ Interacción {} (/{}) expirada tras {:?}; se responderá en el canal",
+                self.command.id,
+                self.command.data.name,
+                self.deferred_at.elapsed()
+            );
+        }
+        Ok(false)
+    }
+
+    /// Actualización intermedia (progreso): si la interacción expiró se omite
+    pub(crate) async fn update(&self, reply: CommandReply, components: Vec<CreateActionRow>) -> Result<()> {
+        self.try_edit(reply.into_edit_response().components(components)).await?;
+        Ok(())
+    }
+
+    /// Respuesta final: si la interacción expiró se publica en el canal original
+    pub(crate) async fn finish(&self, reply: CommandReply, components: Vec<CreateActionRow>) -> Result<()> {
+        if self
+            .try_edit(reply.clone().into_edit_response().components(components.clone()))
+            .await?
+        {
+            return Ok(());
+        }
+
+        // El usuario puede haber salido del canal (o perdido acceso a él) desde
+        // el defer: la mención le avisa igualmente y un fallo aquí no es un error
+        // del comando.
+        let mention = format!("<@{}>", self.command.user.id);
+        let message = match reply {
+            CommandReply::Content(text) | CommandReply::Notice(text) => {
+                CreateMessage::new().content(format!("{} {}", mention, text))
+            }
+            CommandReply::Embed(embed) => CreateMessage::new().content(mention).embed(*embed),
+        }
+        .components(components);
+        if let Err(e) = self.command.channel_id.send_message(&self.ctx.http, message).await {
+            warn!(
+                "No se pudo enviar la respuesta de la interacción {} (/{}) al canal {}: {:?}",
+                self.command.id, self.command.data.name, self.command.channel_id, e
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Tiempo mínimo entre dos ediciones de progreso de un comando largo
+pub const PROGRESS_EDIT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Decide cuándo toca editar el progreso: como mucho una vez por `interval`,
+/// sin importar cuántos avances haya entre medio
+#[derive(Debug)]
+pub struct EditThrottle {
+    interval: Duration,
+    last_edit: Option<Instant>,
+}
+
+impl EditThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last_edit: None }
+    }
+
+    /// `true` si en `now` ya pasó el intervalo (siempre la primera vez) y lo
+    /// cuenta como edición
+    pub fn ready(&mut self, now: Instant) -> bool {
+        if self.last_edit.is_some_and(|last| now.saturating_duration_since(last) < self.interval) {
+            return false;
+        }
+        self.last_edit = Some(now);
+        true
+    }
+}
+
+/// Progreso de una respuesta diferida limitado por [`EditThrottle`].
+///
+/// Los avances que llegan antes de tiempo no se envían: el siguiente que toque
+/// ya muestra el estado más reciente, así una playlist de 500 canciones hace
+/// unas pocas ediciones en vez de una cada pocas canciones. La respuesta final
+/// se sigue enviando con [`InteractionGuard::finish`].
+pub(crate) struct ThrottledEditor<'g, 'a> {
+    guard: &'g InteractionGuard<'a>,
+    throttle: EditThrottle,
+}
+
+impl<'g, 'a> ThrottledEditor<'g, 'a> {
+    pub(crate) fn new(guard: &'g InteractionGuard<'a>, interval: Duration) -> Self {
+        Self {
+            guard,
+            throttle: EditThrottle::new(interval),
+        }
+    }
+
+    /// Edita con el progreso que arma `build` si ya pasó el intervalo; si no,
+    /// no hace nada (ni siquiera arma el embed)
+    pub(crate) async fn update(&mut self, build: impl FnOnce() -> (CommandReply, Vec<CreateActionRow>)) -> Result<()> {
+        if !self.throttle.ready(Instant::now()) {
+            return Ok(());
+        }
+        let (reply, components) = build();
+        self.guard.update(reply, components).await
+    }
+}
+
+/// Resuelve una URL o búsqueda a un único track (sin playlists).
+///
+/// Falla con [`BlockedTrackError`] si el filtro de contenido de la guild lo rechaza.
+async fn resolve_track(
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    query: &str,
+    requested_by: UserId,
+) -> Result<TrackSource> {
+    let source_manager = bot.source_manager(guild_id).await;
+
+    let track = if query.starts_with("http") {
+        // Es una URL directa de video individual
+        bot.run_extraction(guild_id, source_manager.get_track_from_url(query, requested_by))
+            .await?
+    } else {
+        // Es una búsqueda - usar sistema optimizado
+        info!("🔍 Buscando canción: {}", query);
+
+        let search_results = bot.run_extraction(guild_id, source_manager.search_all(query, 5)).await?;
+        let best_result = best_search_result(bot, guild_id, &search_results, query)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No se encontraron resultados para: {}", query))?;
+
+        info!("✅ Seleccionado automáticamente: {}", best_result.title());
+        best_result.with_requested_by(requested_by)
+    };
+
+    check_content_filter(bot, guild_id, &track).await?;
+    Ok(track)
+}
+
+/// Rechaza la canción si el filtro de contenido de la guild la bloquea
+async fn check_content_filter(bot: &OpenMusicBot, guild_id: GuildId, track: &TrackSource) -> Result<(), BlockedTrackError> {
+    bot.content_filter(guild_id).await.check(track).inspect_err(|reason| {
+        info!("🚫 Bloqueada {} en guild {}: {}", track.title(), guild_id, reason);
+    })
+}
+
+/// Elige el mejor candidato de una búsqueda según las preferencias de ranking de la guild
+async fn best_search_result(
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    search_results: &[SearchResult],
+    query: &str,
+) -> Option<TrackSource> {
+    let candidates = search_results.first()?.tracks.as_slice();
+    let prefs = bot.storage.lock().await.get_search_preferences(guild_id.get());
+    ranking::best_match(candidates, query, &prefs)
+}
+
+/// Lógica de `play` para una sola canción, usada por los comandos de texto.
+///
+/// Las playlists se cargan con `/play` o `/playlist`, que muestran el progreso.
+pub(crate) async fn play_core(
+    ctx: &Context,
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    user_id: UserId,
+    query: &str,
+) -> Result<CommandReply> {
+    if query.starts_with("http") && query.contains("list=") {
+        return Ok(CommandReply::notice("📋 Para cargar playlists usa `/play` o `/playlist`"));
+    }
+
+    let voice_channel_id = match get_user_voice_channel(ctx, guild_id, user_id).await {
+        Ok(channel_id) => channel_id,
+        Err(_) => return Ok(CommandReply::notice("❌ Debes estar en un canal de voz para usar este comando")),
+    };
+
+    if bot.get_voice_handler(guild_id).is_none() {
+        bot.join_voice_channel(ctx, guild_id, voice_channel_id).await?;
+    }
+
+    let track_source = match resolve_track(bot, guild_id, query, user_id).await {
+        Ok(track) => track,
+        Err(e) => {
+            if let Some(restriction) = e.downcast_ref::<RestrictedVideoError>() {
+                let embed = embeds::create_restricted_video_embed(
+                    *restriction,
+                    YtDlpOptimizedClient::find_cookies_path().is_some(),
+                );
+                return Ok(CommandReply::embed(embed));
+            }
+            if let Some(permanent) = e.downcast_ref::<crate::sources::PermanentExtractionError>() {
+                return Ok(CommandReply::notice(format!("❌ {}", permanent)));
+            }
+            if let Some(blocked) = e.downcast_ref::<BlockedTrackError>() {
+                return Ok(CommandReply::embed(embeds::create_blocked_track_embed(blocked)));
+            }
+            return Err(e);
+        }
+    };
+
+    let handler = bot
+        .get_voice_handler(guild_id)
+        .ok_or_else(|| anyhow::anyhow!("No hay conexión de voz activa"))?;
+    bot.player.play(guild_id, track_source.clone(), handler).await?;
+
+    let settings = bot.guild_settings(guild_id).await;
+    Ok(CommandReply::embed(embeds::create_track_added_embed(&track_source, Some(&settings))))
+}
+
+/// Primer enlace reproducible (YouTube o Tidal) dentro del texto de un mensaje
+pub fn find_playable_url(content: &str) -> Option<String> {
+    content
+        .split_whitespace()
+        // Discord permite `<url>` para no generar preview; también se limpian signos finales
+        .map(|word| word.trim_start_matches('<').trim_end_matches(['>', ')', ',', '.', '!', '?']))
+        .filter(|word| word.starts_with("http"))
+        .find(|url| {
+            YtDlpOptimizedClient::is_youtube_url(url) || crate::sources::tidal::TidalLink::parse(url).is_some()
+        })
+        .map(str::to_string)
+}
+
+/// Menú contextual "▶ Play This": reproduce el enlace del mensaje seleccionado
+async fn handle_play_this(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let content = match command.data.target() {
+        Some(ResolvedTarget::Message(message)) => message.content.clone(),
+        _ => String::new(),
+    };
+
+    let Some(url) = find_playable_url(&content) else {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Sin enlace",
+            "El mensaje no contiene un enlace de YouTube o Tidal",
+        )
+        .await;
+    };
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+        )
+        .await?;
+
+    info!("▶️ Reproduciendo enlace de mensaje: {} por {}", url, command.user.name);
+
+    let reply = play_core(ctx, bot, guild_id, command.user.id, &url)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Error reproduciendo enlace de mensaje: {:?}", e);
+            CommandReply::notice(format!("❌ {}", e))
+        });
+
+    command.edit_response(&ctx.http, reply.into_edit_response()).await?;
+    Ok(())
+}
+
+// Handlers específicos para cada comando
+
+async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let query = CommandOptions::from_command(&command).require_str("query")?;
+
+    // Defer la respuesta inmediatamente para evitar timeout
+    if let Err(e) = command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await
+    {
+        warn!("Error al hacer defer de la respuesta: {}", e);
+        // Intentar responder con error
+        let _ = command
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("❌ Error al procesar el comando")
+                        .ephemeral(true),
+                ),
+            )
+            .await;
+        return Err(e.into());
+    }
+    let guard = InteractionGuard::new(ctx, &command);
+
+    // Verificar que el usuario esté en un canal de voz
+    let voice_channel_id = match get_user_voice_channel(ctx, guild_id, command.user.id).await {
+        Ok(channel_id) => channel_id,
+        Err(e) => {
+            warn!("Usuario no está en un canal de voz: {}", e);
+            let _ = guard
+                .finish(CommandReply::content("❌ Debes estar en un canal de voz para usar este comando"), vec![])
+                .await;
+            return Err(e);
+        }
+    };
+
+    // Conectar al canal de voz si no está conectado
+    if bot.get_voice_handler(guild_id).is_none() {
+        bot.join_voice_channel(ctx, guild_id, voice_channel_id)
+            .await?;
+    }
+
+    // Buscar y agregar a la cola con sistema optimizado
+    let is_url = query.starts_with("http");
+    // Detectar playlist por el parámetro `list=` (cubre tanto
+    // youtube.com/playlist?list=... como watch?v=...&list=..., la forma más
+    // común de compartir una lista desde un video).
+    let has_list = query.contains("list=");
+    let is_playlist = is_url && has_list;
+    // Tope de 15 temas al cargar una lista vía /play (incluye mixes/radios
+    // infinitos list=RD/UL). Para cargar listas completas está el comando
+    // /playlist, que no aplica este tope.
+    let playlist_limit: Option<usize> = Some(15);
 
     if is_playlist {
         // Es una playlist de YouTube
@@ -325,7 +1231,8 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
         // el resto en segundo plano. No se espera a listar toda la lista, así la
         // música aparece casi al instante sin importar el tamaño de la playlist.
         let cookies = YtDlpOptimizedClient::cookies_working_copy();
-        let mut child = match YtDlpOptimizedClient::spawn_playlist_stream(query, cookies.as_deref(), playlist_limit) {
+        let proxy = bot.ytdlp_proxy(guild_id).await;
+        let mut child = match YtDlpOptimizedClient::spawn_playlist_stream(query, cookies.as_deref(), proxy.as_deref(), playlist_limit) {
             Ok(c) => c,
             Err(e) => {
                 warn!("No se pudo lanzar yt-dlp para playlist: {}", e);
@@ -340,17 +1247,30 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
 
         let queue = bot.player.get_or_create_queue(guild_id);
         let user_id = command.user.id;
+        let filter = bot.content_filter(guild_id).await;
 
-        // Leer hasta el primer track válido del stream
+        // Leer hasta el primer track válido (y permitido) del stream
         let mut first_track: Option<TrackSource> = None;
+        let mut blocked = 0usize;
         while let Ok(Some(line)) = lines.next_line().await {
             if let Some(t) = YtDlpOptimizedClient::parse_playlist_line(&line, user_id) {
+                if filter.check(&t).is_err() {
+                    blocked += 1;
+                    continue;
+                }
                 first_track = Some(t);
                 break;
             }
         }
         let first_track = match first_track {
             Some(t) => t,
+            None if blocked > 0 => {
+                let embed = embeds::create_error_embed(
+                    "Playlist Vacía",
+                    "Todas las canciones de la playlist están bloqueadas en este servidor",
+                );
+                return guard.finish(CommandReply::embed(embed), vec![]).await;
+            }
             None => anyhow::bail!("La playlist está vacía o no se pudo acceder"),
         };
 
@@ -368,15 +1288,10 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
         }
 
         // Responder de inmediato con el primer track (el resto se carga detrás)
-        let embed = embeds::create_track_added_embed(&first_track);
+        let settings = bot.guild_settings(guild_id).await;
+        let embed = embeds::create_track_added_embed(&first_track, Some(&settings));
         let playlist_buttons = crate::ui::buttons::create_playlist_buttons();
-        use serenity::builder::EditInteractionResponse;
-        command
-            .edit_response(&ctx.http, EditInteractionResponse::new()
-                .embed(embed)
-                .components(playlist_buttons)
-            )
-            .await?;
+        guard.finish(CommandReply::embed(embed), playlist_buttons).await?;
 
         // Encolar el RESTO de la playlist en segundo plano
         let queue_bg = queue.clone();
@@ -384,12 +1299,16 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
             let mut count = 1usize;
             while let Ok(Some(line)) = lines.next_line().await {
                 if let Some(t) = YtDlpOptimizedClient::parse_playlist_line(&line, user_id) {
+                    if filter.check(&t).is_err() {
+                        blocked += 1;
+                        continue;
+                    }
                     queue_bg.write().add_track(t).ok();
                     count += 1;
                 }
             }
             let _ = child.wait().await;
-            info!("📋 Playlist cargada completa: {} canciones encoladas", count);
+            info!("📋 Playlist cargada completa: {} canciones encoladas, {} bloqueadas", count, blocked);
         });
 
         return Ok(());
@@ -397,30 +1316,11 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
     }
     
     // Manejar canciones individuales (URL o búsqueda) con sistema optimizado
-    let mut track_source = if is_url {
-        // Es una URL directa de video individual
-        let source_manager = crate::sources::SourceManager::new();
-        source_manager.get_track_from_url(query, command.user.id).await?
-    } else {
-        // Es una búsqueda - usar sistema optimizado
-        info!("🔍 Buscando canción: {}", query);
-        
-        let source_manager = crate::sources::SourceManager::new();
-        let search_results = source_manager.search_all(query, 5).await?;
-        
-        if search_results.is_empty() || search_results[0].tracks.is_empty() {
-            anyhow::bail!("No se encontraron resultados para: {}", query);
-        }
-        
-        // Seleccionar automáticamente el mejor resultado (el primero)
-        let best_result = search_results[0].tracks[0].clone();
-        info!("✅ Seleccionado automáticamente: {}", best_result.title());
-        
-        best_result.with_requested_by(command.user.id)
+    let track_source = match resolve_track(bot, guild_id, query, command.user.id).await {
+        Ok(track) => track,
+        Err(e) => return report_extraction_error(&guard, e).await,
     };
-
-    // Establecer el usuario que solicitó la canción
-    track_source = track_source.with_requested_by(command.user.id);
+    tracing::Span::current().record("track_url", &*track_source.url());
 
     // ¿Había algo sonando antes? Si no, este tema arranca ya y mostramos el
     // "now playing"; si sí, solo se encoló y basta con el embed de "agregado".
@@ -431,12 +1331,9 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
         match bot.player.play(guild_id, track_source.clone(), handler).await {
             Ok(_) => {
                 // Responder con confirmación de que la canción fue agregada
-                let embed = embeds::create_track_added_embed(&track_source);
-                use serenity::builder::EditInteractionResponse;
-                if let Err(e) = command
-                    .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
-                    .await
-                {
+                let settings = bot.guild_settings(guild_id).await;
+                let embed = embeds::create_track_added_embed(&track_source, Some(&settings));
+                if let Err(e) = guard.finish(CommandReply::embed(embed), vec![]).await {
                     warn!("Error al editar respuesta: {}", e);
                 }
 
@@ -447,23 +1344,31 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
                 tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
                 if let Some(current_track) = bot.player.get_current_track(guild_id).await {
-                    let now_playing_embed = embeds::create_now_playing_embed_from_source(&current_track);
+                    let rating = bot.average_rating(guild_id, &current_track);
+                    let now_playing_embed =
+                        embeds::create_now_playing_embed_from_source(&current_track, guild_id, Some(&settings), rating);
                     
                     // Verificar si hay cola para mostrar botones mejorados
                     if let Ok(queue_info) = bot.player.get_queue_info(guild_id).await {
+                        let now_playing_embed = embeds::with_up_next(now_playing_embed, &queue_info);
                         let has_queue = queue_info.total_items > 0;
                         let is_playing = bot.player.is_playing(guild_id).await;
                         let loop_mode = format!("{:?}", queue_info.loop_mode).to_lowercase();
                         
                         let buttons = buttons::create_enhanced_player_buttons(is_playing, has_queue, &loop_mode);
                         
-                        if let Err(e) = command.channel_id.send_message(
+                        let channel_id = bot.get_response_channel(guild_id, command.channel_id);
+                        match channel_id.send_message(
                             &ctx.http,
                             serenity::builder::CreateMessage::new()
                                 .embed(now_playing_embed)
                                 .components(buttons)
                         ).await {
-                            warn!("Error al enviar mensaje de now playing: {}", e);
+                            Ok(message) => bot.track_now_playing_message(ctx, guild_id, &message).await,
+                            Err(e) => {
+                                warn!("Error al enviar mensaje de now playing: {}", e);
+                                record_error(ErrorCategory::DiscordApi);
+                            }
                         }
                     }
                 }
@@ -471,24 +1376,16 @@ async fn handle_play(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
             }
             Err(e) => {
                 warn!("Error al reproducir canción: {}", e);
-                let _ = command
-                    .edit_response(
-                        &ctx.http,
-                        serenity::builder::EditInteractionResponse::new()
-                            .content(format!("❌ Error al reproducir: {}", e)),
-                    )
+                let _ = guard
+                    .finish(CommandReply::content(format!("❌ Error al reproducir: {}", e)), vec![])
                     .await;
                 return Err(e);
             }
         }
     } else {
         warn!("No hay handler de voz disponible");
-        let _ = command
-            .edit_response(
-                &ctx.http,
-                serenity::builder::EditInteractionResponse::new()
-                    .content("❌ Error: No hay conexión de voz activa"),
-            )
+        let _ = guard
+            .finish(CommandReply::content("❌ Error: No hay conexión de voz activa"), vec![])
             .await;
         anyhow::bail!("No hay conexión de voz activa");
     }
@@ -501,35 +1398,19 @@ async fn handle_pause(
     command: CommandInteraction,
     bot: &OpenMusicBot,
 ) -> Result<()> {
-    let guild_id = command.guild_id.unwrap();
+    let reply = pause_core(bot, command.guild_id.unwrap()).await?;
+    respond(ctx, &command, reply).await
+}
 
+/// Lógica de `pause`, compartida por el comando slash y el de texto
+pub(crate) async fn pause_core(bot: &OpenMusicBot, guild_id: GuildId) -> Result<CommandReply> {
     // Validar que hay algo reproduciéndose
     if !bot.player.is_playing(guild_id).await {
-        command
-            .create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content("❌ No hay nada reproduciéndose actualmente")
-                        .ephemeral(true),
-                ),
-            )
-            .await?;
-        return Ok(());
+        return Ok(CommandReply::notice("❌ No hay nada reproduciéndose actualmente"));
     }
 
     bot.player.pause(guild_id).await?;
-
-    command
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().content("⏸️ Reproducción pausada"),
-            ),
-        )
-        .await?;
-
-    Ok(())
+    Ok(CommandReply::content("⏸️ Reproducción pausada"))
 }
 
 async fn handle_resume(
@@ -537,49 +1418,36 @@ async fn handle_resume(
     command: CommandInteraction,
     bot: &OpenMusicBot,
 ) -> Result<()> {
-    let guild_id = command.guild_id.unwrap();
+    let reply = resume_core(bot, command.guild_id.unwrap()).await?;
+    respond(ctx, &command, reply).await
+}
 
+/// Lógica de `resume`, compartida por el comando slash y el de texto
+pub(crate) async fn resume_core(bot: &OpenMusicBot, guild_id: GuildId) -> Result<CommandReply> {
     // Validar que el bot está conectado al canal de voz
     if bot.get_voice_handler(guild_id).is_none() {
-        command
-            .create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content("❌ El bot no está conectado a un canal de voz")
-                        .ephemeral(true),
-                ),
-            )
-            .await?;
-        return Ok(());
+        return Ok(CommandReply::notice("❌ El bot no está conectado a un canal de voz"));
     }
 
     bot.player.resume(guild_id).await?;
-
-    command
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().content("▶️ Reproducción reanudada"),
-            ),
-        )
-        .await?;
-
-    Ok(())
+    Ok(CommandReply::content("▶️ Reproducción reanudada"))
 }
 
 async fn handle_skip(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    let amount = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "amount")
-        .and_then(|opt| opt.value.as_i64())
+    let amount = CommandOptions::from_command(&command)
+        .get_i64("amount")
         .unwrap_or(1)
         .max(1) as usize;
 
+    // Un tema largo que acaba de empezar pide confirmación (DJs y admins no)
+    if skip_requires_confirmation(bot, guild_id).await
+        && !has_dj_role_or_admin(ctx, guild_id, command.user.id, bot).await
+    {
+        return handle_skip_confirm(ctx, &command, bot, amount).await;
+    }
+
     // Defer: obtener el audio de la siguiente canción puede tardar (yt-dlp) y
     // superar el límite de 3 s de la interacción.
     command
@@ -589,48 +1457,210 @@ async fn handle_skip(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
         )
         .await?;
 
-    use serenity::builder::EditInteractionResponse;
-    let content = if let Some(handler) = bot.get_voice_handler(guild_id) {
-        bot.player.skip_tracks(guild_id, amount, handler).await?;
-        format!("⏭️ Saltadas {} canciones", amount)
-    } else {
-        "❌ No hay conexión de voz activa".to_string()
-    };
-
+    let reply = skip_core(bot, guild_id, amount).await?;
     command
-        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .edit_response(&ctx.http, reply.into_edit_response())
         .await?;
 
     Ok(())
 }
 
-async fn handle_stop(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+/// `true` si saltar ahora merece confirmación: la canción lleva sonando menos de
+/// `threshold_secs` y dura más de `long_track_secs`
+pub fn needs_skip_confirmation(
+    position: std::time::Duration,
+    track_duration: Option<std::time::Duration>,
+    threshold_secs: u64,
+    long_track_secs: u64,
+) -> bool {
+    position.as_secs() < threshold_secs && track_duration.is_some_and(|d| d.as_secs() > long_track_secs)
+}
+
+async fn skip_requires_confirmation(bot: &OpenMusicBot, guild_id: GuildId) -> bool {
+    let Some(track) = bot.player.get_current_track(guild_id).await else {
+        return false;
+    };
+    let (threshold_secs, long_track_secs) = bot.storage.lock().await.get_skip_confirm_thresholds(guild_id.get());
+    let position = bot.player.get_playback_position(guild_id).await.unwrap_or_default();
+    needs_skip_confirmation(position, track.duration(), threshold_secs, long_track_secs)
+}
+
+/// Pide confirmación con botones Sí/No antes de saltar; sin respuesta en
+/// [`SKIP_CONFIRM_TIMEOUT`] el salto se cancela
+async fn handle_skip_confirm(ctx: &Context, command: &CommandInteraction, bot: &OpenMusicBot, amount: usize) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
+    let title = bot
+        .player
+        .get_current_track(guild_id)
+        .await
+        .map(|track| track.title())
+        .unwrap_or_default();
 
-    // Validar que el bot está conectado
-    if bot.get_voice_handler(guild_id).is_none() {
-        command
-            .create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content("❌ El bot no está reproduciendo nada")
-                        .ephemeral(true),
-                ),
-            )
-            .await?;
-        return Ok(());
+    let embed = embeds::create_warning_embed(
+        "¿Saltar la canción?",
+        &format!(
+            "**{}** acaba de empezar y es un tema largo.\nConfirma en {} segundos o el salto se cancela.",
+            title,
+            SKIP_CONFIRM_TIMEOUT.as_secs()
+        ),
+    );
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(vec![buttons::create_confirmation_buttons("skip")]),
+            ),
+        )
+        .await?;
+
+    // Solo cuenta el botón de quien pidió el salto
+    let message = command.get_response(&ctx.http).await?;
+    let press = message
+        .await_component_interaction(&ctx.shard)
+        .author_id(command.user.id)
+        .timeout(SKIP_CONFIRM_TIMEOUT)
+        .await;
+
+    let reply = match press {
+        Some(press) => {
+            press.defer(&ctx.http).await?;
+            if press.data.custom_id == SKIP_CONFIRM_ID {
+                skip_core(bot, guild_id, amount).await?
+            } else {
+                CommandReply::notice("❌ Salto cancelado")
+            }
+        }
+        None => CommandReply::notice("⌛ Sin confirmación: salto cancelado"),
+    };
+
+    command
+        .edit_response(&ctx.http, reply.into_edit_response().embeds(Vec::new()).components(Vec::new()))
+        .await?;
+    Ok(())
+}
+
+/// Lógica de `skip`, compartida por el comando slash y el de texto
+pub(crate) async fn skip_core(bot: &OpenMusicBot, guild_id: GuildId, amount: usize) -> Result<CommandReply> {
+    match bot.get_voice_handler(guild_id) {
+        Some(handler) => {
+            bot.player.skip_tracks(guild_id, amount, handler).await?;
+            Ok(CommandReply::content(format!("⏭️ Saltadas {} canciones", amount)))
+        }
+        None => Ok(CommandReply::notice("❌ No hay conexión de voz activa")),
+    }
+}
+
+async fn handle_stop(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let reply = stop_core(ctx, bot, command.guild_id.unwrap()).await?;
+    respond(ctx, &command, reply).await
+}
+
+/// Lógica de `stop`, compartida por el comando slash y el de texto
+pub(crate) async fn stop_core(ctx: &Context, bot: &OpenMusicBot, guild_id: GuildId) -> Result<CommandReply> {
+    // Validar que el bot está conectado
+    if bot.get_voice_handler(guild_id).is_none() {
+        return Ok(CommandReply::notice("❌ El bot no está reproduciendo nada"));
     }
 
     bot.player.stop(guild_id).await?;
     bot.leave_voice_channel(ctx, guild_id).await?;
 
+    Ok(CommandReply::content("⏹️ Reproducción detenida y cola limpiada"))
+}
+
+async fn handle_queue(
+    ctx: &Context,
+    command: CommandInteraction,
+    bot: &OpenMusicBot,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let options = CommandOptions::from_command(&command);
+
+    match options.subcommand() {
+        Some(("sort", sort_options)) => {
+            let key = SortKey::from_name(sort_options.require_str("key")?)
+                .ok_or_else(|| anyhow::anyhow!("Criterio de orden desconocido"))?;
+            let order = match sort_options.get_str("order") {
+                Some("desc") => SortOrder::Desc,
+                _ => SortOrder::Asc,
+            };
+            if !may_sort_queue(ctx, guild_id, command.user.id, bot).await {
+                return respond_ephemeral_error(
+                    ctx,
+                    &command,
+                    "🚫 Sin permisos",
+                    "Solo un DJ puede ordenar una cola con canciones de otros usuarios",
+                )
+                .await;
+            }
+
+            let sorted = bot.player.sort_queue(guild_id, key, order).await?;
+            if sorted == 0 {
+                return respond_ephemeral_error(ctx, &command, "Cola vacía", "No hay canciones pendientes para ordenar").await;
+            }
+
+            info!("🔃 Cola de guild {} ordenada por {} por {}", guild_id, key.display_name(), command.user.name);
+            let items = bot.player.get_queue(guild_id).await.unwrap_or_default();
+            let embed = embeds::create_queue_sort_key_embed(key, order, &items);
+            respond(ctx, &command, CommandReply::embed(embed)).await
+        }
+        Some(("shuffle-remaining", _)) => {
+            let shuffled = bot.player.shuffle_pending(guild_id).await?;
+            if shuffled < 2 {
+                return respond_ephemeral_error(ctx, &command, "Nada que mezclar", "No hay suficientes canciones pendientes para mezclar").await;
+            }
+
+            info!("🔀 Cola pendiente de guild {} mezclada por {}", guild_id, command.user.name);
+            let items = bot.player.get_queue(guild_id).await.unwrap_or_default();
+            let embed = embeds::create_queue_shuffled_embed(shuffled, &items);
+            respond(ctx, &command, CommandReply::embed(embed)).await
+        }
+        Some(("loop-stats", _)) => {
+            let queue_info = bot.player.get_queue_info(guild_id).await?;
+            let Some(current) = &queue_info.current else {
+                return respond_ephemeral_error(ctx, &command, "Nada sonando", "No hay ninguna canción reproduciéndose").await;
+            };
+
+            let embed = embeds::create_loop_stats_embed(current, queue_info.loop_mode, queue_info.loop_count);
+            respond(ctx, &command, CommandReply::embed(embed)).await
+        }
+        show => {
+            let page = show
+                .map(|(_, show_options)| show_options)
+                .unwrap_or(options)
+                .get_i64("page")
+                .unwrap_or(1) as usize;
+
+            let reply = queue_core(bot, guild_id, page).await?;
+            respond(ctx, &command, reply).await
+        }
+    }
+}
+
+/// Lógica de `queue`, compartida por el comando slash y el de texto
+pub(crate) async fn queue_core(bot: &OpenMusicBot, guild_id: GuildId, page: usize) -> Result<CommandReply> {
+    let queue_info = bot.player.get_queue_info(guild_id).await?;
+    let settings = bot.guild_settings(guild_id).await;
+    Ok(CommandReply::embed(embeds::create_queue_embed(&queue_info, page, Some(&settings))))
+}
+
+async fn handle_top_rated(
+    ctx: &Context,
+    command: CommandInteraction,
+    bot: &OpenMusicBot,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let ranked = bot.ratings.top_rated(guild_id.get(), 10);
+    let embed = embeds::create_top_rated_embed(&ranked);
+
     command
         .create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new()
-                    .content("⏹️ Reproducción detenida y cola limpiada"),
+                CreateInteractionResponseMessage::new().embed(embed),
             ),
         )
         .await?;
@@ -638,23 +1668,104 @@ async fn handle_stop(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
     Ok(())
 }
 
-async fn handle_queue(
+/// Empieza un quiz musical (`/quiz start [duration_minutes]`) con canciones
+/// del historial de la guild
+async fn handle_quiz(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let options = CommandOptions::from_command(&command);
+    let Some(("start", options)) = options.subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/quiz start`").await;
+    };
+
+    let Ok(voice_channel_id) = get_user_voice_channel(ctx, guild_id, command.user.id).await else {
+        return respond_ephemeral_error(ctx, &command, "Sin canal de voz", "Debes estar en un canal de voz para jugar").await;
+    };
+    if bot.player.is_playing(guild_id).await {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Música sonando",
+            "Detén la reproducción con `/stop` antes de empezar un quiz",
+        )
+        .await;
+    }
+
+    let history = bot.player.history(guild_id);
+    if history.len() < quiz::MIN_QUIZ_TRACKS {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Historial insuficiente",
+            &format!(
+                "El quiz usa las canciones ya reproducidas y hacen falta al menos {} (hay {})",
+                quiz::MIN_QUIZ_TRACKS,
+                history.len()
+            ),
+        )
+        .await;
+    }
+
+    let tracks = quiz::pick_quiz_tracks(history);
+    if !bot.quizzes.begin(guild_id, command.user.id, tracks.len() as u8) {
+        return respond_ephemeral_error(ctx, &command, "Quiz en curso", "Ya hay un quiz musical en este servidor").await;
+    }
+
+    if bot.get_voice_handler(guild_id).is_none() {
+        if let Err(e) = bot.join_voice_channel(ctx, guild_id, voice_channel_id).await {
+            bot.quizzes.finish(guild_id);
+            return Err(e);
+        }
+    }
+    let Some(handler) = bot.get_voice_handler(guild_id) else {
+        bot.quizzes.finish(guild_id);
+        return respond_ephemeral_error(ctx, &command, "Sin conexión de voz", "No pude conectarme al canal de voz").await;
+    };
+
+    let deadline = options
+        .get_i64("duration_minutes")
+        .map(|minutes| tokio::time::Instant::now() + Duration::from_secs(minutes.max(1) as u64 * 60));
+    let channel_id = bot.get_response_channel(guild_id, command.channel_id);
+    let rounds = tracks.len();
+    let runner = quiz::QuizRunner {
+        ctx: ctx.clone(),
+        sessions: bot.quizzes.clone(),
+        player: bot.player.clone(),
+        handler,
+        channel_id,
+        tracks,
+        deadline,
+    };
+    tokio::spawn(runner.run(guild_id, bot.subscribe_shutdown()));
+
+    info!("🎧 Quiz musical iniciado en guild {} por {} ({} rondas)", guild_id, command.user.name, rounds);
+    let embed = embeds::create_success_embed(
+        "Quiz Musical",
+        &format!(
+            "¡Empieza el quiz! {} rondas con fragmentos de {} segundos de canciones ya escuchadas aquí.",
+            rounds,
+            quiz::SNIPPET_LENGTH.as_secs()
+        ),
+    );
+    respond(ctx, &command, CommandReply::embed(embed)).await
+}
+
+async fn handle_leaderboard(
     ctx: &Context,
     command: CommandInteraction,
     bot: &OpenMusicBot,
 ) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    let page = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "page")
-        .and_then(|opt| opt.value.as_i64())
-        .unwrap_or(1) as usize;
+    // Sin período (o uno desconocido) se muestra el ranking de siempre; "votes" es el de 👍/👎
+    let scope = CommandOptions::from_command(&command).get_str("scope");
+    if scope != Some("votes") {
+        let scope = scope.and_then(LeaderboardScope::parse).unwrap_or(LeaderboardScope::AllTime);
+        return super::leaderboard::handle_play_leaderboard(ctx, &command, bot, scope).await;
+    }
 
-    let queue_info = bot.player.get_queue_info(guild_id).await?;
-    let embed = embeds::create_queue_embed(&queue_info, page);
+    let votes = bot.storage.lock().await.load_guild_votes(guild_id.get()).await?;
+    let ranked = super::voting::VotingSystem::rank(&votes, 10);
+    let embed = embeds::create_leaderboard_embed(&ranked);
 
     command
         .create_response(
@@ -677,7 +1788,10 @@ async fn handle_nowplaying(
 
     if let Some(current) = bot.player.get_current_track(guild_id).await {
         // **NUEVA IMPLEMENTACIÓN**: Crear embed mejorado con estadísticas de audio
-        let mut embed = embeds::create_now_playing_embed_from_source(&current);
+        let settings = bot.guild_settings(guild_id).await;
+        let rating = bot.average_rating(guild_id, &current);
+        let mut embed = embeds::create_now_playing_embed_from_source(&current, guild_id, Some(&settings), rating);
+        bot.remember_now_playing_channel(guild_id, command.channel_id);
         
         // Agregar información del ecualizador
         let eq_details = bot.player.get_equalizer_details(guild_id);
@@ -685,17 +1799,33 @@ async fn handle_nowplaying(
         
         // Agregar estadísticas de volumen
         if let Some(volume) = bot.player.get_volume(guild_id).await {
-            let volume_text = format!("{:.0}% ({})", volume * 100.0, 
+            let mut volume_text = format!("{:.0}% ({})", volume * 100.0, 
                 if volume > 1.0 { "🔊 Amplificado" } 
                 else if volume < 0.3 { "🔉 Bajo" } 
                 else { "🔊 Normal" });
+            if bot.player.limiter_engaged(guild_id) {
+                volume_text.push_str(" · 🛡️ Limitador");
+            }
             embed = embed.field("🔊 Volumen", volume_text, true);
         }
         
+        // Tiempo transcurrido y capítulo activo (si el video tiene capítulos)
+        let position = bot.player.get_playback_position(guild_id).await;
+        if let Some(position) = position {
+            embed = embeds::with_playback_progress(embed, position, current.duration());
+            embed = embeds::with_current_chapter(embed, &current, position);
+        }
+        embed = embeds::with_ab_loop(embed, bot.player.ab_loop(guild_id));
+        if let Ok(queue_info) = bot.player.get_queue_info(guild_id).await {
+            embed = embeds::with_loop_count(embed, &queue_info);
+            embed = embeds::with_up_next(embed, &queue_info);
+        }
+
         // Información del procesador
         embed = embed.field("🎧 Procesamiento", "🎵 Audio Nativo", true);
         
-        let buttons = buttons::create_player_buttons();
+        let mut buttons = buttons::create_player_buttons();
+        buttons.extend(buttons::chapter_navigation_for(&current, position.unwrap_or_default()));
 
         command
             .create_response(
@@ -707,6 +1837,13 @@ async fn handle_nowplaying(
                 ),
             )
             .await?;
+
+        // Con `single_now_playing`, este pasa a ser el único panel con botones
+        if bot.storage.lock().await.get_single_now_playing(guild_id.get()) {
+            if let Ok(message) = command.get_response(&ctx.http).await {
+                bot.track_now_playing_message(ctx, guild_id, &message).await;
+            }
+        }
     } else {
         command
             .create_response(
@@ -723,66 +1860,102 @@ async fn handle_nowplaying(
     Ok(())
 }
 
+async fn handle_undo(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let reply = match bot.player.undo(command.guild_id.unwrap()).await {
+        Some((entry, outcome)) if outcome.dropped > 0 => CommandReply::content(format!(
+            "↩️ Deshecho `{}`: {} canciones de vuelta en la cola; {} no cupieron porque la cola está llena",
+            entry.operation, outcome.restored, outcome.dropped
+        )),
+        Some((entry, outcome)) => CommandReply::content(format!(
+            "↩️ Deshecho `{}`: {} canciones de vuelta en la cola",
+            entry.operation, outcome.restored
+        )),
+        None => CommandReply::notice("❌ No hay cambios de la cola para deshacer"),
+    };
+    respond(ctx, &command, reply).await
+}
+
 async fn handle_shuffle(
     ctx: &Context,
     command: CommandInteraction,
     bot: &OpenMusicBot,
 ) -> Result<()> {
-    let guild_id = command.guild_id.unwrap();
+    let options = CommandOptions::from_command(&command);
+    let once = options.get_str("mode") == Some("now");
+    let seed = options.get_i64("seed").map(|seed| seed.max(0) as u64);
 
-    let shuffled = bot.player.toggle_shuffle(guild_id).await?;
+    let reply = shuffle_core(bot, command.guild_id.unwrap(), once, seed).await?;
+    respond(ctx, &command, reply).await
+}
 
-    command
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(
-                if shuffled {
-                    "🔀 Modo aleatorio activado"
-                } else {
-                    "➡️ Modo aleatorio desactivado"
-                },
-            )),
-        )
-        .await?;
+/// Lógica de `shuffle`, compartida por el comando slash y el de texto.
+///
+/// Con `seed` mezcla la cola una vez de forma reproducible; con `once`, una vez
+/// al azar; si no, alterna el modo aleatorio.
+pub(crate) async fn shuffle_core(
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    once: bool,
+    seed: Option<u64>,
+) -> Result<CommandReply> {
+    if once || seed.is_some() {
+        let shuffled = match seed {
+            Some(seed) => bot.player.shuffle_with_seed(guild_id, seed).await?,
+            None => bot.player.shuffle_once(guild_id).await?,
+        };
+        return Ok(match (shuffled, seed) {
+            (0..=1, _) => CommandReply::notice("❌ No hay suficientes canciones en la cola para mezclar"),
+            (_, Some(seed)) => CommandReply::content(format!("🔀 Cola mezclada ({} canciones, semilla: {})", shuffled, seed)),
+            (_, None) => CommandReply::content(format!("🔀 Cola mezclada ({} canciones)", shuffled)),
+        });
+    }
 
-    Ok(())
+    let shuffled = bot.player.toggle_shuffle(guild_id).await?;
+
+    Ok(CommandReply::content(if shuffled {
+        "🔀 Modo aleatorio activado"
+    } else {
+        "➡️ Modo aleatorio desactivado"
+    }))
 }
 
 async fn handle_loop(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
-    let guild_id = command.guild_id.unwrap();
+    let options = CommandOptions::from_command(&command);
+    let mode = options.get_str("mode").unwrap_or("off");
+    let times = options.get_i64("times").map(|t| t.clamp(1, 100) as u32);
 
-    let mode = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "mode")
-        .and_then(|opt| opt.value.as_str())
-        .unwrap_or("off");
+    let reply = loop_core(bot, command.guild_id.unwrap(), mode, times).await?;
+    respond(ctx, &command, reply).await
+}
 
+/// Lógica de `loop`, compartida por el comando slash y el de texto.
+///
+/// `times` solo aplica al modo `track`: repite la canción N veces y luego sigue.
+pub(crate) async fn loop_core(
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    mode: &str,
+    times: Option<u32>,
+) -> Result<CommandReply> {
     // Set the proper loop mode
-    let loop_mode = match mode {
-        "track" => crate::audio::queue::LoopMode::Track,
-        "queue" => crate::audio::queue::LoopMode::Queue,
+    let loop_mode = match (mode, times) {
+        ("track", Some(times)) => crate::audio::queue::LoopMode::TrackCount(times),
+        ("track", None) => crate::audio::queue::LoopMode::Track,
+        ("queue", _) => crate::audio::queue::LoopMode::Queue,
         _ => crate::audio::queue::LoopMode::Off,
     };
     bot.player.set_loop_mode_specific(guild_id, loop_mode).await?;
 
-    let message = match mode {
-        "track" => "🔂 Repetir canción activado",
-        "queue" => "🔁 Repetir cola activado",
-        _ => "➡️ Repetición desactivada",
+    let message = match loop_mode {
+        crate::audio::queue::LoopMode::TrackCount(times) => {
+            format!("🔂 La canción se repetirá {} {}", times, if times == 1 { "vez" } else { "veces" })
+        }
+        crate::audio::queue::LoopMode::Track => "🔂 Repetir canción activado".to_string(),
+        crate::audio::queue::LoopMode::Queue => "🔁 Repetir cola activado".to_string(),
+        crate::audio::queue::LoopMode::Off => "➡️ Repetición desactivada".to_string(),
     };
 
-    command
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().content(message),
-            ),
-        )
-        .await?;
-
-    Ok(())
+    Ok(CommandReply::content(message))
 }
 
 async fn handle_volume(
@@ -791,35 +1964,60 @@ async fn handle_volume(
     bot: &OpenMusicBot,
 ) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
+    let volume = CommandOptions::from_command(&command).get_i64("level");
 
-    let volume = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "level")
-        .and_then(|opt| opt.value.as_i64());
+    let bypass_ceiling = bypasses_volume_ceiling(ctx, bot, guild_id, command.user.id).await;
+    let reply = volume_core(bot, guild_id, volume, bypass_ceiling).await?;
+    respond(ctx, &command, reply).await
+}
+
+/// `true` si el usuario puede superar el techo de volumen del servidor: solo con
+/// `dj_bypasses_volume_limit` activo y un rol de DJ configurado que el usuario tenga
+pub(crate) async fn bypasses_volume_ceiling(ctx: &Context, bot: &OpenMusicBot, guild_id: GuildId, user_id: UserId) -> bool {
+    let (dj_bypass, has_dj_role) = {
+        let storage = bot.storage.lock().await;
+        (storage.get_volume_limit(guild_id.get()).1, storage.get_dj_role(guild_id.get()).is_some())
+    };
+    dj_bypass && has_dj_role && is_dj(ctx, guild_id, user_id, bot).await
+}
 
-    if let Some(vol) = volume {
+/// Lógica de `volume`, compartida por el comando slash y el de texto
+pub(crate) async fn volume_core(
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+    volume: Option<i64>,
+    bypass_ceiling: bool,
+) -> Result<CommandReply> {
+    if let Some(requested) = volume {
         // Validar rango
-        if vol < 0 || vol > 200 {
-            command
-                .create_response(
-                    &ctx.http,
-                    CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new()
-                            .content("❌ El volumen debe estar entre 0 y 200%")
-                            .ephemeral(true),
-                    ),
-                )
-                .await?;
-            return Ok(());
+        if !(0..=200).contains(&requested) {
+            return Ok(CommandReply::notice("❌ El volumen debe estar entre 0 y 200%"));
         }
 
-        let normalized = (vol as f32 / 100.0).clamp(0.0, 2.0);
+        // Aplicar el techo de volumen del servidor
+        let (max_volume, _) = bot.storage.lock().await.get_volume_limit(guild_id.get());
+        let mut normalized = (requested as f32 / 100.0).clamp(0.0, 2.0);
+        let capped = !bypass_ceiling && normalized > max_volume;
+        if capped {
+            normalized = max_volume;
+        }
+        let vol = (normalized * 100.0).round() as i64;
         bot.player.set_volume(guild_id, normalized).await?;
 
-        // Mensaje con advertencia si > 100%
-        let message = if vol > 100 {
+        // Mensaje con advertencia si > 100%; por encima del umbral del limitador, más fuerte
+        let mut message = if normalized > LIMITER_VOLUME_THRESHOLD {
+            let protection = if bot.storage.lock().await.get_limiter(guild_id.get()) {
+                "🛡️ El limitador evita la saturación (se aplica desde la próxima canción)"
+            } else {
+                "Un administrador puede activar `/settings limiter on` para evitar la saturación"
+            };
+            format!(
+                "📢 Volumen ajustado a {}%\n⚠️ **Advertencia**: a más de {:.0}% las canciones fuertes saturan y pueden molestar a los oyentes\n{}",
+                vol,
+                LIMITER_VOLUME_THRESHOLD * 100.0,
+                protection
+            )
+        } else if vol > 100 {
             format!("🔊 Volumen ajustado a {}%\n⚠️ **Advertencia**: Volúmenes superiores a 100% pueden causar distorsión", vol)
         } else if vol == 0 {
             "🔇 Audio silenciado (0%)".to_string()
@@ -828,15 +2026,11 @@ async fn handle_volume(
         } else {
             format!("🔊 Volumen ajustado a {}%", vol)
         };
+        if capped {
+            message.push_str(&format!("\n🔒 Volumen limitado a {}% por la configuración del servidor", vol));
+        }
 
-        command
-            .create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new().content(message),
-                ),
-            )
-            .await?;
+        Ok(CommandReply::content(message))
     } else {
         let current = bot.player.get_volume(guild_id).await.unwrap_or(0.5);
         let vol_percent = (current * 100.0) as i32;
@@ -845,18 +2039,8 @@ async fn handle_volume(
                     else if vol_percent <= 30 { "🔉" } 
                     else { "🔊" };
         
-        command
-            .create_response(
-                &ctx.http,
-                CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content(format!("{} Volumen actual: {}%", emoji, vol_percent)),
-                ),
-            )
-            .await?;
+        Ok(CommandReply::content(format!("{} Volumen actual: {}%", emoji, vol_percent)))
     }
-
-    Ok(())
 }
 
 async fn handle_join(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
@@ -878,21 +2062,81 @@ async fn handle_join(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
     Ok(())
 }
 
-async fn handle_leave(
-    ctx: &Context,
-    command: CommandInteraction,
-    bot: &OpenMusicBot,
-) -> Result<()> {
+async fn handle_move_channel(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    bot.player.stop(guild_id).await?;
-    bot.leave_voice_channel(ctx, guild_id).await?;
+    let target_channel = match get_user_voice_channel(ctx, guild_id, command.user.id).await {
+        Ok(channel_id) => channel_id,
+        Err(_) => {
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content("❌ Debes estar en un canal de voz para usar este comando")
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let current_channel = match bot.get_voice_handler(guild_id) {
+        Some(handler) => handler
+            .lock()
+            .await
+            .current_channel()
+            .map(|channel| ChannelId::new(channel.0.get())),
+        None => None,
+    };
+
+    if current_channel == Some(target_channel) {
+        command
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content("ℹ️ Ya estoy en tu canal de voz")
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    // Si hay gente escuchando en el canal actual, mover el bot requiere DJ
+    if let Some(current_channel) = current_channel {
+        let listeners = count_listeners(ctx, guild_id, current_channel);
+        if listeners > 0 && !is_dj(ctx, guild_id, command.user.id, bot).await {
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content("🎧 Hay usuarios escuchando en el canal actual; se requiere el rol de DJ para mover el bot")
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    // `join` sobre una llamada existente la mueve sin cortar la reproducción
+    bot.join_voice_channel(ctx, guild_id, target_channel).await?;
+
+    info!(
+        "🔀 Bot movido a canal {} por {} en guild {}",
+        target_channel, command.user.name, guild_id
+    );
 
     command
         .create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().content("👋 Desconectado del canal de voz"),
+                CreateInteractionResponseMessage::new()
+                    .content(format!("🔀 Movido a <#{}>", target_channel)),
             ),
         )
         .await?;
@@ -900,6 +2144,23 @@ async fn handle_leave(
     Ok(())
 }
 
+async fn handle_leave(
+    ctx: &Context,
+    command: CommandInteraction,
+    bot: &OpenMusicBot,
+) -> Result<()> {
+    let reply = leave_core(ctx, bot, command.guild_id.unwrap()).await?;
+    respond(ctx, &command, reply).await
+}
+
+/// Lógica de `leave`, compartida por el comando slash y el de texto
+pub(crate) async fn leave_core(ctx: &Context, bot: &OpenMusicBot, guild_id: GuildId) -> Result<CommandReply> {
+    bot.player.stop(guild_id).await?;
+    bot.leave_voice_channel(ctx, guild_id).await?;
+
+    Ok(CommandReply::content("👋 Desconectado del canal de voz"))
+}
+
 // ===== NUEVOS COMANDOS =====
 
 async fn handle_previous(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
@@ -1000,13 +2261,57 @@ async fn handle_restart(ctx: &Context, command: CommandInteraction, bot: &OpenMu
     Ok(())
 }
 
-async fn handle_seek(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+/// Recupera una pista atascada (input de ffmpeg colgado): misma canción, misma posición
+async fn handle_restart_playback(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    // Validar que hay algo reproduciéndose
-    if !bot.player.is_playing(guild_id).await {
-        command
-            .create_response(
+    let Some(handler) = bot.get_voice_handler(guild_id) else {
+        return respond_ephemeral_error(ctx, &command, "Sin conexión", "El bot no está conectado a un canal de voz").await;
+    };
+
+    // Defer: volver a abrir el stream del tema actual puede tardar (yt-dlp)
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let reply = match bot.player.restart_playback(guild_id, handler).await {
+        Ok(Some(attempt)) => {
+            let title = bot
+                .player
+                .get_current_track(guild_id)
+                .await
+                .map(|track| track.title())
+                .unwrap_or_default();
+            CommandReply::content(format!(
+                "🩹 Reproducción recuperada: **{}** ({}/{})",
+                title, attempt, MAX_PLAYBACK_RESTARTS
+            ))
+        }
+        Ok(None) => CommandReply::notice("❌ No hay nada reproduciéndose"),
+        Err(e) if e.is::<TooManyRestartsError>() => CommandReply::notice(format!(
+            "⚠️ {}; usa `/skip` para pasar a la siguiente",
+            e
+        )),
+        Err(e) => {
+            warn!("Error reiniciando la reproducción en guild {}: {:?}", guild_id, e);
+            CommandReply::notice("❌ No se pudo recuperar la canción; prueba con `/skip`")
+        }
+    };
+
+    command.edit_response(&ctx.http, reply.into_edit_response()).await?;
+    Ok(())
+}
+
+async fn handle_seek(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    // Validar que hay algo reproduciéndose
+    if !bot.player.is_playing(guild_id).await {
+        command
+            .create_response(
                 &ctx.http,
                 CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new()
@@ -1018,26 +2323,152 @@ async fn handle_seek(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
         return Ok(());
     }
 
-    let time_str = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "time")
-        .and_then(|opt| opt.value.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Tiempo requerido"))?;
+    let time_str = CommandOptions::from_command(&command).require_str("time")?;
 
     // Parsear tiempo (formatos: "90", "1:30", "1:30:00")
     let seconds = parse_time_string(time_str)?;
 
-    // Nota: Songbird seek no está implementado de forma directa en todas las fuentes
-    // Por ahora, mostraremos un mensaje informativo
+    let reply = seek_reply(bot, guild_id, std::time::Duration::from_secs(seconds)).await;
+    respond(ctx, &command, reply).await
+}
+
+/// Salta a una posición de la canción actual y describe el resultado
+async fn seek_reply(bot: &OpenMusicBot, guild_id: GuildId, position: std::time::Duration) -> CommandReply {
+    match bot.player.seek(guild_id, position).await {
+        Ok(reached) => CommandReply::content(format!("⏩ Saltando a {}", format_seconds(reached.as_secs()))),
+        Err(e) => {
+            warn!("Seek falló en guild {}: {:?}", guild_id, e);
+            CommandReply::notice("❌ No se pudo saltar a esa posición (en streaming solo se puede avanzar)")
+        }
+    }
+}
+
+/// Repite una sección de la canción actual (`/ab-loop set|clear`)
+async fn handle_ab_loop(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let Some((subcommand, options)) = CommandOptions::from_command(&command).subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/ab-loop set` o `/ab-loop clear`").await;
+    };
+
+    let reply = if subcommand == "clear" {
+        match bot.player.clear_ab_loop(guild_id) {
+            Some(_) => CommandReply::content("🔁 Sección A-B desactivada"),
+            None => CommandReply::notice("❌ No hay ninguna sección A-B activa"),
+        }
+    } else {
+        ab_loop_reply(bot, guild_id, options.require_str("start")?, options.require_str("end")?).await?
+    };
+    respond(ctx, &command, reply).await
+}
+
+/// Activa la sección A-B `start`–`end` (formato `MM:SS`) en la canción actual
+async fn ab_loop_reply(bot: &OpenMusicBot, guild_id: GuildId, start: &str, end: &str) -> Result<CommandReply> {
+    let Some(track) = bot.player.get_current_track(guild_id).await else {
+        return Ok(CommandReply::notice("❌ No hay nada reproduciéndose"));
+    };
+    let (Ok(start), Ok(end)) = (parse_time_string(start), parse_time_string(end)) else {
+        return Ok(CommandReply::notice("❌ Formato de tiempo inválido. Usa: segundos, min:seg, o hora:min:seg"));
+    };
+
+    match AbLoop::new(Duration::from_secs(start), Duration::from_secs(end), track.duration()) {
+        Ok(ab_loop) => {
+            bot.player.set_ab_loop(guild_id, ab_loop)?;
+            Ok(CommandReply::content(format!(
+                "🔁 Repitiendo **{}** de {} a {}",
+                track.title(),
+                format_seconds(start),
+                format_seconds(end)
+            )))
+        }
+        Err(e) => Ok(CommandReply::notice(format!("❌ {}", e))),
+    }
+}
+
+/// Capítulos del track; si no se extrajeron al encolarlo (p. ej. vino de una
+/// búsqueda), se consultan a yt-dlp bajo demanda.
+async fn chapters_for(bot: &OpenMusicBot, guild_id: GuildId, track: &TrackSource) -> Vec<crate::sources::Chapter> {
+    if let Some(chapters) = track.chapters() {
+        return chapters.to_vec();
+    }
+
+    if !YtDlpOptimizedClient::is_youtube_url(&track.url()) {
+        return Vec::new();
+    }
+
+    let source_manager = bot.source_manager(guild_id).await;
+    let url = track.url();
+    match bot
+        .run_extraction(guild_id, source_manager.get_track_from_url(&url, track.requested_by()))
+        .await
+    {
+        Ok(full) => full.chapters().map(|c| c.to_vec()).unwrap_or_default(),
+        Err(e) => {
+            warn!("No se pudieron obtener capítulos de {}: {:?}", url, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Lista los capítulos de la canción actual con un menú para saltar a ellos
+async fn handle_chapters(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let Some(track) = bot.player.get_current_track(guild_id).await else {
+        return respond(ctx, &command, CommandReply::notice("❌ No hay nada reproduciéndose actualmente")).await;
+    };
+
+    // Defer: obtener los capítulos puede requerir consultar a yt-dlp
     command
         .create_response(
             &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new()
-                    .content(format!("⏩ Saltando a {}... (función en desarrollo para streaming directo)", format_seconds(seconds))),
-            ),
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let chapters = chapters_for(bot, guild_id, &track).await;
+    if chapters.is_empty() {
+        command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("📖 Esta canción no tiene capítulos"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let position = bot.player.get_playback_position(guild_id).await.unwrap_or_default();
+    let current = chapters.iter().find(|chapter| chapter.contains(position));
+
+    command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .embed(embeds::create_chapters_embed(&track.title(), &chapters, current))
+                .components(embeds::create_chapter_select(&chapters)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Salta al capítulo elegido en el menú de `/chapters`
+async fn handle_chapter_selection(ctx: &Context, component: &ComponentInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = component.guild_id.unwrap();
+
+    let start = match &component.data.kind {
+        serenity::model::application::ComponentInteractionDataKind::StringSelect { values } => {
+            values.first().and_then(|v| v.parse::<f64>().ok())
+        }
+        _ => None,
+    }
+    .ok_or_else(|| anyhow::anyhow!("Selección de capítulo inválida"))?;
+
+    let reply = seek_reply(bot, guild_id, std::time::Duration::from_secs_f64(start.max(0.0))).await;
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(reply.into_interaction_message()),
         )
         .await?;
 
@@ -1047,13 +2478,7 @@ async fn handle_seek(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
 async fn handle_add(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    let query = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "query")
-        .and_then(|opt| opt.value.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Query requerido"))?;
+    let query = CommandOptions::from_command(&command).require_str("query")?;
 
     // Defer para operaciones largas
     command
@@ -1062,23 +2487,26 @@ async fn handle_add(ctx: &Context, command: CommandInteraction, bot: &OpenMusicB
             CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
         )
         .await?;
+    let guard = InteractionGuard::new(ctx, &command);
 
     // Buscar la canción
-    let source_manager = crate::sources::SourceManager::new();
-    let search_results = source_manager.search_all(query, 1).await?;
+    let source_manager = bot.source_manager(guild_id).await;
+    let search_results = match bot.run_extraction(guild_id, source_manager.search_all(query, 5)).await {
+        Ok(results) => results,
+        Err(e) => return report_extraction_error(&guard, e).await,
+    };
 
-    if search_results.is_empty() || search_results[0].tracks.is_empty() {
-        command
-            .edit_response(
-                &ctx.http,
-                serenity::builder::EditInteractionResponse::new()
-                    .content(format!("❌ No se encontraron resultados para: {}", query)),
-            )
-            .await?;
-        return Ok(());
-    }
+    let Some(track) = best_search_result(bot, guild_id, &search_results, query).await else {
+        return guard
+            .finish(CommandReply::content(format!("❌ No se encontraron resultados para: {}", query)), vec![])
+            .await;
+    };
 
-    let track = search_results[0].tracks[0].clone().with_requested_by(command.user.id);
+    let track = track.with_requested_by(command.user.id);
+    if let Err(blocked) = check_content_filter(bot, guild_id, &track).await {
+        let embed = embeds::create_blocked_track_embed(&blocked);
+        return guard.finish(CommandReply::embed(embed), vec![]).await;
+    }
     let title = track.title();
 
     // Agregar a la cola sin reproducir
@@ -1088,48 +2516,76 @@ async fn handle_add(ctx: &Context, command: CommandInteraction, bot: &OpenMusicB
         q.add_track(track)?;
     }
 
-    command
-        .edit_response(
-            &ctx.http,
-            serenity::builder::EditInteractionResponse::new()
-                .content(format!("➕ **{}** agregado a la cola", title)),
-        )
-        .await?;
-
-    Ok(())
+    guard
+        .finish(CommandReply::content(format!("➕ **{}** agregado a la cola", title)), vec![])
+        .await
 }
 
-async fn handle_remove(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+/// Inserta una canción en una posición concreta de la cola (`/insert`)
+async fn handle_insert(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    let position = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "position")
-        .and_then(|opt| opt.value.as_i64())
-        .ok_or_else(|| anyhow::anyhow!("Posición requerida"))? as usize;
+    let options = CommandOptions::from_command(&command);
+    let query = options.require_str("query")?;
+    let position = options.require_i64("position")?.max(1) as usize;
 
+    // Validar antes de resolver: la extracción puede tardar
     let queue = bot.player.get_or_create_queue(guild_id);
+    let queue_len = queue.read().len();
+    if position > queue_len + 1 {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Posición inválida",
+            &format!("La cola tiene {} canciones: usa una posición entre 1 y {}", queue_len, queue_len + 1),
+        )
+        .await;
+    }
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+    let guard = InteractionGuard::new(ctx, &command);
+
+    let track = match resolve_track(bot, guild_id, query, command.user.id).await {
+        Ok(track) => track,
+        Err(e) => return report_extraction_error(&guard, e).await,
+    };
+    let title = track.title();
+
+    // La cola pudo encogerse mientras se resolvía: como mucho, al final
     let result = {
         let mut q = queue.write();
-        let queue_len = q.get_info().total_items;
-        
-        if position == 0 || position > queue_len {
-            Err(anyhow::anyhow!("Posición {} fuera de rango (1-{})", position, queue_len))
-        } else {
-            q.remove_track(position - 1) // Convertir a 0-indexed
+        let index = (position - 1).min(q.len());
+        q.insert_at(index, track).map(|_| index + 1)
+    };
+
+    let reply = match result {
+        Ok(position) => {
+            info!("📍 {} insertó {} en la posición {} (guild {})", command.user.name, title, position, guild_id);
+            CommandReply::content(format!("📍 Insertada **{}** en la posición {}", title, position))
         }
+        Err(e) => CommandReply::notice(format!("❌ {}", e)),
     };
+    guard.finish(reply, vec![]).await
+}
+
+async fn handle_remove(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
 
-    match result {
+    let position = CommandOptions::from_command(&command).require_i64("position")? as usize;
+
+    match bot.player.remove_queued(guild_id, position).await {
         Ok(_) => {
             command
                 .create_response(
                     &ctx.http,
                     CreateInteractionResponse::Message(
                         CreateInteractionResponseMessage::new()
-                            .content(format!("🗑️ Canción en posición {} removida", position)),
+                            .content(format!("🗑️ Canción en posición {} removida (`/undo` para deshacer)", position)),
                     ),
                 )
                 .await?;
@@ -1154,13 +2610,7 @@ async fn handle_remove(ctx: &Context, command: CommandInteraction, bot: &OpenMus
 async fn handle_jump(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    let position = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "position")
-        .and_then(|opt| opt.value.as_i64())
-        .ok_or_else(|| anyhow::anyhow!("Posición requerida"))? as usize;
+    let position = CommandOptions::from_command(&command).require_i64("position")? as usize;
 
     // Defer: reproducir el track objetivo puede tardar (yt-dlp).
     command
@@ -1219,6 +2669,183 @@ async fn handle_jump(ctx: &Context, command: CommandInteraction, bot: &OpenMusic
     Ok(())
 }
 
+async fn handle_skipto_user(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let user_id = CommandOptions::from_command(&command).require_user_id("user")?;
+
+    let Some(handler) = bot.get_voice_handler(guild_id) else {
+        return respond(ctx, &command, CommandReply::notice("❌ El bot no está conectado a un canal de voz")).await;
+    };
+
+    let queue = bot.player.get_or_create_queue(guild_id);
+    let advanced = queue.write().advance_to_user(user_id);
+    let Some((skipped, source)) = advanced else {
+        return respond(
+            ctx,
+            &command,
+            CommandReply::notice(format!("🤷 <@{}> no tiene canciones en la cola", user_id)),
+        )
+        .await;
+    };
+
+    // Defer: reproducir el track objetivo puede tardar (yt-dlp).
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    // Como en `/jump`, la cola ya fijó el track como actual: se reproduce de inmediato
+    if let Err(e) = bot.player.play_source_now(guild_id, source.clone(), handler).await {
+        warn!("Error reproduciendo track: {:?}", e);
+    }
+
+    let reply = CommandReply::content(format!(
+        "👤 Saltadas {} canciones. Ahora suena **{}** (pedida por <@{}>)",
+        skipped,
+        source.title(),
+        user_id
+    ));
+    command.edit_response(&ctx.http, reply.into_edit_response()).await?;
+    Ok(())
+}
+
+/// Los DJ pueden ordenar cualquier cola; el resto solo si todas las canciones
+/// pendientes son suyas (`/queue-sort` y `/queue sort`).
+async fn may_sort_queue(ctx: &Context, guild_id: GuildId, user_id: UserId, bot: &OpenMusicBot) -> bool {
+    let only_own_tracks = {
+        let queue = bot.player.get_or_create_queue(guild_id);
+        let q = queue.read();
+        q.get_info().items.iter().all(|item| item.requested_by == user_id)
+    };
+    only_own_tracks || is_dj(ctx, guild_id, user_id, bot).await
+}
+
+/// `/queue-sort`: ordena la cola según `by` (ver [`may_sort_queue`]).
+async fn handle_queue_sort(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let by = CommandOptions::from_command(&command).require_str("by")?.to_string();
+    let Some(field) = crate::audio::queue::SortField::from_name(&by) else {
+        return respond_ephemeral_error(ctx, &command, "❌ Criterio no válido", &format!("No se puede ordenar por `{}`", by)).await;
+    };
+
+    if !may_sort_queue(ctx, guild_id, command.user.id, bot).await {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "🚫 Sin permisos",
+            "Solo un DJ puede ordenar una cola con canciones de otros usuarios",
+        )
+        .await;
+    }
+
+    let sorted = bot.player.sort_queue_by_field(guild_id, field).await?;
+    if sorted <= 1 {
+        return respond(ctx, &command, CommandReply::notice("❌ No hay suficientes canciones en la cola para ordenar")).await;
+    }
+
+    let preview = bot.player.get_queue(guild_id).await.unwrap_or_default();
+    let embed = embeds::create_queue_sorted_embed(field, sorted, &preview);
+    respond(ctx, &command, CommandReply::embed(embed)).await
+}
+
+async fn handle_find(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let query = CommandOptions::from_command(&command).require_str("query")?.trim().to_string();
+
+    let (results, total_matches) = {
+        let queue = bot.player.get_or_create_queue(guild_id);
+        let q = queue.read();
+        let matches = q.find_track(&query);
+        let results: Vec<(usize, TrackSource)> = matches
+            .iter()
+            .take(FIND_MAX_RESULTS)
+            .map(|(position, track)| (*position, (*track).clone()))
+            .collect();
+        (results, matches.len())
+    };
+
+    if results.is_empty() {
+        return respond(ctx, &command, CommandReply::notice(format!("🔎 Nada en la cola coincide con \"{}\"", query))).await;
+    }
+
+    // La canción actual no se quita desde aquí (para eso está /skip)
+    let removable: Vec<&(usize, TrackSource)> = results.iter().filter(|(position, _)| *position > 0).collect();
+    let can_remove = !removable.is_empty() && is_dj(ctx, guild_id, command.user.id, bot).await;
+
+    let embed = embeds::create_find_results_embed(&query, &results, total_matches);
+    let mut message = CreateInteractionResponseMessage::new().embed(embed).ephemeral(true);
+    if can_remove {
+        let buttons = removable
+            .iter()
+            .enumerate()
+            .map(|(i, (position, _))| {
+                CreateButton::new(format!("{}{}", FIND_REMOVE_PREFIX, i))
+                    .label(format!("Quitar #{}", position))
+                    .emoji('🗑')
+                    .style(ButtonStyle::Danger)
+            })
+            .collect();
+        message = message.components(vec![CreateActionRow::Buttons(buttons)]);
+    }
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await?;
+
+    if !can_remove {
+        return Ok(());
+    }
+
+    // Las posiciones pueden cambiar mientras el mensaje está abierto: se quita por URL
+    let response = command.get_response(&ctx.http).await?;
+    let deadline = tokio::time::Instant::now() + FIND_REMOVE_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        let Some(press) = response
+            .await_component_interaction(&ctx.shard)
+            .author_id(command.user.id)
+            .timeout(remaining)
+            .await
+        else {
+            break;
+        };
+
+        let track = press
+            .data
+            .custom_id
+            .strip_prefix(FIND_REMOVE_PREFIX)
+            .and_then(|i| i.parse::<usize>().ok())
+            .and_then(|i| removable.get(i))
+            .map(|(_, track)| track);
+        let removed = track.and_then(|track| {
+            let queue = bot.player.get_or_create_queue(guild_id);
+            let mut q = queue.write();
+            q.remove_by_url(&track.url())
+        });
+
+        let content = match removed {
+            Some(track) => format!("🗑️ **{}** quitada de la cola", track.title()),
+            None => "❌ Esa canción ya no está en la cola".to_string(),
+        };
+        press
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content(content).ephemeral(true),
+                ),
+            )
+            .await?;
+    }
+
+    // Al expirar se retiran los botones
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().components(Vec::new()))
+        .await?;
+    Ok(())
+}
+
 // Funciones auxiliares para los nuevos comandos
 
 fn parse_time_string(time_str: &str) -> Result<u64> {
@@ -1258,16 +2885,26 @@ fn format_seconds(total_seconds: u64) -> String {
     }
 }
 
-async fn handle_help(ctx: &Context, command: CommandInteraction, _bot: &OpenMusicBot) -> Result<()> {
-    let specific_command = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "command")
-        .and_then(|opt| opt.value.as_str());
+async fn handle_help(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let specific_command = CommandOptions::from_command(&command).get_str("command");
 
     let embed = if let Some(cmd) = specific_command {
-        embeds::create_command_help_embed(cmd)
+        let mut embed = embeds::create_command_help_embed(cmd);
+        if cmd == "volume" {
+            if let Some(guild_id) = command.guild_id {
+                let (max_volume, dj_bypass) = bot.storage.lock().await.get_volume_limit(guild_id.get());
+                embed = embed.field(
+                    "Límite del servidor",
+                    format!(
+                        "{}%{}",
+                        (max_volume * 100.0).round(),
+                        if dj_bypass { " (el rol de DJ puede superarlo)" } else { "" }
+                    ),
+                    false,
+                );
+            }
+        }
+        embed
     } else {
         embeds::create_help_embed()
     };
@@ -1307,6 +2944,29 @@ async fn get_user_voice_channel(
     Ok(channel_id)
 }
 
+/// Cuenta los usuarios (no bots) conectados a un canal de voz.
+///
+/// Solo cuenta miembros que están en caché (o que vienen en el estado de voz):
+/// uno desconocido podría ser el propio bot u otro bot.
+fn count_listeners(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> usize {
+    let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else {
+        return 0;
+    };
+
+    guild
+        .voice_states
+        .values()
+        .filter(|state| state.channel_id == Some(channel_id))
+        .filter(|state| {
+            guild
+                .members
+                .get(&state.user_id)
+                .or(state.member.as_ref())
+                .is_some_and(|member| !member.user.bot)
+        })
+        .count()
+}
+
 async fn handle_equalizer(
     ctx: &Context,
     command: CommandInteraction,
@@ -1314,27 +2974,20 @@ async fn handle_equalizer(
 ) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
 
-    let preset_name = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "preset")
-        .and_then(|opt| opt.value.as_str())
+    let preset_name = CommandOptions::from_command(&command)
+        .get_str("preset")
         .unwrap_or("flat");
 
-    let preset = match preset_name {
-        "bass" => crate::audio::effects::EqualizerPreset::Bass,
-        "pop" => crate::audio::effects::EqualizerPreset::Pop,
-        "rock" => crate::audio::effects::EqualizerPreset::Rock,
-        "jazz" => crate::audio::effects::EqualizerPreset::Jazz,
-        "classical" => crate::audio::effects::EqualizerPreset::Classical,
-        "electronic" => crate::audio::effects::EqualizerPreset::Electronic,
-        "vocal" => crate::audio::effects::EqualizerPreset::Vocal,
-        _ => crate::audio::effects::EqualizerPreset::Flat,
-    };
+    let preset = crate::audio::effects::EqualizerPreset::from_name(preset_name)
+        .unwrap_or(crate::audio::effects::EqualizerPreset::Flat);
 
     bot.player.apply_equalizer_preset(guild_id, preset).await?;
 
+    // Persistir para que el preset sobreviva a reinicios del bot
+    if let Err(e) = bot.storage.lock().await.set_eq_preset(guild_id.get(), preset.name()).await {
+        warn!("Error guardando preset de ecualizador: {:?}", e);
+    }
+
     info!("✅ Ecualizador aplicado: {:?}", preset);
 
     // El filtro ffmpeg se fija al iniciar cada tema, así que si ya hay algo
@@ -1360,53 +3013,299 @@ async fn handle_equalizer(
     Ok(())
 }
 
-async fn handle_clear(
-    ctx: &Context,
-    command: CommandInteraction,
-    bot: &OpenMusicBot,
-) -> Result<()> {
+/// Bandas de `/eq band` que empiezan por lo escrito (todas si no hay nada)
+fn eq_frequency_suggestions(typed: &str) -> Vec<(String, String)> {
+    let typed = typed.trim();
+    CUSTOM_EQ_FREQUENCIES
+        .iter()
+        .map(|freq| freq.to_string())
+        .filter(|freq| freq.starts_with(typed))
+        .map(|freq| (format!("{} Hz", freq), freq))
+        .collect()
+}
+
+/// Cambia una sola banda del ecualizador personalizado (`/eq band <frequency> <gain_db>`)
+async fn handle_eq(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
     let guild_id = command.guild_id.unwrap();
+    let options = CommandOptions::from_command(&command);
+    let Some(("band", sub)) = options.subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando inválido", "Usa `/eq band <frequency> <gain_db>`").await;
+    };
 
-    let target = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "target")
-        .and_then(|opt| opt.value.as_str())
-        .unwrap_or("queue");
+    let frequency = sub.require_str("frequency")?;
+    let Some(band_hz) = frequency
+        .trim()
+        .trim_end_matches(|c: char| c.is_alphabetic() || c.is_whitespace())
+        .parse::<u32>()
+        .ok()
+        .filter(|hz| eq_band_index(*hz).is_some())
+    else {
+        let bands = CUSTOM_EQ_FREQUENCIES.map(|freq| freq.to_string()).join(", ");
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Banda inválida",
+            &format!("`{}` no es una banda del ecualizador. Bandas (Hz): {}", frequency, bands),
+        )
+        .await;
+    };
+    let gain_db = sub.get_f64("gain_db").unwrap_or(0.0).clamp(-MAX_EQ_GAIN_DB as f64, MAX_EQ_GAIN_DB as f64) as f32;
 
-    match target {
-        "queue" => {
-            bot.player.clear_queue(guild_id).await?;
-            command
-                .create_response(
-                    &ctx.http,
-                    CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new()
-                            .content("🗑️ Cola limpiada"),
-                    ),
-                )
-                .await?;
-        }
-        "duplicates" => {
-            let removed = bot.player.clear_duplicates(guild_id).await?;
-            command
-                .create_response(
-                    &ctx.http,
-                    CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new()
-                            .content(format!("🗑️ Eliminados {} duplicados", removed)),
+    // Defer: re-crear el input de la canción actual puede tardar (yt-dlp)
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let bands = bot.player.set_equalizer_band(guild_id, band_hz, gain_db).await?;
+    if let Err(e) = bot.storage.lock().await.set_custom_eq_bands(guild_id.get(), bands).await {
+        warn!("Error guardando bandas del ecualizador: {:?}", e);
+    }
+    info!("🎚️ Banda de {} Hz a {:+.1} dB en guild {} por {}", band_hz, gain_db, guild_id, command.user.name);
+
+    let rebuilt = match bot.get_voice_handler(guild_id) {
+        Some(handler) => bot
+            .player
+            .rebuild_current_input(guild_id, handler)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Error re-creando el input con la nueva curva: {:?}", e);
+                false
+            }),
+        None => false,
+    };
+
+    let embed = embeds::create_eq_band_embed(band_hz, gain_db, &bands, rebuilt);
+    command.edit_response(&ctx.http, CommandReply::embed(embed).into_edit_response()).await?;
+    Ok(())
+}
+
+/// Nombre del hilo creado por `/session start`
+const SESSION_THREAD_NAME: &str = "🎵 Music Session";
+
+/// Inicia (`/session start [channel]`) o cierra (`/session end`) una sesión de
+/// música en un hilo, al que se envían los mensajes del bot mientras dure
+async fn handle_session(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let Some((subcommand, options)) = CommandOptions::from_command(&command).subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/session start` o `/session end`").await;
+    };
+
+    if subcommand == "end" {
+        let Some(thread_id) = bot.end_session(guild_id) else {
+            return respond_ephemeral_error(ctx, &command, "Sin sesión", "No hay ninguna sesión de música activa").await;
+        };
+
+        // Archivar el hilo; si ya no existe la sesión igualmente quedó cerrada
+        let _ = thread_id
+            .send_message(&ctx.http, CreateMessage::new().content("👋 Sesión de música finalizada"))
+            .await;
+        if let Err(e) = thread_id
+            .edit_thread(&ctx.http, EditThread::new().archived(true).locked(true))
+            .await
+        {
+            warn!("Error archivando hilo de sesión {}: {:?}", thread_id, e);
+        }
+
+        info!("🧵 Sesión de música finalizada en guild {} por {}", guild_id, command.user.name);
+        let embed = embeds::create_success_embed("Sesión Finalizada", "Los mensajes vuelven al canal de cada comando");
+        return respond(ctx, &command, CommandReply::embed(embed)).await;
+    }
+
+    if let Some(thread_id) = bot.session_thread(guild_id) {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Sesión activa",
+            &format!("Ya hay una sesión en <#{}>; ciérrala con `/session end`", thread_id),
+        )
+        .await;
+    }
+
+    let channel_id = options.get_channel_id("channel").unwrap_or(command.channel_id);
+
+    let thread = match channel_id
+        .create_thread(
+            &ctx.http,
+            CreateThread::new(SESSION_THREAD_NAME)
+                .kind(ChannelType::PublicThread)
+                .auto_archive_duration(AutoArchiveDuration::OneDay),
+        )
+        .await
+    {
+        Ok(thread) => thread,
+        Err(e) => {
+            warn!("Error creando hilo de sesión en {}: {:?}", channel_id, e);
+            return respond_ephemeral_error(
+                ctx,
+                &command,
+                "No se pudo crear el hilo",
+                "Verifica que el canal sea de texto y que tenga permiso para crear hilos públicos",
+            )
+            .await;
+        }
+    };
+
+    bot.start_session(guild_id, thread.id);
+    let _ = thread
+        .id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().content(format!(
+                "🎵 Sesión iniciada por <@{}>: aquí se publicarán las canciones. Termina con `/session end` o al archivarse el hilo.",
+                command.user.id
+            )),
+        )
+        .await;
+
+    info!("🧵 Sesión de música iniciada en hilo {} (guild {}) por {}", thread.id, guild_id, command.user.name);
+    let embed = embeds::create_success_embed("Sesión Iniciada", &format!("Los mensajes del bot irán a <#{}>", thread.id));
+    respond(ctx, &command, CommandReply::embed(embed)).await
+}
+
+/// Muestra (`/effects show`) o restablece (`/effects clear`) los efectos de audio
+async fn handle_effects(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let subcommand = CommandOptions::from_command(&command)
+        .subcommand()
+        .map(|(name, _)| name)
+        .unwrap_or("show");
+
+    if subcommand != "clear" {
+        let embed = embeds::create_effects_status_embed(&bot.player.get_effects_state(guild_id));
+        return respond(ctx, &command, CommandReply::embed(embed)).await;
+    }
+
+    // Mostrar el estado es libre; restablecerlo afecta a todos los oyentes
+    if !is_dj(ctx, guild_id, command.user.id, bot).await {
+        return respond_ephemeral_error(ctx, &command, "Sin permisos", "🎧 Este comando requiere el rol de DJ").await;
+    }
+
+    // Defer: re-crear el input de la canción actual puede tardar (yt-dlp)
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    bot.player.clear_effects(guild_id);
+    if let Err(e) = bot.storage.lock().await.reset_effects_settings(guild_id.get()).await {
+        warn!("Error guardando efectos restablecidos: {:?}", e);
+    }
+    info!("🧹 Efectos restablecidos en guild {} por {}", guild_id, command.user.name);
+
+    let rebuilt = match bot.get_voice_handler(guild_id) {
+        Some(handler) => bot
+            .player
+            .rebuild_current_input(guild_id, handler)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Error re-creando el input sin efectos: {:?}", e);
+                false
+            }),
+        None => false,
+    };
+
+    let description = if rebuilt {
+        "Todos los efectos volvieron a sus valores por defecto; la canción actual ya suena sin ellos"
+    } else {
+        "Todos los efectos volvieron a sus valores por defecto"
+    };
+    let reply = CommandReply::embed(embeds::create_success_embed("Efectos Restablecidos", description));
+    command.edit_response(&ctx.http, reply.into_edit_response()).await?;
+    Ok(())
+}
+
+/// Cambia la velocidad de reproducción sin alterar el tono (`/speed`)
+async fn handle_speed(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let tempo = CommandOptions::from_command(&command)
+        .get_f64("factor")
+        .unwrap_or(1.0)
+        .clamp(MIN_TEMPO as f64, MAX_TEMPO as f64) as f32;
+
+    // Defer: re-crear el input de la canción actual puede tardar (yt-dlp)
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+
+    let previous = bot.player.set_tempo(guild_id, tempo);
+    if let Err(e) = bot.storage.lock().await.set_playback_tempo(guild_id.get(), tempo).await {
+        warn!("Error guardando velocidad de reproducción: {:?}", e);
+    }
+    info!("⏱️ Velocidad {:.2}x en guild {} por {}", tempo, guild_id, command.user.name);
+
+    let rebuilt = match bot.get_voice_handler(guild_id) {
+        Some(handler) if previous != tempo => bot
+            .player
+            .rebuild_current_input_scaled(guild_id, handler, (previous / tempo) as f64)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Error re-creando el input con la nueva velocidad: {:?}", e);
+                false
+            }),
+        _ => false,
+    };
+
+    let mut description = if (tempo - 1.0).abs() > f32::EPSILON {
+        format!("Reproduciendo a **{:.2}x**, sin cambiar el tono", tempo)
+    } else {
+        "Velocidad normal restablecida".to_string()
+    };
+    if rebuilt {
+        description.push_str("\nLa canción actual sigue desde el mismo punto");
+    }
+    let reply = CommandReply::embed(embeds::create_success_embed("Velocidad", &description));
+    command.edit_response(&ctx.http, reply.into_edit_response()).await?;
+    Ok(())
+}
+
+async fn handle_clear(
+    ctx: &Context,
+    command: CommandInteraction,
+    bot: &OpenMusicBot,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let target = CommandOptions::from_command(&command)
+        .get_str("target")
+        .unwrap_or("queue");
+
+    match target {
+        "queue" => {
+            bot.player.clear_queue(guild_id).await?;
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content("🗑️ Cola limpiada (`/undo` para deshacer)"),
+                    ),
+                )
+                .await?;
+        }
+        "duplicates" => {
+            let removed = bot.player.clear_duplicates(guild_id).await?;
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content(format!("🗑️ Eliminados {} duplicados", removed)),
                     ),
                 )
                 .await?;
         }
         "user" => {
-            let user = command
-                .data
-                .options
-                .iter()
-                .find(|opt| opt.name == "user")
-                .and_then(|opt| opt.value.as_user_id())
+            let user = CommandOptions::from_command(&command)
+                .get_user_id("user")
                 .unwrap_or(command.user.id);
 
             let removed = bot.player.clear_user_tracks(guild_id, user).await?;
@@ -1445,13 +3344,7 @@ async fn handle_playlist(
     let guild_id = command.guild_id.unwrap();
     
     // Obtener la URL de la playlist del comando
-    let playlist_url = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "url")
-        .and_then(|opt| opt.value.as_str())
-        .ok_or_else(|| anyhow::anyhow!("URL de playlist requerida"))?;
+    let playlist_url = CommandOptions::from_command(&command).require_str("url")?;
 
     // Verificar que el usuario esté en un canal de voz
     let voice_channel_id = get_user_voice_channel(ctx, guild_id, command.user.id).await?;
@@ -1469,42 +3362,70 @@ async fn handle_playlist(
             CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
         )
         .await?;
+    let guard = InteractionGuard::new(ctx, &command);
 
     info!("🎵 Cargando playlist: {} por {}", playlist_url, command.user.name);
 
+    let command_options = CommandOptions::from_command(&command);
+    let bool_option = |name: &str| command_options.get_bool(name).unwrap_or(false);
+    let options = PlaylistLoadOptions {
+        shuffle: bool_option("shuffle"),
+        dedupe: bool_option("dedupe"),
+        wrap: bool_option("wrap"),
+    };
+
     // Determinar el tipo de playlist
     if playlist_url.contains("youtube.com") || playlist_url.contains("youtu.be") {
-        handle_youtube_playlist(ctx, &command, bot, guild_id, playlist_url).await?;
+        let Some(load) = bot.playlist_loads.begin(guild_id, command.user.id) else {
+            let embed = embeds::create_error_embed(
+                "Playlist en curso",
+                "Ya se está cargando una playlist en este servidor. Espera a que termine o cancélala",
+            );
+            return guard.finish(CommandReply::embed(embed), vec![]).await;
+        };
+        let result = handle_youtube_playlist(&guard, bot, guild_id, playlist_url, options, &load).await;
+        bot.playlist_loads.finish(guild_id);
+        result?;
     } else {
         // Intentar como URL directa
-        handle_direct_url_playlist(ctx, &command, bot, guild_id, playlist_url).await?;
+        handle_direct_url_playlist(&guard, bot, guild_id, playlist_url, command.user.id).await?;
     }
 
     Ok(())
 }
 
+/// Opciones de `/playlist` aplicadas antes de encolar
+#[derive(Debug, Clone, Copy)]
+struct PlaylistLoadOptions {
+    /// Mezclar el orden de los tracks resueltos
+    shuffle: bool,
+    /// Descartar URLs repetidas dentro de la playlist
+    dedupe: bool,
+    /// Con `&index=N`, encolar al final los tracks anteriores en vez de descartarlos
+    wrap: bool,
+}
+
+/// Playlists con más canciones que esto muestran una vista previa y esperan `playlist_confirm`
+const PLAYLIST_CONFIRM_THRESHOLD: usize = 25;
+
+/// Tiempo que espera la vista previa de una playlist antes de descartarla
+const PLAYLIST_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Maneja playlist de YouTube
 async fn handle_youtube_playlist(
-    ctx: &Context,
-    command: &CommandInteraction,
+    guard: &InteractionGuard<'_>,
     bot: &OpenMusicBot,
     guild_id: GuildId,
     playlist_url: &str,
+    options: PlaylistLoadOptions,
+    load: &PlaylistLoad,
 ) -> Result<()> {
-    use serenity::builder::EditInteractionResponse;
-    
     let _ytdlp_client = crate::sources::YtDlpOptimizedClient::new();
     
     // Verificar si es una URL de playlist válida
     if !playlist_url.contains("list=") {
-        command
-            .edit_response(
-                &ctx.http,
-                EditInteractionResponse::new()
-                    .embed(embeds::create_error_embed("Error", "URL de playlist de YouTube inválida. Debe contener 'list='"))
-            )
-            .await?;
-        return Ok(());
+        let embed = embeds::create_error_embed("Error", "URL de playlist de YouTube inválida. Debe contener 'list='");
+        return guard.finish(CommandReply::embed(embed), vec![]).await;
     }
 
     // Obtener información básica de la playlist primero
@@ -1520,48 +3441,108 @@ async fn handle_youtube_playlist(
     );
     let loading_buttons = crate::ui::buttons::MusicControls::create_playlist_loading_controls(None);
     
-    command
-        .edit_response(
-            &ctx.http,
-            EditInteractionResponse::new()
-                .embed(loading_embed)
-                .components(loading_buttons)
-        )
-        .await?;
+    guard.update(CommandReply::embed(loading_embed), loading_buttons).await?;
 
     // Obtener tracks de la playlist
-    let ytdlp_client = crate::sources::YtDlpOptimizedClient::new();
-    match ytdlp_client.get_playlist(playlist_url).await {
-        Ok(tracks) => {
+    let ytdlp_client = crate::sources::YtDlpOptimizedClient::with_retry_policy(
+        crate::sources::RetryPolicy::from_config(&bot.settings()),
+    )
+    .with_proxy(bot.ytdlp_proxy(guild_id).await);
+    let extraction = tokio::select! {
+        result = bot.run_extraction(guild_id, ytdlp_client.get_playlist(playlist_url)) => result,
+        _ = load.cancelled() => {
+            info!("⏹️ Carga de playlist cancelada durante el análisis: {}", playlist_url);
+            let embed = embeds::create_playlist_cancelled_embed(0, 0, playlist_url);
+            return guard.finish(CommandReply::embed(embed), vec![]).await;
+        }
+    };
+    match extraction {
+        Ok(mut tracks) => {
+            // `&index=N` (p. ej. al compartir desde un video de la lista): empezar ahí
+            let start_index = YtDlpOptimizedClient::extract_playlist_index(playlist_url)
+                .filter(|_| !tracks.is_empty())
+                .map(|index| crate::audio::queue::start_tracks_at(&mut tracks, index, options.wrap));
+
+            let removed_duplicates = if options.dedupe {
+                crate::audio::queue::dedupe_tracks(&mut tracks)
+            } else {
+                0
+            };
+            let blocked_count = bot.content_filter(guild_id).await.retain_allowed(&mut tracks);
+
+            if options.shuffle {
+                use rand::seq::SliceRandom;
+                tracks.shuffle(&mut rand::thread_rng());
+            }
+
             if tracks.is_empty() {
-                command
-                    .edit_response(
-                        &ctx.http,
-                        EditInteractionResponse::new()
-                            .embed(embeds::create_error_embed("Playlist Vacía", "La playlist no contiene canciones válidas"))
-                            .components(vec![])
-                    )
-                    .await?;
-                return Ok(());
+                let description = if blocked_count > 0 {
+                    "Todas las canciones de la playlist están bloqueadas en este servidor"
+                } else {
+                    "La playlist no contiene canciones válidas"
+                };
+                let embed = embeds::create_error_embed("Playlist Vacía", description);
+                return guard.finish(CommandReply::embed(embed), vec![]).await;
             }
 
             let total_count = tracks.len();
+
+            // Playlists grandes: mostrar las primeras canciones y esperar confirmación
+            if total_count > PLAYLIST_CONFIRM_THRESHOLD {
+                let first_tracks: Vec<String> = tracks.iter().take(5).map(|track| track.title().to_string()).collect();
+                let total_duration: Duration = tracks.iter().filter_map(|track| track.duration()).sum();
+                let preview_embed = embeds::create_playlist_preview_embed(
+                    "Playlist de YouTube",
+                    total_count,
+                    &first_tracks,
+                    (total_duration > Duration::ZERO).then_some(total_duration),
+                    playlist_url,
+                );
+                let confirm_buttons = vec![crate::ui::buttons::MusicControls::create_playlist_confirmation_controls()];
+                guard.update(CommandReply::embed(preview_embed), confirm_buttons).await?;
+
+                match load.wait_for_decision(PLAYLIST_CONFIRM_TIMEOUT).await {
+                    PlaylistDecision::Confirmed => {
+                        info!("✅ Playlist de {} canciones confirmada", total_count);
+                    }
+                    PlaylistDecision::Cancelled => {
+                        let embed = embeds::create_playlist_cancelled_embed(0, total_count, playlist_url);
+                        return guard.finish(CommandReply::embed(embed), vec![]).await;
+                    }
+                    PlaylistDecision::Expired => {
+                        let embed = embeds::create_error_embed(
+                            "Vista previa expirada",
+                            "Nadie confirmó la playlist a tiempo, no se agregó ninguna canción",
+                        );
+                        return guard.finish(CommandReply::embed(embed), vec![]).await;
+                    }
+                }
+            }
+
             let handler = bot.get_voice_handler(guild_id)
                 .ok_or_else(|| anyhow::anyhow!("No hay conexión de voz activa"))?;
 
-            info!("📋 Playlist encontrada con {} canciones, iniciando carga progresiva", total_count);
+            info!(
+                "📋 Playlist encontrada con {} canciones (inicio: {:?}, duplicados eliminados: {}, bloqueadas: {}, mezclada: {}), iniciando carga progresiva",
+                total_count, start_index, removed_duplicates, blocked_count, options.shuffle
+            );
 
             // Carga progresiva de canciones
             let mut added_count = 0;
-            let mut failed_count = 0;
+            let mut failures: std::collections::BTreeMap<TrackFailureReason, usize> = std::collections::BTreeMap::new();
             let mut loaded_tracks = Vec::new();
             let mut total_duration = std::time::Duration::new(0, 0);
+            let mut progress = ThrottledEditor::new(guard, PROGRESS_EDIT_INTERVAL);
 
             for (i, track) in tracks.iter().enumerate() {
+                // `playlist_cancel`: no encolar nada más
+                if load.is_cancelled() {
+                    break;
+                }
                 let current = i + 1;
-                
-                // Actualizar progreso cada 5 canciones o al final
-                if current % 5 == 0 || current == total_count {
+
+                // Actualizar progreso como mucho cada PROGRESS_EDIT_INTERVAL
+                let update = progress.update(|| {
                     let progress_embed = crate::ui::embeds::create_playlist_loading_embed(
                         "Cargando playlist...",
                         current,
@@ -1572,24 +3553,17 @@ async fn handle_youtube_playlist(
                     let progress_buttons = crate::ui::buttons::MusicControls::create_playlist_loading_controls(
                         Some((current, total_count))
                     );
-                    
-                    if let Err(e) = command
-                        .edit_response(
-                            &ctx.http,
-                            EditInteractionResponse::new()
-                                .embed(progress_embed)
-                                .components(progress_buttons)
-                        )
-                        .await {
-                        warn!("Error actualizando progreso de playlist: {:?}", e);
-                    }
+                    (CommandReply::embed(progress_embed), progress_buttons)
+                });
+                if let Err(e) = update.await {
+                    warn!("Error actualizando progreso de playlist: {:?}", e);
                 }
 
                 // Intentar agregar la canción
                 match bot.player.play(guild_id, track.clone(), handler.clone()).await {
                     Ok(_) => {
                         added_count += 1;
-                        loaded_tracks.push(track.title().clone());
+                        loaded_tracks.push(track.title().to_string());
                         if let Some(duration) = track.duration() {
                             total_duration += duration;
                         }
@@ -1600,27 +3574,43 @@ async fn handle_youtube_playlist(
                         }
                     }
                     Err(e) => {
-                        failed_count += 1;
-                        warn!("Error agregando canción {}: {:?}", track.title(), e);
+                        // Un fallo no corta la carga: se anota el motivo y se sigue
+                        let reason = TrackFailureReason::from_error(&e);
+                        *failures.entry(reason).or_default() += 1;
+                        warn!("Error agregando canción {} ({:?}): {:?}", track.title(), reason, e);
                     }
                 }
+            }
 
-                // Pequeña pausa para no saturar la API
-                if current % 10 == 0 {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                }
+            if load.is_cancelled() {
+                info!("⏹️ Carga de playlist cancelada: {}/{} canciones agregadas", added_count, total_count);
+                let embed = embeds::create_playlist_cancelled_embed(added_count, total_count, playlist_url);
+                return guard.finish(CommandReply::embed(embed), vec![]).await;
             }
 
             // Crear respuesta final con estadísticas completas
-            let final_embed = crate::ui::embeds::create_playlist_completed_embed(
+            let mut final_embed = crate::ui::embeds::create_playlist_completed_embed(
                 "Playlist de YouTube",
                 added_count,
                 total_count,
-                failed_count,
+                &failures.into_iter().collect::<Vec<_>>(),
+                removed_duplicates,
                 if total_duration.as_secs() > 0 { Some(total_duration) } else { None },
                 playlist_url
             );
 
+            if let Some(start) = start_index {
+                let note = if options.wrap {
+                    format!("Desde la #{} (las anteriores van al final)", start)
+                } else {
+                    format!("Desde la #{}", start)
+                };
+                final_embed = final_embed.field("▶️ Inicio", note, true);
+            }
+            if blocked_count > 0 {
+                final_embed = final_embed.field("🚫 Bloqueadas", blocked_count.to_string(), true);
+            }
+
             // Botones finales con controles de playlist
             let final_buttons = if added_count > 0 {
                 crate::ui::buttons::create_playlist_buttons()
@@ -1628,27 +3618,17 @@ async fn handle_youtube_playlist(
                 vec![]
             };
 
-            command
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new()
-                        .embed(final_embed)
-                        .components(final_buttons)
-                )
-                .await?;
+            guard.finish(CommandReply::embed(final_embed), final_buttons).await?;
+            if added_count > 0 {
+                bot.spawn_queue_enrichment(guild_id, tracks);
+            }
 
             info!("✅ Playlist cargada: {}/{} canciones agregadas exitosamente", added_count, total_count);
         }
         Err(e) => {
             tracing::error!("Error cargando playlist: {:?}", e);
-            command
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new()
-                        .embed(embeds::create_error_embed("Error", &format!("Error al cargar playlist: {}", e)))
-                        .components(vec![])
-                )
-                .await?;
+            let embed = embeds::create_error_embed("Error", &format!("Error al cargar playlist: {}", e));
+            guard.finish(CommandReply::embed(embed), vec![]).await?;
         }
     }
 
@@ -1658,106 +3638,1393 @@ async fn handle_youtube_playlist(
 
 /// Maneja URL directa (intentar como canción individual)
 async fn handle_direct_url_playlist(
-    ctx: &Context,
-    command: &CommandInteraction,
+    guard: &InteractionGuard<'_>,
     bot: &OpenMusicBot,
     guild_id: GuildId,
     url: &str,
+    requested_by: UserId,
 ) -> Result<()> {
-    use serenity::builder::EditInteractionResponse;
-    
-    // Intentar agregar como canción individual
-    let track_source = TrackSource::new(
-        "Audio desde URL".to_string(),
-        url.to_string(),
-        SourceType::DirectUrl,
-        command.user.id,
-    );
+    if let Err(e) = direct::check_stream_url(url).await {
+        let embed = embeds::create_error_embed("URL no válida", &e.to_string());
+        return guard.finish(CommandReply::embed(embed), vec![]).await;
+    }
+
+    // Intentar agregar como canción individual, con la metadata del stream
+    let track_source = direct::resolve_direct_track(url, requested_by, &bot.cache).await;
+    if let Err(blocked) = check_content_filter(bot, guild_id, &track_source).await {
+        let embed = embeds::create_blocked_track_embed(&blocked);
+        return guard.finish(CommandReply::embed(embed), vec![]).await;
+    }
 
     let handler = bot.get_voice_handler(guild_id)
         .ok_or_else(|| anyhow::anyhow!("No hay conexión de voz activa"))?;
 
-    match bot.player.play(guild_id, track_source, handler).await {
-        Ok(_) => {
-            let embed = embeds::create_success_embed(
-                "🎵 Audio Agregado",
-                "✅ URL directa agregada a la cola"
-            );
-
-            command
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new().embed(embed)
-                )
-                .await?;
-        }
-        Err(e) => {
-            command
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new()
-                        .embed(embeds::create_error_embed("Error", &format!("No se pudo cargar el audio: {}", e)))
-                )
-                .await?;
-        }
-    }
+    let title = track_source.title();
+    let embed = match bot.player.play(guild_id, track_source, handler).await {
+        Ok(_) => embeds::create_success_embed("🎵 Audio Agregado", &format!("✅ **{}** agregado a la cola", title)),
+        Err(e) => embeds::create_error_embed("Error", &format!("No se pudo cargar el audio: {}", e)),
+    };
 
-    Ok(())
+    guard.finish(CommandReply::embed(embed), vec![]).await
 }
 
-async fn handle_health(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
-    
-    
-    let health_status = bot.monitoring.perform_health_check().await;
-    let system_metrics = bot.monitoring.get_system_metrics().await;
-    
-    let status_emoji = match health_status {
-        crate::monitoring::HealthStatus::Healthy => "✅",
-        crate::monitoring::HealthStatus::Warning => "⚠️",
-        crate::monitoring::HealthStatus::Critical => "🚨",
-        crate::monitoring::HealthStatus::Unknown => "❓",
-    };
-    
-    let embed = embeds::create_info_embed(
-        &format!("{} Estado de Salud del Bot", status_emoji),
-        &format!(
-            "**Estado**: {:?}\n**Tiempo activo**: {:?}\n**Comandos procesados**: {}\n**Errores**: {}\n**Tasa de error**: {:.2}%",
-            health_status,
-            system_metrics.uptime,
-            system_metrics.total_commands,
-            system_metrics.total_errors,
-            system_metrics.error_rate
-        )
-    );
+/// Reproduce una radio por internet; su título se actualiza con la metadata ICY
+async fn handle_radio(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let url = CommandOptions::from_command(&command).require_str("url")?.trim();
+
+    if let Err(e) = direct::check_stream_url(url).await {
+        return respond_ephemeral_error(ctx, &command, "URL inválida", &e.to_string()).await;
+    }
+
+    let voice_channel_id = get_user_voice_channel(ctx, guild_id, command.user.id).await?;
+    if bot.get_voice_handler(guild_id).is_none() {
+        bot.join_voice_channel(ctx, guild_id, voice_channel_id).await?;
+    }
 
+    // Defer: leer la metadata del stream puede tardar unos segundos
     command
         .create_response(
             &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new()
-                    .embed(embed)
-                    .ephemeral(true),
-            ),
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
         )
         .await?;
+    let guard = InteractionGuard::new(ctx, &command);
 
-    Ok(())
+    info!("📻 Cargando radio: {} por {}", url, command.user.name);
+
+    let track_source = match direct::resolve_radio_track(url, command.user.id, &bot.cache).await {
+        Ok(track) => track,
+        Err(e) => {
+            let embed = embeds::create_error_embed("Radio no válida", &format!("{}. Usa `/playlist` para audios con duración.", e));
+            return guard.finish(CommandReply::embed(embed), vec![]).await;
+        }
+    };
+    if let Err(blocked) = check_content_filter(bot, guild_id, &track_source).await {
+        let embed = embeds::create_blocked_track_embed(&blocked);
+        return guard.finish(CommandReply::embed(embed), vec![]).await;
+    }
+
+    let handler = bot.get_voice_handler(guild_id)
+        .ok_or_else(|| anyhow::anyhow!("No hay conexión de voz activa"))?;
+
+    let station = track_source.station().unwrap_or_default().to_string();
+    let embed = match bot.player.play(guild_id, track_source, handler).await {
+        Ok(_) => embeds::create_success_embed("📻 Radio Agregada", &format!("🔴 **{}** agregada a la cola", station)),
+        Err(e) => embeds::create_error_embed("Error", &format!("No se pudo cargar la radio: {}", e)),
+    };
+
+    guard.finish(CommandReply::embed(embed), vec![]).await
 }
 
-async fn handle_metrics(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
-    let metrics_type = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "type")
-        .and_then(|opt| opt.value.as_str())
-        .unwrap_or("performance");
+/// Muestra o cambia el prefijo de comandos de texto de la guild
+async fn handle_prefix(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
 
-    match metrics_type {
-        "performance" => {
-            let system_metrics = bot.monitoring.get_system_metrics().await;
-            let embed = embeds::create_info_embed(
-                "📊 Métricas de Rendimiento",
+    let value = CommandOptions::from_command(&command)
+        .get_str("value")
+        .map(str::trim);
+
+    let Some(value) = value else {
+        let current = bot.storage.lock().await.get_command_prefix(guild_id.get());
+        let description = match current {
+            Some(prefix) => format!("Prefijo actual: `{}` (ej: `{}play`)", prefix, prefix),
+            None => "Los comandos de texto están desactivados. Usa `/prefix <valor>` para activarlos".to_string(),
+        };
+        return respond(ctx, &command, CommandReply::embed(embeds::create_info_embed("Prefijo", &description))).await;
+    };
+
+    let new_prefix = if value.eq_ignore_ascii_case("off") {
+        None
+    } else {
+        let len = value.chars().count();
+        if !(1..=super::prefix::MAX_PREFIX_LEN).contains(&len) || value.contains(char::is_whitespace) || value.starts_with('/') {
+            return respond_ephemeral_error(
+                ctx,
+                &command,
+                "Prefijo inválido",
+                "El prefijo debe tener entre 1 y 5 caracteres, sin espacios ni `/`",
+            )
+            .await;
+        }
+        Some(value.to_string())
+    };
+
+    bot.storage
+        .lock()
+        .await
+        .set_command_prefix(guild_id.get(), new_prefix.clone())
+        .await?;
+
+    info!("⌨️ Prefijo de texto en guild {} cambiado a {:?} por {}", guild_id, new_prefix, command.user.name);
+
+    let description = match &new_prefix {
+        Some(prefix) => format!("Comandos de texto activados con `{}` (ej: `{}play`, `{}skip`)", prefix, prefix, prefix),
+        None => "Comandos de texto desactivados".to_string(),
+    };
+    respond(ctx, &command, CommandReply::embed(embeds::create_success_embed("Prefijo Actualizado", &description))).await
+}
+
+/// Muestra o actualiza la configuración del servidor (`/settings search|search-source|url-detect|same-channel|limiter|single-now-playing|bot-nickname|color`)
+async fn handle_settings(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let Some((subcommand, options)) = CommandOptions::from_command(&command).subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/settings search`").await;
+    };
+
+    if subcommand == "url-detect" {
+        let enabled = options.get_str("state") == Some("on");
+        return handle_settings_url_detect(ctx, &command, bot, enabled).await;
+    }
+    if subcommand == "same-channel" {
+        let enabled = options.get_str("state") == Some("on");
+        return handle_settings_same_channel(ctx, &command, bot, enabled).await;
+    }
+    if subcommand == "limiter" {
+        let enabled = options.get_str("state") == Some("on");
+        return handle_settings_limiter(ctx, &command, bot, enabled).await;
+    }
+    if subcommand == "single-now-playing" {
+        let enabled = options.get_str("state") == Some("on");
+        return handle_settings_single_now_playing(ctx, &command, bot, enabled).await;
+    }
+    if subcommand == "search-source" {
+        let source = options.get_str("source").unwrap_or_default();
+        return handle_settings_search_source(ctx, &command, bot, source).await;
+    }
+    if subcommand == "proxy" {
+        let proxy = options.get_str("proxy").map(str::trim).filter(|proxy| !proxy.is_empty());
+        return handle_settings_proxy(ctx, &command, bot, proxy).await;
+    }
+    if subcommand == "bot-nickname" {
+        let pattern = options.get_str("pattern").map(str::trim).unwrap_or_default();
+        return handle_settings_bot_nickname(ctx, &command, bot, pattern).await;
+    }
+    if subcommand == "color" {
+        let hex = options.get_str("hex").map(str::trim).unwrap_or_default();
+        return handle_settings_color(ctx, &command, bot, hex).await;
+    }
+    if subcommand == "block" {
+        return handle_settings_block(ctx, &command, bot, options).await;
+    }
+    if subcommand == "max-volume" {
+        let percent = options.get_i64("percent").unwrap_or(200);
+        let dj_bypass = options.get_bool("dj_bypass");
+        return handle_settings_max_volume(ctx, &command, bot, percent, dj_bypass).await;
+    }
+
+    let int_option = |name: &str| options.get_i64(name);
+    let bool_option = |name: &str| options.get_bool(name);
+
+    let current = bot.storage.lock().await.get_search_preferences(guild_id.get());
+
+    if options.is_empty() {
+        let embed = embeds::create_search_settings_embed(&current, "Ranking de Búsqueda");
+        return respond(ctx, &command, CommandReply::embed(embed)).await;
+    }
+
+    let mut prefs = if bool_option("reset") == Some(true) {
+        SearchPreferences::default()
+    } else {
+        current
+    };
+
+    if let Some(minutes) = int_option("min_minutes") {
+        prefs.min_duration_secs = minutes.max(0) as u64 * 60;
+    }
+    if let Some(minutes) = int_option("max_minutes") {
+        prefs.max_duration_secs = minutes.max(1) as u64 * 60;
+    }
+    if let Some(penalize) = bool_option("penalize_versions") {
+        prefs.penalize_alternate_versions = penalize;
+    }
+    if let Some(official) = bool_option("prefer_official") {
+        prefs.prefer_official = official;
+    }
+
+    if prefs.min_duration_secs >= prefs.max_duration_secs {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Duración inválida",
+            "La duración mínima debe ser menor que la máxima",
+        )
+        .await;
+    }
+
+    bot.storage
+        .lock()
+        .await
+        .set_search_preferences(guild_id.get(), prefs.clone())
+        .await?;
+
+    info!("⚙️ Ranking de búsqueda en guild {} actualizado por {}: {:?}", guild_id, command.user.name, prefs);
+
+    let embed = embeds::create_search_settings_embed(&prefs, "Ranking de Búsqueda Actualizado");
+    respond(ctx, &command, CommandReply::embed(embed)).await
+}
+
+/// Cambia el color de los embeds de música del servidor (`/settings color`)
+async fn handle_settings_color(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    hex: &str,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let color = if hex.eq_ignore_ascii_case("reset") {
+        None
+    } else {
+        match embeds::parse_hex_color(hex) {
+            Some(rgb) => Some(rgb),
+            None => {
+                return respond_ephemeral_error(
+                    ctx,
+                    command,
+                    "Color inválido",
+                    "Usa un color hexadecimal como `#FF6B6B`, o `reset` para la paleta por defecto",
+                )
+                .await;
+            }
+        }
+    };
+
+    bot.storage.lock().await.set_embed_color(guild_id.get(), color).await?;
+
+    info!("🎨 Color de embeds en guild {} cambiado a {:?} por {}", guild_id, color, command.user.name);
+
+    let description = match color {
+        Some([r, g, b]) => format!("Los embeds de música usarán `#{:02X}{:02X}{:02X}`", r, g, b),
+        None => "Los embeds de música vuelven a la paleta por defecto".to_string(),
+    };
+    let embed = embeds::create_success_embed("Color de Embeds", &description)
+        .color(embeds::get_guild_color(color, embeds::colors::SUCCESS_GREEN));
+    respond(ctx, command, CommandReply::embed(embed)).await
+}
+
+async fn handle_settings_max_volume(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    percent: i64,
+    dj_bypass: Option<bool>,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let (max_volume, dj_bypass) = {
+        let mut storage = bot.storage.lock().await;
+        let dj_bypass = dj_bypass.unwrap_or(storage.get_volume_limit(guild_id.get()).1);
+        storage.set_volume_limit(guild_id.get(), percent as f32 / 100.0, dj_bypass).await?;
+        storage.get_volume_limit(guild_id.get())
+    };
+    let max_percent = (max_volume * 100.0).round() as i64;
+
+    info!("🔊 Techo de volumen en guild {}: {}% (DJ lo supera: {}) por {}", guild_id, max_percent, dj_bypass, command.user.name);
+
+    // Si ya suena por encima del nuevo techo, bajarlo ahora
+    if bot.player.get_volume(guild_id).await.is_some_and(|current| current > max_volume) {
+        bot.player.set_volume(guild_id, max_volume).await?;
+    }
+
+    let mut description = format!("`/volume` no podrá superar el **{}%** en este servidor", max_percent);
+    if dj_bypass {
+        description.push_str("\n🎧 El rol de DJ puede superarlo");
+    }
+    let embed = embeds::create_success_embed("Techo de Volumen", &description);
+    respond(ctx, command, CommandReply::embed(embed)).await
+}
+
+/// Muestra una página del log de auditoría del servidor (`/audit`)
+async fn handle_audit(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let page = CommandOptions::from_command(&command).get_i64("page").unwrap_or(1).max(1) as usize;
+
+    let entries = bot.recent_audit_entries(guild_id, AUDIT_PAGE_SIZE * 50).await?;
+    let (page_entries, total_pages) = crate::audit::paginate(&entries, page, AUDIT_PAGE_SIZE);
+    let embed = embeds::create_audit_embed(page_entries, page.min(total_pages), total_pages);
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().embed(embed).ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Tamaño máximo del archivo aceptado por `/import-config`
+const MAX_IMPORT_CONFIG_BYTES: u32 = 64 * 1024;
+
+/// Envía la configuración del servidor como adjunto JSON (`/export-config`)
+async fn handle_export_config(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let mut config = bot.storage.lock().await.get_server_config(guild_id.get()).await?;
+    // El proxy puede llevar credenciales y solo lo configura el dueño del bot
+    config.ytdlp_proxy_override = None;
+    let json = serde_json::to_string_pretty(&config)?;
+    let attachment = CreateAttachment::bytes(json, format!("server_config_{}.json", guild_id));
+
+    info!("📤 Configuración de guild {} exportada por {}", guild_id, command.user.name);
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content("📤 Configuración del servidor. Impórtala en otro servidor con `/import-config`.")
+                    .add_file(attachment)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Reemplaza la configuración del servidor por un JSON exportado (`/import-config`)
+async fn handle_import_config(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let attachment = CommandOptions::from_command(&command)
+        .get_attachment_id("file")
+        .and_then(|id| command.data.resolved.attachments.get(&id));
+    let Some(attachment) = attachment else {
+        return respond_ephemeral_error(ctx, &command, "Archivo requerido", "Adjunta el JSON generado con `/export-config`").await;
+    };
+    if attachment.size > MAX_IMPORT_CONFIG_BYTES {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Archivo demasiado grande",
+            &format!("El archivo no puede superar {} KB", MAX_IMPORT_CONFIG_BYTES / 1024),
+        )
+        .await;
+    }
+
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let content = attachment.download().await?;
+    let mut config: ServerConfig = match serde_json::from_slice(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            let embed = embeds::create_error_embed("Archivo inválido", &format!("No es una configuración válida: {}", e));
+            command.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let mut notes = config.sanitize_import(guild_id.get());
+    if let Some(guild) = ctx.cache.guild(guild_id) {
+        // Roles y canales de otro servidor no existen aquí
+        if config.dj_role_id.is_some_and(|id| !guild.roles.contains_key(&serenity::model::id::RoleId::new(id))) {
+            notes.push("Rol de DJ de otro servidor, se quitó".to_string());
+            config.dj_role_id = None;
+        }
+        if config.announcement_channel_id.is_some_and(|id| !guild.channels.contains_key(&ChannelId::new(id))) {
+            notes.push("Canal de anuncios de otro servidor, se quitó".to_string());
+            config.announcement_channel_id = None;
+        }
+        if config.last_voice_channel_id.is_some_and(|id| !guild.channels.contains_key(&ChannelId::new(id))) {
+            config.last_voice_channel_id = None;
+        }
+    }
+
+    bot.storage.lock().await.update_server_config(config).await?;
+    info!("📥 Configuración de guild {} importada por {} ({} correcciones)", guild_id, command.user.name, notes.len());
+
+    let mut description = "La configuración del servidor se reemplazó por la del archivo".to_string();
+    if !notes.is_empty() {
+        description.push_str("\n\n**Correcciones:**\n");
+        description.push_str(&notes.iter().map(|note| format!("• {}", note)).collect::<Vec<_>>().join("\n"));
+    }
+    let embed = embeds::create_success_embed("Configuración Importada", &description);
+    command.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await?;
+    Ok(())
+}
+
+/// Gestiona el filtro de contenido del servidor (`/settings block ...`)
+async fn handle_settings_block(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    options: CommandOptions<'_>,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let mut filter = bot.content_filter(guild_id).await;
+
+    let Some((action, options)) = options.subcommand() else {
+        return respond_ephemeral_error(ctx, command, "Subcomando desconocido", "Usa `/settings block list`").await;
+    };
+    let value = options.get_str("value").map(str::trim).unwrap_or_default();
+    let adding = options.get_str("action") == Some("add");
+
+    let (changed, title) = match action {
+        "keyword" | "channel" => {
+            let list = if action == "keyword" {
+                &mut filter.blocked_keywords
+            } else {
+                &mut filter.blocked_channels
+            };
+            if adding {
+                (content_filter::add_entry(list, value), format!("{} bloqueado", value))
+            } else {
+                (content_filter::remove_entry(list, value), format!("{} desbloqueado", value))
+            }
+        }
+        "sources" => match content_filter::parse_allowed_sources(value) {
+            Some(sources) => {
+                let changed = sources != filter.allowed_sources;
+                filter.allowed_sources = sources;
+                (changed, "Fuentes permitidas actualizadas".to_string())
+            }
+            None => {
+                return respond_ephemeral_error(
+                    ctx,
+                    command,
+                    "Fuentes inválidas",
+                    &format!("Usa una lista de {} separada por comas, o `all`", content_filter::SOURCE_NAMES.join(", ")),
+                )
+                .await;
+            }
+        },
+        _ => {
+            let embed = embeds::create_content_filter_embed(&filter, "Filtro de Contenido");
+            return respond(ctx, command, CommandReply::embed(embed)).await;
+        }
+    };
+
+    if !changed {
+        let detail = match action {
+            "sources" => "Las fuentes permitidas ya eran esas",
+            _ if adding => "Ese valor ya estaba en la lista",
+            _ => "Ese valor no estaba en la lista",
+        };
+        return respond_ephemeral_error(ctx, command, "Sin cambios", detail).await;
+    }
+
+    bot.storage.lock().await.set_content_filter(guild_id.get(), filter.clone()).await?;
+    info!("🚫 Filtro de contenido de guild {} actualizado por {}: {}", guild_id, command.user.name, title);
+
+    let embed = embeds::create_content_filter_embed(&filter, &title);
+    respond(ctx, command, CommandReply::embed(embed)).await
+}
+
+/// Configura el apodo del bot mientras suena música (`/settings bot-nickname`)
+async fn handle_settings_bot_nickname(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    pattern: &str,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    if pattern.is_empty() {
+        return respond_ephemeral_error(ctx, command, "Patrón inválido", "El patrón no puede estar vacío").await;
+    }
+
+    let pattern = (!pattern.eq_ignore_ascii_case("off")).then(|| pattern.to_string());
+    bot.storage
+        .lock()
+        .await
+        .set_bot_nickname_pattern(guild_id.get(), pattern.clone())
+        .await?;
+
+    info!("🏷️ Patrón de apodo en guild {} cambiado a {:?} por {}", guild_id, pattern, command.user.name);
+
+    let guild_name = ctx.cache.guild(guild_id).map(|g| g.name.clone()).unwrap_or_default();
+    let description = match &pattern {
+        Some(pattern) => format!(
+            "Mientras suene música me llamaré `{}`\nEjemplo: `{}`\n\nDiscord limita los cambios de apodo, así que se actualiza como mucho cada {} minutos.",
+            pattern,
+            super::nickname::format_nickname(pattern, "Never Gonna Give You Up", &guild_name),
+            super::nickname::NICKNAME_DEBOUNCE.as_secs() / 60
+        ),
+        None => "Usaré mi nombre por defecto".to_string(),
+    };
+    respond(ctx, command, CommandReply::embed(embeds::create_success_embed("Apodo del Bot", &description))).await
+}
+
+/// Activa o desactiva la detección de enlaces en mensajes (`/settings url-detect`)
+async fn handle_settings_url_detect(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    if enabled && !bot.settings().auto_detect_urls {
+        return respond_ephemeral_error(
+            ctx,
+            command,
+            "Función desactivada",
+            "La detección de enlaces está desactivada en la configuración del bot (`AUTO_DETECT_URLS`)",
+        )
+        .await;
+    }
+
+    bot.storage.lock().await.set_url_detect(guild_id.get(), enabled).await?;
+    info!("🔗 Detección de enlaces en guild {} {} por {}", guild_id, if enabled { "activada" } else { "desactivada" }, command.user.name);
+
+    let description = if enabled {
+        "Reaccionaré con ▶️ a los enlaces de YouTube o Tidal; pulsa la reacción para encolarlos"
+    } else {
+        "Ya no reaccionaré a los enlaces en los mensajes"
+    };
+    respond(ctx, command, CommandReply::embed(embeds::create_success_embed("Detección de Enlaces", description))).await
+}
+
+/// Exige o no estar en el canal de voz del bot para controlar la reproducción (`/settings same-channel`)
+async fn handle_settings_same_channel(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    bot.storage.lock().await.set_require_same_channel(guild_id.get(), enabled).await?;
+    info!("🔇 Mismo canal de voz en guild {} {} por {}", guild_id, if enabled { "exigido" } else { "no exigido" }, command.user.name);
+
+    let description = if enabled {
+        "Para pausar, saltar, cambiar el volumen o detener la música hay que estar en mi canal de voz (DJs y administradores siempre pueden)"
+    } else {
+        "Cualquiera puede controlar la reproducción, esté o no en mi canal de voz"
+    };
+    respond(ctx, command, CommandReply::embed(embeds::create_success_embed("Mismo Canal de Voz", description))).await
+}
+
+/// Activa o desactiva el limitador para volúmenes amplificados (`/settings limiter`)
+async fn handle_settings_limiter(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    bot.storage.lock().await.set_limiter(guild_id.get(), enabled).await?;
+    bot.player.set_limiter(guild_id, enabled);
+    info!("🛡️ Limitador en guild {} {} por {}", guild_id, if enabled { "activado" } else { "desactivado" }, command.user.name);
+
+    let description = if enabled {
+        format!(
+            "Con el volumen por encima de {:.0}%, un limitador suave evita que las canciones fuertes saturen (desde la próxima canción)",
+            LIMITER_VOLUME_THRESHOLD * 100.0
+        )
+    } else {
+        "Los volúmenes amplificados se reproducen sin limitador".to_string()
+    };
+    respond(ctx, command, CommandReply::embed(embeds::create_success_embed("Limitador", &description))).await
+}
+
+/// Mantiene un único "reproduciendo ahora" con botones por servidor (`/settings single-now-playing`)
+async fn handle_settings_single_now_playing(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    enabled: bool,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    bot.storage.lock().await.set_single_now_playing(guild_id.get(), enabled).await?;
+    info!(
+        "🧹 Reproduciendo ahora único en guild {} {} por {}",
+        guild_id,
+        if enabled { "activado" } else { "desactivado" },
+        command.user.name
+    );
+
+    let description = if enabled {
+        "Al publicar un nuevo \"reproduciendo ahora\" se borra el anterior: solo queda un panel de control activo"
+    } else {
+        "Cada \"reproduciendo ahora\" se conserva en el canal con sus botones"
+    };
+    respond(ctx, command, CommandReply::embed(embeds::create_success_embed("Reproduciendo Ahora", description))).await
+}
+
+/// Elige la fuente de las búsquedas por texto del servidor (`/settings search-source`)
+async fn handle_settings_search_source(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    source: &str,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+
+    let source = match source.parse::<SourceType>() {
+        Ok(source) if source.supports_text_search() => source,
+        parsed => {
+            let searchable = SourceType::ALL
+                .iter()
+                .filter(|source| source.supports_text_search())
+                .map(|source| format!("`{}`", source))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let reason = match parsed {
+                Ok(source) => format!("`{}` no admite búsquedas por texto, solo URLs", source),
+                Err(e) => e.to_string(),
+            };
+            return respond_ephemeral_error(
+                ctx,
+                command,
+                "Fuente no válida",
+                &format!("{}. Fuentes con búsqueda: {}", reason, searchable),
+            )
+            .await;
+        }
+    };
+
+    bot.storage.lock().await.set_default_search_source(guild_id.get(), source).await?;
+    info!("🔍 Fuente de búsqueda en guild {} cambiada a {} por {}", guild_id, source, command.user.name);
+
+    let description = format!("Las búsquedas por texto de `/play`, `/search` y `/add` usan `{}`; las URLs no cambian", source);
+    respond(ctx, command, CommandReply::embed(embeds::create_success_embed("Fuente de Búsqueda", &description))).await
+}
+
+/// Define o quita el proxy de yt-dlp propio del servidor (`/settings proxy`, solo dueño)
+async fn handle_settings_proxy(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    proxy: Option<&str>,
+) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, command).await;
+    }
+    if proxy.is_some_and(|proxy| !crate::config::is_valid_proxy_url(proxy)) {
+        return respond_ephemeral_error(
+            ctx,
+            command,
+            "Proxy inválido",
+            "Usa una URL `http://`, `https://`, `socks4://` o `socks5://` con host, ej: `socks5://proxy:1080`",
+        )
+        .await;
+    }
+    let guild_id = command.guild_id.unwrap();
+
+    let proxy = proxy.map(str::to_string);
+    bot.storage.lock().await.set_ytdlp_proxy_override(guild_id.get(), proxy.clone()).await?;
+    bot.player.set_proxy_override(guild_id, proxy.clone());
+
+    let description = match &proxy {
+        Some(proxy) => {
+            let masked = crate::config::mask_proxy_credentials(proxy);
+            info!("🌐 Proxy de yt-dlp de guild {} cambiado a {} por {}", guild_id, masked, command.user.name);
+            format!("yt-dlp usará `{}` en este servidor. Pruébalo con `/test-proxy`", masked)
+        }
+        None => {
+            info!("🌐 Proxy de yt-dlp de guild {} quitado por {}", guild_id, command.user.name);
+            match bot.settings().ytdlp_proxy.as_deref() {
+                Some(global) => format!(
+                    "Este servidor vuelve a usar el proxy global `{}`",
+                    crate::config::mask_proxy_credentials(global)
+                ),
+                None => "Este servidor ya no usa proxy para yt-dlp".to_string(),
+            }
+        }
+    };
+    let embed = embeds::create_success_embed("Proxy de yt-dlp", &description);
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().embed(embed).ephemeral(true)),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Responde con un embed de error visible solo para el usuario
+async fn respond_ephemeral_error(ctx: &Context, command: &CommandInteraction, title: &str, message: &str) -> Result<()> {
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embeds::create_error_embed(title, message))
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Guarda la cola actual (incluida la canción en reproducción) como playlist personal
+async fn handle_queue_save(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let user_id = command.user.id;
+
+    let name = CommandOptions::from_command(&command)
+        .get_str("name")
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string();
+
+    let name_len = name.chars().count();
+    if !(1..=50).contains(&name_len) {
+        return respond_ephemeral_error(ctx, &command, "Nombre inválido", "El nombre debe tener entre 1 y 50 caracteres").await;
+    }
+
+    let queue_info = bot.player.get_queue_info(guild_id).await?;
+    let tracks: Vec<_> = queue_info
+        .current
+        .iter()
+        .chain(queue_info.items.iter())
+        .map(|item| PlaylistTrack::from_track_source(&item.source, item.requested_by.get()))
+        .collect();
+
+    if tracks.is_empty() {
+        return respond_ephemeral_error(ctx, &command, "Cola vacía", "No hay canciones en la cola para guardar").await;
+    }
+
+    let playlist = {
+        let mut storage = bot.storage.lock().await;
+
+        let name_taken = storage
+            .get_user_playlists(user_id.get(), guild_id.get())
+            .await?
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(&name));
+        if name_taken {
+            drop(storage);
+            return respond_ephemeral_error(
+                ctx,
+                &command,
+                "Nombre en uso",
+                &format!("Ya tienes una playlist llamada **{}** en este servidor", name),
+            )
+            .await;
+        }
+
+        let playlist_id = storage.create_playlist(name, user_id.get(), guild_id.get()).await?;
+        storage.add_tracks_to_playlist(&playlist_id, tracks, user_id.get()).await?;
+        storage
+            .get_playlist(&playlist_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Playlist recién creada no encontrada: {}", playlist_id))?
+    };
+
+    info!(
+        "💾 Cola guardada como playlist '{}' ({} canciones) por {} en guild {}",
+        playlist.name,
+        playlist.tracks.len(),
+        command.user.name,
+        guild_id
+    );
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().embed(embeds::create_queue_saved_embed(&playlist)),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Busca por ID o nombre una playlist de la guild que el usuario puede editar
+async fn find_editable_playlist(bot: &OpenMusicBot, guild_id: GuildId, user_id: UserId, target: &str) -> Result<Option<UserPlaylist>> {
+    let mut storage = bot.storage.lock().await;
+    let by_id = storage
+        .get_playlist(target)
+        .await?
+        .filter(|p| p.guild_id == guild_id.get() && p.can_edit(user_id.get()));
+    if by_id.is_some() {
+        return Ok(by_id);
+    }
+
+    // Con nombres repetidos, primero las propias
+    let mut matches: Vec<_> = storage
+        .get_editable_playlists(user_id.get(), guild_id.get())
+        .await?
+        .into_iter()
+        .filter(|p| p.name.eq_ignore_ascii_case(target))
+        .collect();
+    matches.sort_by_key(|p| p.owner_id != user_id.get());
+    Ok(matches.into_iter().next())
+}
+
+/// Playlists guardadas y colaborativas (`/playlists list|add-editor|remove-editor|add-track|remove-track|activity|export|import`)
+async fn handle_playlists(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let user_id = command.user.id;
+
+    let Some((subcommand, options)) = CommandOptions::from_command(&command).subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/playlists list`").await;
+    };
+
+    if subcommand == "list" {
+        let playlists = bot
+            .storage
+            .lock()
+            .await
+            .get_editable_playlists(user_id.get(), guild_id.get())
+            .await?;
+        let embed = embeds::create_user_playlists_embed(&playlists, user_id.get());
+        return respond(ctx, &command, CommandReply::embed(embed)).await;
+    }
+    if subcommand == "import" {
+        return handle_playlists_import(ctx, &command, bot, options).await;
+    }
+
+    let target = options.require_str("playlist")?.trim();
+    let Some(playlist) = find_editable_playlist(bot, guild_id, user_id, target).await? else {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Playlist no encontrada",
+            &format!("No puedes editar ninguna playlist con ID o nombre **{}**", target),
+        )
+        .await;
+    };
+
+    match subcommand {
+        "add-editor" | "remove-editor" => {
+            if playlist.owner_id != user_id.get() {
+                return respond_ephemeral_error(ctx, &command, "Sin permisos", "Solo el dueño de la playlist puede cambiar sus editores").await;
+            }
+            let editor = options.require_user_id("user")?;
+            let add = subcommand == "add-editor";
+            if add && editor.to_user(&ctx.http).await.is_ok_and(|user| user.bot) {
+                return respond_ephemeral_error(ctx, &command, "Usuario inválido", "Los bots no pueden editar playlists").await;
+            }
+
+            let changed = bot
+                .storage
+                .lock()
+                .await
+                .set_playlist_editor(&playlist.id, user_id.get(), editor.get(), add)
+                .await?;
+            let reply = match (add, changed) {
+                (true, true) => {
+                    info!("👥 {} sumó un editor a la playlist '{}' (guild {})", command.user.name, playlist.name, guild_id);
+                    format!("👥 <@{}> ahora puede agregar y quitar canciones de **{}**", editor, playlist.name)
+                }
+                (true, false) => format!("<@{}> ya puede editar **{}**", editor, playlist.name),
+                (false, true) => {
+                    info!("👥 {} quitó un editor de la playlist '{}' (guild {})", command.user.name, playlist.name, guild_id);
+                    format!("👤 <@{}> ya no puede editar **{}**", editor, playlist.name)
+                }
+                (false, false) => format!("<@{}> no era editor de **{}**", editor, playlist.name),
+            };
+            respond(ctx, &command, CommandReply::content(reply)).await
+        }
+        "add-track" => {
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+                )
+                .await?;
+            let guard = InteractionGuard::new(ctx, &command);
+
+            let track = match options.get_str("query").map(str::trim).filter(|query| !query.is_empty()) {
+                Some(query) => match resolve_track(bot, guild_id, query, user_id).await {
+                    Ok(track) => track,
+                    Err(e) => return report_extraction_error(&guard, e).await,
+                },
+                None => match bot.player.get_current_track(guild_id).await {
+                    Some(track) if !track.is_radio() => track,
+                    _ => {
+                        let reply = CommandReply::notice("❌ No hay ninguna canción sonando: indica una URL o búsqueda");
+                        return guard.finish(reply, vec![]).await;
+                    }
+                },
+            };
+
+            let title = track.title();
+            let added = bot
+                .storage
+                .lock()
+                .await
+                .add_track_to_playlist(&playlist.id, PlaylistTrack::from_track_source(&track, user_id.get()), user_id.get())
+                .await?;
+            let reply = if added {
+                info!("➕ {} agregó {} a la playlist '{}' (guild {})", command.user.name, title, playlist.name, guild_id);
+                CommandReply::content(format!("➕ **{}** agregada a **{}**", title, playlist.name))
+            } else {
+                CommandReply::notice("❌ No se pudo agregar la canción a la playlist")
+            };
+            guard.finish(reply, vec![]).await
+        }
+        "remove-track" => {
+            let position = options.require_i64("position")?.max(1) as usize;
+            let removed = bot
+                .storage
+                .lock()
+                .await
+                .remove_track_from_playlist(&playlist.id, position - 1, user_id.get())
+                .await?;
+            match removed {
+                Some(track) => {
+                    info!("➖ {} quitó {} de la playlist '{}' (guild {})", command.user.name, track.title, playlist.name, guild_id);
+                    respond(ctx, &command, CommandReply::content(format!("➖ **{}** quitada de **{}**", track.title, playlist.name))).await
+                }
+                None => {
+                    respond_ephemeral_error(
+                        ctx,
+                        &command,
+                        "Posición inválida",
+                        &format!("**{}** tiene {} canciones", playlist.name, playlist.tracks.len()),
+                    )
+                    .await
+                }
+            }
+        }
+        "activity" => {
+            let embed = embeds::create_playlist_activity_embed(&playlist);
+            respond(ctx, &command, CommandReply::embed(embed)).await
+        }
+        "export" => {
+            // M3U8 es por ahora el único formato
+            let content = bot.storage.lock().await.export_playlist_to_m3u8(&playlist.id).await?;
+            let file_name: String = playlist
+                .name
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect();
+            let attachment = CreateAttachment::bytes(content, format!("{}.m3u8", file_name));
+
+            info!("📤 Playlist '{}' exportada a M3U8 por {} (guild {})", playlist.name, command.user.name, guild_id);
+
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content(format!(
+                                "📤 **{}** ({} canciones). Impórtala con `/playlists import`.",
+                                playlist.name,
+                                playlist.tracks.len()
+                            ))
+                            .add_file(attachment),
+                    ),
+                )
+                .await?;
+            Ok(())
+        }
+        _ => respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/playlists list`").await,
+    }
+}
+
+/// Tamaño máximo de un archivo M3U8 importado
+const MAX_M3U8_IMPORT_BYTES: u32 = 256 * 1024;
+
+/// Crea una playlist personal desde un archivo M3U8 (`/playlists import`)
+async fn handle_playlists_import(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    options: CommandOptions<'_>,
+) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let user_id = command.user.id;
+
+    let attachment = options
+        .get_attachment_id("file")
+        .and_then(|id| command.data.resolved.attachments.get(&id));
+    let Some(attachment) = attachment else {
+        return respond_ephemeral_error(ctx, command, "Archivo requerido", "Adjunta un archivo `.m3u8` o `.m3u`").await;
+    };
+    if attachment.size > MAX_M3U8_IMPORT_BYTES {
+        return respond_ephemeral_error(
+            ctx,
+            command,
+            "Archivo demasiado grande",
+            &format!("El archivo no puede superar {} KB", MAX_M3U8_IMPORT_BYTES / 1024),
+        )
+        .await;
+    }
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+    let guard = InteractionGuard::new(ctx, command);
+
+    let content = attachment.download().await?;
+    let parsed = m3u::parse_m3u8(&String::from_utf8_lossy(&content));
+    if parsed.entries.is_empty() {
+        let reply = CommandReply::notice("❌ El archivo no tiene canciones en formato M3U8");
+        return guard.finish(reply, vec![]).await;
+    }
+
+    let file_stem = attachment.filename.rsplit_once('.').map_or(attachment.filename.as_str(), |(stem, _)| stem);
+    let name = options
+        .get_str("name")
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .or(parsed.name)
+        .unwrap_or_else(|| file_stem.to_string());
+
+    let name_taken = bot
+        .storage
+        .lock()
+        .await
+        .get_user_playlists(user_id.get(), guild_id.get())
+        .await?
+        .iter()
+        .any(|p| p.name.eq_ignore_ascii_case(&name));
+    if name_taken {
+        let reply = CommandReply::notice(format!("❌ Ya tienes una playlist llamada **{}** en este servidor", name));
+        return guard.finish(reply, vec![]).await;
+    }
+
+    let truncated = parsed.entries.len().saturating_sub(m3u::MAX_M3U8_IMPORT_TRACKS);
+    let entries = &parsed.entries[..parsed.entries.len().min(m3u::MAX_M3U8_IMPORT_TRACKS)];
+    let total = entries.len();
+
+    let mut tracks = Vec::new();
+    let mut skipped = 0;
+    let mut progress = ThrottledEditor::new(&guard, PROGRESS_EDIT_INTERVAL);
+    for (i, entry) in entries.iter().enumerate() {
+        let current = i + 1;
+        // `query()` nunca es `None`: el parser descarta esas entradas
+        let query = entry.query().unwrap_or_default();
+        match resolve_track(bot, guild_id, query, user_id).await {
+            Ok(track) => tracks.push(PlaylistTrack::from_track_source(&track, user_id.get())),
+            Err(e) => {
+                debug!("Canción de M3U8 omitida ({}): {:?}", query, e);
+                skipped += 1;
+            }
+        }
+
+        // Actualizar progreso como mucho cada PROGRESS_EDIT_INTERVAL
+        let update = progress.update(|| {
+            let embed = embeds::create_playlist_import_embed(&name, current, total, tracks.len(), skipped);
+            (CommandReply::embed(embed), vec![])
+        });
+        if let Err(e) = update.await {
+            warn!("Error actualizando progreso de importación M3U8: {:?}", e);
+        }
+    }
+
+    if tracks.is_empty() {
+        let reply = CommandReply::notice("❌ No se encontró ninguna canción del archivo");
+        return guard.finish(reply, vec![]).await;
+    }
+
+    let imported = tracks.len();
+    let playlist_id = {
+        let mut storage = bot.storage.lock().await;
+        let playlist_id = storage.create_playlist(name.clone(), user_id.get(), guild_id.get()).await?;
+        storage.add_tracks_to_playlist(&playlist_id, tracks, user_id.get()).await?;
+        playlist_id
+    };
+
+    info!(
+        "📥 Playlist '{}' importada desde M3U8 por {} ({} canciones, {} omitidas) en guild {}",
+        name, command.user.name, imported, skipped, guild_id
+    );
+
+    let mut description = format!(
+        "**{}** se creó con {} canciones.\nID: `{}` · Cárgala con `/queue-load`",
+        name, imported, playlist_id
+    );
+    if skipped > 0 {
+        description.push_str(&format!("\n⚠️ {} canciones no se encontraron o están bloqueadas", skipped));
+    }
+    if truncated > 0 {
+        description.push_str(&format!(
+            "\n✂️ Se importaron solo las primeras {} entradas ({} quedaron fuera)",
+            m3u::MAX_M3U8_IMPORT_TRACKS,
+            truncated
+        ));
+    }
+    let embed = embeds::create_success_embed("Playlist Importada", &description);
+    guard.finish(CommandReply::embed(embed), vec![]).await
+}
+
+/// Carga una playlist personal (por ID o nombre) en la cola
+async fn handle_queue_load(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let user_id = command.user.id;
+
+    let options = CommandOptions::from_command(&command);
+    let target = options.require_str("playlist")?.trim();
+    let replace = options.get_str("mode") == Some("replace");
+
+    let playlist = {
+        let mut storage = bot.storage.lock().await;
+
+        let by_id = storage.get_playlist(target).await?.filter(|p| {
+            p.guild_id == guild_id.get() && (p.can_edit(user_id.get()) || p.is_public)
+        });
+
+        match by_id {
+            Some(playlist) => Some(playlist),
+            None => storage
+                .get_editable_playlists(user_id.get(), guild_id.get())
+                .await?
+                .into_iter()
+                .find(|p| p.name.eq_ignore_ascii_case(target)),
+        }
+    };
+
+    let mut playlist = match playlist {
+        Some(playlist) if !playlist.tracks.is_empty() => playlist,
+        Some(playlist) => {
+            return respond_ephemeral_error(ctx, &command, "Playlist vacía", &format!("**{}** no tiene canciones", playlist.name)).await;
+        }
+        None => {
+            return respond_ephemeral_error(
+                ctx,
+                &command,
+                "Playlist no encontrada",
+                &format!("No tienes ninguna playlist con ID o nombre **{}**", target),
+            )
+            .await;
+        }
+    };
+
+    let Ok(voice_channel_id) = get_user_voice_channel(ctx, guild_id, user_id).await else {
+        return respond_ephemeral_error(ctx, &command, "Sin canal de voz", "Debes estar en un canal de voz para cargar una playlist").await;
+    };
+
+    // Unirse al canal y encolar muchas canciones puede superar los 3 s de Discord
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await?;
+    let guard = InteractionGuard::new(ctx, &command);
+
+    if bot.get_voice_handler(guild_id).is_none() {
+        if let Err(e) = bot.join_voice_channel(ctx, guild_id, voice_channel_id).await {
+            let _ = guard
+                .finish(CommandReply::content("❌ No pude unirme al canal de voz"), vec![])
+                .await;
+            return Err(e);
+        }
+    }
+
+    if replace {
+        bot.player.stop(guild_id).await?;
+    }
+
+    let filter = bot.content_filter(guild_id).await;
+    let mut added = 0;
+    let mut blocked = 0;
+    {
+        let queue = bot.player.get_or_create_queue(guild_id);
+        let mut q = queue.write();
+        for track in &playlist.tracks {
+            let source = track.to_track_source(user_id);
+            if filter.check(&source).is_err() {
+                blocked += 1;
+                continue;
+            }
+            if q.add_track(source).is_err() {
+                break; // Cola llena
+            }
+            added += 1;
+        }
+    }
+
+    if !bot.player.is_playing(guild_id).await {
+        if let Some(handler) = bot.get_voice_handler(guild_id) {
+            if let Err(e) = bot.player.play_next(guild_id, handler).await {
+                warn!("Error iniciando reproducción de playlist personal: {:?}", e);
+            }
+        }
+    }
+
+    playlist.increment_play_count();
+    let (name, total) = (playlist.name.clone(), playlist.tracks.len());
+    bot.storage.lock().await.update_playlist(playlist).await?;
+
+    info!("📂 Playlist '{}' cargada ({} canciones) por {} en guild {}", name, added, command.user.name, guild_id);
+
+    let mut description = format!(
+        "**{}**: {} canciones {}",
+        name,
+        added,
+        if replace { "reemplazaron la cola" } else { "agregadas a la cola" }
+    );
+    if blocked > 0 {
+        description.push_str(&format!("\n🚫 {} canciones bloqueadas en este servidor", blocked));
+    }
+    if added + blocked < total {
+        description.push_str(&format!("\n⚠️ {} canciones no cupieron en la cola", total - added - blocked));
+    }
+
+    guard
+        .finish(CommandReply::embed(embeds::create_success_embed("Playlist Cargada", &description)), vec![])
+        .await
+}
+
+async fn handle_notify(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let enabled = CommandOptions::from_command(&command).require_str("state")? == "on";
+
+    bot.storage
+        .lock()
+        .await
+        .set_notify_on_play(command.user.id.get(), enabled)
+        .await?;
+
+    let message = if enabled {
+        "📨 Te avisaré por DM cuando empiece a sonar una canción que pediste. Si tienes los DMs cerrados, los avisos se desactivan solos."
+    } else {
+        "🔕 Avisos por DM desactivados"
+    };
+    respond(ctx, &command, CommandReply::notice(message)).await
+}
+
+/// Exporta o borra los datos del usuario en el servidor (`/my-data export|delete`)
+async fn handle_my_data(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let user_id = command.user.id;
+    let subcommand = CommandOptions::from_command(&command).subcommand().map(|(name, _)| name);
+
+    match subcommand {
+        Some("export") => {
+            let data = bot.export_user_data(guild_id, user_id).await?;
+            let json = serde_json::to_string_pretty(&data)?;
+            let attachment = CreateAttachment::bytes(json, format!("my_data_{}.json", guild_id));
+
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content("📦 Estos son los datos que guardo sobre ti en este servidor.")
+                            .add_file(attachment)
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            Ok(())
+        }
+        Some("delete") => {
+            let report = bot.delete_user_data(guild_id, user_id).await?;
+
+            // La entrada no identifica al usuario: solo deja constancia del borrado
+            let entry = AuditEntry {
+                timestamp: Utc::now(),
+                user_id: 0,
+                username: "usuario eliminado".to_string(),
+                command: "my-data delete".to_string(),
+                details: AuditDetails {
+                    query: Some(format!("{} registros borrados", report.total())),
+                    ..Default::default()
+                },
+            };
+            bot.log_audit_event(guild_id, entry).await;
+
+            let description = format!(
+                "• Playlists: {}\n• Playlists compartidas: {}\n• Historial de playlists: {}\n• Preferencias: {}\n\
+                 • Valoraciones: {}\n• Votos: {}\n• Estadísticas de reproducción: {}",
+                report.playlists,
+                report.collaborations,
+                report.history,
+                report.preferences,
+                report.ratings,
+                report.votes,
+                report.play_stats
+            );
+            command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .embed(embeds::create_success_embed("Datos Borrados", &description))
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            Ok(())
+        }
+        _ => respond_ephemeral_error(ctx, &command, "Subcomando desconocido", "Usa `/my-data export` o `/my-data delete`").await,
+    }
+}
+
+async fn handle_health(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    // Las comprobaciones lanzan yt-dlp y ffmpeg: diferir para no agotar los 3s
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+        )
+        .await?;
+
+    let report = bot.health_report().await;
+    let system_metrics = bot.monitoring.get_system_metrics().await;
+
+    let components = report
+        .components
+        .entries()
+        .iter()
+        .map(|(name, status)| {
+            let emoji = match status {
+                ComponentStatus::Ok => "✅",
+                ComponentStatus::Error => "❌",
+                ComponentStatus::Missing => "➖",
+            };
+            format!("{} `{}`", emoji, name)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let audio_backend = if report.components.audio_backend_ok() {
+        "✅ Disponible"
+    } else {
+        "❌ No disponible (los pedidos de canciones se rechazan)"
+    };
+
+    // Versión de yt-dlp y, si la actualizó el mantenimiento, la anterior
+    let ytdlp_version = match DEPENDENCY_VERSIONS.history("yt-dlp").as_slice() {
+        [] => "sin comprobar".to_string(),
+        [(_, version)] => format!("`{}`", version),
+        [.., (_, previous), (since, version)] => {
+            format!("`{}` (antes `{}`, actualizado <t:{}:R>)", version, previous, since.timestamp())
+        }
+    };
+
+    let embed = embeds::create_info_embed(
+        &format!("{} Estado de Salud del Bot", report.status.emoji()),
+        &format!(
+            "**Estado**: {:?}\n**Backend de audio**: {}\n**yt-dlp**: {}\n**Tiempo activo**: {}\n**Servidores**: {}\n**Comandos procesados**: {}\n**Errores**: {}\n**Tasa de error**: {:.2}%\n\n{}",
+            report.status,
+            audio_backend,
+            ytdlp_version,
+            humantime::format_duration(Duration::from_secs(report.uptime_seconds)),
+            report.guild_count,
+            system_metrics.total_commands,
+            system_metrics.total_errors,
+            system_metrics.error_rate,
+            components
+        )
+    );
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_metrics(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let metrics_type = CommandOptions::from_command(&command)
+        .get_str("type")
+        .unwrap_or("performance");
+
+    match metrics_type {
+        "performance" => {
+            let system_metrics = bot.monitoring.get_system_metrics().await;
+            let embed = embeds::create_info_embed(
+                "📊 Métricas de Rendimiento",
                 &format!(
                     "**Tiempo activo**: {:?}\n**Comandos totales**: {}\n**Tasa de error**: {:.2}%\n**Estado**: {:?}",
                     system_metrics.uptime,
@@ -1789,6 +5056,11 @@ async fn handle_metrics(ctx: &Context, command: CommandInteraction, bot: &OpenMu
                     category.total_count
                 ));
             }
+
+            description.push_str("\n**Fallos por origen (24h)**\n");
+            for (category, count) in ERROR_STATS.counts_within(ERROR_WINDOW) {
+                description.push_str(&format!("**{}**: {}\n", category.display_name(), count));
+            }
             
             let embed = embeds::create_info_embed("🔍 Reporte de Errores", &description);
             
@@ -1833,103 +5105,333 @@ async fn handle_metrics(ctx: &Context, command: CommandInteraction, bot: &OpenMu
 }
 
 
+// ===== COMANDOS DE ADMINISTRACIÓN =====
 
-/// Selecciona el mejor resultado basándose en heurísticas de relevancia
-#[allow(dead_code)]
-fn select_best_result(results: &[TrackSource], query: &str) -> TrackSource {
-    if results.is_empty() {
-        panic!("No se pueden seleccionar resultados de una lista vacía");
+/// Verifica si el usuario es el dueño del bot (`OWNER_ID` en la config)
+fn is_bot_owner(bot: &OpenMusicBot, user_id: UserId) -> bool {
+    bot.settings().owner_id == Some(user_id.get())
+}
+
+/// Responde con un mensaje ephemeral de acceso denegado para comandos de dueño
+async fn respond_owner_only(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content("🔒 Este comando solo puede usarlo el dueño del bot")
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Muestra el estado interno del reproductor de una guild (solo dueño)
+async fn handle_debug(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, &command).await;
     }
-    
-    if results.len() == 1 {
-        return results[0].clone();
+
+    // Por defecto la guild actual; el dueño puede inspeccionar otra por ID
+    let guild_id = match CommandOptions::from_command(&command).get_str("guild") {
+        Some(raw) => match raw.trim().parse::<u64>() {
+            Ok(id) if id > 0 => GuildId::new(id),
+            _ => return respond_ephemeral_error(ctx, &command, "ID inválido", "El ID de guild debe ser numérico").await,
+        },
+        None => command.guild_id.unwrap(),
+    };
+
+    let state = bot.player.debug_state(guild_id).await;
+    let handler = bot.get_voice_handler(guild_id);
+    let voice_channel = match &handler {
+        Some(handler) => handler.lock().await.current_channel().map(|c| c.0.get()),
+        None => None,
+    };
+
+    let embed = embeds::create_debug_embed(guild_id, &state, handler.is_some(), voice_channel);
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn handle_clearcache(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, &command).await;
     }
-    
-    let query_lower = query.to_lowercase();
-    let mut best_result = &results[0];
-    let mut best_score = 0.0;
-    
-    for result in results {
-        let mut score = 0.0;
-        let title_lower = result.title().to_lowercase();
-        
-        // Factor 1: Coincidencia exacta en el título (peso alto)
-        if title_lower.contains(&query_lower) {
-            score += 100.0;
-        }
-        
-        // Factor 2: Similitud de palabras clave
-        let query_words: Vec<&str> = query_lower.split_whitespace().collect();
-        let title_words: Vec<&str> = title_lower.split_whitespace().collect();
-        
-        for query_word in &query_words {
-            if query_word.len() > 2 { // Solo palabras significativas
-                for title_word in &title_words {
-                    if title_word.contains(query_word) {
-                        score += 50.0;
-                    }
-                }
-            }
-        }
-        
-        // Factor 3: Penalizar ciertos tipos de contenido
-        let title_penalties = [
-            ("remix", -20.0),
-            ("cover", -15.0),
-            ("karaoke", -30.0),
-            ("instrumental", -25.0),
-            ("live", -10.0),
-            ("8d", -20.0),
-            ("slowed", -15.0),
-            ("reverb", -15.0),
-            ("speed", -20.0),
-            ("nightcore", -25.0),
-        ];
-        
-        for (penalty_word, penalty_value) in &title_penalties {
-            if title_lower.contains(penalty_word) {
-                score += penalty_value;
-            }
-        }
-        
-        // Factor 4: Preferir contenido oficial
-        let official_bonus = [
-            ("official", 30.0),
-            ("music video", 25.0),
-            ("video oficial", 30.0),
-            ("official music", 35.0),
-        ];
-        
-        for (bonus_word, bonus_value) in &official_bonus {
-            if title_lower.contains(bonus_word) {
-                score += bonus_value;
-            }
-        }
-        
-        // Factor 5: Preferir duraciones normales para canciones (2-8 minutos)
-        if let Some(duration) = result.duration() {
-            let duration_secs = duration.as_secs();
-            if duration_secs >= 120 && duration_secs <= 480 { // 2-8 minutos
-                score += 10.0;
-            } else if duration_secs < 60 || duration_secs > 600 { // Muy corto o muy largo
-                score -= 15.0;
-            }
-        }
-        
-        // Factor 6: Bonus por artista conocido (si coincide con la búsqueda)
-        if let Some(artist) = result.artist() {
-            let artist_lower = artist.to_lowercase();
-            if query_lower.contains(&artist_lower) || artist_lower.contains(&query_lower) {
-                score += 40.0;
-            }
-        }
-        
-        // Actualizar el mejor resultado si este tiene mejor score
-        if score > best_score {
-            best_score = score;
-            best_result = result;
+
+    let target = CommandOptions::from_command(&command)
+        .get_str("target")
+        .unwrap_or("all");
+
+    let clear_metadata = matches!(target, "all" | "metadata");
+    let clear_search = matches!(target, "all" | "search");
+
+    let mut lines = Vec::new();
+    let mut total = 0;
+
+    if clear_metadata {
+        let removed = bot.cache.clear();
+        total += removed;
+        lines.push(format!("🎵 **Metadata**: {} entradas", removed));
+    }
+
+    if clear_search {
+        let removed = super::search::SEARCH_SESSIONS.len();
+        super::search::SEARCH_SESSIONS.clear();
+        total += removed;
+        lines.push(format!("🔍 **Búsquedas**: {} sesiones", removed));
+
+        let removed = AUTOCOMPLETE_CACHE.clear();
+        total += removed;
+        lines.push(format!("💡 **Sugerencias**: {} entradas", removed));
+    }
+
+    info!("🧹 Cache limpiado por {} (target: {}): {} entradas", command.user.name, target, total);
+
+    let embed = embeds::create_success_embed(
+        "Cache Limpiado",
+        &format!("{}\n\n**Total eliminado**: {}", lines.join("\n"), total),
+    );
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_cachestats(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, &command).await;
+    }
+
+    let metrics = bot.cache.metrics();
+    let entries = bot.cache.len();
+    let capacity = bot.cache.capacity();
+    let fill = if capacity > 0 { entries as f64 / capacity as f64 * 100.0 } else { 0.0 };
+
+    let pressure = if fill >= 90.0 {
+        "🚨 Alta"
+    } else if fill >= 70.0 {
+        "⚠️ Media"
+    } else {
+        "✅ Baja"
+    };
+
+    let embed = embeds::create_info_embed(
+        "Estadísticas de Cache",
+        &format!(
+            "**Entradas de metadata**: {}/{} ({:.1}%)\n**Presión**: {}\n**Aciertos**: {}\n**Fallos**: {}\n**Tasa de aciertos**: {:.1}%\n**Expiradas eliminadas**: {}\n**Sesiones de búsqueda**: {}",
+            entries,
+            capacity,
+            fill,
+            pressure,
+            metrics.hits,
+            metrics.misses,
+            metrics.hit_rate() * 100.0,
+            metrics.expired_removals,
+            super::search::SEARCH_SESSIONS.len()
+        ),
+    );
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_cache(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    let line = |name: &str, entries: usize, capacity: usize, hit_ratio: f64| {
+        format!(
+            "**{}**: {}/{} entradas ({:.0}%) · {:.1}% de aciertos",
+            name,
+            entries,
+            capacity,
+            entries as f64 / capacity as f64 * 100.0,
+            hit_ratio * 100.0
+        )
+    };
+    let embed = embeds::create_info_embed(
+        "🗃️ Cache",
+        &format!(
+            "{}\n{}",
+            line("Metadata", bot.cache.len(), bot.cache.capacity(), bot.cache.hit_ratio()),
+            line(
+                "Autocompletado",
+                AUTOCOMPLETE_CACHE.len(),
+                AUTOCOMPLETE_CACHE.capacity(),
+                AUTOCOMPLETE_CACHE.hit_ratio()
+            ),
+        ),
+    );
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_config_reload(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, &command).await;
+    }
+
+    info!("🔄 Recarga de configuración solicitada por {}", command.user.name);
+
+    let embed = match bot.reload_config() {
+        Ok(changes) if changes.is_empty() => embeds::create_info_embed(
+            "Configuración Recargada",
+            "No hubo cambios en la configuración",
+        ),
+        Ok(changes) => embeds::create_success_embed(
+            "Configuración Recargada",
+            &format!(
+                "**Campos modificados**:\n{}",
+                changes
+                    .iter()
+                    .map(|change| format!("• `{}`", change))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        ),
+        Err(e) => {
+            warn!("Error recargando configuración: {:?}", e);
+            embeds::create_error_embed(
+                "Configuración Inválida",
+                &format!("Se mantiene la configuración actual.\n\n**Error**: {}", e),
+            )
         }
+    };
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Cambia el archivo de cookies de yt-dlp hasta el próximo reinicio o `/config-reload` (solo dueño)
+async fn handle_cookies(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, &command).await;
     }
-    
-    best_result.clone()
+    let options = CommandOptions::from_command(&command);
+    let Some(("set-file", sub)) = options.subcommand() else {
+        return respond_ephemeral_error(ctx, &command, "Subcomando inválido", "Usa `/cookies set-file <path>`").await;
+    };
+
+    let path = std::path::PathBuf::from(sub.require_str("path")?.trim());
+    if !path.is_file() {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Archivo no encontrado",
+            &format!("`{}` no existe o no es un archivo", path.display()),
+        )
+        .await;
+    }
+
+    info!("🍪 {} cambió el archivo de cookies de yt-dlp a {}", command.user.name, path.display());
+    let embed = embeds::create_success_embed(
+        "Cookies Actualizadas",
+        &format!(
+            "yt-dlp usará `{}` hasta el próximo reinicio o `/config-reload`; para que dure, define `YTDLP_COOKIES`",
+            path.display()
+        ),
+    );
+    bot.set_cookies_file(path);
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().embed(embed).ephemeral(true)),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Comprueba que yt-dlp acepta un proxy: `yt-dlp --proxy <url> --version` (solo dueño)
+async fn handle_test_proxy(ctx: &Context, command: CommandInteraction, bot: &OpenMusicBot) -> Result<()> {
+    if !is_bot_owner(bot, command.user.id) {
+        return respond_owner_only(ctx, &command).await;
+    }
+    let guild_id = command.guild_id.unwrap();
+
+    let proxy = match CommandOptions::from_command(&command).get_str("proxy").map(str::trim) {
+        Some(proxy) if !proxy.is_empty() => Some(proxy.to_string()),
+        _ => bot.ytdlp_proxy(guild_id).await,
+    };
+    let Some(proxy) = proxy else {
+        return respond_ephemeral_error(
+            ctx,
+            &command,
+            "Sin proxy",
+            "No hay proxy configurado: indica uno o define `YTDLP_PROXY` / `/settings proxy`",
+        )
+        .await;
+    };
+    let masked = crate::config::mask_proxy_credentials(&proxy);
+    if !crate::config::is_valid_proxy_url(&proxy) {
+        return respond_ephemeral_error(ctx, &command, "Proxy inválido", &format!("`{}` no es una URL de proxy válida", masked)).await;
+    }
+
+    command.defer_ephemeral(&ctx.http).await?;
+
+    info!("🌐 Prueba del proxy {} solicitada por {}", masked, command.user.name);
+    let started = std::time::Instant::now();
+    let embed = match YtDlpOptimizedClient::test_proxy(&proxy).await {
+        Ok(title) => embeds::create_success_embed(
+            "Proxy Funcionando",
+            &format!(
+                "yt-dlp extrajo **{}** a través de `{}` en {:.1}s",
+                title,
+                masked,
+                started.elapsed().as_secs_f32()
+            ),
+        ),
+        Err(e) => {
+            warn!("Prueba del proxy {} fallida: {}", masked, e);
+            embeds::create_error_embed("Proxy Rechazado", &format!("yt-dlp falló con `{}`:\n```{}```", masked, e))
+        }
+    };
+    command.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await?;
+    Ok(())
 }
@@ -0,0 +1,107 @@
+//! Ranking de las canciones más escuchadas del servidor (`/leaderboard`).
+//!
+//! Muestra [`LEADERBOARD_PAGE_SIZE`] canciones por página; los botones ◀️/▶️
+//! llevan el período y la página en el `custom_id`, así cualquiera puede
+//! pasar de página sin estado en memoria.
+
+use serenity::{
+    all::{ButtonStyle, CommandInteraction, ComponentInteraction, Context},
+    builder::{CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage},
+    model::id::GuildId,
+};
+
+use super::OpenMusicBot;
+use crate::{
+    storage::leaderboard::{LeaderboardScope, LEADERBOARD_PAGE_SIZE},
+    ui::embeds,
+};
+
+/// Prefijo de los botones de página (`leaderboard_page:<período>:<página>`)
+pub const LEADERBOARD_PREFIX: &str = "leaderboard_page:";
+
+/// `custom_id` del botón que lleva a `page` (1-based)
+pub fn page_button_id(scope: LeaderboardScope, page: usize) -> String {
+    format!("{}{}:{}", LEADERBOARD_PREFIX, scope.as_str(), page)
+}
+
+/// Período y página de un botón de [`page_button_id`]
+pub fn parse_page_button_id(custom_id: &str) -> Option<(LeaderboardScope, usize)> {
+    let (scope, page) = custom_id.strip_prefix(LEADERBOARD_PREFIX)?.split_once(':')?;
+    Some((LeaderboardScope::parse(scope)?, page.parse().ok()?))
+}
+
+/// Embed y botones de una página del ranking; sin botones si entra en una sola
+fn leaderboard_page(bot: &OpenMusicBot, guild_id: GuildId, scope: LeaderboardScope, page: usize) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let ranking = bot.play_stats.ranking(guild_id.get(), scope);
+    let total_pages = ranking.len().div_ceil(LEADERBOARD_PAGE_SIZE).max(1);
+    let page = page.clamp(1, total_pages);
+    let start = (page - 1) * LEADERBOARD_PAGE_SIZE;
+    let end = (start + LEADERBOARD_PAGE_SIZE).min(ranking.len());
+
+    let embed = embeds::create_play_leaderboard_embed(&ranking[start..end], scope, start + 1, page, total_pages);
+    if total_pages == 1 {
+        return (embed, Vec::new());
+    }
+
+    let buttons = vec![
+        CreateButton::new(page_button_id(scope, page - 1))
+            .emoji('◀')
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 1),
+        CreateButton::new(page_button_id(scope, page + 1))
+            .emoji('▶')
+            .style(ButtonStyle::Secondary)
+            .disabled(page == total_pages),
+    ];
+    (embed, vec![CreateActionRow::Buttons(buttons)])
+}
+
+/// `/leaderboard [scope]`: primera página del ranking del período
+pub async fn handle_play_leaderboard(
+    ctx: &Context,
+    command: &CommandInteraction,
+    bot: &OpenMusicBot,
+    scope: LeaderboardScope,
+) -> anyhow::Result<()> {
+    let guild_id = command.guild_id.unwrap();
+    let (embed, components) = leaderboard_page(bot, guild_id, scope, 1);
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().embed(embed).components(components)),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Botones ◀️/▶️: reemplaza el mensaje por la página pedida
+pub async fn handle_leaderboard_page(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+) -> anyhow::Result<()> {
+    let Some((scope, page)) = parse_page_button_id(&component.data.custom_id) else {
+        component
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content("❌ Página no reconocida").ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let (embed, components) = leaderboard_page(bot, guild_id, scope, page);
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new().embed(embed).components(components),
+            ),
+        )
+        .await?;
+    Ok(())
+}
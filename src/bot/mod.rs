@@ -33,18 +33,67 @@
 use anyhow::Result;
 use dashmap::DashMap;
 use serenity::{
-    all::{ChannelId, Context, EventHandler, GuildId, Interaction, Ready, VoiceState},
+    all::{
+        Cache, ChannelId, ConnectionStage, Context, EventHandler, GuildChannel, GuildId, Interaction, Message,
+        PartialGuildChannel, Reaction, Ready, ResumedEvent, ShardStageUpdateEvent, UserId, VoiceState,
+    },
     async_trait,
+    builder::EditMessage,
 };
-use std::sync::Arc;
+use songbird::Songbird;
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tracing::{error, info, warn};
 
+pub mod command_options;
 pub mod commands;
+pub mod enrich;
 pub mod events;
+pub mod guild_stats;
 pub mod handlers;
+pub mod leaderboard;
+pub mod nickname;
+pub mod notify;
+pub mod permissions;
+pub mod playlist_load;
+pub mod prefix;
+pub mod presence;
+pub mod queue_manage;
+pub mod quiz;
+pub mod rating;
 pub mod search;
+pub mod url_detect;
+pub mod voting;
 
-use crate::{audio::player::AudioPlayer, cache::MusicCache, config::Config, storage::JsonStorage, monitoring::MonitoringSystem};
+use self::permissions::SpamDetector;
+use self::playlist_load::PlaylistLoads;
+use self::quiz::QuizSessions;
+use crate::{
+    audio::{effects::EqualizerPreset, player::{resume_point, AudioPlayer}},
+    audit::{AuditEntry, AuditLog, MAX_AUDIT_LOG_BYTES},
+    cache::MusicCache,
+    config::{self, Config, ReloadableConfig, SharedConfig},
+    errors::{record_classified_error, record_error, ErrorCategory},
+    health::{self, ComponentHealth, ComponentStatus, HealthReport, DEPENDENCY_VERSIONS},
+    monitoring::MonitoringSystem,
+    sources::{
+        ytdlp_optimized, ContentFilter, ExtractionPool, MusicSource, RetryPolicy, SourceManager, TrackSource,
+        UpdateResult, YtDlpOptimizedClient, YtDlpUpdater,
+    },
+    storage::{play_stats::PlayStatsStore, ratings::RatingStore, DeletionReport, GuildSettings, JsonStorage, PlaylistTrack, QueueStorage, SavedGuildQueue, UserDataExport},
+    ws::WsEvent,
+};
+
+/// Cada cuánto cada proceso copia las colas de sus guilds al almacenamiento compartido (Redis)
+const QUEUE_SYNC_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Cada cuánto se intenta actualizar yt-dlp (el primer intento es al arrancar)
+const YTDLP_UPDATE_INTERVAL: Duration = Duration::from_secs(24 * 3600);
 
 /// Main Discord bot handler for Open Music Bot.
 ///
@@ -55,6 +104,7 @@ use crate::{audio::player::AudioPlayer, cache::MusicCache, config::Config, stora
 ///
 /// - `config`: Bot configuration (tokens, limits, features)
 /// - `storage`: Persistent JSON storage for settings and data
+/// - `queue_store`: Saved queues, in JSON files or shared through Redis
 /// - `cache`: LRU cache for track metadata and audio data
 /// - `player`: Audio player instance for music playback
 /// - `voice_handlers`: Per-guild voice connection handlers
@@ -68,17 +118,58 @@ use crate::{audio::player::AudioPlayer, cache::MusicCache, config::Config, stora
 pub struct OpenMusicBot {
     /// Bot configuration loaded from environment variables
     config: Arc<Config>,
+    /// Hot-reloadable part of the configuration (see [`OpenMusicBot::reload_config`])
+    live_config: SharedConfig,
     /// JSON-based persistent storage (server settings, playlists, etc.)
     #[allow(dead_code)]
     pub storage: Arc<tokio::sync::Mutex<JsonStorage>>,
+    /// Where guild queues are saved (`QUEUE_BACKEND`: JSON files or Redis)
+    queue_store: Arc<dyn QueueStorage>,
     /// LRU cache for track metadata and audio data
     cache: Arc<MusicCache>,
     /// Audio player for music playback and queue management
     pub player: Arc<AudioPlayer>,
     /// Voice connection handlers per Discord guild
     voice_handlers: DashMap<GuildId, Arc<tokio::sync::Mutex<songbird::Call>>>,
+    /// Hilo de la sesión de música activa por guild (`/session start`)
+    session_threads: DashMap<GuildId, ChannelId>,
+    /// Pool de extracciones (yt-dlp) con cupo por guild
+    extractions: Arc<ExtractionPool>,
+    /// Límite de canciones pedidas por minuto por usuario
+    pub spam_detector: SpamDetector,
+    /// Carga de `/playlist` en curso por guild (botones confirmar/cancelar)
+    pub playlist_loads: PlaylistLoads,
+    /// Quiz musical en curso por guild (`/quiz start`)
+    pub quizzes: Arc<QuizSessions>,
+    /// Log de auditoría de comandos por guild (`data/audit/`)
+    audit: Arc<AuditLog>,
+    /// Valoraciones ⭐ de las canciones por guild (`data/ratings/`)
+    pub ratings: Arc<RatingStore>,
+    /// Reproducciones acumuladas por guild para `/stats guild` y `/leaderboard` (`data/stats/`)
+    pub play_stats: Arc<PlayStatsStore>,
+    /// Canal donde se mostró el último "reproduciendo ahora" de cada guild,
+    /// donde se pide valorar la canción al terminar
+    now_playing_channels: Arc<DashMap<GuildId, ChannelId>>,
     /// Sistema de monitoreo para métricas y logs
     pub monitoring: Arc<MonitoringSystem>,
+    /// Momento de arranque, para el `uptime_seconds` del endpoint de salud
+    started_at: Instant,
+    /// Caché de Serenity, disponible desde el primer `ready`
+    discord_cache: OnceLock<Arc<Cache>>,
+    /// `true` mientras el shard está conectado al gateway
+    discord_connected: AtomicBool,
+    /// `false` si yt-dlp o ffmpeg no respondieron en la última comprobación
+    audio_backend_available: Arc<AtomicBool>,
+    /// Señal de apagado compartida con las tareas en segundo plano
+    shutdown_tx: broadcast::Sender<()>,
+    /// Evita que el apagado se ejecute dos veces (`true` una vez completado)
+    shutdown_lock: tokio::sync::Mutex<bool>,
+    /// `true` desde que el primer `ready` lanzó las tareas en segundo plano y cargó los datos
+    background_started: AtomicBool,
+    /// `true` desde que el primer `ready` lanzó la restauración de colas
+    queues_restored: AtomicBool,
+    /// Referencia a sí mismo para lanzar tareas desde los eventos de Serenity
+    this: Weak<OpenMusicBot>,
 }
 
 impl OpenMusicBot {
@@ -89,10 +180,12 @@ impl OpenMusicBot {
     /// * `config` - Bot configuration (Discord tokens, audio settings, etc.)
     /// * `storage` - Persistent storage for server settings and data
     /// * `cache` - LRU cache for track metadata and performance optimization
+    /// * `monitoring` - Metrics and logging system
+    /// * `queue_store` - Backend for saved queues (see [`crate::storage::open_queue_store`])
     ///
     /// # Returns
     ///
-    /// A new [`OpenMusicBot`] instance ready to handle Discord events.
+    /// A new shared [`OpenMusicBot`] instance ready to handle Discord events.
     ///
     /// # Example
     ///
@@ -110,18 +203,53 @@ impl OpenMusicBot {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn new(config: Config, storage: Arc<tokio::sync::Mutex<JsonStorage>>, cache: Arc<MusicCache>, monitoring: Arc<MonitoringSystem>) -> Self {
+    pub fn new(
+        config: Config,
+        storage: Arc<tokio::sync::Mutex<JsonStorage>>,
+        cache: Arc<MusicCache>,
+        monitoring: Arc<MonitoringSystem>,
+        queue_store: Arc<dyn QueueStorage>,
+    ) -> Arc<Self> {
+        let live_config = ReloadableConfig::shared(&config);
         let config = Arc::new(config);
-        let player = Arc::new(AudioPlayer::new(config.default_volume));
+        let player = Arc::new(AudioPlayer::new(config.default_volume, config.smooth_volume));
+        player.set_loudness_normalization(config.loudness_normalization);
+        player.set_ytdlp_proxy(config.ytdlp_proxy.clone());
+        ytdlp_optimized::set_cookies_file(config.ytdlp_cookies_file.clone());
+        ytdlp_optimized::set_cookies_from_browser(config.ytdlp_cookies_from_browser.clone());
+        let audit = Arc::new(AuditLog::new(config.data_dir.join("audit"), MAX_AUDIT_LOG_BYTES));
+        let ratings = Arc::new(RatingStore::new(config.data_dir.join("ratings")));
+        let play_stats = Arc::new(PlayStatsStore::new(config.data_dir.join("stats")));
+        let (shutdown_tx, _) = broadcast::channel(1);
 
-        Self {
+        Arc::new_cyclic(|this| Self {
             config,
+            live_config,
             storage,
+            queue_store,
             cache,
             player,
             voice_handlers: DashMap::new(),
+            session_threads: DashMap::new(),
+            extractions: Arc::new(ExtractionPool::default()),
+            spam_detector: SpamDetector::new(),
+            playlist_loads: PlaylistLoads::new(),
+            quizzes: Arc::new(QuizSessions::new()),
+            audit,
+            ratings,
+            play_stats,
+            now_playing_channels: Arc::new(DashMap::new()),
             monitoring,
-        }
+            started_at: Instant::now(),
+            discord_cache: OnceLock::new(),
+            discord_connected: AtomicBool::new(false),
+            audio_backend_available: Arc::new(AtomicBool::new(true)),
+            shutdown_tx,
+            shutdown_lock: tokio::sync::Mutex::new(false),
+            background_started: AtomicBool::new(false),
+            queues_restored: AtomicBool::new(false),
+            this: this.clone(),
+        })
     }
 
     /// Registers slash commands with Discord.
@@ -130,6 +258,9 @@ impl OpenMusicBot {
     /// (faster updates, useful for development). The registration strategy is
     /// determined by the `guild_id` configuration option.
     ///
+    /// Registration is skipped when the command set is unchanged since the last
+    /// run (hash stored in `JsonStorage`) and Discord still lists the same commands.
+    ///
     /// # Arguments
     ///
     /// * `ctx` - Discord context for API operations
@@ -167,21 +298,31 @@ impl OpenMusicBot {
                     return Ok(()); // No fallar, pero no registrar comandos
                 }
                 
-                commands::register_guild_commands(ctx, guild_id).await
+                let registered = commands::register_guild_commands(ctx, guild_id, &self.storage).await
                     .map_err(|e| {
                         error!("❌ Error registrando comandos de guild: {:?}", e);
+                        record_error(ErrorCategory::DiscordApi);
                         anyhow::anyhow!("No se pudieron registrar comandos de guild. Verifica que el bot tenga permisos de 'applications.commands' en la guild.")
                     })?;
-                info!("✅ Comandos de guild registrados para: {}", guild_id);
+                if registered {
+                    info!("✅ Comandos de guild registrados para: {}", guild_id);
+                } else {
+                    info!("⏭️ Comandos de guild sin cambios para: {} (registro omitido)", guild_id);
+                }
             },
             None => {
                 info!("🌐 Registrando comandos globalmente");
-                commands::register_global_commands(ctx).await
+                let registered = commands::register_global_commands(ctx, &self.storage).await
                     .map_err(|e| {
                         error!("❌ Error registrando comandos globales: {:?}", e);
+                        record_error(ErrorCategory::DiscordApi);
                         anyhow::anyhow!("No se pudieron registrar comandos globales. Verifica que el bot tenga permisos de 'applications.commands'.")
                     })?;
-                info!("✅ Comandos globales registrados");
+                if registered {
+                    info!("✅ Comandos globales registrados");
+                } else {
+                    info!("⏭️ Comandos globales sin cambios (registro omitido)");
+                }
             }
         }
 
@@ -194,6 +335,10 @@ impl OpenMusicBot {
     /// for future audio operations. The connection is automatically managed
     /// and will be cleaned up when the bot is disconnected.
     ///
+    /// If the bot is already connected in the guild, the existing call is
+    /// moved to `channel_id` without interrupting playback. The channel is
+    /// recorded as the guild's last voice channel.
+    ///
     /// # Arguments
     ///
     /// * `ctx` - Discord context for API operations
@@ -241,23 +386,56 @@ impl OpenMusicBot {
                 {
                     let mut call = connection_info.lock().await;
                     call.set_bitrate(songbird::driver::Bitrate::Bits(
-                        self.config.opus_bitrate as i32,
+                        self.settings().opus_bitrate as i32,
                     ));
+
+                    // Retomar la pista si el driver se reconecta. El `Call` se
+                    // reutiliza entre joins, así que se evita duplicar el handler.
+                    call.remove_all_global_events();
+                    call.add_global_event(
+                        songbird::Event::Core(songbird::events::CoreEvent::DriverReconnect),
+                        events::ReconnectHandler {
+                            guild_id,
+                            player: self.player.clone(),
+                            handler: connection_info.clone(),
+                        },
+                    );
                 }
 
                 // Guardar handler para uso futuro
                 self.voice_handlers
                     .insert(guild_id, connection_info.clone());
 
+                if let Err(e) = self
+                    .storage
+                    .lock()
+                    .await
+                    .set_last_voice_channel(guild_id.get(), channel_id.get())
+                    .await
+                {
+                    warn!("Error guardando último canal de voz: {:?}", e);
+                }
+
+                let (silence_skip, limiter, proxy) = {
+                    let mut storage = self.storage.lock().await;
+                    let proxy = storage.get_ytdlp_proxy_override(guild_id.get()).await.unwrap_or_default();
+                    (storage.get_silence_skip(guild_id.get()), storage.get_limiter(guild_id.get()), proxy)
+                };
+                self.player.set_silence_detection(guild_id, silence_skip);
+                self.player.set_limiter(guild_id, limiter);
+                self.player.set_proxy_override(guild_id, proxy);
+                self.player.emit(WsEvent::BotJoinedVoice { guild_id, channel_id });
+
                 info!(
                     "🔊 Conectado al canal de voz en guild {} (Opus {} kbps)",
                     guild_id,
-                    self.config.opus_bitrate / 1000
+                    self.settings().opus_bitrate / 1000
                 );
                 Ok(())
             }
             Err(e) => {
                 error!("Error al obtener handler de voz: {:?}", e);
+                record_error(ErrorCategory::Voice);
                 Err(anyhow::anyhow!("Error al conectar al canal de voz"))
             }
         }
@@ -288,13 +466,471 @@ impl OpenMusicBot {
             .await
             .ok_or_else(|| anyhow::anyhow!("Songbird no inicializado"))?;
 
+        self.leave_with_manager(&manager, guild_id).await
+    }
+
+    /// Revisa cada conexión de voz y vuelve a conectar las que quedaron caídas.
+    ///
+    /// Una conexión está viva si el `Call` tiene conexión activa y Discord (según
+    /// la caché del gateway) ve al bot en ese mismo canal.
+    async fn recover_voice_connections(&self, ctx: &Context) {
+        let bot_id = ctx.cache.current_user().id;
+        let guilds: Vec<GuildId> = self.voice_handlers.iter().map(|entry| *entry.key()).collect();
+
+        for guild_id in guilds {
+            let Some(handler) = self.get_voice_handler(guild_id) else {
+                continue;
+            };
+            let (call_channel, connected) = {
+                let call = handler.lock().await;
+                (
+                    call.current_channel().map(|channel| ChannelId::new(channel.0.get())),
+                    call.current_connection().is_some(),
+                )
+            };
+            let discord_channel = ctx
+                .cache
+                .guild(guild_id)
+                .and_then(|guild| guild.voice_states.get(&bot_id).and_then(|state| state.channel_id));
+
+            if connected && call_channel.is_some() && call_channel == discord_channel {
+                continue;
+            }
+            let Some(channel_id) = call_channel.or(discord_channel) else {
+                continue;
+            };
+
+            warn!(
+                "🧟 Conexión de voz caída en guild {} tras reanudar el gateway, reconectando a {}",
+                guild_id, channel_id
+            );
+            if let Err(e) = self.join_voice_channel(ctx, guild_id, channel_id).await {
+                warn!("No se pudo reconectar al canal de voz en guild {}: {:?}", guild_id, e);
+                record_error(ErrorCategory::Voice);
+                continue;
+            }
+            let Some(handler) = self.get_voice_handler(guild_id) else {
+                continue;
+            };
+            match self.player.rebuild_current_input(guild_id, handler).await {
+                Ok(true) => info!("▶️ Reproducción retomada tras reconectar (guild {})", guild_id),
+                Ok(false) => {}
+                Err(e) => warn!("Error retomando la reproducción en guild {}: {:?}", guild_id, e),
+            }
+        }
+    }
+
+    /// Desconecta de una guild usando directamente el manager de Songbird.
+    ///
+    /// Permite desconectar fuera de un evento de Discord (sin [`Context`]),
+    /// como ocurre durante el apagado.
+    async fn leave_with_manager(&self, manager: &Songbird, guild_id: GuildId) -> Result<()> {
         manager.remove(guild_id).await?;
         self.voice_handlers.remove(&guild_id);
+        self.player.emit(WsEvent::BotLeftVoice { guild_id });
 
         info!("👋 Desconectado del canal de voz en guild {}", guild_id);
         Ok(())
     }
 
+    /// Performs a graceful shutdown of the bot.
+    ///
+    /// Notifies background tasks through the shutdown broadcast channel, persists
+    /// the queue of every active guild (including the playback position of the
+    /// current track and the voice channel) and then disconnects from voice.
+    ///
+    /// # Arguments
+    ///
+    /// * `manager` - Songbird manager used to disconnect the voice calls
+    ///
+    /// Calling it more than once is a no-op.
+    pub async fn shutdown(&self, manager: &Songbird) {
+        let mut done = self.shutdown_lock.lock().await;
+        if *done {
+            return;
+        }
+
+        info!("🛑 Iniciando apagado graceful...");
+        let _ = self.shutdown_tx.send(());
+
+        let mut saved = 0;
+        for guild_id in self.player.active_guilds() {
+            match self.save_guild_state(guild_id).await {
+                Ok(true) => saved += 1,
+                Ok(false) => {}
+                Err(e) => error!("Error guardando cola de guild {}: {:?}", guild_id, e),
+            }
+        }
+
+        let guilds: Vec<GuildId> = self.voice_handlers.iter().map(|entry| *entry.key()).collect();
+        for guild_id in guilds {
+            // Detener primero para que el fin de pista no avance la cola
+            if let Err(e) = self.player.stop(guild_id).await {
+                warn!("Error deteniendo reproducción en guild {}: {:?}", guild_id, e);
+            }
+            if let Err(e) = self.leave_with_manager(manager, guild_id).await {
+                warn!("Error desconectando de guild {}: {:?}", guild_id, e);
+            }
+        }
+
+        *done = true;
+        info!("✅ Apagado completado ({} colas guardadas)", saved);
+    }
+
+    /// Returns the current hot-reloadable configuration.
+    ///
+    /// Never blocks; always reflects the latest successful reload.
+    pub fn settings(&self) -> Arc<ReloadableConfig> {
+        self.live_config.load_full()
+    }
+
+    /// Runs a source extraction for `guild_id` through the shared extraction pool.
+    ///
+    /// Each guild gets a bounded share of the pool so a slow or stuck guild
+    /// cannot starve the others, and the extraction is aborted with
+    /// [`ExtractionTimeoutError`](crate::sources::ExtractionTimeoutError) after
+    /// the configured `extraction_timeout`.
+    pub async fn run_extraction<T>(&self, guild_id: GuildId, extraction: impl Future<Output = Result<T>>) -> Result<T> {
+        let timeout = Duration::from_secs(self.settings().extraction_timeout);
+        let result = self.extractions.run(guild_id, timeout, extraction).await;
+        if result.is_err() {
+            record_error(ErrorCategory::Extraction);
+        }
+        result
+    }
+
+    /// Fills in, in the background, the metadata that a flat playlist
+    /// extraction leaves out (duration, thumbnail) for the queued `tracks`.
+    ///
+    /// Extractions go through the shared pool, results are cached and the
+    /// queue is updated in place, so `/queue` shows them on its next render.
+    pub fn spawn_queue_enrichment(&self, guild_id: GuildId, tracks: Vec<TrackSource>) {
+        if !tracks.iter().any(enrich::needs_enrichment) {
+            return;
+        }
+        let settings = self.settings();
+        let enricher = enrich::QueueEnricher {
+            player: self.player.clone(),
+            cache: self.cache.clone(),
+            extractions: self.extractions.clone(),
+            client: YtDlpOptimizedClient::with_retry_policy(RetryPolicy::from_config(&settings))
+                .with_proxy(self.player.ytdlp_proxy(guild_id)),
+            timeout: Duration::from_secs(settings.extraction_timeout),
+        };
+        let shutdown_rx = self.subscribe_shutdown();
+        tokio::spawn(enricher.run(guild_id, tracks, shutdown_rx));
+    }
+
+    /// Returns up to `limit` tracks similar to `seed`, taken from its YouTube mix.
+    ///
+    /// Seeds from other sources are first matched to a YouTube video by artist
+    /// and title. Both extractions go through [`run_extraction`](Self::run_extraction);
+    /// an empty list means no recommendations were found.
+    pub async fn similar_tracks(&self, guild_id: GuildId, seed: &TrackSource, limit: usize) -> Result<Vec<TrackSource>> {
+        let client = YtDlpOptimizedClient::with_retry_policy(RetryPolicy::from_config(&self.settings()))
+            .with_proxy(self.player.ytdlp_proxy(guild_id));
+
+        let seed_url = seed.url();
+        let seed_url = if YtDlpOptimizedClient::is_youtube_url(&seed_url) {
+            seed_url.to_string()
+        } else {
+            let query = match seed.artist() {
+                Some(artist) => format!("{} {}", artist, seed.title()),
+                None => seed.title().to_string(),
+            };
+            match self.run_extraction(guild_id, client.search(&query, 1)).await?.into_iter().next() {
+                Some(track) => track.url().to_string(),
+                None => return Ok(Vec::new()),
+            }
+        };
+
+        self.run_extraction(guild_id, client.get_related(&seed_url, limit)).await
+    }
+
+    /// Re-reads and validates the configuration, swapping it in if valid.
+    ///
+    /// Used by the SIGHUP handler and the `/config-reload` command. Returns
+    /// the list of changed fields (sensitive values are never included).
+    pub fn reload_config(&self) -> Result<Vec<String>> {
+        let changes = config::reload(&self.live_config)?;
+        let settings = self.live_config.load();
+        self.player.set_loudness_normalization(settings.loudness_normalization);
+        self.player.set_ytdlp_proxy(settings.ytdlp_proxy.clone());
+        ytdlp_optimized::set_cookies_file(settings.ytdlp_cookies_file.clone());
+        if changes.iter().any(|change| change.starts_with("ytdlp_cookies_from_browser")) {
+            ytdlp_optimized::set_cookies_from_browser(settings.ytdlp_cookies_from_browser.clone());
+        }
+        Ok(changes)
+    }
+
+    /// Switches yt-dlp to the cookies file at `path` until the next reload or restart.
+    ///
+    /// The caller validates that the file exists; the change is reflected in
+    /// [`settings`](Self::settings).
+    pub fn set_cookies_file(&self, path: PathBuf) {
+        let mut settings = ReloadableConfig::clone(&self.live_config.load());
+        settings.ytdlp_cookies_file = Some(path.clone());
+        self.live_config.store(Arc::new(settings));
+        ytdlp_optimized::set_cookies_file(Some(path));
+    }
+
+    /// Appends a command to the guild's audit log.
+    ///
+    /// Failures are only logged: auditing must never break a command.
+    pub async fn log_audit_event(&self, guild_id: GuildId, entry: AuditEntry) {
+        if let Err(e) = self.audit.log_audit_event(guild_id.get(), &entry).await {
+            warn!("Error escribiendo log de auditoría en guild {}: {:?}", guild_id, e);
+        }
+    }
+
+    /// Returns the most recent audit entries of a guild, newest first.
+    pub async fn recent_audit_entries(&self, guild_id: GuildId, limit: usize) -> Result<Vec<AuditEntry>> {
+        self.audit.read_recent(guild_id.get(), limit).await
+    }
+
+    /// Checks every dependency (yt-dlp, ffmpeg, Discord gateway, storage) and
+    /// aggregates them into a [`HealthReport`].
+    ///
+    /// Shared by the `/health` command and the HTTP endpoint enabled with `HEALTH_PORT`.
+    pub async fn health_report(&self) -> HealthReport {
+        let data_dir = self.config.data_dir.clone();
+        let (yt_dlp, ffmpeg, storage) = tokio::join!(
+            health::check_yt_dlp(),
+            health::check_ffmpeg(),
+            health::check_storage(&data_dir)
+        );
+
+        let components = ComponentHealth {
+            yt_dlp,
+            ffmpeg,
+            discord: ComponentStatus::from_ok(self.discord_connected.load(Ordering::Relaxed)),
+            storage,
+            lavalink: ComponentStatus::Missing,
+        };
+        set_audio_backend_available(&self.audio_backend_available, components.audio_backend_ok());
+        let guild_count = self.discord_cache.get().map_or(0, |cache| cache.guild_count());
+
+        HealthReport::new(components, self.started_at.elapsed(), guild_count)
+    }
+
+    /// Returns `false` while yt-dlp or ffmpeg are missing, so track requests
+    /// are refused up front instead of failing with cryptic errors.
+    pub fn audio_backend_available(&self) -> bool {
+        self.audio_backend_available.load(Ordering::Relaxed)
+    }
+
+    /// Suscribe una tarea a la señal de apagado.
+    pub fn subscribe_shutdown(&self) -> broadcast::Receiver<()> {
+        self.shutdown_tx.subscribe()
+    }
+
+    /// Guarda la cola de una guild en el almacenamiento de colas.
+    ///
+    /// Retorna `Ok(false)` si la cola estaba vacía (se borra la que hubiera
+    /// guardada, para no restaurarla luego) o si la guild pertenece al shard
+    /// de otro proceso.
+    async fn save_guild_state(&self, guild_id: GuildId) -> Result<bool> {
+        if !self.owns_guild(guild_id) {
+            return Ok(false);
+        }
+
+        let info = self.player.get_queue_info(guild_id).await?;
+        if info.current.is_none() && info.items.is_empty() {
+            self.queue_store.delete_queue(guild_id.get()).await?;
+            return Ok(false);
+        }
+
+        let resume_at = self.player.get_playback_position(guild_id).await;
+
+        let voice_channel_id = match self.get_voice_handler(guild_id) {
+            Some(handler) => handler.lock().await.current_channel().map(|c| c.0.get()),
+            None => None,
+        };
+
+        let saved = SavedGuildQueue {
+            guild_id: guild_id.get(),
+            voice_channel_id,
+            current: info
+                .current
+                .as_ref()
+                .map(|item| PlaylistTrack::from_track_source(&item.source, item.requested_by.get())),
+            tracks: info
+                .items
+                .iter()
+                .map(|item| PlaylistTrack::from_track_source(&item.source, item.requested_by.get()))
+                .collect(),
+            resume_at,
+            loop_mode: info.loop_mode,
+            shuffle: info.shuffle,
+            saved_at: chrono::Utc::now(),
+        };
+
+        self.queue_store.save_queue(guild_id.get(), &saved).await?;
+        Ok(true)
+    }
+
+    /// Returns whether this process writes the saved queue of `guild_id`.
+    ///
+    /// Each guild is owned by the process running its shard (`SHARD_ID` /
+    /// `SHARD_COUNT`, see [`Config::owns_guild`]).
+    pub fn owns_guild(&self, guild_id: GuildId) -> bool {
+        self.config.owns_guild(guild_id.get())
+    }
+
+    /// Reads the saved queue of a guild from the queue backend.
+    ///
+    /// With `QUEUE_BACKEND=redis` this is the latest snapshot written by the
+    /// process that owns the guild's shard.
+    pub async fn load_guild_state(&self, guild_id: GuildId) -> Result<Option<SavedGuildQueue>> {
+        self.queue_store.load_queue(guild_id.get()).await
+    }
+
+    /// Restaura la cola guardada de una guild al conectarse.
+    ///
+    /// Solo actúa si la cola en memoria está vacía. Si se guardó el canal de voz,
+    /// vuelve a unirse y retoma la canción actual donde se quedó. La cola guardada
+    /// se borra al restaurarla, para no volver a añadirla. Retorna `Ok(false)`
+    /// si no había nada que restaurar.
+    async fn restore_guild_state(&self, ctx: &Context, guild_id: GuildId) -> Result<bool> {
+        let Some(saved) = self.load_guild_state(guild_id).await? else {
+            return Ok(false);
+        };
+        let info = self.player.get_queue_info(guild_id).await?;
+        if info.current.is_some() || !info.items.is_empty() {
+            return Ok(false);
+        }
+
+        let mut restored = 0;
+        {
+            let queue = self.player.get_or_create_queue(guild_id);
+            let mut q = queue.write();
+            for track in saved.current.iter().chain(&saved.tracks) {
+                if q.add_track(track.to_track_source(UserId::new(track.added_by))).is_err() {
+                    break; // Cola llena
+                }
+                restored += 1;
+            }
+        }
+        self.queue_store.delete_queue(guild_id.get()).await?;
+        if restored == 0 {
+            return Ok(false);
+        }
+        self.player.set_loop_mode_specific(guild_id, saved.loop_mode).await?;
+
+        if let Some(channel_id) = saved.voice_channel_id {
+            self.join_voice_channel(ctx, guild_id, ChannelId::new(channel_id)).await?;
+            if let Some(handler) = self.get_voice_handler(guild_id) {
+                self.player.play_next(guild_id, handler).await?;
+                let duration = saved.current.as_ref().and_then(|track| track.duration);
+                if let Some(position) = saved.resume_at.and_then(|saved_at| resume_point(saved_at, duration)) {
+                    if let Err(e) = self.player.seek(guild_id, position).await {
+                        warn!("No se pudo retomar la cola restaurada en guild {}: {:?}", guild_id, e);
+                    }
+                }
+            }
+        }
+
+        // Con el modo aleatorio activo `play_next` no empezaría por la canción guardada
+        if saved.shuffle {
+            self.player.toggle_shuffle(guild_id).await?;
+        }
+
+        info!("♻️ Cola restaurada en guild {} ({} canciones)", guild_id, restored);
+        Ok(true)
+    }
+
+    /// Restaura las colas guardadas de las guilds recibidas en `ready`.
+    async fn restore_guild_queues(&self, ctx: &Context, guilds: &[GuildId]) {
+        let mut restored = 0;
+        for &guild_id in guilds {
+            match self.restore_guild_state(ctx, guild_id).await {
+                Ok(true) => restored += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Error restaurando la cola de guild {}: {:?}", guild_id, e),
+            }
+        }
+        if restored > 0 {
+            info!("♻️ Colas restauradas en {} servidores", restored);
+        }
+    }
+
+    /// Periodically writes the queue of every active guild to the queue backend.
+    ///
+    /// Only needed with a shared backend (Redis): runs until shutdown and only
+    /// writes the guilds of this process's shard. Queues that became empty are
+    /// deleted so other processes never read a stale one.
+    pub async fn run_queue_sync(&self) {
+        let mut shutdown_rx = self.subscribe_shutdown();
+        let mut interval = tokio::time::interval(QUEUE_SYNC_INTERVAL);
+        let mut synced: HashSet<GuildId> = HashSet::new();
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_rx.recv() => break,
+            }
+
+            let mut saved = HashSet::new();
+            for guild_id in self.player.active_guilds() {
+                match self.save_guild_state(guild_id).await {
+                    Ok(true) => {
+                        saved.insert(guild_id);
+                    }
+                    Ok(false) => {}
+                    Err(e) => warn!("Error sincronizando cola de guild {}: {:?}", guild_id, e),
+                }
+            }
+
+            for guild_id in synced.difference(&saved) {
+                if let Err(e) = self.queue_store.delete_queue(guild_id.get()).await {
+                    warn!("Error eliminando cola sincronizada de guild {}: {:?}", guild_id, e);
+                }
+            }
+            synced = saved;
+        }
+    }
+
+    /// Vuelve a aplicar los presets de ecualizador (y la velocidad de `/speed`)
+    /// persistidos en la configuración de cada guild.
+    ///
+    /// Se restaura el preset de todas las guilds con configuración, no solo las
+    /// que tenían una cola guardada.
+    async fn restore_equalizer_presets(&self) {
+        let presets: Vec<_> = {
+            let storage = self.storage.lock().await;
+            storage
+                .list_servers()
+                .into_iter()
+                .map(|guild_id| (guild_id, storage.get_eq_settings(guild_id), storage.get_playback_tempo(guild_id)))
+                .collect()
+        };
+
+        let mut restored = 0;
+        for (guild_id, (name, bands), tempo) in presets {
+            let guild = GuildId::new(guild_id);
+            if tempo != 1.0 {
+                self.player.set_tempo(guild, tempo);
+            }
+            let result = match name.as_deref().and_then(EqualizerPreset::from_name) {
+                None | Some(EqualizerPreset::Flat) => continue,
+                Some(EqualizerPreset::Custom) => match bands {
+                    Some(bands) => self.player.apply_custom_equalizer(guild, bands).await,
+                    None => continue,
+                },
+                Some(preset) => self.player.apply_equalizer_preset(guild, preset).await,
+            };
+
+            match result {
+                Ok(()) => restored += 1,
+                Err(e) => warn!("Error restaurando ecualizador de guild {}: {:?}", guild_id, e),
+            }
+        }
+
+        if restored > 0 {
+            info!("🎛️ Ecualizador restaurado en {} servidores", restored);
+        }
+    }
+
     /// Retrieves the voice handler for a guild.
     ///
     /// Returns the Songbird call handler for the specified guild, which can be used
@@ -328,6 +964,132 @@ impl OpenMusicBot {
     ) -> Option<Arc<tokio::sync::Mutex<songbird::Call>>> {
         self.voice_handlers.get(&guild_id).map(|h| h.clone())
     }
+
+    /// Returns the guild settings used to theme embeds (`/settings color`).
+    pub async fn guild_settings(&self, guild_id: GuildId) -> GuildSettings {
+        self.storage.lock().await.get_guild_settings(guild_id.get())
+    }
+
+    /// Returns the guild's content filter (`/settings block`).
+    pub async fn content_filter(&self, guild_id: GuildId) -> ContentFilter {
+        self.storage.lock().await.get_content_filter(guild_id.get())
+    }
+
+    /// Returns the yt-dlp proxy for a guild: its own override
+    /// (`/settings proxy`) or, if it has none, the global `YTDLP_PROXY`.
+    pub async fn ytdlp_proxy(&self, guild_id: GuildId) -> Option<String> {
+        let guild_proxy = self
+            .storage
+            .lock()
+            .await
+            .get_ytdlp_proxy_override(guild_id.get())
+            .await
+            .unwrap_or_default();
+        guild_proxy.or_else(|| self.settings().ytdlp_proxy.clone())
+    }
+
+    /// Creates a [`SourceManager`] for extractions in a guild, with the
+    /// configured retry policy, the guild's yt-dlp proxy and its default
+    /// search source (`/settings search-source`).
+    pub async fn source_manager(&self, guild_id: GuildId) -> SourceManager {
+        let search_source = self.storage.lock().await.get_default_search_source(guild_id.get());
+        SourceManager::with_proxy(&self.settings(), self.ytdlp_proxy(guild_id).await).with_search_source(search_source)
+    }
+
+    /// Returns the channel where bot messages for a guild should be sent.
+    ///
+    /// While a music session is active (`/session start`) this is the session
+    /// thread; otherwise it is `default`, usually the channel of the command.
+    pub fn get_response_channel(&self, guild_id: GuildId, default: ChannelId) -> ChannelId {
+        self.session_threads.get(&guild_id).map(|t| *t).unwrap_or(default)
+    }
+
+    /// Returns the guild's average ⭐ rating for `track`, if anyone rated it.
+    pub fn average_rating(&self, guild_id: GuildId, track: &TrackSource) -> Option<f32> {
+        self.ratings.get_average_rating(guild_id.get(), &track.url())
+    }
+
+    /// Everything stored about `user_id` in the guild, for `/my-data export`:
+    /// the storage records plus their ratings, votes and play stats.
+    pub async fn export_user_data(&self, guild_id: GuildId, user_id: UserId) -> Result<UserDataExport> {
+        let mut export = self.storage.lock().await.export_user_data(guild_id.get(), user_id.get()).await?;
+        export.ratings = self.ratings.user_ratings(guild_id.get(), user_id.get());
+        export.votes = voting::VotingSystem::voted_by(guild_id, user_id);
+        export.play_stats = self.play_stats.user_plays(guild_id.get(), user_id.get());
+        Ok(export)
+    }
+
+    /// Deletes what [`Self::export_user_data`] returns (`/my-data delete`).
+    ///
+    /// Vote tallies and the guild's play totals are kept: they no longer
+    /// identify the user once their ID is gone.
+    pub async fn delete_user_data(&self, guild_id: GuildId, user_id: UserId) -> Result<DeletionReport> {
+        let mut report = self.storage.lock().await.delete_user_data(guild_id.get(), user_id.get()).await?;
+        report.ratings = self.ratings.remove_user(guild_id.get(), user_id.get()).await?;
+        report.play_stats = usize::from(self.play_stats.remove_user(guild_id.get(), user_id.get()).await?);
+
+        report.votes = voting::VotingSystem::forget_voter(guild_id, user_id);
+        if report.votes > 0 {
+            let votes = voting::VotingSystem::to_persisted(guild_id);
+            self.storage.lock().await.save_guild_votes(&votes).await?;
+        }
+        Ok(report)
+    }
+
+    /// Remembers where the guild's now-playing embed was posted, so the
+    /// rating prompt for the track shows up below it when it finishes.
+    pub fn remember_now_playing_channel(&self, guild_id: GuildId, channel_id: ChannelId) {
+        self.now_playing_channels.insert(guild_id, channel_id);
+    }
+
+    /// Registers `message` as the guild's now-playing message.
+    ///
+    /// With `single_now_playing` enabled (`/settings single-now-playing`), the
+    /// previous now-playing message is deleted, or stripped of its buttons if
+    /// it can't be deleted, so only one live control panel remains.
+    pub async fn track_now_playing_message(&self, ctx: &Context, guild_id: GuildId, message: &Message) {
+        self.remember_now_playing_channel(guild_id, message.channel_id);
+        let Some((channel_id, message_id)) =
+            self.player
+                .replace_now_playing_message(guild_id, message.channel_id, message.id)
+        else {
+            return;
+        };
+        if !self.storage.lock().await.get_single_now_playing(guild_id.get()) {
+            return;
+        }
+
+        if let Err(e) = channel_id.delete_message(&ctx.http, message_id).await {
+            warn!("No se pudo borrar el reproduciendo ahora anterior en guild {}: {:?}", guild_id, e);
+            let _ = channel_id
+                .edit_message(&ctx.http, message_id, EditMessage::new().components(vec![]))
+                .await;
+        }
+    }
+
+    /// Returns the thread of the active music session of a guild, if any.
+    pub fn session_thread(&self, guild_id: GuildId) -> Option<ChannelId> {
+        self.session_threads.get(&guild_id).map(|t| *t)
+    }
+
+    /// Routes the guild's bot messages to `thread_id` from now on.
+    pub fn start_session(&self, guild_id: GuildId, thread_id: ChannelId) {
+        self.session_threads.insert(guild_id, thread_id);
+    }
+
+    /// Ends the guild's music session, returning its thread.
+    pub fn end_session(&self, guild_id: GuildId) -> Option<ChannelId> {
+        self.session_threads.remove(&guild_id).map(|(_, thread_id)| thread_id)
+    }
+
+    /// Ends the session bound to `thread_id` (archived or deleted thread).
+    ///
+    /// Returns `true` if a session was using that thread.
+    pub fn expire_session(&self, guild_id: GuildId, thread_id: ChannelId) -> bool {
+        self.session_threads
+            .remove_if(&guild_id, |_, current| *current == thread_id)
+            .is_some()
+    }
 }
 
 #[async_trait]
@@ -345,28 +1107,130 @@ impl EventHandler for OpenMusicBot {
     /// # Setup Tasks
     ///
     /// 1. Register slash commands (global or per-guild)
-    /// 2. Set bot activity status
-    /// 3. Start background maintenance tasks
-    /// 4. Log connection information
+    /// 2. Start the activity status rotation (current track per guild)
+    /// 3. Load persisted track votes and equalizer presets
+    /// 4. Start background maintenance tasks
+    /// 5. Log connection information
     async fn ready(&self, ctx: Context, ready: Ready) {
         info!("🤖 {} está en línea!", ready.user.name);
         info!("📊 Conectado a {} servidores", ready.guilds.len());
+        self.discord_connected.store(true, Ordering::Relaxed);
+        let _ = self.discord_cache.set(ctx.cache.clone());
+
+        if let Some(shard) = ready.shard {
+            info!("🧩 Shard {}/{}", shard.id.0, shard.total);
+        }
 
         // Registrar comandos
         if let Err(e) = self.register_commands(&ctx).await {
             error!("Error al registrar comandos: {:?}", e);
         }
 
-        // Establecer estado del bot
-        // ctx.set_activity(Some(Activity::playing("/play")));
+        // Tareas en segundo plano y datos persistidos: solo en el primer `ready`.
+        // Serenity lo vuelve a emitir en cada re-identify: cada tarea se lanzaría
+        // otra vez y cada carga pisaría lo que cambió en memoria desde el arranque.
+        let first_ready = !self.background_started.swap(true, Ordering::SeqCst);
+        if first_ready {
+            // Estado del bot: rota entre los temas que suenan en cada guild
+            let player = self.player.clone();
+            let presence_ctx = ctx.clone();
+            let presence_shutdown = self.subscribe_shutdown();
+            tokio::spawn(async move {
+                presence::presence_task(presence_ctx, player, presence_shutdown).await;
+            });
 
-        // Iniciar tareas de mantenimiento
-        let config = self.config.clone();
-        let cache = self.cache.clone();
+            // Apodo por guild que refleja la canción actual (opt-in con /settings bot-nickname)
+            let player = self.player.clone();
+            let storage = self.storage.clone();
+            let nickname_ctx = ctx.clone();
+            let nickname_shutdown = self.subscribe_shutdown();
+            tokio::spawn(async move {
+                nickname::nickname_task(nickname_ctx, player, storage, nickname_shutdown).await;
+            });
 
-        tokio::spawn(async move {
-            maintenance_tasks(config, cache).await;
-        });
+            // Avisos por DM al empezar la canción de cada usuario (opt-in con /notify)
+            let player = self.player.clone();
+            let storage = self.storage.clone();
+            let notify_ctx = ctx.clone();
+            let notify_shutdown = self.subscribe_shutdown();
+            tokio::spawn(async move {
+                notify::notify_task(notify_ctx, player, storage, notify_shutdown).await;
+            });
+
+            // Pedir una valoración ⭐ al terminar cada canción
+            let player = self.player.clone();
+            let channels = self.now_playing_channels.clone();
+            let rating_ctx = ctx.clone();
+            let rating_shutdown = self.subscribe_shutdown();
+            tokio::spawn(async move {
+                rating::rating_prompt_task(rating_ctx, player, channels, rating_shutdown).await;
+            });
+
+            // Iniciar tareas de mantenimiento
+            let config = self.live_config.clone();
+            let cache = self.cache.clone();
+            let audit = self.audit.clone();
+            let audio_backend = self.audio_backend_available.clone();
+            let shutdown_rx = self.subscribe_shutdown();
+
+            tokio::spawn(async move {
+                maintenance_tasks(config, cache, audit, audio_backend, shutdown_rx).await;
+            });
+
+            // Cargar valoraciones persistidas
+            match self.ratings.load().await {
+                Ok(guilds) if guilds > 0 => info!("⭐ Valoraciones cargadas para {} servidores", guilds),
+                Ok(_) => {}
+                Err(e) => warn!("Error cargando valoraciones: {:?}", e),
+            }
+
+            // Cargar estadísticas de reproducción y seguir sumando cada canción que empieza
+            match self.play_stats.load().await {
+                Ok(guilds) if guilds > 0 => info!("📊 Estadísticas cargadas para {} servidores", guilds),
+                Ok(_) => {}
+                Err(e) => warn!("Error cargando estadísticas de reproducción: {:?}", e),
+            }
+            let player = self.player.clone();
+            let play_stats = self.play_stats.clone();
+            let stats_shutdown = self.subscribe_shutdown();
+            tokio::spawn(async move {
+                guild_stats::play_stats_task(player, play_stats, stats_shutdown).await;
+            });
+
+            // Cargar votos persistidos
+            match self.storage.lock().await.load_all_votes().await {
+                Ok(all_votes) => {
+                    let guilds = all_votes.len();
+                    all_votes.into_iter().for_each(voting::VotingSystem::load);
+                    if guilds > 0 {
+                        info!("🗳️ Votos cargados para {} servidores", guilds);
+                    }
+                }
+                Err(e) => warn!("Error cargando votos: {:?}", e),
+            }
+
+            // Restaurar presets de ecualizador persistidos
+            self.restore_equalizer_presets().await;
+
+            // Reenviar inicio/fin/salto de pistas a EVENT_WEBHOOK_URL (si está configurado)
+            let live_config = self.live_config.clone();
+            let player = self.player.clone();
+            let webhook_shutdown = self.subscribe_shutdown();
+            tokio::spawn(async move {
+                crate::webhook::event_webhook_task(live_config, player, webhook_shutdown).await;
+            });
+        }
+
+        // Restaurar las colas guardadas al apagar (solo en el primer `ready`:
+        // Serenity lo vuelve a emitir en cada re-identify)
+        if !self.queues_restored.swap(true, Ordering::SeqCst) {
+            if let Some(bot) = self.this.upgrade() {
+                let guilds: Vec<GuildId> = ready.guilds.iter().map(|guild| guild.id).collect();
+                tokio::spawn(async move {
+                    bot.restore_guild_queues(&ctx, &guilds).await;
+                });
+            }
+        }
     }
 
     /// Handles incoming Discord interactions.
@@ -375,6 +1239,7 @@ impl EventHandler for OpenMusicBot {
     /// - Slash commands (`/play`, `/pause`, etc.)
     /// - Button clicks (play/pause controls, queue navigation)
     /// - Select menu interactions (equalizer presets, etc.)
+    /// - Autocomplete requests (`/play` search suggestions)
     ///
     /// # Arguments
     ///
@@ -390,18 +1255,119 @@ impl EventHandler for OpenMusicBot {
             Interaction::Command(command_interaction) => {
                 if let Err(e) = handlers::handle_command(&ctx, command_interaction, self).await {
                     error!("Error manejando comando: {:?}", e);
+                    record_classified_error(&e);
+                }
+            }
+            Interaction::Autocomplete(autocomplete_interaction) => {
+                if let Err(e) = handlers::handle_autocomplete(&ctx, autocomplete_interaction, self).await {
+                    error!("Error manejando autocompletado: {:?}", e);
+                    record_classified_error(&e);
                 }
             }
             Interaction::Component(component_interaction) => {
                 if let Err(e) = handlers::handle_component(&ctx, component_interaction, self).await
                 {
                     error!("Error manejando componente: {:?}", e);
+                    record_classified_error(&e);
                 }
             }
             _ => {}
         }
     }
 
+    /// Handles text messages for guilds that enabled a command prefix or URL detection.
+    ///
+    /// Text commands (e.g. `!play`) are opt-in per guild via `/prefix` and are
+    /// dispatched to the same logic as their slash equivalents. Guilds that
+    /// enabled `/settings url-detect` also get a ▶️ reaction on messages with a
+    /// playable link. Other messages are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Discord context for API operations
+    /// * `msg` - The received message
+    async fn message(&self, ctx: Context, msg: Message) {
+        if let Err(e) = prefix::handle_message(&ctx, &msg, self).await {
+            error!("Error manejando comando de texto: {:?}", e);
+            record_classified_error(&e);
+        }
+        if let Err(e) = url_detect::handle_message(&ctx, &msg, self).await {
+            error!("Error detectando enlace en mensaje: {:?}", e);
+        }
+    }
+
+    /// Handles reactions added to messages.
+    ///
+    /// A ▶️ reaction on a message the bot marked as containing a playable link
+    /// queues that link for the reacting user.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Discord context for API operations
+    /// * `reaction` - The added reaction
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        if let Err(e) = url_detect::handle_reaction(&ctx, &reaction, self).await {
+            error!("Error manejando reacción: {:?}", e);
+        }
+    }
+
+    /// Expires the music session of a thread that Discord archived.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Discord context for API operations
+    /// * `old` - Previous thread state (if cached)
+    /// * `new` - Updated thread
+    async fn thread_update(&self, _ctx: Context, _old: Option<GuildChannel>, new: GuildChannel) {
+        let archived = new.thread_metadata.is_some_and(|m| m.archived);
+        if archived && self.expire_session(new.guild_id, new.id) {
+            info!("🧵 Sesión de música finalizada: hilo {} archivado (guild {})", new.id, new.guild_id);
+        }
+    }
+
+    /// Expires the music session of a deleted thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Discord context for API operations
+    /// * `thread` - The deleted thread
+    /// * `full_thread_data` - Full thread data, if it was cached
+    async fn thread_delete(&self, _ctx: Context, thread: PartialGuildChannel, _full_thread_data: Option<GuildChannel>) {
+        if self.expire_session(thread.guild_id, thread.id) {
+            info!("🧵 Sesión de música finalizada: hilo {} eliminado (guild {})", thread.id, thread.guild_id);
+        }
+    }
+
+    /// Tracks the gateway connection for the health report.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Discord context for API operations
+    /// * `event` - Previous and new connection stage of the shard
+    async fn shard_stage_update(&self, _ctx: Context, event: ShardStageUpdateEvent) {
+        let connected = event.new == ConnectionStage::Connected;
+        if self.discord_connected.swap(connected, Ordering::Relaxed) != connected {
+            info!("🔌 Shard {}: {:?} → {:?}", event.shard_id, event.old, event.new);
+        }
+    }
+
+    /// Verifies the voice connections after the gateway resumes a dropped session.
+    ///
+    /// A gateway hiccup can leave a zombie `Call` that believes it is still
+    /// connected; those are rejoined and the current track resumes at its last
+    /// sampled position. Disabled with `RECONNECT_ON_RESUME=false`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Discord context for API operations
+    /// * `_event` - Resume payload (carries no data)
+    async fn resume(&self, ctx: Context, _event: ResumedEvent) {
+        info!("🔄 Sesión del gateway reanudada");
+        if self.settings().reconnect_on_resume {
+            self.recover_voice_connections(&ctx).await;
+        }
+    }
+
     /// Handles voice state updates for users and the bot.
     ///
     /// Monitors voice channel changes to implement features like:
@@ -521,6 +1487,7 @@ impl EventHandler for OpenMusicBot {
                                 }
                             }
                             
+                            player_clone.emit(WsEvent::BotLeftVoice { guild_id });
                             info!("👋 Auto-desconectado por inactividad (guild {})", guild_id);
                         }
                     });
@@ -530,6 +1497,17 @@ impl EventHandler for OpenMusicBot {
     }
 }
 
+/// Actualiza el estado del backend de audio, avisando en el log solo al cambiar
+fn set_audio_backend_available(flag: &AtomicBool, available: bool) {
+    if flag.swap(available, Ordering::Relaxed) != available {
+        if available {
+            info!("✅ yt-dlp y ffmpeg disponibles de nuevo, reproducción reactivada");
+        } else {
+            error!("🚨 yt-dlp o ffmpeg no disponibles: se rechazan los pedidos de canciones");
+        }
+    }
+}
+
 /// Runs periodic maintenance tasks in the background.
 ///
 /// Performs housekeeping operations to keep the bot running efficiently:
@@ -542,35 +1520,95 @@ impl EventHandler for OpenMusicBot {
 ///
 /// * `_config` - Bot configuration (currently unused but reserved for future use)
 /// * `cache` - Music cache to clean up
+/// * `audio_backend` - Flag cleared while yt-dlp or ffmpeg are missing
+/// * `shutdown_rx` - Shutdown signal; the loop exits when it fires
 ///
 /// # Schedule
 ///
-/// Runs every hour (3600 seconds) in an infinite loop.
+/// Runs every hour (3600 seconds) until the bot shuts down.
 ///
 /// # Tasks Performed
 ///
 /// 1. **Cache Cleanup**: Removes expired metadata and audio data
 /// 2. **yt-dlp Update**: Updates YouTube extractor for compatibility
-/// 3. **Memory Stats**: Logs memory usage information
+/// 3. **Dependency Check**: Re-verifies yt-dlp/ffmpeg and updates the audio backend flag
+/// 4. **Memory Stats**: Logs memory usage information
 ///
 /// # Error Handling
 ///
 /// Individual task failures are logged as warnings but don't stop the maintenance cycle.
-async fn maintenance_tasks(_config: Arc<Config>, cache: Arc<MusicCache>) {
+async fn maintenance_tasks(
+    config: SharedConfig,
+    cache: Arc<MusicCache>,
+    audit: Arc<AuditLog>,
+    audio_backend: Arc<AtomicBool>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
     let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // Cada hora
+    let mut ytdlp_update_interval = tokio::time::interval(YTDLP_UPDATE_INTERVAL);
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = ytdlp_update_interval.tick() => {
+                update_ytdlp(&config).await;
+                continue;
+            }
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Tareas de mantenimiento detenidas");
+                break;
+            }
+        }
 
         // Limpiar caché viejo
         cache.cleanup_old_entries();
 
-        // Verificar dependencias yt-dlp
-        let source_manager = crate::sources::SourceManager::new();
-        if let Err(e) = source_manager.verify_dependencies().await {
-            warn!("Error verificando dependencias: {:?}", e);
+        // Purgar logs de auditoría fuera del período de retención
+        let retention = Duration::from_secs(u64::from(config.load().audit_log_retention_days) * 24 * 3600);
+        match audit.purge_older_than(retention).await {
+            Ok(0) => {}
+            Ok(removed) => info!("📜 {} logs de auditoría antiguos eliminados", removed),
+            Err(e) => warn!("Error purgando logs de auditoría: {:?}", e),
         }
 
+        // Verificar dependencias yt-dlp (el primer tick es inmediato: comprobación de arranque)
+        let source_manager = crate::sources::SourceManager::new();
+        let available = match source_manager.verify_dependencies().await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Error verificando dependencias: {:?}", e);
+                false
+            }
+        };
+        set_audio_backend_available(&audio_backend, available);
+
         info!("🧹 Tareas de mantenimiento completadas");
     }
 }
+
+/// Actualiza yt-dlp (`yt-dlp -U`) y, si se instaló una versión nueva, avisa
+/// al webhook de alertas
+async fn update_ytdlp(config: &SharedConfig) {
+    let message = match YtDlpUpdater::check_and_update().await {
+        Ok(UpdateResult::UpToDate { version }) => {
+            DEPENDENCY_VERSIONS.record("yt-dlp", &version);
+            return;
+        }
+        Ok(UpdateResult::Updated { previous, current }) => {
+            DEPENDENCY_VERSIONS.record("yt-dlp", &previous);
+            DEPENDENCY_VERSIONS.record("yt-dlp", &current);
+            format!("⬆️ yt-dlp actualizado: {} → {}", previous, current)
+        }
+        Err(e) => {
+            warn!("No se pudo actualizar yt-dlp: {:?}", e);
+            return;
+        }
+    };
+
+    let Some(url) = config.load().alert_webhook_url.clone() else {
+        return;
+    };
+    if let Err(e) = health::send_alert_webhook(&url, &message).await {
+        warn!("Error enviando alerta al webhook: {:?}", e);
+    }
+}
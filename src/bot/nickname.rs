@@ -0,0 +1,122 @@
+use dashmap::DashMap;
+use serenity::{model::id::GuildId, prelude::Context};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::{audio::player::AudioPlayer, storage::JsonStorage};
+
+/// Cada cuánto se revisa si el apodo de alguna guild debe cambiar
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Tiempo mínimo entre dos cambios de apodo en la misma guild.
+///
+/// Discord limita mucho la edición del propio apodo; cambiarlo en cada canción
+/// acabaría en 429, así que se aplica como mucho un cambio por ventana.
+pub const NICKNAME_DEBOUNCE: Duration = Duration::from_secs(10 * 60);
+
+/// Caracteres del título que se insertan en `{track}`
+pub const TRACK_CHARS: usize = 20;
+
+/// Longitud máxima de un apodo en Discord
+pub const MAX_NICKNAME_LEN: usize = 32;
+
+/// Sustituye `{track}` (primeros 20 caracteres del título) y `{guild}` en el
+/// patrón, recortando el resultado al máximo que admite Discord.
+pub fn format_nickname(pattern: &str, track: &str, guild: &str) -> String {
+    let track: String = track.chars().take(TRACK_CHARS).collect();
+    pattern
+        .replace("{track}", track.trim_end())
+        .replace("{guild}", guild)
+        .chars()
+        .take(MAX_NICKNAME_LEN)
+        .collect()
+}
+
+/// Apodos aplicados por guild y cuándo se cambiaron por última vez
+#[derive(Debug, Default)]
+pub struct NicknameState {
+    applied: DashMap<GuildId, Option<String>>,
+    last_update: DashMap<GuildId, Instant>,
+}
+
+impl NicknameState {
+    /// `true` si el apodo deseado difiere del aplicado y ya pasó el debounce.
+    ///
+    /// Las guilds en las que nunca se cambió el apodo no se tocan mientras el
+    /// deseado sea el por defecto (`None`).
+    pub fn should_update(&self, guild_id: GuildId, desired: &Option<String>, now: Instant) -> bool {
+        let applied = self.applied.get(&guild_id).map(|a| a.clone()).unwrap_or(None);
+        if applied == *desired {
+            return false;
+        }
+
+        self.last_update
+            .get(&guild_id)
+            .is_none_or(|last| now.duration_since(*last) >= NICKNAME_DEBOUNCE)
+    }
+
+    /// Registra un cambio de apodo ya aplicado en Discord
+    pub fn record(&self, guild_id: GuildId, nickname: Option<String>, now: Instant) {
+        self.applied.insert(guild_id, nickname);
+        self.last_update.insert(guild_id, now);
+    }
+}
+
+/// Tarea de fondo que refleja la canción actual en el apodo del bot, para las
+/// guilds que configuraron un patrón con `/settings bot-nickname`.
+pub async fn nickname_task(
+    ctx: Context,
+    player: Arc<AudioPlayer>,
+    storage: Arc<Mutex<JsonStorage>>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let state = NicknameState::default();
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Actualización de apodos detenida");
+                break;
+            }
+        }
+
+        let patterns: Vec<_> = {
+            let storage = storage.lock().await;
+            ctx.cache
+                .guilds()
+                .into_iter()
+                .map(|guild_id| (guild_id, storage.get_bot_nickname_pattern(guild_id.get())))
+                .collect()
+        };
+
+        for (guild_id, pattern) in patterns {
+            let desired = match pattern {
+                Some(pattern) if player.is_playing(guild_id).await => {
+                    let guild_name = ctx.cache.guild(guild_id).map(|g| g.name.clone()).unwrap_or_default();
+                    player
+                        .get_current_track(guild_id)
+                        .await
+                        .map(|track| format_nickname(&pattern, &track.title(), &guild_name))
+                }
+                _ => None,
+            };
+
+            let now = Instant::now();
+            if !state.should_update(guild_id, &desired, now) {
+                continue;
+            }
+
+            match guild_id.edit_nickname(&ctx.http, desired.as_deref()).await {
+                Ok(()) => {
+                    debug!("🏷️ Apodo actualizado en guild {}: {:?}", guild_id, desired);
+                    state.record(guild_id, desired, now);
+                }
+                Err(e) => warn!("Error cambiando el apodo en guild {}: {:?}", guild_id, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use serenity::{builder::CreateMessage, model::id::UserId, prelude::Context};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::{
+    audio::player::{AudioPlayer, TrackStarted},
+    storage::JsonStorage,
+    ui::embeds,
+};
+
+/// `true` si el error de Discord indica que el usuario no acepta DMs del bot
+fn is_dm_blocked(error: &serenity::Error) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(serenity::http::HttpError::UnsuccessfulRequest(response))
+            if response.status_code.as_u16() == 403
+    )
+}
+
+/// Tarea de fondo que avisa por DM a quien pidió una canción cuando empieza a
+/// sonar, si lo activó con `/notify on`.
+pub async fn notify_task(
+    ctx: Context,
+    player: Arc<AudioPlayer>,
+    storage: Arc<Mutex<JsonStorage>>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut track_starts = player.subscribe_track_starts();
+
+    loop {
+        let started = tokio::select! {
+            started = track_starts.recv() => started,
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Avisos por DM detenidos");
+                break;
+            }
+        };
+
+        match started {
+            Ok(started) => notify_requester(&ctx, &storage, started).await,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!("Se perdieron {} avisos de inicio de pista", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+async fn notify_requester(ctx: &Context, storage: &Mutex<JsonStorage>, started: TrackStarted) {
+    let user_id = started.source.requested_by();
+    // Las pistas sin solicitante real llevan el ID por defecto
+    if user_id == UserId::default() {
+        return;
+    }
+
+    let enabled = match storage.lock().await.get_user_preferences(user_id.get()).await {
+        Ok(prefs) => prefs.notify_on_play,
+        Err(e) => {
+            warn!("Error leyendo preferencias del usuario {}: {:?}", user_id, e);
+            false
+        }
+    };
+    if !enabled {
+        return;
+    }
+
+    let guild_name = ctx
+        .cache
+        .guild(started.guild_id)
+        .map(|guild| guild.name.clone())
+        .unwrap_or_else(|| "el servidor".to_string());
+    let embed = embeds::create_track_started_dm_embed(&started.source.title(), &guild_name);
+
+    let sent = match user_id.create_dm_channel(&ctx.http).await {
+        Ok(channel) => channel.send_message(&ctx.http, CreateMessage::new().embed(embed)).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    match sent {
+        Ok(()) => debug!("📨 Aviso de reproducción enviado a {}", user_id),
+        Err(e) if is_dm_blocked(&e) => {
+            // DMs cerrados: no se reintenta en cada canción
+            warn!("⚠️ El usuario {} no acepta DMs; se desactiva /notify", user_id);
+            if let Err(e) = storage.lock().await.set_notify_on_play(user_id.get(), false).await {
+                warn!("Error desactivando /notify para {}: {:?}", user_id, e);
+            }
+        }
+        Err(e) => warn!("Error enviando aviso por DM a {}: {:?}", user_id, e),
+    }
+}
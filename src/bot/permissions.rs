@@ -0,0 +1,91 @@
+//! Permisos de los usuarios sobre la música: anti-spam de pedidos y canal de voz.
+//!
+//! El rate limit de comandos evita ráfagas de cualquier comando; el anti-spam
+//! limita cuántas canciones pide cada usuario por minuto, aunque sean todas
+//! distintas. Con `require_same_channel`, además, solo quien escucha en el
+//! canal del bot puede controlar la reproducción.
+
+use dashmap::DashMap;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Ventana deslizante en la que se cuentan los pedidos de un usuario
+pub const SPAM_WINDOW: Duration = Duration::from_secs(60);
+
+/// Tiempo que un usuario queda sin poder pedir canciones tras superar el límite
+pub const SPAM_BLOCK_DURATION: Duration = Duration::from_secs(60);
+
+/// El usuario superó su límite de pedidos por minuto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Demasiadas canciones pedidas, espera {} segundos", .wait.as_secs().max(1))]
+pub struct SpamThrottled {
+    /// Tiempo que falta para poder volver a pedir
+    pub wait: Duration,
+    /// `true` solo en el pedido que activó el bloqueo
+    pub newly_blocked: bool,
+}
+
+#[derive(Debug, Default)]
+struct UserRequests {
+    /// Pedidos dentro de la ventana, del más antiguo al más reciente
+    recent: VecDeque<Instant>,
+    blocked_until: Option<Instant>,
+}
+
+/// Contador de pedidos por usuario y guild (ventana deslizante de un minuto).
+///
+/// Una playlist cuenta como un único pedido, sin importar cuántas canciones traiga.
+#[derive(Debug, Default)]
+pub struct SpamDetector {
+    requests: DashMap<(GuildId, UserId), UserRequests>,
+}
+
+impl SpamDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra un pedido del usuario, o lo rechaza si está bloqueado o si
+    /// supera `max_per_minute` (con `0` no hay límite).
+    pub fn check(&self, guild_id: GuildId, user_id: UserId, max_per_minute: u32) -> Result<(), SpamThrottled> {
+        self.check_at(guild_id, user_id, max_per_minute, Instant::now())
+    }
+
+    /// Como [`SpamDetector::check`], con un instante explícito
+    pub fn check_at(&self, guild_id: GuildId, user_id: UserId, max_per_minute: u32, now: Instant) -> Result<(), SpamThrottled> {
+        if max_per_minute == 0 {
+            return Ok(());
+        }
+
+        let mut user = self.requests.entry((guild_id, user_id)).or_default();
+        if let Some(until) = user.blocked_until {
+            if now < until {
+                return Err(SpamThrottled { wait: until - now, newly_blocked: false });
+            }
+            user.blocked_until = None;
+        }
+
+        while user.recent.front().is_some_and(|at| now.duration_since(*at) >= SPAM_WINDOW) {
+            user.recent.pop_front();
+        }
+
+        if user.recent.len() >= max_per_minute as usize {
+            user.recent.clear();
+            user.blocked_until = Some(now + SPAM_BLOCK_DURATION);
+            return Err(SpamThrottled { wait: SPAM_BLOCK_DURATION, newly_blocked: true });
+        }
+
+        user.recent.push_back(now);
+        Ok(())
+    }
+}
+
+/// `true` si un usuario en `user_channel` puede controlar la reproducción del
+/// bot conectado a `bot_channel`. Sin el bot en un canal no hay nada que proteger.
+pub fn may_control_playback(bot_channel: Option<ChannelId>, user_channel: Option<ChannelId>) -> bool {
+    match bot_channel {
+        Some(bot_channel) => user_channel == Some(bot_channel),
+        None => true,
+    }
+}
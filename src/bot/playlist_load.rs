@@ -0,0 +1,96 @@
+//! Cargas de `/playlist` en curso, cancelables desde los botones del mensaje.
+//!
+//! Cada guild tiene como mucho una carga activa. Las playlists grandes esperan
+//! primero a que quien las pidió pulse `playlist_confirm`; durante la vista
+//! previa o la carga, `playlist_cancel` detiene los encolados pendientes.
+
+use dashmap::DashMap;
+use serenity::model::id::{GuildId, UserId};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Respuesta a la vista previa de una playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistDecision {
+    Confirmed,
+    Cancelled,
+    /// Nadie respondió a tiempo
+    Expired,
+}
+
+/// Carga de playlist de un guild
+#[derive(Debug, Clone)]
+pub struct PlaylistLoad {
+    pub requested_by: UserId,
+    cancel: CancellationToken,
+    confirm: CancellationToken,
+}
+
+impl PlaylistLoad {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Se completa cuando alguien pulsa `playlist_cancel`
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await
+    }
+
+    /// Espera la respuesta a la vista previa, como mucho `timeout`
+    pub async fn wait_for_decision(&self, timeout: Duration) -> PlaylistDecision {
+        tokio::select! {
+            // La cancelación gana si ambos botones llegaron a la vez
+            biased;
+            _ = self.cancel.cancelled() => PlaylistDecision::Cancelled,
+            _ = self.confirm.cancelled() => PlaylistDecision::Confirmed,
+            _ = tokio::time::sleep(timeout) => PlaylistDecision::Expired,
+        }
+    }
+}
+
+/// Registro de las cargas de playlist activas por guild
+#[derive(Debug, Default)]
+pub struct PlaylistLoads {
+    loads: DashMap<GuildId, PlaylistLoad>,
+}
+
+impl PlaylistLoads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra una carga nueva. `None` si el guild ya tiene una en curso.
+    pub fn begin(&self, guild_id: GuildId, requested_by: UserId) -> Option<PlaylistLoad> {
+        match self.loads.entry(guild_id) {
+            dashmap::mapref::entry::Entry::Occupied(_) => None,
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                let load = PlaylistLoad {
+                    requested_by,
+                    cancel: CancellationToken::new(),
+                    confirm: CancellationToken::new(),
+                };
+                Some(entry.insert(load).clone())
+            }
+        }
+    }
+
+    /// Usuario que pidió la carga en curso del guild
+    pub fn requested_by(&self, guild_id: GuildId) -> Option<UserId> {
+        self.loads.get(&guild_id).map(|load| load.requested_by)
+    }
+
+    /// Confirma la vista previa. `false` si no hay carga en curso.
+    pub fn confirm(&self, guild_id: GuildId) -> bool {
+        self.loads.get(&guild_id).map(|load| load.confirm.cancel()).is_some()
+    }
+
+    /// Detiene la carga en curso. `false` si no hay ninguna.
+    pub fn cancel(&self, guild_id: GuildId) -> bool {
+        self.loads.get(&guild_id).map(|load| load.cancel.cancel()).is_some()
+    }
+
+    /// Quita la carga del guild al terminar (completa, cancelada o con error)
+    pub fn finish(&self, guild_id: GuildId) {
+        self.loads.remove(&guild_id);
+    }
+}
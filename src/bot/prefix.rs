@@ -0,0 +1,155 @@
+use anyhow::Result;
+use serenity::{model::channel::Message, prelude::Context};
+use tracing::{info, info_span, warn, Instrument};
+use uuid::Uuid;
+
+use super::{
+    handlers::{self, CommandReply},
+    OpenMusicBot,
+};
+use crate::ui::embeds;
+
+/// Longitud máxima del prefijo configurable
+pub const MAX_PREFIX_LEN: usize = 5;
+
+/// Comando de texto ya parseado: nombre (en minúsculas) y argumentos crudos
+#[derive(Debug, PartialEq)]
+pub struct TextCommand<'a> {
+    pub name: String,
+    pub args: &'a str,
+}
+
+/// Separa prefijo, nombre y argumentos (`!play never gonna` → `play`, `never gonna`)
+pub fn parse<'a>(content: &'a str, prefix: &str) -> Option<TextCommand<'a>> {
+    let rest = content.trim_start().strip_prefix(prefix)?;
+    let mut parts = rest.splitn(2, char::is_whitespace);
+    let name = parts.next().filter(|n| !n.is_empty())?.to_lowercase();
+    let args = parts.next().unwrap_or("").trim();
+
+    Some(TextCommand { name, args })
+}
+
+/// Traduce alias cortos al nombre del comando slash equivalente
+fn canonical_name(name: &str) -> &str {
+    match name {
+        "p" => "play",
+        "s" => "skip",
+        "q" => "queue",
+        "vol" => "volume",
+        "dc" | "disconnect" => "leave",
+        other => other,
+    }
+}
+
+/// Maneja un mensaje con prefijo de texto. Solo actúa si la guild activó un prefijo.
+pub async fn handle_message(ctx: &Context, msg: &Message, bot: &OpenMusicBot) -> Result<()> {
+    if msg.author.bot {
+        return Ok(());
+    }
+
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    let prefix = bot.storage.lock().await.get_command_prefix(guild_id.get());
+    let Some(prefix) = prefix else {
+        return Ok(());
+    };
+
+    let Some(command) = parse(&msg.content, &prefix) else {
+        return Ok(());
+    };
+    let name = canonical_name(&command.name);
+    let args = command.args;
+
+    // Comandos desconocidos se ignoran para no reaccionar a mensajes casuales
+    if !matches!(
+        name,
+        "play" | "pause" | "resume" | "skip" | "stop" | "queue" | "shuffle" | "loop" | "volume" | "leave"
+    ) {
+        return Ok(());
+    }
+
+    // Mismos campos que el span de los comandos slash, para poder filtrar por guild/usuario
+    let span = info_span!(
+        "text_command",
+        request_id = %Uuid::new_v4(),
+        guild_id = guild_id.get(),
+        user_id = msg.author.id.get(),
+        command = name,
+    );
+
+    async move {
+        if handlers::check_rate_limit(guild_id, msg.author.id) {
+            return Ok(());
+        }
+
+        let throttled = match name {
+            "play" if !args.is_empty() && !bot.audio_backend_available() => {
+                Some(embeds::create_audio_backend_unavailable_embed())
+            }
+            "play" if !args.is_empty() => {
+                handlers::check_request_spam(ctx, bot, guild_id, msg.author.id, &msg.author.name, name).await
+            }
+            _ => None,
+        };
+
+        let reply = if !handlers::has_dj_permission(ctx, guild_id, msg.author.id, name, bot).await {
+            CommandReply::notice("🎧 Este comando requiere el rol de DJ")
+        } else if !handlers::has_channel_permission(ctx, guild_id, msg.author.id, name, bot).await {
+            CommandReply::notice(handlers::SAME_CHANNEL_REQUIRED)
+        } else if let Some(embed) = throttled {
+            CommandReply::embed(embed)
+        } else {
+            info!(
+                "📝 Comando de texto {}{} usado por {} en guild {}",
+                prefix, name, msg.author.name, guild_id
+            );
+
+            let result = match name {
+                "play" if args.is_empty() => Ok(CommandReply::notice(format!("❌ Uso: `{}play <búsqueda|url>`", prefix))),
+                "play" => handlers::play_core(ctx, bot, guild_id, msg.author.id, args).await,
+                "pause" => handlers::pause_core(bot, guild_id).await,
+                "resume" => handlers::resume_core(bot, guild_id).await,
+                "skip" => {
+                    let amount = args.parse::<usize>().unwrap_or(1).clamp(1, 100);
+                    handlers::skip_core(bot, guild_id, amount).await
+                }
+                "stop" => handlers::stop_core(ctx, bot, guild_id).await,
+                "queue" => {
+                    let page = args.parse::<usize>().unwrap_or(1).max(1);
+                    handlers::queue_core(bot, guild_id, page).await
+                }
+                "shuffle" => {
+                    let seed = args.parse::<u64>().ok();
+                    handlers::shuffle_core(bot, guild_id, args.eq_ignore_ascii_case("now"), seed).await
+                }
+                "loop" => {
+                    // `!loop track 3` repite la canción actual 3 veces
+                    let mut parts = args.split_whitespace();
+                    let mode = parts.next().unwrap_or("").to_lowercase();
+                    let times = parts.next().and_then(|t| t.parse::<u32>().ok()).map(|t| t.clamp(1, 100));
+                    handlers::loop_core(bot, guild_id, &mode, times).await
+                }
+                "volume" => {
+                    let bypass_ceiling = handlers::bypasses_volume_ceiling(ctx, bot, guild_id, msg.author.id).await;
+                    handlers::volume_core(bot, guild_id, args.parse::<i64>().ok(), bypass_ceiling).await
+                }
+                _ => handlers::leave_core(ctx, bot, guild_id).await,
+            };
+
+            result.unwrap_or_else(|e| {
+                warn!("Error en comando de texto {}: {:?}", name, e);
+                CommandReply::notice(format!("❌ {}", e))
+            })
+        };
+
+        msg.channel_id
+            .send_message(&ctx.http, reply.into_message().reference_message(msg))
+            .await?;
+
+        Ok(())
+    }
+    .instrument(span)
+    .await
+}
@@ -0,0 +1,87 @@
+use serenity::{gateway::ActivityData, model::id::GuildId, prelude::Context};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info};
+
+use crate::audio::player::AudioPlayer;
+
+/// Cada cuánto se rota la presencia entre las guilds que están reproduciendo
+pub const ROTATION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Estado mostrado cuando no suena nada en ninguna guild
+pub const IDLE_STATUS: &str = "/play para poner música";
+
+/// Rotación de la presencia global entre las guilds activas.
+///
+/// Discord solo permite una actividad por bot (compartida por todas las guilds),
+/// así que se va mostrando por turnos el tema de cada guild que esté sonando.
+#[derive(Debug, Default)]
+pub struct PresenceRotation {
+    cursor: usize,
+}
+
+impl PresenceRotation {
+    /// Elige el próximo título a mostrar; `None` si no suena nada en ninguna guild.
+    ///
+    /// Las guilds se ordenan por ID para que el turno sea estable aunque cambie
+    /// el orden en que se reportan.
+    pub fn next(&mut self, playing: &mut [(GuildId, String)]) -> Option<String> {
+        if playing.is_empty() {
+            self.cursor = 0;
+            return None;
+        }
+
+        playing.sort_by_key(|(guild_id, _)| *guild_id);
+        let title = playing[self.cursor % playing.len()].1.clone();
+        self.cursor = (self.cursor + 1) % playing.len();
+        Some(title)
+    }
+}
+
+/// Temas que están sonando ahora mismo en cada guild
+async fn now_playing(player: &AudioPlayer) -> Vec<(GuildId, String)> {
+    let mut playing = Vec::new();
+    for guild_id in player.active_guilds() {
+        if !player.is_playing(guild_id).await {
+            continue;
+        }
+        if let Some(track) = player.get_current_track(guild_id).await {
+            playing.push((guild_id, track.title().to_string()));
+        }
+    }
+    playing
+}
+
+/// Tarea de fondo que actualiza la presencia del bot hasta el apagado
+pub async fn presence_task(ctx: Context, player: Arc<AudioPlayer>, mut shutdown_rx: broadcast::Receiver<()>) {
+    let mut interval = tokio::time::interval(ROTATION_INTERVAL);
+    let mut rotation = PresenceRotation::default();
+    let mut shown: Option<Option<String>> = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Rotación de presencia detenida");
+                break;
+            }
+        }
+
+        let mut playing = now_playing(&player).await;
+        let title = rotation.next(&mut playing);
+
+        // Evitar reenviar la misma actividad al gateway en cada tick
+        if shown.as_ref() == Some(&title) {
+            continue;
+        }
+
+        let activity = match &title {
+            Some(title) => ActivityData::listening(title.as_str()),
+            None => ActivityData::playing(IDLE_STATUS),
+        };
+        debug!("🎧 Presencia actualizada: {:?}", title);
+        ctx.set_activity(Some(activity));
+        shown = Some(title);
+    }
+}
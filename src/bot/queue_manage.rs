@@ -0,0 +1,225 @@
+//! Reordenar la cola con menús (`/queue-manage`): el primer menú elige una de
+//! las primeras canciones pendientes y el segundo la posición a la que moverla.
+//!
+//! La canción elegida se guarda por guild y usuario durante [`SELECTION_TTL`];
+//! pasado ese tiempo hay que volver a elegirla. Se guarda su URL y no solo su
+//! posición, así que si la cola cambia entre los dos menús se mueve la misma
+//! canción (o ninguna, si ya no está).
+
+use dashmap::DashMap;
+use serenity::{
+    all::{CommandInteraction, ComponentInteraction, ComponentInteractionDataKind, Context},
+    builder::{
+        CreateActionRow, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu,
+        CreateSelectMenuKind, CreateSelectMenuOption,
+    },
+    model::id::{GuildId, UserId},
+};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use super::OpenMusicBot;
+use crate::{audio::queue::QueueItem, ui::embeds};
+
+/// Canciones pendientes que se pueden reordenar desde el panel
+pub const MANAGE_LEN: usize = 10;
+/// Tiempo que se recuerda la canción elegida en el primer menú
+pub const SELECTION_TTL: Duration = Duration::from_secs(30);
+/// Prefijo de los menús del panel
+pub const QUEUE_MANAGE_PREFIX: &str = "queue_manage_";
+
+const PICK_ID: &str = "queue_manage_pick";
+const TARGET_ID: &str = "queue_manage_target";
+
+/// Canción elegida por cada usuario en cada guild, pendiente de destino
+static PENDING_MOVES: LazyLock<PendingMoves> = LazyLock::new(PendingMoves::default);
+
+/// Canción elegida en el primer menú
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMove {
+    /// Posición (0-based) que tenía al elegirla
+    pub index: usize,
+    pub url: String,
+}
+
+impl PendingMove {
+    /// Posición actual de la canción en `items`: la misma si no se movió o, si
+    /// no, la primera con su URL. `None` si ya no está en la cola.
+    pub fn locate(&self, items: &[QueueItem]) -> Option<usize> {
+        if items.get(self.index).is_some_and(|item| *item.url == *self.url) {
+            return Some(self.index);
+        }
+        items.iter().position(|item| *item.url == *self.url)
+    }
+}
+
+/// Selecciones del primer menú a la espera del segundo
+#[derive(Default)]
+pub struct PendingMoves {
+    selections: DashMap<(GuildId, UserId), (PendingMove, Instant)>,
+}
+
+impl PendingMoves {
+    /// Recuerda la canción que eligió `user_id` en la guild. De paso olvida
+    /// las selecciones vencidas que nadie usó.
+    pub fn select(&self, guild_id: GuildId, user_id: UserId, selection: PendingMove) {
+        let now = Instant::now();
+        self.sweep_at(now);
+        self.selections.insert((guild_id, user_id), (selection, now));
+    }
+
+    /// Consume la selección de `user_id` en la guild si no venció
+    pub fn take(&self, guild_id: GuildId, user_id: UserId) -> Option<PendingMove> {
+        self.take_at(guild_id, user_id, Instant::now())
+    }
+
+    /// Como [`take`](Self::take), evaluando el vencimiento en `now`
+    pub fn take_at(&self, guild_id: GuildId, user_id: UserId, now: Instant) -> Option<PendingMove> {
+        let (_, (selection, selected_at)) = self.selections.remove(&(guild_id, user_id))?;
+        (now.saturating_duration_since(selected_at) <= SELECTION_TTL).then_some(selection)
+    }
+
+    /// Quita las selecciones vencidas en `now`
+    pub fn sweep_at(&self, now: Instant) {
+        self.selections
+            .retain(|_, (_, selected_at)| now.saturating_duration_since(*selected_at) <= SELECTION_TTL);
+    }
+}
+
+/// Menús del panel para las canciones mostradas
+fn manage_components(items: &[QueueItem]) -> Vec<CreateActionRow> {
+    let tracks = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let label: String = format!("{}. {}", i + 1, item.title).chars().take(100).collect();
+            CreateSelectMenuOption::new(label, i.to_string())
+        })
+        .collect();
+    let positions = (1..=items.len())
+        .map(|position| CreateSelectMenuOption::new(format!("Posición {}", position), (position - 1).to_string()))
+        .collect();
+
+    let pick = CreateSelectMenu::new(PICK_ID, CreateSelectMenuKind::String { options: tracks })
+        .placeholder("Canción a mover...")
+        .min_values(1)
+        .max_values(1);
+    let target = CreateSelectMenu::new(TARGET_ID, CreateSelectMenuKind::String { options: positions })
+        .placeholder("Mover a la posición...")
+        .min_values(1)
+        .max_values(1);
+
+    vec![CreateActionRow::SelectMenu(pick), CreateActionRow::SelectMenu(target)]
+}
+
+/// Primeras [`MANAGE_LEN`] canciones pendientes de la guild
+async fn managed_items(bot: &OpenMusicBot, guild_id: GuildId) -> Vec<QueueItem> {
+    let mut items = bot
+        .player
+        .get_queue_info(guild_id)
+        .await
+        .map(|info| info.items)
+        .unwrap_or_default();
+    items.truncate(MANAGE_LEN);
+    items
+}
+
+/// Muestra el panel de `/queue-manage`
+pub async fn handle_queue_manage_command(
+    ctx: &Context,
+    command: CommandInteraction,
+    bot: &OpenMusicBot,
+) -> anyhow::Result<()> {
+    let guild_id = command
+        .guild_id
+        .ok_or_else(|| anyhow::anyhow!("Comando usado fuera de un servidor"))?;
+
+    let items = managed_items(bot, guild_id).await;
+    let message = if items.len() < 2 {
+        CreateInteractionResponseMessage::new()
+            .content("❌ Hacen falta al menos 2 canciones pendientes para reordenar")
+            .ephemeral(true)
+    } else {
+        CreateInteractionResponseMessage::new()
+            .embed(embeds::create_queue_manage_embed(&items, None))
+            .components(manage_components(&items))
+    };
+
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await?;
+    Ok(())
+}
+
+/// Atiende los dos menús del panel
+pub async fn handle_queue_manage_select(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+) -> anyhow::Result<()> {
+    let user_id = component.user.id;
+    if !super::handlers::has_dj_permission(ctx, guild_id, user_id, "queue-manage", bot).await {
+        return respond_ephemeral(ctx, component, "❌ Necesitas el rol de DJ para reordenar la cola").await;
+    }
+    if !super::handlers::is_in_bot_channel(ctx, bot, guild_id, user_id).await {
+        return respond_ephemeral(ctx, component, super::handlers::SAME_CHANNEL_REQUIRED).await;
+    }
+
+    let selected = match &component.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().and_then(|v| v.parse::<usize>().ok()),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow::anyhow!("Selección inválida"))?;
+
+    let highlighted = if component.data.custom_id == PICK_ID {
+        let Some(item) = managed_items(bot, guild_id).await.into_iter().nth(selected) else {
+            return respond_ephemeral(ctx, component, "❌ Esa canción ya no está en la cola").await;
+        };
+        PENDING_MOVES.select(guild_id, user_id, PendingMove { index: selected, url: item.url.to_string() });
+        selected
+    } else {
+        let Some(selection) = PENDING_MOVES.take(guild_id, user_id) else {
+            return respond_ephemeral(ctx, component, "⏱️ Primero elige la canción a mover (la elección dura 30 segundos)").await;
+        };
+
+        let queue = bot.player.get_or_create_queue(guild_id);
+        let moved = {
+            let mut q = queue.write();
+            match selection.locate(&q.get_info().items) {
+                Some(from) => q.move_track(from, selected).map(|_| from),
+                None => Err(anyhow::anyhow!("la canción ya no está en la cola")),
+            }
+        };
+        let from = match moved {
+            Ok(from) => from,
+            Err(e) => return respond_ephemeral(ctx, component, &format!("❌ No se pudo mover: {}", e)).await,
+        };
+        info!("🛠️ {} movió la canción {} a la posición {} (guild {})", component.user.name, from + 1, selected + 1, guild_id);
+        selected
+    };
+
+    let items = managed_items(bot, guild_id).await;
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(embeds::create_queue_manage_embed(&items, Some(highlighted)))
+                    .components(manage_components(&items)),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn respond_ephemeral(ctx: &Context, component: &ComponentInteraction, content: &str) -> anyhow::Result<()> {
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(content).ephemeral(true)),
+        )
+        .await?;
+    Ok(())
+}
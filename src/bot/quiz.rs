@@ -0,0 +1,442 @@
+//! Quiz musical (`/quiz start`): suenan los primeros segundos de canciones del
+//! historial de la guild y los oyentes adivinan el título desde un modal.
+//!
+//! Cada guild tiene como mucho un quiz activo. Gana el punto de la ronda la
+//! primera respuesta cuyo título se parezca al menos un 80% (distancia de
+//! Levenshtein) al de la canción; tras la última ronda se muestra la tabla.
+
+use dashmap::DashMap;
+use rand::seq::SliceRandom;
+use serenity::{
+    all::{ComponentInteraction, Context},
+    builder::{
+        CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage,
+    },
+    model::id::{ChannelId, GuildId, UserId},
+    utils::CreateQuickModal,
+};
+use songbird::Call;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::OpenMusicBot;
+use crate::{
+    audio::{loudness::ONE_PASS_FILTER, player::AudioPlayer},
+    sources::TrackSource,
+    ui::{
+        buttons::{button_ids, MusicControls},
+        embeds,
+    },
+};
+
+/// Rondas de un quiz (menos si el historial no alcanza)
+pub const QUIZ_ROUNDS: u8 = 10;
+/// Canciones distintas que necesita el historial para empezar un quiz
+pub const MIN_QUIZ_TRACKS: usize = 3;
+/// Fragmento de cada canción que se reproduce
+pub const SNIPPET_LENGTH: Duration = Duration::from_secs(15);
+/// Tiempo para adivinar antes de revelar la canción
+pub const ROUND_TIMEOUT: Duration = Duration::from_secs(30);
+/// Similitud mínima entre la respuesta y el título para sumar el punto
+pub const MATCH_THRESHOLD: f32 = 0.8;
+/// Prefijo de los botones del quiz
+pub const QUIZ_PREFIX: &str = "quiz_";
+
+/// Distancia de Levenshtein entre dos textos, por caracteres
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Similitud entre 0.0 y 1.0 (1.0 = iguales) según la distancia de Levenshtein
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f32 / longest as f32
+}
+
+/// Título en minúsculas, sin lo que va entre paréntesis o corchetes
+/// ("(Official Video)", "[4K]") ni signos de puntuación
+pub fn normalize_title(title: &str) -> String {
+    let mut depth = 0usize;
+    let mut normalized = String::with_capacity(title.len());
+    for c in title.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            c if depth == 0 && c.is_alphanumeric() => normalized.extend(c.to_lowercase()),
+            _ if depth == 0 => normalized.push(' '),
+            _ => {}
+        }
+    }
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `true` si `guess` acierta `title`: vale el título completo o, en los
+/// títulos "Artista - Canción", solo el nombre de la canción
+pub fn is_correct_guess(guess: &str, title: &str) -> bool {
+    let guess = normalize_title(guess);
+    if guess.is_empty() {
+        return false;
+    }
+
+    let song = title.split_once(" - ").map(|(_, song)| song);
+    std::iter::once(title)
+        .chain(song)
+        .map(normalize_title)
+        .filter(|candidate| !candidate.is_empty())
+        .any(|candidate| similarity(&guess, &candidate) >= MATCH_THRESHOLD)
+}
+
+/// Resultado de una respuesta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessOutcome {
+    /// Acertó y se llevó el punto; `score` es su total
+    Correct { score: u32 },
+    Wrong,
+    /// La ronda ya terminó (otro acertó antes) o todavía no empezó
+    RoundClosed,
+    /// No hay quiz activo en el guild
+    NoQuiz,
+}
+
+/// Partida de quiz de un guild
+#[derive(Debug, Clone)]
+pub struct QuizSession {
+    pub guild_id: GuildId,
+    pub started_by: UserId,
+    /// Canción de la ronda en curso
+    pub current_track: Option<TrackSource>,
+    pub scores: HashMap<UserId, u32>,
+    /// Ronda en curso (la primera es la 1)
+    pub round: u8,
+    pub total_rounds: u8,
+    round_winner: Option<UserId>,
+    round_done: CancellationToken,
+    stop: CancellationToken,
+}
+
+impl QuizSession {
+    pub fn new(guild_id: GuildId, started_by: UserId, total_rounds: u8) -> Self {
+        Self {
+            guild_id,
+            started_by,
+            current_track: None,
+            scores: HashMap::new(),
+            round: 0,
+            total_rounds,
+            round_winner: None,
+            round_done: CancellationToken::new(),
+            stop: CancellationToken::new(),
+        }
+    }
+
+    /// Empieza la ronda siguiente con `track`. Retorna su número.
+    pub fn start_round(&mut self, track: TrackSource) -> u8 {
+        self.round += 1;
+        self.current_track = Some(track);
+        self.round_winner = None;
+        self.round_done = CancellationToken::new();
+        debug!("🎧 Ronda {}/{} del quiz en guild {}", self.round, self.total_rounds, self.guild_id);
+        self.round
+    }
+
+    /// Registra la respuesta de `user_id` a la ronda en curso
+    pub fn submit_guess(&mut self, user_id: UserId, guess: &str) -> GuessOutcome {
+        let Some(track) = &self.current_track else {
+            return GuessOutcome::RoundClosed;
+        };
+        if self.round_winner.is_some() || self.round_done.is_cancelled() {
+            return GuessOutcome::RoundClosed;
+        }
+        if !is_correct_guess(guess, &track.title()) {
+            return GuessOutcome::Wrong;
+        }
+
+        let score = self.scores.entry(user_id).or_default();
+        *score += 1;
+        self.round_winner = Some(user_id);
+        self.round_done.cancel();
+        GuessOutcome::Correct { score: *score }
+    }
+
+    /// Quien acertó la ronda en curso, si alguien lo hizo
+    pub fn round_winner(&self) -> Option<UserId> {
+        self.round_winner
+    }
+
+    /// Termina la ronda en curso sin ganador
+    pub fn skip_round(&self) {
+        self.round_done.cancel();
+    }
+
+    /// Termina el quiz tras la ronda en curso
+    pub fn end(&self) {
+        self.stop.cancel();
+        self.round_done.cancel();
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.stop.is_cancelled()
+    }
+
+    /// Puntajes de mayor a menor (a igual puntaje, por ID de usuario)
+    pub fn leaderboard(&self) -> Vec<(UserId, u32)> {
+        let mut ranked: Vec<(UserId, u32)> = self.scores.iter().map(|(user, score)| (*user, *score)).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+    }
+}
+
+/// Registro de los quizzes activos por guild
+#[derive(Debug, Default)]
+pub struct QuizSessions {
+    sessions: DashMap<GuildId, QuizSession>,
+}
+
+impl QuizSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra un quiz nuevo. `false` si el guild ya tiene uno en curso.
+    pub fn begin(&self, guild_id: GuildId, started_by: UserId, total_rounds: u8) -> bool {
+        match self.sessions.entry(guild_id) {
+            dashmap::mapref::entry::Entry::Occupied(_) => false,
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(QuizSession::new(guild_id, started_by, total_rounds));
+                true
+            }
+        }
+    }
+
+    pub fn is_active(&self, guild_id: GuildId) -> bool {
+        self.sessions.contains_key(&guild_id)
+    }
+
+    /// Usuario que empezó el quiz del guild
+    pub fn started_by(&self, guild_id: GuildId) -> Option<UserId> {
+        self.sessions.get(&guild_id).map(|session| session.started_by)
+    }
+
+    /// Empieza la ronda siguiente. Retorna su número y la señal de fin de
+    /// ronda, o `None` si el quiz ya no existe o se pidió terminarlo.
+    pub fn start_round(&self, guild_id: GuildId, track: TrackSource) -> Option<(u8, CancellationToken)> {
+        let mut session = self.sessions.get_mut(&guild_id)?;
+        if session.is_ended() {
+            return None;
+        }
+        let round = session.start_round(track);
+        Some((round, session.round_done.clone()))
+    }
+
+    pub fn submit_guess(&self, guild_id: GuildId, user_id: UserId, guess: &str) -> GuessOutcome {
+        match self.sessions.get_mut(&guild_id) {
+            Some(mut session) => session.submit_guess(user_id, guess),
+            None => GuessOutcome::NoQuiz,
+        }
+    }
+
+    pub fn round_winner(&self, guild_id: GuildId) -> Option<UserId> {
+        self.sessions.get(&guild_id).and_then(|session| session.round_winner())
+    }
+
+    /// Salta la ronda en curso. `false` si no hay quiz.
+    pub fn skip_round(&self, guild_id: GuildId) -> bool {
+        self.sessions.get(&guild_id).map(|session| session.skip_round()).is_some()
+    }
+
+    /// Pide terminar el quiz. `false` si no hay quiz.
+    pub fn end(&self, guild_id: GuildId) -> bool {
+        self.sessions.get(&guild_id).map(|session| session.end()).is_some()
+    }
+
+    pub fn is_ended(&self, guild_id: GuildId) -> bool {
+        self.sessions.get(&guild_id).is_none_or(|session| session.is_ended())
+    }
+
+    /// Quita el quiz del guild al terminar, devolviendo la partida
+    pub fn finish(&self, guild_id: GuildId) -> Option<QuizSession> {
+        self.sessions.remove(&guild_id).map(|(_, session)| session)
+    }
+}
+
+/// Elige al azar las canciones de las rondas entre las del historial
+pub fn pick_quiz_tracks(mut history: Vec<TrackSource>) -> Vec<TrackSource> {
+    history.shuffle(&mut rand::thread_rng());
+    history.truncate(usize::from(QUIZ_ROUNDS));
+    history
+}
+
+/// Partida en curso: reproduce los fragmentos y publica cada ronda
+pub struct QuizRunner {
+    pub ctx: Context,
+    pub sessions: Arc<QuizSessions>,
+    pub player: Arc<AudioPlayer>,
+    pub handler: Arc<Mutex<Call>>,
+    pub channel_id: ChannelId,
+    pub tracks: Vec<TrackSource>,
+    /// Límite de `/quiz start duration_minutes`: no empiezan rondas después
+    pub deadline: Option<Instant>,
+}
+
+impl QuizRunner {
+    pub async fn run(self, guild_id: GuildId, mut shutdown_rx: broadcast::Receiver<()>) {
+        let total_rounds = self.tracks.len() as u8;
+        let mut rounds_played = 0;
+
+        for track in &self.tracks {
+            if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                info!("⏰ Quiz de guild {} terminado por tiempo", guild_id);
+                break;
+            }
+            if self.sessions.is_ended(guild_id) {
+                break;
+            }
+
+            let proxy = self.player.ytdlp_proxy(guild_id);
+            let input = match track.get_snippet_input(ONE_PASS_FILTER, SNIPPET_LENGTH, proxy.as_deref()).await {
+                Ok(input) => input,
+                Err(e) => {
+                    warn!("Error preparando fragmento de {} para el quiz: {:?}", track.title(), e);
+                    continue;
+                }
+            };
+            let Some((round, round_done)) = self.sessions.start_round(guild_id, track.clone()) else {
+                break;
+            };
+            rounds_played = round;
+
+            let handle = self.handler.lock().await.play_input(input);
+            let volume = self.player.get_volume(guild_id).await.unwrap_or(1.0);
+            let _ = handle.set_volume(volume);
+
+            let message = CreateMessage::new()
+                .embed(embeds::create_quiz_round_embed(round, total_rounds, SNIPPET_LENGTH, ROUND_TIMEOUT))
+                .components(MusicControls::create_quiz_controls());
+            let mut round_message = match self.channel_id.send_message(&self.ctx.http, message).await {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    warn!("Error publicando ronda del quiz en guild {}: {:?}", guild_id, e);
+                    None
+                }
+            };
+
+            let shutdown = tokio::select! {
+                _ = round_done.cancelled() => false,
+                _ = tokio::time::sleep(ROUND_TIMEOUT) => false,
+                _ = shutdown_rx.recv() => true,
+            };
+            let _ = handle.stop();
+
+            if let Some(message) = round_message.as_mut() {
+                let _ = message.edit(&self.ctx.http, EditMessage::new().components(vec![])).await;
+            }
+            let reveal = embeds::create_quiz_reveal_embed(track, self.sessions.round_winner(guild_id));
+            let _ = self.channel_id.send_message(&self.ctx.http, CreateMessage::new().embed(reveal)).await;
+
+            if shutdown {
+                info!("🛑 Quiz de guild {} detenido por apagado", guild_id);
+                break;
+            }
+        }
+
+        let ranked = self.sessions.finish(guild_id).map(|session| session.leaderboard()).unwrap_or_default();
+        let embed = embeds::create_quiz_leaderboard_embed(&ranked, rounds_played);
+        if let Err(e) = self.channel_id.send_message(&self.ctx.http, CreateMessage::new().embed(embed)).await {
+            warn!("Error publicando resultados del quiz en guild {}: {:?}", guild_id, e);
+        }
+        info!("🏁 Quiz de guild {} terminado tras {} rondas", guild_id, rounds_played);
+    }
+}
+
+/// Atiende los botones de una ronda: Adivinar abre un modal con el título,
+/// Saltar y Terminar quedan para quien empezó el quiz o un DJ
+pub async fn handle_quiz_button(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+) -> anyhow::Result<()> {
+    let user_id = component.user.id;
+
+    if component.data.custom_id == button_ids::QUIZ_GUESS {
+        if !bot.quizzes.is_active(guild_id) {
+            return respond_ephemeral(ctx, component, "❌ No hay ningún quiz en curso").await;
+        }
+
+        let modal = CreateQuickModal::new("🎤 ¿Qué canción es?")
+            .timeout(ROUND_TIMEOUT)
+            .short_field("Título de la canción");
+        let Some(response) = component.quick_modal(ctx, modal).await? else {
+            return Ok(());
+        };
+        let guess = response.inputs.first().map(String::as_str).unwrap_or_default();
+
+        let message = match bot.quizzes.submit_guess(guild_id, user_id, guess) {
+            GuessOutcome::Correct { score } => {
+                info!("🎤 {} acertó una ronda del quiz en guild {}", component.user.name, guild_id);
+                format!("✅ ¡Correcto! Llevas **{}** {}", score, if score == 1 { "punto" } else { "puntos" })
+            }
+            GuessOutcome::Wrong => "❌ No es esa, ¡sigue intentando!".to_string(),
+            GuessOutcome::RoundClosed => "⏱️ Esta ronda ya terminó".to_string(),
+            GuessOutcome::NoQuiz => "❌ No hay ningún quiz en curso".to_string(),
+        };
+        response
+            .interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(message).ephemeral(true)),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let Some(started_by) = bot.quizzes.started_by(guild_id) else {
+        return respond_ephemeral(ctx, component, "❌ No hay ningún quiz en curso").await;
+    };
+    if user_id != started_by && !super::handlers::is_dj(ctx, guild_id, user_id, bot).await {
+        return respond_ephemeral(ctx, component, "🎧 Solo quien empezó el quiz o un DJ puede hacer esto").await;
+    }
+
+    let message = match component.data.custom_id.as_str() {
+        button_ids::QUIZ_SKIP => {
+            bot.quizzes.skip_round(guild_id);
+            "⏭️ Ronda saltada"
+        }
+        button_ids::QUIZ_END => {
+            bot.quizzes.end(guild_id);
+            "⏹️ El quiz termina con esta ronda"
+        }
+        _ => "❌ Acción no reconocida",
+    };
+    respond_ephemeral(ctx, component, message).await
+}
+
+async fn respond_ephemeral(ctx: &Context, component: &ComponentInteraction, content: &str) -> anyhow::Result<()> {
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(content).ephemeral(true)),
+        )
+        .await?;
+    Ok(())
+}
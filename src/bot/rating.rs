@@ -0,0 +1,133 @@
+use dashmap::DashMap;
+use serenity::{
+    all::{ButtonStyle, ComponentInteraction, Context},
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+        CreateMessage,
+    },
+    model::id::{ChannelId, GuildId},
+};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use super::OpenMusicBot;
+use crate::{
+    audio::player::AudioPlayer,
+    storage::ratings::{MAX_STARS, MIN_STARS},
+    ui::embeds::{self, colors},
+    ws::WsEvent,
+};
+
+/// Prefijo de los botones de valoración (`rating_1` … `rating_5`)
+pub const RATING_PREFIX: &str = "rating_";
+
+/// Mensaje que pide valorar una canción recién terminada. La URL y el título
+/// viajan en el embed, así los botones no necesitan guardar estado.
+pub fn rating_prompt(title: &str, url: &str) -> CreateMessage {
+    let embed = CreateEmbed::default()
+        .title(title.chars().take(256).collect::<String>())
+        .url(url)
+        .description("¿Qué tal estuvo? Califícala:")
+        .color(colors::MUSIC_PURPLE);
+    let buttons = (MIN_STARS..=MAX_STARS)
+        .map(|stars| {
+            CreateButton::new(format!("{}{}", RATING_PREFIX, stars))
+                .label(format!("{} ⭐", stars))
+                .style(ButtonStyle::Secondary)
+        })
+        .collect();
+
+    CreateMessage::new()
+        .embed(embed)
+        .components(vec![CreateActionRow::Buttons(buttons)])
+}
+
+/// Tarea de fondo que, al terminar una canción, pide valorarla en el canal
+/// donde se mostró el "reproduciendo ahora" de la guild.
+pub async fn rating_prompt_task(
+    ctx: Context,
+    player: Arc<AudioPlayer>,
+    channels: Arc<DashMap<GuildId, ChannelId>>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut events = player.subscribe_events();
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Valoraciones de canciones detenidas");
+                break;
+            }
+        };
+
+        match event {
+            Ok(WsEvent::TrackEnded { guild_id, title, url, .. }) => {
+                let Some(channel_id) = channels.get(&guild_id).map(|channel| *channel) else {
+                    continue;
+                };
+                if let Err(e) = channel_id.send_message(&ctx.http, rating_prompt(&title, &url)).await {
+                    warn!("Error pidiendo valoración en guild {}: {:?}", guild_id, e);
+                }
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!("Se perdieron {} eventos del reproductor", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Registra la valoración de un botón ⭐ del mensaje de [`rating_prompt`]
+pub async fn handle_rating_button(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bot: &OpenMusicBot,
+    guild_id: GuildId,
+) -> anyhow::Result<()> {
+    let stars = component
+        .data
+        .custom_id
+        .strip_prefix(RATING_PREFIX)
+        .and_then(|stars| stars.parse::<u8>().ok());
+    let track = component
+        .message
+        .embeds
+        .first()
+        .and_then(|embed| Some((embed.url.clone()?, embed.title.clone()?)));
+
+    let message = match (stars, track) {
+        (Some(stars), Some((url, title))) => {
+            match bot
+                .ratings
+                .add_rating(guild_id.get(), &url, &title, component.user.id.get(), stars)
+                .await
+            {
+                Ok(average) => {
+                    debug!("⭐ {} valoró {} con {} estrellas", component.user.id, url, stars);
+                    format!(
+                        "⭐ Valoraste **{}** con {} estrellas\nPromedio del servidor: {}",
+                        title,
+                        stars,
+                        embeds::rating_stars(average)
+                    )
+                }
+                Err(e) => {
+                    warn!("Error guardando valoración: {:?}", e);
+                    "❌ No se pudo guardar tu valoración".to_string()
+                }
+            }
+        }
+        _ => "❌ Valoración no reconocida".to_string(),
+    };
+
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(message).ephemeral(true)),
+        )
+        .await?;
+    Ok(())
+}
@@ -13,18 +13,22 @@ use std::sync::LazyLock;
 use crate::{
     ui::embeds::{colors, create_success_embed, create_error_embed},
     sources::TrackSource,
-    bot::OpenMusicBot,
+    bot::{command_options::CommandOptions, OpenMusicBot},
+    config::MAX_SEARCH_RESULT_COUNT,
 };
 use std::time::Duration;
 use tracing::info;
 
+/// Opciones que Discord admite en un menú de selección
+const SELECT_MENU_MAX_OPTIONS: usize = 25;
+
+/// Caracteres de "título - artista" por línea del embed: con 10 resultados el
+/// campo sigue por debajo de los 1024 caracteres que admite Discord
+const RESULT_LINE_MAX_CHARS: usize = 80;
+
 // Almacén global para sesiones de búsqueda
 pub static SEARCH_SESSIONS: LazyLock<DashMap<String, Vec<TrackSource>>> = LazyLock::new(DashMap::new);
 
-use crate::{
-    sources::{SourceManager},
-};
-
 /// Estructura para manejar resultados de búsqueda
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -35,23 +39,27 @@ pub struct SearchSession {
     pub guild_id: GuildId,
 }
 
+/// Cantidad de resultados a mostrar: la opción `count` si se indicó, si no
+/// `default` (`SEARCH_RESULT_COUNT`), siempre entre 1 y [`MAX_SEARCH_RESULT_COUNT`]
+/// y sin superar las opciones de un menú de selección
+pub fn search_result_limit(requested: Option<i64>, default: usize) -> usize {
+    let count = requested.map_or(default, |count| count.clamp(1, MAX_SEARCH_RESULT_COUNT as i64) as usize);
+    count.clamp(1, MAX_SEARCH_RESULT_COUNT.min(SELECT_MENU_MAX_OPTIONS))
+}
+
 /// Maneja el comando de búsqueda con selección múltiple
 pub async fn handle_search_command(
     ctx: &Context,
     command: CommandInteraction,
-    _bot: &OpenMusicBot,
+    bot: &OpenMusicBot,
 ) -> Result<()> {
-    let _guild_id = command
+    let guild_id = command
         .guild_id
         .ok_or_else(|| anyhow::anyhow!("Comando usado fuera de un servidor"))?;
 
-    let query = command
-        .data
-        .options
-        .iter()
-        .find(|opt| opt.name == "query")
-        .and_then(|opt| opt.value.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Query no proporcionado"))?;
+    let options = CommandOptions::from_command(&command);
+    let query = options.require_str("query")?;
+    let limit = search_result_limit(options.get_i64("count"), bot.settings().search_result_count);
 
     // Defer la respuesta
     command
@@ -61,11 +69,11 @@ pub async fn handle_search_command(
         )
         .await?;
 
-    info!("🔍 Búsqueda iniciada por {}: {}", command.user.name, query);
+    info!("🔍 Búsqueda iniciada por {} ({} resultados): {}", command.user.name, limit, query);
 
     // Usar el sistema optimizado
-    let source_manager = SourceManager::new();
-    let search_results = match source_manager.search_all(query, 5).await {
+    let source_manager = bot.source_manager(guild_id).await;
+    let mut search_results = match bot.run_extraction(guild_id, source_manager.search_all(query, limit)).await {
         Ok(results) if !results.is_empty() => {
             // Extraer tracks del primer resultado
             let tracks = results[0].tracks.clone();
@@ -78,10 +86,29 @@ pub async fn handle_search_command(
         }
         Err(e) => {
             info!("❌ Búsqueda falló: {}", e);
+            let embed = if let Some(restriction) = e.downcast_ref::<crate::sources::RestrictedVideoError>() {
+                Some(crate::ui::embeds::create_restricted_video_embed(
+                    *restriction,
+                    crate::sources::YtDlpOptimizedClient::find_cookies_path().is_some(),
+                ))
+            } else {
+                e.downcast_ref::<crate::sources::PermanentExtractionError>()
+                    .map(|permanent| create_error_embed("Error en búsqueda", &permanent.to_string()))
+            };
+            if let Some(embed) = embed {
+                use serenity::builder::EditInteractionResponse;
+                command
+                    .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+                    .await?;
+                return Ok(());
+            }
             return Err(anyhow::anyhow!("Error en búsqueda: {}", e));
         }
     };
 
+    // Ocultar los resultados que el filtro de contenido de la guild bloquea
+    bot.content_filter(guild_id).await.retain_allowed(&mut search_results);
+
     if search_results.is_empty() {
         use serenity::builder::EditInteractionResponse;
         command
@@ -97,7 +124,7 @@ pub async fn handle_search_command(
     // Los resultados ya son TrackSource, solo necesitamos limitarlos y configurar el usuario
     let track_results: Vec<TrackSource> = search_results
         .into_iter()
-        .take(5) // Limitar a 5 resultados para el menú
+        .take(limit)
         .map(|mut track| {
             // Configurar el usuario que solicitó la búsqueda
             track = track.with_requested_by(command.user.id);
@@ -262,13 +289,12 @@ fn create_search_results_embed(query: &str, results: &[TrackSource]) -> CreateEm
             String::new()
         };
 
-        field_value.push_str(&format!(
-            "**{}**. {}{} `[{}]`\n",
-            i + 1,
-            track.title(),
-            artist_str,
-            duration_str
-        ));
+        let mut name = format!("{}{}", track.title(), artist_str);
+        if name.chars().count() > RESULT_LINE_MAX_CHARS {
+            name = format!("{}…", name.chars().take(RESULT_LINE_MAX_CHARS - 1).collect::<String>());
+        }
+
+        field_value.push_str(&format!("**{}**. {} `[{}]`\n", i + 1, name, duration_str));
     }
 
     embed = embed.field("Canciones Encontradas", field_value, false);
@@ -317,7 +343,7 @@ fn create_track_selection_menu(tracks: &[TrackSource]) -> CreateSelectMenu {
         options.push(
             CreateSelectMenuOption::new(truncated_label, format!("track_{}", i))
                 .description(format!("YouTube • {}", 
-                    if let Some(artist) = track.artist() { artist } else { "Desconocido".to_string() }
+                    if let Some(artist) = track.artist() { artist.to_string() } else { "Desconocido".to_string() }
                 ))
         );
     }
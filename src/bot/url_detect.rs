@@ -0,0 +1,176 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use serenity::{
+    builder::CreateMessage,
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        id::{GuildId, MessageId},
+    },
+    prelude::Context,
+};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::{
+    handlers::{self, CommandReply},
+    OpenMusicBot,
+};
+use crate::ui::embeds;
+
+/// Emoji con el que el bot marca los mensajes que contienen un enlace reproducible
+pub const PLAY_REACTION: &str = "▶️";
+
+/// Tiempo durante el que un mensaje marcado sigue aceptando la reacción
+pub const DETECTION_TTL: Duration = Duration::from_secs(120);
+
+/// Ventana en la que no se vuelve a reaccionar al mismo enlace en una guild
+pub const DEBOUNCE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Enlaces detectados en mensajes, pendientes de que alguien pulse ▶️
+#[derive(Debug, Default)]
+pub struct DetectedUrls {
+    by_message: DashMap<MessageId, (String, Instant)>,
+    recent: DashMap<(GuildId, String), Instant>,
+}
+
+impl DetectedUrls {
+    /// Registra el enlace de un mensaje. Devuelve `false` si el mismo enlace ya
+    /// se detectó en la guild hace menos de [`DEBOUNCE_WINDOW`].
+    pub fn register(&self, guild_id: GuildId, message_id: MessageId, url: &str, now: Instant) -> bool {
+        self.purge(now);
+
+        let key = (guild_id, url.to_string());
+        if let Some(last) = self.recent.get(&key) {
+            if now.duration_since(*last) < DEBOUNCE_WINDOW {
+                return false;
+            }
+        }
+
+        self.recent.insert(key, now);
+        self.by_message.insert(message_id, (url.to_string(), now));
+        true
+    }
+
+    /// Retira el enlace de un mensaje si sigue vigente (se encola una sola vez)
+    pub fn take(&self, message_id: MessageId, now: Instant) -> Option<String> {
+        let (_, (url, detected_at)) = self.by_message.remove(&message_id)?;
+        (now.duration_since(detected_at) < DETECTION_TTL).then_some(url)
+    }
+
+    /// Devuelve un enlace retirado con [`take`](Self::take) si no se pudo encolar
+    pub fn restore(&self, message_id: MessageId, url: String, now: Instant) {
+        self.by_message.insert(message_id, (url, now));
+    }
+
+    /// Descarta las entradas caducadas
+    fn purge(&self, now: Instant) {
+        self.by_message
+            .retain(|_, (_, detected_at)| now.duration_since(*detected_at) < DETECTION_TTL);
+        self.recent
+            .retain(|_, last| now.duration_since(*last) < DEBOUNCE_WINDOW);
+    }
+}
+
+static DETECTED_URLS: LazyLock<DetectedUrls> = LazyLock::new(DetectedUrls::default);
+
+/// Reacciona con ▶️ a los mensajes con un enlace de YouTube o Tidal, si la
+/// guild activó la detección con `/settings url-detect`.
+pub async fn handle_message(ctx: &Context, msg: &Message, bot: &OpenMusicBot) -> Result<()> {
+    if msg.author.bot || !bot.settings().auto_detect_urls {
+        return Ok(());
+    }
+
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+
+    let (enabled, prefix) = {
+        let storage = bot.storage.lock().await;
+        (storage.get_url_detect(guild_id.get()), storage.get_command_prefix(guild_id.get()))
+    };
+    if !enabled {
+        return Ok(());
+    }
+
+    // Los comandos de texto (`!play <url>`) ya encolan el enlace
+    if prefix.is_some_and(|prefix| msg.content.trim_start().starts_with(prefix.as_str())) {
+        return Ok(());
+    }
+
+    let Some(url) = handlers::find_playable_url(&msg.content) else {
+        return Ok(());
+    };
+
+    if !DETECTED_URLS.register(guild_id, msg.id, &url, Instant::now()) {
+        debug!("🔗 Enlace repetido ignorado en guild {}: {}", guild_id, url);
+        return Ok(());
+    }
+
+    msg.react(&ctx.http, ReactionType::Unicode(PLAY_REACTION.to_string()))
+        .await?;
+    debug!("🔗 Enlace detectado en guild {}: {}", guild_id, url);
+    Ok(())
+}
+
+/// Encola el enlace detectado cuando un usuario pulsa ▶️ en el mensaje
+pub async fn handle_reaction(ctx: &Context, reaction: &Reaction, bot: &OpenMusicBot) -> Result<()> {
+    let (Some(guild_id), Some(user_id)) = (reaction.guild_id, reaction.user_id) else {
+        return Ok(());
+    };
+
+    if user_id == ctx.cache.current_user().id || !reaction.emoji.unicode_eq(PLAY_REACTION) {
+        return Ok(());
+    }
+
+    let Some(url) = DETECTED_URLS.take(reaction.message_id, Instant::now()) else {
+        return Ok(());
+    };
+
+    if handlers::check_rate_limit(guild_id, user_id) {
+        DETECTED_URLS.restore(reaction.message_id, url, Instant::now());
+        return Ok(());
+    }
+
+    let username = reaction.user(&ctx.http).await.map(|user| user.name).unwrap_or_else(|_| user_id.to_string());
+    let rejection = if bot.audio_backend_available() {
+        handlers::check_request_spam(ctx, bot, guild_id, user_id, &username, "play").await
+    } else {
+        Some(embeds::create_audio_backend_unavailable_embed())
+    };
+    if let Some(embed) = rejection {
+        DETECTED_URLS.restore(reaction.message_id, url, Instant::now());
+        let message = CreateMessage::new()
+            .content(format!("<@{}>", user_id))
+            .embed(embed)
+            .reference_message((reaction.channel_id, reaction.message_id));
+        reaction.channel_id.send_message(&ctx.http, message).await?;
+        return Ok(());
+    }
+
+    info!("🔗 Encolando enlace detectado para {} en guild {}: {}", user_id, guild_id, url);
+
+    let reply = match handlers::play_core(ctx, bot, guild_id, user_id, &url).await {
+        Ok(reply @ CommandReply::Notice(_)) => {
+            // No se encoló (p. ej. el usuario no está en un canal de voz)
+            DETECTED_URLS.restore(reaction.message_id, url, Instant::now());
+            reply
+        }
+        Ok(reply) => reply,
+        Err(e) => {
+            warn!("Error encolando enlace detectado: {:?}", e);
+            // Otro usuario (p. ej. uno que sí esté en voz) puede volver a intentarlo
+            DETECTED_URLS.restore(reaction.message_id, url, Instant::now());
+            CommandReply::notice(format!("❌ <@{}> {}", user_id, e))
+        }
+    };
+
+    reaction
+        .channel_id
+        .send_message(
+            &ctx.http,
+            reply.into_message().reference_message((reaction.channel_id, reaction.message_id)),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use dashmap::DashMap;
+use serenity::model::id::{GuildId, UserId};
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+use tracing::debug;
+
+use crate::storage::{GuildVotes, TrackVotes, UserVote};
+
+/// URL → (upvotes, downvotes)
+type TrackTallies = HashMap<String, (i32, i32)>;
+
+/// URL → usuarios que ya votaron
+type TrackVoters = HashMap<String, HashSet<UserId>>;
+
+/// Votos de una guild y quién votó cada track.
+///
+/// Van juntos en una sola entrada para que votar, cargar y guardar tomen un
+/// único lock del mapa.
+#[derive(Default)]
+struct GuildTally {
+    votes: TrackTallies,
+    voters: TrackVoters,
+}
+
+/// Votos por guild
+static VOTES: LazyLock<DashMap<GuildId, GuildTally>> = LazyLock::new(DashMap::new);
+
+/// Títulos conocidos por URL (para mostrar en el leaderboard)
+static TITLES: LazyLock<DashMap<String, String>> = LazyLock::new(DashMap::new);
+
+/// Resultado de un voto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoteOutcome {
+    /// Voto registrado; contiene el nuevo (upvotes, downvotes)
+    Recorded(i32, i32),
+    /// El usuario ya había votado este track
+    AlreadyVoted,
+}
+
+/// Sistema de votación 👍/👎 por track y por guild
+pub struct VotingSystem;
+
+impl VotingSystem {
+    /// Registra el voto de un usuario. Cada usuario vota una sola vez por track.
+    pub fn vote(guild_id: GuildId, url: &str, user_id: UserId, is_upvote: bool) -> VoteOutcome {
+        let mut guild = VOTES.entry(guild_id).or_default();
+        if !guild.voters.entry(url.to_string()).or_default().insert(user_id) {
+            return VoteOutcome::AlreadyVoted;
+        }
+
+        let tally = guild.votes.entry(url.to_string()).or_insert((0, 0));
+        if is_upvote {
+            tally.0 += 1;
+        } else {
+            tally.1 += 1;
+        }
+
+        debug!(
+            "🗳️ Voto {} de {} para {} en guild {}",
+            if is_upvote { "👍" } else { "👎" },
+            user_id,
+            url,
+            guild_id
+        );
+
+        VoteOutcome::Recorded(tally.0, tally.1)
+    }
+
+    /// Recuerda el título de un track para mostrarlo en el leaderboard
+    pub fn remember_title(url: &str, title: &str) {
+        TITLES.insert(url.to_string(), title.to_string());
+    }
+
+    /// (upvotes, downvotes) de un track en la guild
+    pub fn votes(guild_id: GuildId, url: &str) -> (i32, i32) {
+        VOTES
+            .get(&guild_id)
+            .and_then(|guild| guild.votes.get(url).copied())
+            .unwrap_or((0, 0))
+    }
+
+    /// Tracks que votó un usuario en la guild
+    pub fn voted_by(guild_id: GuildId, user_id: UserId) -> Vec<UserVote> {
+        let Some(guild) = VOTES.get(&guild_id) else {
+            return Vec::new();
+        };
+        guild
+            .voters
+            .iter()
+            .filter(|(_, voters)| voters.contains(&user_id))
+            .map(|(url, _)| UserVote {
+                url: url.clone(),
+                title: TITLES.get(url).map(|t| t.clone()).unwrap_or_else(|| url.clone()),
+            })
+            .collect()
+    }
+
+    /// Quita al usuario de los votantes de la guild y retorna de cuántos tracks.
+    ///
+    /// Los totales no cambian: no se guarda el sentido de cada voto, y sin el
+    /// usuario ya no lo identifican.
+    pub fn forget_voter(guild_id: GuildId, user_id: UserId) -> usize {
+        let Some(mut guild) = VOTES.get_mut(&guild_id) else {
+            return 0;
+        };
+        guild.voters.values_mut().map(|voters| voters.remove(&user_id)).filter(|removed| *removed).count()
+    }
+
+    /// Carga votos persistidos (al iniciar el bot)
+    pub fn load(data: GuildVotes) {
+        let guild_id = GuildId::new(data.guild_id);
+        let mut guild = VOTES.entry(guild_id).or_default();
+
+        for (url, track) in data.tracks {
+            guild.votes.insert(url.clone(), (track.upvotes, track.downvotes));
+            guild.voters.insert(
+                url.clone(),
+                track.voters.into_iter().map(UserId::new).collect(),
+            );
+            TITLES.insert(url, track.title);
+        }
+    }
+
+    /// Convierte los votos en memoria de una guild al formato persistido
+    pub fn to_persisted(guild_id: GuildId) -> GuildVotes {
+        let mut tracks = HashMap::new();
+
+        if let Some(guild) = VOTES.get(&guild_id) {
+            for (url, (upvotes, downvotes)) in guild.votes.iter() {
+                let track_voters = guild
+                    .voters
+                    .get(url)
+                    .map(|set| set.iter().map(|u| u.get()).collect())
+                    .unwrap_or_default();
+
+                tracks.insert(
+                    url.clone(),
+                    TrackVotes {
+                        title: TITLES
+                            .get(url)
+                            .map(|t| t.clone())
+                            .unwrap_or_else(|| url.clone()),
+                        upvotes: *upvotes,
+                        downvotes: *downvotes,
+                        voters: track_voters,
+                    },
+                );
+            }
+        }
+
+        GuildVotes {
+            guild_id: guild_id.get(),
+            tracks,
+        }
+    }
+
+    /// Ordena los tracks por puntuación (`upvotes - downvotes`) y devuelve los primeros `limit`
+    pub fn rank(votes: &GuildVotes, limit: usize) -> Vec<(&String, &TrackVotes)> {
+        let mut ranked: Vec<_> = votes.tracks.iter().collect();
+        ranked.sort_by(|(_, a), (_, b)| {
+            b.score()
+                .cmp(&a.score())
+                .then_with(|| b.upvotes.cmp(&a.upvotes))
+        });
+        ranked.truncate(limit);
+        ranked
+    }
+}
@@ -1,31 +1,35 @@
 use dashmap::DashMap;
 use std::{
     hash::Hash,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tracing::debug;
 
 /// Cache entry con TTL simplificado
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 #[allow(dead_code)]
 struct CacheEntry<V> {
     value: V,
     created_at: u64,
     ttl: Option<Duration>,
+    /// Último uso (inserción o lectura), según el reloj lógico del cache
+    last_used: AtomicU64,
 }
 
 impl<V> CacheEntry<V> {
-    #[allow(dead_code)]
-    fn new(value: V, ttl: Option<Duration>) -> Self {
+    fn new(value: V, ttl: Option<Duration>, now: u64) -> Self {
         Self {
             value,
             created_at: current_timestamp(),
             ttl,
+            last_used: AtomicU64::new(now),
         }
     }
 
-    #[allow(dead_code)]
     fn is_expired(&self) -> bool {
         if let Some(ttl) = self.ttl {
             let now = current_timestamp();
@@ -34,12 +38,33 @@ impl<V> CacheEntry<V> {
             false
         }
     }
+
+    /// Tiempo de vida restante (`None` si la entrada no expira)
+    fn remaining_ttl(&self) -> Option<Duration> {
+        let expires_at = self.created_at + self.ttl?.as_secs();
+        Some(Duration::from_secs(expires_at.saturating_sub(current_timestamp())))
+    }
+}
+
+/// Contadores de aciertos/fallos compartidos entre clones del cache
+#[derive(Debug, Default)]
+struct CacheCounters {
+    /// Reloj lógico para ordenar los usos: los segundos de `created_at` no
+    /// distinguen accesos dentro del mismo segundo
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expired_removals: AtomicU64,
 }
 
 /// Cache LRU simplificado para el proyecto
 #[derive(Debug)]
 pub struct LRUCache<K: Clone + Eq + Hash, V> {
     data: Arc<DashMap<K, CacheEntry<V>>>,
+    counters: Arc<CacheCounters>,
+    /// Entradas máximas; al llenarse se descarta la usada hace más tiempo
+    capacity: usize,
 }
 
 impl<K, V> LRUCache<K, V>
@@ -47,41 +72,95 @@ where
     K: Clone + Eq + Hash + Send + Sync + 'static,
     V: Clone + Send + Sync + 'static,
 {
-    pub fn new(_capacity: usize) -> Self {
+    pub fn new(capacity: usize) -> Self {
         Self {
             data: Arc::new(DashMap::new()),
+            counters: Arc::new(CacheCounters::default()),
+            capacity: capacity.max(1),
         }
     }
 
-    #[allow(dead_code)]
     pub fn insert_with_ttl(&self, key: K, value: V, ttl: Option<Duration>) -> Option<V> {
-        let entry = CacheEntry::new(value, ttl);
+        if !self.data.contains_key(&key) && self.data.len() >= self.capacity {
+            self.evict_least_recently_used();
+        }
+        let entry = CacheEntry::new(value, ttl, self.tick());
         self.data.insert(key, entry).map(|old| old.value)
     }
 
-    #[allow(dead_code)]
+    /// Siguiente instante del reloj lógico
+    fn tick(&self) -> u64 {
+        self.counters.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Descarta la entrada leída o insertada hace más tiempo para dejar sitio
+    fn evict_least_recently_used(&self) {
+        let least_recent = self
+            .data
+            .iter()
+            .min_by_key(|entry| entry.value().last_used.load(Ordering::Relaxed))
+            .map(|entry| entry.key().clone());
+        if let Some(key) = least_recent {
+            if self.data.remove(&key).is_some() {
+                self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
     pub fn get(&self, key: &K) -> Option<V> {
         if let Some(entry) = self.data.get(key) {
             if entry.is_expired() {
                 drop(entry);
                 self.data.remove(key);
+                self.counters.expired_removals.fetch_add(1, Ordering::Relaxed);
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
                 None
             } else {
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                entry.last_used.store(self.tick(), Ordering::Relaxed);
                 Some(entry.value.clone())
             }
         } else {
+            self.counters.misses.fetch_add(1, Ordering::Relaxed);
             None
         }
     }
 
-    #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.data.len()
     }
 
     #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     pub fn capacity(&self) -> usize {
-        1000 // Capacidad fija simplificada
+        self.capacity
+    }
+
+    /// Fracción de lecturas que encontraron la entrada (0.0 sin lecturas)
+    pub fn hit_ratio(&self) -> f64 {
+        self.metrics().hit_rate()
+    }
+
+    /// Tiempo que le queda a la entrada antes de expirar; `None` si no existe,
+    /// ya expiró o no tiene TTL. No cuenta como acierto ni fallo.
+    #[allow(dead_code)]
+    pub fn remaining_ttl(&self, key: &K) -> Option<Duration> {
+        let entry = self.data.get(key)?;
+        if entry.is_expired() {
+            return None;
+        }
+        entry.remaining_ttl()
+    }
+
+    /// Vacía el cache por completo y retorna el número de entradas eliminadas
+    pub fn clear(&self) -> usize {
+        let removed = self.data.len();
+        self.data.clear();
+        debug!("Cache vaciado: {} entradas eliminadas", removed);
+        removed
     }
 
     /// Limpia entradas expiradas y retorna el número de elementos removidos
@@ -106,6 +185,9 @@ where
         }
 
         if removed > 0 {
+            self.counters
+                .expired_removals
+                .fetch_add(removed as u64, Ordering::Relaxed);
             debug!("Limpiadas {} entradas expiradas del cache", removed);
         }
 
@@ -115,10 +197,10 @@ where
     #[allow(dead_code)]
     pub fn metrics(&self) -> CacheMetrics {
         CacheMetrics {
-            hits: 0,
-            misses: 0,
-            evictions: 0,
-            expired_removals: 0,
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            expired_removals: self.counters.expired_removals.load(Ordering::Relaxed),
         }
     }
 }
@@ -131,6 +213,8 @@ where
     fn clone(&self) -> Self {
         Self {
             data: self.data.clone(),
+            counters: self.counters.clone(),
+            capacity: self.capacity,
         }
     }
 }
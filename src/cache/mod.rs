@@ -58,6 +58,7 @@
 //!     thumbnail: Some("https://example.com/thumb.jpg".to_string()),
 //!     url: "https://example.com/song.mp3".to_string(),
 //!     source: "youtube".to_string(),
+//!     authenticated: false,
 //! };
 //!
 //! // Cache the track info
@@ -124,6 +125,10 @@ pub struct CachedTrackInfo {
     pub url: String,
     /// Source service identifier ("youtube", "soundcloud", "direct", etc.)
     pub source: String,
+    /// Whether the metadata could only be extracted with a signed-in session
+    /// (age-restricted or members-only videos); stored under its own key
+    #[serde(default)]
+    pub authenticated: bool,
 }
 
 impl MusicCache {
@@ -0,0 +1,123 @@
+//! Fallos operativos agrupados por categoría para `/metrics errors`.
+//!
+//! Las extracciones, conexiones de voz, reproducciones y llamadas a la API de
+//! Discord que fallan se registran aquí además de en los logs. Registrar no
+//! hace IO ni espera: agrega la marca de tiempo a una ventana acotada.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Ventana de `/metrics errors`
+pub const ERROR_WINDOW: Duration = Duration::from_secs(24 * 3600);
+
+/// Fallos que se recuerdan como mucho; con más, se descartan los más viejos
+const MAX_TRACKED_ERRORS: usize = 10_000;
+
+/// Fallos del bot registrados desde que arrancó
+pub static ERROR_STATS: LazyLock<ErrorStats> = LazyLock::new(ErrorStats::default);
+
+/// Origen de un fallo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// yt-dlp: búsquedas, metadata y playlists (incluye los timeouts)
+    Extraction,
+    /// Conectar, reconectar o desconectar del canal de voz
+    Voice,
+    /// Crear el input de una pista o errores de songbird al sonar
+    Playback,
+    /// Respuestas, mensajes y ediciones rechazados por Discord
+    DiscordApi,
+}
+
+impl ErrorCategory {
+    pub const ALL: [ErrorCategory; 4] = [Self::Extraction, Self::Voice, Self::Playback, Self::DiscordApi];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Extraction => "extraction",
+            Self::Voice => "voice",
+            Self::Playback => "playback",
+            Self::DiscordApi => "discord_api",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Extraction => "Extracción",
+            Self::Voice => "Voz",
+            Self::Playback => "Reproducción",
+            Self::DiscordApi => "API de Discord",
+        }
+    }
+
+    /// Categoría de un error que llegó hasta el manejador de interacciones,
+    /// si se puede saber por su tipo
+    pub fn classify(error: &anyhow::Error) -> Option<Self> {
+        if error.downcast_ref::<serenity::Error>().is_some() {
+            Some(Self::DiscordApi)
+        } else if error.downcast_ref::<songbird::error::JoinError>().is_some() {
+            Some(Self::Voice)
+        } else if error.downcast_ref::<crate::sources::ExtractionTimeoutError>().is_some()
+            || error.downcast_ref::<crate::sources::PermanentExtractionError>().is_some()
+        {
+            Some(Self::Extraction)
+        } else {
+            None
+        }
+    }
+}
+
+/// Fallos recientes con su momento
+#[derive(Default)]
+pub struct ErrorStats {
+    events: Mutex<VecDeque<(Instant, ErrorCategory)>>,
+}
+
+impl ErrorStats {
+    /// Registra un fallo ocurrido ahora
+    pub fn record(&self, category: ErrorCategory) {
+        self.record_at(category, Instant::now());
+    }
+
+    fn record_at(&self, category: ErrorCategory, at: Instant) {
+        let mut events = self.events.lock();
+        while events.front().is_some_and(|(time, _)| at.duration_since(*time) > ERROR_WINDOW)
+            || events.len() >= MAX_TRACKED_ERRORS
+        {
+            events.pop_front();
+        }
+        events.push_back((at, category));
+        debug!("📉 Fallo registrado: {}", category.as_str());
+    }
+
+    /// Fallos por categoría dentro de `window` (todas las categorías, también las sin fallos)
+    pub fn counts_within(&self, window: Duration) -> Vec<(ErrorCategory, u64)> {
+        let now = Instant::now();
+        let events = self.events.lock();
+        ErrorCategory::ALL
+            .iter()
+            .map(|category| {
+                let count = events
+                    .iter()
+                    .filter(|(time, event)| event == category && now.duration_since(*time) <= window)
+                    .count();
+                (*category, count as u64)
+            })
+            .collect()
+    }
+}
+
+/// Registra un fallo en [`ERROR_STATS`]
+pub fn record_error(category: ErrorCategory) {
+    ERROR_STATS.record(category);
+}
+
+/// Registra `error` si [`ErrorCategory::classify`] reconoce su origen
+pub fn record_classified_error(error: &anyhow::Error) {
+    if let Some(category) = ErrorCategory::classify(error) {
+        record_error(category);
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Tiempo máximo para que `yt-dlp --version` / `ffmpeg -version` respondan
+const BINARY_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tamaño máximo de la petición HTTP que se lee (solo interesa la primera línea)
+const MAX_REQUEST_BYTES: usize = 4096;
+
+/// Tiempo máximo para recibir la petición HTTP completa
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tiempo máximo para entregar una alerta al webhook
+const ALERT_WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Versiones de dependencias vistas desde que arrancó el bot
+pub static DEPENDENCY_VERSIONS: LazyLock<DependencyVersions> = LazyLock::new(DependencyVersions::default);
+
+/// Versiones de una dependencia con el momento en que se vieron por primera vez
+pub type VersionHistory = Vec<(DateTime<Utc>, String)>;
+
+/// Historial de versiones por dependencia (yt-dlp), en orden de aparición
+#[derive(Default)]
+pub struct DependencyVersions {
+    versions: Mutex<HashMap<&'static str, VersionHistory>>,
+}
+
+impl DependencyVersions {
+    /// Registra la versión de `name`; solo se agrega si cambió respecto a la última
+    pub fn record(&self, name: &'static str, version: &str) {
+        let mut versions = self.versions.lock();
+        let history = versions.entry(name).or_default();
+        if history.last().is_some_and(|(_, last)| last == version) {
+            return;
+        }
+        debug!("📦 {} versión {}", name, version);
+        history.push((Utc::now(), version.to_string()));
+    }
+
+    /// Versiones de `name`, de la más vieja a la actual
+    pub fn history(&self, name: &str) -> VersionHistory {
+        self.versions.lock().get(name).cloned().unwrap_or_default()
+    }
+}
+
+/// Envía `content` al webhook de alertas (formato de webhook de Discord)
+pub async fn send_alert_webhook(url: &str, content: &str) -> anyhow::Result<()> {
+    reqwest::Client::builder()
+        .timeout(ALERT_WEBHOOK_TIMEOUT)
+        .build()?
+        .post(url)
+        .json(&serde_json::json!({ "content": content }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Estado global del bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthLevel {
+    Healthy,
+    Warning,
+    Critical,
+}
+
+impl HealthLevel {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            HealthLevel::Healthy => "✅",
+            HealthLevel::Warning => "⚠️",
+            HealthLevel::Critical => "🚨",
+        }
+    }
+}
+
+/// Estado de un componente individual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentStatus {
+    Ok,
+    Error,
+    /// Componente opcional no configurado
+    Missing,
+}
+
+impl ComponentStatus {
+    pub fn from_ok(ok: bool) -> Self {
+        if ok {
+            ComponentStatus::Ok
+        } else {
+            ComponentStatus::Error
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        *self == ComponentStatus::Ok
+    }
+}
+
+/// Estado de cada dependencia del bot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub yt_dlp: ComponentStatus,
+    pub ffmpeg: ComponentStatus,
+    pub discord: ComponentStatus,
+    pub storage: ComponentStatus,
+    /// El audio se reproduce con Songbird local, así que Lavalink siempre figura como `missing`
+    pub lavalink: ComponentStatus,
+}
+
+impl ComponentHealth {
+    /// Sin yt-dlp, ffmpeg o gateway de Discord no se puede reproducir nada (crítico);
+    /// un fallo de almacenamiento solo impide persistir datos (aviso).
+    pub fn level(&self) -> HealthLevel {
+        if !self.yt_dlp.is_ok() || !self.ffmpeg.is_ok() || !self.discord.is_ok() {
+            HealthLevel::Critical
+        } else if !self.storage.is_ok() {
+            HealthLevel::Warning
+        } else {
+            HealthLevel::Healthy
+        }
+    }
+
+    /// yt-dlp y ffmpeg responden: se puede abrir audio
+    pub fn audio_backend_ok(&self) -> bool {
+        self.yt_dlp.is_ok() && self.ffmpeg.is_ok()
+    }
+
+    /// Pares `(nombre, estado)` en el orden en que se muestran
+    pub fn entries(&self) -> [(&'static str, ComponentStatus); 5] {
+        [
+            ("yt_dlp", self.yt_dlp),
+            ("ffmpeg", self.ffmpeg),
+            ("discord", self.discord),
+            ("storage", self.storage),
+            ("lavalink", self.lavalink),
+        ]
+    }
+}
+
+/// Respuesta de `GET /health` y de `/health`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthLevel,
+    pub components: ComponentHealth,
+    pub uptime_seconds: u64,
+    pub guild_count: usize,
+}
+
+impl HealthReport {
+    pub fn new(components: ComponentHealth, uptime: Duration, guild_count: usize) -> Self {
+        Self {
+            status: components.level(),
+            components,
+            uptime_seconds: uptime.as_secs(),
+            guild_count,
+        }
+    }
+
+    /// 503 solo cuando el estado es crítico; un aviso sigue respondiendo 200
+    pub fn http_status(&self) -> u16 {
+        match self.status {
+            HealthLevel::Critical => 503,
+            HealthLevel::Healthy | HealthLevel::Warning => 200,
+        }
+    }
+}
+
+/// `true` si el binario existe y termina con éxito (p. ej. `yt-dlp --version`)
+async fn check_binary(program: &str, arg: &str) -> ComponentStatus {
+    let output = tokio::process::Command::new(program)
+        .arg(arg)
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(BINARY_CHECK_TIMEOUT, output).await {
+        Ok(Ok(output)) => ComponentStatus::from_ok(output.status.success()),
+        Ok(Err(e)) => {
+            debug!("No se pudo ejecutar {}: {:?}", program, e);
+            ComponentStatus::Error
+        }
+        Err(_) => {
+            debug!("{} no respondió a tiempo", program);
+            ComponentStatus::Error
+        }
+    }
+}
+
+pub async fn check_yt_dlp() -> ComponentStatus {
+    check_binary("yt-dlp", "--version").await
+}
+
+pub async fn check_ffmpeg() -> ComponentStatus {
+    check_binary("ffmpeg", "-version").await
+}
+
+/// Comprueba que el directorio de datos admite escrituras
+pub async fn check_storage(data_dir: &Path) -> ComponentStatus {
+    let probe = data_dir.join(".health_check");
+    let result = async {
+        tokio::fs::create_dir_all(data_dir).await?;
+        tokio::fs::write(&probe, b"ok").await?;
+        tokio::fs::remove_file(&probe).await
+    }
+    .await;
+
+    if let Err(e) = &result {
+        debug!("Almacenamiento no escribible en {:?}: {:?}", data_dir, e);
+    }
+    ComponentStatus::from_ok(result.is_ok())
+}
+
+/// Sirve `GET /health` en `listener` hasta recibir la señal de apagado.
+/// `report` se ejecuta en cada petición, así que el estado siempre está al día.
+pub async fn serve<F, Fut>(listener: TcpListener, report: F, mut shutdown_rx: broadcast::Receiver<()>)
+where
+    F: Fn() -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = HealthReport> + Send,
+{
+    if let Ok(addr) = listener.local_addr() {
+        info!("🩺 Endpoint de salud escuchando en http://{}/health", addr);
+    }
+
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => {
+                debug!("🩺 Endpoint de salud detenido");
+                break;
+            }
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let report = report.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, report).await {
+                            debug!("Error atendiendo petición de salud: {:?}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Error aceptando conexión de salud: {:?}", e),
+            },
+        }
+    }
+}
+
+async fn handle_connection<F, Fut>(mut stream: TcpStream, report: F) -> std::io::Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = HealthReport>,
+{
+    let mut buf = vec![0u8; MAX_REQUEST_BYTES];
+    let mut len = 0;
+    let read_request = async {
+        while len < buf.len() && !buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            let read = stream.read(&mut buf[len..]).await?;
+            if read == 0 {
+                break;
+            }
+            len += read;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    // Un cliente que no termina de enviar la petición no retiene la conexión:
+    // al vencer el plazo se descarta `stream` y se cierra
+    tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request)
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "la petición no llegó a tiempo"))??;
+
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+
+    let (code, body) = if method == "GET" && path == "/health" {
+        let report = report().await;
+        let body = serde_json::to_string(&report).map_err(std::io::Error::other)?;
+        (report.http_status(), body)
+    } else {
+        (404, r#"{"error":"not found"}"#.to_string())
+    };
+
+    let reason = match code {
+        200 => "OK",
+        503 => "Service Unavailable",
+        _ => "Not Found",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -3,10 +3,16 @@
 //! This module exposes the core functionality for testing.
 
 pub mod audio;
+pub mod audit;
 pub mod bot;
 pub mod cache;
 pub mod config;
+pub mod errors;
+pub mod health;
 pub mod monitoring;
 pub mod sources;
 pub mod storage;
+pub mod telemetry;
 pub mod ui;
+pub mod webhook;
+pub mod ws;
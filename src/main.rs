@@ -2,41 +2,97 @@ use anyhow::Result;
 use serenity::{model::gateway::GatewayIntents, Client};
 use songbird::{SerenityInit, Songbird};
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod audio;
+mod audit;
 mod bot;
 mod cache;
 mod config;
+mod errors;
+mod health;
 mod monitoring;
 mod sources;
 mod storage;
+mod telemetry;
 mod ui;
+mod webhook;
+mod ws;
 
 use crate::bot::OpenMusicBot;
 use crate::cache::MusicCache;
-use crate::config::Config;
+use crate::config::{Config, InvalidConfig, LogFormat, QueueBackend};
 use crate::monitoring::{MonitoringSystem, MonitoringConfig};
-use crate::storage::JsonStorage;
+use crate::storage::{open_queue_store, JsonStorage};
+use crate::telemetry::Telemetry;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Inicializar logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("open_music=debug".parse()?)
-                .add_directive("serenity=info".parse()?)
-                .add_directive("songbird=info".parse()?),
-        )
-        .init();
+    // Inicializar logging (LOG_FORMAT=json para agregadores como Datadog o Elastic).
+    // `.env` se lee ya aquí para que LOG_FORMAT y RUST_LOG también se tomen de él.
+    dotenvy::dotenv().ok();
+    let env_filter = tracing_subscriber::EnvFilter::from_default_env()
+        .add_directive("open_music=debug".parse()?)
+        .add_directive("serenity=info".parse()?)
+        .add_directive("songbird=info".parse()?);
+
+    // Con OTEL_EXPORTER_OTLP_ENDPOINT, los spans también se exportan por OTLP
+    let telemetry = telemetry::endpoint_from_env().map(|endpoint| Telemetry::init(&endpoint));
+    let otel_layer = match &telemetry {
+        Some(Ok(telemetry)) => Some(telemetry.layer()),
+        _ => None,
+    };
+    let subscriber = tracing_subscriber::registry().with(env_filter).with(otel_layer);
+
+    if LogFormat::from_env() == LogFormat::Json {
+        subscriber
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false),
+            )
+            .init();
+    } else {
+        subscriber.with(tracing_subscriber::fmt::layer()).init();
+    }
+    let telemetry = match telemetry {
+        Some(Ok(telemetry)) => {
+            info!("📡 Exportando trazas y métricas por OTLP");
+            Some(telemetry)
+        }
+        Some(Err(e)) => {
+            warn!("⚠️ OpenTelemetry desactivado: {:?}", e);
+            None
+        }
+        None => None,
+    };
 
     info!("🎵 Iniciando Open Music Bot v{}", env!("CARGO_PKG_VERSION"));
 
-    // Cargar configuración
-    let config = Config::load()?;
+    // Cargar y validar configuración
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            if let Some(InvalidConfig(errors)) = e.downcast_ref::<InvalidConfig>() {
+                for error in errors {
+                    error!("❌ Configuración inválida: {}", error);
+                }
+                anyhow::bail!("{} error(es) de configuración, el bot no se iniciará", errors.len());
+            }
+            return Err(e);
+        }
+    };
     info!("⚙️ {}", config.summary());
 
+    // Solo validar la configuración, sin iniciar el bot
+    if std::env::args().any(|arg| arg == "--validate-config") {
+        println!("OK");
+        return Ok(());
+    }
+
     // Manejar health check si es necesario
     if std::env::args().any(|arg| arg == "--health-check") {
         return health_check().await;
@@ -47,6 +103,9 @@ async fn main() -> Result<()> {
         JsonStorage::new(config.data_dir.clone()).await?
     ));
 
+    // Almacenamiento de colas: archivos JSON o Redis compartido entre procesos
+    let queue_store = open_queue_store(&config, storage.clone())?;
+
     // Inicializar caché
     let cache = Arc::new(MusicCache::new(config.cache_size));
 
@@ -59,15 +118,46 @@ async fn main() -> Result<()> {
     let intents = GatewayIntents::GUILDS
         | GatewayIntents::GUILD_VOICE_STATES
         | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::MESSAGE_CONTENT;
 
-    // Crear handler del bot
-    let handler = OpenMusicBot::new(config.clone(), storage, cache, monitoring);
+    // Crear handler del bot (compartido con el apagado graceful)
+    let handler = OpenMusicBot::new(config.clone(), storage, cache, monitoring, queue_store);
+
+    // Con Redis, cada proceso mantiene actualizadas las colas de las guilds de su shard
+    if matches!(config.queue_backend, QueueBackend::Redis(_)) {
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            handler.run_queue_sync().await;
+        });
+    }
+
+    // Endpoint HTTP de salud (GET /health) si HEALTH_PORT está definido
+    if let Some(port) = config.health_port {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+        let handler = handler.clone();
+        let shutdown_rx = handler.subscribe_shutdown();
+        tokio::spawn(async move {
+            let report = move || {
+                let handler = handler.clone();
+                async move { handler.health_report().await }
+            };
+            health::serve(listener, report, shutdown_rx).await;
+        });
+    }
+
+    // Stream de eventos por WebSocket si WS_PORT está definido (la validación exige API_TOKEN)
+    if let (Some(port), Some(api_token)) = (config.ws_port, config.api_token.clone()) {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+        let player = handler.player.clone();
+        let shutdown_rx = handler.subscribe_shutdown();
+        tokio::spawn(ws::serve(listener, api_token, player, shutdown_rx));
+    }
 
     // Construir cliente con Songbird
     let songbird = Songbird::serenity();
     let mut client = Client::builder(&config.discord_token, intents)
-        .event_handler(handler)
+        .event_handler_arc(handler.clone())
         .register_songbird_with(songbird.clone())
         .await?;
 
@@ -75,37 +165,57 @@ async fn main() -> Result<()> {
     // Las conexiones de voz se gestionan vía songbird::get(ctx) en los handlers.
     info!("🎵 Sistema de audio listo (AudioPlayer + Songbird + yt-dlp)");
 
-    // Manejar shutdown graceful
+    // Recargar configuración en caliente con SIGHUP
+    #[cfg(unix)]
+    {
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                Ok(signal) => signal,
+                Err(e) => {
+                    error!("Error al registrar SIGHUP: {:?}", e);
+                    return;
+                }
+            };
+
+            while hangup.recv().await.is_some() {
+                info!("🔄 SIGHUP recibido, recargando configuración...");
+                if let Err(e) = handler.reload_config() {
+                    error!("❌ Configuración inválida, se mantiene la actual: {:?}", e);
+                }
+            }
+        });
+    }
+
+    // Manejar shutdown graceful: guardar colas, salir de voz y cerrar shards
+    let shard_manager = client.shard_manager.clone();
     tokio::spawn(async move {
         tokio::signal::ctrl_c()
             .await
             .expect("Error al registrar Ctrl+C");
         info!("⚠️ Señal de shutdown recibida, cerrando...");
-        std::process::exit(0);
+        handler.shutdown(&songbird).await;
+        shard_manager.shutdown_all().await;
     });
 
     // Iniciar bot
-    info!("🚀 Bot iniciado exitosamente");
-    if let Err(why) = client.start().await {
+    info!("🚀 Bot iniciado exitosamente (shard {}/{})", config.shard_id, config.shard_count);
+    if let Err(why) = client.start_shard(config.shard_id, config.shard_count).await {
         error!("Error al ejecutar cliente: {:?}", why);
     }
 
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
+    }
+
     Ok(())
 }
 
 async fn health_check() -> Result<()> {
     // Verificar dependencias críticas
-    let yt_dlp = async_process::Command::new("yt-dlp")
-        .arg("--version")
-        .output()
-        .await?;
-
-    let ffmpeg = async_process::Command::new("ffmpeg")
-        .arg("-version")
-        .output()
-        .await?;
+    let (yt_dlp, ffmpeg) = tokio::join!(health::check_yt_dlp(), health::check_ffmpeg());
 
-    if yt_dlp.status.success() && ffmpeg.status.success() {
+    if yt_dlp.is_ok() && ffmpeg.is_ok() {
         println!("OK");
         Ok(())
     } else {
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use super::TrackSource;
+
+/// Fuentes que se pueden permitir con `/settings block sources`
+pub const SOURCE_NAMES: &[&str] = &["youtube", "tidal", "direct"];
+
+/// Filtro de contenido para lo que se reproduce, configurable por guild (`/settings block`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentFilter {
+    /// Palabras que no pueden aparecer en el título (sin distinguir mayúsculas)
+    pub blocked_keywords: Vec<String>,
+    /// Canales bloqueados: nombre del uploader o ID/handle presente en la URL
+    pub blocked_channels: Vec<String>,
+    /// Fuentes permitidas (`youtube`, `tidal`, `direct`); vacío = todas
+    pub allowed_sources: Vec<String>,
+}
+
+/// Motivo por el que el filtro de la guild rechazó una canción
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BlockedTrackError {
+    #[error("El título contiene la palabra bloqueada `{0}`")]
+    Keyword(String),
+    #[error("El canal `{0}` está bloqueado en este servidor")]
+    Channel(String),
+    #[error("La fuente `{0}` no está permitida en este servidor")]
+    Source(&'static str),
+}
+
+impl ContentFilter {
+    /// `true` si el filtro no bloquea nada
+    pub fn is_empty(&self) -> bool {
+        self.blocked_keywords.is_empty() && self.blocked_channels.is_empty() && self.allowed_sources.is_empty()
+    }
+
+    /// Comprueba una canción ya resuelta contra el filtro
+    pub fn check(&self, track: &TrackSource) -> Result<(), BlockedTrackError> {
+        let source = track.source_type().as_str();
+        if !self.allowed_sources.is_empty() && !self.allowed_sources.iter().any(|s| s == source) {
+            return Err(BlockedTrackError::Source(source));
+        }
+
+        let title = track.title().to_lowercase();
+        if let Some(keyword) = self
+            .blocked_keywords
+            .iter()
+            .find(|keyword| title.contains(&keyword.to_lowercase()))
+        {
+            return Err(BlockedTrackError::Keyword(keyword.clone()));
+        }
+
+        let artist = track.artist().unwrap_or_default().to_lowercase();
+        let url = track.url();
+        if let Some(channel) = self
+            .blocked_channels
+            .iter()
+            .find(|channel| artist == channel.to_lowercase() || url.contains(channel.as_str()))
+        {
+            return Err(BlockedTrackError::Channel(channel.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Quita de `tracks` las canciones bloqueadas y devuelve cuántas se quitaron
+    pub fn retain_allowed(&self, tracks: &mut Vec<TrackSource>) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let before = tracks.len();
+        tracks.retain(|track| self.check(track).is_ok());
+        before - tracks.len()
+    }
+}
+
+/// Añade `value` a una lista del filtro; `false` si ya estaba (sin distinguir mayúsculas)
+pub fn add_entry(list: &mut Vec<String>, value: &str) -> bool {
+    let value = value.trim();
+    if value.is_empty() || list.iter().any(|entry| entry.eq_ignore_ascii_case(value)) {
+        return false;
+    }
+    list.push(value.to_string());
+    true
+}
+
+/// Quita `value` de una lista del filtro; `false` si no estaba
+pub fn remove_entry(list: &mut Vec<String>, value: &str) -> bool {
+    let before = list.len();
+    list.retain(|entry| !entry.eq_ignore_ascii_case(value.trim()));
+    list.len() != before
+}
+
+/// Parsea `youtube,tidal` (o `all` para permitir todas) a la lista de fuentes permitidas
+pub fn parse_allowed_sources(value: &str) -> Option<Vec<String>> {
+    if value.trim().eq_ignore_ascii_case("all") {
+        return Some(Vec::new());
+    }
+
+    let mut sources: Vec<String> = value
+        .split([',', ' '])
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    sources.sort();
+    sources.dedup();
+
+    let valid = !sources.is_empty() && sources.iter().all(|s| SOURCE_NAMES.contains(&s.as_str()));
+    valid.then_some(sources)
+}
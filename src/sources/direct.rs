@@ -0,0 +1,341 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serenity::model::id::UserId;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::{SourceType, TrackSource};
+use crate::cache::{CachedTrackInfo, MusicCache};
+
+/// Tiempo máximo para leer la metadata de una URL directa con ffprobe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Vigencia de la metadata de una URL directa en la caché
+const DIRECT_METADATA_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Redirecciones que se siguen (validando cada destino) antes de pasar la URL a ffmpeg
+const MAX_REDIRECTS: usize = 5;
+
+/// Tiempo máximo de cada petición al seguir las redirecciones
+const REDIRECT_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Protocolos que ffmpeg/ffprobe pueden abrir al leer una URL directa
+/// (`-protocol_whitelist`): nada de `file`, `concat`, `subfile`, etc.
+pub const FFMPEG_PROTOCOL_WHITELIST: &str = "http,https,tcp,tls";
+
+/// Comprueba que una URL escrita por un usuario se pueda pasar a ffmpeg.
+///
+/// Solo se aceptan `http`/`https` hacia hosts públicos: se rechazan otros
+/// esquemas (`file://`, ...), `localhost`, los nombres de una sola etiqueta
+/// (servicios internos como `redis`) y las IPs privadas, de loopback o
+/// link-local (p. ej. `169.254.169.254`), para no hacer peticiones internas.
+///
+/// No resuelve el host: [`check_stream_url`] y [`resolve_stream_url`] además
+/// comprueban a qué direcciones apunta.
+pub fn validate_stream_url(url: &str) -> Result<url::Url> {
+    let parsed = url::Url::parse(url.trim()).map_err(|_| anyhow::anyhow!("URL inválida"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        anyhow::bail!("Solo se admiten URLs http:// o https://");
+    }
+
+    match parsed.host() {
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") || !domain.contains('.') {
+                anyhow::bail!("La URL apunta a una dirección interna");
+            }
+        }
+        Some(url::Host::Ipv4(ip)) if is_internal_ip(IpAddr::V4(ip)) => {
+            anyhow::bail!("La URL apunta a una dirección interna");
+        }
+        Some(url::Host::Ipv6(ip)) if is_internal_ip(IpAddr::V6(ip)) => {
+            anyhow::bail!("La URL apunta a una dirección interna");
+        }
+        Some(_) => {}
+        None => anyhow::bail!("La URL no tiene host"),
+    }
+    Ok(parsed)
+}
+
+/// Resuelve `host` y falla si alguna de sus direcciones es interna (o si no
+/// resuelve). Devuelve las direcciones comprobadas.
+pub async fn ensure_public_host(host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| anyhow::anyhow!("No se pudo resolver {}", host))?
+        .collect();
+    if addrs.is_empty() {
+        anyhow::bail!("No se pudo resolver {}", host);
+    }
+    if addrs.iter().any(|addr| is_internal_ip(addr.ip())) {
+        anyhow::bail!("La URL apunta a una dirección interna");
+    }
+    Ok(addrs)
+}
+
+/// [`validate_stream_url`] más la resolución del host con [`ensure_public_host`]
+pub async fn check_stream_url(url: &str) -> Result<url::Url> {
+    let parsed = validate_stream_url(url)?;
+    let host = parsed.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+    ensure_public_host(host, parsed.port_or_known_default().unwrap_or(80)).await?;
+    Ok(parsed)
+}
+
+/// URL que se le pasa a ffmpeg/ffprobe: sigue las redirecciones una a una
+/// (como mucho [`MAX_REDIRECTS`]) con [`check_stream_url`] en cada salto, así
+/// una redirección no puede llevar a ffmpeg a una dirección interna.
+///
+/// Si el servidor no responde HTTP válido (p. ej. `ICY 200 OK` de radios
+/// SHOUTcast antiguas) se usa la última URL ya comprobada.
+pub async fn resolve_stream_url(url: &str) -> Result<String> {
+    let mut current = check_stream_url(url).await?;
+    for _ in 0..=MAX_REDIRECTS {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(REDIRECT_CHECK_TIMEOUT)
+            .build()?;
+        let response = match client.get(current.clone()).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                debug!("Sin respuesta HTTP de {} al seguir redirecciones: {}", current, e);
+                return Ok(current.to_string());
+            }
+        };
+        if !response.status().is_redirection() {
+            return Ok(current.to_string());
+        }
+
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| anyhow::anyhow!("Redirección sin destino"))?;
+        let next = current.join(location).map_err(|_| anyhow::anyhow!("Redirección a una URL inválida"))?;
+        current = check_stream_url(next.as_str()).await?;
+    }
+    anyhow::bail!("Demasiadas redirecciones")
+}
+
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_internal_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_internal_ipv4(mapped),
+            None => is_internal_ipv6(ip),
+        },
+    }
+}
+
+fn is_internal_ipv4(ip: Ipv4Addr) -> bool {
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // 100.64.0.0/10 (CGNAT)
+        || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+}
+
+fn is_internal_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        // fc00::/7 (ULA) y fe80::/10 (link-local)
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+}
+
+/// Metadata de un audio directo, manifiesto HLS/DASH o radio por internet (ICY)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    /// `None` en streams en vivo
+    pub duration: Option<Duration>,
+    /// Nombre de la emisora (`icy-name`) en radios por internet
+    pub station: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeOutput {
+    format: FfprobeFormat,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeFormat {
+    duration: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+impl StreamMetadata {
+    /// Parsea la salida de `ffprobe -print_format json -show_format`
+    pub fn from_ffprobe_json(json: &str) -> Result<Self> {
+        let output: FfprobeOutput = serde_json::from_str(json)?;
+        // Las claves de los tags varían de mayúsculas según el contenedor
+        let tags: HashMap<String, String> = output
+            .format
+            .tags
+            .into_iter()
+            .filter(|(_, value)| !value.trim().is_empty())
+            .map(|(key, value)| (key.to_lowercase(), value.trim().to_string()))
+            .collect();
+
+        let duration = output
+            .format
+            .duration
+            .and_then(|d| d.parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .map(Duration::from_secs_f64);
+
+        Ok(Self {
+            title: tags.get("title").or_else(|| tags.get("streamtitle")).cloned(),
+            artist: tags.get("artist").cloned(),
+            duration,
+            station: tags.get("icy-name").cloned(),
+        })
+    }
+
+    /// Convierte la metadata en un track. En radios, la emisora hace de
+    /// artista (o de título si no anuncia la canción que suena).
+    pub fn into_track(self, url: &str, requested_by: UserId) -> TrackSource {
+        // Cabeceras ICY sin duración: radio en vivo
+        if let (Some(station), None) = (self.station.clone(), self.duration) {
+            let title = self.title.unwrap_or_else(|| station.clone());
+            return TrackSource::new(title, url.to_string(), SourceType::DirectUrl, requested_by).with_station(station);
+        }
+
+        let (title, artist) = match (self.title, self.artist, self.station) {
+            (Some(title), Some(artist), _) => (title, Some(artist)),
+            (Some(title), None, station) => (title, station),
+            (None, artist, Some(station)) => (station, artist),
+            (None, artist, None) => (fallback_title(url), artist),
+        };
+
+        let mut track = TrackSource::new(title, url.to_string(), SourceType::DirectUrl, requested_by);
+        if let Some(artist) = artist {
+            track = track.with_artist(artist);
+        }
+        if let Some(duration) = self.duration {
+            track = track.with_duration(duration);
+        }
+        track
+    }
+}
+
+/// Título para un stream sin metadata: el nombre del archivo o, si la URL no
+/// tiene, el host
+pub fn fallback_title(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+
+    let file_name = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .map(|name| urlencoding::decode(name).map(|n| n.into_owned()).unwrap_or_else(|_| name.to_string()));
+
+    file_name
+        .or_else(|| parsed.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Lee la metadata de la URL con ffprobe (sin descargar el audio completo)
+pub async fn probe(url: &str) -> Result<StreamMetadata> {
+    let stream_url = resolve_stream_url(url).await?;
+
+    let mut cmd = tokio::process::Command::new("ffprobe");
+    cmd.args([
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_format",
+        "-icy", "1",
+        "-protocol_whitelist", FFMPEG_PROTOCOL_WHITELIST,
+        "-i", &stream_url,
+    ])
+    .kill_on_drop(true);
+
+    let output = tokio::time::timeout(PROBE_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| anyhow::anyhow!("ffprobe tardó más de {} segundos", PROBE_TIMEOUT.as_secs()))??;
+    if !output.status.success() {
+        anyhow::bail!("ffprobe no pudo leer {}", url);
+    }
+
+    StreamMetadata::from_ffprobe_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Crea el track de una URL directa con su metadata real, usando la caché de
+/// metadata cuando ya se consultó. Si ffprobe falla, el título es el archivo o el host.
+pub async fn resolve_direct_track(url: &str, requested_by: UserId, cache: &MusicCache) -> TrackSource {
+    let key = format!("direct:{}", url);
+    if let Some(cached) = cache.get(&key) {
+        debug!("📦 Metadata de URL directa desde caché: {}", url);
+        let metadata = StreamMetadata {
+            title: Some(cached.title),
+            artist: cached.artist,
+            duration: cached.duration,
+            station: None,
+        };
+        return metadata.into_track(url, requested_by);
+    }
+
+    let metadata = match probe(url).await {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warn!("Sin metadata para {}: {:?}", url, e);
+            StreamMetadata::default()
+        }
+    };
+
+    let track = metadata.into_track(url, requested_by);
+    // La canción que anuncia una radio cambia constantemente: no se cachea
+    if track.is_radio() {
+        return track;
+    }
+    cache.insert_with_ttl(
+        key,
+        CachedTrackInfo {
+            title: track.title().to_string(),
+            artist: track.artist().map(|artist| artist.to_string()),
+            duration: track.duration(),
+            thumbnail: None,
+            url: url.to_string(),
+            source: SourceType::DirectUrl.as_str().to_string(),
+            authenticated: false,
+        },
+        Some(DIRECT_METADATA_TTL),
+    );
+    track
+}
+
+/// Como [`resolve_direct_track`], pero exige un stream en vivo (`/radio`). Los
+/// streams sin cabeceras ICY usan el host como nombre de la emisora.
+pub async fn resolve_radio_track(url: &str, requested_by: UserId, cache: &MusicCache) -> Result<TrackSource> {
+    let track = resolve_direct_track(url, requested_by, cache).await;
+    if track.is_radio() {
+        return Ok(track);
+    }
+    if track.duration().is_some() {
+        anyhow::bail!("La URL no es un stream en vivo");
+    }
+
+    let station = url::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(str::to_string))
+        .unwrap_or_else(|| fallback_title(url));
+    let track = if *track.title() == fallback_title(url) {
+        track.with_title(station.clone())
+    } else {
+        track
+    };
+    Ok(track.with_station(station))
+}
+
+/// Canción que anuncia la radio en este momento (`StreamTitle` de ICY)
+pub async fn current_stream_title(url: &str) -> Option<String> {
+    probe(url).await.ok()?.title
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use serenity::model::id::GuildId;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+/// Extracciones (yt-dlp) simultáneas en todo el bot
+pub const GLOBAL_EXTRACTION_LIMIT: usize = 8;
+
+/// Extracciones simultáneas por guild: una guild con muchas peticiones lentas
+/// no puede ocupar todo el pool global
+pub const PER_GUILD_EXTRACTION_LIMIT: usize = 2;
+
+/// La extracción superó el `extraction_timeout` configurado
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("La extracción tardó más de {} segundos; inténtalo de nuevo", .0.as_secs())]
+pub struct ExtractionTimeoutError(pub Duration);
+
+/// Pool acotado de extracciones con un límite adicional por guild
+#[derive(Debug)]
+pub struct ExtractionPool {
+    global: Arc<Semaphore>,
+    per_guild: DashMap<GuildId, Arc<Semaphore>>,
+    per_guild_limit: usize,
+}
+
+impl Default for ExtractionPool {
+    fn default() -> Self {
+        Self::new(GLOBAL_EXTRACTION_LIMIT, PER_GUILD_EXTRACTION_LIMIT)
+    }
+}
+
+impl ExtractionPool {
+    pub fn new(global_limit: usize, per_guild_limit: usize) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(global_limit.max(1))),
+            per_guild: DashMap::new(),
+            per_guild_limit: per_guild_limit.max(1),
+        }
+    }
+
+    /// Ejecuta `extraction` cuando haya hueco en la guild y en el pool global,
+    /// cortándola con [`ExtractionTimeoutError`] si supera `timeout`.
+    pub async fn run<T>(
+        &self,
+        guild_id: GuildId,
+        timeout: Duration,
+        extraction: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let guild = self
+            .per_guild
+            .entry(guild_id)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.per_guild_limit)))
+            .clone();
+
+        // Primero el cupo de la guild: mientras espera no retiene uno global
+        let _guild_permit = guild.acquire_owned().await?;
+        let _global_permit = self.global.acquire().await?;
+
+        match tokio::time::timeout(timeout, extraction).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("⏱️ Extracción cancelada en guild {} tras {:?}", guild_id, timeout);
+                Err(ExtractionTimeoutError(timeout).into())
+            }
+        }
+    }
+}
@@ -1,13 +1,30 @@
+pub mod content_filter;
+pub mod direct;
+pub mod extraction;
+pub mod ranking;
+pub mod tidal;
 pub mod ytdlp_optimized;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use serenity::model::id::UserId;
+use serde::Deserialize;
 use songbird::input::Input;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::info;
 
-pub use ytdlp_optimized::YtDlpOptimizedClient;
+pub use content_filter::{BlockedTrackError, ContentFilter};
+pub use extraction::{ExtractionPool, ExtractionTimeoutError};
+pub use ranking::SearchPreferences;
+pub use tidal::TidalClient;
+pub use ytdlp_optimized::{
+    PermanentExtractionError, RestrictedVideoError, RetryPolicy, TrackFailureReason, UpdateResult, YtDlpOptimizedClient,
+    YtDlpUpdater,
+};
+
+use crate::audio::silence::SilenceMonitor;
+use crate::config::ReloadableConfig;
 
 
 /// Trait común para todas las fuentes de música
@@ -31,51 +48,84 @@ pub trait MusicSource {
     fn source_name(&self) -> &'static str;
 }
 
-/// Representa un track de música
+/// Representa un track de música.
+///
+/// Los textos son `Arc<str>`: el track se clona en cada operación de cola y embed,
+/// y así clonar solo copia punteros en vez de reservar memoria.
 #[derive(Debug, Clone)]
 pub struct TrackSource {
-    title: String,
-    artist: Option<String>,
+    title: Arc<str>,
+    artist: Option<Arc<str>>,
     duration: Option<Duration>,
-    thumbnail: Option<String>,
-    url: String,
-    stream_url: Option<String>,
+    thumbnail: Option<Arc<str>>,
+    url: Arc<str>,
+    stream_url: Option<Arc<str>>,
     source_type: SourceType,
     requested_by: UserId,
+    chapters: Option<Vec<Chapter>>,
+    /// Álbum al que pertenece, si llegó desde una playlist detectada como álbum
+    album_name: Option<String>,
+    /// Emisora, si es una radio por internet (Shoutcast/Icecast) en vivo
+    station: Option<String>,
+}
+
+/// Capítulo de un video (marcadores de YouTube extraídos por yt-dlp)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Chapter {
+    pub title: String,
+    /// Inicio en segundos
+    pub start_time: f64,
+    /// Fin en segundos
+    pub end_time: f64,
+}
+
+impl Chapter {
+    /// `true` si `position` cae dentro del capítulo
+    pub fn contains(&self, position: Duration) -> bool {
+        let secs = position.as_secs_f64();
+        secs >= self.start_time && secs < self.end_time
+    }
+
+    pub fn start(&self) -> Duration {
+        Duration::from_secs_f64(self.start_time.max(0.0))
+    }
 }
 
 impl TrackSource {
-    pub fn new(title: String, url: String, source_type: SourceType, requested_by: UserId) -> Self {
+    pub fn new(title: impl Into<Arc<str>>, url: impl Into<Arc<str>>, source_type: SourceType, requested_by: UserId) -> Self {
         Self {
-            title,
+            title: title.into(),
             artist: None,
             duration: None,
             thumbnail: None,
-            url,
+            url: url.into(),
             stream_url: None,
             source_type,
             requested_by,
+            chapters: None,
+            album_name: None,
+            station: None,
         }
     }
 
     // Getters
-    pub fn title(&self) -> String {
+    pub fn title(&self) -> Arc<str> {
         self.title.clone()
     }
-    pub fn artist(&self) -> Option<String> {
+    pub fn artist(&self) -> Option<Arc<str>> {
         self.artist.clone()
     }
     pub fn duration(&self) -> Option<Duration> {
         self.duration
     }
-    pub fn thumbnail(&self) -> Option<String> {
+    pub fn thumbnail(&self) -> Option<Arc<str>> {
         self.thumbnail.clone()
     }
-    pub fn url(&self) -> String {
+    pub fn url(&self) -> Arc<str> {
         self.url.clone()
     }
     #[allow(dead_code)]
-    pub fn stream_url(&self) -> Option<String> {
+    pub fn stream_url(&self) -> Option<Arc<str>> {
         self.stream_url.clone()
     }
     pub fn source_type(&self) -> SourceType {
@@ -84,10 +134,34 @@ impl TrackSource {
     pub fn requested_by(&self) -> UserId {
         self.requested_by
     }
+    pub fn chapters(&self) -> Option<&[Chapter]> {
+        self.chapters.as_deref()
+    }
+    pub fn album_name(&self) -> Option<&str> {
+        self.album_name.as_deref()
+    }
+    pub fn station(&self) -> Option<&str> {
+        self.station.as_deref()
+    }
+
+    /// `true` si es una radio por internet (en vivo, con metadata ICY)
+    pub fn is_radio(&self) -> bool {
+        self.station.is_some()
+    }
+
+    /// Capítulo activo en la posición de reproducción dada
+    pub fn current_chapter(&self, position: Duration) -> Option<&Chapter> {
+        self.chapters()?.get(self.current_chapter_index(position)?)
+    }
+
+    /// Índice del capítulo activo en la posición de reproducción dada
+    pub fn current_chapter_index(&self, position: Duration) -> Option<usize> {
+        self.chapters()?.iter().position(|chapter| chapter.contains(position))
+    }
 
     // Setters
-    pub fn with_artist(mut self, artist: String) -> Self {
-        self.artist = Some(artist);
+    pub fn with_artist(mut self, artist: impl Into<Arc<str>>) -> Self {
+        self.artist = Some(artist.into());
         self
     }
 
@@ -96,14 +170,48 @@ impl TrackSource {
         self
     }
 
-    pub fn with_thumbnail(mut self, thumbnail: String) -> Self {
-        self.thumbnail = Some(thumbnail);
+    pub fn with_thumbnail(mut self, thumbnail: impl Into<Arc<str>>) -> Self {
+        self.thumbnail = Some(thumbnail.into());
+        self
+    }
+
+    pub fn with_chapters(mut self, chapters: Vec<Chapter>) -> Self {
+        self.chapters = (!chapters.is_empty()).then_some(chapters);
+        self
+    }
+
+    /// Completa los datos que faltan (duración, miniatura, artista, capítulos)
+    /// con los de `other`, la misma canción extraída con más detalle.
+    pub fn with_metadata_from(mut self, other: &TrackSource) -> Self {
+        self.duration = self.duration.or(other.duration);
+        self.thumbnail = self.thumbnail.or_else(|| other.thumbnail.clone());
+        self.artist = self.artist.or_else(|| other.artist.clone());
+        self.chapters = self.chapters.or_else(|| other.chapters.clone());
+        self
+    }
+
+    pub fn with_album_name(mut self, album_name: String) -> Self {
+        self.album_name = Some(album_name);
+        self
+    }
+
+    /// Marca el track como radio en vivo: sin duración y con la emisora como artista
+    pub fn with_station(mut self, station: String) -> Self {
+        self.artist = Some(Arc::from(station.as_str()));
+        self.duration = None;
+        self.station = Some(station);
+        self
+    }
+
+    /// Reemplaza el título (p. ej. con la canción que anuncia una radio)
+    pub fn with_title(mut self, title: impl Into<Arc<str>>) -> Self {
+        self.title = title.into();
         self
     }
 
     #[allow(dead_code)]
-    pub fn with_stream_url(mut self, stream_url: String) -> Self {
-        self.stream_url = Some(stream_url);
+    pub fn with_stream_url(mut self, stream_url: impl Into<Arc<str>>) -> Self {
+        self.stream_url = Some(stream_url.into());
         self
     }
 
@@ -123,37 +231,91 @@ impl TrackSource {
     ///
     /// `filter` es la cadena de filtros ffmpeg construida por `AudioEffects::build_filter`
     /// (loudnorm + EQ del preset). Si la cadena ffmpeg falla, cae a un fallback sin efectos.
-    pub async fn get_input(&self, filter: &str) -> Result<Input> {
+    /// Con `silence`, ffmpeg además reporta los tramos de silencio (el fallback no).
+    /// `proxy` es el proxy de yt-dlp de la guild, si tiene uno.
+    pub async fn get_input(&self, filter: &str, silence: Option<Arc<SilenceMonitor>>, proxy: Option<&str>) -> Result<Input> {
         info!("🎵 Creando input para: {}", self.title);
 
         // Camino principal: yt-dlp | ffmpeg con loudnorm + EQ reales
-        match self.get_ffmpeg_input(filter).await {
+        match self.get_ffmpeg_input(filter, silence, proxy).await {
             Ok(input) => Ok(input),
             Err(e) => {
                 tracing::warn!("⚠️ Cadena ffmpeg falló: {:?}, fallback sin efectos...", e);
                 // Fallback sin efectos: streaming directo vía songbird YoutubeDl
-                self.get_simple_input().await
+                self.get_simple_input(proxy).await
             }
         }
     }
 }
 
 /// Tipos de fuentes de música
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum SourceType {
     YouTube,
+    /// Metadata de Tidal resuelta a un stream de YouTube
+    Tidal,
     DirectUrl,
 }
 
 impl SourceType {
-    #[allow(dead_code)]
+    /// Todas las fuentes
+    pub const ALL: [SourceType; 3] = [SourceType::YouTube, SourceType::Tidal, SourceType::DirectUrl];
+
+    /// Nombre con el que se guarda la fuente (playlists, cache, filtros). Su
+    /// inverso es `str::parse::<SourceType>()`.
     pub fn as_str(&self) -> &'static str {
         match self {
             SourceType::YouTube => "youtube",
+            SourceType::Tidal => "tidal",
             SourceType::DirectUrl => "direct",
         }
     }
+
+    /// Si la fuente puede buscar por texto (las demás solo aceptan URLs)
+    pub fn supports_text_search(&self) -> bool {
+        matches!(self, SourceType::YouTube)
+    }
+}
+
+/// Se guarda como `as_str()` (`ServerConfig::default_search_source`)
+impl serde::Serialize for SourceType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::fmt::Display for SourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Nombre de fuente que no corresponde a ningún [`SourceType`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Fuente desconocida: `{0}`")]
+pub struct UnknownSourceTypeError(pub String);
+
+impl std::str::FromStr for SourceType {
+    type Err = UnknownSourceTypeError;
+
+    /// Acepta `as_str()` sin distinguir mayúsculas y, por compatibilidad con
+    /// las playlists ya guardadas, los nombres `{:?}` ("YouTube", "DirectUrl")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "youtube" => Ok(SourceType::YouTube),
+            "tidal" => Ok(SourceType::Tidal),
+            "direct" | "directurl" => Ok(SourceType::DirectUrl),
+            _ => Err(UnknownSourceTypeError(s.to_string())),
+        }
+    }
 }
 
 /// Información de resultado de búsqueda
@@ -168,40 +330,85 @@ pub struct SearchResult {
 /// Manager optimizado para extracción de música usando solo yt-dlp
 pub struct SourceManager {
     ytdlp: YtDlpOptimizedClient,
+    tidal: TidalClient,
+    /// Fuente de las búsquedas por texto
+    search_source: SourceType,
 }
 
 impl SourceManager {
     pub fn new() -> Self {
         Self {
             ytdlp: YtDlpOptimizedClient::new(),
+            tidal: TidalClient::new(YtDlpOptimizedClient::new()),
+            search_source: SourceType::YouTube,
+        }
+    }
+
+    /// Crea un manager con la política de reintentos y el proxy global de la configuración
+    pub fn with_config(config: &ReloadableConfig) -> Self {
+        Self::with_proxy(config, config.ytdlp_proxy.clone())
+    }
+
+    /// Como [`with_config`](Self::with_config), pero con un proxy concreto
+    /// (el de la guild, si sobrescribe el global)
+    pub fn with_proxy(config: &ReloadableConfig, proxy: Option<String>) -> Self {
+        let retry = RetryPolicy::from_config(config);
+
+        Self {
+            ytdlp: YtDlpOptimizedClient::with_retry_policy(retry).with_proxy(proxy.clone()),
+            tidal: TidalClient::new(YtDlpOptimizedClient::with_retry_policy(retry).with_proxy(proxy)),
+            search_source: SourceType::YouTube,
         }
     }
 
+    /// Busca por texto en `source` en vez de YouTube (la fuente por defecto de la guild)
+    pub fn with_search_source(mut self, source: SourceType) -> Self {
+        self.search_source = source;
+        self
+    }
+
     /// Verifica que todas las dependencias estén disponibles
     pub async fn verify_dependencies(&self) -> Result<()> {
         self.ytdlp.verify_dependencies().await
     }
 
-    /// Busca música usando yt-dlp optimizado
+    /// Busca música en la fuente de búsqueda del manager (YouTube salvo
+    /// [`with_search_source`](Self::with_search_source))
+    #[tracing::instrument(
+        name = "sources.search",
+        skip(self),
+        fields(source_type = self.search_source.as_str(), ytdlp_duration_ms = tracing::field::Empty)
+    )]
     pub async fn search_all(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        let tracks = self.ytdlp.search(query, limit).await?;
-        
+        let started = std::time::Instant::now();
+        let tracks = match self.search_source {
+            SourceType::YouTube => self.ytdlp.search(query, limit).await?,
+            SourceType::Tidal => self.tidal.search(query, limit).await?,
+            SourceType::DirectUrl => anyhow::bail!("Las URLs directas no admiten búsquedas por texto"),
+        };
+        tracing::Span::current().record("ytdlp_duration_ms", started.elapsed().as_millis() as u64);
+
         let results = vec![SearchResult {
             tracks,
             total: limit,
-            source: SourceType::YouTube,
+            source: self.search_source,
         }];
 
         Ok(results)
     }
 
-    /// Obtiene track de URL usando yt-dlp optimizado
+    /// Obtiene track de URL usando yt-dlp optimizado (las URLs de Tidal se resuelven a YouTube)
     pub async fn get_track_from_url(&self, url: &str, requested_by: UserId) -> Result<TrackSource> {
+        if self.tidal.is_valid_url(url) {
+            let track = self.tidal.get_track(url).await?;
+            return Ok(track.with_requested_by(requested_by));
+        }
+
         if self.ytdlp.is_valid_url(url) {
             let track = self.ytdlp.get_track(url).await?;
             return Ok(track.with_requested_by(requested_by));
         }
 
-        anyhow::bail!("URL no soportada (solo YouTube): {}", url)
+        anyhow::bail!("URL no soportada (solo YouTube y Tidal): {}", url)
     }
 }
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use super::TrackSource;
+
+/// Palabras que delatan versiones alternativas y su penalización
+const ALTERNATE_VERSION_PENALTIES: &[(&str, f64)] = &[
+    ("reaction", -40.0),
+    ("karaoke", -30.0),
+    ("instrumental", -25.0),
+    ("nightcore", -25.0),
+    ("remix", -20.0),
+    ("8d", -20.0),
+    ("sped up", -20.0),
+    ("cover", -15.0),
+    ("slowed", -15.0),
+    ("reverb", -15.0),
+    ("live", -10.0),
+];
+
+/// Palabras del título que indican contenido oficial
+const OFFICIAL_TITLE_BONUSES: &[(&str, f64)] = &[
+    ("official music", 35.0),
+    ("official", 30.0),
+    ("video oficial", 30.0),
+    ("music video", 25.0),
+];
+
+/// Preferencias de ranking de búsqueda, configurables por guild (`/settings search`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchPreferences {
+    /// Inicio de la ventana de duración preferida (segundos)
+    pub min_duration_secs: u64,
+    /// Fin de la ventana de duración preferida (segundos)
+    pub max_duration_secs: u64,
+    /// Penalizar live/cover/reaction/remix y similares
+    pub penalize_alternate_versions: bool,
+    /// Preferir uploaders oficiales (VEVO, "- Topic", "Official")
+    pub prefer_official: bool,
+}
+
+impl Default for SearchPreferences {
+    fn default() -> Self {
+        Self {
+            min_duration_secs: 120, // 2 minutos
+            max_duration_secs: 480, // 8 minutos
+            penalize_alternate_versions: true,
+            prefer_official: true,
+        }
+    }
+}
+
+/// Puntúa un resultado de búsqueda según la consulta y las preferencias de la guild
+pub fn score(track: &TrackSource, query: &str, prefs: &SearchPreferences) -> f64 {
+    let query_lower = query.to_lowercase();
+    let title_lower = track.title().to_lowercase();
+    let mut score = 0.0;
+
+    // Coincidencia de la consulta completa en el título
+    if title_lower.contains(&query_lower) {
+        score += 100.0;
+    }
+
+    // Palabras significativas de la consulta presentes en el título
+    let title_words: Vec<&str> = title_lower.split_whitespace().collect();
+    for query_word in query_lower.split_whitespace().filter(|w| w.len() > 2) {
+        if title_words.iter().any(|title_word| title_word.contains(query_word)) {
+            score += 50.0;
+        }
+    }
+
+    if prefs.penalize_alternate_versions {
+        for (word, penalty) in ALTERNATE_VERSION_PENALTIES {
+            // Solo si el usuario no lo pidió explícitamente
+            if title_lower.contains(word) && !query_lower.contains(word) {
+                score += penalty;
+            }
+        }
+    }
+
+    let artist_lower = track.artist().map(|a| a.to_lowercase());
+
+    if prefs.prefer_official {
+        for (word, bonus) in OFFICIAL_TITLE_BONUSES {
+            if title_lower.contains(word) {
+                score += bonus;
+                break;
+            }
+        }
+
+        if let Some(artist) = &artist_lower {
+            if artist.contains("vevo") || artist.ends_with(" - topic") || artist.contains("official") {
+                score += 35.0;
+            }
+        }
+    }
+
+    // Ventana de duración preferida
+    if let Some(duration) = track.duration() {
+        let secs = duration.as_secs();
+        if (prefs.min_duration_secs..=prefs.max_duration_secs).contains(&secs) {
+            score += 10.0;
+        } else {
+            score -= 15.0;
+        }
+    }
+
+    // Artista que coincide con la búsqueda
+    if let Some(artist) = &artist_lower {
+        if !artist.is_empty() && (query_lower.contains(artist.as_str()) || artist.contains(&query_lower)) {
+            score += 40.0;
+        }
+    }
+
+    score
+}
+
+/// Ordena los resultados de mayor a menor puntuación (estable ante empates)
+pub fn rank(results: &[TrackSource], query: &str, prefs: &SearchPreferences) -> Vec<TrackSource> {
+    let mut scored: Vec<(f64, &TrackSource)> = results
+        .iter()
+        .map(|track| (score(track, query, prefs), track))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().map(|(_, track)| track.clone()).collect()
+}
+
+/// Mejor resultado según las preferencias, si hay alguno
+pub fn best_match(results: &[TrackSource], query: &str, prefs: &SearchPreferences) -> Option<TrackSource> {
+    rank(results, query, prefs).into_iter().next()
+}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use super::{MusicSource, SourceType, TrackSource, YtDlpOptimizedClient};
+
+/// Endpoint oEmbed público de Tidal (no requiere autenticación)
+const TIDAL_OEMBED_URL: &str = "https://oembed.tidal.com/";
+
+/// Tipo de recurso enlazado en una URL de Tidal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TidalResource {
+    Track,
+    Album,
+    Playlist,
+}
+
+/// URL de Tidal ya parseada (`tidal.com/browse/track/123`, `listen.tidal.com/album/456`, ...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TidalLink {
+    pub resource: TidalResource,
+    pub id: String,
+}
+
+impl TidalLink {
+    /// Parsea una URL de Tidal; retorna `None` si no es un track/álbum/playlist de Tidal
+    pub fn parse(url: &str) -> Option<Self> {
+        let parsed = url::Url::parse(url).ok()?;
+        let host = parsed.host_str()?;
+        if host != "tidal.com" && !host.ends_with(".tidal.com") {
+            return None;
+        }
+
+        let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+        let resource = loop {
+            match segments.next()? {
+                "browse" => continue,
+                "track" => break TidalResource::Track,
+                "album" => break TidalResource::Album,
+                "playlist" => break TidalResource::Playlist,
+                _ => return None,
+            }
+        };
+
+        let id = segments.next()?.to_string();
+        let valid_id = match resource {
+            TidalResource::Track | TidalResource::Album => id.chars().all(|c| c.is_ascii_digit()),
+            TidalResource::Playlist => id.chars().all(|c| c.is_ascii_hexdigit() || c == '-'),
+        };
+
+        valid_id.then_some(Self { resource, id })
+    }
+
+    /// URL canónica usada para consultar el oEmbed
+    pub fn canonical_url(&self) -> String {
+        let kind = match self.resource {
+            TidalResource::Track => "track",
+            TidalResource::Album => "album",
+            TidalResource::Playlist => "playlist",
+        };
+        format!("https://tidal.com/browse/{}/{}", kind, self.id)
+    }
+}
+
+/// Metadata pública de un recurso de Tidal
+#[derive(Debug, Clone, Deserialize)]
+pub struct TidalMetadata {
+    pub title: String,
+    #[serde(rename = "author_name")]
+    pub artist: Option<String>,
+    #[serde(rename = "thumbnail_url")]
+    pub thumbnail: Option<String>,
+}
+
+impl TidalMetadata {
+    /// Consulta de búsqueda en YouTube ("artista - título")
+    pub fn search_query(&self) -> String {
+        match self.artist.as_deref().filter(|a| !a.is_empty()) {
+            Some(artist) => format!("{} - {}", artist, self.title),
+            None => self.title.clone(),
+        }
+    }
+}
+
+/// Fuente de Tidal basada solo en metadata.
+///
+/// El audio de Tidal requiere una cuenta autenticada, así que aquí únicamente
+/// se obtienen título y artista del oEmbed público y se resuelve el track al
+/// mejor resultado de YouTube. Los álbumes y playlists se resuelven igual a
+/// partir de su título: listar su contenido también requiere autenticación.
+pub struct TidalClient {
+    http: reqwest::Client,
+    ytdlp: YtDlpOptimizedClient,
+}
+
+impl TidalClient {
+    pub fn new(ytdlp: YtDlpOptimizedClient) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self { http, ytdlp }
+    }
+
+    /// Obtiene título, artista y portada desde el oEmbed de Tidal
+    pub async fn fetch_metadata(&self, link: &TidalLink) -> Result<TidalMetadata> {
+        let response = self
+            .http
+            .get(TIDAL_OEMBED_URL)
+            .query(&[("url", link.canonical_url())])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Tidal no devolvió metadata para {} (HTTP {})",
+                link.canonical_url(),
+                response.status()
+            );
+        }
+
+        let metadata: TidalMetadata = response.json().await?;
+        debug!("🌊 Metadata de Tidal: {:?}", metadata);
+        Ok(metadata)
+    }
+}
+
+#[async_trait]
+impl MusicSource for TidalClient {
+    async fn search(&self, _query: &str, _limit: usize) -> Result<Vec<TrackSource>> {
+        anyhow::bail!("La búsqueda en Tidal requiere autenticación; usa una URL de Tidal")
+    }
+
+    async fn get_track(&self, url: &str) -> Result<TrackSource> {
+        let link = TidalLink::parse(url)
+            .ok_or_else(|| anyhow::anyhow!("URL de Tidal no válida: {}", url))?;
+        let metadata = self.fetch_metadata(&link).await?;
+        let query = metadata.search_query();
+
+        info!("🌊 Resolviendo Tidal en YouTube: {}", query);
+        let best = self
+            .ytdlp
+            .search(&query, 1)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No se encontró en YouTube: {}", query))?;
+
+        let track = TrackSource::new(metadata.title, best.url(), SourceType::Tidal, best.requested_by());
+
+        let track = match metadata.artist.map(Into::into).or_else(|| best.artist()) {
+            Some(artist) => track.with_artist(artist),
+            None => track,
+        };
+
+        let track = match best.duration() {
+            Some(duration) => track.with_duration(duration),
+            None => track,
+        };
+
+        let track = match metadata.thumbnail.map(Into::into).or_else(|| best.thumbnail()) {
+            Some(thumbnail) => track.with_thumbnail(thumbnail),
+            None => track,
+        };
+
+        Ok(track)
+    }
+
+    async fn get_playlist(&self, url: &str) -> Result<Vec<TrackSource>> {
+        anyhow::bail!(
+            "Listar álbumes y playlists de Tidal requiere autenticación: {}",
+            url
+        )
+    }
+
+    fn is_valid_url(&self, url: &str) -> bool {
+        TidalLink::parse(url).is_some()
+    }
+
+    fn source_name(&self) -> &'static str {
+        "Tidal"
+    }
+}
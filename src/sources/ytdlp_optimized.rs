@@ -1,11 +1,18 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use parking_lot::RwLock;
 use serenity::model::id::UserId;
 use songbird::input::Input;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 use tracing::{debug, info, warn, error};
 
-use super::{MusicSource, TrackSource, SourceType};
+use super::{direct, Chapter, MusicSource, TrackSource, SourceType};
+use crate::audio::loudness::{parse_loudnorm_output, LoudnessMeasurement, MEASURE_FILTER};
+use crate::audio::silence::{with_silence_filter, SilenceMonitor};
+use crate::config::{mask_proxy_credentials, ReloadableConfig};
 
 /// Extractor-arg que apunta yt-dlp al proveedor de PO Tokens (servicio
 /// `bgutil-provider` en la red del compose). Evita el bloqueo anti-bot de
@@ -17,12 +24,467 @@ fn pot_extractor_arg() -> String {
     format!("youtubepot-bgutilhttp:base_url={base}")
 }
 
+/// Argumentos `--proxy` de una invocación de yt-dlp (vacío sin proxy).
+fn proxy_args(proxy: Option<&str>) -> Vec<String> {
+    match proxy {
+        Some(proxy) => {
+            debug!("🌐 yt-dlp vía proxy {}", mask_proxy_credentials(proxy));
+            vec!["--proxy".to_string(), proxy.to_string()]
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Cookies configuradas para yt-dlp (`YTDLP_COOKIES`, `YTDLP_COOKIES_FROM_BROWSER`, `/cookies`)
+#[derive(Debug, Default)]
+struct CookieSettings {
+    file: Option<PathBuf>,
+    browser: Option<String>,
+}
+
+static COOKIE_SETTINGS: LazyLock<RwLock<CookieSettings>> = LazyLock::new(Default::default);
+
+/// Archivo de cookies con prioridad sobre las rutas por defecto (None: solo las rutas por defecto)
+pub fn set_cookies_file(path: Option<PathBuf>) {
+    COOKIE_SETTINGS.write().file = path;
+}
+
+/// Navegador del que yt-dlp lee la sesión al reintentar un video que pide iniciar sesión
+pub fn set_cookies_from_browser(browser: Option<String>) {
+    if let Some(browser) = &browser {
+        // Sin sesión gráfica casi nunca hay un perfil de navegador con sesión iniciada
+        if cfg!(target_os = "linux") && std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            warn!(
+                "🍪 YTDLP_COOKIES_FROM_BROWSER={} en un entorno sin sesión gráfica: es probable que no haya \
+                 sesión del navegador que leer; usa YTDLP_COOKIES con un cookies.txt exportado",
+                browser
+            );
+        }
+    }
+    COOKIE_SETTINGS.write().browser = browser;
+}
+
+/// Argumentos extra para repetir con sesión una invocación que la pidió (None: no hay navegador configurado).
+///
+/// El cookies.txt ya se pasa en cada invocación, así que el reintento usa el navegador.
+fn authenticated_retry_args() -> Option<Vec<String>> {
+    let browser = COOKIE_SETTINGS.read().browser.clone()?;
+    Some(vec!["--cookies-from-browser".to_string(), browser])
+}
+
+/// `true` si el fallo se resuelve con una sesión de YouTube (restricción de edad,
+/// miembros o "Sign in to confirm...")
+pub fn needs_authentication(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<RestrictedVideoError>().is_some() || error.to_string().to_lowercase().contains("sign in")
+}
+
+/// Argumentos de yt-dlp para streamear el mejor audio a stdout (sin la URL).
+fn ytdlp_stream_args(pot_arg: &str) -> [&str; 13] {
+    [
+        "--ignore-config",
+        "-f", "bestaudio[acodec=opus]/bestaudio[ext=webm]/bestaudio/best",
+        "-o", "-",
+        "--no-playlist",
+        "--no-check-certificate",
+        "--geo-bypass",
+        "--force-ipv4",
+        // Acelerar el arranque: no hacer HEAD requests para verificar
+        // formatos (ya elegimos uno concreto con -f).
+        "--no-check-formats",
+        "--extractor-args", pot_arg,
+        "--quiet",
+    ]
+}
+
+/// Tiempo máximo para medir la sonoridad de una pista (descarga + análisis)
+const LOUDNESS_MEASURE_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Tope del delay entre reintentos, sin importar el número de intento.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(8);
+
+/// Política de reintentos para extracciones de yt-dlp
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Reintentos adicionales tras el primer intento
+    pub max_retries: u32,
+    /// Delay base; se duplica en cada reintento (más jitter)
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &ReloadableConfig) -> Self {
+        Self {
+            max_retries: config.ytdlp_extraction_retries,
+            base_delay: Duration::from_millis(config.ytdlp_retry_base_delay_ms),
+        }
+    }
+
+    /// Delay antes del reintento `attempt` (1-based): backoff exponencial con
+    /// hasta un 50% de jitter para no sincronizar reintentos entre guilds.
+    fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(MAX_RETRY_DELAY);
+        let jitter_ms = fastrand::u64(0..=exp.as_millis() as u64 / 2);
+        exp + Duration::from_millis(jitter_ms)
+    }
+}
+
+/// Error permanente de yt-dlp (video privado, eliminado, bloqueado...).
+///
+/// No se reintenta; el mensaje está pensado para mostrarse al usuario.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct PermanentExtractionError(pub &'static str);
+
+/// Video que yt-dlp solo puede extraer con una sesión de YouTube habilitada (cookies).
+///
+/// Se separa de [`PermanentExtractionError`] porque tiene solución: configurar cookies
+/// de una cuenta con edad verificada o con membresía del canal.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum RestrictedVideoError {
+    #[error("🔞 El video tiene restricción de edad")]
+    AgeRestricted,
+    #[error("💎 El video es exclusivo para miembros del canal")]
+    MembersOnly,
+}
+
+/// Clasificación de un fallo de yt-dlp según su stderr
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailureKind {
+    /// Fallo transitorio (timeout, rate limit, 5xx): vale la pena reintentar
+    Retryable,
+    /// Fallo definitivo con un mensaje amigable para el usuario
+    Permanent(&'static str),
+    /// Video restringido (edad / miembros): requiere cookies de una cuenta habilitada
+    Restricted(RestrictedVideoError),
+    /// Fallo desconocido: no se reintenta, se propaga tal cual
+    Unknown,
+}
+
+/// Mensaje de [`PermanentExtractionError`] para videos bloqueados por región
+const REGION_LOCKED_MESSAGE: &str = "🌍 El video no está disponible en esta región";
+
+/// Motivo por el que una canción de una playlist no se pudo cargar, para el
+/// resumen de la carga
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TrackFailureReason {
+    /// Privada, eliminada, restringida o bloqueada por derechos
+    Unavailable,
+    /// No disponible en la región del bot
+    RegionLocked,
+    /// La fuente no respondió (timeouts, rate limit, errores 5xx)
+    SourceDown,
+    /// Cualquier otro fallo (cola llena, errores desconocidos)
+    Other,
+}
+
+impl TrackFailureReason {
+    /// Motivo de `error`, según su tipo o el stderr de yt-dlp que lleva
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        if let Some(permanent) = error.downcast_ref::<PermanentExtractionError>() {
+            return Self::from_permanent(permanent.0);
+        }
+        if error.downcast_ref::<RestrictedVideoError>().is_some() {
+            return Self::Unavailable;
+        }
+        if error.downcast_ref::<super::ExtractionTimeoutError>().is_some() {
+            return Self::SourceDown;
+        }
+
+        match classify_failure(&format!("{:#}", error)) {
+            FailureKind::Permanent(message) => Self::from_permanent(message),
+            FailureKind::Restricted(_) => Self::Unavailable,
+            FailureKind::Retryable => Self::SourceDown,
+            FailureKind::Unknown => Self::Other,
+        }
+    }
+
+    fn from_permanent(message: &str) -> Self {
+        if message == REGION_LOCKED_MESSAGE {
+            Self::RegionLocked
+        } else {
+            Self::Unavailable
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Unavailable => "🚫 No disponibles",
+            Self::RegionLocked => "🌍 Bloqueadas por región",
+            Self::SourceDown => "📡 Fuente caída",
+            Self::Other => "❓ Otros errores",
+        }
+    }
+}
+
+/// Clasifica el mensaje de error de yt-dlp
+pub fn classify_failure(stderr: &str) -> FailureKind {
+    let lower = stderr.to_lowercase();
+
+    const RESTRICTED: &[(&str, RestrictedVideoError)] = &[
+        ("confirm your age", RestrictedVideoError::AgeRestricted),
+        ("age-restricted", RestrictedVideoError::AgeRestricted),
+        ("inappropriate for some users", RestrictedVideoError::AgeRestricted),
+        ("members-only", RestrictedVideoError::MembersOnly),
+        ("join this channel", RestrictedVideoError::MembersOnly),
+        ("available to this channel's members", RestrictedVideoError::MembersOnly),
+    ];
+
+    if let Some((_, restriction)) = RESTRICTED.iter().find(|(pattern, _)| lower.contains(pattern)) {
+        return FailureKind::Restricted(*restriction);
+    }
+
+    const PERMANENT: &[(&str, &str)] = &[
+        ("private video", "🔒 El video es privado"),
+        ("video unavailable", "🚫 El video no está disponible"),
+        ("has been removed", "🗑️ El video fue eliminado"),
+        ("account associated with this video has been terminated", "🗑️ El video fue eliminado"),
+        ("copyright", "©️ El video fue bloqueado por derechos de autor"),
+        ("available in your country", REGION_LOCKED_MESSAGE),
+        ("is not a valid url", "❌ La URL no es válida"),
+        ("unsupported url", "❌ La URL no es compatible"),
+    ];
+
+    if let Some((_, message)) = PERMANENT.iter().find(|(pattern, _)| lower.contains(pattern)) {
+        return FailureKind::Permanent(message);
+    }
+
+    const RETRYABLE: &[&str] = &[
+        "timed out",
+        "timeout",
+        "http error 429",
+        "too many requests",
+        "http error 500",
+        "http error 502",
+        "http error 503",
+        "http error 504",
+        "connection reset",
+        "connection refused",
+        "temporary failure in name resolution",
+        "unable to download webpage",
+        "incompleteread",
+    ];
+
+    if RETRYABLE.iter().any(|pattern| lower.contains(pattern)) {
+        FailureKind::Retryable
+    } else {
+        FailureKind::Unknown
+    }
+}
+
+/// Tiempo máximo para `yt-dlp -U` (descarga el binario nuevo)
+const YTDLP_UPDATE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Video público y estable que `/test-proxy` extrae a través del proxy
+const PROXY_TEST_URL: &str = "https://www.youtube.com/watch?v=jNQXAC9IVRw";
+
+/// Tiempo máximo de la extracción de prueba de `/test-proxy`
+const PROXY_TEST_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Resultado de [`YtDlpUpdater::check_and_update`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateResult {
+    UpToDate { version: String },
+    Updated { previous: String, current: String },
+}
+
+/// Versión a la que se actualizó yt-dlp según la salida de `yt-dlp -U`
+/// ("Updated yt-dlp to stable@2024.08.06 from yt-dlp/yt-dlp"), sin el canal.
+/// `None` si no se instaló nada.
+pub fn parse_updated_version(output: &str) -> Option<String> {
+    let rest = output.lines().find_map(|line| line.trim().strip_prefix("Updated yt-dlp to "))?;
+    let version = rest.split_whitespace().next()?;
+    let version = version.rsplit('@').next().unwrap_or(version);
+    Some(version.to_string())
+}
+
+/// Actualiza yt-dlp con su propio `-U`; YouTube cambia a menudo y una versión
+/// vieja termina fallando en todas las extracciones.
+pub struct YtDlpUpdater;
+
+impl YtDlpUpdater {
+    /// Versión instalada (`yt-dlp --version`)
+    pub async fn current_version() -> Result<String> {
+        let output = tokio::process::Command::new("yt-dlp")
+            .arg("--version")
+            .output()
+            .await?;
+        if !output.status.success() {
+            anyhow::bail!("yt-dlp --version falló");
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Ejecuta `yt-dlp -U` y compara con la versión anterior.
+    ///
+    /// Falla si yt-dlp no está o no se puede actualizar solo (p. ej. instalado
+    /// con pip, que pide actualizar con pip).
+    pub async fn check_and_update() -> Result<UpdateResult> {
+        let previous = Self::current_version().await?;
+
+        let output = tokio::time::timeout(
+            YTDLP_UPDATE_TIMEOUT,
+            tokio::process::Command::new("yt-dlp").arg("-U").kill_on_drop(true).output(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("yt-dlp -U no terminó en {}s", YTDLP_UPDATE_TIMEOUT.as_secs()))??;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().rev().chain(stdout.lines().rev()).find(|line| !line.trim().is_empty()).unwrap_or("sin salida");
+            anyhow::bail!("yt-dlp -U falló: {}", reason.trim());
+        }
+
+        match parse_updated_version(&stdout) {
+            Some(current) if current != previous => {
+                info!("⬆️ yt-dlp actualizado: {} → {}", previous, current);
+                Ok(UpdateResult::Updated { previous, current })
+            }
+            _ => {
+                debug!("yt-dlp {} ya está al día", previous);
+                Ok(UpdateResult::UpToDate { version: previous })
+            }
+        }
+    }
+}
+
 /// Cliente optimizado que usa solo yt-dlp + FFmpeg con streaming directo
-pub struct YtDlpOptimizedClient;
+pub struct YtDlpOptimizedClient {
+    retry: RetryPolicy,
+    /// Proxy de yt-dlp (`--proxy`), si hay uno configurado
+    proxy: Option<String>,
+}
 
 impl YtDlpOptimizedClient {
     pub fn new() -> Self {
-        Self
+        Self {
+            retry: RetryPolicy::default(),
+            proxy: None,
+        }
+    }
+
+    /// Crea un cliente con una política de reintentos específica
+    pub fn with_retry_policy(retry: RetryPolicy) -> Self {
+        Self { retry, proxy: None }
+    }
+
+    /// Enruta las invocaciones de yt-dlp de este cliente por `proxy`
+    pub fn with_proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Extrae un video conocido (`PROXY_TEST_URL`) a través de `proxy` sin descargar nada
+    /// (`--simulate`) y devuelve el título del video.
+    ///
+    /// Así se comprueba que el proxy responde y deja pasar a YouTube, no solo
+    /// que yt-dlp acepta la URL; falla con su stderr si no.
+    pub async fn test_proxy(proxy: &str) -> Result<String> {
+        let mut cmd = tokio::process::Command::new("yt-dlp");
+        cmd.args(proxy_args(Some(proxy)))
+            .args(["--simulate", "--no-playlist", "--no-warnings", "--print", "title"])
+            .args(["--socket-timeout", "15"])
+            .arg(PROXY_TEST_URL)
+            .kill_on_drop(true);
+
+        let output = tokio::time::timeout(PROXY_TEST_TIMEOUT, cmd.output())
+            .await
+            .map_err(|_| anyhow::anyhow!("yt-dlp no respondió en {}s a través del proxy", PROXY_TEST_TIMEOUT.as_secs()))??;
+        if !output.status.success() {
+            anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Ejecuta una extracción reintentando los fallos transitorios con backoff.
+    ///
+    /// Los fallos permanentes se devuelven de inmediato como
+    /// [`PermanentExtractionError`]; los desconocidos se propagan sin reintentar.
+    async fn with_retry<T, F, Fut>(&self, operation: &str, mut f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            let error = match f().await {
+                Ok(value) => {
+                    if attempt > 0 {
+                        info!("✅ yt-dlp {} exitoso tras {} reintento(s)", operation, attempt);
+                    }
+                    return Ok(value);
+                }
+                Err(e) => e,
+            };
+
+            match classify_failure(&error.to_string()) {
+                FailureKind::Permanent(message) => {
+                    warn!("🚫 yt-dlp {} falló de forma permanente: {}", operation, message);
+                    return Err(PermanentExtractionError(message).into());
+                }
+                FailureKind::Restricted(restriction) => {
+                    warn!("🔞 yt-dlp {} requiere una sesión habilitada: {}", operation, restriction);
+                    return Err(restriction.into());
+                }
+                FailureKind::Retryable if attempt < self.retry.max_retries => {
+                    attempt += 1;
+                    let delay = self.retry.delay_for(attempt);
+                    warn!(
+                        "🔄 yt-dlp {} falló (intento {}/{}), reintentando en {:?}: {}",
+                        operation,
+                        attempt,
+                        self.retry.max_retries + 1,
+                        delay,
+                        error.to_string().trim()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                FailureKind::Retryable => {
+                    error!(
+                        "❌ yt-dlp {} falló tras {} intentos: {}",
+                        operation,
+                        attempt + 1,
+                        error.to_string().trim()
+                    );
+                    return Err(error);
+                }
+                FailureKind::Unknown => return Err(error),
+            }
+        }
+    }
+
+    /// Como [`Self::with_retry`], pero si yt-dlp pide iniciar sesión y hay un navegador
+    /// configurado, repite la operación con sus cookies. `f` recibe los argumentos
+    /// extra (vacíos en el primer intento); devuelve si hizo falta la sesión.
+    async fn with_authenticated_retry<T, F, Fut>(&self, operation: &str, mut f: F) -> Result<(T, bool)>
+    where
+        F: FnMut(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let error = match self.with_retry(operation, || f(Vec::new())).await {
+            Ok(value) => return Ok((value, false)),
+            Err(e) => e,
+        };
+        let Some(auth_args) = authenticated_retry_args().filter(|_| needs_authentication(&error)) else {
+            return Err(error);
+        };
+
+        info!("🍪 yt-dlp {} pide iniciar sesión, reintentando con las cookies del navegador", operation);
+        let value = self.with_retry(operation, || f(auth_args.clone())).await?;
+        Ok((value, true))
     }
 
     /// Verifica que yt-dlp y ffmpeg estén disponibles
@@ -64,7 +526,7 @@ impl YtDlpOptimizedClient {
     }
 
     /// Extrae información del video usando yt-dlp
-    async fn extract_video_info(&self, url: &str) -> Result<VideoInfo> {
+    async fn extract_video_info(&self, url: &str, extra_args: Vec<String>) -> Result<VideoInfo> {
         let cookies_path = Self::cookies_working_copy();
 
         let pot_arg = pot_extractor_arg();
@@ -72,6 +534,7 @@ impl YtDlpOptimizedClient {
         cmd.args([
             "--ignore-config",
             "--print", "%(title)s|%(uploader)s|%(duration)s|%(thumbnail)s",
+            "--print", "%(chapters)j",
             "--no-playlist",
             "--socket-timeout", "30",
             "--retries", "3",
@@ -82,8 +545,12 @@ impl YtDlpOptimizedClient {
         if let Some(cookies) = cookies_path {
             cmd.args(["--cookies", &cookies]);
         }
+        cmd.args(proxy_args(self.proxy.as_deref()));
+        cmd.args(extra_args);
 
         cmd.arg(url);
+        // Si la extracción se cancela por timeout, no dejar yt-dlp huérfano
+        cmd.kill_on_drop(true);
 
         let output = cmd.output().await?;
 
@@ -93,7 +560,15 @@ impl YtDlpOptimizedClient {
         }
 
         let info_str = String::from_utf8_lossy(&output.stdout);
-        let parts: Vec<&str> = info_str.trim().split('|').collect();
+        let mut lines = info_str.trim().lines();
+        let parts: Vec<&str> = lines.next().unwrap_or_default().split('|').collect();
+
+        // Segunda línea: capítulos en JSON ("null"/"NA" si el video no tiene)
+        let chapters = lines
+            .next()
+            .and_then(|json| serde_json::from_str::<Option<Vec<Chapter>>>(json).ok())
+            .flatten()
+            .unwrap_or_default();
 
         Ok(VideoInfo {
             title: parts.get(0).unwrap_or(&"Unknown").to_string(),
@@ -102,6 +577,7 @@ impl YtDlpOptimizedClient {
                 .and_then(|s| s.parse::<f64>().ok())
                 .map(|d| Duration::from_secs_f64(d)),
             thumbnail: parts.get(3).map(|s| s.to_string()),
+            chapters,
         })
     }
 
@@ -156,6 +632,11 @@ impl YtDlpOptimizedClient {
             }
         }
         
+        // youtube.com/shorts/VIDEO_ID
+        if let Some(video_id) = Self::shorts_video_id(&parsed) {
+            return Ok(video_id);
+        }
+
         // youtu.be/VIDEO_ID
         if parsed.host_str() == Some("youtu.be") {
             if let Some(segments) = parsed.path_segments() {
@@ -168,13 +649,57 @@ impl YtDlpOptimizedClient {
         anyhow::bail!("No se pudo extraer video ID de: {}", url)
     }
 
-    /// Verifica si la URL es válida para YouTube
+    /// ID del video de un enlace de Shorts (`youtube.com/shorts/VIDEO_ID`, también `m.` y `www.`)
+    fn shorts_video_id(parsed: &url::Url) -> Option<String> {
+        let host = parsed.host_str()?;
+        if host != "youtube.com" && !host.ends_with(".youtube.com") {
+            return None;
+        }
+
+        let mut segments = parsed.path_segments()?;
+        if segments.next()? != "shorts" {
+            return None;
+        }
+        segments.next().filter(|id| !id.is_empty()).map(str::to_string)
+    }
+
+    /// Convierte un enlace de Shorts en `watch?v=VIDEO_ID`, para que se extraiga y se
+    /// guarde en la cola como cualquier otro video. El resto de URLs no se modifica.
+    pub fn normalize_youtube_url(url: &str) -> String {
+        url::Url::parse(url)
+            .ok()
+            .and_then(|parsed| Self::shorts_video_id(&parsed))
+            .map(|video_id| format!("https://www.youtube.com/watch?v={}", video_id))
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// Extrae la posición inicial (`index=N`, base 1) de una URL de playlist de YouTube
+    pub fn extract_playlist_index(url: &str) -> Option<usize> {
+        let parsed = url::Url::parse(url).ok()?;
+        parsed
+            .query_pairs()
+            .find(|(key, _)| key == "index")
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .filter(|index| *index > 0)
+    }
+
+    /// Verifica si la URL es válida para YouTube (videos, Shorts, playlists y YouTube Music)
     pub fn is_youtube_url(url: &str) -> bool {
         url.contains("youtube.com") || url.contains("youtu.be") || url.contains("music.youtube.com")
     }
 
     /// Busca un archivo de cookies en las rutas conocidas (versión síncrona).
+    ///
+    /// El archivo configurado (`YTDLP_COOKIES` o `/cookies set-file`, un cookies.txt
+    /// en formato Netscape) tiene prioridad sobre las rutas por defecto.
     pub fn find_cookies_path() -> Option<String> {
+        if let Some(path) = COOKIE_SETTINGS.read().file.as_deref() {
+            if path.exists() {
+                return Some(path.display().to_string());
+            }
+            warn!("🍪 El archivo de cookies configurado no existe: {}", path.display());
+        }
+
         [
             "/app/config/cookies.txt".to_string(),
             "./config/cookies.txt".to_string(),
@@ -191,10 +716,11 @@ impl YtDlpOptimizedClient {
     /// track a medida que los procesa, sin esperar a listar toda la lista. No pide
     /// thumbnail (se resuelve al reproducir cada track) para acelerar la aparición.
     /// `limit` corta la cantidad de tracks (para mixes/radios infinitos `list=RD`).
-    /// Formato por línea: `url|title|uploader|duration`.
+    /// Formato por línea: `url|title|uploader|duration|playlist_title`.
     pub fn spawn_playlist_stream(
         url: &str,
         cookies: Option<&str>,
+        proxy: Option<&str>,
         limit: Option<usize>,
     ) -> std::io::Result<tokio::process::Child> {
         let pot_arg = pot_extractor_arg();
@@ -206,7 +732,7 @@ impl YtDlpOptimizedClient {
             "--lazy-playlist",
             // Forzar procesar la lista cuando la URL es watch?v=...&list=...
             "--yes-playlist",
-            "--print", "%(url)s|%(title)s|%(uploader)s|%(duration)s",
+            "--print", "%(url)s|%(title)s|%(uploader)s|%(duration)s|%(playlist_title)s",
             "--no-warnings",
             "--socket-timeout", "30",
             "--extractor-args", &pot_arg,
@@ -218,12 +744,24 @@ impl YtDlpOptimizedClient {
         if let Some(c) = cookies {
             cmd.args(["--cookies", c]);
         }
+        cmd.args(proxy_args(proxy));
         cmd.stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::null());
         cmd.spawn()
     }
 
-    /// Parsea una línea del stream de playlist (`url|title|uploader|duration`).
+    /// `true` si el título de una playlist indica que es un álbum publicado como
+    /// lista ("Artista - Álbum [Full Album]", discografías...).
+    pub fn is_youtube_album(playlist_title: &str) -> bool {
+        let title = playlist_title.to_lowercase();
+        ["album", "álbum", "discography", "discografía"]
+            .iter()
+            .any(|keyword| title.contains(keyword))
+    }
+
+    /// Parsea una línea del stream de playlist (`url|title|uploader|duration|playlist_title`).
+    ///
+    /// Si la playlist parece un álbum, cada track lleva su nombre en `album_name`.
     pub fn parse_playlist_line(line: &str, requested_by: UserId) -> Option<TrackSource> {
         let parts: Vec<&str> = line.split('|').collect();
         if parts.len() < 2 || parts[0].is_empty() || parts[0] == "NA" {
@@ -243,13 +781,16 @@ impl YtDlpOptimizedClient {
         if let Some(dur) = parts.get(3).and_then(|s| s.parse::<f64>().ok()) {
             track = track.with_duration(Duration::from_secs_f64(dur));
         }
+        if let Some(playlist_title) = parts.get(4).map(|s| s.trim()) {
+            if playlist_title != "NA" && Self::is_youtube_album(playlist_title) {
+                track = track.with_album_name(playlist_title.to_string());
+            }
+        }
         Some(track)
     }
-}
 
-#[async_trait]
-impl MusicSource for YtDlpOptimizedClient {
-    async fn search(&self, query: &str, limit: usize) -> Result<Vec<TrackSource>> {
+    /// Ejecuta una búsqueda con yt-dlp (un solo intento, sin reintentos)
+    async fn search_once(&self, query: &str, limit: usize) -> Result<Vec<TrackSource>> {
         info!("🔍 Iniciando búsqueda yt-dlp optimizada: {}", query);
         
         // Usar URL de YouTube search en lugar de ytsearch extractor (más confiable)
@@ -262,6 +803,7 @@ impl MusicSource for YtDlpOptimizedClient {
         let cookies_path = Self::cookies_working_copy();
         let search_limit = limit.min(5);
         let pot_arg = pot_extractor_arg();
+        let proxy = proxy_args(self.proxy.as_deref());
 
         // Usar std::process en lugar de tokio::process para evitar problemas de señales
         let output = tokio::task::spawn_blocking(move || {
@@ -286,6 +828,7 @@ impl MusicSource for YtDlpOptimizedClient {
             if let Some(cookies) = cookies_path {
                 cmd.args(["--cookies", &cookies]);
             }
+            cmd.args(proxy);
             
             cmd.arg(&search_url);
             cmd.output()
@@ -340,42 +883,8 @@ impl MusicSource for YtDlpOptimizedClient {
         Ok(tracks)
     }
 
-    async fn get_track(&self, url: &str) -> Result<TrackSource> {
-        if !Self::is_youtube_url(url) {
-            anyhow::bail!("URL no es de YouTube: {}", url);
-        }
-
-        let video_info = self.extract_video_info(url).await?;
-
-        let track = TrackSource::new(
-            video_info.title,
-            url.to_string(),
-            SourceType::YouTube,
-            UserId::new(1), // placeholder válido
-        );
-
-        let track = if let Some(artist) = video_info.uploader {
-            track.with_artist(artist)
-        } else {
-            track
-        };
-
-        let track = if let Some(duration) = video_info.duration {
-            track.with_duration(duration)
-        } else {
-            track
-        };
-
-        let track = if let Some(thumbnail) = video_info.thumbnail {
-            track.with_thumbnail(thumbnail)
-        } else {
-            track
-        };
-
-        Ok(track)
-    }
-
-    async fn get_playlist(&self, url: &str) -> Result<Vec<TrackSource>> {
+    /// Extrae los tracks de una playlist con yt-dlp (un solo intento)
+    async fn get_playlist_once(&self, url: &str, extra_args: Vec<String>) -> Result<Vec<TrackSource>> {
         let cookies_path = Self::cookies_working_copy();
 
         let pot_arg = pot_extractor_arg();
@@ -384,7 +893,7 @@ impl MusicSource for YtDlpOptimizedClient {
         cmd.args([
             url,
             "--ignore-config",
-            "--print", "%(url)s|%(title)s|%(uploader)s|%(duration)s",
+            "--print", "%(url)s|%(title)s|%(uploader)s|%(duration)s|%(playlist_title)s",
             "--flat-playlist",
             "--lazy-playlist",
             "--socket-timeout", "30",
@@ -395,6 +904,9 @@ impl MusicSource for YtDlpOptimizedClient {
         if let Some(cookies) = cookies_path {
             cmd.args(["--cookies", &cookies]);
         }
+        cmd.args(proxy_args(self.proxy.as_deref()));
+        cmd.args(extra_args);
+        cmd.kill_on_drop(true);
 
         let output = cmd.output().await?;
 
@@ -412,6 +924,24 @@ impl MusicSource for YtDlpOptimizedClient {
         info!("🎵 Playlist extraída con {} tracks", tracks.len());
         Ok(tracks)
     }
+}
+
+#[async_trait]
+impl MusicSource for YtDlpOptimizedClient {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<TrackSource>> {
+        self.with_retry("search", || self.search_once(query, limit)).await
+    }
+
+    async fn get_track(&self, url: &str) -> Result<TrackSource> {
+        self.get_track_authenticated(url).await.map(|(track, _)| track)
+    }
+
+    async fn get_playlist(&self, url: &str) -> Result<Vec<TrackSource>> {
+        let (tracks, _) = self
+            .with_authenticated_retry("playlist", |extra_args| self.get_playlist_once(url, extra_args))
+            .await?;
+        Ok(tracks)
+    }
 
     fn is_valid_url(&self, url: &str) -> bool {
         Self::is_youtube_url(url)
@@ -422,6 +952,66 @@ impl MusicSource for YtDlpOptimizedClient {
     }
 }
 
+impl YtDlpOptimizedClient {
+    /// Hasta `limit` canciones relacionadas con el video de `url`, sacadas de su
+    /// mix de YouTube (`list=RD<id>`) y sin la propia canción.
+    pub async fn get_related(&self, url: &str, limit: usize) -> Result<Vec<TrackSource>> {
+        let video_id = Self::extract_video_id(&Self::normalize_youtube_url(url))?;
+        let mix_url = format!("https://www.youtube.com/watch?v={video_id}&list=RD{video_id}");
+        // El mix empieza por la propia canción; se piden de sobra por si se repite más adelante
+        let items = vec!["--playlist-items".to_string(), format!("2:{}", limit + 2)];
+        let tracks = self
+            .with_retry("related", || self.get_playlist_once(&mix_url, items.clone()))
+            .await?;
+
+        Ok(tracks
+            .into_iter()
+            .filter(|track| Self::extract_video_id(&track.url()).is_ok_and(|id| id != video_id))
+            .take(limit)
+            .collect())
+    }
+
+    /// Como [`MusicSource::get_track`], indicando además si la extracción
+    /// necesitó la sesión del navegador (`YTDLP_COOKIES_FROM_BROWSER`).
+    pub async fn get_track_authenticated(&self, url: &str) -> Result<(TrackSource, bool)> {
+        if !Self::is_youtube_url(url) {
+            anyhow::bail!("URL no es de YouTube: {}", url);
+        }
+
+        let url = &Self::normalize_youtube_url(url);
+        let (video_info, authenticated) = self
+            .with_authenticated_retry("info", |extra_args| self.extract_video_info(url, extra_args))
+            .await?;
+
+        let track = TrackSource::new(
+            video_info.title,
+            url.to_string(),
+            SourceType::YouTube,
+            UserId::new(1), // placeholder válido
+        );
+
+        let track = if let Some(artist) = video_info.uploader {
+            track.with_artist(artist)
+        } else {
+            track
+        };
+
+        let track = if let Some(duration) = video_info.duration {
+            track.with_duration(duration)
+        } else {
+            track
+        };
+
+        let track = if let Some(thumbnail) = video_info.thumbnail {
+            track.with_thumbnail(thumbnail)
+        } else {
+            track
+        };
+
+        Ok((track.with_chapters(video_info.chapters), authenticated))
+    }
+}
+
 /// Información de video extraída
 #[derive(Debug)]
 struct VideoInfo {
@@ -429,6 +1019,7 @@ struct VideoInfo {
     uploader: Option<String>,
     duration: Option<Duration>,
     thumbnail: Option<String>,
+    chapters: Vec<Chapter>,
 }
 
 impl TrackSource {
@@ -438,41 +1029,98 @@ impl TrackSource {
     /// (`loudnorm` + EQ) y entrega WAV/PCM que songbird decodifica. Una sola pasada
     /// de transcode. Encadenar los dos procesos evita el problema de URLs `-g` que
     /// expiran. Devuelve un `Input` vía `ChildContainer`.
-    pub async fn get_ffmpeg_input(&self, filter: &str) -> Result<Input> {
+    ///
+    /// Las URLs directas (archivos, HLS/DASH, radios) las lee ffmpeg sin yt-dlp.
+    /// Con `silence`, se agrega `silencedetect` y el stderr de ffmpeg alimenta el monitor.
+    /// Con `proxy`, yt-dlp descarga a través de él.
+    pub async fn get_ffmpeg_input(
+        &self,
+        filter: &str,
+        silence: Option<Arc<SilenceMonitor>>,
+        proxy: Option<&str>,
+    ) -> Result<Input> {
+        self.ffmpeg_input(filter, silence, None, proxy).await
+    }
+
+    /// Como [`get_ffmpeg_input`](Self::get_ffmpeg_input), pero ffmpeg corta la
+    /// salida a los primeros `length` (`-t`), p. ej. los fragmentos de `/quiz`.
+    pub async fn get_snippet_input(&self, filter: &str, length: Duration, proxy: Option<&str>) -> Result<Input> {
+        self.ffmpeg_input(filter, None, Some(length), proxy).await
+    }
+
+    async fn ffmpeg_input(
+        &self,
+        filter: &str,
+        silence: Option<Arc<SilenceMonitor>>,
+        limit: Option<Duration>,
+        proxy: Option<&str>,
+    ) -> Result<Input> {
         use std::process::{Command, Stdio};
 
-        if !YtDlpOptimizedClient::is_youtube_url(&self.url()) {
+        let is_direct = self.source_type() == SourceType::DirectUrl;
+        if !is_direct && !YtDlpOptimizedClient::is_youtube_url(&self.url()) {
             anyhow::bail!("Solo se soportan URLs de YouTube");
         }
 
+        // ffmpeg lee la URL ya comprobada tras seguir las redirecciones
+        let url = if is_direct {
+            direct::resolve_stream_url(&self.url()).await?.into()
+        } else {
+            self.url()
+        };
+
         let cookies_path = YtDlpOptimizedClient::cookies_working_copy();
 
-        let url = self.url();
-        let filter = filter.to_string();
+        let filter = match silence {
+            Some(_) => with_silence_filter(filter),
+            None => filter.to_string(),
+        };
         let title = self.title();
         let pot_arg = pot_extractor_arg();
+        let limit = limit.map(|limit| limit.as_secs_f32().to_string());
+        let proxy = proxy_args(proxy);
 
         let input = tokio::task::spawn_blocking(move || -> Result<Input> {
+            if is_direct {
+                // ffmpeg lee la URL directamente; reconecta si el stream se corta
+                let mut ffmpeg_cmd = Command::new("ffmpeg");
+                ffmpeg_cmd.args([
+                    "-reconnect", "1",
+                    "-reconnect_streamed", "1",
+                    "-reconnect_delay_max", "5",
+                    "-hide_banner",
+                    "-nostats",
+                    "-protocol_whitelist", direct::FFMPEG_PROTOCOL_WHITELIST,
+                    "-i", &url,
+                    "-af", &filter,
+                    "-ac", "2",
+                    "-ar", "48000",
+                ]);
+                if let Some(ref limit) = limit {
+                    ffmpeg_cmd.args(["-t", limit]);
+                }
+                ffmpeg_cmd.args(["-f", "wav", "pipe:1"]);
+                ffmpeg_cmd
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(if silence.is_some() { Stdio::piped() } else { Stdio::null() });
+
+                let mut ffmpeg = ffmpeg_cmd.spawn()
+                    .map_err(|e| anyhow::anyhow!("no se pudo lanzar ffmpeg: {}", e))?;
+                if let (Some(monitor), Some(stderr)) = (silence, ffmpeg.stderr.take()) {
+                    monitor.watch_stderr(stderr);
+                }
+                return Ok(Input::from(songbird::input::ChildContainer::new(vec![ffmpeg])));
+            }
+
             // 1) yt-dlp streamea el contenedor de mejor audio a stdout
             let mut ytdlp_cmd = Command::new("yt-dlp");
-            ytdlp_cmd.args([
-                "--ignore-config",
-                "-f", "bestaudio[acodec=opus]/bestaudio[ext=webm]/bestaudio/best",
-                "-o", "-",
-                "--no-playlist",
-                "--no-check-certificate",
-                "--geo-bypass",
-                "--force-ipv4",
-                // Acelerar el arranque: no hacer HEAD requests para verificar
-                // formatos (ya elegimos uno concreto con -f).
-                "--no-check-formats",
-                "--extractor-args", &pot_arg,
-                "--quiet",
-            ]);
+            ytdlp_cmd.args(ytdlp_stream_args(&pot_arg));
             if let Some(ref c) = cookies_path {
                 ytdlp_cmd.args(["--cookies", c]);
             }
-            ytdlp_cmd.arg(&url);
+            ytdlp_cmd.args(proxy);
+            ytdlp_cmd.arg(&*url);
             ytdlp_cmd.stdout(Stdio::piped()).stderr(Stdio::null());
 
             let mut ytdlp = ytdlp_cmd.spawn()
@@ -483,20 +1131,27 @@ impl TrackSource {
             // 2) ffmpeg aplica filtros (loudnorm + EQ) y produce WAV a stdout
             let mut ffmpeg_cmd = Command::new("ffmpeg");
             ffmpeg_cmd.args([
+                "-hide_banner",
+                "-nostats",
                 "-i", "pipe:0",
                 "-af", &filter,
                 "-ac", "2",
                 "-ar", "48000",
-                "-f", "wav",
-                "pipe:1",
             ]);
+            if let Some(ref limit) = limit {
+                ffmpeg_cmd.args(["-t", limit]);
+            }
+            ffmpeg_cmd.args(["-f", "wav", "pipe:1"]);
             ffmpeg_cmd
                 .stdin(Stdio::from(ytdlp_stdout))
                 .stdout(Stdio::piped())
-                .stderr(Stdio::null());
+                .stderr(if silence.is_some() { Stdio::piped() } else { Stdio::null() });
 
-            let ffmpeg = ffmpeg_cmd.spawn()
+            let mut ffmpeg = ffmpeg_cmd.spawn()
                 .map_err(|e| anyhow::anyhow!("no se pudo lanzar ffmpeg: {}", e))?;
+            if let (Some(monitor), Some(stderr)) = (silence, ffmpeg.stderr.take()) {
+                monitor.watch_stderr(stderr);
+            }
 
             // ChildContainer lee del último proceso (ffmpeg); mantiene yt-dlp vivo
             // y lo limpia al hacer drop.
@@ -509,10 +1164,83 @@ impl TrackSource {
         Ok(input)
     }
 
+    /// Primera pasada de la normalización: decodifica la pista entera con
+    /// `loudnorm` en modo medición y devuelve su sonoridad.
+    ///
+    /// Misma cadena que [`get_ffmpeg_input`](Self::get_ffmpeg_input), pero
+    /// ffmpeg descarta el audio (`-f null`) y se lee el resumen de su stderr.
+    pub async fn measure_loudness(&self, proxy: Option<&str>) -> Result<LoudnessMeasurement> {
+        use std::process::Stdio;
+        use tokio::process::Command;
+
+        let is_direct = self.source_type() == SourceType::DirectUrl;
+        if !is_direct && !YtDlpOptimizedClient::is_youtube_url(&self.url()) {
+            anyhow::bail!("Solo se soportan URLs de YouTube");
+        }
+        let url = self.url();
+
+        let mut ffmpeg_cmd = Command::new("ffmpeg");
+        ffmpeg_cmd.args(["-hide_banner", "-nostats"]);
+        // Se mantiene vivo mientras ffmpeg lee de él; `kill_on_drop` lo corta al terminar
+        let mut ytdlp = None;
+        if is_direct {
+            let stream_url = direct::resolve_stream_url(&url).await?;
+            ffmpeg_cmd
+                .args(["-protocol_whitelist", direct::FFMPEG_PROTOCOL_WHITELIST, "-i"])
+                .arg(stream_url)
+                .stdin(Stdio::null());
+        } else {
+            let pot_arg = pot_extractor_arg();
+            let mut ytdlp_cmd = Command::new("yt-dlp");
+            ytdlp_cmd.args(ytdlp_stream_args(&pot_arg));
+            if let Some(cookies) = YtDlpOptimizedClient::cookies_working_copy() {
+                ytdlp_cmd.args(["--cookies", &cookies]);
+            }
+            ytdlp_cmd
+                .args(proxy_args(proxy))
+                .arg(&*url)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true);
+
+            let mut child = ytdlp_cmd.spawn()
+                .map_err(|e| anyhow::anyhow!("no se pudo lanzar yt-dlp: {}", e))?;
+            let stdout: Stdio = child.stdout.take()
+                .ok_or_else(|| anyhow::anyhow!("yt-dlp sin stdout"))?
+                .try_into()?;
+            ffmpeg_cmd.args(["-i", "pipe:0"]).stdin(stdout);
+            ytdlp = Some(child);
+        }
+        ffmpeg_cmd
+            .args(["-af", MEASURE_FILTER, "-f", "null", "-"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let ffmpeg = ffmpeg_cmd.spawn()
+            .map_err(|e| anyhow::anyhow!("no se pudo lanzar ffmpeg: {}", e))?;
+        let output = tokio::time::timeout(LOUDNESS_MEASURE_TIMEOUT, ffmpeg.wait_with_output())
+            .await
+            .map_err(|_| anyhow::anyhow!("la medición de sonoridad no terminó a tiempo"))??;
+        drop(ytdlp);
+
+        parse_loudnorm_output(&String::from_utf8_lossy(&output.stderr))
+            .ok_or_else(|| anyhow::anyhow!("ffmpeg no reportó la sonoridad de la pista"))
+    }
+
     /// Método de fallback más simple si el optimizado falla
-    pub async fn get_simple_input(&self) -> Result<Input> {
+    pub async fn get_simple_input(&self, proxy: Option<&str>) -> Result<Input> {
         info!("🔄 Usando método simple de fallback para: {}", self.title());
-        
+
+        // URL directa: songbird la descarga por HTTP sin pasar por yt-dlp
+        if self.source_type() == SourceType::DirectUrl {
+            let stream_url = direct::resolve_stream_url(&self.url()).await?;
+            let client = reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?;
+            return Ok(Input::from(songbird::input::HttpRequest::new(client, stream_url)));
+        }
+
         // Verificar que sea URL de YouTube
         if !YtDlpOptimizedClient::is_youtube_url(&self.url()) {
             anyhow::bail!("Solo se soportan URLs de YouTube");
@@ -529,7 +1257,7 @@ impl TrackSource {
             .build()?;
 
         // Usar configuración mínima y confiable
-        let ytdl = songbird::input::YoutubeDl::new(client, self.url());
+        let ytdl = songbird::input::YoutubeDl::new(client, self.url().to_string()).user_args(proxy_args(proxy));
         let input = Input::from(ytdl);
 
         info!("✅ Input simple creado para: {}", self.title());
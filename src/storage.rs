@@ -1,12 +1,71 @@
 use anyhow::Result;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::fs;
-use tracing::{info, warn, error};
+use tracing::{info, instrument, warn, error};
 use chrono::{DateTime, Utc};
 
+use crate::audio::queue::LoopMode;
+use crate::config::{Config, QueueBackend};
+use crate::sources::{ContentFilter, SearchPreferences, SourceType};
+
+pub mod guild_json;
+pub mod leaderboard;
+pub mod m3u;
+pub mod play_stats;
+pub mod ratings;
+pub mod redis_queue;
+
+use self::redis_queue::RedisQueueStore;
+
+/// Límite inferior del techo de volumen de un servidor (10%)
+pub const MIN_VOLUME_CEILING: f32 = 0.1;
+
+/// Límite superior del techo de volumen de un servidor (200%, el máximo de `/volume`)
+pub const MAX_VOLUME_CEILING: f32 = 2.0;
+
+/// Tope de `max_queue_size` al importar una configuración
+pub const MAX_IMPORTED_QUEUE_SIZE: usize = 10_000;
+
+/// Ganancia máxima (en valor absoluto) de una banda del ecualizador
+pub const MAX_EQ_GAIN_DB: f32 = 12.0;
+
+fn default_max_volume() -> f32 {
+    MAX_VOLUME_CEILING
+}
+
+fn default_skip_confirm_threshold_secs() -> u64 {
+    30
+}
+
+fn default_skip_confirm_long_track_secs() -> u64 {
+    600
+}
+
+fn default_silence_detection() -> bool {
+    true
+}
+
+fn default_silence_skip_secs() -> u64 {
+    10
+}
+
+fn default_max_requests_per_minute() -> u32 {
+    5
+}
+
+fn default_playback_tempo() -> f32 {
+    1.0
+}
+
+fn default_search_source() -> SourceType {
+    SourceType::YouTube
+}
+
 /// Configuración de servidor almacenada en JSON
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -17,6 +76,190 @@ pub struct ServerConfig {
     pub dj_role_id: Option<u64>,
     pub announcement_channel_id: Option<u64>,
     pub auto_leave_empty: bool,
+    #[serde(default)]
+    pub last_voice_channel_id: Option<u64>, // Último canal de voz usado
+    #[serde(default)]
+    pub command_prefix: Option<String>, // Prefijo de comandos de texto (None = desactivado)
+    #[serde(default)]
+    pub search_preferences: SearchPreferences, // Ranking de resultados de búsqueda
+    #[serde(default)]
+    pub active_eq_preset: Option<String>, // Preset de ecualizador activo ("flat", "bass", ...)
+    #[serde(default)]
+    pub custom_eq_bands: Option<[f32; 10]>, // Ganancias (dB) del preset "custom"
+    #[serde(default)]
+    pub url_detect: bool, // Reaccionar a URLs de música en mensajes normales (opt-in)
+    #[serde(default)]
+    pub bot_nickname_pattern: Option<String>, // Apodo del bot mientras suena algo ("🎵 {track}")
+    #[serde(default)]
+    pub embed_color: Option<[u8; 3]>, // Color RGB de los embeds de música (None = paleta por defecto)
+    #[serde(default)]
+    pub content_filter: ContentFilter, // Palabras, canales y fuentes bloqueados (`/settings block`)
+    #[serde(default = "default_max_volume")]
+    pub max_volume: f32, // Techo de `/volume` en el servidor (0.1–2.0, `/settings max-volume`)
+    #[serde(default)]
+    pub dj_bypasses_volume_limit: bool, // El rol de DJ puede superar `max_volume`
+    #[serde(default = "default_skip_confirm_threshold_secs")]
+    pub skip_confirm_threshold_secs: u64, // `/skip` pide confirmación si el tema lleva menos que esto...
+    #[serde(default = "default_skip_confirm_long_track_secs")]
+    pub skip_confirm_long_track_secs: u64, // ...y dura más que esto
+    #[serde(default = "default_silence_detection")]
+    pub silence_detection: bool, // Saltar pistas que se quedan en silencio (streams corruptos)
+    #[serde(default = "default_silence_skip_secs")]
+    pub silence_skip_secs: u64, // Segundos seguidos de silencio antes de saltar
+    #[serde(default = "default_max_requests_per_minute")]
+    pub max_requests_per_minute: u32, // Canciones que cada usuario puede pedir por minuto (0 = sin límite)
+    #[serde(default)]
+    pub require_same_channel: bool, // Controlar la reproducción exige estar en el canal de voz del bot
+    #[serde(default = "default_playback_tempo")]
+    pub playback_tempo: f32, // Velocidad de reproducción sin cambiar el tono (`/speed`, 0.5–2.0)
+    #[serde(default)]
+    pub limiter: bool, // Limitador suave cuando el volumen supera 150% (`/settings limiter`)
+    #[serde(default)]
+    pub single_now_playing: bool, // Un solo "reproduciendo ahora" con botones: se borra el anterior
+    #[serde(default)]
+    pub ytdlp_proxy_override: Option<String>, // Proxy de yt-dlp del servidor; gana sobre `YTDLP_PROXY`
+    #[serde(default = "default_search_source")]
+    pub default_search_source: SourceType, // Fuente de las búsquedas por texto de `/play`, `/search`... (con búsqueda)
+}
+
+impl ServerConfig {
+    /// Ajusta `max_volume` a su rango y baja `default_volume` si lo supera.
+    /// Devuelve `true` si hubo que corregir algo.
+    pub fn normalize_volume_limits(&mut self) -> bool {
+        let max_volume = self.max_volume.clamp(MIN_VOLUME_CEILING, MAX_VOLUME_CEILING);
+        let default_volume = self.default_volume.min(max_volume);
+        let changed = max_volume != self.max_volume || default_volume != self.default_volume;
+        self.max_volume = max_volume;
+        self.default_volume = default_volume;
+        changed
+    }
+
+    /// Corrige una configuración importada con `/import-config` para la guild
+    /// `guild_id`: fuerza su id y lleva cada campo a un valor válido.
+    /// Devuelve una nota por cada corrección aplicada.
+    pub fn sanitize_import(&mut self, guild_id: u64) -> Vec<String> {
+        let mut notes = Vec::new();
+
+        if self.guild_id != guild_id {
+            notes.push(format!("`guild_id` {} reemplazado por el de este servidor", self.guild_id));
+            self.guild_id = guild_id;
+        }
+
+        if !self.default_volume.is_finite() || self.default_volume < 0.0 {
+            notes.push("`default_volume` inválido, se usa 0%".to_string());
+            self.default_volume = 0.0;
+        }
+        if !self.max_volume.is_finite() {
+            self.max_volume = default_max_volume();
+        }
+        if self.normalize_volume_limits() {
+            notes.push(format!(
+                "Volumen ajustado: máximo {:.0}%, por defecto {:.0}%",
+                self.max_volume * 100.0,
+                self.default_volume * 100.0
+            ));
+        }
+
+        let max_queue_size = self.max_queue_size.clamp(1, MAX_IMPORTED_QUEUE_SIZE);
+        if max_queue_size != self.max_queue_size {
+            notes.push(format!("`max_queue_size` ajustado a {}", max_queue_size));
+            self.max_queue_size = max_queue_size;
+        }
+
+        if self.silence_skip_secs == 0 {
+            notes.push(format!("`silence_skip_secs` ajustado a {}", default_silence_skip_secs()));
+            self.silence_skip_secs = default_silence_skip_secs();
+        }
+
+        let preferences = &mut self.search_preferences;
+        if preferences.min_duration_secs > preferences.max_duration_secs {
+            notes.push("Duraciones de `search_preferences` invertidas, se intercambiaron".to_string());
+            std::mem::swap(&mut preferences.min_duration_secs, &mut preferences.max_duration_secs);
+        }
+
+        let tempo = if self.playback_tempo.is_finite() {
+            self.playback_tempo.clamp(crate::audio::effects::MIN_TEMPO, crate::audio::effects::MAX_TEMPO)
+        } else {
+            default_playback_tempo()
+        };
+        if tempo != self.playback_tempo {
+            notes.push(format!("`playback_tempo` ajustado a {:.2}x", tempo));
+            self.playback_tempo = tempo;
+        }
+
+        if !self.default_search_source.supports_text_search() {
+            notes.push(format!(
+                "La fuente `{}` no admite búsquedas por texto, se usa `{}`",
+                self.default_search_source,
+                default_search_source()
+            ));
+            self.default_search_source = default_search_source();
+        }
+
+        // Solo el dueño del bot configura proxies, y pueden llevar credenciales
+        if self.ytdlp_proxy_override.take().is_some() {
+            notes.push("Proxy de yt-dlp de otro servidor, se quitó".to_string());
+        }
+
+        if let Some(prefix) = &self.command_prefix {
+            let len = prefix.chars().count();
+            if !(1..=crate::bot::prefix::MAX_PREFIX_LEN).contains(&len)
+                || prefix.contains(char::is_whitespace)
+                || prefix.starts_with('/')
+            {
+                notes.push(format!("Prefijo `{}` inválido, comandos de texto desactivados", prefix));
+                self.command_prefix = None;
+            }
+        }
+
+        if let Some(pattern) = &self.bot_nickname_pattern {
+            if pattern.trim().is_empty() {
+                notes.push("Patrón de apodo vacío, se usa el nombre por defecto".to_string());
+                self.bot_nickname_pattern = None;
+            } else if pattern.chars().count() > crate::bot::nickname::MAX_NICKNAME_LEN {
+                notes.push("Patrón de apodo recortado".to_string());
+                self.bot_nickname_pattern =
+                    Some(pattern.chars().take(crate::bot::nickname::MAX_NICKNAME_LEN).collect());
+            }
+        }
+
+        if let Some(preset) = &self.active_eq_preset {
+            if crate::audio::effects::EqualizerPreset::from_name(preset).is_none() {
+                notes.push(format!("Preset de ecualizador `{}` desconocido, se usa `flat`", preset));
+                self.active_eq_preset = Some("flat".to_string());
+            }
+        }
+
+        if let Some(bands) = &mut self.custom_eq_bands {
+            let mut clamped = false;
+            for gain in bands.iter_mut() {
+                let valid = if gain.is_finite() { gain.clamp(-MAX_EQ_GAIN_DB, MAX_EQ_GAIN_DB) } else { 0.0 };
+                if valid != *gain {
+                    *gain = valid;
+                    clamped = true;
+                }
+            }
+            if clamped {
+                notes.push(format!("Bandas del ecualizador ajustadas a ±{} dB", MAX_EQ_GAIN_DB));
+            }
+        }
+
+        notes
+    }
+}
+
+/// Vista ligera de [`ServerConfig`] con lo que necesitan los embeds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GuildSettings {
+    pub embed_color: Option<[u8; 3]>,
+}
+
+impl From<&ServerConfig> for GuildSettings {
+    fn from(config: &ServerConfig) -> Self {
+        Self {
+            embed_color: config.embed_color,
+        }
+    }
 }
 
 impl Default for ServerConfig {
@@ -29,6 +272,28 @@ impl Default for ServerConfig {
             dj_role_id: None,
             announcement_channel_id: None,
             auto_leave_empty: true,
+            last_voice_channel_id: None,
+            command_prefix: None,
+            search_preferences: SearchPreferences::default(),
+            active_eq_preset: Some("flat".to_string()),
+            custom_eq_bands: None,
+            url_detect: false,
+            bot_nickname_pattern: None,
+            embed_color: None,
+            content_filter: ContentFilter::default(),
+            max_volume: MAX_VOLUME_CEILING,
+            dj_bypasses_volume_limit: false,
+            skip_confirm_threshold_secs: default_skip_confirm_threshold_secs(),
+            skip_confirm_long_track_secs: default_skip_confirm_long_track_secs(),
+            silence_detection: default_silence_detection(),
+            silence_skip_secs: default_silence_skip_secs(),
+            max_requests_per_minute: default_max_requests_per_minute(),
+            require_same_channel: false,
+            playback_tempo: default_playback_tempo(),
+            limiter: false,
+            single_now_playing: false,
+            ytdlp_proxy_override: None,
+            default_search_source: default_search_source(),
         }
     }
 }
@@ -48,6 +313,44 @@ pub struct UserPlaylist {
     pub is_favorite: bool,
     pub play_count: u32,
     pub tags: Vec<String>,
+    /// Usuarios que pueden agregar y quitar canciones además del dueño
+    #[serde(default)]
+    pub editors: Vec<u64>,
+    /// Últimos cambios de la playlist (como mucho [`MAX_PLAYLIST_EDIT_LOG`])
+    #[serde(default)]
+    pub edit_log: VecDeque<EditEvent>,
+}
+
+/// Cambios que se guardan en el historial de una playlist
+pub const MAX_PLAYLIST_EDIT_LOG: usize = 20;
+
+/// Cambio en una playlist colaborativa
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditEvent {
+    pub user_id: u64,
+    pub action: EditAction,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl EditEvent {
+    /// `true` si el cambio lo hizo `user_id` o lo nombra (alta o baja de editor)
+    pub fn involves(&self, user_id: u64) -> bool {
+        self.user_id == user_id
+            || matches!(
+                self.action,
+                EditAction::AddEditor { user_id: editor } | EditAction::RemoveEditor { user_id: editor } if editor == user_id
+            )
+    }
+}
+
+/// Qué se cambió en una playlist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EditAction {
+    AddTracks { count: usize },
+    RemoveTrack { title: String },
+    AddEditor { user_id: u64 },
+    RemoveEditor { user_id: u64 },
 }
 
 /// Canción dentro de una playlist personal
@@ -60,7 +363,7 @@ pub struct PlaylistTrack {
     pub thumbnail: Option<String>,
     pub added_by: u64,
     pub added_at: DateTime<Utc>,
-    pub source_type: String, // "YouTube", "Spotify", etc.
+    pub source_type: String, // `SourceType::as_str()`: "youtube", "tidal", "direct"
 }
 
 /// Historial de playlists cargadas
@@ -85,6 +388,164 @@ pub struct PlaylistHistoryEntry {
     pub source: String, // "YouTube", "Personal", etc.
 }
 
+/// Estado de la cola de una guild, persistido durante el apagado graceful
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGuildQueue {
+    pub guild_id: u64,
+    pub voice_channel_id: Option<u64>,
+    pub current: Option<PlaylistTrack>,
+    pub tracks: Vec<PlaylistTrack>,
+    pub resume_at: Option<Duration>, // Posición de la pista actual al apagar
+    #[serde(default, deserialize_with = "lenient_loop_mode")]
+    pub loop_mode: LoopMode,
+    pub shuffle: bool,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Lee el modo de repetición de una cola guardada; los valores que ya no se
+/// reconocen (formatos antiguos) se restauran sin repetición.
+fn lenient_loop_mode<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<LoopMode, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+/// Almacenamiento de las colas guardadas de cada guild.
+///
+/// [`JsonStorage`] las guarda en `DATA_DIR/queues`; [`RedisQueueStore`] las
+/// comparte entre procesos cuando el bot corre en varios shards.
+#[async_trait]
+pub trait QueueStorage: Send + Sync {
+    async fn load_queue(&self, guild_id: u64) -> Result<Option<SavedGuildQueue>>;
+    async fn save_queue(&self, guild_id: u64, queue: &SavedGuildQueue) -> Result<()>;
+    async fn delete_queue(&self, guild_id: u64) -> Result<()>;
+}
+
+/// Permite usar el storage compartido del bot (`Arc<Mutex<JsonStorage>>`) como [`QueueStorage`]
+#[async_trait]
+impl<S: QueueStorage> QueueStorage for tokio::sync::Mutex<S> {
+    async fn load_queue(&self, guild_id: u64) -> Result<Option<SavedGuildQueue>> {
+        self.lock().await.load_queue(guild_id).await
+    }
+
+    async fn save_queue(&self, guild_id: u64, queue: &SavedGuildQueue) -> Result<()> {
+        self.lock().await.save_queue(guild_id, queue).await
+    }
+
+    async fn delete_queue(&self, guild_id: u64) -> Result<()> {
+        self.lock().await.delete_queue(guild_id).await
+    }
+}
+
+/// Crea el almacenamiento de colas elegido en `QUEUE_BACKEND`
+pub fn open_queue_store(config: &Config, storage: Arc<tokio::sync::Mutex<JsonStorage>>) -> Result<Arc<dyn QueueStorage>> {
+    match &config.queue_backend {
+        QueueBackend::Json => Ok(storage),
+        QueueBackend::Redis(url) => {
+            info!("🗄️ Colas en Redis (pool de {} conexiones)", config.redis_pool_size);
+            Ok(Arc::new(RedisQueueStore::new(url, config.redis_pool_size)?))
+        }
+    }
+}
+
+/// Preferencias personales de un usuario, comunes a todas las guilds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserPreferences {
+    pub user_id: u64,
+    /// Avisar por DM cuando empieza a sonar una canción que pidió (`/notify`)
+    #[serde(default)]
+    pub notify_on_play: bool,
+}
+
+/// Datos de un usuario en una guild, tal como los entrega `/my-data export`.
+///
+/// [`JsonStorage::export_user_data`] llena lo que guarda el almacenamiento;
+/// las valoraciones, los votos y las estadísticas viven en sus propios stores
+/// y los agrega `OpenMusicBot::export_user_data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDataExport {
+    pub user_id: u64,
+    pub guild_id: u64,
+    pub exported_at: DateTime<Utc>,
+    pub preferences: UserPreferences,
+    pub playlists: Vec<UserPlaylist>,
+    pub playlist_history: Option<PlaylistHistory>,
+    /// Playlists ajenas de las que es editor o en las que hizo cambios
+    #[serde(default)]
+    pub collaborations: Vec<PlaylistCollaboration>,
+    #[serde(default)]
+    pub ratings: Vec<ratings::UserRating>,
+    #[serde(default)]
+    pub votes: Vec<UserVote>,
+    /// Canciones que pidió, según `/stats guild`
+    #[serde(default)]
+    pub play_stats: Option<play_stats::UserPlays>,
+}
+
+/// Participación de un usuario en una playlist de otro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistCollaboration {
+    pub playlist_id: String,
+    pub name: String,
+    pub is_editor: bool,
+    /// Cambios suyos (o sobre él) que siguen en el historial
+    pub edits: Vec<EditEvent>,
+}
+
+/// Canción que votó un usuario (no se guarda si fue 👍 o 👎)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserVote {
+    pub url: String,
+    pub title: String,
+}
+
+/// Registros borrados por `/my-data delete`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeletionReport {
+    pub playlists: usize,
+    pub history: usize,
+    pub preferences: usize,
+    /// Playlists ajenas de las que se quitó como editor o de su historial
+    pub collaborations: usize,
+    pub ratings: usize,
+    pub votes: usize,
+    pub play_stats: usize,
+}
+
+impl DeletionReport {
+    pub fn total(&self) -> usize {
+        self.playlists
+            + self.history
+            + self.preferences
+            + self.collaborations
+            + self.ratings
+            + self.votes
+            + self.play_stats
+    }
+}
+
+/// Votos persistidos de una guild
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuildVotes {
+    pub guild_id: u64,
+    pub tracks: HashMap<String, TrackVotes>, // URL → votos
+}
+
+/// Votos de un track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackVotes {
+    pub title: String,
+    pub upvotes: i32,
+    pub downvotes: i32,
+    pub voters: Vec<u64>, // Usuarios que ya votaron
+}
+
+impl TrackVotes {
+    /// Puntuación neta del track
+    pub fn score(&self) -> i32 {
+        self.upvotes - self.downvotes
+    }
+}
+
 impl UserPlaylist {
     #[allow(dead_code)]
     pub fn new(name: String, owner_id: u64, guild_id: u64) -> Self {
@@ -104,6 +565,51 @@ impl UserPlaylist {
             is_favorite: false,
             play_count: 0,
             tags: Vec::new(),
+            editors: Vec::new(),
+            edit_log: VecDeque::new(),
+        }
+    }
+
+    /// El dueño y los editores pueden agregar y quitar canciones
+    pub fn can_edit(&self, user_id: u64) -> bool {
+        self.owner_id == user_id || self.editors.contains(&user_id)
+    }
+
+    /// `true` si alguien además del dueño puede editarla
+    pub fn is_collaborative(&self) -> bool {
+        !self.editors.is_empty()
+    }
+
+    /// Agrega un editor. `false` si ya lo era o es el dueño.
+    pub fn add_editor(&mut self, user_id: u64) -> bool {
+        if self.can_edit(user_id) {
+            return false;
+        }
+        self.editors.push(user_id);
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// Quita un editor. `false` si no lo era.
+    pub fn remove_editor(&mut self, user_id: u64) -> bool {
+        let before = self.editors.len();
+        self.editors.retain(|editor| *editor != user_id);
+        if self.editors.len() == before {
+            return false;
+        }
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// Anota un cambio en el historial, descartando los más viejos
+    pub fn record_edit(&mut self, user_id: u64, action: EditAction) {
+        self.edit_log.push_back(EditEvent {
+            user_id,
+            action,
+            timestamp: Utc::now(),
+        });
+        while self.edit_log.len() > MAX_PLAYLIST_EDIT_LOG {
+            self.edit_log.pop_front();
         }
     }
     
@@ -142,16 +648,36 @@ impl PlaylistTrack {
     #[allow(dead_code)]
     pub fn from_track_source(track: &crate::sources::TrackSource, added_by: u64) -> Self {
         Self {
-            title: track.title(),
-            artist: track.artist(),
-            url: track.url(),
+            title: track.title().to_string(),
+            artist: track.artist().map(|artist| artist.to_string()),
+            url: track.url().to_string(),
             duration: track.duration(),
-            thumbnail: track.thumbnail(),
+            thumbnail: track.thumbnail().map(|thumbnail| thumbnail.to_string()),
             added_by,
             added_at: chrono::Utc::now(),
-            source_type: format!("{:?}", track.source_type()),
+            source_type: track.source_type().as_str().to_string(),
         }
     }
+
+    /// Reconstruye un `TrackSource` reproducible a partir de la canción guardada
+    pub fn to_track_source(&self, requested_by: serenity::model::id::UserId) -> crate::sources::TrackSource {
+        use crate::sources::TrackSource;
+
+        // Fuentes desconocidas (p. ej. de versiones viejas) se reproducen como YouTube
+        let source_type = self.source_type.parse().unwrap_or(SourceType::YouTube);
+
+        let mut track = TrackSource::new(self.title.clone(), self.url.clone(), source_type, requested_by);
+        if let Some(artist) = &self.artist {
+            track = track.with_artist(artist.clone());
+        }
+        if let Some(duration) = self.duration {
+            track = track.with_duration(duration);
+        }
+        if let Some(thumbnail) = &self.thumbnail {
+            track = track.with_thumbnail(thumbnail.clone());
+        }
+        track
+    }
 }
 
 /// Manager de almacenamiento basado en archivos JSON
@@ -162,6 +688,8 @@ pub struct JsonStorage {
     playlists_cache: HashMap<String, UserPlaylist>,
     #[allow(dead_code)]
     history_cache: HashMap<(u64, u64), PlaylistHistory>, // (user_id, guild_id)
+    command_hashes: HashMap<String, u64>, // Hash del último registro de comandos ("global", "guild_<id>")
+    prefs_cache: HashMap<u64, UserPreferences>,
 }
 
 impl JsonStorage {
@@ -178,6 +706,15 @@ impl JsonStorage {
         let history_dir = data_dir.join("history");
         fs::create_dir_all(&history_dir).await?;
         
+        let queues_dir = data_dir.join("queues");
+        fs::create_dir_all(&queues_dir).await?;
+        
+        let votes_dir = data_dir.join("votes");
+        fs::create_dir_all(&votes_dir).await?;
+        
+        let prefs_dir = data_dir.join("prefs");
+        fs::create_dir_all(&prefs_dir).await?;
+        
         info!("📁 Storage inicializado en: {}", data_dir.display());
         
         let mut storage = Self {
@@ -185,10 +722,14 @@ impl JsonStorage {
             servers_cache: HashMap::new(),
             playlists_cache: HashMap::new(),
             history_cache: HashMap::new(),
+            command_hashes: HashMap::new(),
+            prefs_cache: HashMap::new(),
         };
         
-        // Cargar configuraciones existentes
+        // Cargar configuraciones y playlists existentes
         storage.load_all_servers().await?;
+        storage.load_all_playlists().await?;
+        storage.load_command_hashes().await;
         
         Ok(storage)
     }
@@ -268,6 +809,16 @@ impl JsonStorage {
         self.update_server_config(config).await
     }
     
+    /// Registra el último canal de voz al que se conectó el bot
+    pub async fn set_last_voice_channel(&mut self, guild_id: u64, channel_id: u64) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        if config.last_voice_channel_id == Some(channel_id) {
+            return Ok(());
+        }
+        config.last_voice_channel_id = Some(channel_id);
+        self.update_server_config(config).await
+    }
+    
     /// Lista todas las configuraciones de servidores
     #[allow(dead_code)]
     pub fn list_servers(&self) -> Vec<u64> {
@@ -278,7 +829,239 @@ impl JsonStorage {
     pub fn get_dj_role(&self, guild_id: u64) -> Option<u64> {
         self.servers_cache.get(&guild_id).and_then(|c| c.dj_role_id)
     }
+
+    /// Obtiene el prefijo de comandos de texto de un servidor (sin async)
+    pub fn get_command_prefix(&self, guild_id: u64) -> Option<String> {
+        self.servers_cache.get(&guild_id).and_then(|c| c.command_prefix.clone())
+    }
+
+    /// Actualiza el prefijo de comandos de texto (`None` desactiva los comandos de texto)
+    pub async fn set_command_prefix(&mut self, guild_id: u64, prefix: Option<String>) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.command_prefix = prefix;
+        self.update_server_config(config).await
+    }
+
+    /// Obtiene las preferencias de ranking de búsqueda de un servidor (sin async)
+    pub fn get_search_preferences(&self, guild_id: u64) -> SearchPreferences {
+        self.servers_cache.get(&guild_id)
+            .map(|c| c.search_preferences.clone())
+            .unwrap_or_default()
+    }
+
+    /// Actualiza las preferencias de ranking de búsqueda de un servidor
+    pub async fn set_search_preferences(&mut self, guild_id: u64, prefs: SearchPreferences) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.search_preferences = prefs;
+        self.update_server_config(config).await
+    }
     
+    /// Indica si el servidor activó la detección de URLs en mensajes (sin async)
+    pub fn get_url_detect(&self, guild_id: u64) -> bool {
+        self.servers_cache.get(&guild_id).map(|c| c.url_detect).unwrap_or(false)
+    }
+
+    /// Activa o desactiva la detección de URLs en mensajes de un servidor
+    pub async fn set_url_detect(&mut self, guild_id: u64, enabled: bool) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.url_detect = enabled;
+        self.update_server_config(config).await
+    }
+
+    /// Indica si controlar la reproducción exige estar en el canal de voz del bot (sin async)
+    pub fn get_require_same_channel(&self, guild_id: u64) -> bool {
+        self.servers_cache.get(&guild_id).map(|c| c.require_same_channel).unwrap_or(false)
+    }
+
+    /// Activa o desactiva la exigencia de estar en el canal de voz del bot
+    pub async fn set_require_same_channel(&mut self, guild_id: u64, enabled: bool) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.require_same_channel = enabled;
+        self.update_server_config(config).await
+    }
+
+    /// Indica si el servidor tiene activo el limitador de volumen (sin async)
+    pub fn get_limiter(&self, guild_id: u64) -> bool {
+        self.servers_cache.get(&guild_id).map(|c| c.limiter).unwrap_or(false)
+    }
+
+    /// Activa o desactiva el limitador para volúmenes amplificados
+    pub async fn set_limiter(&mut self, guild_id: u64, enabled: bool) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.limiter = enabled;
+        self.update_server_config(config).await
+    }
+
+    /// Fuente de las búsquedas por texto del servidor (sin async)
+    pub fn get_default_search_source(&self, guild_id: u64) -> SourceType {
+        self.servers_cache
+            .get(&guild_id)
+            .map(|c| c.default_search_source)
+            .unwrap_or_else(default_search_source)
+    }
+
+    /// Cambia la fuente de las búsquedas por texto; debe admitir búsquedas
+    pub async fn set_default_search_source(&mut self, guild_id: u64, source: SourceType) -> Result<()> {
+        if !source.supports_text_search() {
+            anyhow::bail!("La fuente `{}` no admite búsquedas por texto", source);
+        }
+        let mut config = self.get_server_config(guild_id).await?;
+        config.default_search_source = source;
+        self.update_server_config(config).await
+    }
+
+    /// Indica si el servidor mantiene un único "reproduciendo ahora" activo (sin async)
+    pub fn get_single_now_playing(&self, guild_id: u64) -> bool {
+        self.servers_cache.get(&guild_id).map(|c| c.single_now_playing).unwrap_or(false)
+    }
+
+    /// Activa o desactiva el borrado del "reproduciendo ahora" anterior al publicar uno nuevo
+    pub async fn set_single_now_playing(&mut self, guild_id: u64, enabled: bool) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.single_now_playing = enabled;
+        self.update_server_config(config).await
+    }
+
+    /// Proxy de yt-dlp propio del servidor, si sobrescribe el global
+    pub async fn get_ytdlp_proxy_override(&mut self, guild_id: u64) -> Result<Option<String>> {
+        Ok(self.get_server_config(guild_id).await?.ytdlp_proxy_override)
+    }
+
+    /// Define (o con `None`, quita) el proxy de yt-dlp propio del servidor
+    pub async fn set_ytdlp_proxy_override(&mut self, guild_id: u64, proxy: Option<String>) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.ytdlp_proxy_override = proxy;
+        self.update_server_config(config).await
+    }
+
+    /// Obtiene el patrón de apodo del bot de un servidor (sin async)
+    pub fn get_bot_nickname_pattern(&self, guild_id: u64) -> Option<String> {
+        self.servers_cache.get(&guild_id).and_then(|c| c.bot_nickname_pattern.clone())
+    }
+
+    /// Actualiza el patrón de apodo del bot (`None` mantiene el nombre por defecto)
+    pub async fn set_bot_nickname_pattern(&mut self, guild_id: u64, pattern: Option<String>) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.bot_nickname_pattern = pattern;
+        self.update_server_config(config).await
+    }
+
+    /// Obtiene la vista de configuración usada por los embeds (sin async)
+    pub fn get_guild_settings(&self, guild_id: u64) -> GuildSettings {
+        self.servers_cache
+            .get(&guild_id)
+            .map(GuildSettings::from)
+            .unwrap_or_default()
+    }
+
+    /// Actualiza el color de los embeds (`None` vuelve a la paleta por defecto)
+    pub async fn set_embed_color(&mut self, guild_id: u64, color: Option<[u8; 3]>) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.embed_color = color;
+        self.update_server_config(config).await
+    }
+
+    /// Obtiene el filtro de contenido de un servidor (sin async)
+    pub fn get_content_filter(&self, guild_id: u64) -> ContentFilter {
+        self.servers_cache.get(&guild_id)
+            .map(|c| c.content_filter.clone())
+            .unwrap_or_default()
+    }
+
+    /// Actualiza el filtro de contenido de un servidor
+    pub async fn set_content_filter(&mut self, guild_id: u64, filter: ContentFilter) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.content_filter = filter;
+        self.update_server_config(config).await
+    }
+
+    /// Obtiene el techo de volumen y si el rol de DJ puede superarlo (sin async)
+    pub fn get_volume_limit(&self, guild_id: u64) -> (f32, bool) {
+        self.servers_cache.get(&guild_id)
+            .map(|c| (c.max_volume, c.dj_bypasses_volume_limit))
+            .unwrap_or((MAX_VOLUME_CEILING, false))
+    }
+
+    /// Actualiza el techo de volumen (se ajusta a 0.1–2.0) y la excepción para DJs
+    pub async fn set_volume_limit(&mut self, guild_id: u64, max_volume: f32, dj_bypass: bool) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.max_volume = max_volume;
+        config.dj_bypasses_volume_limit = dj_bypass;
+        config.normalize_volume_limits();
+        self.update_server_config(config).await
+    }
+
+    /// Canciones que cada usuario puede pedir por minuto (`0` = sin límite, sin async)
+    pub fn get_max_requests_per_minute(&self, guild_id: u64) -> u32 {
+        self.servers_cache.get(&guild_id)
+            .map(|c| c.max_requests_per_minute)
+            .unwrap_or_else(default_max_requests_per_minute)
+    }
+
+    /// Umbrales de confirmación de `/skip`: `(posición máxima, duración mínima)` en segundos (sin async)
+    pub fn get_skip_confirm_thresholds(&self, guild_id: u64) -> (u64, u64) {
+        self.servers_cache.get(&guild_id)
+            .map(|c| (c.skip_confirm_threshold_secs, c.skip_confirm_long_track_secs))
+            .unwrap_or((default_skip_confirm_threshold_secs(), default_skip_confirm_long_track_secs()))
+    }
+
+    /// Silencio tolerado antes de saltar la pista, o `None` si la detección está
+    /// desactivada en el servidor (sin async)
+    pub fn get_silence_skip(&self, guild_id: u64) -> Option<Duration> {
+        let (enabled, secs) = self.servers_cache.get(&guild_id)
+            .map(|c| (c.silence_detection, c.silence_skip_secs))
+            .unwrap_or((default_silence_detection(), default_silence_skip_secs()));
+        enabled.then(|| Duration::from_secs(secs))
+    }
+
+    /// Obtiene el preset de ecualizador persistido y sus bandas personalizadas (sin async)
+    pub fn get_eq_settings(&self, guild_id: u64) -> (Option<String>, Option<[f32; 10]>) {
+        self.servers_cache.get(&guild_id)
+            .map(|c| (c.active_eq_preset.clone(), c.custom_eq_bands))
+            .unwrap_or_default()
+    }
+
+    /// Persiste el preset de ecualizador activo de un servidor
+    pub async fn set_eq_preset(&mut self, guild_id: u64, preset: &str) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        if config.active_eq_preset.as_deref() == Some(preset) {
+            return Ok(());
+        }
+        config.active_eq_preset = Some(preset.to_string());
+        self.update_server_config(config).await
+    }
+
+    /// Persiste las bandas del ecualizador personalizado y lo deja como preset activo
+    pub async fn set_custom_eq_bands(&mut self, guild_id: u64, bands: [f32; 10]) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.custom_eq_bands = Some(bands);
+        config.active_eq_preset = Some(crate::audio::effects::EqualizerPreset::Custom.name().to_string());
+        self.update_server_config(config).await
+    }
+
+    /// Restablece los efectos persistidos (preset plano, sin bandas personalizadas, velocidad normal)
+    pub async fn reset_effects_settings(&mut self, guild_id: u64) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.active_eq_preset = Some("flat".to_string());
+        config.custom_eq_bands = None;
+        config.playback_tempo = default_playback_tempo();
+        self.update_server_config(config).await
+    }
+
+    /// Velocidad de reproducción persistida de un servidor (sin async)
+    pub fn get_playback_tempo(&self, guild_id: u64) -> f32 {
+        self.servers_cache.get(&guild_id)
+            .map(|c| c.playback_tempo)
+            .unwrap_or_else(default_playback_tempo)
+    }
+
+    /// Persiste la velocidad de reproducción de un servidor (`/speed`)
+    pub async fn set_playback_tempo(&mut self, guild_id: u64, tempo: f32) -> Result<()> {
+        let mut config = self.get_server_config(guild_id).await?;
+        config.playback_tempo = tempo;
+        self.update_server_config(config).await
+    }
+
     /// Obtiene el timeout de auto-leave de un servidor (sin async)
     pub fn get_auto_leave_timeout(&self, guild_id: u64) -> u64 {
         self.servers_cache.get(&guild_id)
@@ -320,6 +1103,7 @@ impl JsonStorage {
     
     // Métodos privados
     
+    #[instrument(level = "debug", name = "storage.load_server_config", skip(self))]
     async fn load_server_config(&self, guild_id: u64) -> Result<ServerConfig> {
         let file_path = self.get_server_file_path(guild_id);
         let content = fs::read_to_string(&file_path).await?;
@@ -327,7 +1111,7 @@ impl JsonStorage {
         Ok(config)
     }
     
-    #[allow(dead_code)]
+    #[instrument(level = "debug", name = "storage.save_server_config", skip_all, fields(guild_id = config.guild_id))]
     async fn save_server_config(&self, config: &ServerConfig) -> Result<()> {
         let file_path = self.get_server_file_path(config.guild_id);
         let content = serde_json::to_string_pretty(config)?;
@@ -353,7 +1137,26 @@ impl JsonStorage {
                     if let Some(guild_id_str) = file_name.strip_prefix("guild_") {
                         if let Ok(guild_id) = guild_id_str.parse::<u64>() {
                             match self.load_server_config(guild_id).await {
-                                Ok(config) => {
+                                Ok(mut config) => {
+                                    // Configs anteriores al ecualizador persistido: migrar a Flat
+                                    if config.active_eq_preset.is_none() {
+                                        config.active_eq_preset = Some("flat".to_string());
+                                        if let Err(e) = self.save_server_config(&config).await {
+                                            warn!("Error migrando configuración de guild {}: {}", guild_id, e);
+                                        }
+                                    }
+                                    // El volumen por defecto nunca puede superar el techo del servidor
+                                    if config.normalize_volume_limits() {
+                                        warn!(
+                                            "🔊 Límites de volumen corregidos en guild {} (techo {:.0}%, por defecto {:.0}%)",
+                                            guild_id,
+                                            config.max_volume * 100.0,
+                                            config.default_volume * 100.0
+                                        );
+                                        if let Err(e) = self.save_server_config(&config).await {
+                                            warn!("Error guardando configuración de guild {}: {}", guild_id, e);
+                                        }
+                                    }
                                     self.servers_cache.insert(guild_id, config);
                                     loaded_count += 1;
                                 }
@@ -419,6 +1222,28 @@ impl JsonStorage {
             dj_role_id: None,
             announcement_channel_id: None,
             auto_leave_empty: true,
+            last_voice_channel_id: None,
+            command_prefix: None,
+            search_preferences: SearchPreferences::default(),
+            active_eq_preset: Some("flat".to_string()),
+            custom_eq_bands: None,
+            url_detect: false,
+            bot_nickname_pattern: None,
+            embed_color: None,
+            content_filter: ContentFilter::default(),
+            max_volume: MAX_VOLUME_CEILING,
+            dj_bypasses_volume_limit: false,
+            skip_confirm_threshold_secs: default_skip_confirm_threshold_secs(),
+            skip_confirm_long_track_secs: default_skip_confirm_long_track_secs(),
+            silence_detection: default_silence_detection(),
+            silence_skip_secs: default_silence_skip_secs(),
+            max_requests_per_minute: default_max_requests_per_minute(),
+            require_same_channel: false,
+            playback_tempo: default_playback_tempo(),
+            limiter: false,
+            single_now_playing: false,
+            ytdlp_proxy_override: None,
+            default_search_source: default_search_source(),
         };
         
         self.update_server_config(config).await?;
@@ -541,11 +1366,29 @@ impl JsonStorage {
     /// Añade una canción a una playlist
     pub async fn add_track_to_playlist(&mut self, playlist_id: &str, track: PlaylistTrack, user_id: u64) -> Result<bool> {
         if let Some(mut playlist) = self.get_playlist(playlist_id).await? {
-            if playlist.owner_id != user_id {
+            if !playlist.can_edit(user_id) {
                 return Ok(false); // No autorizado
             }
             
             playlist.add_track(track);
+            playlist.record_edit(user_id, EditAction::AddTracks { count: 1 });
+            self.update_playlist(playlist).await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+    
+    /// Añade varias canciones a una playlist guardándola una sola vez
+    pub async fn add_tracks_to_playlist(&mut self, playlist_id: &str, tracks: Vec<PlaylistTrack>, user_id: u64) -> Result<bool> {
+        if let Some(mut playlist) = self.get_playlist(playlist_id).await? {
+            if !playlist.can_edit(user_id) {
+                return Ok(false); // No autorizado
+            }
+            
+            playlist.record_edit(user_id, EditAction::AddTracks { count: tracks.len() });
+            playlist.tracks.extend(tracks);
+            playlist.updated_at = Utc::now();
             self.update_playlist(playlist).await?;
             Ok(true)
         } else {
@@ -557,11 +1400,12 @@ impl JsonStorage {
     /// Remueve una canción de una playlist
     pub async fn remove_track_from_playlist(&mut self, playlist_id: &str, track_index: usize, user_id: u64) -> Result<Option<PlaylistTrack>> {
         if let Some(mut playlist) = self.get_playlist(playlist_id).await? {
-            if playlist.owner_id != user_id {
+            if !playlist.can_edit(user_id) {
                 return Ok(None); // No autorizado
             }
             
             if let Some(removed_track) = playlist.remove_track(track_index) {
+                playlist.record_edit(user_id, EditAction::RemoveTrack { title: removed_track.title.clone() });
                 self.update_playlist(playlist).await?;
                 Ok(Some(removed_track))
             } else {
@@ -572,6 +1416,54 @@ impl JsonStorage {
         }
     }
     
+    /// Playlists de la guild que el usuario puede editar (propias y compartidas con él)
+    pub async fn get_editable_playlists(&mut self, user_id: u64, guild_id: u64) -> Result<Vec<UserPlaylist>> {
+        let mut playlists: Vec<UserPlaylist> = self
+            .playlists_cache
+            .values()
+            .filter(|playlist| playlist.guild_id == guild_id && playlist.can_edit(user_id))
+            .cloned()
+            .collect();
+        playlists.sort_by_key(|playlist| playlist.name.to_lowercase());
+        Ok(playlists)
+    }
+
+    /// Agrega (`add = true`) o quita un editor de una playlist. Solo el dueño
+    /// puede hacerlo; retorna `false` si no está autorizado o no hubo cambios.
+    pub async fn set_playlist_editor(&mut self, playlist_id: &str, owner_id: u64, editor_id: u64, add: bool) -> Result<bool> {
+        let Some(mut playlist) = self.get_playlist(playlist_id).await? else {
+            return Ok(false);
+        };
+        if playlist.owner_id != owner_id {
+            return Ok(false); // Los editores no pueden sumar otros editores
+        }
+
+        let changed = if add {
+            playlist.add_editor(editor_id)
+        } else {
+            playlist.remove_editor(editor_id)
+        };
+        if changed {
+            let action = if add {
+                EditAction::AddEditor { user_id: editor_id }
+            } else {
+                EditAction::RemoveEditor { user_id: editor_id }
+            };
+            playlist.record_edit(owner_id, action);
+            self.update_playlist(playlist).await?;
+        }
+        Ok(changed)
+    }
+
+    /// Exporta la playlist en formato M3U8 extendido
+    pub async fn export_playlist_to_m3u8(&mut self, playlist_id: &str) -> Result<String> {
+        let playlist = self
+            .get_playlist(playlist_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Playlist no encontrada: {}", playlist_id))?;
+        Ok(m3u::playlist_to_m3u8(&playlist))
+    }
+
     // === MÉTODOS PARA HISTORIAL DE PLAYLISTS ===
     
     #[allow(dead_code)]
@@ -663,6 +1555,7 @@ impl JsonStorage {
     // === MÉTODOS PRIVADOS PARA PLAYLISTS ===
     
     #[allow(dead_code)]
+    #[instrument(level = "debug", name = "storage.load_playlist", skip(self))]
     async fn load_playlist(&self, playlist_id: &str) -> Result<UserPlaylist> {
         let file_path = self.get_playlist_file_path(playlist_id);
         let content = fs::read_to_string(&file_path).await?;
@@ -671,6 +1564,7 @@ impl JsonStorage {
     }
     
     #[allow(dead_code)]
+    #[instrument(level = "debug", name = "storage.save_playlist", skip_all, fields(playlist_id = %playlist.id))]
     async fn save_playlist(&self, playlist: &UserPlaylist) -> Result<()> {
         let file_path = self.get_playlist_file_path(&playlist.id);
         let content = serde_json::to_string_pretty(playlist)?;
@@ -678,7 +1572,6 @@ impl JsonStorage {
         Ok(())
     }
     
-    #[allow(dead_code)]
     async fn load_all_playlists(&mut self) -> Result<()> {
         let playlists_dir = self.data_dir.join("playlists");
         
@@ -719,6 +1612,7 @@ impl JsonStorage {
     }
     
     #[allow(dead_code)]
+    #[instrument(level = "debug", name = "storage.load_playlist_history", skip(self))]
     async fn load_playlist_history(&self, user_id: u64, guild_id: u64) -> Result<PlaylistHistory> {
         let file_path = self.get_history_file_path(user_id, guild_id);
         let content = fs::read_to_string(&file_path).await?;
@@ -727,6 +1621,7 @@ impl JsonStorage {
     }
     
     #[allow(dead_code)]
+    #[instrument(level = "debug", name = "storage.save_playlist_history", skip_all, fields(guild_id = history.guild_id))]
     async fn save_playlist_history(&self, history: &PlaylistHistory) -> Result<()> {
         let file_path = self.get_history_file_path(history.user_id, history.guild_id);
         let content = serde_json::to_string_pretty(history)?;
@@ -743,4 +1638,280 @@ impl JsonStorage {
     fn get_history_file_path(&self, user_id: u64, guild_id: u64) -> PathBuf {
         self.data_dir.join("history").join(format!("history_{}_{}.json", guild_id, user_id))
     }
-}
\ No newline at end of file
+    
+    // === MÉTODOS PARA ESTADO DE COLAS ===
+    
+    /// Guarda el estado completo de la cola de una guild
+    #[instrument(level = "debug", name = "storage.save_guild_queue", skip_all, fields(guild_id = queue.guild_id))]
+    pub async fn save_guild_queue(&self, queue: &SavedGuildQueue) -> Result<()> {
+        let file_path = self.get_queue_file_path(queue.guild_id);
+        let content = serde_json::to_string_pretty(queue)?;
+        fs::write(&file_path, content).await?;
+        
+        info!("💾 Cola guardada para guild {} ({} canciones)", queue.guild_id, queue.tracks.len());
+        Ok(())
+    }
+    
+    /// Carga el estado guardado de la cola de una guild, si existe
+    #[instrument(level = "debug", name = "storage.load_guild_queue", skip(self))]
+    pub async fn load_guild_queue(&self, guild_id: u64) -> Result<Option<SavedGuildQueue>> {
+        let file_path = self.get_queue_file_path(guild_id);
+        
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        
+        let content = fs::read_to_string(&file_path).await?;
+        let queue: SavedGuildQueue = serde_json::from_str(&content)?;
+        Ok(Some(queue))
+    }
+    
+    /// Elimina el estado guardado de la cola de una guild
+    #[instrument(level = "debug", name = "storage.delete_guild_queue", skip(self))]
+    pub async fn delete_guild_queue(&self, guild_id: u64) -> Result<()> {
+        let file_path = self.get_queue_file_path(guild_id);
+        
+        if file_path.exists() {
+            fs::remove_file(&file_path).await?;
+        }
+        
+        Ok(())
+    }
+    
+    fn get_queue_file_path(&self, guild_id: u64) -> PathBuf {
+        self.data_dir.join("queues").join(format!("queue_{}.json", guild_id))
+    }
+    
+    // === MÉTODOS PARA REGISTRO DE COMANDOS ===
+    
+    /// Hash del conjunto de comandos registrado por última vez en un ámbito (sin async)
+    pub fn get_command_hash(&self, scope: &str) -> Option<u64> {
+        self.command_hashes.get(scope).copied()
+    }
+    
+    /// Guarda el hash del conjunto de comandos recién registrado en un ámbito
+    pub async fn set_command_hash(&mut self, scope: &str, hash: u64) -> Result<()> {
+        self.command_hashes.insert(scope.to_string(), hash);
+        let content = serde_json::to_string_pretty(&self.command_hashes)?;
+        fs::write(self.get_command_hashes_file_path(), content).await?;
+        Ok(())
+    }
+    
+    /// Carga los hashes de registro de comandos. Si el archivo falta o está
+    /// corrupto se empieza vacío: como mucho se vuelve a registrar una vez.
+    async fn load_command_hashes(&mut self) {
+        let file_path = self.get_command_hashes_file_path();
+        let Ok(content) = fs::read_to_string(&file_path).await else {
+            return;
+        };
+        
+        match serde_json::from_str(&content) {
+            Ok(hashes) => self.command_hashes = hashes,
+            Err(e) => warn!("Error cargando hashes de comandos: {}", e),
+        }
+    }
+    
+    fn get_command_hashes_file_path(&self) -> PathBuf {
+        self.data_dir.join("commands.json")
+    }
+    
+    // === MÉTODOS PARA VOTOS ===
+    
+    /// Guarda los votos de una guild
+    #[instrument(level = "debug", name = "storage.save_guild_votes", skip_all, fields(guild_id = votes.guild_id))]
+    pub async fn save_guild_votes(&self, votes: &GuildVotes) -> Result<()> {
+        let file_path = self.get_votes_file_path(votes.guild_id);
+        let content = serde_json::to_string_pretty(votes)?;
+        fs::write(&file_path, content).await?;
+        Ok(())
+    }
+    
+    /// Carga los votos persistidos de una guild
+    #[instrument(level = "debug", name = "storage.load_guild_votes", skip(self))]
+    pub async fn load_guild_votes(&self, guild_id: u64) -> Result<GuildVotes> {
+        let file_path = self.get_votes_file_path(guild_id);
+        
+        if !file_path.exists() {
+            return Ok(GuildVotes { guild_id, ..Default::default() });
+        }
+        
+        let content = fs::read_to_string(&file_path).await?;
+        Ok(serde_json::from_str(&content)?)
+    }
+    
+    /// Carga los votos de todas las guilds
+    pub async fn load_all_votes(&self) -> Result<Vec<GuildVotes>> {
+        let votes_dir = self.data_dir.join("votes");
+        let mut files = fs::read_dir(&votes_dir).await?;
+        let mut all_votes = Vec::new();
+        
+        while let Some(entry) = files.next_entry().await? {
+            let path = entry.path();
+            let guild_id = path
+                .file_stem()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix("guild_"))
+                .and_then(|id| id.parse::<u64>().ok());
+            
+            if let Some(guild_id) = guild_id {
+                match self.load_guild_votes(guild_id).await {
+                    Ok(votes) => all_votes.push(votes),
+                    Err(e) => warn!("Error cargando votos para guild {}: {}", guild_id, e),
+                }
+            }
+        }
+        
+        Ok(all_votes)
+    }
+    
+    fn get_votes_file_path(&self, guild_id: u64) -> PathBuf {
+        self.data_dir.join("votes").join(format!("guild_{}.json", guild_id))
+    }
+    
+    // === MÉTODOS PARA PREFERENCIAS DE USUARIO ===
+    
+    /// Preferencias de un usuario (por defecto si nunca cambió ninguna)
+    pub async fn get_user_preferences(&mut self, user_id: u64) -> Result<UserPreferences> {
+        if let Some(prefs) = self.prefs_cache.get(&user_id) {
+            return Ok(prefs.clone());
+        }
+        
+        let file_path = self.get_prefs_file_path(user_id);
+        let prefs = if file_path.exists() {
+            let content = fs::read_to_string(&file_path).await?;
+            serde_json::from_str(&content)?
+        } else {
+            UserPreferences { user_id, ..Default::default() }
+        };
+        
+        self.prefs_cache.insert(user_id, prefs.clone());
+        Ok(prefs)
+    }
+    
+    /// Activa o desactiva los avisos por DM al empezar una canción del usuario
+    pub async fn set_notify_on_play(&mut self, user_id: u64, enabled: bool) -> Result<()> {
+        let mut prefs = self.get_user_preferences(user_id).await?;
+        prefs.notify_on_play = enabled;
+        
+        let content = serde_json::to_string_pretty(&prefs)?;
+        fs::write(self.get_prefs_file_path(user_id), content).await?;
+        self.prefs_cache.insert(user_id, prefs);
+        Ok(())
+    }
+    
+    fn get_prefs_file_path(&self, user_id: u64) -> PathBuf {
+        self.data_dir.join("prefs").join(format!("user_{}.json", user_id))
+    }
+    
+    // === MÉTODOS PARA DATOS PERSONALES (/my-data) ===
+    
+    /// Todo lo guardado sobre un usuario en una guild: sus playlists, su
+    /// participación en playlists ajenas, su historial y sus preferencias
+    /// (estas son comunes a todas las guilds)
+    pub async fn export_user_data(&mut self, guild_id: u64, user_id: u64) -> Result<UserDataExport> {
+        let preferences = self.get_user_preferences(user_id).await?;
+        let playlists = self.get_user_playlists(user_id, guild_id).await?;
+        let playlist_history = match self.history_cache.get(&(user_id, guild_id)) {
+            Some(history) => Some(history.clone()),
+            None => self.load_playlist_history(user_id, guild_id).await.ok(),
+        };
+        let collaborations = self
+            .playlists_cache
+            .values()
+            .filter(|playlist| playlist.guild_id == guild_id && playlist.owner_id != user_id)
+            .filter_map(|playlist| {
+                let is_editor = playlist.editors.contains(&user_id);
+                let edits: Vec<EditEvent> = playlist.edit_log.iter().filter(|event| event.involves(user_id)).cloned().collect();
+                (is_editor || !edits.is_empty()).then(|| PlaylistCollaboration {
+                    playlist_id: playlist.id.clone(),
+                    name: playlist.name.clone(),
+                    is_editor,
+                    edits,
+                })
+            })
+            .collect();
+
+        Ok(UserDataExport {
+            user_id,
+            guild_id,
+            exported_at: Utc::now(),
+            preferences,
+            playlists,
+            playlist_history,
+            collaborations,
+            ratings: Vec::new(),
+            votes: Vec::new(),
+            play_stats: None,
+        })
+    }
+    
+    /// Borra los datos de [`JsonStorage::export_user_data`] (archivos y caché)
+    pub async fn delete_user_data(&mut self, guild_id: u64, user_id: u64) -> Result<DeletionReport> {
+        let mut report = DeletionReport::default();
+
+        let playlist_ids: Vec<String> = self
+            .playlists_cache
+            .values()
+            .filter(|playlist| playlist.owner_id == user_id && playlist.guild_id == guild_id)
+            .map(|playlist| playlist.id.clone())
+            .collect();
+        for playlist_id in playlist_ids {
+            remove_file_if_exists(&self.get_playlist_file_path(&playlist_id)).await?;
+            self.playlists_cache.remove(&playlist_id);
+            report.playlists += 1;
+        }
+
+        // En playlists ajenas: deja de ser editor y sale del historial de cambios
+        let shared: Vec<UserPlaylist> = self
+            .playlists_cache
+            .values()
+            .filter(|playlist| playlist.guild_id == guild_id)
+            .filter(|playlist| playlist.editors.contains(&user_id) || playlist.edit_log.iter().any(|event| event.involves(user_id)))
+            .cloned()
+            .collect();
+        for mut playlist in shared {
+            playlist.editors.retain(|editor| *editor != user_id);
+            playlist.edit_log.retain(|event| !event.involves(user_id));
+            self.update_playlist(playlist).await?;
+            report.collaborations += 1;
+        }
+
+        self.history_cache.remove(&(user_id, guild_id));
+        if remove_file_if_exists(&self.get_history_file_path(user_id, guild_id)).await? {
+            report.history += 1;
+        }
+
+        self.prefs_cache.remove(&user_id);
+        if remove_file_if_exists(&self.get_prefs_file_path(user_id)).await? {
+            report.preferences += 1;
+        }
+
+        info!("🗑️ Datos de un usuario borrados en guild {}: {} registros", guild_id, report.total());
+        Ok(report)
+    }
+}
+
+/// Borra un archivo; `false` si no existía
+async fn remove_file_if_exists(path: &std::path::Path) -> Result<bool> {
+    match fs::remove_file(path).await {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[async_trait]
+impl QueueStorage for JsonStorage {
+    async fn load_queue(&self, guild_id: u64) -> Result<Option<SavedGuildQueue>> {
+        self.load_guild_queue(guild_id).await
+    }
+
+    async fn save_queue(&self, guild_id: u64, queue: &SavedGuildQueue) -> Result<()> {
+        anyhow::ensure!(guild_id == queue.guild_id, "La cola guardada es de otra guild");
+        self.save_guild_queue(queue).await
+    }
+
+    async fn delete_queue(&self, guild_id: u64) -> Result<()> {
+        self.delete_guild_queue(guild_id).await
+    }
+}
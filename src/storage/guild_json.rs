@@ -0,0 +1,131 @@
+//! Almacén genérico de un archivo JSON por guild (`{dir}/guild_{id}.json`),
+//! en memoria y escrito a disco en cada cambio. Es la base de las
+//! estadísticas de reproducción y de las valoraciones.
+//!
+//! Un archivo dañado no impide cargar el resto (se registra y se salta), y
+//! cada escritura va a un archivo temporal que luego se renombra, así un
+//! corte a mitad de escritura nunca deja un JSON a medias.
+
+use anyhow::Result;
+use dashmap::{mapref::one::Ref, DashMap};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Datos que se guardan en un archivo por guild
+pub trait GuildFile: Serialize + DeserializeOwned {
+    /// Datos vacíos de una guild, antes de su primer cambio
+    fn for_guild(guild_id: u64) -> Self;
+
+    /// Guild a la que pertenecen los datos (se guarda dentro del archivo)
+    fn guild_id(&self) -> u64;
+
+    /// Ajuste al cargar del disco, p. ej. descartar datos ya vencidos
+    fn after_load(&mut self) {}
+}
+
+/// Datos de todas las guilds, en memoria y escritos a disco en cada cambio
+#[derive(Debug)]
+pub struct GuildJsonStore<T> {
+    dir: PathBuf,
+    guilds: DashMap<u64, T>,
+    /// Serializa las escrituras para que un archivo nunca quede con datos viejos
+    write_lock: Mutex<()>,
+}
+
+impl<T: GuildFile> GuildJsonStore<T> {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            guilds: DashMap::new(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Ruta del archivo de una guild
+    pub fn path(&self, guild_id: u64) -> PathBuf {
+        self.dir.join(format!("guild_{}.json", guild_id))
+    }
+
+    /// Carga los archivos guardados (al iniciar el bot). Los que no se pueden
+    /// leer se saltan con un aviso. Retorna cuántas guilds se cargaron.
+    pub async fn load(&self) -> Result<usize> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let mut files = tokio::fs::read_dir(&self.dir).await?;
+        let mut loaded = 0;
+
+        while let Some(entry) = files.next_entry().await? {
+            let path = entry.path();
+            let is_guild_file = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("guild_") && name.ends_with(".json"));
+            if !is_guild_file {
+                continue;
+            }
+
+            match read_guild_file::<T>(&path).await {
+                Ok(mut data) => {
+                    data.after_load();
+                    self.guilds.insert(data.guild_id(), data);
+                    loaded += 1;
+                }
+                Err(e) => warn!("Error cargando {}: {}", path.display(), e),
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Datos de una guild, si tiene
+    pub fn get(&self, guild_id: u64) -> Option<Ref<'_, u64, T>> {
+        self.guilds.get(&guild_id)
+    }
+
+    /// Modifica los datos de una guild (creándolos si no tenía) y los guarda
+    pub async fn update<R>(&self, guild_id: u64, change: impl FnOnce(&mut T) -> R) -> Result<R> {
+        let _guard = self.write_lock.lock().await;
+        let (content, result) = {
+            let mut guild = self.guilds.entry(guild_id).or_insert_with(|| T::for_guild(guild_id));
+            let result = change(&mut guild);
+            (serde_json::to_string(&*guild)?, result)
+        };
+
+        self.write(guild_id, content).await?;
+        Ok(result)
+    }
+
+    /// Como [`update`](Self::update), pero solo si la guild ya tiene datos; si
+    /// `change` retorna `None` no hubo cambios y no se escribe nada
+    pub async fn update_existing<R>(&self, guild_id: u64, change: impl FnOnce(&mut T) -> Option<R>) -> Result<Option<R>> {
+        let _guard = self.write_lock.lock().await;
+        let (content, result) = {
+            let Some(mut guild) = self.guilds.get_mut(&guild_id) else {
+                return Ok(None);
+            };
+            let Some(result) = change(&mut guild) else {
+                return Ok(None);
+            };
+            (serde_json::to_string(&*guild)?, result)
+        };
+
+        self.write(guild_id, content).await?;
+        Ok(Some(result))
+    }
+
+    /// Escribe el archivo de la guild a través de un temporal
+    async fn write(&self, guild_id: u64, content: String) -> Result<()> {
+        let path = self.path(guild_id);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+}
+
+async fn read_guild_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&content)?)
+}
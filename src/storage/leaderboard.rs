@@ -0,0 +1,153 @@
+//! Reproducciones por canción para `/leaderboard`, guardadas junto a las
+//! estadísticas de reproducción de la guild ([`super::play_stats`]).
+//!
+//! Cada canción guarda su total y las reproducciones por día de los últimos 30
+//! días, así el ranking se puede filtrar por semana o mes sin guardar cada
+//! reproducción. El tiempo escuchado se suma cuando la canción termina o se
+//! salta, con lo que sonó realmente.
+
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Canciones por página de `/leaderboard`
+pub const LEADERBOARD_PAGE_SIZE: usize = 10;
+
+/// Período que cubre el ranking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardScope {
+    Week,
+    Month,
+    AllTime,
+}
+
+impl LeaderboardScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardScope::Week => "week",
+            LeaderboardScope::Month => "month",
+            LeaderboardScope::AllTime => "all-time",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(LeaderboardScope::Week),
+            "month" => Some(LeaderboardScope::Month),
+            "all-time" => Some(LeaderboardScope::AllTime),
+            _ => None,
+        }
+    }
+
+    /// Nombre para mostrar en el embed
+    pub fn label(&self) -> &'static str {
+        match self {
+            LeaderboardScope::Week => "últimos 7 días",
+            LeaderboardScope::Month => "últimos 30 días",
+            LeaderboardScope::AllTime => "desde siempre",
+        }
+    }
+
+    /// Primer día que entra en el ranking, `None` si no hay límite
+    pub fn since(&self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            LeaderboardScope::Week => Some(today - ChronoDuration::days(6)),
+            LeaderboardScope::Month => Some(today - ChronoDuration::days(29)),
+            LeaderboardScope::AllTime => None,
+        }
+    }
+}
+
+/// Reproducciones de una canción
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackPlayCount {
+    pub url: String,
+    pub title: String,
+    pub play_count: u32,
+    /// Tiempo que sonó en total, sumado al terminar o saltarse
+    pub total_duration_played: Duration,
+    pub last_played: DateTime<Utc>,
+    /// Reproducciones por día (UTC), solo de los días que cubre [`LeaderboardScope::Month`]
+    #[serde(default)]
+    pub daily: BTreeMap<NaiveDate, u32>,
+}
+
+impl TrackPlayCount {
+    /// La misma canción con los totales limitados a los días desde `since`;
+    /// `None` si no sonó en ese período
+    fn within(&self, since: NaiveDate) -> Option<Self> {
+        let play_count: u32 = self.daily.range(since..).map(|(_, plays)| plays).sum();
+        if play_count == 0 {
+            return None;
+        }
+        // El tiempo escuchado no se guarda por día: se reparte igual entre las reproducciones
+        let per_play = self.total_duration_played / self.play_count.max(1);
+        Some(Self {
+            play_count,
+            total_duration_played: per_play * play_count,
+            ..self.clone()
+        })
+    }
+}
+
+/// Ranking persistido de una guild
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuildLeaderboard {
+    /// URL → reproducciones
+    pub tracks: HashMap<String, TrackPlayCount>,
+}
+
+impl GuildLeaderboard {
+    /// Suma una reproducción de `url` en `at` y descarta los días que ya no
+    /// entran en ningún período
+    pub fn record(&mut self, url: &str, title: &str, at: DateTime<Utc>) {
+        let track = self.tracks.entry(url.to_string()).or_insert_with(|| TrackPlayCount {
+            url: url.to_string(),
+            title: title.to_string(),
+            play_count: 0,
+            total_duration_played: Duration::ZERO,
+            last_played: at,
+            daily: BTreeMap::new(),
+        });
+        track.title = title.to_string();
+        track.play_count += 1;
+        track.last_played = at;
+        *track.daily.entry(at.date_naive()).or_default() += 1;
+        self.prune(at.date_naive());
+    }
+
+    /// Suma a `url` el tiempo que sonó; `false` si la canción no tiene reproducciones
+    pub fn record_listened(&mut self, url: &str, played: Duration) -> bool {
+        let Some(track) = self.tracks.get_mut(url) else {
+            return false;
+        };
+        track.total_duration_played += played;
+        true
+    }
+
+    /// Quita las reproducciones por día anteriores al período más largo (30 días)
+    pub fn prune(&mut self, today: NaiveDate) {
+        let Some(cutoff) = LeaderboardScope::Month.since(today) else {
+            return;
+        };
+        for track in self.tracks.values_mut() {
+            track.daily.retain(|day, _| *day >= cutoff);
+        }
+    }
+
+    /// Canciones del período, de más a menos reproducidas (las más recientes primero si empatan)
+    pub fn ranking(&self, scope: LeaderboardScope, today: NaiveDate) -> Vec<TrackPlayCount> {
+        let mut ranked: Vec<TrackPlayCount> = match scope.since(today) {
+            Some(since) => self.tracks.values().filter_map(|track| track.within(since)).collect(),
+            None => self.tracks.values().cloned().collect(),
+        };
+        ranked.sort_by(|a, b| {
+            b.play_count
+                .cmp(&a.play_count)
+                .then(b.last_played.cmp(&a.last_played))
+                .then(a.url.cmp(&b.url))
+        });
+        ranked
+    }
+}
@@ -0,0 +1,98 @@
+//! Exportación e importación de playlists en formato M3U8 extendido.
+//!
+//! Cada canción se escribe como `#EXTINF:{segundos},{artista} - {título}`
+//! seguido de su URL. Al importar, las entradas sin URL (rutas locales de
+//! otros reproductores) se buscan por su texto.
+
+use std::time::Duration;
+
+use super::UserPlaylist;
+
+/// Canciones que se importan como mucho de un archivo M3U8
+pub const MAX_M3U8_IMPORT_TRACKS: usize = 100;
+
+/// Canción leída de un archivo M3U8
+#[derive(Debug, Clone, PartialEq)]
+pub struct M3u8Entry {
+    /// URL remota de la canción, si la entrada tiene una
+    pub url: Option<String>,
+    /// Texto de `#EXTINF` (normalmente "artista - título")
+    pub title: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl M3u8Entry {
+    /// URL o texto con el que buscar la canción
+    pub fn query(&self) -> Option<&str> {
+        self.url.as_deref().or(self.title.as_deref())
+    }
+}
+
+/// Contenido de un archivo M3U8
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct M3u8Playlist {
+    /// Nombre de `#PLAYLIST`, si el archivo lo trae
+    pub name: Option<String>,
+    pub entries: Vec<M3u8Entry>,
+}
+
+/// Una línea de `#EXTINF` no puede partirse: los saltos rompen el formato
+fn single_line(text: &str) -> String {
+    text.split(['\r', '\n']).map(str::trim).filter(|part| !part.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Escribe la playlist en formato M3U8 extendido
+pub fn playlist_to_m3u8(playlist: &UserPlaylist) -> String {
+    let mut output = format!("#EXTM3U\n#PLAYLIST:{}\n", single_line(&playlist.name));
+    for track in &playlist.tracks {
+        // -1: duración desconocida, según la convención del formato
+        let duration = track.duration.map_or(-1, |d| d.as_secs() as i64);
+        let label = match track.artist.as_deref().filter(|artist| !artist.trim().is_empty()) {
+            Some(artist) => format!("{} - {}", single_line(artist), single_line(&track.title)),
+            None => single_line(&track.title),
+        };
+        output.push_str(&format!("#EXTINF:{},{}\n{}\n", duration, label, track.url.trim()));
+    }
+    output
+}
+
+/// Lee un archivo M3U8; las entradas sin URL ni texto se descartan
+pub fn parse_m3u8(content: &str) -> M3u8Playlist {
+    let mut playlist = M3u8Playlist::default();
+    let mut pending: Option<(Option<Duration>, Option<String>)> = None;
+
+    for line in content.trim_start_matches('\u{feff}').lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+            playlist.name = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+            let duration = duration
+                .split_whitespace()
+                .next()
+                .and_then(|secs| secs.parse::<f64>().ok())
+                // `-1` (duración desconocida), `inf`, `NaN` o valores enormes: sin duración
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+            let title = Some(title.trim().to_string()).filter(|title| !title.is_empty());
+            pending = Some((duration, title));
+        } else if !line.starts_with('#') {
+            let (duration, title) = pending.take().unwrap_or_default();
+            let is_remote = line.starts_with("http://") || line.starts_with("https://");
+            // Ruta local: sin título, el nombre del archivo es lo mejor que hay
+            let title = title.or_else(|| {
+                (!is_remote)
+                    .then(|| line.rsplit(['/', '\\']).next().unwrap_or(line))
+                    .map(|file| file.rsplit_once('.').map_or(file, |(stem, _)| stem).trim().to_string())
+                    .filter(|stem| !stem.is_empty())
+            });
+            let entry = M3u8Entry { url: is_remote.then(|| line.to_string()), title, duration };
+            if entry.query().is_some() {
+                playlist.entries.push(entry);
+            }
+        }
+    }
+
+    playlist
+}
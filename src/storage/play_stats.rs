@@ -0,0 +1,225 @@
+//! Estadísticas de reproducción por guild para `/stats guild`, un archivo JSON
+//! por guild (`DATA_DIR/stats/guild_{id}.json`).
+//!
+//! Se guardan totales acumulados (no cada reproducción): por canción, por
+//! usuario que la pidió y por día, así el archivo crece con las canciones
+//! distintas y los días con actividad, no con las horas de música. El mismo
+//! archivo guarda el ranking de `/leaderboard`, así cada canción que empieza
+//! se escribe una sola vez.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::guild_json::{GuildFile, GuildJsonStore};
+use super::leaderboard::{GuildLeaderboard, LeaderboardScope, TrackPlayCount};
+
+/// Usuarios que se muestran en el ranking de `/stats guild`
+pub const TOP_REQUESTERS: usize = 3;
+
+/// Veces que sonó una canción
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackPlays {
+    pub title: String,
+    pub plays: u64,
+}
+
+/// Canciones que pidió un usuario
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserPlays {
+    pub plays: u64,
+    pub listening_secs: u64,
+    pub last_active: Option<DateTime<Utc>>,
+}
+
+/// Reproducciones persistidas de una guild
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuildPlayStats {
+    pub guild_id: u64,
+    pub total_plays: u64,
+    pub listening_secs: u64,
+    /// URL → reproducciones
+    pub tracks: HashMap<String, TrackPlays>,
+    /// Usuario que la pidió → reproducciones
+    pub users: HashMap<u64, UserPlays>,
+    /// Reproducciones por día (UTC)
+    pub daily: BTreeMap<NaiveDate, u64>,
+    /// Ranking de `/leaderboard`
+    #[serde(default)]
+    pub leaderboard: GuildLeaderboard,
+}
+
+/// Resumen que muestra `/stats guild`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuildStatsSummary {
+    pub total_plays: u64,
+    pub listening_time: Duration,
+    pub unique_tracks: usize,
+    /// (usuario, reproducciones), de más a menos
+    pub top_requesters: Vec<(u64, u64)>,
+    /// (título, URL, reproducciones)
+    pub most_played: Option<(String, String, u64)>,
+    /// Reproducciones de los últimos 7 días y de los 7 anteriores
+    pub this_week: u64,
+    pub last_week: u64,
+    /// Reproducciones por día de la semana, de lunes a domingo
+    pub by_weekday: [u64; 7],
+}
+
+impl GuildStatsSummary {
+    /// Día de la semana con más reproducciones (el primero de la semana si empatan)
+    pub fn peak_day(&self) -> Option<Weekday> {
+        let (index, plays) = self
+            .by_weekday
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, plays)| **plays)?;
+        if *plays == 0 {
+            return None;
+        }
+        Weekday::try_from(index as u8).ok()
+    }
+}
+
+impl GuildPlayStats {
+    /// Suma una reproducción de `url` pedida por `user_id`
+    pub fn record(&mut self, user_id: u64, url: &str, title: &str, duration: Option<Duration>, at: DateTime<Utc>) {
+        let secs = duration.map_or(0, |duration| duration.as_secs());
+        self.total_plays += 1;
+        self.listening_secs += secs;
+
+        let track = self.tracks.entry(url.to_string()).or_default();
+        track.title = title.to_string();
+        track.plays += 1;
+
+        // Las pistas sin solicitante real (ID 0) no entran en el ranking
+        if user_id != 0 {
+            let user = self.users.entry(user_id).or_default();
+            user.plays += 1;
+            user.listening_secs += secs;
+            user.last_active = Some(at);
+        }
+
+        *self.daily.entry(at.date_naive()).or_default() += 1;
+        self.leaderboard.record(url, title, at);
+    }
+
+    /// Resumen para `/stats guild`; las semanas se cuentan hacia atrás desde `today`
+    pub fn summary(&self, today: NaiveDate) -> GuildStatsSummary {
+        let mut top_requesters: Vec<(u64, u64)> = self.users.iter().map(|(user, stats)| (*user, stats.plays)).collect();
+        top_requesters.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        top_requesters.truncate(TOP_REQUESTERS);
+
+        let most_played = self
+            .tracks
+            .iter()
+            .max_by(|a, b| a.1.plays.cmp(&b.1.plays).then(b.0.cmp(a.0)))
+            .map(|(url, track)| (track.title.clone(), url.clone(), track.plays));
+
+        let plays_between = |from: NaiveDate, to: NaiveDate| -> u64 { self.daily.range(from..=to).map(|(_, plays)| plays).sum() };
+        let week_start = today - ChronoDuration::days(6);
+        let this_week = plays_between(week_start, today);
+        let last_week = plays_between(week_start - ChronoDuration::days(7), week_start - ChronoDuration::days(1));
+
+        let mut by_weekday = [0; 7];
+        for (day, plays) in &self.daily {
+            by_weekday[day.weekday().num_days_from_monday() as usize] += plays;
+        }
+
+        GuildStatsSummary {
+            total_plays: self.total_plays,
+            listening_time: Duration::from_secs(self.listening_secs),
+            unique_tracks: self.tracks.len(),
+            top_requesters,
+            most_played,
+            this_week,
+            last_week,
+            by_weekday,
+        }
+    }
+}
+
+impl GuildFile for GuildPlayStats {
+    fn for_guild(guild_id: u64) -> Self {
+        Self {
+            guild_id,
+            ..Default::default()
+        }
+    }
+
+    fn guild_id(&self) -> u64 {
+        self.guild_id
+    }
+
+    fn after_load(&mut self) {
+        self.leaderboard.prune(Utc::now().date_naive());
+    }
+}
+
+/// Estadísticas de todas las guilds, en memoria y escritas a disco en cada reproducción
+#[derive(Debug)]
+pub struct PlayStatsStore {
+    store: GuildJsonStore<GuildPlayStats>,
+}
+
+impl PlayStatsStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            store: GuildJsonStore::new(dir),
+        }
+    }
+
+    /// Carga las estadísticas guardadas (al iniciar el bot). Retorna cuántas guilds se cargaron.
+    pub async fn load(&self) -> Result<usize> {
+        self.store.load().await
+    }
+
+    /// Registra que empezó a sonar una canción y lo guarda
+    pub async fn record_play(&self, guild_id: u64, user_id: u64, url: &str, title: &str, duration: Option<Duration>) -> Result<()> {
+        self.store
+            .update(guild_id, |guild| guild.record(user_id, url, title, duration, Utc::now()))
+            .await
+    }
+
+    /// Suma el tiempo que sonó una canción al terminar o saltarse y lo guarda
+    pub async fn record_listened(&self, guild_id: u64, url: &str, played: Duration) -> Result<()> {
+        self.store
+            .update_existing(guild_id, |guild| guild.leaderboard.record_listened(url, played).then_some(()))
+            .await?;
+        Ok(())
+    }
+
+    /// Canciones que pidió un usuario en la guild
+    pub fn user_plays(&self, guild_id: u64, user_id: u64) -> Option<UserPlays> {
+        self.store.get(guild_id)?.users.get(&user_id).cloned()
+    }
+
+    /// Borra las estadísticas de un usuario en la guild y lo guarda; los
+    /// totales de la guild, que no lo identifican, se mantienen. `false` si no tenía.
+    pub async fn remove_user(&self, guild_id: u64, user_id: u64) -> Result<bool> {
+        let removed = self
+            .store
+            .update_existing(guild_id, |guild| guild.users.remove(&user_id))
+            .await?;
+        Ok(removed.is_some())
+    }
+
+    /// Resumen de la guild a día de hoy, `None` si nunca sonó nada
+    pub fn summary(&self, guild_id: u64) -> Option<GuildStatsSummary> {
+        self.store
+            .get(guild_id)
+            .map(|guild| guild.summary(Utc::now().date_naive()))
+    }
+
+    /// Ranking de `/leaderboard` para el período, calculado a día de hoy
+    pub fn ranking(&self, guild_id: u64, scope: LeaderboardScope) -> Vec<TrackPlayCount> {
+        self.store
+            .get(guild_id)
+            .map(|guild| guild.leaderboard.ranking(scope, Utc::now().date_naive()))
+            .unwrap_or_default()
+    }
+}
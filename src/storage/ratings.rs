@@ -0,0 +1,194 @@
+//! Valoraciones de 1 a 5 estrellas por canción, un archivo JSON por guild
+//! (`DATA_DIR/ratings/guild_{id}.json`).
+//!
+//! Se guardan todas las valoraciones con su autor, también las de usuarios que
+//! ya salieron del servidor: el promedio histórico no cambia cuando alguien se va.
+//! Solo `/my-data delete` quita las de un usuario.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::guild_json::{GuildFile, GuildJsonStore};
+
+/// Estrellas mínimas y máximas de una valoración
+pub const MIN_STARS: u8 = 1;
+pub const MAX_STARS: u8 = 5;
+
+/// Valoración de un usuario
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub user_id: u64,
+    pub stars: u8,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Valoraciones persistidas de una guild
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuildRatings {
+    pub guild_id: u64,
+    /// URL → valoraciones (una por usuario)
+    pub tracks: HashMap<String, Vec<Rating>>,
+    /// URL → título, para mostrarlo en `/top-rated`
+    #[serde(default)]
+    pub titles: HashMap<String, String>,
+}
+
+/// Canción del ranking de `/top-rated`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatedTrack {
+    pub url: String,
+    pub title: String,
+    pub average: f32,
+    pub count: usize,
+}
+
+/// Valoración de un usuario con su canción, para `/my-data export`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserRating {
+    pub url: String,
+    pub title: String,
+    pub stars: u8,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Promedio de estrellas, `None` sin valoraciones
+fn average(ratings: &[Rating]) -> Option<f32> {
+    if ratings.is_empty() {
+        return None;
+    }
+    let total: u32 = ratings.iter().map(|rating| u32::from(rating.stars)).sum();
+    Some(total as f32 / ratings.len() as f32)
+}
+
+impl GuildFile for GuildRatings {
+    fn for_guild(guild_id: u64) -> Self {
+        Self {
+            guild_id,
+            ..Default::default()
+        }
+    }
+
+    fn guild_id(&self) -> u64 {
+        self.guild_id
+    }
+}
+
+/// Valoraciones de todas las guilds, en memoria y escritas a disco en cada cambio
+#[derive(Debug)]
+pub struct RatingStore {
+    store: GuildJsonStore<GuildRatings>,
+}
+
+impl RatingStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            store: GuildJsonStore::new(dir),
+        }
+    }
+
+    /// Carga las valoraciones guardadas (al iniciar el bot). Retorna cuántas guilds se cargaron.
+    pub async fn load(&self) -> Result<usize> {
+        self.store.load().await
+    }
+
+    /// Registra la valoración de un usuario (reemplaza la anterior si ya había
+    /// valorado la canción) y la guarda. Retorna el nuevo promedio.
+    pub async fn add_rating(&self, guild_id: u64, url: &str, title: &str, user_id: u64, stars: u8) -> Result<f32> {
+        if !(MIN_STARS..=MAX_STARS).contains(&stars) {
+            anyhow::bail!("La valoración debe estar entre {} y {} estrellas", MIN_STARS, MAX_STARS);
+        }
+
+        self.store
+            .update(guild_id, |guild| {
+                guild.titles.insert(url.to_string(), title.to_string());
+
+                let ratings = guild.tracks.entry(url.to_string()).or_default();
+                ratings.retain(|rating| rating.user_id != user_id);
+                ratings.push(Rating {
+                    user_id,
+                    stars,
+                    timestamp: Utc::now(),
+                });
+                average(ratings).unwrap_or_default()
+            })
+            .await
+    }
+
+    /// Promedio de estrellas de una canción en la guild, si alguien la valoró
+    pub fn get_average_rating(&self, guild_id: u64, url: &str) -> Option<f32> {
+        self.store
+            .get(guild_id)
+            .and_then(|guild| guild.tracks.get(url).and_then(|ratings| average(ratings)))
+    }
+
+    /// Valoraciones de un usuario en la guild, de la más reciente a la más vieja
+    pub fn user_ratings(&self, guild_id: u64, user_id: u64) -> Vec<UserRating> {
+        let Some(guild) = self.store.get(guild_id) else {
+            return Vec::new();
+        };
+
+        let mut ratings: Vec<UserRating> = guild
+            .tracks
+            .iter()
+            .flat_map(|(url, ratings)| ratings.iter().filter(|rating| rating.user_id == user_id).map(move |rating| (url, rating)))
+            .map(|(url, rating)| UserRating {
+                url: url.clone(),
+                title: guild.titles.get(url).cloned().unwrap_or_else(|| url.clone()),
+                stars: rating.stars,
+                timestamp: rating.timestamp,
+            })
+            .collect();
+        ratings.sort_by_key(|rating| std::cmp::Reverse(rating.timestamp));
+        ratings
+    }
+
+    /// Borra las valoraciones de un usuario en la guild y lo guarda. Retorna cuántas borró.
+    pub async fn remove_user(&self, guild_id: u64, user_id: u64) -> Result<usize> {
+        let removed = self
+            .store
+            .update_existing(guild_id, |guild| {
+                let mut removed = 0;
+                for ratings in guild.tracks.values_mut() {
+                    let before = ratings.len();
+                    ratings.retain(|rating| rating.user_id != user_id);
+                    removed += before - ratings.len();
+                }
+                if removed == 0 {
+                    return None;
+                }
+                guild.tracks.retain(|_, ratings| !ratings.is_empty());
+                let GuildRatings { tracks, titles, .. } = guild;
+                titles.retain(|url, _| tracks.contains_key(url));
+                Some(removed)
+            })
+            .await?;
+        Ok(removed.unwrap_or(0))
+    }
+
+    /// Las `limit` canciones mejor valoradas de la guild; a igual promedio,
+    /// primero la que tiene más valoraciones
+    pub fn top_rated(&self, guild_id: u64, limit: usize) -> Vec<RatedTrack> {
+        let Some(guild) = self.store.get(guild_id) else {
+            return Vec::new();
+        };
+
+        let mut ranked: Vec<RatedTrack> = guild
+            .tracks
+            .iter()
+            .filter_map(|(url, ratings)| {
+                Some(RatedTrack {
+                    url: url.clone(),
+                    title: guild.titles.get(url).cloned().unwrap_or_else(|| url.clone()),
+                    average: average(ratings)?,
+                    count: ratings.len(),
+                })
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.average.total_cmp(&a.average).then(b.count.cmp(&a.count)));
+        ranked.truncate(limit);
+        ranked
+    }
+}
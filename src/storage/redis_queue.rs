@@ -0,0 +1,111 @@
+//! Colas de las guilds en Redis (`QUEUE_BACKEND=redis`).
+//!
+//! Con el bot repartido en varios procesos, el estado en memoria de cada uno
+//! deja de ser la única copia: cada proceso escribe aquí las colas de las
+//! guilds de su shard y cualquiera las lee. Cada cola se guarda como el mismo
+//! JSON que usa `DATA_DIR/queues`.
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use deadpool_redis::redis::AsyncCommands;
+use deadpool_redis::{Config as RedisConfig, Connection, Pool, PoolConfig, Runtime, Timeouts};
+use std::time::Duration;
+use tracing::warn;
+
+use super::{QueueStorage, SavedGuildQueue};
+
+/// Prefijo de las claves de colas en Redis (`open_music:queue:<guild_id>`)
+const QUEUE_KEY_PREFIX: &str = "open_music:queue:";
+
+/// Reintentos al obtener una conexión antes de dar Redis por caído
+const RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Delay del primer reintento; se duplica en cada uno
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Tope del backoff entre reintentos
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Tiempo máximo para abrir una conexión o esperar una libre del pool
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Clave de Redis de la cola de una guild
+pub fn queue_key(guild_id: u64) -> String {
+    format!("{}{}", QUEUE_KEY_PREFIX, guild_id)
+}
+
+/// Delay antes del reintento `attempt` (1-based): backoff exponencial con tope
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_BASE_DELAY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(RECONNECT_MAX_DELAY)
+}
+
+/// Colas guardadas en Redis a través de un pool de conexiones
+pub struct RedisQueueStore {
+    pool: Pool,
+}
+
+impl RedisQueueStore {
+    /// Crea el pool (sin conectar todavía) con hasta `pool_size` conexiones
+    pub fn new(url: &str, pool_size: usize) -> Result<Self> {
+        let mut config = RedisConfig::from_url(url);
+        config.pool = Some(PoolConfig {
+            max_size: pool_size,
+            timeouts: Timeouts {
+                wait: Some(CONNECT_TIMEOUT),
+                create: Some(CONNECT_TIMEOUT),
+                recycle: Some(CONNECT_TIMEOUT),
+            },
+        });
+        let pool = config
+            .create_pool(Some(Runtime::Tokio1))
+            .context("Configuración de Redis inválida")?;
+        Ok(Self { pool })
+    }
+
+    /// Conexión del pool. Si Redis no responde (reinicio, corte de red),
+    /// reintenta con backoff exponencial; el pool descarta las conexiones rotas.
+    async fn connection(&self) -> Result<Connection> {
+        let mut attempt = 0;
+        loop {
+            match self.pool.get().await {
+                Ok(connection) => return Ok(connection),
+                Err(e) if attempt < RECONNECT_ATTEMPTS => {
+                    attempt += 1;
+                    let delay = reconnect_delay(attempt);
+                    warn!(
+                        "🔌 Redis no disponible ({}), reintento {}/{} en {:?}",
+                        e, attempt, RECONNECT_ATTEMPTS, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e).context("No se pudo conectar a Redis"),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl QueueStorage for RedisQueueStore {
+    async fn load_queue(&self, guild_id: u64) -> Result<Option<SavedGuildQueue>> {
+        let mut connection = self.connection().await?;
+        let content: Option<String> = connection.get(queue_key(guild_id)).await?;
+        content
+            .map(|content| serde_json::from_str(&content).map_err(Into::into))
+            .transpose()
+    }
+
+    async fn save_queue(&self, guild_id: u64, queue: &SavedGuildQueue) -> Result<()> {
+        let content = serde_json::to_string(queue)?;
+        let mut connection = self.connection().await?;
+        connection.set::<_, _, ()>(queue_key(guild_id), content).await?;
+        Ok(())
+    }
+
+    async fn delete_queue(&self, guild_id: u64) -> Result<()> {
+        let mut connection = self.connection().await?;
+        connection.del::<_, ()>(queue_key(guild_id)).await?;
+        Ok(())
+    }
+}
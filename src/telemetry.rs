@@ -0,0 +1,111 @@
+//! Exportación OpenTelemetry (`OTEL_EXPORTER_OTLP_ENDPOINT`).
+//!
+//! Con el endpoint configurado, los spans de `tracing` (comandos, búsquedas,
+//! inicio de reproducción, almacenamiento) se envían por OTLP/HTTP a un
+//! collector (Jaeger, Tempo, el OpenTelemetry Collector...) y la duración de
+//! cada comando se exporta como el histograma `openmusic.command.duration_ms`.
+//! Sin endpoint no se crea ningún exportador y las métricas no hacen nada.
+
+use anyhow::{Context as _, Result};
+use opentelemetry::{
+    global,
+    metrics::Histogram,
+    trace::TracerProvider as _,
+    KeyValue,
+};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    metrics::SdkMeterProvider,
+    trace::{SdkTracerProvider, Tracer},
+    Resource,
+};
+use std::sync::LazyLock;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Variable con la URL base del collector (p. ej. `http://otel-collector:4318`)
+pub const OTEL_ENDPOINT_VAR: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Nombre del servicio en las trazas y métricas exportadas
+const SERVICE_NAME: &str = "open-music";
+
+/// Duración de cada comando slash, por nombre de comando
+static COMMAND_DURATION: LazyLock<Histogram<u64>> = LazyLock::new(|| {
+    global::meter(SERVICE_NAME)
+        .u64_histogram("openmusic.command.duration_ms")
+        .with_unit("ms")
+        .with_description("Duración de los comandos slash")
+        .build()
+});
+
+/// Lee el endpoint antes de cargar la configuración, ya que el logging (y con él
+/// la capa de OpenTelemetry) se inicializa primero. [`Config::load`] lo valida luego.
+///
+/// [`Config::load`]: crate::config::Config::load
+pub fn endpoint_from_env() -> Option<String> {
+    std::env::var(OTEL_ENDPOINT_VAR)
+        .ok()
+        .map(|endpoint| endpoint.trim().trim_end_matches('/').to_string())
+        .filter(|endpoint| !endpoint.is_empty())
+}
+
+/// Exportadores OTLP de trazas y métricas
+pub struct Telemetry {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+}
+
+impl Telemetry {
+    /// Crea los exportadores hacia `endpoint` (`/v1/traces` y `/v1/metrics`) y
+    /// registra el de métricas como global.
+    pub fn init(endpoint: &str) -> Result<Self> {
+        let resource = Resource::builder().with_service_name(SERVICE_NAME).build();
+
+        let span_exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint))
+            .build()
+            .context("No se pudo crear el exportador OTLP de trazas")?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource.clone())
+            .build();
+
+        let metric_exporter = MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/metrics", endpoint))
+            .build()
+            .context("No se pudo crear el exportador OTLP de métricas")?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter)
+            .with_resource(resource)
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
+        Ok(Self { tracer_provider, meter_provider })
+    }
+
+    /// Capa de `tracing` que exporta los spans
+    pub fn layer<S>(&self) -> OpenTelemetryLayer<S, Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.tracer_provider.tracer(SERVICE_NAME))
+    }
+
+    /// Envía lo pendiente y cierra los exportadores (al apagar)
+    pub fn shutdown(&self) {
+        if let Err(e) = self.tracer_provider.shutdown() {
+            tracing::warn!("Error cerrando el exportador de trazas: {:?}", e);
+        }
+        if let Err(e) = self.meter_provider.shutdown() {
+            tracing::warn!("Error cerrando el exportador de métricas: {:?}", e);
+        }
+    }
+}
+
+/// Registra la duración de un comando en `openmusic.command.duration_ms`
+pub fn record_command_duration(command: &str, duration_ms: u64) {
+    COMMAND_DURATION.record(duration_ms, &[KeyValue::new("command", command.to_string())]);
+}
@@ -4,14 +4,24 @@ use serenity::{
     builder::{CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter},
 };
 use std::time::Duration;
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    audio::player::AudioPlayer, 
-    sources::TrackSource,
+    bot::{
+        handlers::{self, TrackRequestBlock},
+        voting::{VoteOutcome, VotingSystem},
+        OpenMusicBot,
+    },
+    sources::{Chapter, TrackSource},
 };
 use serenity::model::id::GuildId;
 
+/// Canciones que agrega el botón "Similares"
+const SIMILAR_TRACKS: usize = 5;
+
+/// Margen de redondeo al ubicar el capítulo actual tras un seek con `/tempo`
+const CHAPTER_SEEK_TOLERANCE: Duration = Duration::from_millis(250);
+
 /// IDs personalizados para los botones
 #[allow(dead_code)]
 pub mod button_ids {
@@ -27,6 +37,12 @@ pub mod button_ids {
     pub const EFFECTS: &str = "music_effects";
     pub const PREVIOUS_PAGE: &str = "music_prev_page";
     pub const NEXT_PAGE: &str = "queue_next";
+    pub const VOTE_UP: &str = "music_vote_up";
+    pub const VOTE_DOWN: &str = "music_vote_down";
+    pub const REFRESH: &str = "music_refresh";
+    pub const SIMILAR: &str = "music_similar";
+    pub const PREV_CHAPTER: &str = "music_prev_chapter";
+    pub const NEXT_CHAPTER: &str = "music_next_chapter";
     
     // Botones específicos para playlists
     pub const PLAYLIST_LOAD: &str = "playlist_load";
@@ -44,6 +60,17 @@ pub mod button_ids {
     pub const PLAYLIST_SHARE: &str = "playlist_share";
     pub const PLAYLIST_REMOVE_DUPLICATES: &str = "playlist_remove_dupes";
     pub const PLAYLIST_QUEUE_POSITION: &str = "playlist_queue_pos";
+
+    // Botones de `/quiz`
+    pub const QUIZ_GUESS: &str = "quiz_guess";
+    pub const QUIZ_SKIP: &str = "quiz_skip";
+    pub const QUIZ_END: &str = "quiz_end";
+
+    /// Botones que controlan la reproducción (sujetos a `require_same_channel`)
+    pub const PLAYBACK_CONTROLS: &[&str] = &[
+        PLAY_PAUSE, SKIP, RESTART, STOP, SHUFFLE, LOOP_TRACK, VOLUME_UP, VOLUME_DOWN, PLAYLIST_SHUFFLE, SIMILAR,
+        PREV_CHAPTER, NEXT_CHAPTER,
+    ];
 }
 
 /// Constructor de controles de música
@@ -124,7 +151,24 @@ impl MusicControls {
 
         let row2 = CreateActionRow::Buttons(vec![restart_btn, vol_down_btn, vol_up_btn, queue_btn, effects_btn]);
 
-        vec![row1, row2]
+        vec![row1, row2, Self::create_vote_row()]
+    }
+
+    /// Crea la fila de votación (👍/👎) para el track actual
+    pub fn create_vote_row() -> CreateActionRow {
+        CreateActionRow::Buttons(Self::vote_buttons())
+    }
+
+    fn vote_buttons() -> Vec<CreateButton> {
+        let up_btn = CreateButton::new(button_ids::VOTE_UP)
+            .emoji('👍')
+            .style(ButtonStyle::Success);
+
+        let down_btn = CreateButton::new(button_ids::VOTE_DOWN)
+            .emoji('👎')
+            .style(ButtonStyle::Danger);
+
+        vec![up_btn, down_btn]
     }
 
     /// Crea controles de paginación para la cola
@@ -184,7 +228,6 @@ impl MusicControls {
     }
     
     /// Crea controles de confirmación para playlists
-    #[allow(dead_code)]
     pub fn create_playlist_confirmation_controls() -> CreateActionRow {
         let confirm_btn = CreateButton::new(button_ids::PLAYLIST_CONFIRM)
             .label("Sí, agregar playlist")
@@ -242,7 +285,6 @@ impl MusicControls {
     }
     
     /// Crea controles de playlist con carga progresiva
-    #[allow(dead_code)]
     pub fn create_playlist_loading_controls(progress: Option<(usize, usize)>) -> Vec<CreateActionRow> {
         let progress_text = if let Some((current, total)) = progress {
             format!("Cargando... {}/{}", current, total)
@@ -265,6 +307,26 @@ impl MusicControls {
         vec![row]
     }
     
+    /// Crea los botones de una ronda de `/quiz`
+    pub fn create_quiz_controls() -> Vec<CreateActionRow> {
+        let guess_btn = CreateButton::new(button_ids::QUIZ_GUESS)
+            .label("Adivinar")
+            .emoji('🎤')
+            .style(ButtonStyle::Primary);
+
+        let skip_btn = CreateButton::new(button_ids::QUIZ_SKIP)
+            .label("Saltar")
+            .emoji('⏭')
+            .style(ButtonStyle::Secondary);
+
+        let end_btn = CreateButton::new(button_ids::QUIZ_END)
+            .label("Terminar Quiz")
+            .emoji('⏹')
+            .style(ButtonStyle::Danger);
+
+        vec![CreateActionRow::Buttons(vec![guess_btn, skip_btn, end_btn])]
+    }
+
     /// Crea botones mejorados para el reproductor con más opciones
     #[allow(dead_code)]
     pub fn create_enhanced_player_buttons(
@@ -334,7 +396,22 @@ impl MusicControls {
 
         let row2 = CreateActionRow::Buttons(vec![restart_btn, vol_down_btn, vol_up_btn, queue_btn, effects_btn]);
 
-        vec![row1, row2]
+        // Tercera fila: votos, actualizar el progreso a mano y encolar similares
+        let refresh_btn = CreateButton::new(button_ids::REFRESH)
+            .label("Actualizar")
+            .emoji('🔃')
+            .style(ButtonStyle::Secondary);
+
+        let similar_btn = CreateButton::new(button_ids::SIMILAR)
+            .label("Similares")
+            .emoji('✨')
+            .style(ButtonStyle::Secondary);
+
+        let mut row3 = Self::vote_buttons();
+        row3.push(refresh_btn);
+        row3.push(similar_btn);
+
+        vec![row1, row2, CreateActionRow::Buttons(row3)]
     }
 }
 
@@ -353,6 +430,40 @@ pub fn create_enhanced_player_buttons(is_playing: bool, has_queue: bool, loop_mo
     MusicControls::create_enhanced_player_buttons(is_playing, has_queue, loop_mode)
 }
 
+/// Fila para saltar al capítulo anterior o siguiente; los extremos quedan deshabilitados
+pub fn create_chapter_navigation_buttons(chapters: &[Chapter], current_chapter_index: usize) -> Vec<CreateActionRow> {
+    if chapters.is_empty() {
+        return Vec::new();
+    }
+    let current = current_chapter_index.min(chapters.len() - 1);
+
+    let prev_btn = CreateButton::new(button_ids::PREV_CHAPTER)
+        .label("Capítulo anterior")
+        .emoji('⏮')
+        .style(ButtonStyle::Secondary)
+        .disabled(current == 0);
+
+    let progress_btn = CreateButton::new("chapter_progress")
+        .label(format!("📖 {}/{}", current + 1, chapters.len()))
+        .style(ButtonStyle::Secondary)
+        .disabled(true);
+
+    let next_btn = CreateButton::new(button_ids::NEXT_CHAPTER)
+        .label("Capítulo siguiente")
+        .emoji('⏭')
+        .style(ButtonStyle::Secondary)
+        .disabled(current + 1 >= chapters.len());
+
+    vec![CreateActionRow::Buttons(vec![prev_btn, progress_btn, next_btn])]
+}
+
+/// Navegación de capítulos de `track` en `position` (vacía si no tiene capítulos).
+/// Antes del primer capítulo se considera el primero.
+pub fn chapter_navigation_for(track: &TrackSource, position: Duration) -> Vec<CreateActionRow> {
+    let chapters = track.chapters().unwrap_or_default();
+    create_chapter_navigation_buttons(chapters, track.current_chapter_index(position).unwrap_or(0))
+}
+
 /// Crea botón de reintentar para errores
 #[allow(dead_code)]
 pub fn create_retry_button() -> CreateActionRow {
@@ -365,7 +476,6 @@ pub fn create_retry_button() -> CreateActionRow {
 }
 
 /// Crea botones de confirmación estándar (Sí/No)
-#[allow(dead_code)]
 pub fn create_confirmation_buttons(action_id: &str) -> CreateActionRow {
     let confirm_btn = CreateButton::new(format!("confirm_{}", action_id))
         .label("Sí, confirmar")
@@ -460,17 +570,17 @@ impl MusicEmbeds {
 
         let embed = CreateEmbed::default()
             .title(status)
-            .description(&track.title())
+            .description(&*track.title())
             .field(
                 "Artista",
-                track.artist().unwrap_or("Desconocido".to_string()),
+                track.artist().as_deref().unwrap_or("Desconocido"),
                 true,
             )
             .field("Duración", Self::format_duration(track.duration()), true)
             .field("Fuente", format!("{:?}", track.source_type()), true)
             .field("Progreso", progress_bar, false)
             .colour(Colour::from_rgb(255, 73, 108))
-            .thumbnail(track.thumbnail().unwrap_or_default())
+            .thumbnail(track.thumbnail().as_deref().unwrap_or_default())
             .footer(CreateEmbedFooter::new("🎵 Open Music Bot"))
             .timestamp(Timestamp::now());
         embed
@@ -584,14 +694,27 @@ pub async fn handle_music_component(
         .guild_id
         .ok_or_else(|| anyhow::anyhow!("No guild ID"))?;
 
+    if button_ids::PLAYBACK_CONTROLS.contains(&interaction.data.custom_id.as_str())
+        && !crate::bot::handlers::is_in_bot_channel(ctx, bot, guild_id, interaction.user.id).await
+    {
+        interaction.create_response(&ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                serenity::builder::CreateInteractionResponseMessage::new()
+                    .content(crate::bot::handlers::SAME_CHANNEL_REQUIRED)
+                    .ephemeral(true)
+            )
+        ).await?;
+        return Ok(());
+    }
+
     match interaction.data.custom_id.as_str() {
         button_ids::PLAY_PAUSE => {
             if player.is_playing(guild_id).await {
                 player.pause(guild_id).await?;
-                respond_with_updated_now_playing(ctx, interaction, guild_id, player, "⏸️ Música pausada").await?;
+                respond_with_updated_now_playing(ctx, interaction, guild_id, bot, "⏸️ Música pausada").await?;
             } else {
                 player.resume(guild_id).await?;
-                respond_with_updated_now_playing(ctx, interaction, guild_id, player, "▶️ Música reanudada").await?;
+                respond_with_updated_now_playing(ctx, interaction, guild_id, bot, "▶️ Música reanudada").await?;
             }
         }
         button_ids::SKIP => {
@@ -676,7 +799,9 @@ pub async fn handle_music_component(
         button_ids::VOLUME_UP => {
             interaction.defer(&ctx.http).await?;
             let current_volume = player.get_volume(guild_id).await.unwrap_or(0.5);
-            let new_volume = (current_volume + 0.1).min(2.0);
+            // El botón respeta el techo del servidor (sin bajar un volumen ya superior)
+            let (max_volume, _) = bot.storage.lock().await.get_volume_limit(guild_id.get());
+            let new_volume = (current_volume + 0.1).min(max_volume.max(current_volume)).min(2.0);
             
             if let Err(e) = player.set_volume(guild_id, new_volume).await {
                 error!("Error ajustando volumen: {:?}", e);
@@ -690,7 +815,8 @@ pub async fn handle_music_component(
         button_ids::QUEUE => {
             match player.get_queue_info(guild_id).await {
                 Ok(queue_info) => {
-                    let embed = crate::ui::embeds::create_queue_embed(&queue_info, 1);
+                    let settings = bot.guild_settings(guild_id).await;
+                    let embed = crate::ui::embeds::create_queue_embed(&queue_info, 1, Some(&settings));
                     interaction.create_response(&ctx.http, 
                         serenity::builder::CreateInteractionResponse::Message(
                             serenity::builder::CreateInteractionResponseMessage::new()
@@ -711,6 +837,158 @@ pub async fn handle_music_component(
                 }
             }
         }
+        button_ids::VOTE_UP | button_ids::VOTE_DOWN => {
+            let is_upvote = interaction.data.custom_id == button_ids::VOTE_UP;
+
+            let Some(current_track) = player.get_current_track(guild_id).await else {
+                interaction.create_response(&ctx.http,
+                    serenity::builder::CreateInteractionResponse::Message(
+                        serenity::builder::CreateInteractionResponseMessage::new()
+                            .content("❌ No hay nada reproduciéndose")
+                            .ephemeral(true)
+                    )
+                ).await?;
+                return Ok(());
+            };
+
+            let url = current_track.url();
+            VotingSystem::remember_title(&url, &current_track.title());
+
+            let message = match VotingSystem::vote(guild_id, &url, interaction.user.id, is_upvote) {
+                VoteOutcome::Recorded(up, down) => {
+                    let votes = VotingSystem::to_persisted(guild_id);
+                    if let Err(e) = bot.storage.lock().await.save_guild_votes(&votes).await {
+                        error!("Error guardando votos: {:?}", e);
+                    }
+                    format!(
+                        "{} Voto registrado para **{}** (👍 {} · 👎 {})",
+                        if is_upvote { "👍" } else { "👎" },
+                        current_track.title(),
+                        up,
+                        down
+                    )
+                }
+                VoteOutcome::AlreadyVoted => "🗳️ Ya votaste por esta canción".to_string(),
+            };
+
+            respond_with_updated_now_playing(ctx, interaction, guild_id, bot, &message).await?;
+        }
+        button_ids::REFRESH => refresh_now_playing(ctx, interaction, guild_id, bot).await?,
+        button_ids::SIMILAR => {
+            interaction.defer_ephemeral(&ctx.http).await?;
+
+            // Encola canciones: mismas comprobaciones que `/play`
+            match handlers::track_request_block(bot.audio_backend_available(), &bot.quizzes, guild_id) {
+                Some(TrackRequestBlock::AudioBackendUnavailable) => {
+                    update_response_embed(ctx, interaction, crate::ui::embeds::create_audio_backend_unavailable_embed()).await?;
+                    return Ok(());
+                }
+                Some(TrackRequestBlock::QuizActive) => {
+                    update_response(ctx, interaction, handlers::QUIZ_ACTIVE_MESSAGE).await?;
+                    return Ok(());
+                }
+                None => {}
+            }
+            let user = &interaction.user;
+            if let Some(embed) = handlers::check_request_spam(ctx, bot, guild_id, user.id, &user.name, "play").await {
+                update_response_embed(ctx, interaction, embed).await?;
+                return Ok(());
+            }
+
+            let Some(current_track) = player.get_current_track(guild_id).await else {
+                update_response(ctx, interaction, "❌ No hay nada reproduciéndose").await?;
+                return Ok(());
+            };
+
+            // Se piden de más: las bloqueadas o ya encoladas no cuentan
+            let similar = match bot.similar_tracks(guild_id, &current_track, SIMILAR_TRACKS * 2).await {
+                Ok(tracks) => tracks,
+                Err(e) => {
+                    warn!("Sin canciones similares a {}: {:?}", current_track.title(), e);
+                    Vec::new()
+                }
+            };
+
+            let filter = bot.content_filter(guild_id).await;
+            let queue = player.get_or_create_queue(guild_id);
+            let mut added = Vec::new();
+            {
+                let mut queue = queue.write();
+                for track in similar {
+                    if added.len() >= SIMILAR_TRACKS {
+                        break;
+                    }
+                    if filter.check(&track).is_err() || queue.contains_url(&track.url()) {
+                        continue;
+                    }
+                    let track = track.with_requested_by(interaction.user.id);
+                    if queue.add_track(track.clone()).is_ok() {
+                        added.push(track);
+                    }
+                }
+            }
+
+            let message = if added.is_empty() {
+                format!("🤷 No encontré canciones similares a **{}** para agregar", current_track.title())
+            } else {
+                info!("✨ {} canciones similares a {} encoladas en guild {}", added.len(), current_track.title(), guild_id);
+                format!("✨ Agregué {} canciones similares a **{}**", added.len(), current_track.title())
+            };
+            bot.spawn_queue_enrichment(guild_id, added);
+            update_response(ctx, interaction, &message).await?;
+        }
+        button_ids::PREV_CHAPTER | button_ids::NEXT_CHAPTER => {
+            let Some(current_track) = player.get_current_track(guild_id).await else {
+                interaction.create_response(&ctx.http,
+                    serenity::builder::CreateInteractionResponse::Message(
+                        serenity::builder::CreateInteractionResponseMessage::new()
+                            .content("❌ No hay nada reproduciéndose")
+                            .ephemeral(true)
+                    )
+                ).await?;
+                return Ok(());
+            };
+
+            // Posición y capítulos en tiempo de la canción (el reproductor ya
+            // descuenta `/tempo`); el margen evita que un seek que cae justo
+            // antes del inicio de un capítulo cuente como el anterior
+            let chapters = current_track.chapters().unwrap_or_default();
+            let position = player.get_playback_position(guild_id).await.unwrap_or_default();
+            let current = current_track.current_chapter_index(position + CHAPTER_SEEK_TOLERANCE).unwrap_or(0);
+            let target = if interaction.data.custom_id == button_ids::PREV_CHAPTER {
+                current.checked_sub(1)
+            } else {
+                Some(current + 1).filter(|next| *next < chapters.len())
+            };
+
+            let Some(chapter) = target.and_then(|index| chapters.get(index)) else {
+                interaction.create_response(&ctx.http,
+                    serenity::builder::CreateInteractionResponse::Message(
+                        serenity::builder::CreateInteractionResponseMessage::new()
+                            .content("📖 No hay más capítulos en esa dirección")
+                            .ephemeral(true)
+                    )
+                ).await?;
+                return Ok(());
+            };
+
+            match player.seek(guild_id, chapter.start()).await {
+                Ok(_) => {
+                    let message = format!("📖 Capítulo {}/{}: {}", target.unwrap_or(0) + 1, chapters.len(), chapter.title);
+                    respond_with_updated_now_playing(ctx, interaction, guild_id, bot, &message).await?;
+                }
+                Err(e) => {
+                    warn!("Seek a capítulo falló en guild {}: {:?}", guild_id, e);
+                    interaction.create_response(&ctx.http,
+                        serenity::builder::CreateInteractionResponse::Message(
+                            serenity::builder::CreateInteractionResponseMessage::new()
+                                .content("❌ No se pudo saltar a ese capítulo (en streaming solo se puede avanzar)")
+                                .ephemeral(true)
+                        )
+                    ).await?;
+                }
+            }
+        }
         button_ids::EFFECTS => {
             let eq_details = player.get_equalizer_details(guild_id);
             
@@ -916,7 +1194,7 @@ pub async fn handle_music_component(
             if let Ok(queue_info) = player.get_queue_info(guild_id).await {
                 let total_tracks = queue_info.total_items;
                 let preview_msg = format!(
-                    "👁️ **Vista Previa de la Cola**\n\n📊 **Estadísticas:**\n• Total de canciones: {}\n• Duración total: {}\n• Modo loop: {:?}\n• Shuffle: {}\n\n💡 Usa `/queue` para ver la lista completa",
+                    "👁️ **Vista Previa de la Cola**\n\n📊 **Estadísticas:**\n• Total de canciones: {}\n• Duración total: {}\n• Modo loop: {:?}\n• Shuffle: {}\n\n💡 Usa `/queue show` para ver la lista completa",
                     total_tracks,
                     if queue_info.total_duration.as_secs() > 0 {
                         format!("{} minutos", queue_info.total_duration.as_secs() / 60)
@@ -955,6 +1233,52 @@ pub async fn handle_music_component(
                 )
             ).await?;
         }
+        button_ids::PLAYLIST_CONFIRM => {
+            let message = match bot.playlist_loads.requested_by(guild_id) {
+                Some(requester) if requester == interaction.user.id => {
+                    bot.playlist_loads.confirm(guild_id);
+                    // El mensaje lo actualiza la carga de `/playlist`
+                    interaction.create_response(&ctx.http,
+                        serenity::builder::CreateInteractionResponse::Acknowledge
+                    ).await?;
+                    return Ok(());
+                }
+                Some(_) => "❌ Solo quien pidió la playlist puede confirmarla",
+                None => "⚠️ No hay ninguna playlist esperando confirmación",
+            };
+            interaction.create_response(&ctx.http,
+                serenity::builder::CreateInteractionResponse::Message(
+                    serenity::builder::CreateInteractionResponseMessage::new()
+                        .content(message)
+                        .ephemeral(true)
+                )
+            ).await?;
+        }
+        button_ids::PLAYLIST_CANCEL => {
+            let can_cancel = match bot.playlist_loads.requested_by(guild_id) {
+                Some(requester) if requester == interaction.user.id => Some(true),
+                Some(_) => Some(crate::bot::handlers::is_dj(ctx, guild_id, interaction.user.id, bot).await),
+                None => None,
+            };
+            let message = match can_cancel {
+                Some(true) => {
+                    bot.playlist_loads.cancel(guild_id);
+                    interaction.create_response(&ctx.http,
+                        serenity::builder::CreateInteractionResponse::Acknowledge
+                    ).await?;
+                    return Ok(());
+                }
+                Some(false) => "❌ Solo quien pidió la playlist o un DJ puede cancelarla",
+                None => "⚠️ No hay ninguna carga de playlist en curso",
+            };
+            interaction.create_response(&ctx.http,
+                serenity::builder::CreateInteractionResponse::Message(
+                    serenity::builder::CreateInteractionResponseMessage::new()
+                        .content(message)
+                        .ephemeral(true)
+                )
+            ).await?;
+        }
         _ => {
             debug!("Componente no manejado: {}", interaction.data.custom_id);
             interaction.create_response(&ctx.http,
@@ -989,17 +1313,91 @@ async fn update_response(
     Ok(())
 }
 
+/// Como [`update_response`], con un embed
+async fn update_response_embed(ctx: &Context, interaction: &ComponentInteraction, embed: CreateEmbed) -> Result<()> {
+    use serenity::builder::CreateInteractionResponseFollowup;
+    interaction
+        .create_followup(
+            &ctx.http,
+            CreateInteractionResponseFollowup::new().embed(embed).ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Vuelve a dibujar el mensaje de "reproduciendo ahora" con la posición actual.
+///
+/// Si la canción terminó entre que se envió el mensaje y se pulsó el botón, el
+/// mensaje pasa a indicarlo y pierde los controles.
+async fn refresh_now_playing(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    guild_id: GuildId,
+    bot: &OpenMusicBot,
+) -> Result<()> {
+    let current_track = bot.player.get_current_track(guild_id).await;
+    let position = bot.player.get_playback_position(guild_id).await;
+
+    let message = match (current_track, position) {
+        (Some(track), Some(position)) => {
+            let settings = bot.guild_settings(guild_id).await;
+            let rating = bot.average_rating(guild_id, &track);
+            let embed = crate::ui::embeds::create_now_playing_embed_from_source(&track, guild_id, Some(&settings), rating);
+            let embed = crate::ui::embeds::with_playback_progress(embed, position, track.duration());
+            let embed = crate::ui::embeds::with_ab_loop(embed, bot.player.ab_loop(guild_id));
+
+            let queue_info = bot.player.get_queue_info(guild_id).await?;
+            let embed = crate::ui::embeds::with_up_next(embed, &queue_info);
+            let loop_mode = format!("{:?}", queue_info.loop_mode).to_lowercase();
+            let is_playing = bot.player.is_playing(guild_id).await;
+            let buttons = create_enhanced_player_buttons(is_playing, queue_info.total_items > 0, &loop_mode);
+
+            serenity::builder::CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(buttons)
+        }
+        _ => {
+            debug!("🔃 Actualización sin canción activa en guild {}", guild_id);
+            let embed = crate::ui::embeds::create_info_embed(
+                "Reproducción terminada",
+                "Esta canción ya no está sonando. Usa `/nowplaying` para ver la actual.",
+            );
+            serenity::builder::CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(Vec::new())
+        }
+    };
+
+    interaction
+        .create_response(&ctx.http, serenity::builder::CreateInteractionResponse::UpdateMessage(message))
+        .await?;
+    Ok(())
+}
+
 /// Responde a la interacción con el embed actualizado de "now playing"
 async fn respond_with_updated_now_playing(
     ctx: &Context,
     interaction: &ComponentInteraction,
     guild_id: GuildId,
-    player: &AudioPlayer,
+    bot: &OpenMusicBot,
     ephemeral_message: &str,
 ) -> Result<()> {
-    if let Some(current_track) = player.get_current_track(guild_id).await {
-        let embed = crate::ui::embeds::create_now_playing_embed_from_source(&current_track);
-        let buttons = create_player_buttons();
+    if let Some(current_track) = bot.player.get_current_track(guild_id).await {
+        let settings = bot.guild_settings(guild_id).await;
+        let rating = bot.average_rating(guild_id, &current_track);
+        let mut embed = crate::ui::embeds::create_now_playing_embed_from_source(&current_track, guild_id, Some(&settings), rating);
+        let position = bot.player.get_playback_position(guild_id).await;
+        if let Some(position) = position {
+            embed = crate::ui::embeds::with_playback_progress(embed, position, current_track.duration());
+            embed = crate::ui::embeds::with_current_chapter(embed, &current_track, position);
+        }
+        if let Ok(queue_info) = bot.player.get_queue_info(guild_id).await {
+            embed = crate::ui::embeds::with_loop_count(embed, &queue_info);
+            embed = crate::ui::embeds::with_up_next(embed, &queue_info);
+        }
+        let mut buttons = create_player_buttons();
+        buttons.extend(chapter_navigation_for(&current_track, position.unwrap_or_default()));
 
         // Responder actualizando el mensaje original
         interaction.create_response(
@@ -1,22 +1,80 @@
 use serenity::{
-    all::{Colour, Timestamp},
+    all::{Colour, GuildId, Timestamp, UserId},
     builder::{CreateEmbed, CreateEmbedFooter, CreateActionRow},
 };
 use std::time::Duration;
 
 use crate::{
-    audio::queue::{LoopMode, QueueInfo, QueueItem},
-    sources::TrackSource,
-    bot::OpenMusicBot,
+    audio::{
+        effects::{EffectsState, EqualizerPreset, CUSTOM_EQ_FREQUENCIES},
+        player::{AbLoop, PlayerDebugState},
+        queue::{group_by_album, LoopMode, QueueInfo, QueueItem, SortField, SortKey, SortOrder},
+    },
+    audit::AuditEntry,
+    sources::{
+        BlockedTrackError, Chapter, ContentFilter, RestrictedVideoError, SearchPreferences, TrackFailureReason,
+        TrackSource,
+    },
+    bot::{voting::VotingSystem, OpenMusicBot},
+    storage::{
+        leaderboard::{LeaderboardScope, TrackPlayCount},
+        play_stats::GuildStatsSummary,
+        ratings::RatedTrack,
+        EditAction, GuildSettings, TrackVotes, UserPlaylist,
+    },
 };
 
+/// Color de un embed: el configurado por la guild (`/settings color`) o `default`
+pub fn get_guild_color(color_override: Option<[u8; 3]>, default: Colour) -> Colour {
+    match color_override {
+        Some([r, g, b]) => Colour::from_rgb(r, g, b),
+        None => default,
+    }
+}
+
+/// Color de un embed según la configuración de la guild, si se conoce
+fn themed_color(guild_settings: Option<&GuildSettings>, default: Colour) -> Colour {
+    get_guild_color(guild_settings.and_then(|settings| settings.embed_color), default)
+}
+
+/// Parsea un color hexadecimal (`#FF6B6B` o `FF6B6B`) a RGB
+pub fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
 /// Crea un embed para mostrar la canción actual desde TrackSource
-pub fn create_now_playing_embed_from_source(track: &TrackSource) -> CreateEmbed {
+pub fn create_now_playing_embed_from_source(
+    track: &TrackSource,
+    guild_id: GuildId,
+    guild_settings: Option<&GuildSettings>,
+    average_rating: Option<f32>,
+) -> CreateEmbed {
+    // En radios, el título es la canción que anuncia la emisora
+    if let Some(station) = track.station() {
+        return CreateEmbed::default()
+            .title("🎵 Reproduciendo Ahora")
+            .description(format!("🔴 **En vivo** · {}", track.title()))
+            .color(themed_color(guild_settings, colors::SUCCESS_GREEN))
+            .field("📻 Emisora", station, true)
+            .field("⏱️ Duración", "🔴 En vivo", true)
+            .field("👤 Solicitado por", format!("<@{}>", track.requested_by()), true)
+            .field("🔗 Fuente", "Radio", true)
+            .url(&*track.url())
+            .timestamp(Timestamp::now())
+            .footer(CreateEmbedFooter::new(STANDARD_FOOTER));
+    }
+
     let mut embed = CreateEmbed::default()
         .title("🎵 Reproduciendo Ahora")
         .description(format!("**{}**", track.title()))
-        .color(colors::SUCCESS_GREEN)
-        .field("🎤 Artista", track.artist().as_ref().unwrap_or(&"Desconocido".to_string()), true);
+        .color(themed_color(guild_settings, colors::SUCCESS_GREEN))
+        .field("🎤 Artista", track.artist().as_deref().unwrap_or("Desconocido"), true);
 
     if let Some(duration) = track.duration() {
         embed = embed.field("⏱️ Duración", format_duration(duration), true);
@@ -28,18 +86,146 @@ pub fn create_now_playing_embed_from_source(track: &TrackSource) -> CreateEmbed
         .field("👤 Solicitado por", format!("<@{}>", track.requested_by()), true)
         .field("🔗 Fuente", "YouTube", true);
 
+    let (upvotes, downvotes) = VotingSystem::votes(guild_id, &track.url());
+    embed = embed.field(
+        "🗳️ Votos",
+        format!("{:+} (👍 {} · 👎 {})", upvotes - downvotes, upvotes, downvotes),
+        true,
+    );
+    if let Some(average) = average_rating {
+        embed = embed.field("⭐ Valoración", rating_stars(average), true);
+    }
+
     if let Some(thumbnail) = track.thumbnail() {
-        embed = embed.thumbnail(&thumbnail);
+        embed = embed.thumbnail(&*thumbnail);
     }
 
     embed = embed
-        .url(&track.url())
+        .url(&*track.url())
         .timestamp(Timestamp::now())
         .footer(CreateEmbedFooter::new(STANDARD_FOOTER));
 
     embed
 }
 
+/// Promedio de valoración como estrellas redondeadas (`⭐⭐⭐⭐ 4.3/5`)
+pub fn rating_stars(average: f32) -> String {
+    let stars = (average.round() as usize).clamp(1, 5);
+    format!("{} {:.1}/5", "⭐".repeat(stars), average)
+}
+
+/// Segmentos de la barra de progreso de la canción actual
+const PLAYBACK_BAR_LEN: usize = 15;
+
+/// Barra de progreso y tiempo transcurrido de la canción actual
+/// (`` `▬▬▬🔘▬▬▬` 1:23 / 4:56 ``); los streams en vivo solo muestran lo transcurrido.
+pub fn playback_progress(position: Duration, total: Option<Duration>) -> String {
+    match total.filter(|total| !total.is_zero()) {
+        Some(total) => {
+            let ratio = (position.as_secs_f64() / total.as_secs_f64()).clamp(0.0, 1.0);
+            let filled = ((ratio * (PLAYBACK_BAR_LEN - 1) as f64).round() as usize).min(PLAYBACK_BAR_LEN - 1);
+            format!(
+                "`{}🔘{}` {} / {}",
+                "▬".repeat(filled),
+                "▬".repeat(PLAYBACK_BAR_LEN - 1 - filled),
+                format_duration(position.min(total)),
+                format_duration(total)
+            )
+        }
+        None => format!("🔴 En vivo · {}", format_duration(position)),
+    }
+}
+
+/// Agrega al embed de "reproduciendo ahora" el tiempo transcurrido
+pub fn with_playback_progress(embed: CreateEmbed, position: Duration, total: Option<Duration>) -> CreateEmbed {
+    embed.field("⏳ Progreso", playback_progress(position, total), false)
+}
+
+/// Canciones de la cola que se muestran en "A continuación"
+const UP_NEXT_LEN: usize = 3;
+
+/// Caracteres máximos de cada título en "A continuación", para que títulos muy
+/// largos no lleven el embed a los límites de Discord
+const UP_NEXT_TITLE_MAX_CHARS: usize = 60;
+
+/// Próximas canciones de la cola en formato compacto (`` `1.` Título · 3:20 ``),
+/// o `None` si no queda nada por sonar
+pub fn up_next_list(queue_info: &QueueInfo) -> Option<String> {
+    if queue_info.items.is_empty() {
+        return None;
+    }
+
+    let mut lines: Vec<String> = queue_info
+        .items
+        .iter()
+        .take(UP_NEXT_LEN)
+        .enumerate()
+        .map(|(i, item)| {
+            let title = if item.title.chars().count() > UP_NEXT_TITLE_MAX_CHARS {
+                let truncated: String = item.title.chars().take(UP_NEXT_TITLE_MAX_CHARS - 1).collect();
+                format!("{}…", truncated)
+            } else {
+                item.title.to_string()
+            };
+            let duration = item.duration.map(format_duration).unwrap_or_else(|| "🔴 En vivo".to_string());
+            format!("`{}.` {} · {}", i + 1, title, duration)
+        })
+        .collect();
+
+    let remaining = queue_info.items.len().saturating_sub(UP_NEXT_LEN);
+    if remaining > 0 {
+        lines.push(format!("…y {} más en `/queue`", remaining));
+    }
+    Some(lines.join("\n"))
+}
+
+/// Agrega al embed de "reproduciendo ahora" las próximas canciones de la cola
+pub fn with_up_next(embed: CreateEmbed, queue_info: &QueueInfo) -> CreateEmbed {
+    let Some(list) = up_next_list(queue_info) else {
+        return embed;
+    };
+    // Con shuffle la próxima se elige al azar: se muestra la cola, no un orden
+    let name = if queue_info.shuffle { "🔀 En la cola (orden aleatorio)" } else { "⏭️ A continuación" };
+    embed.field(name, list, false)
+}
+
+/// "🔂 Repetida 7×" si la canción actual volvió a sonar por loop
+fn loop_count_label(queue_info: &QueueInfo) -> Option<String> {
+    (queue_info.loop_count > 0).then(|| format!("🔂 Repetida {}×", queue_info.loop_count))
+}
+
+/// Agrega al embed de "reproduciendo ahora" el capítulo activo, con el
+/// avance ("📖 Capítulo 2/5: Introducción") en el pie
+pub fn with_current_chapter(embed: CreateEmbed, track: &TrackSource, position: Duration) -> CreateEmbed {
+    let (Some(chapter), Some(index)) = (track.current_chapter(position), track.current_chapter_index(position)) else {
+        return embed;
+    };
+    let total = track.chapters().map_or(0, <[Chapter]>::len);
+    embed
+        .field("📖 Capítulo", &chapter.title, false)
+        .footer(CreateEmbedFooter::new(format!("📖 Capítulo {}/{}: {}", index + 1, total, chapter.title)))
+}
+
+/// Agrega al embed de "reproduciendo ahora" las veces que se repitió la canción actual
+pub fn with_loop_count(embed: CreateEmbed, queue_info: &QueueInfo) -> CreateEmbed {
+    match loop_count_label(queue_info) {
+        Some(label) => embed.field("🔂 Loop", label, true),
+        None => embed,
+    }
+}
+
+/// Agrega al embed de "reproduciendo ahora" la sección A-B activa (`/ab-loop`)
+pub fn with_ab_loop(embed: CreateEmbed, ab_loop: Option<AbLoop>) -> CreateEmbed {
+    match ab_loop {
+        Some(ab_loop) => embed.field(
+            "🔁 A-B Loop",
+            format!("{} → {}", format_duration(ab_loop.start), format_duration(ab_loop.end)),
+            true,
+        ),
+        None => embed,
+    }
+}
+
 /// Paleta de colores estandarizada para el bot
 pub mod colors {
     use serenity::all::Colour;
@@ -59,12 +245,12 @@ const STANDARD_FOOTER: &str = "🎵 Open Music Bot";
 
 /// Crea un embed para mostrar la canción actual desde QueueItem
 #[allow(dead_code)]
-pub fn create_now_playing_embed(track: &QueueItem) -> CreateEmbed {
+pub fn create_now_playing_embed(track: &QueueItem, guild_settings: Option<&GuildSettings>) -> CreateEmbed {
     let mut embed = CreateEmbed::default()
         .title("🎵 Reproduciendo Ahora")
         .description(format!("**{}**", track.title))
-        .color(colors::SUCCESS_GREEN)
-        .field("🎤 Artista", track.artist.as_ref().unwrap_or(&"Desconocido".to_string()), true);
+        .color(themed_color(guild_settings, colors::SUCCESS_GREEN))
+        .field("🎤 Artista", track.artist.as_deref().unwrap_or("Desconocido"), true);
 
     if let Some(duration) = track.duration {
         embed = embed.field("⏱️ Duración", format_duration(duration), true);
@@ -77,11 +263,11 @@ pub fn create_now_playing_embed(track: &QueueItem) -> CreateEmbed {
         .field("🔗 Fuente", "YouTube", true);
 
     if let Some(thumbnail) = &track.thumbnail {
-        embed = embed.thumbnail(thumbnail);
+        embed = embed.thumbnail(&**thumbnail);
     }
 
     embed = embed
-        .url(&track.url)
+        .url(&*track.url)
         .timestamp(Timestamp::now())
         .footer(CreateEmbedFooter::new(STANDARD_FOOTER));
 
@@ -89,7 +275,7 @@ pub fn create_now_playing_embed(track: &QueueItem) -> CreateEmbed {
 }
 
 /// Crea un embed para mostrar que se agregó una canción
-pub fn create_track_added_embed(track: &TrackSource) -> CreateEmbed {
+pub fn create_track_added_embed(track: &TrackSource, guild_settings: Option<&GuildSettings>) -> CreateEmbed {
     let description = format!(
         "**{}** se ha agregado a la cola de reproducción",
         track.title()
@@ -98,8 +284,8 @@ pub fn create_track_added_embed(track: &TrackSource) -> CreateEmbed {
     let mut embed = CreateEmbed::default()
         .title("✅ Canción Agregada Exitosamente")
         .description(&description)
-        .color(colors::SUCCESS_GREEN)
-        .field("🎤 Artista", track.artist().as_ref().unwrap_or(&"Desconocido".to_string()), true);
+        .color(themed_color(guild_settings, colors::SUCCESS_GREEN))
+        .field("🎤 Artista", track.artist().as_deref().unwrap_or("Desconocido"), true);
 
     if let Some(duration) = track.duration() {
         embed = embed.field("⏱️ Duración", format_duration(duration), true);
@@ -112,11 +298,11 @@ pub fn create_track_added_embed(track: &TrackSource) -> CreateEmbed {
         .field("🔗 Fuente", "YouTube", true);
 
     if let Some(thumbnail) = track.thumbnail() {
-        embed = embed.thumbnail(&thumbnail);
+        embed = embed.thumbnail(&*thumbnail);
     }
 
     embed = embed
-        .url(&track.url())
+        .url(&*track.url())
         .timestamp(Timestamp::now())
         .footer(CreateEmbedFooter::new("🎵 Se reproducirá automáticamente si no hay música sonando"));
 
@@ -124,13 +310,13 @@ pub fn create_track_added_embed(track: &TrackSource) -> CreateEmbed {
 }
 
 /// Crea un embed para mostrar la cola de reproducción
-pub fn create_queue_embed(queue_info: &QueueInfo, page: usize) -> CreateEmbed {
+pub fn create_queue_embed(queue_info: &QueueInfo, page: usize, guild_settings: Option<&GuildSettings>) -> CreateEmbed {
     let items_per_page = 10;
     let queue_page = queue_info.get_page(page, items_per_page);
 
     let mut embed = CreateEmbed::default()
         .title("📋 Cola de Reproducción")
-        .color(colors::INFO_BLUE);
+        .color(themed_color(guild_settings, colors::INFO_BLUE));
 
     if queue_info.total_items == 0 {
         return embed
@@ -143,7 +329,7 @@ pub fn create_queue_embed(queue_info: &QueueInfo, page: usize) -> CreateEmbed {
     // Canción actual
     if let Some(current) = &queue_info.current {
         let status = match queue_info.loop_mode {
-            LoopMode::Track => "🔂",
+            LoopMode::Track | LoopMode::TrackCount(_) => "🔂",
             LoopMode::Queue => "🔁",
             LoopMode::Off => "▶️",
         };
@@ -163,15 +349,28 @@ pub fn create_queue_embed(queue_info: &QueueInfo, page: usize) -> CreateEmbed {
             }
         );
 
+        let current_display = match loop_count_label(queue_info) {
+            Some(label) => format!("{}\n{}", current_display, label),
+            None => current_display,
+        };
+
         embed = embed.field(format!("{} Reproduciendo", status), current_display, false);
     }
 
     // Próximas canciones con agrupación mejorada
     if !queue_page.items.is_empty() {
         let mut description = String::new();
+        let mut position = page.saturating_sub(1) * items_per_page;
+
+        for group in group_by_album(&queue_page.items) {
+            if let Some(line) = album_group_line(group, position + 1) {
+                description.push_str(&line);
+                position += group.len();
+                continue;
+            }
 
-        for (i, item) in queue_page.items.iter().enumerate() {
-            let position = page.saturating_sub(1) * items_per_page + i + 1;
+            let item = &group[0];
+            position += 1;
             let duration = if let Some(dur) = item.duration {
                 format!(" `[{}]`", format_duration(dur))
             } else {
@@ -226,11 +425,16 @@ pub fn create_queue_embed(queue_info: &QueueInfo, page: usize) -> CreateEmbed {
         info.push_str("\n**➡️ Modo:** Secuencial");
     }
 
+    if let Some(seed) = queue_info.shuffle_seed {
+        info.push_str(&format!("\n🔀 Shuffled (seed: {})", seed));
+    }
+
     // Información de loop
     let loop_text = match queue_info.loop_mode {
-        LoopMode::Track => "🔂 Repetir canción",
-        LoopMode::Queue => "🔁 Repetir cola",
-        LoopMode::Off => "➡️ Sin repetición",
+        LoopMode::Track => "🔂 Repetir canción".to_string(),
+        LoopMode::TrackCount(remaining) => format!("🔂 Repetir canción ({} restantes)", remaining),
+        LoopMode::Queue => "🔁 Repetir cola".to_string(),
+        LoopMode::Off => "➡️ Sin repetición".to_string(),
     };
     info.push_str(&format!("\n**{}**", loop_text));
 
@@ -275,8 +479,16 @@ pub fn create_enhanced_queue_embed(queue_info: &QueueInfo, page: usize, _show_pl
         description.push_str(&format!("🎵 **Reproduciendo ahora:** {}\n\n", current.title));
     }
 
-    for (i, item) in items.iter().enumerate() {
-        let position = start_idx + i + 1;
+    let mut position = start_idx;
+    for group in group_by_album(items) {
+        if let Some(line) = album_group_line(group, position + 1) {
+            description.push_str(&line);
+            position += group.len();
+            continue;
+        }
+
+        let item = &group[0];
+        position += 1;
         let duration = item.duration.map(|d| format!(" ({})", format_duration(d))).unwrap_or_default();
         description.push_str(&format!("{}. {} {}\n", position, item.title, duration));
     }
@@ -291,6 +503,30 @@ pub fn create_enhanced_queue_embed(queue_info: &QueueInfo, page: usize, _show_pl
     embed
 }
 
+/// Línea única para un tramo de canciones del mismo álbum que empieza en
+/// `first_position`; `None` si el grupo es una sola canción y se lista normal.
+fn album_group_line(group: &[QueueItem], first_position: usize) -> Option<String> {
+    let album = group.first()?.source.album_name()?;
+    if group.len() < 2 {
+        return None;
+    }
+
+    let total: Duration = group.iter().filter_map(|item| item.duration).sum();
+    let duration = if total > Duration::ZERO {
+        format!(" `[{}]`", format_duration(total))
+    } else {
+        String::new()
+    };
+    Some(format!(
+        "💿 **{}-{}**. Álbum: {} — {} canciones{}\n",
+        first_position,
+        first_position + group.len() - 1,
+        album,
+        group.len(),
+        duration
+    ))
+}
+
 /// Crea una barra de progreso para la paginación
 fn create_pagination_bar(current: usize, total: usize) -> String {
     if total <= 1 {
@@ -319,28 +555,72 @@ pub fn create_help_embed() -> CreateEmbed {
             • `/skip [cantidad]` - Salta canciones\n\
             • `/previous` - Vuelve a la canción anterior\n\
             • `/restart` - Reinicia la canción actual\n\
+            • `/restart-playback` - Recupera la canción si se quedó en silencio\n\
+            • `/seek <tiempo>` - Salta a una posición\n\
+            • `/ab-loop set <inicio> <fin>` / `/ab-loop clear` - Repite una sección\n\
+            • `/chapters` - Capítulos del video actual\n\
+            • `/radio <url>` - Reproduce una radio por internet\n\
             • `/stop` - Detiene y limpia la cola",
             false,
         )
         .field(
             "📜 Cola",
-            "• `/queue [página]` - Muestra la cola\n\
-            • `/shuffle` - Activa/desactiva aleatorio\n\
-            • `/loop <modo>` - Configura repetición\n\
+            "• `/queue show [página]` - Muestra la cola\n\
+            • `/queue sort <criterio> [orden]` - Ordena la cola\n\
+            • `/queue shuffle-remaining` - Mezcla solo las pendientes\n\
+            • `/queue loop-stats` - Cuántas veces se repitió la actual 🔂\n\
+            • `/queue-manage` - Reordena la cola con menús 🛠️\n\
+            • `/undo` - Deshace el último `/clear` o `/remove` ↩️\n\
+            • `/leaderboard [período]` - Canciones más escuchadas 🏆\n\
+            • `/top-rated` - Canciones mejor valoradas ⭐\n\
+            • `/stats guild` - Estadísticas de música del servidor 📊\n\
+            • `/quiz start [minutos]` - Quiz: adivina canciones del historial 🎧\n\
+            • `/insert <canción> <posición>` - Inserta en una posición de la cola\n\
+            • `/skipto-user <usuario>` - Salta a la próxima canción de un usuario\n\
+            • `/find <búsqueda>` - Busca una canción en la cola\n\
+            • `/queue-sort <criterio>` - Ordena la cola (título, artista, duración, solicitante o aleatorio)\n\
+            • `/queue-save <nombre>` - Guarda la cola como playlist\n\
+            • `/queue-load <playlist>` - Carga una playlist guardada\n\
+            • `/playlists list|add-editor|add-track|activity` - Playlists guardadas y colaborativas 👥\n\
+            • `/playlists export|import` - Exporta o importa playlists en M3U8\n\
+            • `/shuffle [now] [seed]` - Mezcla la cola o alterna aleatorio\n\
+            • `/loop <modo> [times]` - Configura repetición\n\
             • `/clear [filtro]` - Limpia la cola",
             false,
         )
         .field(
             "🎛️ Audio",
             "• `/volume [nivel]` - Ajusta el volumen\n\
-            • `/equalizer <preset>` - Aplica ecualizador",
+            • `/equalizer <preset>` - Aplica ecualizador\n\
+            • `/eq band <Hz> <dB>` - Ajusta una banda del ecualizador\n\
+            • `/speed <0.5-2.0>` - Velocidad sin cambiar el tono\n\
+            • `/effects show|clear` - Estado de los efectos o restablecerlos",
             false,
         )
         .field(
             "🔊 Conexión",
             "• `/join` - Conecta al canal de voz\n\
+            • `/move-channel` - Mueve el bot a tu canal\n\
             • `/leave` - Desconecta del canal\n\
-            • `/nowplaying` - Muestra canción actual",
+            • `/session start|end` - Sesión de música en un hilo\n\
+            • `/nowplaying` - Muestra canción actual\n\
+            • `/notify on|off` - Aviso por DM cuando suena tu canción\n\
+            • `/my-data export|delete` - Descargar o borrar tus datos",
+            false,
+        )
+        .field(
+            "⚙️ Servidor",
+            "• `/prefix [valor|off]` - Activa comandos como `!play` (opcional)\n\
+            • `/settings search` - Ranking de búsqueda (duración, versiones, oficiales)\n\
+            • `/settings url-detect on|off` - Reaccionar con ▶️ a enlaces en mensajes\n\
+            • `/settings same-channel on|off` - Controlar la música solo desde su canal de voz\n\
+            • `/settings single-now-playing on|off` - Un solo panel de reproducción en el canal\n\
+            • `/settings bot-nickname <patrón|off>` - Apodo con la canción actual\n\
+            • `/settings color <#RRGGBB|reset>` - Color de los embeds de música\n\
+            • `/settings max-volume <10-200> [dj_bypass]` - Techo de `/volume`\n\
+            • `/settings block keyword|channel|sources|list` - Filtro de contenido\n\
+            • `/audit [página]` - Historial de comandos (administradores)\n\
+            • `/export-config` / `/import-config` - Copiar la configuración entre servidores",
             false,
         )
         .field(
@@ -348,7 +628,7 @@ pub fn create_help_embed() -> CreateEmbed {
             "• YouTube / YouTube Music\n\
             • Spotify (metadata)\n\
             • SoundCloud\n\
-            • Tidal HiFi\n\
+            • Tidal (metadata → YouTube)\n\
             • URLs directas de audio",
             false,
         )
@@ -390,12 +670,19 @@ pub fn create_command_help_embed(command: &str) -> CreateEmbed {
         "queue" => {
             embed = embed
                 .title("📜 Comando /queue")
-                .description("Muestra la cola de reproducción actual")
-                .field("Uso", "`/queue [página]`", false)
+                .description("Muestra u ordena la cola de reproducción actual")
+                .field(
+                    "Uso",
+                    "`/queue show [página]`\n`/queue sort <duration|title|requester> [asc|desc]`\n`/queue shuffle-remaining`\n`/queue loop-stats`",
+                    false,
+                )
                 .field(
                     "Ejemplos",
-                    "• `/queue` - Primera página\n\
-                    • `/queue 2` - Página 2",
+                    "• `/queue show` - Primera página\n\
+                    • `/queue show 2` - Página 2\n\
+                    • `/queue sort duration desc` - Las más largas primero\n\
+                    • `/queue shuffle-remaining` - Mezcla las pendientes sin mover la actual\n\
+                    • `/queue loop-stats` - Veces que se repitió la canción actual",
                     false,
                 );
         }
@@ -423,6 +710,332 @@ pub fn create_command_help_embed(command: &str) -> CreateEmbed {
     embed.footer(CreateEmbedFooter::new("Open Music Bot"))
 }
 
+/// Crea el embed del ranking de canciones mejor valoradas (`/top-rated`)
+pub fn create_top_rated_embed(ranked: &[RatedTrack]) -> CreateEmbed {
+    let description = if ranked.is_empty() {
+        "Todavía no hay valoraciones en este servidor.\nValora las canciones con los botones ⭐ al terminar cada una.".to_string()
+    } else {
+        ranked
+            .iter()
+            .enumerate()
+            .map(|(i, track)| {
+                format!(
+                    "**{}.** [{}]({}) — {} ({} {})",
+                    i + 1,
+                    track.title,
+                    track.url,
+                    rating_stars(track.average),
+                    track.count,
+                    if track.count == 1 { "valoración" } else { "valoraciones" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title("⭐ Canciones Mejor Valoradas")
+        .description(description)
+        .color(colors::MUSIC_PURPLE)
+        .timestamp(Timestamp::now())
+        .footer(CreateEmbedFooter::new(STANDARD_FOOTER))
+}
+
+/// Ancho (en caracteres) de las barras de `/stats guild`
+const STATS_BAR_WIDTH: usize = 12;
+
+/// Nombres cortos de los días, de lunes a domingo
+const WEEKDAY_NAMES: [&str; 7] = ["Lun", "Mar", "Mié", "Jue", "Vie", "Sáb", "Dom"];
+
+/// Barra horizontal proporcional a `value / max` con bloques Unicode
+/// (octavos de bloque para la fracción final)
+pub fn stats_bar(value: u64, max: u64, width: usize) -> String {
+    const PARTIAL: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+    if max == 0 {
+        return " ".repeat(width);
+    }
+    let eighths = (value.min(max) * width as u64 * 8 / max) as usize;
+    let bar = "█".repeat(eighths / 8) + PARTIAL[eighths % 8];
+    let padding = width.saturating_sub(bar.chars().count());
+    bar + &" ".repeat(padding)
+}
+
+/// Tiempo escuchado como "3d 4h 12m"
+fn format_listening_time(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+/// Crea el embed de `/stats guild` con gráficos de barras
+pub fn create_guild_stats_embed(guild_name: &str, summary: &GuildStatsSummary) -> CreateEmbed {
+    let mut embed = CreateEmbed::default()
+        .title(format!("📊 Estadísticas de {}", guild_name))
+        .color(colors::MUSIC_PURPLE)
+        .field("🎵 Reproducciones", summary.total_plays.to_string(), true)
+        .field("⏱️ Tiempo escuchado", format_listening_time(summary.listening_time), true)
+        .field("💿 Canciones distintas", summary.unique_tracks.to_string(), true);
+
+    if let Some((title, url, plays)) = &summary.most_played {
+        embed = embed.field(
+            "🔁 Más escuchada",
+            format!("[{}]({}) — {} {}", title, url, plays, if *plays == 1 { "vez" } else { "veces" }),
+            false,
+        );
+    }
+
+    if !summary.top_requesters.is_empty() {
+        let max = summary.top_requesters[0].1;
+        let lines = summary
+            .top_requesters
+            .iter()
+            .zip(["🥇", "🥈", "🥉"])
+            .map(|((user_id, plays), medal)| {
+                format!("{} `{}` {} <@{}>", medal, stats_bar(*plays, max, STATS_BAR_WIDTH), plays, user_id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        embed = embed.field("🏆 Quién más pide", lines, false);
+    }
+
+    let week_max = summary.this_week.max(summary.last_week);
+    let trend = match (summary.this_week, summary.last_week) {
+        (_, 0) => String::new(),
+        (this, last) => {
+            let change = (this as f64 - last as f64) / last as f64 * 100.0;
+            format!("\n{} {:+.0}%", if change >= 0.0 { "📈" } else { "📉" }, change)
+        }
+    };
+    embed = embed.field(
+        "📅 Esta semana vs. la anterior",
+        format!(
+            "```\nÚltimos 7 días {} {}\n7 anteriores   {} {}\n```{}",
+            stats_bar(summary.this_week, week_max, STATS_BAR_WIDTH),
+            summary.this_week,
+            stats_bar(summary.last_week, week_max, STATS_BAR_WIDTH),
+            summary.last_week,
+            trend
+        ),
+        false,
+    );
+
+    let day_max = summary.by_weekday.iter().copied().max().unwrap_or_default();
+    let days = WEEKDAY_NAMES
+        .iter()
+        .zip(summary.by_weekday)
+        .map(|(name, plays)| format!("{} {} {}", name, stats_bar(plays, day_max, STATS_BAR_WIDTH), plays))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let peak = summary
+        .peak_day()
+        .map(|day| format!("Día pico: **{}**", WEEKDAY_NAMES[day.num_days_from_monday() as usize]))
+        .unwrap_or_default();
+    embed
+        .field("📆 Actividad por día", format!("```\n{}\n```{}", days, peak), false)
+        .timestamp(Timestamp::now())
+        .footer(CreateEmbedFooter::new(STANDARD_FOOTER))
+}
+
+/// Crea el embed de una página de `/leaderboard`; `first_rank` es el puesto
+/// de la primera canción de la página (1 en la primera)
+pub fn create_play_leaderboard_embed(
+    tracks: &[TrackPlayCount],
+    scope: LeaderboardScope,
+    first_rank: usize,
+    page: usize,
+    total_pages: usize,
+) -> CreateEmbed {
+    let description = if tracks.is_empty() {
+        format!("No sonó ninguna canción en este servidor ({}).", scope.label())
+    } else {
+        tracks
+            .iter()
+            .enumerate()
+            .map(|(i, track)| {
+                let rank = first_rank + i;
+                let medal = match rank {
+                    1 => "🥇".to_string(),
+                    2 => "🥈".to_string(),
+                    3 => "🥉".to_string(),
+                    _ => format!("**{}.**", rank),
+                };
+                format!(
+                    "{} [{}]({}) — **{}** {} · {}\n└ Última vez: {}",
+                    medal,
+                    track.title,
+                    track.url,
+                    track.play_count,
+                    if track.play_count == 1 { "vez" } else { "veces" },
+                    format_listening_time(track.total_duration_played),
+                    track.last_played.format("%d/%m/%Y")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title(format!("🏆 Más Escuchadas — {}", scope.label()))
+        .description(description)
+        .color(colors::MUSIC_PURPLE)
+        .timestamp(Timestamp::now())
+        .footer(CreateEmbedFooter::new(format!("Página {}/{} · {}", page, total_pages, STANDARD_FOOTER)))
+}
+
+/// Crea el embed de una ronda de `/quiz`
+pub fn create_quiz_round_embed(round: u8, total_rounds: u8, snippet: Duration, timeout: Duration) -> CreateEmbed {
+    CreateEmbed::default()
+        .title(format!("🎧 Quiz musical — Ronda {}/{}", round, total_rounds))
+        .description(format!(
+            "Suenan los primeros **{} segundos** de una canción del historial.\n\
+            Pulsa **Adivinar** y escribe el título; gana el punto el primero que acierte.\n\
+            La ronda termina en {} segundos.",
+            snippet.as_secs(),
+            timeout.as_secs()
+        ))
+        .color(colors::MUSIC_PURPLE)
+        .footer(CreateEmbedFooter::new(STANDARD_FOOTER))
+}
+
+/// Crea el embed que revela la canción al cerrar una ronda de `/quiz`
+pub fn create_quiz_reveal_embed(track: &TrackSource, winner: Option<UserId>) -> CreateEmbed {
+    let (title, color) = match winner {
+        Some(_) => ("✅ ¡Respuesta correcta!", colors::SUCCESS_GREEN),
+        None => ("⏱️ Nadie acertó", colors::NEUTRAL_GRAY),
+    };
+    let mut description = format!("Era **[{}]({})**", track.title(), track.url());
+    if let Some(user_id) = winner {
+        description.push_str(&format!("\n<@{}> se lleva el punto", user_id));
+    }
+
+    let mut embed = CreateEmbed::default()
+        .title(title)
+        .description(description)
+        .color(color);
+    if let Some(thumbnail) = track.thumbnail() {
+        embed = embed.thumbnail(thumbnail.to_string());
+    }
+    embed
+}
+
+/// Crea el embed con la tabla final de `/quiz`
+pub fn create_quiz_leaderboard_embed(ranked: &[(UserId, u32)], rounds_played: u8) -> CreateEmbed {
+    let description = if ranked.is_empty() {
+        "Nadie sumó puntos esta vez. ¡Suerte en la próxima!".to_string()
+    } else {
+        ranked
+            .iter()
+            .enumerate()
+            .map(|(i, (user_id, points))| {
+                let medal = match i {
+                    0 => "🥇".to_string(),
+                    1 => "🥈".to_string(),
+                    2 => "🥉".to_string(),
+                    _ => format!("**{}.**", i + 1),
+                };
+                format!("{} <@{}> — **{}** {}", medal, user_id, points, if *points == 1 { "punto" } else { "puntos" })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title("🏁 Fin del Quiz Musical")
+        .description(description)
+        .color(colors::MUSIC_PURPLE)
+        .timestamp(Timestamp::now())
+        .footer(CreateEmbedFooter::new(format!("{} rondas jugadas · {}", rounds_played, STANDARD_FOOTER)))
+}
+
+/// Crea el embed del ranking de canciones más votadas
+pub fn create_leaderboard_embed(ranked: &[(&String, &TrackVotes)]) -> CreateEmbed {
+    let description = if ranked.is_empty() {
+        "Todavía no hay votos en este servidor.\nVota con 👍/👎 en el mensaje de reproducción.".to_string()
+    } else {
+        ranked
+            .iter()
+            .enumerate()
+            .map(|(i, (url, votes))| {
+                let medal = match i {
+                    0 => "🥇".to_string(),
+                    1 => "🥈".to_string(),
+                    2 => "🥉".to_string(),
+                    _ => format!("**{}.**", i + 1),
+                };
+                format!(
+                    "{} [{}]({}) — **{:+}** (👍 {} · 👎 {})",
+                    medal,
+                    votes.title,
+                    url,
+                    votes.score(),
+                    votes.upvotes,
+                    votes.downvotes
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title("🏆 Canciones Más Votadas")
+        .description(description)
+        .color(colors::MUSIC_PURPLE)
+        .timestamp(Timestamp::now())
+        .footer(CreateEmbedFooter::new(STANDARD_FOOTER))
+}
+
+/// Crea el embed de una página del log de auditoría (`/audit`)
+pub fn create_audit_embed(entries: &[AuditEntry], page: usize, total_pages: usize) -> CreateEmbed {
+    let description = if entries.is_empty() {
+        "Todavía no hay comandos registrados en este servidor.".to_string()
+    } else {
+        entries
+            .iter()
+            .map(|entry| {
+                let mut line = format!(
+                    "<t:{}:R> **/{}** · <@{}>",
+                    entry.timestamp.timestamp(),
+                    entry.command,
+                    entry.user_id
+                );
+                if !entry.details.success {
+                    line.push_str(" ❌");
+                }
+
+                let details = &entry.details;
+                let mut extra = Vec::new();
+                if let Some(query) = &details.query {
+                    extra.push(format!("`{}`", query.chars().take(60).collect::<String>()));
+                }
+                if let (Some(before), Some(after)) = (details.queue_before, details.queue_after) {
+                    if before != after {
+                        extra.push(format!("cola {} → {}", before, after));
+                    }
+                }
+                if let Some(volume) = details.volume {
+                    extra.push(format!("🔊 {}%", volume));
+                }
+                if !extra.is_empty() {
+                    line.push_str(&format!("\n└ {}", extra.join(" · ")));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title("📜 Registro de Auditoría")
+        .description(description)
+        .color(colors::NEUTRAL_GRAY)
+        .footer(CreateEmbedFooter::new(format!("Página {}/{}", page, total_pages)))
+        .timestamp(Timestamp::now())
+}
+
 /// Crea un embed de error
 #[allow(dead_code)]
 pub fn create_error_embed(title: &str, description: &str) -> CreateEmbed {
@@ -445,6 +1058,417 @@ pub fn create_success_embed(title: &str, description: &str) -> CreateEmbed {
         .footer(CreateEmbedFooter::new("Open Music Bot"))
 }
 
+/// Crea el DM que avisa al usuario de que su canción empezó a sonar (`/notify`)
+pub fn create_track_started_dm_embed(title: &str, guild_name: &str) -> CreateEmbed {
+    CreateEmbed::default()
+        .description(format!("🎵 Tu canción **{}** está sonando en {}", title, guild_name))
+        .color(colors::MUSIC_PURPLE)
+        .footer(CreateEmbedFooter::new("Desactívalo con /notify off"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed con las coincidencias de `/find` (posición 0 = canción actual)
+pub fn create_find_results_embed(query: &str, results: &[(usize, TrackSource)], total_matches: usize) -> CreateEmbed {
+    let mut description = String::new();
+    for (position, track) in results {
+        let marker = if *position == 0 { "▶️ sonando".to_string() } else { format!("#{}", position) };
+        description.push_str(&format!(
+            "`{}` **{}**{} · <@{}>\n",
+            marker,
+            track.title(),
+            track.artist().map(|artist| format!(" - {}", artist)).unwrap_or_default(),
+            track.requested_by()
+        ));
+    }
+
+    let footer = if total_matches > results.len() {
+        format!("Mostrando {} de {} coincidencias", results.len(), total_matches)
+    } else {
+        format!("{} coincidencias", total_matches)
+    };
+
+    CreateEmbed::default()
+        .title(format!("🔎 \"{}\" en la cola", query))
+        .description(description)
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new(footer))
+        .timestamp(Timestamp::now())
+}
+
+/// Canciones de la cola que se muestran tras `/queue sort` y `/queue shuffle-remaining`
+const SORT_PREVIEW_LEN: usize = 5;
+
+/// Crea el embed de confirmación de `/queue sort` con las primeras canciones del nuevo orden
+pub fn create_queue_sort_key_embed(key: SortKey, order: SortOrder, items: &[QueueItem]) -> CreateEmbed {
+    let direction = match order {
+        SortOrder::Asc => "ascendente",
+        SortOrder::Desc => "descendente",
+    };
+
+    let mut preview = String::new();
+    for (i, item) in items.iter().take(SORT_PREVIEW_LEN).enumerate() {
+        let detail = match key {
+            SortKey::Duration => item.duration.map(format_duration).unwrap_or_else(|| "🔴 En vivo".to_string()),
+            SortKey::Title => item.artist.as_deref().unwrap_or("Desconocido").to_string(),
+            SortKey::Requester => format!("<@{}>", item.requested_by),
+        };
+        preview.push_str(&format!("`{}.` **{}** · {}\n", i + 1, item.title, detail));
+    }
+    if items.len() > SORT_PREVIEW_LEN {
+        preview.push_str(&format!("*...y {} más*", items.len() - SORT_PREVIEW_LEN));
+    }
+
+    CreateEmbed::default()
+        .title("🔃 Cola Ordenada")
+        .description(format!(
+            "{} canciones ordenadas por **{}** ({})",
+            items.len(),
+            key.display_name(),
+            direction
+        ))
+        .field("📋 Nuevo orden", preview, false)
+        .color(colors::SUCCESS_GREEN)
+        .footer(CreateEmbedFooter::new("La canción actual sigue sonando"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de `/queue-sort` con el criterio y las tres primeras canciones
+pub fn create_queue_sorted_embed(field: SortField, sorted: usize, items: &[QueueItem]) -> CreateEmbed {
+    let preview = items
+        .iter()
+        .take(3)
+        .enumerate()
+        .map(|(i, item)| {
+            format!(
+                "`{}.` **{}**{}",
+                i + 1,
+                item.title,
+                item.artist.as_ref().map(|artist| format!(" - {}", artist)).unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CreateEmbed::default()
+        .title("↕️ Cola Ordenada")
+        .description(format!("{} canciones ordenadas por **{}**", sorted, field.display_name()))
+        .field("🔜 Ahora siguen", preview, false)
+        .color(colors::SUCCESS_GREEN)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de confirmación de `/queue shuffle-remaining` con las primeras canciones del nuevo orden
+pub fn create_queue_shuffled_embed(shuffled: usize, items: &[QueueItem]) -> CreateEmbed {
+    let mut preview = String::new();
+    for (i, item) in items.iter().take(SORT_PREVIEW_LEN).enumerate() {
+        let pin = if item.pinned { " 📍" } else { "" };
+        preview.push_str(&format!("`{}.` **{}**{}\n", i + 1, item.title, pin));
+    }
+    if items.len() > SORT_PREVIEW_LEN {
+        preview.push_str(&format!("*...y {} más*", items.len() - SORT_PREVIEW_LEN));
+    }
+
+    let pinned = items.len() - shuffled;
+    let mut description = format!("{} canciones pendientes mezcladas", shuffled);
+    if pinned > 0 {
+        description.push_str(&format!("\n📍 {} colocadas con `/insert` siguen en su posición", pinned));
+    }
+
+    CreateEmbed::default()
+        .title("🔀 Cola Mezclada")
+        .description(description)
+        .field("📋 Nuevo orden", preview, false)
+        .color(colors::SUCCESS_GREEN)
+        .footer(CreateEmbedFooter::new("La canción actual sigue sonando"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de `/queue loop-stats` para la canción actual
+pub fn create_loop_stats_embed(current: &QueueItem, loop_mode: LoopMode, loop_count: u32) -> CreateEmbed {
+    let mode = match loop_mode {
+        LoopMode::Track => "🔂 Repetir canción".to_string(),
+        LoopMode::TrackCount(remaining) => format!("🔂 Repetir canción ({} restantes)", remaining),
+        LoopMode::Queue => "🔁 Repetir cola".to_string(),
+        LoopMode::Off => "➡️ Sin repetición".to_string(),
+    };
+    let times = match loop_count {
+        0 => "Todavía no se repitió".to_string(),
+        1 => "1 vez".to_string(),
+        n => format!("{} veces", n),
+    };
+
+    CreateEmbed::default()
+        .title("🔂 Repeticiones")
+        .description(format!("**{}**", current.title))
+        .field("Repetida", times, true)
+        .field("Modo", mode, true)
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new("El contador se reinicia al cambiar de canción o desactivar el loop"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de confirmación de `/queue-save`
+pub fn create_queue_saved_embed(playlist: &UserPlaylist) -> CreateEmbed {
+    CreateEmbed::default()
+        .title("💾 Cola Guardada")
+        .description(format!("Playlist **{}** creada", playlist.name))
+        .field("🆔 ID", format!("`{}`", playlist.id), false)
+        .field("🎵 Canciones", playlist.tracks.len().to_string(), true)
+        .field("⏱️ Duración", format_duration(playlist.total_duration()), true)
+        .color(colors::SUCCESS_GREEN)
+        .footer(CreateEmbedFooter::new("Usa /queue-load para volver a cargarla"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de `/playlists list`: playlists propias y compartidas con el usuario
+pub fn create_user_playlists_embed(playlists: &[UserPlaylist], user_id: u64) -> CreateEmbed {
+    let description = if playlists.is_empty() {
+        "No tienes playlists en este servidor.\nGuarda la cola con `/queue-save <nombre>`.".to_string()
+    } else {
+        playlists
+            .iter()
+            .take(20)
+            .map(|playlist| {
+                let collaborative = if playlist.is_collaborative() { " 👥" } else { "" };
+                let owner = if playlist.owner_id == user_id {
+                    String::new()
+                } else {
+                    format!(" · de <@{}>", playlist.owner_id)
+                };
+                format!(
+                    "**{}**{} — {} canciones{}\n`{}`",
+                    playlist.name,
+                    collaborative,
+                    playlist.tracks.len(),
+                    owner,
+                    playlist.id
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title("📂 Tus Playlists")
+        .description(description)
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new("👥 = colaborativa · Usa /queue-load para cargarlas"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed con los últimos cambios de una playlist (`/playlists activity`)
+pub fn create_playlist_activity_embed(playlist: &UserPlaylist) -> CreateEmbed {
+    let description = if playlist.edit_log.is_empty() {
+        "Todavía no hay cambios registrados.".to_string()
+    } else {
+        playlist
+            .edit_log
+            .iter()
+            .rev()
+            .map(|event| {
+                let action = match &event.action {
+                    EditAction::AddTracks { count: 1 } => "agregó 1 canción".to_string(),
+                    EditAction::AddTracks { count } => format!("agregó {} canciones", count),
+                    EditAction::RemoveTrack { title } => format!("quitó **{}**", title),
+                    EditAction::AddEditor { user_id } => format!("sumó a <@{}> como editor", user_id),
+                    EditAction::RemoveEditor { user_id } => format!("quitó a <@{}> de los editores", user_id),
+                };
+                format!("<t:{}:R> <@{}> {}", event.timestamp.timestamp(), event.user_id, action)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let editors = if playlist.editors.is_empty() {
+        "Solo el dueño".to_string()
+    } else {
+        playlist.editors.iter().map(|editor| format!("<@{}>", editor)).collect::<Vec<_>>().join(", ")
+    };
+
+    CreateEmbed::default()
+        .title(format!("📜 Actividad de {}", playlist.name))
+        .description(description)
+        .field("👑 Dueño", format!("<@{}>", playlist.owner_id), true)
+        .field("👥 Editores", editors, true)
+        .color(colors::INFO_BLUE)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de progreso de `/playlists import`
+pub fn create_playlist_import_embed(name: &str, current: usize, total: usize, imported: usize, skipped: usize) -> CreateEmbed {
+    let progress_percent = if total > 0 {
+        current as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    CreateEmbed::default()
+        .title("📥 Importando Playlist")
+        .description(format!(
+            "**{}**\n\n📊 **Progreso**: {}/{} canciones ({:.1}%)\n{}",
+            name,
+            current,
+            total,
+            progress_percent,
+            create_progress_bar(progress_percent, 20)
+        ))
+        .field("✅ Importadas", imported.to_string(), true)
+        .field("⚠️ Omitidas", skipped.to_string(), true)
+        .color(colors::WARNING_ORANGE)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea un embed con las preferencias de ranking de búsqueda de la guild
+pub fn create_search_settings_embed(prefs: &SearchPreferences, title: &str) -> CreateEmbed {
+    let on_off = |enabled: bool| if enabled { "✅ Sí" } else { "❌ No" };
+
+    CreateEmbed::default()
+        .title(format!("⚙️ {}", title))
+        .description("Criterios usados para elegir el mejor resultado de `/play` y `/add`")
+        .field(
+            "⏱️ Duración preferida",
+            format!(
+                "{} – {}",
+                format_duration(Duration::from_secs(prefs.min_duration_secs)),
+                format_duration(Duration::from_secs(prefs.max_duration_secs))
+            ),
+            false,
+        )
+        .field("🎭 Penalizar versiones", on_off(prefs.penalize_alternate_versions), true)
+        .field("✔️ Preferir oficiales", on_off(prefs.prefer_official), true)
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new("Usa /settings search para cambiarlas"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea un embed de diagnóstico con el estado del reproductor de una guild (`/debug`)
+pub fn create_debug_embed(guild_id: GuildId, state: &PlayerDebugState, has_voice_handler: bool, voice_channel: Option<u64>) -> CreateEmbed {
+    let yes_no = |value: bool| if value { "✅ Sí" } else { "❌ No" };
+
+    let track = match &state.current {
+        Some(track) => format!("**{}**\n{}", track.title(), track.url()),
+        None => "Ninguna".to_string(),
+    };
+    let position = match (state.position, state.current.as_ref().and_then(|t| t.duration())) {
+        (Some(position), Some(duration)) => format!("{} / {}", format_duration(position), format_duration(duration)),
+        (Some(position), None) => format!("{} / en vivo", format_duration(position)),
+        (None, _) => "—".to_string(),
+    };
+    let voice = match (has_voice_handler, voice_channel) {
+        (true, Some(channel)) => format!("✅ <#{}>", channel),
+        (true, None) => "⚠️ Handler sin canal".to_string(),
+        (false, _) => "❌ Sin handler".to_string(),
+    };
+
+    let mut effects = vec![format!("EQ: {}", state.effects.preset.name())];
+    effects.extend(state.effects.active_filters());
+
+    CreateEmbed::default()
+        .title("🐞 Estado del Reproductor")
+        .description(format!("Guild `{}`", guild_id))
+        .field("▶️ Reproduciendo", yes_no(state.is_playing), true)
+        .field("🎚️ Estado songbird", state.play_mode.as_deref().unwrap_or("Sin pista"), true)
+        .field("🔊 Voz", voice, true)
+        .field("🎵 Pista actual", track, false)
+        .field("⏱️ Posición", position, true)
+        .field("📋 Cola", state.queue_len.to_string(), true)
+        .field("🔁 Bucle", format!("{:?}", state.loop_mode), true)
+        .field("🔀 Aleatorio", yes_no(state.shuffle), true)
+        .field("🔉 Volumen", format!("{:.0}%", state.volume * 100.0), true)
+        .field("🔢 Generación", state.generation.to_string(), true)
+        .field("🎛️ Efectos", effects.join("\n"), false)
+        .color(colors::INFO_BLUE)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea un embed para videos con restricción de edad o exclusivos para miembros
+pub fn create_restricted_video_embed(restriction: RestrictedVideoError, cookies_configured: bool) -> CreateEmbed {
+    let requirement = match restriction {
+        RestrictedVideoError::AgeRestricted => "una cuenta de YouTube con la **edad verificada**",
+        RestrictedVideoError::MembersOnly => "una cuenta de YouTube **miembro del canal**",
+    };
+
+    let hint = if cookies_configured {
+        format!(
+            "Las cookies configuradas no tienen acceso. Exporta las de {} (ver `docs/COOKIES.md`).",
+            requirement
+        )
+    } else {
+        format!(
+            "No hay cookies configuradas. Un administrador puede exportar las de {} a `config/cookies.txt` \
+             o indicar la ruta con `YTDLP_COOKIES` (ver `docs/COOKIES.md`).",
+            requirement
+        )
+    };
+
+    CreateEmbed::default()
+        .title("🔒 Video Restringido")
+        .description(format!("{}\n\n{}", restriction, hint))
+        .color(colors::WARNING_ORANGE)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de una canción rechazada por el filtro de contenido de la guild
+pub fn create_blocked_track_embed(reason: &BlockedTrackError) -> CreateEmbed {
+    CreateEmbed::default()
+        .title("🚫 Canción Bloqueada")
+        .description(format!("{}\n\nLos moderadores del servidor lo configuran con `/settings block`.", reason))
+        .color(colors::ERROR_RED)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed que rechaza pedidos mientras yt-dlp o ffmpeg no están disponibles
+pub fn create_audio_backend_unavailable_embed() -> CreateEmbed {
+    CreateEmbed::default()
+        .title("🔇 Audio No Disponible")
+        .description(
+            "El backend de audio (yt-dlp/ffmpeg) no está disponible en este momento, \
+            así que no se pueden reproducir canciones.\n\n\
+            Contacta al administrador del bot. El servicio se reactiva solo cuando vuelva a estar disponible.",
+        )
+        .color(colors::ERROR_RED)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed para un usuario que superó su límite de pedidos por minuto
+pub fn create_request_throttled_embed(max_per_minute: u32, wait: Duration) -> CreateEmbed {
+    CreateEmbed::default()
+        .title("⏳ Demasiados Pedidos")
+        .description(format!(
+            "Solo puedes pedir **{}** canciones por minuto (una playlist cuenta como un pedido).\n\n\
+            Podrás volver a pedir en **{}** segundos.",
+            max_per_minute,
+            wait.as_secs().max(1)
+        ))
+        .color(colors::WARNING_ORANGE)
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed con el filtro de contenido de la guild (`/settings block list`)
+pub fn create_content_filter_embed(filter: &ContentFilter, title: &str) -> CreateEmbed {
+    fn list(entries: &[String]) -> String {
+        if entries.is_empty() {
+            "*Ninguno*".to_string()
+        } else {
+            entries.iter().map(|entry| format!("`{}`", entry)).collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    let sources = if filter.allowed_sources.is_empty() {
+        "Todas".to_string()
+    } else {
+        list(&filter.allowed_sources)
+    };
+
+    CreateEmbed::default()
+        .title(format!("🚫 {}", title))
+        .field("🔤 Palabras bloqueadas", list(&filter.blocked_keywords), false)
+        .field("📺 Canales bloqueados", list(&filter.blocked_channels), false)
+        .field("🌐 Fuentes permitidas", sources, false)
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new("Usa /settings block para cambiarlo"))
+        .timestamp(Timestamp::now())
+}
+
 /// Crea un embed de información
 #[allow(dead_code)]
 pub fn create_info_embed(title: &str, description: &str) -> CreateEmbed {
@@ -491,39 +1515,123 @@ pub async fn create_equalizer_status_embed(guild_id: serenity::model::id::GuildI
     Ok(embed)
 }
 
-/// Crea un embed para mostrar el estado del ecualizador
-#[allow(dead_code)]
-pub async fn create_effects_status_embed(guild_id: serenity::model::id::GuildId, bot: &OpenMusicBot) -> anyhow::Result<CreateEmbed> {
-    let eq_details = bot.player.get_equalizer_details(guild_id);
-    
-    let description = format!("**Estado Actual:** 🎛️ {}\n\n**Presets de Ecualizador Disponibles:**\n🎵 **Bass** - Enfatiza graves\n🎤 **Pop** - Equilibrado moderno\n🎸 **Rock** - Graves y agudos\n🎺 **Jazz** - Claridad vocal\n🎼 **Classical** - Dinámico natural\n🔊 **Electronic** - Sintético\n🗣️ **Vocal** - Enfatiza voces\n📏 **Flat** - Sin modificaciones", eq_details);
-    
-    let embed = CreateEmbed::default()
-        .title("🎛️ ECUALIZADOR DE AUDIO")
+/// Crea un embed con el estado completo de los efectos de audio de la guild
+pub fn create_effects_status_embed(state: &EffectsState) -> CreateEmbed {
+    let equalizer = match (state.preset, state.custom_bands) {
+        (EqualizerPreset::Custom, Some(bands)) => {
+            let curve = CUSTOM_EQ_FREQUENCIES
+                .iter()
+                .zip(bands)
+                .filter(|(_, gain)| *gain != 0.0)
+                .map(|(freq, gain)| format!("{}Hz {:+.1}dB", freq, gain))
+                .collect::<Vec<_>>();
+            if curve.is_empty() {
+                "**custom** (todas las bandas en 0 dB)".to_string()
+            } else {
+                format!("**custom**\n`{}`", curve.join(" • "))
+            }
+        }
+        (preset, _) => format!("**{}**", preset.name()),
+    };
+
+    let filters = state.active_filters();
+    let filters = if filters.is_empty() {
+        "Ninguno".to_string()
+    } else {
+        filters.iter().map(|f| format!("• {}", f)).collect::<Vec<_>>().join("\n")
+    };
+
+    let description = if state.is_default() {
+        "Sin efectos personalizados: se usa la configuración por defecto"
+    } else {
+        "Usa `/effects clear` para restablecer todo"
+    };
+
+    CreateEmbed::default()
+        .title("🎛️ Efectos de Audio")
         .description(description)
         .color(Colour::from_rgb(100, 149, 237))
-        .field("Comandos", 
-               "• `/equalizer <preset>` - Aplicar preset de ecualizador", false)
+        .field("Ecualizador", equalizer, true)
+        .field("Normalización", if state.normalize { "✅ Activa" } else { "❌ Desactivada" }, true)
+        .field("Tempo", format!("{:.2}x", state.tempo), true)
+        .field("Filtros activos", filters, false)
         .timestamp(Timestamp::now())
-        .footer(CreateEmbedFooter::new("Open Music Bot • Audio Engine v2.0"));
-    
-    Ok(embed)
+        .footer(CreateEmbedFooter::new("Los cambios se aplican desde la próxima canción"))
 }
 
-/// Crea un embed para mostrar información detallada de una playlist antes de agregar
-#[allow(dead_code)]
-pub fn create_playlist_preview_embed(playlist_title: &str, track_count: usize, playlist_url: &str) -> CreateEmbed {
+/// Curva del ecualizador personalizado como barras de texto: negativas a la
+/// izquierda del eje, positivas a la derecha (un bloque cada 2 dB). La banda
+/// `highlighted` se marca con ▶.
+pub fn eq_curve(bands: &[f32; 10], highlighted: Option<u32>) -> String {
+    CUSTOM_EQ_FREQUENCIES
+        .iter()
+        .zip(bands)
+        .map(|(freq, gain)| {
+            let label = if *freq >= 1000 { format!("{}k", freq / 1000) } else { freq.to_string() };
+            let blocks = "█".repeat((gain.abs() / 2.0).round() as usize);
+            let (negative, positive) = if *gain < 0.0 { (blocks, String::new()) } else { (String::new(), blocks) };
+            let marker = if highlighted == Some(*freq) { '▶' } else { ' ' };
+            format!("{}{:>4} {:>6}│{:<6} {:+5.1} dB", marker, label, negative, positive, gain)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Crea el embed de `/eq band` con la banda cambiada y la curva completa
+pub fn create_eq_band_embed(band_hz: u32, gain_db: f32, bands: &[f32; 10], applied_now: bool) -> CreateEmbed {
+    let footer = if applied_now {
+        "La canción actual ya suena con la nueva curva"
+    } else {
+        "Se aplica desde la próxima canción"
+    };
+    CreateEmbed::default()
+        .title("🎚️ Ecualizador Personalizado")
+        .description(format!(
+            "Banda de **{} Hz** a **{:+.1} dB**\n```\n{}\n```",
+            band_hz,
+            gain_db,
+            eq_curve(bands, Some(band_hz))
+        ))
+        .color(Colour::from_rgb(100, 149, 237))
+        .timestamp(Timestamp::now())
+        .footer(CreateEmbedFooter::new(footer))
+}
+
+/// Crea la vista previa de una playlist grande, que espera confirmación antes de encolarse
+pub fn create_playlist_preview_embed(
+    playlist_title: &str,
+    track_count: usize,
+    first_tracks: &[String],
+    total_duration: Option<Duration>,
+    playlist_url: &str,
+) -> CreateEmbed {
     let description = format!(
-        "**Playlist detectada**: {}\n\n📊 **Canciones encontradas**: {}\n🎵 **Fuente**: YouTube\n\n⏳ Procesando canciones...",
+        "**{}**\n\n📊 **Canciones encontradas**: {}\n🎵 **Fuente**: YouTube\n\n¿Agregar todas a la cola?",
         playlist_title,
         track_count
     );
 
     let mut embed = CreateEmbed::default()
-        .title("📋 Cargando Playlist")
+        .title("📋 Vista Previa de Playlist")
         .description(&description)
-        .color(colors::WARNING_ORANGE) // Naranja para carga
-        .thumbnail("https://img.youtube.com/vi/thumbnail_placeholder/maxresdefault.jpg");
+        .color(colors::WARNING_ORANGE);
+
+    if !first_tracks.is_empty() {
+        let mut list = first_tracks
+            .iter()
+            .enumerate()
+            .map(|(i, title)| format!("{}. {}", i + 1, title))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if track_count > first_tracks.len() {
+            list.push_str(&format!("\n... y {} más", track_count - first_tracks.len()));
+        }
+        embed = embed.field("🎵 Primeras canciones", list, false);
+    }
+
+    if let Some(duration) = total_duration {
+        embed = embed.field("⏱️ Duración total", format_duration(duration), true);
+    }
 
     // Extraer el ID de la playlist
     if let Some(list_start) = playlist_url.find("list=") {
@@ -532,11 +1640,35 @@ pub fn create_playlist_preview_embed(playlist_title: &str, track_count: usize, p
         embed = embed.field("🆔 ID de Playlist", format!("`{}`", clean_list_id), true);
     }
 
-    embed = embed
-        .footer(CreateEmbedFooter::new("🔄 Las canciones se están agregando a la cola..."))
-        .timestamp(Timestamp::now());
-
     embed
+        .footer(CreateEmbedFooter::new("⏳ Solo quien pidió la playlist puede confirmarla"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el embed de una carga de playlist cancelada con el botón `playlist_cancel`
+pub fn create_playlist_cancelled_embed(loaded_count: usize, total_count: usize, playlist_url: &str) -> CreateEmbed {
+    let description = if loaded_count == 0 {
+        "No se agregó ninguna canción de la playlist".to_string()
+    } else {
+        format!(
+            "Se agregaron **{}** de {} canciones antes de cancelar",
+            loaded_count,
+            total_count
+        )
+    };
+
+    let mut embed = CreateEmbed::default()
+        .title("⏹️ Carga de Playlist Cancelada")
+        .description(description)
+        .color(colors::WARNING_ORANGE);
+
+    if let Some(list_start) = playlist_url.find("list=") {
+        let list_id = &playlist_url[list_start + 5..];
+        let clean_list_id = list_id.split('&').next().unwrap_or(list_id);
+        embed = embed.field("🆔 Playlist ID", format!("`{}`", clean_list_id), true);
+    }
+
+    embed.footer(CreateEmbedFooter::new(STANDARD_FOOTER)).timestamp(Timestamp::now())
 }
 
 /// Crea un embed para mostrar cuando una playlist está vacía o hay error
@@ -768,15 +1900,19 @@ pub fn create_enhanced_playlist_embed(
     embed
 }
 
-/// Crea un embed para playlist completada con estadísticas
+/// Crea un embed para playlist completada con estadísticas.
+///
+/// `failures` cuenta las canciones que no se cargaron por motivo.
 pub fn create_playlist_completed_embed(
     playlist_title: &str,
     loaded_count: usize,
     total_count: usize,
-    failed_count: usize,
+    failures: &[(TrackFailureReason, usize)],
+    removed_duplicates: usize,
     total_duration: Option<Duration>,
     playlist_url: &str
 ) -> CreateEmbed {
+    let failed_count: usize = failures.iter().map(|(_, count)| count).sum();
     let success_rate = if total_count > 0 {
         loaded_count as f64 / total_count as f64 * 100.0
     } else {
@@ -812,6 +1948,19 @@ pub fn create_playlist_completed_embed(
         .field("🎵 En cola", format!("{} canciones", loaded_count), true)
         .field("🔗 Fuente", "YouTube", true);
 
+    if removed_duplicates > 0 {
+        embed = embed.field("🧹 Duplicados eliminados", removed_duplicates.to_string(), true);
+    }
+
+    // Desglose de los fallos por motivo
+    if !failures.is_empty() {
+        let lines: Vec<String> = failures
+            .iter()
+            .map(|(reason, count)| format!("{}: **{}**", reason.display_name(), count))
+            .collect();
+        embed = embed.field("❌ Motivos de los fallos", lines.join("\n"), false);
+    }
+
     // Extraer el ID de la playlist
     if let Some(list_start) = playlist_url.find("list=") {
         let list_id = &playlist_url[list_start + 5..];
@@ -820,7 +1969,7 @@ pub fn create_playlist_completed_embed(
     }
 
     let footer_text = if failed_count > 0 {
-        "⚠️ Algunas canciones no pudieron cargarse (privadas o no disponibles)"
+        "⚠️ Algunas canciones no pudieron cargarse; el resto se agregó igual"
     } else {
         "🎵 Todas las canciones se cargaron exitosamente"
     };
@@ -903,6 +2052,78 @@ pub fn create_warning_embed(title: &str, description: &str) -> CreateEmbed {
         .footer(CreateEmbedFooter::new("Open Music Bot"))
 }
 
+/// Máximo de opciones que Discord permite en un menú de selección
+const MAX_SELECT_OPTIONS: usize = 25;
+
+/// Crea el embed de `/chapters` con los capítulos del track actual
+pub fn create_chapters_embed(title: &str, chapters: &[Chapter], current: Option<&Chapter>) -> CreateEmbed {
+    let mut lines: Vec<String> = chapters
+        .iter()
+        .take(MAX_SELECT_OPTIONS)
+        .map(|chapter| {
+            let marker = if current == Some(chapter) { "▶️" } else { "▫️" };
+            format!("{} `{}` {}", marker, format_duration(chapter.start()), chapter.title)
+        })
+        .collect();
+
+    if chapters.len() > MAX_SELECT_OPTIONS {
+        lines.push(format!("*… y {} capítulos más*", chapters.len() - MAX_SELECT_OPTIONS));
+    }
+
+    CreateEmbed::default()
+        .title("📖 Capítulos")
+        .description(format!("**{}**\n\n{}", title, lines.join("\n")))
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new("Elige un capítulo para saltar a él"))
+        .timestamp(Timestamp::now())
+}
+
+/// Crea el menú de selección de capítulos (el valor es el inicio en segundos)
+pub fn create_chapter_select(chapters: &[Chapter]) -> Vec<CreateActionRow> {
+    use serenity::builder::{CreateSelectMenu, CreateSelectMenuOption};
+
+    let options = chapters
+        .iter()
+        .take(MAX_SELECT_OPTIONS)
+        .enumerate()
+        .map(|(i, chapter)| {
+            let label: String = format!("{}. {}", i + 1, chapter.title).chars().take(100).collect();
+            CreateSelectMenuOption::new(label, format!("{:.3}", chapter.start_time))
+                .description(format_duration(chapter.start()))
+        })
+        .collect();
+
+    let select_menu = CreateSelectMenu::new("chapter_select", serenity::builder::CreateSelectMenuKind::String { options })
+        .placeholder("Saltar a un capítulo...")
+        .min_values(1)
+        .max_values(1);
+
+    vec![CreateActionRow::SelectMenu(select_menu)]
+}
+
+/// Crea el embed de `/queue-manage` con las primeras canciones pendientes;
+/// `highlighted` (índice 0-based) marca la elegida o la recién movida
+pub fn create_queue_manage_embed(items: &[QueueItem], highlighted: Option<usize>) -> CreateEmbed {
+    let lines: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if highlighted == Some(i) {
+                format!("👉 `{}.` **{}**", i + 1, item.title)
+            } else {
+                format!("▫️ `{}.` {}", i + 1, item.title)
+            }
+        })
+        .collect();
+
+    CreateEmbed::default()
+        .title("🛠️ Reordenar Cola")
+        .description(lines.join("\n"))
+        .color(colors::INFO_BLUE)
+        .footer(CreateEmbedFooter::new("Elige una canción y después la posición a la que moverla"))
+        .timestamp(Timestamp::now())
+}
+
 /// Crea componentes de selección para múltiples resultados
 #[allow(dead_code)]
 pub fn create_selection_components(results: &[crate::sources::TrackSource]) -> Vec<CreateActionRow> {
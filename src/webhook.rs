@@ -0,0 +1,138 @@
+//! Webhook saliente de eventos de pista (`EVENT_WEBHOOK_URL`).
+//!
+//! Cada vez que una pista empieza, termina o se salta se envía un POST con el
+//! mismo JSON que el stream WebSocket más la hora del evento. Los envíos están
+//! limitados por minuto y un fallo solo queda en el log: la reproducción nunca
+//! espera al webhook.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::{audio::player::AudioPlayer, config::SharedConfig, ws::WsEvent};
+
+/// Envíos como máximo por minuto; el resto se descarta
+pub const WEBHOOK_MAX_PER_MINUTE: usize = 30;
+
+/// Tiempo máximo para entregar un evento
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ventana del límite de envíos
+const WEBHOOK_WINDOW: Duration = Duration::from_secs(60);
+
+/// Cuerpo del POST: el evento tal como sale por el WebSocket más `timestamp`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrackWebhookPayload {
+    #[serde(flatten)]
+    pub event: WsEvent,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl TrackWebhookPayload {
+    /// Payload de un evento de pista (`track_started`, `track_ended`, `track_skipped`);
+    /// `None` para el resto de eventos
+    pub fn from_event(event: &WsEvent, timestamp: DateTime<Utc>) -> Option<Self> {
+        match event {
+            WsEvent::TrackStarted { .. } | WsEvent::TrackEnded { .. } | WsEvent::TrackSkipped { .. } => Some(Self {
+                event: event.clone(),
+                timestamp,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Límite de envíos con ventana deslizante de un minuto
+#[derive(Debug)]
+pub struct WebhookRateLimiter {
+    max_per_minute: usize,
+    sent: VecDeque<Instant>,
+}
+
+impl WebhookRateLimiter {
+    pub fn new(max_per_minute: usize) -> Self {
+        Self {
+            max_per_minute,
+            sent: VecDeque::with_capacity(max_per_minute),
+        }
+    }
+
+    /// Reserva un envío en `now`; `false` si ya se llegó al máximo del último minuto
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.saturating_duration_since(*sent) >= WEBHOOK_WINDOW)
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.max_per_minute {
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
+
+/// Tarea de fondo que reenvía los eventos de pista al webhook configurado.
+/// La URL se lee en cada evento, así `/reload-config` la activa o la cambia.
+pub async fn event_webhook_task(config: SharedConfig, player: Arc<AudioPlayer>, mut shutdown_rx: broadcast::Receiver<()>) {
+    let client = match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("No se pudo crear el cliente del webhook de eventos: {:?}", e);
+            return;
+        }
+    };
+    let mut limiter = WebhookRateLimiter::new(WEBHOOK_MAX_PER_MINUTE);
+    let mut events = player.subscribe_events();
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = shutdown_rx.recv() => {
+                info!("🛑 Webhook de eventos detenido");
+                break;
+            }
+        };
+
+        let event = match event {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!("El webhook de eventos perdió {} eventos", missed);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let Some(url) = config.load().event_webhook_url.clone() else {
+            continue;
+        };
+        let Some(payload) = TrackWebhookPayload::from_event(&event, Utc::now()) else {
+            continue;
+        };
+        if !limiter.try_acquire(Instant::now()) {
+            debug!("Webhook de eventos: límite de {} por minuto alcanzado, se descarta", WEBHOOK_MAX_PER_MINUTE);
+            continue;
+        }
+
+        // Cada envío en su propia tarea: un webhook lento no retrasa los siguientes eventos
+        let client = client.clone();
+        tokio::spawn(async move {
+            let result = client
+                .post(&url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            // Sin la URL en el log: la de un webhook de Discord incluye el token
+            if let Err(e) = result {
+                warn!("⚠️ No se pudo enviar el evento al webhook: {}", e.without_url());
+            }
+        });
+    }
+}
@@ -0,0 +1,202 @@
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_tungstenite::tungstenite::{
+    self,
+    handshake::server::{Callback, ErrorResponse, Request, Response},
+    http::{header::AUTHORIZATION, StatusCode},
+    Message,
+};
+use tracing::{debug, info, warn};
+
+use crate::audio::player::AudioPlayer;
+
+/// Evento del reproductor que se envía a los clientes del WebSocket,
+/// como JSON con el nombre en `event` (`{"event":"track_started",...}`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WsEvent {
+    /// Empezó a sonar la siguiente pista de la cola
+    TrackStarted {
+        guild_id: GuildId,
+        title: String,
+        url: String,
+        requested_by: UserId,
+    },
+    /// La pista terminó sola (no incluye `/skip`, `/stop` ni desconexiones)
+    TrackEnded {
+        guild_id: GuildId,
+        title: String,
+        url: String,
+        requested_by: UserId,
+    },
+    /// La pista se saltó (`/skip`, botón ⏭️) antes de terminar
+    TrackSkipped {
+        guild_id: GuildId,
+        title: String,
+        url: String,
+        requested_by: UserId,
+    },
+    /// Cambió la cantidad de canciones pendientes
+    QueueUpdated { guild_id: GuildId, queue_length: usize },
+    BotJoinedVoice { guild_id: GuildId, channel_id: ChannelId },
+    BotLeftVoice { guild_id: GuildId },
+    /// Fallo de reproducción: `source` (no se pudo abrir el audio) o `playback`
+    Error {
+        guild_id: GuildId,
+        category: String,
+        message: String,
+    },
+}
+
+/// Comando que un cliente envía por el WebSocket (`{"action":"pause","guild_id":"..."}`)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum WsCommand {
+    Pause { guild_id: GuildId },
+    Resume { guild_id: GuildId },
+}
+
+/// Sirve el stream de eventos en `listener` hasta recibir la señal de apagado.
+/// Solo se aceptan clientes que presenten `api_token`, como
+/// `Authorization: Bearer <token>` o en la query (`?token=<token>`).
+pub async fn serve(
+    listener: TcpListener,
+    api_token: String,
+    player: Arc<AudioPlayer>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    if let Ok(addr) = listener.local_addr() {
+        info!("📡 Stream de eventos escuchando en ws://{}", addr);
+    }
+    let api_token: Arc<str> = api_token.into();
+
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => {
+                debug!("📡 Stream de eventos detenido");
+                break;
+            }
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    let api_token = api_token.clone();
+                    let player = player.clone();
+                    let shutdown_rx = shutdown_rx.resubscribe();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &api_token, player, shutdown_rx).await {
+                            debug!("Conexión WebSocket de {} cerrada con error: {:?}", addr, e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Error aceptando conexión WebSocket: {:?}", e),
+            },
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    api_token: &str,
+    player: Arc<AudioPlayer>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) -> Result<(), tungstenite::Error> {
+    // Suscrito antes del handshake: el cliente recibe todo lo que pase desde que se acepta
+    let mut events = player.subscribe_events();
+    let socket = tokio_tungstenite::accept_hdr_async(stream, Authorize { api_token }).await?;
+    let (mut sink, mut incoming) = socket.split();
+
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => {
+                let _ = sink.send(Message::Close(None)).await;
+                break;
+            }
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let json = serde_json::to_string(&event).expect("WsEvent siempre se serializa");
+                    sink.send(Message::text(json)).await?;
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("📡 Cliente WebSocket lento: se descartaron {} eventos", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    if let Some(reply) = run_command(&player, &text).await {
+                        sink.send(Message::text(reply)).await?;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                // tungstenite responde los ping por su cuenta
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Rechaza el handshake (401) si la petición no trae el token correcto
+struct Authorize<'a> {
+    api_token: &'a str,
+}
+
+impl Callback for Authorize<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        if request_token(request).is_some_and(|token| token_matches(&token, self.api_token)) {
+            return Ok(response);
+        }
+        let mut error = ErrorResponse::new(Some("invalid or missing API token".to_string()));
+        *error.status_mut() = StatusCode::UNAUTHORIZED;
+        Err(error)
+    }
+}
+
+/// Ejecuta un comando del cliente; devuelve el JSON de error a responder si falló.
+async fn run_command(player: &AudioPlayer, text: &str) -> Option<String> {
+    let result = match serde_json::from_str::<WsCommand>(text) {
+        Ok(WsCommand::Pause { guild_id }) => player.pause(guild_id).await,
+        Ok(WsCommand::Resume { guild_id }) => player.resume(guild_id).await,
+        Err(e) => {
+            debug!("Comando WebSocket inválido {:?}: {}", text, e);
+            return Some(serde_json::json!({ "error": format!("invalid command: {}", e) }).to_string());
+        }
+    };
+    result
+        .err()
+        .map(|e| serde_json::json!({ "error": e.to_string() }).to_string())
+}
+
+/// Token de la petición: cabecera `Authorization: Bearer` o parámetro `token`
+fn request_token(request: &Request) -> Option<String> {
+    let header = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if let Some(token) = header {
+        return Some(token.trim().to_string());
+    }
+
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .and_then(|token| urlencoding::decode(token).ok())
+        .map(|token| token.into_owned())
+}
+
+/// Compara en tiempo constante para no filtrar el token por temporización
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,97 @@
+//! Tests for the per-guild command audit log
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use open_music::audit::{paginate, rotated_path, AuditDetails, AuditEntry, AuditLog};
+    use std::time::Duration;
+
+    fn entry(command: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            user_id: 42,
+            username: "dj".to_string(),
+            command: command.to_string(),
+            details: AuditDetails {
+                query: Some("lofi beats".to_string()),
+                queue_before: Some(0),
+                queue_after: Some(1),
+                volume: Some(50),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("open_music_audit_{}_{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_log_and_read_newest_first() {
+        let dir = temp_dir("read");
+        let audit = AuditLog::new(dir.clone(), 10 * 1024 * 1024);
+
+        audit.log_audit_event(7, &entry("play")).await.unwrap();
+        audit.log_audit_event(7, &entry("skip")).await.unwrap();
+        audit.log_audit_event(8, &entry("stop")).await.unwrap();
+
+        let entries = audit.read_recent(7, 10).await.unwrap();
+        let commands: Vec<_> = entries.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(commands, vec!["skip", "play"]);
+        assert_eq!(entries[0].details.query.as_deref(), Some("lofi beats"));
+        assert!(entries[0].details.success);
+
+        // Una línea JSON por comando
+        let raw = std::fs::read_to_string(audit.path(7)).unwrap();
+        assert_eq!(raw.lines().count(), 2);
+        assert!(audit.read_recent(9, 10).await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_rotation_and_purge() {
+        let dir = temp_dir("rotate");
+        // Cualquier entrada supera el límite: la siguiente escritura rota
+        let audit = AuditLog::new(dir.clone(), 1);
+
+        audit.log_audit_event(7, &entry("play")).await.unwrap();
+        audit.log_audit_event(7, &entry("skip")).await.unwrap();
+
+        let rotated = rotated_path(&audit.path(7));
+        assert!(rotated.ends_with("guild_7.log.1"));
+        assert!(rotated.exists());
+        let active = audit.read_recent(7, 10).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].command, "skip");
+
+        // Con retención amplia no se borra nada; con cero se borran los dos archivos
+        assert_eq!(audit.purge_older_than(Duration::from_secs(3600)).await.unwrap(), 0);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(audit.purge_older_than(Duration::ZERO).await.unwrap(), 2);
+        assert!(!audit.path(7).exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_paginate() {
+        let entries: Vec<_> = (0..25).map(|i| entry(&format!("cmd{}", i))).collect();
+
+        let (page, total) = paginate(&entries, 1, 10);
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 10);
+        assert_eq!(page[0].command, "cmd0");
+
+        let (page, _) = paginate(&entries, 3, 10);
+        assert_eq!(page.len(), 5);
+
+        // Fuera de rango se queda en la última página
+        let (page, _) = paginate(&entries, 99, 10);
+        assert_eq!(page[0].command, "cmd20");
+
+        let (page, total) = paginate(&[], 1, 10);
+        assert!(page.is_empty());
+        assert_eq!(total, 1);
+    }
+}
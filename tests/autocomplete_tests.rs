@@ -0,0 +1,35 @@
+//! Tests for /play autocomplete suggestions
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::handlers::autocomplete_choices;
+    use open_music::sources::{SourceType, TrackSource};
+    use serenity::model::id::UserId;
+
+    fn track(title: &str, url: &str) -> TrackSource {
+        TrackSource::new(title.to_string(), url.to_string(), SourceType::YouTube, UserId::new(1))
+    }
+
+    #[test]
+    fn test_autocomplete_choices_respect_discord_limits() {
+        let long_title = "a".repeat(150);
+        let long_url = format!("https://www.youtube.com/watch?v={}", "x".repeat(100));
+        let tracks = vec![
+            track(&long_title, "https://www.youtube.com/watch?v=1"),
+            track("Descartado", &long_url),
+            track("Dos", "https://www.youtube.com/watch?v=2"),
+            track("Tres", "https://www.youtube.com/watch?v=3"),
+            track("Cuatro", "https://www.youtube.com/watch?v=4"),
+            track("Cinco", "https://www.youtube.com/watch?v=5"),
+            track("Seis", "https://www.youtube.com/watch?v=6"),
+        ];
+
+        let choices = autocomplete_choices(&tracks);
+
+        assert_eq!(choices.len(), 5);
+        assert_eq!(choices[0].0.chars().count(), 100);
+        assert!(choices[0].0.ends_with('…'));
+        assert_eq!(choices[1], ("Dos".to_string(), "https://www.youtube.com/watch?v=2".to_string()));
+        assert!(choices.iter().all(|(name, _)| name != "Descartado"));
+    }
+}
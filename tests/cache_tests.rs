@@ -0,0 +1,81 @@
+//! Tests for cache module
+
+#[cfg(test)]
+mod tests {
+    use open_music::cache::lru_cache::LRUCache;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cache_hit_miss_counters() {
+        let cache: LRUCache<String, u32> = LRUCache::new(10);
+        cache.insert_with_ttl("a".to_string(), 1, None);
+
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        assert_eq!(cache.get(&"b".to_string()), None);
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_cache_clear_returns_removed_count() {
+        let cache: LRUCache<String, u32> = LRUCache::new(10);
+        cache.insert_with_ttl("a".to_string(), 1, None);
+        cache.insert_with_ttl("b".to_string(), 2, None);
+
+        assert_eq!(cache.clear(), 2);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.clear(), 0);
+    }
+
+    #[test]
+    fn test_cache_hit_ratio_and_capacity() {
+        let cache: LRUCache<String, u32> = LRUCache::new(2);
+        assert_eq!(cache.capacity(), 2);
+        assert_eq!(cache.hit_ratio(), 0.0);
+
+        cache.insert_with_ttl("a".to_string(), 1, None);
+        cache.get(&"a".to_string());
+        cache.get(&"a".to_string());
+        cache.get(&"a".to_string());
+        cache.get(&"z".to_string());
+        assert_eq!(cache.hit_ratio(), 0.75);
+
+        // Reemplazar una clave no expulsa nada; una clave nueva con el cache lleno sí
+        cache.insert_with_ttl("b".to_string(), 2, None);
+        cache.insert_with_ttl("b".to_string(), 3, None);
+        assert_eq!(cache.metrics().evictions, 0);
+        cache.insert_with_ttl("c".to_string(), 4, None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.metrics().evictions, 1);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache: LRUCache<String, u32> = LRUCache::new(2);
+        cache.insert_with_ttl("a".to_string(), 1, None);
+        cache.insert_with_ttl("b".to_string(), 2, None);
+
+        // Leer "a" la vuelve la más reciente: se expulsa "b" aunque sea más nueva
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        cache.insert_with_ttl("c".to_string(), 3, None);
+        assert_eq!(cache.get(&"b".to_string()), None);
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        assert_eq!(cache.get(&"c".to_string()), Some(3));
+    }
+
+    #[test]
+    fn test_cache_remaining_ttl() {
+        let cache: LRUCache<String, u32> = LRUCache::new(10);
+        cache.insert_with_ttl("a".to_string(), 1, Some(Duration::from_secs(60)));
+        cache.insert_with_ttl("b".to_string(), 2, None);
+
+        let remaining = cache.remaining_ttl(&"a".to_string()).unwrap();
+        assert!(remaining <= Duration::from_secs(60) && remaining >= Duration::from_secs(58));
+        assert_eq!(cache.remaining_ttl(&"b".to_string()), None);
+        assert_eq!(cache.remaining_ttl(&"z".to_string()), None);
+        assert_eq!(cache.metrics().hits + cache.metrics().misses, 0);
+    }
+}
@@ -0,0 +1,104 @@
+//! Tests for typed slash command option parsing
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::command_options::{CommandOptions, CommandParseError};
+    use serde_json::json;
+    use serenity::model::{
+        application::CommandDataOption,
+        id::{ChannelId, UserId},
+    };
+
+    fn options(raw: serde_json::Value) -> Vec<CommandDataOption> {
+        serde_json::from_value(raw).expect("opciones válidas")
+    }
+
+    fn sample() -> Vec<CommandDataOption> {
+        options(json!([
+            {"name": "query", "type": 3, "value": "lofi beats"},
+            {"name": "page", "type": 4, "value": 3},
+            {"name": "ratio", "type": 10, "value": 1.25},
+            {"name": "shuffle", "type": 5, "value": true},
+            {"name": "user", "type": 6, "value": "42"},
+            {"name": "channel", "type": 7, "value": "77"}
+        ]))
+    }
+
+    #[test]
+    fn test_getters_read_each_type() {
+        let raw = sample();
+        let opts = CommandOptions::new(&raw);
+
+        assert_eq!(opts.get_str("query"), Some("lofi beats"));
+        assert_eq!(opts.get_i64("page"), Some(3));
+        assert_eq!(opts.get_f64("ratio"), Some(1.25));
+        assert_eq!(opts.get_bool("shuffle"), Some(true));
+        assert_eq!(opts.get_user_id("user"), Some(UserId::new(42)));
+        assert_eq!(opts.get_channel_id("channel"), Some(ChannelId::new(77)));
+        // Los enteros también valen como decimales
+        assert_eq!(opts.get_f64("page"), Some(3.0));
+    }
+
+    #[test]
+    fn test_order_does_not_matter() {
+        let mut raw = sample();
+        raw.reverse();
+        let opts = CommandOptions::new(&raw);
+
+        assert_eq!(opts.get_str("query"), Some("lofi beats"));
+        assert_eq!(opts.get_i64("page"), Some(3));
+        assert_eq!(opts.get_bool("shuffle"), Some(true));
+    }
+
+    #[test]
+    fn test_missing_and_mistyped_options() {
+        let raw = sample();
+        let opts = CommandOptions::new(&raw);
+
+        assert_eq!(opts.get_str("missing"), None);
+        assert_eq!(opts.get_i64("query"), None);
+        assert_eq!(opts.get_bool("page"), None);
+        assert_eq!(opts.get_user_id("query"), None);
+
+        assert_eq!(opts.require_str("query"), Ok("lofi beats"));
+        assert_eq!(opts.require_str("missing"), Err(CommandParseError::Missing("missing".to_string())));
+        assert_eq!(opts.require_str("page"), Err(CommandParseError::WrongType("page".to_string())));
+        assert_eq!(opts.require_i64("page"), Ok(3));
+        assert_eq!(
+            CommandParseError::Missing("query".to_string()).to_string(),
+            "Falta la opción requerida `query`"
+        );
+    }
+
+    #[test]
+    fn test_subcommand_options() {
+        let raw = options(json!([
+            {"name": "url-detect", "type": 1, "options": [
+                {"name": "state", "type": 3, "value": "on"}
+            ]}
+        ]));
+        let (name, sub) = CommandOptions::new(&raw).subcommand().expect("subcomando");
+
+        assert_eq!(name, "url-detect");
+        assert_eq!(sub.get_str("state"), Some("on"));
+        assert!(!sub.is_empty());
+
+        // Grupo de subcomandos: primero el grupo, luego su subcomando
+        let raw = options(json!([
+            {"name": "block", "type": 2, "options": [
+                {"name": "keyword", "type": 1, "options": [
+                    {"name": "action", "type": 3, "value": "add"},
+                    {"name": "value", "type": 3, "value": "nightcore"}
+                ]}
+            ]}
+        ]));
+        let (group, group_options) = CommandOptions::new(&raw).subcommand().expect("grupo");
+        let (name, sub) = group_options.subcommand().expect("subcomando del grupo");
+        assert_eq!((group, name), ("block", "keyword"));
+        assert_eq!(sub.get_str("value"), Some("nightcore"));
+
+        // Sin subcomando (o con opciones planas) no hay nada que devolver
+        assert!(CommandOptions::new(&[]).subcommand().is_none());
+        assert!(CommandOptions::new(&sample()).subcommand().is_none());
+    }
+}
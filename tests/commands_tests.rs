@@ -0,0 +1,24 @@
+//! Tests for slash command registration
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::commands::{command_names, command_set_hash};
+    use serenity::builder::{CreateCommand, CreateCommandOption};
+    use serenity::model::application::CommandOptionType;
+
+    fn commands(description: &str) -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new("skip").description("Salta"),
+            CreateCommand::new("play").description("Reproduce").add_option(
+                CreateCommandOption::new(CommandOptionType::String, "query", description).required(true),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_command_set_hash_detects_changes() {
+        assert_eq!(command_set_hash(&commands("URL")), command_set_hash(&commands("URL")));
+        assert_ne!(command_set_hash(&commands("URL")), command_set_hash(&commands("Búsqueda")));
+        assert_eq!(command_names(&commands("URL")), vec!["play", "skip"]);
+    }
+}
@@ -0,0 +1,23 @@
+//! Tests for the "▶ Play This" message context menu
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::handlers::find_playable_url;
+
+    #[test]
+    fn test_find_playable_url_in_message() {
+        assert_eq!(
+            find_playable_url("escuchen esto <https://www.youtube.com/watch?v=dQw4w9WgXcQ>!"),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            find_playable_url("https://example.com/x y también https://youtu.be/abc123."),
+            Some("https://youtu.be/abc123".to_string())
+        );
+        assert_eq!(
+            find_playable_url("tidal: https://tidal.com/browse/track/123456"),
+            Some("https://tidal.com/browse/track/123456".to_string())
+        );
+        assert_eq!(find_playable_url("sin enlaces aquí"), None);
+    }
+}
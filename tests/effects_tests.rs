@@ -0,0 +1,140 @@
+//! Tests for audio effects state
+
+#[cfg(test)]
+mod tests {
+    use open_music::audio::effects::{atempo_filters, limiter_filter, AudioEffects, EffectsState, EqualizerPreset};
+    use open_music::audio::loudness::{estimated_filter, parse_loudnorm_output};
+    use serenity::model::id::GuildId;
+
+    #[test]
+    fn test_effects_state_per_guild_and_clear() {
+        let effects = AudioEffects::new();
+        let guild = GuildId::new(1);
+        let other = GuildId::new(2);
+
+        assert!(effects.state(guild).is_default());
+        assert_eq!(effects.build_filter(guild), "loudnorm=I=-16:TP=-1.5:LRA=11");
+
+        let mut bands = [0.0; 10];
+        bands[0] = 4.0;
+        effects.apply_custom_bands(guild, bands);
+        let state = effects.state(guild);
+        assert_eq!(state.preset, EqualizerPreset::Custom);
+        assert!(!state.is_default());
+        assert!(effects.build_filter(guild).ends_with(",equalizer=f=32:t=o:w=1:g=4"));
+        assert!(effects.state(other).is_default());
+
+        effects.clear(guild);
+        assert_eq!(effects.state(guild), EffectsState::default());
+        assert_eq!(effects.state(guild).custom_bands, None);
+    }
+
+    #[test]
+    fn test_set_single_band_keeps_other_bands() {
+        let effects = AudioEffects::new();
+        let guild = GuildId::new(1);
+
+        effects.set_single_band(guild, 1000, -3.5).unwrap();
+        effects.set_single_band(guild, 32, 6.0).unwrap();
+        let state = effects.state(guild);
+        assert_eq!(state.preset, EqualizerPreset::Custom);
+        assert_eq!(state.custom_bands, Some([6.0, 0.0, 0.0, 0.0, 0.0, -3.5, 0.0, 0.0, 0.0, 0.0]));
+        assert!(effects.build_filter(guild).contains("equalizer=f=1000:t=o:w=1:g=-3.5"));
+
+        assert!(effects.set_single_band(guild, 31, 1.0).is_err());
+        assert!(effects.set_single_band(guild, 500, 12.5).is_err());
+        assert_eq!(effects.state(guild).custom_bands.unwrap()[4], 0.0);
+    }
+
+    #[test]
+    fn test_tempo_chains_atempo_stages() {
+        assert!(atempo_filters(1.0).is_empty());
+        assert_eq!(atempo_filters(1.5), ["atempo=1.5"]);
+        assert_eq!(atempo_filters(4.0), ["atempo=2", "atempo=2"]);
+        assert_eq!(atempo_filters(0.25), ["atempo=0.5", "atempo=0.5"]);
+
+        let effects = AudioEffects::new();
+        let guild = GuildId::new(1);
+        assert_eq!(effects.set_tempo(guild, 1.25), 1.0);
+        assert!(effects.build_filter(guild).ends_with(",atempo=1.25"));
+        // `/speed` no pasa de 2.0x
+        assert_eq!(effects.set_tempo(guild, 3.0), 1.25);
+        assert_eq!(effects.state(guild).tempo, 2.0);
+
+        effects.clear(guild);
+        assert_eq!(effects.state(guild).tempo, 1.0);
+    }
+
+    #[test]
+    fn test_silencedetect_output_updates_monitor() {
+        use open_music::audio::silence::{parse_silencedetect_line, with_silence_filter, SilenceEvent, SilenceMonitor};
+
+        assert_eq!(
+            parse_silencedetect_line("[silencedetect @ 0x55d1c2a0] silence_start: 12.5"),
+            Some(SilenceEvent::Start)
+        );
+        assert_eq!(
+            parse_silencedetect_line("[silencedetect @ 0x55d1c2a0] silence_end: 20.1 | silence_duration: 7.6"),
+            Some(SilenceEvent::End)
+        );
+        assert_eq!(parse_silencedetect_line("size=    1024kB time=00:00:05.00"), None);
+
+        assert_eq!(with_silence_filter("anull"), "silencedetect=n=-50dB:d=5");
+        assert_eq!(
+            with_silence_filter("loudnorm=I=-16:TP=-1.5:LRA=11"),
+            "loudnorm=I=-16:TP=-1.5:LRA=11,silencedetect=n=-50dB:d=5"
+        );
+
+        let monitor = SilenceMonitor::default();
+        monitor.observe_line("[silencedetect @ 0x1] silence_start: 3");
+        assert!(monitor.is_silent());
+        monitor.observe_line("[silencedetect @ 0x1] silence_end: 9 | silence_duration: 6");
+        assert!(!monitor.is_silent());
+    }
+
+    #[test]
+    fn test_two_pass_loudnorm_filter() {
+        let stderr = r#"[Parsed_loudnorm_0 @ 0x55d] 
+{
+	"input_i" : "-9.87",
+	"input_tp" : "0.12",
+	"input_lra" : "5.40",
+	"input_thresh" : "-20.01",
+	"output_i" : "-16.02",
+	"output_tp" : "-1.50",
+	"output_lra" : "4.90",
+	"output_thresh" : "-26.11",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.02"
+}"#;
+        let measurement = parse_loudnorm_output(stderr).unwrap();
+        assert_eq!(measurement.input_i, -9.87);
+        assert_eq!(
+            measurement.filter(),
+            "loudnorm=I=-16:LRA=11:TP=-1.5:measured_I=-9.87:measured_LRA=5.40:measured_TP=0.12:measured_thresh=-20.01:offset=0.02:linear=true"
+        );
+
+        // Una pista muda mide -inf: no sirve como medición
+        assert!(parse_loudnorm_output(&stderr.replace("-9.87", "-inf")).is_none());
+        assert!(parse_loudnorm_output("sin resumen").is_none());
+        assert_eq!(estimated_filter(), "volume=-2dB");
+
+        let effects = AudioEffects::new();
+        let guild = GuildId::new(1);
+        assert_eq!(effects.build_filter_normalized(guild, "volume=-2dB"), "volume=-2dB");
+        effects.set_tempo(guild, 1.5);
+        assert_eq!(effects.build_filter_normalized(guild, &measurement.filter()), format!("{},atempo=1.5", measurement.filter()));
+    }
+
+    #[test]
+    fn test_limiter_only_above_threshold() {
+        assert_eq!(limiter_filter(1.0), None);
+        assert_eq!(limiter_filter(1.5), None);
+        // El techo compensa la ganancia que songbird aplica después de ffmpeg
+        assert_eq!(
+            limiter_filter(2.0).as_deref(),
+            Some("alimiter=limit=0.500:attack=5:release=50:level=false")
+        );
+        assert!(limiter_filter(1.6).unwrap().contains("limit=0.625"));
+    }
+}
@@ -0,0 +1,39 @@
+//! Tests for error categorization
+
+#[cfg(test)]
+mod tests {
+    use open_music::errors::{ErrorCategory, ErrorStats, ERROR_WINDOW};
+    use open_music::sources::ExtractionTimeoutError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_error_counts_include_every_category() {
+        let stats = ErrorStats::default();
+        stats.record(ErrorCategory::Extraction);
+        stats.record(ErrorCategory::Extraction);
+        stats.record(ErrorCategory::DiscordApi);
+
+        let counts = stats.counts_within(ERROR_WINDOW);
+        assert_eq!(
+            counts,
+            vec![
+                (ErrorCategory::Extraction, 2),
+                (ErrorCategory::Voice, 0),
+                (ErrorCategory::Playback, 0),
+                (ErrorCategory::DiscordApi, 1),
+            ]
+        );
+        assert_eq!(ErrorCategory::DiscordApi.as_str(), "discord_api");
+    }
+
+    #[test]
+    fn test_classify_known_error_types() {
+        let timeout = anyhow::Error::new(ExtractionTimeoutError(Duration::from_secs(30)));
+        assert_eq!(ErrorCategory::classify(&timeout), Some(ErrorCategory::Extraction));
+
+        let discord = anyhow::Error::new(serenity::Error::Other("sin permisos"));
+        assert_eq!(ErrorCategory::classify(&discord), Some(ErrorCategory::DiscordApi));
+
+        assert_eq!(ErrorCategory::classify(&anyhow::anyhow!("otro fallo")), None);
+    }
+}
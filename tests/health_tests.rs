@@ -0,0 +1,112 @@
+//! Tests for the health report and the HTTP health endpoint
+
+#[cfg(test)]
+mod tests {
+    use open_music::health::{serve, ComponentHealth, ComponentStatus, DependencyVersions, HealthLevel, HealthReport};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn components() -> ComponentHealth {
+        ComponentHealth {
+            yt_dlp: ComponentStatus::Ok,
+            ffmpeg: ComponentStatus::Ok,
+            discord: ComponentStatus::Ok,
+            storage: ComponentStatus::Ok,
+            lavalink: ComponentStatus::Missing,
+        }
+    }
+
+    #[test]
+    fn test_health_level_aggregation() {
+        // Lavalink ausente no afecta al estado
+        assert_eq!(components().level(), HealthLevel::Healthy);
+
+        let storage_down = ComponentHealth { storage: ComponentStatus::Error, ..components() };
+        assert_eq!(storage_down.level(), HealthLevel::Warning);
+
+        let ffmpeg_down = ComponentHealth { ffmpeg: ComponentStatus::Error, ..storage_down };
+        assert_eq!(ffmpeg_down.level(), HealthLevel::Critical);
+
+        let discord_down = ComponentHealth { discord: ComponentStatus::Error, ..components() };
+        assert_eq!(discord_down.level(), HealthLevel::Critical);
+    }
+
+    #[test]
+    fn test_audio_backend_requires_yt_dlp_and_ffmpeg() {
+        assert!(components().audio_backend_ok());
+
+        // Discord o el almacenamiento caídos no bloquean el audio
+        let storage_down = ComponentHealth { storage: ComponentStatus::Error, discord: ComponentStatus::Error, ..components() };
+        assert!(storage_down.audio_backend_ok());
+
+        assert!(!ComponentHealth { yt_dlp: ComponentStatus::Error, ..components() }.audio_backend_ok());
+        assert!(!ComponentHealth { ffmpeg: ComponentStatus::Error, ..components() }.audio_backend_ok());
+    }
+
+    #[test]
+    fn test_dependency_versions_keep_changes_only() {
+        let versions = DependencyVersions::default();
+        versions.record("yt-dlp", "2024.07.25");
+        versions.record("yt-dlp", "2024.07.25");
+        versions.record("yt-dlp", "2024.08.06");
+
+        let history: Vec<String> = versions.history("yt-dlp").into_iter().map(|(_, v)| v).collect();
+        assert_eq!(history, ["2024.07.25", "2024.08.06"]);
+        assert!(versions.history("ffmpeg").is_empty());
+    }
+
+    #[test]
+    fn test_health_report_json_and_status_code() {
+        let report = HealthReport::new(components(), Duration::from_secs(12345), 42);
+        assert_eq!(report.http_status(), 200);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["status"], "healthy");
+        assert_eq!(json["components"]["yt_dlp"], "ok");
+        assert_eq!(json["components"]["lavalink"], "missing");
+        assert_eq!(json["uptime_seconds"], 12345);
+        assert_eq!(json["guild_count"], 42);
+
+        let warning = HealthReport::new(ComponentHealth { storage: ComponentStatus::Error, ..components() }, Duration::ZERO, 0);
+        assert_eq!(warning.http_status(), 200);
+
+        let critical = HealthReport::new(ComponentHealth { yt_dlp: ComponentStatus::Error, ..components() }, Duration::ZERO, 0);
+        assert_eq!(critical.http_status(), 503);
+        assert_eq!(serde_json::to_value(&critical).unwrap()["status"], "critical");
+    }
+
+    async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_http_endpoint() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+
+        let report = || async {
+            let components = ComponentHealth { discord: ComponentStatus::Error, ..components() };
+            HealthReport::new(components, Duration::from_secs(5), 3)
+        };
+        let server = tokio::spawn(serve(listener, report, shutdown_rx));
+
+        let response = get(addr, "/health").await;
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let parsed: HealthReport = serde_json::from_str(body).unwrap();
+        assert_eq!(parsed.status, HealthLevel::Critical);
+        assert_eq!(parsed.components.discord, ComponentStatus::Error);
+        assert_eq!(parsed.guild_count, 3);
+
+        assert!(get(addr, "/metrics").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+//! Tests for deferred interaction expiry detection
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::handlers::{
+        interaction_token_expired, is_expired_interaction_status, INTERACTION_TOKEN_TTL,
+    };
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_token_expires_after_fifteen_minutes() {
+        let deferred_at = Instant::now();
+
+        assert!(!interaction_token_expired(deferred_at, deferred_at));
+        assert!(!interaction_token_expired(deferred_at, deferred_at + Duration::from_secs(14 * 60)));
+        // Una playlist que tarda los 15 minutos completos ya no puede editar la respuesta
+        assert!(interaction_token_expired(deferred_at, deferred_at + INTERACTION_TOKEN_TTL));
+        assert!(interaction_token_expired(deferred_at, deferred_at + Duration::from_secs(20 * 60)));
+    }
+
+    #[test]
+    fn test_expired_interaction_errors() {
+        // "Unknown Webhook" al editar una respuesta caducada
+        assert!(is_expired_interaction_status(404, 10015));
+        assert!(is_expired_interaction_status(404, 0));
+        // Token inválido aunque el estado no sea 404
+        assert!(is_expired_interaction_status(401, 50027));
+        assert!(is_expired_interaction_status(400, 10062));
+
+        // Otros errores se propagan
+        assert!(!is_expired_interaction_status(403, 50013));
+        assert!(!is_expired_interaction_status(429, 0));
+        assert!(!is_expired_interaction_status(500, 0));
+    }
+
+    #[test]
+    fn test_progress_edits_are_throttled() {
+        use open_music::bot::handlers::{EditThrottle, PROGRESS_EDIT_INTERVAL};
+
+        let start = Instant::now();
+        let mut throttle = EditThrottle::new(PROGRESS_EDIT_INTERVAL);
+
+        // La primera edición sale enseguida; las siguientes esperan el intervalo
+        assert!(throttle.ready(start));
+        assert!(!throttle.ready(start + Duration::from_millis(500)));
+        assert!(!throttle.ready(start + Duration::from_millis(1999)));
+        assert!(throttle.ready(start + PROGRESS_EDIT_INTERVAL));
+        // El intervalo cuenta desde la última edición enviada
+        assert!(!throttle.ready(start + Duration::from_secs(3)));
+        assert!(throttle.ready(start + Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn test_skip_confirmation_only_for_long_fresh_tracks() {
+        use open_music::bot::handlers::needs_skip_confirmation;
+
+        let secs = Duration::from_secs;
+        // Tema de 15 minutos con 10 segundos sonando: se pide confirmación
+        assert!(needs_skip_confirmation(secs(10), Some(secs(900)), 30, 600));
+        // Pasado el umbral, o con un tema corto, se salta directo
+        assert!(!needs_skip_confirmation(secs(30), Some(secs(900)), 30, 600));
+        assert!(!needs_skip_confirmation(secs(10), Some(secs(600)), 30, 600));
+        // Streams en vivo (sin duración) nunca piden confirmación
+        assert!(!needs_skip_confirmation(secs(0), None, 30, 600));
+        // Umbral 0 desactiva la confirmación
+        assert!(!needs_skip_confirmation(secs(0), Some(secs(900)), 0, 600));
+    }
+}
@@ -0,0 +1,39 @@
+//! Tests for playback-driven bot nicknames
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::nickname::{format_nickname, NicknameState, NICKNAME_DEBOUNCE};
+    use serenity::model::id::GuildId;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_format_nickname_pattern() {
+        assert_eq!(
+            format_nickname("🎵 {track}", "Never Gonna Give You Up", "Servidor"),
+            "🎵 Never Gonna Give You"
+        );
+        assert_eq!(format_nickname("Open Music | {guild}", "x", "Lofi"), "Open Music | Lofi");
+        // Recortado al máximo de Discord
+        assert_eq!(
+            format_nickname("{guild}", "x", &"a".repeat(50)).chars().count(),
+            32
+        );
+    }
+
+    #[test]
+    fn test_nickname_updates_are_debounced() {
+        let state = NicknameState::default();
+        let guild = GuildId::new(1);
+        let start = Instant::now();
+        let playing = Some("🎵 Tema".to_string());
+
+        // Sin cambios previos no se toca el apodo por defecto
+        assert!(!state.should_update(guild, &None, start));
+        assert!(state.should_update(guild, &playing, start));
+        state.record(guild, playing.clone(), start);
+
+        assert!(!state.should_update(guild, &playing, start + Duration::from_secs(60)));
+        assert!(!state.should_update(guild, &None, start + Duration::from_secs(60)));
+        assert!(state.should_update(guild, &None, start + NICKNAME_DEBOUNCE));
+    }
+}
@@ -0,0 +1,70 @@
+//! Tests for the per-user track request throttling
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::permissions::{SpamDetector, SPAM_BLOCK_DURATION};
+    use serenity::model::id::{GuildId, UserId};
+    use std::time::{Duration, Instant};
+
+    const GUILD: GuildId = GuildId::new(1);
+    const USER: UserId = UserId::new(10);
+
+    #[test]
+    fn test_spam_detector_blocks_after_limit() {
+        let detector = SpamDetector::new();
+        let start = Instant::now();
+
+        for i in 0..5 {
+            assert!(detector.check_at(GUILD, USER, 5, start + Duration::from_secs(i)).is_ok());
+        }
+        let throttled = detector.check_at(GUILD, USER, 5, start + Duration::from_secs(10)).unwrap_err();
+        assert!(throttled.newly_blocked);
+        assert_eq!(throttled.wait, SPAM_BLOCK_DURATION);
+
+        // Bloqueado aunque la ventana original ya haya pasado
+        let later = detector.check_at(GUILD, USER, 5, start + Duration::from_secs(40)).unwrap_err();
+        assert!(!later.newly_blocked);
+        assert_eq!(later.wait, Duration::from_secs(30));
+
+        // Otros usuarios y otras guilds no se ven afectados
+        assert!(detector.check_at(GUILD, UserId::new(11), 5, start + Duration::from_secs(10)).is_ok());
+        assert!(detector.check_at(GuildId::new(2), USER, 5, start + Duration::from_secs(10)).is_ok());
+
+        assert!(detector.check_at(GUILD, USER, 5, start + Duration::from_secs(71)).is_ok());
+    }
+
+    #[test]
+    fn test_spam_detector_sliding_window() {
+        let detector = SpamDetector::new();
+        let start = Instant::now();
+
+        for i in 0..5 {
+            assert!(detector.check_at(GUILD, USER, 5, start + Duration::from_secs(i * 10)).is_ok());
+        }
+        // A los 60 s el primer pedido sale de la ventana y deja un hueco
+        assert!(detector.check_at(GUILD, USER, 5, start + Duration::from_secs(60)).is_ok());
+        assert!(detector.check_at(GUILD, USER, 5, start + Duration::from_secs(61)).is_err());
+    }
+
+    #[test]
+    fn test_spam_detector_zero_limit_disables() {
+        let detector = SpamDetector::new();
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert!(detector.check_at(GUILD, USER, 0, now).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_may_control_playback_requires_bot_channel() {
+        use open_music::bot::permissions::may_control_playback;
+        use serenity::model::id::ChannelId;
+
+        let bot_channel = Some(ChannelId::new(100));
+        assert!(may_control_playback(bot_channel, Some(ChannelId::new(100))));
+        assert!(!may_control_playback(bot_channel, Some(ChannelId::new(200))));
+        assert!(!may_control_playback(bot_channel, None));
+        // Sin el bot en voz no hay reproducción que proteger
+        assert!(may_control_playback(None, None));
+    }
+}
@@ -0,0 +1,43 @@
+//! Tests for the cancellable `/playlist` loads
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::playlist_load::{PlaylistDecision, PlaylistLoads};
+    use serenity::model::id::{GuildId, UserId};
+    use std::time::Duration;
+
+    const GUILD: GuildId = GuildId::new(1);
+    const USER: UserId = UserId::new(10);
+
+    #[test]
+    fn test_one_playlist_load_per_guild() {
+        let loads = PlaylistLoads::new();
+
+        assert!(loads.begin(GUILD, USER).is_some());
+        assert!(loads.begin(GUILD, UserId::new(11)).is_none());
+        assert!(loads.begin(GuildId::new(2), USER).is_some());
+        assert_eq!(loads.requested_by(GUILD), Some(USER));
+
+        loads.finish(GUILD);
+        assert_eq!(loads.requested_by(GUILD), None);
+        assert!(!loads.cancel(GUILD));
+        assert!(loads.begin(GUILD, USER).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_playlist_load_decisions() {
+        let loads = PlaylistLoads::new();
+
+        let load = loads.begin(GUILD, USER).unwrap();
+        assert!(loads.confirm(GUILD));
+        assert_eq!(load.wait_for_decision(Duration::from_secs(5)).await, PlaylistDecision::Confirmed);
+        assert!(!load.is_cancelled());
+        loads.finish(GUILD);
+
+        let load = loads.begin(GUILD, USER).unwrap();
+        assert_eq!(load.wait_for_decision(Duration::from_millis(10)).await, PlaylistDecision::Expired);
+        assert!(loads.cancel(GUILD));
+        assert!(load.is_cancelled());
+        assert_eq!(load.wait_for_decision(Duration::from_secs(5)).await, PlaylistDecision::Cancelled);
+    }
+}
@@ -0,0 +1,31 @@
+//! Tests for text command prefix parsing
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::prefix::{parse, TextCommand};
+
+    #[test]
+    fn test_parse_command_with_args() {
+        assert_eq!(
+            parse("!play never gonna give you up", "!"),
+            Some(TextCommand {
+                name: "play".to_string(),
+                args: "never gonna give you up",
+            })
+        );
+        assert_eq!(
+            parse("?Skip 3", "?"),
+            Some(TextCommand {
+                name: "skip".to_string(),
+                args: "3",
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_unprefixed_messages() {
+        assert_eq!(parse("play something", "!"), None);
+        assert_eq!(parse("!", "!"), None);
+        assert_eq!(parse("! play", "!"), None);
+    }
+}
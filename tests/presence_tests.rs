@@ -0,0 +1,26 @@
+//! Tests for presence rotation
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::presence::PresenceRotation;
+    use serenity::model::id::GuildId;
+
+    #[test]
+    fn test_rotation_cycles_through_playing_guilds() {
+        let mut rotation = PresenceRotation::default();
+        let playing = || {
+            vec![
+                (GuildId::new(2), "Segundo".to_string()),
+                (GuildId::new(1), "Primero".to_string()),
+            ]
+        };
+
+        assert_eq!(rotation.next(&mut playing()).as_deref(), Some("Primero"));
+        assert_eq!(rotation.next(&mut playing()).as_deref(), Some("Segundo"));
+        assert_eq!(rotation.next(&mut playing()).as_deref(), Some("Primero"));
+
+        // Sin nada sonando vuelve al estado inactivo y reinicia el turno
+        assert_eq!(rotation.next(&mut Vec::new()), None);
+        assert_eq!(rotation.next(&mut playing()).as_deref(), Some("Primero"));
+    }
+}
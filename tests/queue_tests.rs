@@ -0,0 +1,738 @@
+//! Tests for music queue
+
+#[cfg(test)]
+mod tests {
+    use open_music::audio::queue::{MusicQueue, SortField};
+    use open_music::sources::{SourceType, TrackSource};
+    use serenity::model::id::UserId;
+
+    fn track(n: usize) -> TrackSource {
+        TrackSource::new(
+            format!("Track {}", n),
+            format!("https://www.youtube.com/watch?v={}", n),
+            SourceType::YouTube,
+            UserId::new(1),
+        )
+    }
+
+    #[test]
+    fn test_shuffle_once_permutes_pending_and_keeps_current() {
+        let mut queue = MusicQueue::new(100);
+        for n in 0..31 {
+            queue.add_track(track(n)).unwrap();
+        }
+        let current = queue.next_track().unwrap();
+
+        let before: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+        let shuffled = queue.shuffle_once();
+        let after: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+
+        assert_eq!(shuffled, 30);
+        assert_eq!(queue.current_track().unwrap().url(), current.url());
+        assert!(!queue.is_shuffle());
+
+        let mut sorted_before = before.clone();
+        let mut sorted_after = after.clone();
+        sorted_before.sort();
+        sorted_after.sort();
+        assert_eq!(sorted_before, sorted_after);
+        // 30! permutaciones: la probabilidad de conservar el orden es despreciable
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn test_dedupe_tracks_keeps_first_occurrence() {
+        use open_music::audio::queue::dedupe_tracks;
+
+        let mut tracks = vec![track(1), track(2), track(1), track(3), track(2)];
+        let removed = dedupe_tracks(&mut tracks);
+
+        assert_eq!(removed, 2);
+        let titles: Vec<String> = tracks.iter().map(|t| t.title().to_string()).collect();
+        assert_eq!(titles, vec!["Track 1", "Track 2", "Track 3"]);
+    }
+
+    #[test]
+    fn test_seeded_shuffle_is_deterministic() {
+        let build = || {
+            let mut queue = MusicQueue::new(100);
+            for n in 0..20 {
+                queue.add_track(track(n)).unwrap();
+            }
+            queue
+        };
+        let order = |queue: &MusicQueue| -> Vec<String> { queue.get_tracks().iter().map(|t| t.url().to_string()).collect() };
+
+        let mut first = build();
+        let mut second = build();
+        first.shuffle_with_seed(42);
+        second.shuffle_with_seed(42);
+
+        assert_eq!(order(&first), order(&second));
+        assert_eq!(first.get_shuffle_seed(), Some(42));
+
+        first.shuffle_once();
+        assert_eq!(first.get_shuffle_seed(), None);
+    }
+
+    #[test]
+    fn test_start_tracks_at_index() {
+        use open_music::audio::queue::start_tracks_at;
+
+        let titles = |tracks: &[TrackSource]| tracks.iter().map(|t| t.title().to_string()).collect::<Vec<_>>();
+
+        let mut tracks: Vec<TrackSource> = (1..=5).map(track).collect();
+        assert_eq!(start_tracks_at(&mut tracks, 3, false), 3);
+        assert_eq!(titles(&tracks), vec!["Track 3", "Track 4", "Track 5"]);
+
+        let mut tracks: Vec<TrackSource> = (1..=5).map(track).collect();
+        assert_eq!(start_tracks_at(&mut tracks, 4, true), 4);
+        assert_eq!(titles(&tracks), vec!["Track 4", "Track 5", "Track 1", "Track 2", "Track 3"]);
+
+        // Fuera de rango: se acota al último
+        let mut tracks: Vec<TrackSource> = (1..=5).map(track).collect();
+        assert_eq!(start_tracks_at(&mut tracks, 99, false), 5);
+        assert_eq!(titles(&tracks), vec!["Track 5"]);
+    }
+
+    #[test]
+    fn test_loop_count_repeats_then_continues() {
+        use open_music::audio::queue::LoopMode;
+
+        let mut queue = MusicQueue::new(100);
+        queue.add_track(track(1)).unwrap();
+        queue.add_track(track(2)).unwrap();
+        queue.next_track().unwrap();
+        queue.set_loop_mode(LoopMode::TrackCount(2));
+
+        assert_eq!(&*queue.next_track().unwrap().title(), "Track 1");
+        assert_eq!(queue.get_info().loop_mode, LoopMode::TrackCount(1));
+        assert_eq!(&*queue.next_track().unwrap().title(), "Track 1");
+        assert_eq!(queue.get_info().loop_mode, LoopMode::Off);
+        assert_eq!(&*queue.next_track().unwrap().title(), "Track 2");
+    }
+
+    #[test]
+    fn test_loop_stats_counts_repeats_and_resets() {
+        use open_music::audio::queue::LoopMode;
+
+        let mut queue = MusicQueue::new(100);
+        queue.add_track(track(1)).unwrap();
+        queue.add_track(track(2)).unwrap();
+        queue.next_track().unwrap();
+        queue.set_loop_mode(LoopMode::Track);
+        assert_eq!(queue.get_info().loop_count, 0);
+
+        queue.next_track().unwrap();
+        queue.next_track().unwrap();
+        assert_eq!(queue.get_info().loop_count, 2);
+
+        // Desactivar el loop reinicia el contador
+        queue.set_loop_mode(LoopMode::Off);
+        assert_eq!(queue.get_info().loop_count, 0);
+
+        // Cambiar de canción también
+        queue.set_loop_mode(LoopMode::TrackCount(1));
+        queue.next_track().unwrap();
+        assert_eq!(queue.get_info().loop_count, 1);
+        assert_eq!(&*queue.next_track().unwrap().title(), "Track 2");
+        assert_eq!(queue.get_info().loop_count, 0);
+    }
+
+    #[test]
+    fn test_restore_removed_after_clear_keeps_added_tracks() {
+        let mut queue = MusicQueue::new(100);
+        for n in 0..4 {
+            queue.add_track(track(n)).unwrap();
+        }
+        queue.next_track().unwrap();
+
+        let snapshot = queue.snapshot();
+        queue.clear();
+        let removed = queue.removed_since(snapshot);
+        assert_eq!(removed.len(), 3);
+        queue.add_track(track(9)).unwrap();
+
+        assert_eq!(queue.restore_removed(removed).restored, 3);
+        assert_eq!(titles(&queue), ["Track 1", "Track 2", "Track 3", "Track 9"]);
+        // La canción actual no se toca
+        assert_eq!(&*queue.current_track().unwrap().title(), "Track 0");
+    }
+
+    #[test]
+    fn test_restore_removed_after_remove_skips_played_tracks() {
+        let mut queue = MusicQueue::new(100);
+        for n in 0..4 {
+            queue.add_track(track(n)).unwrap();
+        }
+
+        let snapshot = queue.snapshot();
+        queue.remove_track(2).unwrap();
+        let removed = queue.removed_since(snapshot);
+        assert_eq!(removed.len(), 1);
+
+        // Suena la siguiente: no debe volver a la cola al deshacer
+        queue.next_track().unwrap();
+        queue.restore_removed(removed);
+        assert_eq!(titles(&queue), ["Track 1", "Track 2", "Track 3"]);
+        assert_eq!(&*queue.current_track().unwrap().title(), "Track 0");
+    }
+
+    #[test]
+    fn test_restore_removed_respects_max_size() {
+        use open_music::audio::queue::RestoreOutcome;
+
+        let mut queue = MusicQueue::new(3);
+        for n in 0..3 {
+            queue.add_track(track(n)).unwrap();
+        }
+
+        let snapshot = queue.snapshot();
+        queue.clear();
+        let removed = queue.removed_since(snapshot);
+        queue.add_track(track(8)).unwrap();
+        queue.add_track(track(9)).unwrap();
+
+        // Solo queda un hueco: vuelve la primera y las otras dos se descartan
+        assert_eq!(queue.restore_removed(removed), RestoreOutcome { restored: 1, dropped: 2 });
+        assert_eq!(titles(&queue), ["Track 0", "Track 8", "Track 9"]);
+
+        // Con la cola llena no vuelve ninguna
+        let snapshot = queue.snapshot();
+        queue.remove_track(0).unwrap();
+        let removed = queue.removed_since(snapshot);
+        queue.add_track(track(7)).unwrap();
+        assert_eq!(queue.restore_removed(removed), RestoreOutcome { restored: 0, dropped: 1 });
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_queue_manage_selection_expires() {
+        use open_music::bot::queue_manage::{PendingMove, PendingMoves, SELECTION_TTL};
+        use serenity::model::id::GuildId;
+        use std::time::{Duration, Instant};
+
+        let pending = PendingMoves::default();
+        let (guild, other_guild) = (GuildId::new(1), GuildId::new(2));
+        let user = UserId::new(7);
+        let selection = |index: usize| PendingMove { index, url: format!("https://www.youtube.com/watch?v={}", index) };
+
+        pending.select(guild, user, selection(3));
+        // Cada guild tiene su propia selección
+        assert_eq!(pending.take(other_guild, user), None);
+        assert_eq!(pending.take(guild, user), Some(selection(3)));
+        // Se consume al usarla
+        assert_eq!(pending.take(guild, user), None);
+
+        pending.select(guild, user, selection(1));
+        let late = Instant::now() + SELECTION_TTL + Duration::from_secs(1);
+        assert_eq!(pending.take_at(guild, user, late), None);
+
+        // Las vencidas que nadie usó se barren
+        pending.select(other_guild, user, selection(2));
+        pending.sweep_at(late);
+        assert_eq!(pending.take(other_guild, user), None);
+    }
+
+    #[test]
+    fn test_queue_manage_selection_follows_moved_track() {
+        use open_music::bot::queue_manage::PendingMove;
+
+        let mut queue = MusicQueue::new(100);
+        for n in 0..4 {
+            queue.add_track(track(n)).unwrap();
+        }
+        let selection = PendingMove { index: 2, url: "https://www.youtube.com/watch?v=2".to_string() };
+        assert_eq!(selection.locate(&queue.get_info().items), Some(2));
+
+        // La cola cambió entre los dos menús: se sigue a la misma canción
+        queue.move_track(0, 3).unwrap();
+        assert_eq!(selection.locate(&queue.get_info().items), Some(1));
+        queue.remove_track(1).unwrap();
+        assert_eq!(selection.locate(&queue.get_info().items), None);
+    }
+
+    #[test]
+    fn test_skip_cancels_loop_count() {
+        use open_music::audio::queue::LoopMode;
+
+        let mut queue = MusicQueue::new(100);
+        queue.add_track(track(1)).unwrap();
+        queue.add_track(track(2)).unwrap();
+        queue.next_track().unwrap();
+        queue.set_loop_mode(LoopMode::TrackCount(3));
+
+        // `/skip` de una canción: skip(0) y luego avanzar
+        queue.skip(0);
+        assert_eq!(queue.get_info().loop_mode, LoopMode::Off);
+        assert_eq!(&*queue.next_track().unwrap().title(), "Track 2");
+    }
+
+    #[test]
+    fn test_insert_at_position() {
+        let mut queue = MusicQueue::new(100);
+        for n in 0..3 {
+            queue.add_track(track(n)).unwrap();
+        }
+
+        queue.insert_at(1, track(10)).unwrap();
+        queue.insert_at(4, track(11)).unwrap(); // al final
+        assert!(queue.insert_at(6, track(12)).is_err());
+
+        let urls: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+        let expected: Vec<String> = [0, 10, 1, 2, 11].iter().map(|n| track(*n).url().to_string()).collect();
+        assert_eq!(urls, expected);
+    }
+
+    #[test]
+    fn test_shuffle_mode_keeps_inserted_position() {
+        let mut queue = MusicQueue::new(100);
+        for n in 0..10 {
+            queue.add_track(track(n)).unwrap();
+        }
+        queue.insert_at(3, track(99)).unwrap();
+        queue.toggle_shuffle();
+
+        // Suenan primero, en cualquier orden, las 3 canciones que tenía delante
+        let mut first: Vec<String> = (0..3).map(|_| queue.next_track().unwrap().url().to_string()).collect();
+        first.sort();
+        let ahead: Vec<String> = (0..3).map(|n| track(n).url().to_string()).collect();
+        assert_eq!(first, ahead);
+        assert_eq!(queue.next_track().unwrap().url(), track(99).url());
+    }
+
+    #[test]
+    fn test_shuffle_pending_keeps_current_and_inserted() {
+        let mut queue = MusicQueue::new(100);
+        for n in 0..21 {
+            queue.add_track(track(n)).unwrap();
+        }
+        let current = queue.next_track().unwrap();
+        queue.insert_at(0, track(98)).unwrap();
+        queue.insert_at(10, track(99)).unwrap();
+
+        let before: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+        let shuffled = queue.shuffle_pending();
+        let after: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+
+        assert_eq!(shuffled, 20);
+        assert_eq!(queue.current_track().unwrap().url(), current.url());
+        assert!(!queue.is_shuffle());
+        assert_eq!(after[0], track(98).url().to_string());
+        assert_eq!(after[10], track(99).url().to_string());
+
+        let mut sorted_before = before.clone();
+        let mut sorted_after = after.clone();
+        sorted_before.sort();
+        sorted_after.sort();
+        assert_eq!(sorted_before, sorted_after);
+        // 20! permutaciones: la probabilidad de conservar el orden es despreciable
+        assert_ne!(before, after);
+
+        // Las fijadas siguen fijadas para el modo aleatorio
+        queue.toggle_shuffle();
+        assert_eq!(queue.next_track().unwrap().url(), track(98).url());
+    }
+
+    #[test]
+    fn test_resume_point_after_reconnect() {
+        use open_music::audio::player::resume_point;
+        use std::time::Duration;
+
+        let secs = Duration::from_secs;
+        assert_eq!(resume_point(secs(1), Some(secs(200))), None);
+        assert_eq!(resume_point(secs(90), Some(secs(200))), Some(secs(90)));
+        // Cerca del final se recorta para que la pista termine normalmente
+        assert_eq!(resume_point(secs(199), Some(secs(200))), Some(secs(195)));
+        // Streams en vivo (sin duración) se retoman tal cual
+        assert_eq!(resume_point(secs(600), None), Some(secs(600)));
+    }
+
+    #[test]
+    fn test_peek_next_matches_next_track() {
+        use open_music::audio::queue::LoopMode;
+
+        let mut queue = MusicQueue::new(100);
+        assert!(queue.peek_next().is_none());
+        queue.add_track(track(1)).unwrap();
+        queue.add_track(track(2)).unwrap();
+        queue.next_track().unwrap();
+        assert_eq!(&*queue.peek_next().unwrap().title(), "Track 2");
+
+        queue.set_loop_mode(LoopMode::TrackCount(1));
+        assert_eq!(&*queue.peek_next().unwrap().title(), "Track 1");
+        assert_eq!(&*queue.next_track().unwrap().title(), "Track 1");
+        assert_eq!(&*queue.peek_next().unwrap().title(), "Track 2");
+
+        // Con shuffle la siguiente es aleatoria
+        queue.toggle_shuffle();
+        assert!(queue.peek_next().is_none());
+    }
+
+    #[test]
+    fn test_prefetch_starts_before_track_end() {
+        use open_music::audio::player::prefetch_delay;
+        use std::time::Duration;
+
+        let secs = Duration::from_secs;
+        assert_eq!(prefetch_delay(Some(secs(200)), 1.0), Some(secs(180)));
+        // A doble velocidad la pista dura la mitad
+        assert_eq!(prefetch_delay(Some(secs(200)), 2.0), Some(secs(80)));
+        assert_eq!(prefetch_delay(Some(secs(10)), 1.0), Some(Duration::ZERO));
+        assert_eq!(prefetch_delay(None, 1.0), None);
+    }
+
+    #[test]
+    fn test_ab_loop_validation() {
+        use open_music::audio::player::{AbLoop, AbLoopError};
+        use std::time::Duration;
+
+        let secs = Duration::from_secs;
+        let ab_loop = AbLoop::new(secs(60), secs(150), Some(secs(200))).unwrap();
+        assert!(ab_loop.contains(secs(60)));
+        assert!(ab_loop.contains(secs(149)));
+        // El final ya no pertenece a la sección: ahí se vuelve al inicio
+        assert!(!ab_loop.contains(secs(150)));
+        assert!(!ab_loop.contains(secs(30)));
+
+        assert_eq!(AbLoop::new(secs(60), secs(60), Some(secs(200))), Err(AbLoopError::TooShort));
+        assert_eq!(AbLoop::new(secs(90), secs(60), Some(secs(200))), Err(AbLoopError::TooShort));
+        assert_eq!(AbLoop::new(secs(60), secs(250), Some(secs(200))), Err(AbLoopError::PastTrackEnd));
+        assert_eq!(AbLoop::new(secs(0), secs(30), None), Err(AbLoopError::UnknownDuration));
+    }
+
+    #[test]
+    fn test_playback_progress_display() {
+        use open_music::ui::embeds::playback_progress;
+        use std::time::Duration;
+
+        let secs = Duration::from_secs;
+        assert_eq!(playback_progress(secs(0), Some(secs(200))), "`🔘▬▬▬▬▬▬▬▬▬▬▬▬▬▬` 0:00 / 3:20");
+        assert_eq!(playback_progress(secs(100), Some(secs(200))), "`▬▬▬▬▬▬▬🔘▬▬▬▬▬▬▬` 1:40 / 3:20");
+        // Posición más allá del final (la pista acaba de terminar): se acota
+        assert_eq!(playback_progress(secs(250), Some(secs(200))), "`▬▬▬▬▬▬▬▬▬▬▬▬▬▬🔘` 3:20 / 3:20");
+        assert_eq!(playback_progress(secs(65), None), "🔴 En vivo · 1:05");
+    }
+
+    #[test]
+    fn test_volume_ramp_steps() {
+        use open_music::audio::player::volume_ramp;
+
+        let ramp = volume_ramp(0.4, 1.5, 10);
+        assert_eq!(ramp.len(), 10);
+        assert!(ramp.windows(2).all(|w| w[1] > w[0]));
+        assert_eq!(*ramp.last().unwrap(), 1.5);
+        assert!((ramp[0] - 0.51).abs() < 1e-4);
+
+        // Bajar también es gradual
+        assert!(volume_ramp(1.0, 0.0, 4).windows(2).all(|w| w[1] < w[0]));
+        // Sin cambio (o sin pasos) se aplica directo
+        assert_eq!(volume_ramp(0.5, 0.5, 10), vec![0.5]);
+        assert_eq!(volume_ramp(0.2, 0.8, 0), vec![0.8]);
+    }
+
+    #[tokio::test]
+    async fn test_debug_state_of_idle_guild() {
+        use open_music::audio::player::AudioPlayer;
+        use serenity::model::id::GuildId;
+
+        let player = AudioPlayer::new(0.5, false);
+        let guild = GuildId::new(1);
+        player.get_or_create_queue(guild).write().add_track(track(1)).unwrap();
+
+        let state = player.debug_state(guild).await;
+        assert!(!state.is_playing);
+        assert_eq!(state.play_mode, None);
+        assert_eq!(state.queue_len, 1);
+        assert_eq!(state.volume, 0.5);
+        assert!(state.effects.is_default());
+    }
+
+    #[test]
+    fn test_now_playing_message_replacement() {
+        use open_music::audio::player::AudioPlayer;
+        use serenity::model::id::{ChannelId, GuildId, MessageId};
+
+        let player = AudioPlayer::new(0.5, false);
+        let guild = GuildId::new(1);
+        let channel = ChannelId::new(10);
+
+        assert_eq!(player.replace_now_playing_message(guild, channel, MessageId::new(100)), None);
+        assert_eq!(
+            player.replace_now_playing_message(guild, channel, MessageId::new(101)),
+            Some((channel, MessageId::new(100)))
+        );
+        // Volver a registrar el mismo mensaje no lo marca para borrar
+        assert_eq!(player.replace_now_playing_message(guild, channel, MessageId::new(101)), None);
+        assert_eq!(player.replace_now_playing_message(GuildId::new(2), channel, MessageId::new(200)), None);
+    }
+
+    #[tokio::test]
+    async fn test_restart_limit_per_track() {
+        use open_music::audio::player::{AudioPlayer, TooManyRestartsError, MAX_PLAYBACK_RESTARTS};
+        use serenity::model::id::GuildId;
+
+        let player = AudioPlayer::new(0.5, false);
+        let guild = GuildId::new(1);
+
+        for attempt in 1..=MAX_PLAYBACK_RESTARTS {
+            assert_eq!(player.register_restart(guild), Ok(attempt));
+        }
+        assert_eq!(
+            player.register_restart(guild),
+            Err(TooManyRestartsError(MAX_PLAYBACK_RESTARTS))
+        );
+        // Otras guilds no comparten el contador
+        assert_eq!(player.register_restart(GuildId::new(2)), Ok(1));
+
+        // Al detener la reproducción el contador se reinicia
+        player.stop(guild).await.unwrap();
+        assert_eq!(player.register_restart(guild), Ok(1));
+    }
+
+    #[test]
+    fn test_advance_to_user() {
+        use open_music::audio::queue::LoopMode;
+
+        let by = |n: usize, user: u64| track(n).with_requested_by(UserId::new(user));
+        let mut queue = MusicQueue::new(100);
+        for (n, user) in [(0, 1), (1, 1), (2, 2), (3, 1), (4, 2)] {
+            queue.add_track(by(n, user)).unwrap();
+        }
+        queue.next_track().unwrap();
+
+        // Sin canciones del usuario no cambia nada
+        assert!(queue.advance_to_user(UserId::new(3)).is_none());
+        assert_eq!(queue.current_track().unwrap().url(), track(0).url());
+        assert_eq!(queue.len(), 4);
+
+        let (skipped, now) = queue.advance_to_user(UserId::new(2)).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(now.url(), track(2).url());
+        assert_eq!(queue.current_track().unwrap().url(), track(2).url());
+        let urls: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+        assert_eq!(urls, vec![track(3).url().to_string(), track(4).url().to_string()]);
+
+        // Con loop de cola las saltadas vuelven al final
+        queue.set_loop_mode(LoopMode::Queue);
+        let (skipped, now) = queue.advance_to_user(UserId::new(2)).unwrap();
+        assert_eq!((skipped, now.url()), (1, track(4).url()));
+        let urls: Vec<String> = queue.get_tracks().iter().map(|t| t.url().to_string()).collect();
+        assert_eq!(urls, vec![track(3).url().to_string(), track(4).url().to_string()]);
+    }
+
+    #[test]
+    fn test_group_consecutive_album_tracks() {
+        use open_music::audio::queue::{group_by_album, QueueItem};
+
+        let album = |n: usize, name: &str| QueueItem::from(track(n).with_album_name(name.to_string()));
+        let items = vec![
+            album(1, "The Wall"),
+            album(2, "The Wall"),
+            QueueItem::from(track(3)),
+            QueueItem::from(track(4)),
+            album(5, "Animals"),
+            album(6, "The Wall"),
+        ];
+
+        let sizes: Vec<usize> = group_by_album(&items).iter().map(|group| group.len()).collect();
+        // Las canciones sueltas nunca se agrupan entre sí
+        assert_eq!(sizes, vec![2, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_find_track_overlapping_matches() {
+        let named = |n: usize, title: &str, artist: &str| {
+            TrackSource::new(
+                title.to_string(),
+                format!("https://www.youtube.com/watch?v={}", n),
+                SourceType::YouTube,
+                UserId::new(1),
+            )
+            .with_artist(artist.to_string())
+        };
+
+        let mut queue = MusicQueue::new(100);
+        queue.add_track(named(0, "Love Story", "Taylor Swift")).unwrap();
+        queue.add_track(named(1, "Lovely", "Billie Eilish")).unwrap();
+        queue.add_track(named(2, "Glove Box", "Lovers Club")).unwrap();
+        queue.add_track(named(3, "Yesterday", "The Beatles")).unwrap();
+        queue.next_track().unwrap();
+
+        // "love" aparece en la actual, en otro título y en un artista
+        let positions: Vec<usize> = queue.find_track("LOVE").iter().map(|(position, _)| *position).collect();
+        assert_eq!(positions, vec![0, 1, 2]);
+
+        // Una coincidencia más larga descarta las parciales
+        let lovely = queue.find_track("lovely");
+        assert_eq!(lovely.len(), 1);
+        assert_eq!(&*lovely[0].1.title(), "Lovely");
+
+        assert_eq!(queue.find_track("beatles")[0].0, 3);
+        assert!(queue.find_track("queen").is_empty());
+    }
+
+    #[test]
+    fn test_sort_by_field_orders_pending_and_keeps_current() {
+        let track = |n: u64, title: &str, artist: Option<&str>, secs: Option<u64>, user: u64| {
+            let mut source = TrackSource::new(
+                title.to_string(),
+                format!("https://www.youtube.com/watch?v={}", n),
+                SourceType::YouTube,
+                UserId::new(user),
+            );
+            if let Some(artist) = artist {
+                source = source.with_artist(artist.to_string());
+            }
+            if let Some(secs) = secs {
+                source = source.with_duration(std::time::Duration::from_secs(secs));
+            }
+            source
+        };
+
+        let mut queue = MusicQueue::new(100);
+        queue.add_track(track(0, "Now Playing", None, Some(10), 1)).unwrap();
+        queue.add_track(track(1, "bravo", Some("Zeta"), Some(300), 3)).unwrap();
+        queue.add_track(track(2, "Alpha", None, None, 2)).unwrap();
+        queue.add_track(track(3, "charlie", Some("alpha band"), Some(120), 1)).unwrap();
+        queue.next_track().unwrap();
+
+        let titles = |queue: &MusicQueue| -> Vec<String> { queue.get_tracks().iter().map(|t| t.title().to_string()).collect() };
+
+        assert_eq!(queue.sort_by_field(SortField::Title), 3);
+        assert_eq!(titles(&queue), vec!["Alpha", "bravo", "charlie"]);
+
+        // Sin artista o sin duración, al final
+        queue.sort_by_field(SortField::Artist);
+        assert_eq!(titles(&queue), vec!["charlie", "bravo", "Alpha"]);
+        queue.sort_by_field(SortField::DurationAsc);
+        assert_eq!(titles(&queue), vec!["charlie", "bravo", "Alpha"]);
+        queue.sort_by_field(SortField::DurationDesc);
+        assert_eq!(titles(&queue), vec!["bravo", "charlie", "Alpha"]);
+        queue.sort_by_field(SortField::Requester);
+        assert_eq!(titles(&queue), vec!["charlie", "Alpha", "bravo"]);
+
+        assert_eq!(queue.sort_by_field(SortField::Shuffle), 3);
+        assert!(!queue.is_shuffle());
+        assert_eq!(&*queue.current_track().unwrap().title(), "Now Playing");
+
+        assert_eq!(SortField::from_name("duration-desc"), Some(SortField::DurationDesc));
+        assert_eq!(SortField::from_name("album"), None);
+    }
+
+    fn sortable_queue() -> MusicQueue {
+        let mut queue = MusicQueue::new(100);
+        queue.add_track(track(0)).unwrap();
+        let tracks = [
+            ("delta", Some(200), 3),
+            ("Alpha", Some(90), 2),
+            ("charlie", None, 1),
+            ("Bravo", Some(90), 3),
+        ];
+        for (n, (title, secs, user)) in tracks.into_iter().enumerate() {
+            let mut source = TrackSource::new(
+                title.to_string(),
+                format!("https://www.youtube.com/watch?v=s{}", n),
+                SourceType::YouTube,
+                UserId::new(user),
+            );
+            if let Some(secs) = secs {
+                source = source.with_duration(std::time::Duration::from_secs(secs));
+            }
+            queue.add_track(source).unwrap();
+        }
+        queue.next_track().unwrap();
+        queue
+    }
+
+    #[test]
+    fn test_up_next_list_is_compact() {
+        use open_music::ui::embeds::up_next_list;
+
+        let mut queue = sortable_queue();
+        let list = up_next_list(&queue.get_info()).unwrap();
+        assert_eq!(list, "`1.` delta · 3:20\n`2.` Alpha · 1:30\n`3.` charlie · 🔴 En vivo\n…y 1 más en `/queue`");
+
+        // Títulos largos se recortan
+        let long = "x".repeat(200);
+        let mut queue_long = MusicQueue::new(100);
+        queue_long.add_track(TrackSource::new(long, "https://example.com/a", SourceType::DirectUrl, UserId::new(1))).unwrap();
+        let list = up_next_list(&queue_long.get_info()).unwrap();
+        assert!(list.chars().count() < 80);
+        assert!(list.contains('…'));
+
+        queue.clear();
+        assert_eq!(up_next_list(&queue.get_info()), None);
+    }
+
+    fn titles(queue: &MusicQueue) -> Vec<String> {
+        queue.get_tracks().iter().map(|t| t.title().to_string()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_duration_keeps_live_tracks_last() {
+        use open_music::audio::queue::{SortKey, SortOrder};
+
+        let mut queue = sortable_queue();
+        assert_eq!(queue.sort_by(SortKey::Duration, SortOrder::Asc), 4);
+        // Empate de 90s: se conserva el orden original (estable)
+        assert_eq!(titles(&queue), ["Alpha", "Bravo", "delta", "charlie"]);
+        assert_eq!(&*queue.current_track().unwrap().title(), "Track 0");
+
+        let mut queue = sortable_queue();
+        queue.sort_by(SortKey::Duration, SortOrder::Desc);
+        assert_eq!(titles(&queue), ["delta", "Alpha", "Bravo", "charlie"]);
+    }
+
+    #[test]
+    fn test_sort_by_title_ignores_case() {
+        use open_music::audio::queue::{SortKey, SortOrder};
+
+        let mut queue = sortable_queue();
+        queue.sort_by(SortKey::Title, SortOrder::Asc);
+        assert_eq!(titles(&queue), ["Alpha", "Bravo", "charlie", "delta"]);
+
+        queue.sort_by(SortKey::Title, SortOrder::Desc);
+        assert_eq!(titles(&queue), ["delta", "charlie", "Bravo", "Alpha"]);
+    }
+
+    #[test]
+    fn test_sort_by_requester_is_stable() {
+        use open_music::audio::queue::{SortKey, SortOrder};
+
+        let mut queue = sortable_queue();
+        queue.sort_by(SortKey::Requester, SortOrder::Asc);
+        assert_eq!(titles(&queue), ["charlie", "Alpha", "delta", "Bravo"]);
+
+        let mut queue = sortable_queue();
+        queue.sort_by(SortKey::Requester, SortOrder::Desc);
+        assert_eq!(titles(&queue), ["delta", "Bravo", "Alpha", "charlie"]);
+    }
+
+    #[test]
+    fn test_enrich_track_fills_metadata_in_place() {
+        use std::time::Duration;
+
+        let mut queue = MusicQueue::new(100);
+        for n in 0..3 {
+            queue.add_track(track(n)).unwrap();
+        }
+        let full = TrackSource::new("Track 1 (Official)", "https://www.youtube.com/watch?v=1", SourceType::YouTube, UserId::new(9))
+            .with_duration(Duration::from_secs(200))
+            .with_thumbnail("https://i.ytimg.com/vi/1/hqdefault.jpg")
+            .with_artist("Artista");
+
+        assert_eq!(queue.enrich_track(&full), 1);
+        let tracks = queue.get_tracks();
+        let enriched = &tracks[1];
+        assert_eq!(&*enriched.title(), "Track 1");
+        assert_eq!(enriched.requested_by(), UserId::new(1));
+        assert_eq!(enriched.duration(), Some(Duration::from_secs(200)));
+        assert_eq!(enriched.artist().as_deref(), Some("Artista"));
+        assert!(tracks[0].duration().is_none());
+        assert_eq!(queue.get_info().total_duration, Duration::from_secs(200));
+
+        assert!(queue.contains_url("https://www.youtube.com/watch?v=2"));
+        assert!(!queue.contains_url("https://www.youtube.com/watch?v=7"));
+    }
+}
@@ -0,0 +1,111 @@
+//! Tests for the music quiz game mode
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::quiz::{
+        is_correct_guess, levenshtein, normalize_title, similarity, GuessOutcome, QuizSession, QuizSessions,
+    };
+    use open_music::sources::{SourceType, TrackSource};
+    use serenity::model::id::{GuildId, UserId};
+
+    fn track(title: &str) -> TrackSource {
+        TrackSource::new(
+            title.to_string(),
+            format!("https://www.youtube.com/watch?v={}", title.len()),
+            SourceType::YouTube,
+            UserId::new(1),
+        )
+    }
+
+    #[test]
+    fn test_levenshtein_and_similarity() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("canción", "cancion"), 1);
+        assert_eq!(similarity("abc", "abc"), 1.0);
+        assert_eq!(similarity("abcd", "abce"), 0.75);
+    }
+
+    #[test]
+    fn test_normalize_title_drops_decorations() {
+        assert_eq!(
+            normalize_title("Queen - Bohemian Rhapsody (Official Video) [4K]"),
+            "queen bohemian rhapsody"
+        );
+        assert_eq!(normalize_title("  Don't   Stop Me Now!! "), "don t stop me now");
+    }
+
+    #[test]
+    fn test_guess_matches_full_title_or_song_name() {
+        let title = "Queen - Bohemian Rhapsody (Official Video)";
+        assert!(is_correct_guess("bohemian rhapsody", title));
+        assert!(is_correct_guess("Bohemian Rapsody", title));
+        assert!(is_correct_guess("queen bohemian rhapsody", title));
+        assert!(!is_correct_guess("queen", title));
+        assert!(!is_correct_guess("", title));
+        assert!(!is_correct_guess("(official video)", title));
+    }
+
+    #[test]
+    fn test_first_correct_guess_wins_the_round() {
+        let mut session = QuizSession::new(GuildId::new(7), UserId::new(1), 10);
+        assert_eq!(session.submit_guess(UserId::new(2), "anything"), GuessOutcome::RoundClosed);
+
+        assert_eq!(session.start_round(track("Artist - Song One")), 1);
+        assert_eq!(session.submit_guess(UserId::new(2), "song two"), GuessOutcome::Wrong);
+        assert_eq!(session.submit_guess(UserId::new(3), "song one"), GuessOutcome::Correct { score: 1 });
+        assert_eq!(session.submit_guess(UserId::new(2), "song one"), GuessOutcome::RoundClosed);
+        assert_eq!(session.round_winner(), Some(UserId::new(3)));
+
+        assert_eq!(session.start_round(track("Artist - Another Tune")), 2);
+        assert_eq!(session.round_winner(), None);
+        assert_eq!(session.submit_guess(UserId::new(2), "another tune"), GuessOutcome::Correct { score: 1 });
+        session.start_round(track("Artist - Last One"));
+        assert_eq!(session.submit_guess(UserId::new(3), "last one"), GuessOutcome::Correct { score: 2 });
+
+        assert_eq!(session.leaderboard(), vec![(UserId::new(3), 2), (UserId::new(2), 1)]);
+    }
+
+    #[test]
+    fn test_sessions_one_quiz_per_guild() {
+        let sessions = QuizSessions::new();
+        let guild = GuildId::new(7);
+        assert_eq!(sessions.submit_guess(guild, UserId::new(2), "x"), GuessOutcome::NoQuiz);
+
+        assert!(sessions.begin(guild, UserId::new(1), 3));
+        assert!(!sessions.begin(guild, UserId::new(2), 3));
+        assert_eq!(sessions.started_by(guild), Some(UserId::new(1)));
+
+        let (round, round_done) = sessions.start_round(guild, track("Artist - Song")).unwrap();
+        assert_eq!(round, 1);
+        assert!(sessions.skip_round(guild));
+        assert!(round_done.is_cancelled());
+        assert_eq!(sessions.submit_guess(guild, UserId::new(2), "song"), GuessOutcome::RoundClosed);
+
+        assert!(sessions.end(guild));
+        assert!(sessions.start_round(guild, track("Artist - Other")).is_none());
+        assert!(sessions.finish(guild).is_some());
+        assert!(!sessions.is_active(guild));
+    }
+
+    #[test]
+    fn test_track_requests_refused_during_quiz() {
+        // La usan los comandos que piden canciones y el botón ✨ Similares
+        use open_music::bot::handlers::{track_request_block, TrackRequestBlock};
+
+        let sessions = QuizSessions::new();
+        let guild = GuildId::new(7);
+        assert_eq!(track_request_block(true, &sessions, guild), None);
+        assert_eq!(
+            track_request_block(false, &sessions, guild),
+            Some(TrackRequestBlock::AudioBackendUnavailable)
+        );
+
+        assert!(sessions.begin(guild, UserId::new(1), 3));
+        assert_eq!(track_request_block(true, &sessions, guild), Some(TrackRequestBlock::QuizActive));
+        assert_eq!(track_request_block(true, &sessions, GuildId::new(8)), None);
+
+        sessions.finish(guild);
+        assert_eq!(track_request_block(true, &sessions, guild), None);
+    }
+}
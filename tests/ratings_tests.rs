@@ -0,0 +1,66 @@
+//! Tests for the per-guild track ratings
+
+#[cfg(test)]
+mod tests {
+    use open_music::storage::ratings::RatingStore;
+    use open_music::ui::embeds::rating_stars;
+
+    const SONG: &str = "https://www.youtube.com/watch?v=song";
+    const OTHER: &str = "https://www.youtube.com/watch?v=other";
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("open_music_ratings_{}_{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_average_replaces_previous_rating_per_user() {
+        let dir = temp_dir("average");
+        let store = RatingStore::new(dir.clone());
+        assert_eq!(store.get_average_rating(7, SONG), None);
+
+        store.add_rating(7, SONG, "Song", 1, 5).await.unwrap();
+        store.add_rating(7, SONG, "Song", 2, 2).await.unwrap();
+        assert_eq!(store.get_average_rating(7, SONG), Some(3.5));
+
+        // El mismo usuario cambia de opinión: no cuenta dos veces
+        let average = store.add_rating(7, SONG, "Song", 2, 4).await.unwrap();
+        assert_eq!(average, 4.5);
+        assert_eq!(store.get_average_rating(8, SONG), None);
+
+        assert!(store.add_rating(7, SONG, "Song", 3, 0).await.is_err());
+        assert!(store.add_rating(7, SONG, "Song", 3, 6).await.is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_top_rated_and_reload_from_disk() {
+        let dir = temp_dir("top");
+        let store = RatingStore::new(dir.clone());
+        store.add_rating(7, OTHER, "Other", 1, 3).await.unwrap();
+        store.add_rating(7, SONG, "Song", 1, 4).await.unwrap();
+        store.add_rating(7, SONG, "Song", 2, 5).await.unwrap();
+        assert!(dir.join("guild_7.json").exists());
+        // Un archivo dañado no impide cargar las demás guilds
+        std::fs::write(dir.join("guild_8.json"), "{ roto").unwrap();
+
+        let reloaded = RatingStore::new(dir.clone());
+        assert_eq!(reloaded.load().await.unwrap(), 1);
+        let top = reloaded.top_rated(7, 10);
+        let titles: Vec<_> = top.iter().map(|track| track.title.as_str()).collect();
+        assert_eq!(titles, vec!["Song", "Other"]);
+        assert_eq!(top[0].average, 4.5);
+        assert_eq!(top[0].count, 2);
+        assert_eq!(reloaded.top_rated(7, 1).len(), 1);
+        assert!(reloaded.top_rated(9, 10).is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rating_stars() {
+        assert_eq!(rating_stars(4.5), "⭐⭐⭐⭐⭐ 4.5/5");
+        assert_eq!(rating_stars(3.25), "⭐⭐⭐ 3.2/5");
+        assert_eq!(rating_stars(1.0), "⭐ 1.0/5");
+    }
+}
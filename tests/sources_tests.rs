@@ -0,0 +1,453 @@
+//! Tests for sources module
+
+#[cfg(test)]
+mod tests {
+    use open_music::sources::tidal::{TidalLink, TidalResource};
+    use open_music::sources::ytdlp_optimized::{classify_failure, FailureKind};
+
+    #[test]
+    fn test_classify_transient_failures() {
+        assert_eq!(
+            classify_failure("ERROR: Unable to download webpage: HTTP Error 429: Too Many Requests"),
+            FailureKind::Retryable
+        );
+        assert_eq!(
+            classify_failure("ERROR: HTTP Error 503: Service Unavailable"),
+            FailureKind::Retryable
+        );
+        assert_eq!(classify_failure("ERROR: The read operation timed out"), FailureKind::Retryable);
+    }
+
+    #[test]
+    fn test_classify_permanent_failures() {
+        assert!(matches!(
+            classify_failure("ERROR: [youtube] abc: Private video. Sign in if you've been granted access"),
+            FailureKind::Permanent(_)
+        ));
+        assert!(matches!(
+            classify_failure("ERROR: [youtube] abc: Video unavailable"),
+            FailureKind::Permanent(_)
+        ));
+    }
+
+    #[test]
+    fn test_track_failure_reasons() {
+        use open_music::sources::{ExtractionTimeoutError, PermanentExtractionError, TrackFailureReason};
+        use std::time::Duration;
+
+        let reason = |error: anyhow::Error| TrackFailureReason::from_error(&error);
+        assert_eq!(
+            reason(anyhow::anyhow!("yt-dlp info failed: ERROR: [youtube] abc: Video unavailable")),
+            TrackFailureReason::Unavailable
+        );
+        assert_eq!(
+            reason(anyhow::anyhow!("ERROR: [youtube] abc: The uploader has not made this video available in your country")),
+            TrackFailureReason::RegionLocked
+        );
+        assert_eq!(reason(PermanentExtractionError("🔒 El video es privado").into()), TrackFailureReason::Unavailable);
+        assert_eq!(reason(ExtractionTimeoutError(Duration::from_secs(90)).into()), TrackFailureReason::SourceDown);
+        assert_eq!(reason(anyhow::anyhow!("HTTP Error 503: Service Unavailable")), TrackFailureReason::SourceDown);
+        assert_eq!(reason(anyhow::anyhow!("La cola está llena (máximo 10 canciones)")), TrackFailureReason::Other);
+    }
+
+    #[test]
+    fn test_sign_in_failures_need_authentication() {
+        use open_music::sources::ytdlp_optimized::needs_authentication;
+        use open_music::sources::{PermanentExtractionError, RestrictedVideoError};
+
+        assert!(needs_authentication(&RestrictedVideoError::MembersOnly.into()));
+        assert!(needs_authentication(&anyhow::anyhow!(
+            "yt-dlp info failed: ERROR: [youtube] abc: Sign in to confirm you're not a bot"
+        )));
+        assert!(!needs_authentication(&PermanentExtractionError("🔒 El video es privado").into()));
+        assert!(!needs_authentication(&anyhow::anyhow!("yt-dlp info failed: HTTP Error 429")));
+    }
+
+    #[test]
+    fn test_classify_restricted_failures() {
+        use open_music::sources::RestrictedVideoError;
+
+        assert_eq!(
+            classify_failure("ERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate for some users."),
+            FailureKind::Restricted(RestrictedVideoError::AgeRestricted)
+        );
+        assert_eq!(
+            classify_failure("ERROR: [youtube] abc: Join this channel to get access to members-only content like this video"),
+            FailureKind::Restricted(RestrictedVideoError::MembersOnly)
+        );
+    }
+
+    #[test]
+    fn test_classify_unknown_failure() {
+        assert_eq!(classify_failure("ERROR: something unexpected"), FailureKind::Unknown);
+    }
+
+    #[test]
+    fn test_parse_tidal_links() {
+        let track = TidalLink::parse("https://tidal.com/browse/track/77640617").unwrap();
+        assert_eq!(track.resource, TidalResource::Track);
+        assert_eq!(track.id, "77640617");
+
+        let album = TidalLink::parse("https://listen.tidal.com/album/77640616").unwrap();
+        assert_eq!(album.resource, TidalResource::Album);
+        assert_eq!(album.canonical_url(), "https://tidal.com/browse/album/77640616");
+
+        let playlist = TidalLink::parse(
+            "https://tidal.com/playlist/0ae1d4c8-2a3a-4b5e-9f5c-3f1a2b3c4d5e",
+        )
+        .unwrap();
+        assert_eq!(playlist.resource, TidalResource::Playlist);
+    }
+
+    #[test]
+    fn test_reject_non_tidal_links() {
+        assert!(TidalLink::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").is_none());
+        assert!(TidalLink::parse("https://tidal.com/browse/artist/123").is_none());
+        assert!(TidalLink::parse("https://tidal.com/browse/track/abc").is_none());
+        assert!(TidalLink::parse("https://nottidal.com/browse/track/123").is_none());
+    }
+
+    #[test]
+    fn test_extract_playlist_index() {
+        use open_music::sources::YtDlpOptimizedClient;
+
+        assert_eq!(
+            YtDlpOptimizedClient::extract_playlist_index("https://www.youtube.com/watch?v=abc&list=PLx&index=5"),
+            Some(5)
+        );
+        assert_eq!(YtDlpOptimizedClient::extract_playlist_index("https://www.youtube.com/playlist?list=PLx"), None);
+        assert_eq!(YtDlpOptimizedClient::extract_playlist_index("https://www.youtube.com/playlist?list=PLx&index=0"), None);
+    }
+
+    #[test]
+    fn test_youtube_shorts_urls() {
+        use open_music::sources::YtDlpOptimizedClient;
+
+        let shorts = "https://www.youtube.com/shorts/dQw4w9WgXcQ?feature=share";
+        assert!(YtDlpOptimizedClient::is_youtube_url(shorts));
+        assert_eq!(YtDlpOptimizedClient::extract_video_id(shorts).unwrap(), "dQw4w9WgXcQ");
+        assert_eq!(
+            YtDlpOptimizedClient::normalize_youtube_url(shorts),
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        );
+        assert_eq!(
+            YtDlpOptimizedClient::normalize_youtube_url("https://m.youtube.com/shorts/abc123"),
+            "https://www.youtube.com/watch?v=abc123"
+        );
+
+        // Las URLs que no son Shorts no cambian
+        for url in [
+            "https://www.youtube.com/watch?v=abc123",
+            "https://youtu.be/abc123",
+            "https://www.youtube.com/shorts/",
+            "https://example.com/shorts/abc123",
+        ] {
+            assert_eq!(YtDlpOptimizedClient::normalize_youtube_url(url), url);
+        }
+    }
+
+    #[test]
+    fn test_youtube_album_detection() {
+        use open_music::sources::YtDlpOptimizedClient;
+        use serenity::model::id::UserId;
+
+        assert!(YtDlpOptimizedClient::is_youtube_album("Pink Floyd - The Wall [Full Album]"));
+        assert!(YtDlpOptimizedClient::is_youtube_album("Soda Stereo - Discografía completa"));
+        assert!(!YtDlpOptimizedClient::is_youtube_album("Mix de verano 2024"));
+
+        let album = "https://www.youtube.com/watch?v=abc|Another Brick|Pink Floyd|240|Pink Floyd - The Wall (Full Album)";
+        let track = YtDlpOptimizedClient::parse_playlist_line(album, UserId::new(7)).unwrap();
+        assert_eq!(track.album_name(), Some("Pink Floyd - The Wall (Full Album)"));
+
+        let playlist = "https://www.youtube.com/watch?v=abc|Another Brick|Pink Floyd|240|Rock clásico";
+        assert_eq!(YtDlpOptimizedClient::parse_playlist_line(playlist, UserId::new(7)).unwrap().album_name(), None);
+        let no_playlist = "https://www.youtube.com/watch?v=abc|Another Brick|Pink Floyd|240|NA";
+        assert_eq!(YtDlpOptimizedClient::parse_playlist_line(no_playlist, UserId::new(7)).unwrap().album_name(), None);
+    }
+
+    #[test]
+    fn test_current_chapter_lookup() {
+        use open_music::sources::{Chapter, SourceType, TrackSource};
+        use serenity::model::id::UserId;
+        use std::time::Duration;
+
+        let chapters: Vec<Chapter> = serde_json::from_str(
+            r#"[{"start_time": 0.0, "title": "Intro", "end_time": 30.0},
+                {"start_time": 30.0, "title": "Verso", "end_time": 95.5}]"#,
+        )
+        .unwrap();
+
+        let track = TrackSource::new(
+            "Video".to_string(),
+            "https://www.youtube.com/watch?v=abc".to_string(),
+            SourceType::YouTube,
+            UserId::new(1),
+        )
+        .with_chapters(chapters);
+
+        let title_at = |secs| track.current_chapter(Duration::from_secs(secs)).map(|c| c.title.as_str());
+        assert_eq!(title_at(0), Some("Intro"));
+        assert_eq!(title_at(30), Some("Verso"));
+        assert_eq!(title_at(120), None);
+        assert_eq!(track.current_chapter_index(Duration::from_secs(45)), Some(1));
+    }
+
+    fn candidate(title: &str, artist: &str, secs: u64) -> open_music::sources::TrackSource {
+        use open_music::sources::{SourceType, TrackSource};
+        use serenity::model::id::UserId;
+
+        TrackSource::new(
+            title.to_string(),
+            format!("https://www.youtube.com/watch?v={}", title.len()),
+            SourceType::YouTube,
+            UserId::new(1),
+        )
+        .with_artist(artist.to_string())
+        .with_duration(std::time::Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn test_ranking_prefers_official_studio_version() {
+        use open_music::sources::ranking::best_match;
+        use open_music::sources::SearchPreferences;
+
+        let candidates = vec![
+            candidate("Daft Punk - One More Time (Live at Coachella)", "fanchannel", 420),
+            candidate("Daft Punk - One More Time (Reaction)", "reactor", 600),
+            candidate("Daft Punk - One More Time (Official Video)", "Daft Punk - Topic", 320),
+        ];
+
+        let best = best_match(&candidates, "daft punk one more time", &SearchPreferences::default()).unwrap();
+        assert_eq!(&*best.title(), "Daft Punk - One More Time (Official Video)");
+
+        // Sin penalizaciones ni preferencia oficial gana el primero que empate en relevancia
+        let neutral = SearchPreferences {
+            penalize_alternate_versions: false,
+            prefer_official: false,
+            min_duration_secs: 0,
+            max_duration_secs: 3600,
+        };
+        let best = best_match(&candidates, "daft punk one more time", &neutral).unwrap();
+        assert_eq!(&*best.title(), "Daft Punk - One More Time (Live at Coachella)");
+    }
+
+    #[test]
+    fn test_ranking_duration_window_and_explicit_variant() {
+        use open_music::sources::ranking::{best_match, score};
+        use open_music::sources::SearchPreferences;
+
+        let short = candidate("Lex Fridman Podcast clip", "clips", 240);
+        let full = candidate("Lex Fridman Podcast episode", "clips", 3 * 3600);
+        let candidates = vec![short.clone(), full.clone()];
+
+        let podcasts = SearchPreferences {
+            min_duration_secs: 30 * 60,
+            max_duration_secs: 4 * 3600,
+            ..SearchPreferences::default()
+        };
+        assert_eq!(best_match(&candidates, "lex fridman podcast", &podcasts).unwrap().title(), full.title());
+        assert_eq!(
+            best_match(&candidates, "lex fridman podcast", &SearchPreferences::default()).unwrap().title(),
+            short.title()
+        );
+
+        // Pedir "live" explícitamente no penaliza la versión en vivo
+        let live = candidate("Song live", "band", 200);
+        let prefs = SearchPreferences::default();
+        assert!(score(&live, "song live", &prefs) > score(&live, "song", &prefs));
+        assert!(best_match(&[], "song", &prefs).is_none());
+    }
+
+    #[test]
+    fn test_content_filter_blocks_keywords_channels_and_sources() {
+        use open_music::sources::content_filter::{add_entry, parse_allowed_sources, remove_entry};
+        use open_music::sources::{BlockedTrackError, ContentFilter, SourceType};
+
+        let track = candidate("Song (Nightcore Remix)", "Some Channel", 200);
+        let mut filter = ContentFilter::default();
+        assert!(filter.is_empty());
+        assert_eq!(filter.check(&track), Ok(()));
+
+        assert!(add_entry(&mut filter.blocked_keywords, "nightcore"));
+        assert!(!add_entry(&mut filter.blocked_keywords, "NightCore"));
+        assert_eq!(filter.check(&track), Err(BlockedTrackError::Keyword("nightcore".to_string())));
+        assert!(remove_entry(&mut filter.blocked_keywords, "NIGHTCORE"));
+        assert!(!remove_entry(&mut filter.blocked_keywords, "nightcore"));
+
+        add_entry(&mut filter.blocked_channels, "some channel");
+        assert_eq!(filter.check(&track), Err(BlockedTrackError::Channel("some channel".to_string())));
+        filter.blocked_channels.clear();
+
+        // Solo Tidal permitido: YouTube y URLs directas quedan fuera
+        filter.allowed_sources = parse_allowed_sources("tidal").unwrap();
+        assert_eq!(filter.check(&track), Err(BlockedTrackError::Source("youtube")));
+        assert_eq!(filter.check(&track.clone().with_source_type(SourceType::Tidal)), Ok(()));
+
+        let mut tracks = vec![track.clone(), track.with_source_type(SourceType::Tidal)];
+        assert_eq!(filter.retain_allowed(&mut tracks), 1);
+        assert_eq!(tracks.len(), 1);
+    }
+
+    #[test]
+    fn test_source_type_string_round_trip() {
+        use open_music::sources::{SourceType, UnknownSourceTypeError};
+
+        for source in SourceType::ALL {
+            assert_eq!(source.as_str().parse::<SourceType>(), Ok(source));
+            assert_eq!(source.to_string(), source.as_str());
+        }
+        // Playlists guardadas antes con `{:?}`
+        assert_eq!("YouTube".parse::<SourceType>(), Ok(SourceType::YouTube));
+        assert_eq!("DirectUrl".parse::<SourceType>(), Ok(SourceType::DirectUrl));
+        assert_eq!(
+            "spotify".parse::<SourceType>(),
+            Err(UnknownSourceTypeError("spotify".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_allowed_sources() {
+        use open_music::sources::content_filter::parse_allowed_sources;
+
+        assert_eq!(parse_allowed_sources("all"), Some(vec![]));
+        assert_eq!(
+            parse_allowed_sources("YouTube, tidal,youtube"),
+            Some(vec!["tidal".to_string(), "youtube".to_string()])
+        );
+        assert_eq!(parse_allowed_sources("spotify"), None);
+        assert_eq!(parse_allowed_sources(" , "), None);
+    }
+
+    #[tokio::test]
+    async fn test_extraction_timeout() {
+        use open_music::sources::{ExtractionPool, ExtractionTimeoutError};
+        use serenity::model::id::GuildId;
+        use std::time::Duration;
+
+        let pool = ExtractionPool::default();
+        let timeout = Duration::from_millis(20);
+
+        let err = pool
+            .run(GuildId::new(1), timeout, std::future::pending::<anyhow::Result<()>>())
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<ExtractionTimeoutError>(), Some(&ExtractionTimeoutError(timeout)));
+
+        // Tras el timeout el cupo de la guild queda libre
+        assert_eq!(pool.run(GuildId::new(1), timeout, async { Ok(7) }).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_stuck_guild_does_not_starve_others() {
+        use open_music::sources::ExtractionPool;
+        use serenity::model::id::GuildId;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        // Pool global de 2 con 1 por guild: una guild atascada solo ocupa uno
+        let pool = Arc::new(ExtractionPool::new(2, 1));
+        for _ in 0..3 {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let _ = pool
+                    .run(GuildId::new(1), Duration::from_secs(60), std::future::pending::<anyhow::Result<()>>())
+                    .await;
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let other = pool.run(GuildId::new(2), Duration::from_secs(1), async { Ok("ok") });
+        let result = tokio::time::timeout(Duration::from_secs(1), other).await;
+        assert_eq!(result.expect("la otra guild no debe esperar").unwrap(), "ok");
+    }
+
+    #[test]
+    fn test_direct_url_metadata_from_ffprobe() {
+        use open_music::sources::direct::StreamMetadata;
+        use serenity::model::id::UserId;
+
+        let file = r#"{"format": {"duration": "183.5", "tags": {"TITLE": "Canción", "ARTIST": "Autor"}}}"#;
+        let track = StreamMetadata::from_ffprobe_json(file).unwrap().into_track("https://cdn.example.com/a.mp3", UserId::new(1));
+        assert_eq!(&*track.title(), "Canción");
+        assert_eq!(track.artist().as_deref(), Some("Autor"));
+        assert_eq!(track.duration(), Some(std::time::Duration::from_secs_f64(183.5)));
+
+        // Radio ICY: la emisora hace de artista y no hay duración
+        let radio = r#"{"format": {"duration": "N/A", "tags": {"icy-name": "Radio Uno", "StreamTitle": "Tema en vivo"}}}"#;
+        let track = StreamMetadata::from_ffprobe_json(radio).unwrap().into_track("http://radio.example.com:8000/live", UserId::new(1));
+        assert_eq!(&*track.title(), "Tema en vivo");
+        assert_eq!(track.artist().as_deref(), Some("Radio Uno"));
+        assert_eq!(track.duration(), None);
+        assert_eq!(track.station(), Some("Radio Uno"));
+
+        // Radio que no anuncia la canción: el título es la emisora
+        let silent = r#"{"format": {"tags": {"icy-name": "Radio Dos"}}}"#;
+        let track = StreamMetadata::from_ffprobe_json(silent).unwrap().into_track("http://radio.example.com/live", UserId::new(1));
+        assert_eq!(&*track.title(), "Radio Dos");
+        assert!(track.is_radio());
+    }
+
+    #[test]
+    fn test_direct_url_fallback_title() {
+        use open_music::sources::direct::fallback_title;
+
+        assert_eq!(fallback_title("https://cdn.example.com/music/mi%20tema.mp3?x=1"), "mi tema.mp3");
+        assert_eq!(fallback_title("https://stream.example.com/"), "stream.example.com");
+        assert_eq!(fallback_title("no es una url"), "no es una url");
+    }
+
+    #[test]
+    fn test_direct_url_validation_rejects_unsafe_inputs() {
+        use open_music::sources::direct::validate_stream_url;
+
+        assert!(validate_stream_url("https://cdn.example.com/tema.mp3").is_ok());
+        assert!(validate_stream_url("http://radio.example.com:8000/live").is_ok());
+        assert!(validate_stream_url("http://8.8.8.8/stream").is_ok());
+
+        for url in [
+            "file:///etc/passwd",
+            "concat:https://a.example.com/1.mp3|https://a.example.com/2.mp3",
+            "subfile,,start,0,end,100,,:/etc/passwd",
+            "-i /etc/passwd",
+            "ftp://files.example.com/tema.mp3",
+            "http://localhost:8080/admin",
+            "http://127.0.0.1/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.5/stream",
+            "http://192.168.1.1/",
+            "http://[::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[fd00::1]/",
+            "http://redis:6379/",
+            "http://bgutil-provider:4416/",
+        ] {
+            assert!(validate_stream_url(url).is_err(), "{} debería rechazarse", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_host_resolving_to_loopback_is_rejected() {
+        use open_music::sources::direct::{check_stream_url, ensure_public_host};
+
+        // `localhost` resuelve a 127.0.0.1 / ::1 vía /etc/hosts, sin red
+        assert!(ensure_public_host("localhost", 80).await.is_err());
+        assert!(check_stream_url("http://localhost./stream").await.is_err());
+        assert!(ensure_public_host("8.8.8.8", 80).await.is_ok());
+    }
+
+    #[test]
+    fn test_parse_ytdlp_update_output() {
+        use open_music::sources::ytdlp_optimized::parse_updated_version;
+
+        let updated = "Current version: stable@2024.07.25 from yt-dlp/yt-dlp\n\
+            Latest version: stable@2024.08.06 from yt-dlp/yt-dlp\n\
+            Current Build Hash: 0a1b2c\n\
+            Updating to stable@2024.08.06 from yt-dlp/yt-dlp ...\n\
+            Updated yt-dlp to stable@2024.08.06 from yt-dlp/yt-dlp\n";
+        assert_eq!(parse_updated_version(updated).as_deref(), Some("2024.08.06"));
+
+        let up_to_date = "Latest version: stable@2024.08.06 from yt-dlp/yt-dlp\n\
+            yt-dlp is up to date (stable@2024.08.06 from yt-dlp/yt-dlp)\n";
+        assert_eq!(parse_updated_version(up_to_date), None);
+    }
+}
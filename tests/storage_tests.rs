@@ -171,4 +171,615 @@ mod tests {
             "/app/data/playlists/987654321/my_playlist.json"
         );
     }
+
+    #[test]
+    fn test_playlist_track_roundtrip_to_track_source() {
+        use open_music::sources::{SourceType, TrackSource};
+        use open_music::storage::PlaylistTrack;
+        use serenity::model::id::UserId;
+
+        let source = TrackSource::new(
+            "Song".to_string(),
+            "https://www.youtube.com/watch?v=abc".to_string(),
+            SourceType::Tidal,
+            UserId::new(7),
+        )
+        .with_artist("Artist".to_string())
+        .with_duration(Duration::from_secs(215));
+
+        let saved = PlaylistTrack::from_track_source(&source, 7);
+        let restored = saved.to_track_source(UserId::new(9));
+
+        assert_eq!(&*restored.title(), "Song");
+        assert_eq!(restored.artist().as_deref(), Some("Artist"));
+        assert_eq!(restored.duration(), Some(Duration::from_secs(215)));
+        assert_eq!(restored.source_type(), SourceType::Tidal);
+        assert_eq!(restored.requested_by(), UserId::new(9));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_server_config_migrates_eq_preset_to_flat() {
+        use open_music::storage::JsonStorage;
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_eq_{}", std::process::id()));
+        let servers_dir = data_dir.join("servers");
+        std::fs::create_dir_all(&servers_dir).unwrap();
+
+        // Config guardada antes de que existiera el ecualizador persistido
+        std::fs::write(
+            servers_dir.join("guild_42.json"),
+            r#"{"guild_id":42,"default_volume":0.5,"max_queue_size":100,"auto_leave_timeout":300,
+                "dj_role_id":null,"announcement_channel_id":null,"auto_leave_empty":true}"#,
+        )
+        .unwrap();
+
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert_eq!(storage.get_eq_settings(42), (Some("flat".to_string()), None));
+
+        let on_disk = std::fs::read_to_string(servers_dir.join("guild_42.json")).unwrap();
+        assert!(on_disk.contains(r#""active_eq_preset": "flat""#));
+
+        storage.set_eq_preset(42, "bass").await.unwrap();
+        let reloaded = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert_eq!(reloaded.get_eq_settings(42).0.as_deref(), Some("bass"));
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_embed_color_persists_in_guild_settings() {
+        use open_music::storage::{GuildSettings, JsonStorage};
+        use open_music::ui::embeds::{colors, get_guild_color, parse_hex_color};
+        use serenity::all::Colour;
+
+        assert_eq!(parse_hex_color("#FF6B6B"), Some([255, 107, 107]));
+        assert_eq!(parse_hex_color("00ff7f"), Some([0, 255, 127]));
+        assert_eq!(parse_hex_color("#FFF"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+        assert_eq!(parse_hex_color("#FF6B6B00"), None);
+
+        assert_eq!(get_guild_color(None, colors::INFO_BLUE), colors::INFO_BLUE);
+        assert_eq!(get_guild_color(Some([255, 107, 107]), colors::INFO_BLUE), Colour::from_rgb(255, 107, 107));
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_color_{}", std::process::id()));
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert_eq!(storage.get_guild_settings(7), GuildSettings::default());
+
+        storage.set_embed_color(7, Some([255, 107, 107])).await.unwrap();
+        let reloaded = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert_eq!(reloaded.get_guild_settings(7).embed_color, Some([255, 107, 107]));
+
+        storage.set_embed_color(7, None).await.unwrap();
+        assert_eq!(storage.get_guild_settings(7).embed_color, None);
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_volume_ceiling_validated_on_load() {
+        use open_music::storage::{JsonStorage, ServerConfig, MAX_VOLUME_CEILING};
+
+        let mut config = ServerConfig { default_volume: 1.5, max_volume: 5.0, ..Default::default() };
+        assert!(config.normalize_volume_limits());
+        assert_eq!(config.max_volume, MAX_VOLUME_CEILING);
+        config.max_volume = 0.8;
+        assert!(config.normalize_volume_limits());
+        assert_eq!(config.default_volume, 0.8);
+        assert!(!config.normalize_volume_limits());
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_volume_{}", std::process::id()));
+        let servers_dir = data_dir.join("servers");
+        std::fs::create_dir_all(&servers_dir).unwrap();
+
+        // Config anterior al techo: se asume 200% sin excepción para DJs
+        std::fs::write(
+            servers_dir.join("guild_42.json"),
+            r#"{"guild_id":42,"default_volume":0.5,"max_queue_size":100,"auto_leave_timeout":300,
+                "dj_role_id":null,"announcement_channel_id":null,"auto_leave_empty":true}"#,
+        )
+        .unwrap();
+        // Volumen por defecto por encima del techo: se corrige al cargar
+        std::fs::write(
+            servers_dir.join("guild_43.json"),
+            r#"{"guild_id":43,"default_volume":1.2,"max_queue_size":100,"auto_leave_timeout":300,
+                "dj_role_id":null,"announcement_channel_id":null,"auto_leave_empty":true,"max_volume":0.9}"#,
+        )
+        .unwrap();
+
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert_eq!(storage.get_volume_limit(42), (MAX_VOLUME_CEILING, false));
+        assert_eq!(storage.get_server_config(43).await.unwrap().default_volume, 0.9);
+
+        // El techo se ajusta al rango 10%–200%
+        storage.set_volume_limit(42, 0.01, true).await.unwrap();
+        assert_eq!(storage.get_volume_limit(42), (0.1, true));
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[test]
+    fn test_skip_confirm_thresholds_default_for_legacy_configs() {
+        use open_music::storage::ServerConfig;
+
+        let config: ServerConfig = serde_json::from_str(
+            r#"{"guild_id":42,"default_volume":0.5,"max_queue_size":100,"auto_leave_timeout":300,
+                "dj_role_id":null,"announcement_channel_id":null,"auto_leave_empty":true}"#,
+        )
+        .unwrap();
+        assert_eq!(config.skip_confirm_threshold_secs, 30);
+        assert_eq!(config.skip_confirm_long_track_secs, 600);
+        assert_eq!(ServerConfig::default().skip_confirm_threshold_secs, 30);
+        // La detección de silencio viene activada en configs antiguas
+        assert!(config.silence_detection);
+        assert_eq!(config.silence_skip_secs, 10);
+    }
+
+    #[tokio::test]
+    async fn test_user_notify_preference_persists() {
+        use open_music::storage::JsonStorage;
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_prefs_{}", std::process::id()));
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert!(!storage.get_user_preferences(7).await.unwrap().notify_on_play);
+
+        storage.set_notify_on_play(7, true).await.unwrap();
+        assert!(data_dir.join("prefs").join("user_7.json").exists());
+
+        // Otra instancia lee el archivo, no la caché
+        let mut reloaded = JsonStorage::new(data_dir.clone()).await.unwrap();
+        assert!(reloaded.get_user_preferences(7).await.unwrap().notify_on_play);
+        assert!(!reloaded.get_user_preferences(8).await.unwrap().notify_on_play);
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_json_queue_storage_roundtrip() {
+        use open_music::audio::queue::LoopMode;
+        use open_music::storage::{JsonStorage, QueueStorage, SavedGuildQueue};
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_queues_{}", std::process::id()));
+        let storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        let queue = SavedGuildQueue {
+            guild_id: 42,
+            voice_channel_id: Some(7),
+            current: None,
+            tracks: vec![],
+            resume_at: Some(Duration::from_secs(30)),
+            loop_mode: LoopMode::Queue,
+            shuffle: true,
+            saved_at: chrono::Utc::now(),
+        };
+
+        assert!(storage.load_queue(42).await.unwrap().is_none());
+        storage.save_queue(42, &queue).await.unwrap();
+        assert!(storage.save_queue(43, &queue).await.is_err());
+
+        let loaded = storage.load_queue(42).await.unwrap().unwrap();
+        assert_eq!(loaded.voice_channel_id, Some(7));
+        assert_eq!(loaded.resume_at, Some(Duration::from_secs(30)));
+        assert!(loaded.shuffle);
+        assert_eq!(loaded.loop_mode, LoopMode::Queue);
+
+        let counted = SavedGuildQueue { loop_mode: LoopMode::TrackCount(3), ..loaded };
+        storage.save_queue(42, &counted).await.unwrap();
+        let loaded = storage.load_queue(42).await.unwrap().unwrap();
+        assert_eq!(loaded.loop_mode, LoopMode::TrackCount(3));
+
+        // Un modo que ya no existe no impide restaurar la cola
+        let mut json = serde_json::to_value(&loaded).unwrap();
+        json["loop_mode"] = serde_json::json!("Album");
+        let unknown: SavedGuildQueue = serde_json::from_value(json).unwrap();
+        assert_eq!(unknown.loop_mode, LoopMode::Off);
+
+        storage.delete_queue(42).await.unwrap();
+        assert!(storage.load_queue(42).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[test]
+    fn test_redis_queue_keys_and_backoff() {
+        use open_music::storage::redis_queue::{queue_key, reconnect_delay};
+
+        assert_eq!(queue_key(42), "open_music:queue:42");
+        assert_eq!(reconnect_delay(1), Duration::from_millis(200));
+        assert_eq!(reconnect_delay(2), Duration::from_millis(400));
+        assert_eq!(reconnect_delay(4), Duration::from_millis(1600));
+        assert_eq!(reconnect_delay(10), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_imported_config_is_sanitized() {
+        use open_music::storage::ServerConfig;
+
+        let mut config = ServerConfig {
+            guild_id: 1,
+            default_volume: 5.0,
+            max_queue_size: 0,
+            command_prefix: Some("play now".to_string()),
+            active_eq_preset: Some("loudest".to_string()),
+            custom_eq_bands: Some([30.0, -30.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0]),
+            ..ServerConfig::default()
+        };
+
+        let notes = config.sanitize_import(42);
+        assert_eq!(config.guild_id, 42);
+        assert_eq!(config.default_volume, config.max_volume);
+        assert_eq!(config.max_queue_size, 1);
+        assert_eq!(config.command_prefix, None);
+        assert_eq!(config.active_eq_preset.as_deref(), Some("flat"));
+        assert_eq!(config.custom_eq_bands.unwrap()[..2], [12.0, -12.0]);
+        assert_eq!(config.custom_eq_bands.unwrap()[9], 3.0);
+        assert_eq!(notes.len(), 6);
+
+        // Una configuración válida no cambia
+        let mut valid = config.clone();
+        assert!(valid.sanitize_import(42).is_empty());
+    }
+
+    #[test]
+    fn test_default_search_source_requires_text_search() {
+        use open_music::sources::SourceType;
+        use open_music::storage::ServerConfig;
+
+        // Configuraciones guardadas antes del ajuste buscan en YouTube
+        let legacy: ServerConfig = serde_json::from_value(serde_json::json!({
+            "guild_id": 1, "default_volume": 0.5, "max_queue_size": 100, "auto_leave_timeout": 300,
+            "dj_role_id": null, "announcement_channel_id": null, "auto_leave_empty": true
+        }))
+        .unwrap();
+        assert_eq!(legacy.default_search_source, SourceType::YouTube);
+
+        let mut config = ServerConfig { guild_id: 1, default_search_source: SourceType::DirectUrl, ..Default::default() };
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["default_search_source"], "direct");
+
+        let notes = config.sanitize_import(1);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(config.default_search_source, SourceType::YouTube);
+        assert!(SourceType::ALL.iter().any(|source| source.supports_text_search()));
+    }
+
+    #[test]
+    fn test_guild_play_stats_summary() {
+        use chrono::{NaiveDate, TimeZone, Utc, Weekday};
+        use open_music::storage::play_stats::GuildPlayStats;
+        use open_music::ui::embeds::stats_bar;
+        use std::time::Duration;
+
+        let mut stats = GuildPlayStats::default();
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 20, 0, 0).unwrap();
+        let three_min = Some(Duration::from_secs(180));
+        // 2026-03-06 es viernes
+        stats.record(1, "https://youtu.be/a", "A", three_min, day(6));
+        stats.record(1, "https://youtu.be/a", "A", three_min, day(6));
+        stats.record(2, "https://youtu.be/b", "B", None, day(13));
+        stats.record(0, "https://youtu.be/a", "A", three_min, day(16));
+
+        let summary = stats.summary(NaiveDate::from_ymd_opt(2026, 3, 16).unwrap());
+        assert_eq!(summary.total_plays, 4);
+        assert_eq!(summary.listening_time, Duration::from_secs(540));
+        assert_eq!(summary.unique_tracks, 2);
+        // El autoplay (ID 0) no entra en el ranking
+        assert_eq!(summary.top_requesters, [(1, 2), (2, 1)]);
+        assert_eq!(summary.most_played, Some(("A".to_string(), "https://youtu.be/a".to_string(), 3)));
+        // Días 10–16 frente a 3–9
+        assert_eq!((summary.this_week, summary.last_week), (2, 2));
+        assert_eq!(summary.peak_day(), Some(Weekday::Fri));
+
+        assert_eq!(stats_bar(4, 8, 4), "██  ");
+        assert_eq!(stats_bar(1, 8, 2), "▎ ");
+        assert_eq!(stats_bar(0, 0, 3), "   ");
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_scopes() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+        use open_music::bot::leaderboard::{page_button_id, parse_page_button_id};
+        use open_music::storage::leaderboard::{GuildLeaderboard, LeaderboardScope};
+        use open_music::storage::play_stats::PlayStatsStore;
+        use std::time::Duration;
+
+        let mut leaderboard = GuildLeaderboard::default();
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 20, 0, 0).unwrap();
+        let three_min = Duration::from_secs(180);
+        for d in [1, 2, 3] {
+            leaderboard.record("https://youtu.be/a", "A", day(d));
+            assert!(leaderboard.record_listened("https://youtu.be/a", three_min));
+        }
+        leaderboard.record("https://youtu.be/b", "B", day(20));
+        leaderboard.record("https://youtu.be/b", "B", day(28));
+        leaderboard.record("https://youtu.be/a", "A (remaster)", day(29));
+        // Saltada al minuto: solo cuenta lo que sonó
+        assert!(leaderboard.record_listened("https://youtu.be/a", Duration::from_secs(60)));
+        assert!(!leaderboard.record_listened("https://youtu.be/never-played", three_min));
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 30).unwrap();
+        let all_time = leaderboard.ranking(LeaderboardScope::AllTime, today);
+        assert_eq!(all_time.len(), 2);
+        assert_eq!((all_time[0].title.as_str(), all_time[0].play_count), ("A (remaster)", 4));
+        assert_eq!(all_time[0].total_duration_played, Duration::from_secs(600));
+        assert_eq!(all_time[0].last_played, day(29));
+
+        // Últimos 30 días: 1–30 de marzo (todo); últimos 7: 24–30
+        assert_eq!(leaderboard.ranking(LeaderboardScope::Month, today).len(), 2);
+        let week = leaderboard.ranking(LeaderboardScope::Week, today);
+        assert_eq!(week.iter().map(|track| track.play_count).collect::<Vec<_>>(), [1, 1]);
+        // Empate: primero la que sonó más recientemente
+        assert_eq!(week[0].url, "https://youtu.be/a");
+        assert_eq!(week[0].total_duration_played, Duration::from_secs(150));
+
+        // Los días fuera de los últimos 30 se descartan; el total se conserva
+        leaderboard.prune(NaiveDate::from_ymd_opt(2026, 4, 20).unwrap());
+        let a = &leaderboard.tracks["https://youtu.be/a"];
+        assert_eq!(a.daily.keys().copied().collect::<Vec<_>>(), [day(29).date_naive()]);
+        assert_eq!(a.play_count, 4);
+
+        let id = page_button_id(LeaderboardScope::Month, 3);
+        assert_eq!(parse_page_button_id(&id), Some((LeaderboardScope::Month, 3)));
+        assert_eq!(parse_page_button_id("leaderboard_page:year:1"), None);
+
+        // Se guarda con las estadísticas de reproducción y se conserva entre reinicios
+        let dir = std::env::temp_dir().join(format!("open_music_leaderboard_{}", std::process::id()));
+        let store = PlayStatsStore::new(dir.clone());
+        store.record_play(42, 7, "https://youtu.be/a", "A", None).await.unwrap();
+        store.record_listened(42, "https://youtu.be/a", three_min).await.unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        let reloaded = PlayStatsStore::new(dir.clone());
+        assert_eq!(reloaded.load().await.unwrap(), 1);
+        assert_eq!(reloaded.ranking(42, LeaderboardScope::Week)[0].play_count, 1);
+        assert_eq!(reloaded.ranking(42, LeaderboardScope::Week)[0].total_duration_played, three_min);
+        assert!(reloaded.ranking(7, LeaderboardScope::AllTime).is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_user_data_export_and_delete() {
+        use open_music::storage::{DeletionReport, JsonStorage, PlaylistHistoryEntry};
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_my_data_{}", std::process::id()));
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        storage.create_playlist("Mía".to_string(), 7, 42).await.unwrap();
+        storage.create_playlist("De otro servidor".to_string(), 7, 43).await.unwrap();
+        let shared = storage.create_playlist("Ajena".to_string(), 8, 42).await.unwrap();
+        assert!(storage.set_playlist_editor(&shared, 8, 7, true).await.unwrap());
+        assert!(storage.set_playlist_editor(&shared, 8, 9, true).await.unwrap());
+        storage.set_notify_on_play(7, true).await.unwrap();
+        let entry = PlaylistHistoryEntry {
+            playlist_id: None,
+            playlist_url: Some("https://youtube.com/playlist?list=abc".to_string()),
+            playlist_name: "Mix".to_string(),
+            track_count: 10,
+            loaded_at: chrono::Utc::now(),
+            source: "YouTube".to_string(),
+        };
+        storage.add_to_playlist_history(7, 42, entry).await.unwrap();
+
+        let export = storage.export_user_data(42, 7).await.unwrap();
+        assert_eq!(export.playlists.len(), 1);
+        assert!(export.preferences.notify_on_play);
+        assert_eq!(export.playlist_history.unwrap().recent_playlists.len(), 1);
+        assert_eq!(export.collaborations.len(), 1);
+        assert!(export.collaborations[0].is_editor);
+        assert_eq!(export.collaborations[0].edits.len(), 1);
+
+        let report = storage.delete_user_data(42, 7).await.unwrap();
+        assert_eq!(
+            report,
+            DeletionReport { playlists: 1, history: 1, preferences: 1, collaborations: 1, ..Default::default() }
+        );
+        // Deja de ser editor y sale del historial; lo de los demás queda
+        let playlist = storage.get_playlist(&shared).await.unwrap().unwrap();
+        assert_eq!(playlist.editors, [9]);
+        assert_eq!(playlist.edit_log.len(), 1);
+
+        // Solo se borra lo del usuario en esta guild
+        let export = storage.export_user_data(42, 7).await.unwrap();
+        assert!(export.playlists.is_empty() && export.playlist_history.is_none());
+        assert!(!export.preferences.notify_on_play);
+        assert_eq!(storage.get_user_playlists(7, 43).await.unwrap().len(), 1);
+        assert_eq!(storage.get_user_playlists(8, 42).await.unwrap().len(), 1);
+        assert_eq!(storage.delete_user_data(42, 7).await.unwrap().total(), 0);
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_user_data_in_ratings_and_play_stats() {
+        use open_music::storage::{play_stats::PlayStatsStore, ratings::RatingStore};
+
+        let dir = std::env::temp_dir().join(format!("open_music_my_data_stores_{}", std::process::id()));
+        let ratings = RatingStore::new(dir.join("ratings"));
+        ratings.add_rating(42, "https://youtu.be/a", "A", 7, 5).await.unwrap();
+        ratings.add_rating(42, "https://youtu.be/a", "A", 8, 3).await.unwrap();
+        ratings.add_rating(43, "https://youtu.be/a", "A", 7, 1).await.unwrap();
+
+        let mine = ratings.user_ratings(42, 7);
+        assert_eq!(mine.len(), 1);
+        assert_eq!((mine[0].title.as_str(), mine[0].stars), ("A", 5));
+        assert_eq!(ratings.remove_user(42, 7).await.unwrap(), 1);
+        assert_eq!(ratings.get_average_rating(42, "https://youtu.be/a"), Some(3.0));
+        assert_eq!(ratings.user_ratings(43, 7).len(), 1);
+        let reloaded = RatingStore::new(dir.join("ratings"));
+        reloaded.load().await.unwrap();
+        assert!(reloaded.user_ratings(42, 7).is_empty());
+
+        let stats = PlayStatsStore::new(dir.join("stats"));
+        stats.record_play(42, 7, "https://youtu.be/a", "A", None).await.unwrap();
+        stats.record_play(42, 8, "https://youtu.be/a", "A", None).await.unwrap();
+        assert_eq!(stats.user_plays(42, 7).unwrap().plays, 1);
+        assert!(stats.remove_user(42, 7).await.unwrap());
+        assert!(!stats.remove_user(42, 7).await.unwrap());
+        assert!(stats.user_plays(42, 7).is_none());
+        // Los totales de la guild se mantienen
+        assert_eq!(stats.summary(42).unwrap().total_plays, 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_play_stats_load_skips_corrupt_guild_files() {
+        use open_music::storage::play_stats::PlayStatsStore;
+
+        let dir = std::env::temp_dir().join(format!("open_music_stats_load_{}", std::process::id()));
+        let stats = PlayStatsStore::new(dir.clone());
+        stats.record_play(42, 7, "https://youtu.be/a", "A", None).await.unwrap();
+        std::fs::write(dir.join("guild_43.json"), "{ roto").unwrap();
+
+        // El archivo se escribe por un temporal que no queda en el directorio
+        assert!(!dir.join("guild_42.json.tmp").exists());
+
+        let reloaded = PlayStatsStore::new(dir.clone());
+        assert_eq!(reloaded.load().await.unwrap(), 1);
+        assert_eq!(reloaded.summary(42).unwrap().total_plays, 1);
+        assert!(reloaded.summary(43).is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_collaborative_playlist_editing() {
+        use open_music::storage::{EditAction, JsonStorage, PlaylistTrack, MAX_PLAYLIST_EDIT_LOG};
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_collab_{}", std::process::id()));
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        let id = storage.create_playlist("Compartida".to_string(), 7, 42).await.unwrap();
+        let track = |title: &str, added_by: u64| PlaylistTrack {
+            title: title.to_string(),
+            artist: None,
+            url: format!("https://youtube.com/watch?v={}", title),
+            duration: None,
+            thumbnail: None,
+            added_by,
+            added_at: chrono::Utc::now(),
+            source_type: "youtube".to_string(),
+        };
+
+        // Sin ser editor no puede agregar
+        assert!(!storage.add_track_to_playlist(&id, track("a", 8), 8).await.unwrap());
+        // Un editor no puede sumar editores; el dueño sí, una sola vez
+        assert!(!storage.set_playlist_editor(&id, 8, 9, true).await.unwrap());
+        assert!(storage.set_playlist_editor(&id, 7, 8, true).await.unwrap());
+        assert!(!storage.set_playlist_editor(&id, 7, 8, true).await.unwrap());
+        assert!(!storage.set_playlist_editor(&id, 8, 9, true).await.unwrap());
+
+        assert!(storage.add_track_to_playlist(&id, track("a", 8), 8).await.unwrap());
+        assert_eq!(storage.remove_track_from_playlist(&id, 0, 8).await.unwrap().unwrap().title, "a");
+        // Editar no da permiso para borrarla
+        assert!(!storage.delete_playlist(&id, 8).await.unwrap());
+
+        let editable = storage.get_editable_playlists(8, 42).await.unwrap();
+        assert_eq!(editable.len(), 1);
+        let playlist = &editable[0];
+        assert!(playlist.is_collaborative() && playlist.can_edit(8) && !playlist.can_edit(9));
+        let actions: Vec<_> = playlist.edit_log.iter().map(|event| (event.user_id, event.action.clone())).collect();
+        assert_eq!(
+            actions,
+            vec![
+                (7, EditAction::AddEditor { user_id: 8 }),
+                (8, EditAction::AddTracks { count: 1 }),
+                (8, EditAction::RemoveTrack { title: "a".to_string() }),
+            ]
+        );
+
+        // El historial guarda solo los últimos cambios
+        for i in 0..MAX_PLAYLIST_EDIT_LOG {
+            storage.add_track_to_playlist(&id, track(&i.to_string(), 7), 7).await.unwrap();
+        }
+        let playlist = storage.get_playlist(&id).await.unwrap().unwrap();
+        assert_eq!(playlist.edit_log.len(), MAX_PLAYLIST_EDIT_LOG);
+        assert_eq!(playlist.edit_log.front().unwrap().action, EditAction::AddTracks { count: 1 });
+
+        assert!(storage.set_playlist_editor(&id, 7, 8, false).await.unwrap());
+        assert!(storage.get_editable_playlists(8, 42).await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_m3u8_export_round_trip() {
+        use open_music::storage::m3u::parse_m3u8;
+        use open_music::storage::{JsonStorage, PlaylistTrack};
+
+        let data_dir = std::env::temp_dir().join(format!("open_music_m3u8_{}", std::process::id()));
+        let mut storage = JsonStorage::new(data_dir.clone()).await.unwrap();
+        let id = storage.create_playlist("Viaje".to_string(), 7, 42).await.unwrap();
+        let track = |title: &str, artist: Option<&str>, duration: Option<u64>| PlaylistTrack {
+            title: title.to_string(),
+            artist: artist.map(str::to_string),
+            url: format!("https://youtube.com/watch?v={}", title.len()),
+            duration: duration.map(Duration::from_secs),
+            thumbnail: None,
+            added_by: 7,
+            added_at: chrono::Utc::now(),
+            source_type: "youtube".to_string(),
+        };
+        let tracks = vec![track("Song", Some("Artist"), Some(215)), track("Multi\nline", None, None)];
+        storage.add_tracks_to_playlist(&id, tracks, 7).await.unwrap();
+
+        let exported = storage.export_playlist_to_m3u8(&id).await.unwrap();
+        assert_eq!(
+            exported,
+            "#EXTM3U\n#PLAYLIST:Viaje\n\
+             #EXTINF:215,Artist - Song\nhttps://youtube.com/watch?v=4\n\
+             #EXTINF:-1,Multi line\nhttps://youtube.com/watch?v=10\n"
+        );
+        assert!(storage.export_playlist_to_m3u8("missing").await.is_err());
+
+        let parsed = parse_m3u8(&exported);
+        assert_eq!(parsed.name.as_deref(), Some("Viaje"));
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].title.as_deref(), Some("Artist - Song"));
+        assert_eq!(parsed.entries[0].duration, Some(Duration::from_secs(215)));
+        assert_eq!(parsed.entries[0].query(), Some("https://youtube.com/watch?v=4"));
+        assert_eq!(parsed.entries[1].duration, None);
+
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
+    #[test]
+    fn test_m3u8_parse_local_entries() {
+        use open_music::storage::m3u::parse_m3u8;
+
+        let content = "\u{feff}#EXTM3U\r\n\
+                       #EXTINF:180,Queen - Bohemian Rhapsody\r\n\
+                       C:\\Music\\queen.mp3\r\n\
+                       # comentario\r\n\
+                       /home/user/Artist - Song.flac\r\n\
+                       \r\n\
+                       #EXTINF:-1,\r\n\
+                       http://radio.example/stream\r\n";
+        let parsed = parse_m3u8(content);
+
+        assert_eq!(parsed.name, None);
+        let queries: Vec<_> = parsed.entries.iter().map(|entry| entry.query().unwrap()).collect();
+        assert_eq!(
+            queries,
+            vec!["Queen - Bohemian Rhapsody", "Artist - Song", "http://radio.example/stream"]
+        );
+        assert_eq!(parsed.entries[0].url, None);
+        assert_eq!(parsed.entries[0].duration, Some(Duration::from_secs(180)));
+        assert!(parsed.entries[2].title.is_none());
+        assert!(parse_m3u8("#EXTM3U\n#EXTINF:10,\n").entries.is_empty());
+    }
+
+    #[test]
+    fn test_m3u8_parse_invalid_durations() {
+        use open_music::storage::m3u::parse_m3u8;
+
+        let content = "#EXTM3U\n\
+                       #EXTINF:inf,Infinita\n\
+                       song1.mp3\n\
+                       #EXTINF:NaN,Sin número\n\
+                       song2.mp3\n\
+                       #EXTINF:1e300,Enorme\n\
+                       song3.mp3\n";
+        let parsed = parse_m3u8(content);
+
+        let titles: Vec<_> = parsed.entries.iter().map(|entry| entry.query().unwrap()).collect();
+        assert_eq!(titles, vec!["Infinita", "Sin número", "Enorme"]);
+        assert!(parsed.entries.iter().all(|entry| entry.duration.is_none()));
+    }
 }
@@ -0,0 +1,37 @@
+//! Tests for message URL detection
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::url_detect::{DetectedUrls, DEBOUNCE_WINDOW, DETECTION_TTL};
+    use serenity::model::id::{GuildId, MessageId};
+    use std::time::{Duration, Instant};
+
+    const URL: &str = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+
+    #[test]
+    fn test_debounce_same_url_in_guild() {
+        let detected = DetectedUrls::default();
+        let guild = GuildId::new(1);
+        let start = Instant::now();
+
+        assert!(detected.register(guild, MessageId::new(10), URL, start));
+        assert!(!detected.register(guild, MessageId::new(11), URL, start + Duration::from_secs(1)));
+        // Otra guild no comparte la ventana
+        assert!(detected.register(GuildId::new(2), MessageId::new(12), URL, start + Duration::from_secs(1)));
+        assert!(detected.register(guild, MessageId::new(13), URL, start + DEBOUNCE_WINDOW));
+    }
+
+    #[test]
+    fn test_take_once_and_expire() {
+        let detected = DetectedUrls::default();
+        let guild = GuildId::new(1);
+        let start = Instant::now();
+
+        detected.register(guild, MessageId::new(10), URL, start);
+        assert_eq!(detected.take(MessageId::new(10), start).as_deref(), Some(URL));
+        assert_eq!(detected.take(MessageId::new(10), start), None);
+
+        detected.register(guild, MessageId::new(20), "https://youtu.be/abc", start);
+        assert_eq!(detected.take(MessageId::new(20), start + DETECTION_TTL), None);
+    }
+}
@@ -0,0 +1,55 @@
+//! Tests for track voting
+
+#[cfg(test)]
+mod tests {
+    use open_music::bot::voting::{VoteOutcome, VotingSystem};
+    use open_music::storage::{GuildVotes, TrackVotes};
+    use serenity::model::id::{GuildId, UserId};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_vote_once_per_user() {
+        let guild = GuildId::new(4242);
+        let url = "https://youtube.com/watch?v=vote";
+
+        assert_eq!(
+            VotingSystem::vote(guild, url, UserId::new(1), true),
+            VoteOutcome::Recorded(1, 0)
+        );
+        assert_eq!(
+            VotingSystem::vote(guild, url, UserId::new(1), false),
+            VoteOutcome::AlreadyVoted
+        );
+        assert_eq!(
+            VotingSystem::vote(guild, url, UserId::new(2), false),
+            VoteOutcome::Recorded(1, 1)
+        );
+        assert_eq!(VotingSystem::votes(guild, url), (1, 1));
+
+        let persisted = VotingSystem::to_persisted(guild);
+        assert_eq!(persisted.tracks[url].voters.len(), 2);
+    }
+
+    #[test]
+    fn test_rank_orders_by_score() {
+        let track = |title: &str, upvotes, downvotes| TrackVotes {
+            title: title.to_string(),
+            upvotes,
+            downvotes,
+            voters: Vec::new(),
+        };
+
+        let mut tracks = HashMap::new();
+        tracks.insert("a".to_string(), track("A", 3, 2));
+        tracks.insert("b".to_string(), track("B", 5, 0));
+        tracks.insert("c".to_string(), track("C", 1, 4));
+        let votes = GuildVotes {
+            guild_id: 1,
+            tracks,
+        };
+
+        let ranked = VotingSystem::rank(&votes, 2);
+        let titles: Vec<_> = ranked.iter().map(|(_, v)| v.title.as_str()).collect();
+        assert_eq!(titles, vec!["B", "A"]);
+    }
+}
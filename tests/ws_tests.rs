@@ -0,0 +1,115 @@
+//! Tests for the WebSocket event stream
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use open_music::audio::player::AudioPlayer;
+    use open_music::webhook::{TrackWebhookPayload, WebhookRateLimiter};
+    use open_music::ws::{serve, WsCommand, WsEvent};
+    use serenity::model::id::{GuildId, UserId};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[test]
+    fn test_event_json() {
+        let event = WsEvent::TrackStarted {
+            guild_id: GuildId::new(42),
+            title: "Canción".to_string(),
+            url: "https://youtu.be/abc".to_string(),
+            requested_by: UserId::new(7),
+        };
+        let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "track_started");
+        assert_eq!(json["guild_id"], "42");
+        assert_eq!(json["requested_by"], "7");
+
+        let json = serde_json::to_value(WsEvent::QueueUpdated { guild_id: GuildId::new(42), queue_length: 3 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "event": "queue_updated", "guild_id": "42", "queue_length": 3 }));
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let timestamp = chrono::DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z").unwrap().to_utc();
+        let event = WsEvent::TrackSkipped {
+            guild_id: GuildId::new(42),
+            title: "Canción".to_string(),
+            url: "https://youtu.be/abc".to_string(),
+            requested_by: UserId::new(7),
+        };
+        let payload = TrackWebhookPayload::from_event(&event, timestamp).unwrap();
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "event": "track_skipped",
+                "guild_id": "42",
+                "title": "Canción",
+                "url": "https://youtu.be/abc",
+                "requested_by": "7",
+                "timestamp": "2025-06-01T12:00:00Z",
+            })
+        );
+
+        // Solo los eventos de pista van al webhook
+        let queue = WsEvent::QueueUpdated { guild_id: GuildId::new(42), queue_length: 3 };
+        assert!(TrackWebhookPayload::from_event(&queue, timestamp).is_none());
+    }
+
+    #[test]
+    fn test_webhook_rate_limit() {
+        let mut limiter = WebhookRateLimiter::new(2);
+        let start = std::time::Instant::now();
+        assert!(limiter.try_acquire(start));
+        assert!(limiter.try_acquire(start + Duration::from_secs(1)));
+        assert!(!limiter.try_acquire(start + Duration::from_secs(30)));
+
+        // Pasado un minuto del primer envío vuelve a haber lugar
+        assert!(limiter.try_acquire(start + Duration::from_secs(60)));
+        assert!(!limiter.try_acquire(start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_command_json() {
+        let command: WsCommand = serde_json::from_str(r#"{"action":"pause","guild_id":"42"}"#).unwrap();
+        assert_eq!(command, WsCommand::Pause { guild_id: GuildId::new(42) });
+        assert!(serde_json::from_str::<WsCommand>(r#"{"action":"shutdown"}"#).is_err());
+    }
+
+    async fn connect(
+        addr: std::net::SocketAddr,
+        path: &str,
+    ) -> tokio_tungstenite::tungstenite::Result<
+        tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+    > {
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let url = format!("ws://{}{}", addr, path);
+        tokio_tungstenite::client_async(url, stream).await.map(|(socket, _)| socket)
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        let player = Arc::new(AudioPlayer::new(0.5, false));
+        let server = tokio::spawn(serve(listener, "secreto".to_string(), player.clone(), shutdown_rx));
+
+        // Sin token (o con uno incorrecto) se rechaza el handshake
+        assert!(connect(addr, "/").await.is_err());
+        assert!(connect(addr, "/?token=otro").await.is_err());
+
+        let mut socket = connect(addr, "/?token=secreto").await.unwrap();
+        player.emit(WsEvent::BotLeftVoice { guild_id: GuildId::new(42) });
+        let message = tokio::time::timeout(Duration::from_secs(5), socket.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(message.into_text().unwrap().as_str(), r#"{"event":"bot_left_voice","guild_id":"42"}"#);
+
+        // Un comando inválido responde con el error; `pause` sin pista no responde nada
+        socket.send(Message::text(r#"{"action":"pause","guild_id":"42"}"#)).await.unwrap();
+        socket.send(Message::text("no es json")).await.unwrap();
+        let reply = tokio::time::timeout(Duration::from_secs(5), socket.next()).await.unwrap().unwrap().unwrap();
+        assert!(reply.into_text().unwrap().contains("invalid command"));
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+    }
+}